@@ -0,0 +1,39 @@
+// Builds the elevated helper (`flowwatcher-elevated`) and copies it to
+// src-tauri/binaries/ under the name Tauri expects for an `externalBin`:
+// the binary name followed by the target triple. Tauri installs it next
+// to the app executable, where `execute_action_elevated` looks for it.
+
+import { execFileSync } from 'node:child_process';
+import { copyFileSync, mkdirSync } from 'node:fs';
+import { dirname, join } from 'node:path';
+import { fileURLToPath } from 'node:url';
+
+const root = join(dirname(fileURLToPath(import.meta.url)), '..');
+const coreDir = join(root, '..', '..', 'core');
+
+const triple =
+  process.env.TAURI_ENV_TARGET_TRIPLE ||
+  execFileSync('rustc', ['-vV'], { encoding: 'utf8' }).match(/^host: (\S+)$/m)[1];
+const exe = triple.includes('windows') ? '.exe' : '';
+
+execFileSync(
+  'cargo',
+  [
+    'build',
+    '--release',
+    '-p',
+    'flowwatcher-cli',
+    '--bin',
+    'flowwatcher-elevated',
+    '--target',
+    triple,
+  ],
+  { cwd: coreDir, stdio: 'inherit' }
+);
+
+const binaries = join(root, 'src-tauri', 'binaries');
+mkdirSync(binaries, { recursive: true });
+copyFileSync(
+  join(coreDir, 'target', triple, 'release', `flowwatcher-elevated${exe}`),
+  join(binaries, `flowwatcher-elevated-${triple}${exe}`)
+);
@@ -0,0 +1,500 @@
+//! Local HTTP API for integrations.
+//!
+//! Stream Deck buttons, scripts and home-automation hubs can read the
+//! monitoring status and act on a pending action without the window. When
+//! `http_api.enabled` is set in settings.json, [`apply`] serves a small
+//! HTTP/1.1 API on `127.0.0.1:<port>`:
+//!
+//! | Route                 | Does                                          |
+//! |-----------------------|-----------------------------------------------|
+//! | `GET /status`         | monitoring status and the latest speed        |
+//! | `GET /logs?limit=n`   | the newest `n` activity log entries           |
+//! | `POST /cancel`        | cancel the pending action                     |
+//! | `POST /acknowledge`   | acknowledge the pre-warning ("I'm aware")     |
+//! | `POST /execute-now`   | skip the countdown and run the action         |
+//! | `POST /pause`         | pause monitoring                              |
+//! | `POST /resume`        | resume monitoring                             |
+//!
+//! Every request must carry `Authorization: Bearer <token>` with the
+//! token from settings.json. The handlers use the same helpers, state and
+//! operation guard as the Tauri commands and the tray, so a remote cancel
+//! is logged and hooked like any other. The server only listens on the
+//! loopback interface; reach it from another machine through a forwarded
+//! port or an SSH tunnel.
+
+use crate::commands::{self, SpeedData};
+use crate::settings::SettingsError;
+use crate::state::{AppState, MonitoringStatus};
+use flowwatcher_engine::{CancelReason, LogFilter};
+use serde::{Deserialize, Serialize};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Port the API listens on unless configured otherwise.
+pub const DEFAULT_PORT: u16 = 47474;
+
+/// Entries `GET /logs` returns without a `limit`.
+pub const DEFAULT_LOG_LIMIT: usize = 50;
+
+/// Largest `limit` `GET /logs` accepts.
+pub const MAX_LOG_LIMIT: usize = 1000;
+
+/// Largest request head (request line plus headers) read.
+const MAX_HEAD_BYTES: usize = 8 * 1024;
+
+/// Time a client has to send its request head.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ---------------------------------------------------------------------------
+// Settings
+// ---------------------------------------------------------------------------
+
+/// The `http_api` section of settings.json.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpApiSettings {
+    /// Serve the API.
+    pub enabled: bool,
+    /// Loopback port to listen on.
+    pub port: u16,
+    /// Bearer token every request must carry. The API refuses to start
+    /// while it is empty.
+    pub token: String,
+}
+
+impl Default for HttpApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            token: String::new(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Requests and responses
+// ---------------------------------------------------------------------------
+
+/// The parts of a request head the API looks at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    /// Request method, e.g. `GET`.
+    pub method: String,
+    /// Path without the query string.
+    pub path: String,
+    /// Query string after `?`, if any.
+    pub query: Option<String>,
+    /// Token from an `Authorization: Bearer` header.
+    pub token: Option<String>,
+}
+
+impl Request {
+    /// Parse an HTTP/1.x request head. Returns `None` if the request line
+    /// is malformed.
+    pub fn parse(head: &str) -> Option<Self> {
+        let mut lines = head.split("\r\n");
+        let mut parts = lines.next()?.split(' ');
+        let method = parts.next().filter(|m| !m.is_empty())?;
+        let target = parts.next().filter(|t| t.starts_with('/'))?;
+        if !parts.next()?.starts_with("HTTP/1.") || parts.next().is_some() {
+            return None;
+        }
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path, Some(query.to_string())),
+            None => (target, None),
+        };
+        let token = lines
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"))
+            .and_then(|(_, value)| {
+                let (scheme, token) = value.trim().split_once(' ')?;
+                scheme
+                    .eq_ignore_ascii_case("bearer")
+                    .then(|| token.trim().to_string())
+            });
+        Some(Self {
+            method: method.to_string(),
+            path: path.to_string(),
+            query,
+            token,
+        })
+    }
+
+    /// Whether the request carries `token`. An empty `token` never
+    /// matches.
+    pub fn is_authorized(&self, token: &str) -> bool {
+        let Some(given) = self.token.as_deref() else {
+            return false;
+        };
+        // Compare every byte, so the time taken does not reveal how much
+        // of the token was right.
+        !token.is_empty()
+            && given.len() == token.len()
+            && given
+                .bytes()
+                .zip(token.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    /// The `limit` query parameter, clamped to [`MAX_LOG_LIMIT`], or
+    /// [`DEFAULT_LOG_LIMIT`] without one.
+    pub fn log_limit(&self) -> Result<usize, String> {
+        let value = self
+            .query
+            .iter()
+            .flat_map(|query| query.split('&'))
+            .find_map(|pair| pair.strip_prefix("limit="));
+        match value {
+            None => Ok(DEFAULT_LOG_LIMIT),
+            Some(value) => value
+                .parse::<usize>()
+                .map(|limit| limit.min(MAX_LOG_LIMIT))
+                .map_err(|_| format!("limit must be a non-negative integer, got \"{value}\"")),
+        }
+    }
+}
+
+/// An API endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// `GET /status`.
+    Status,
+    /// `GET /logs`.
+    Logs,
+    /// `POST /cancel`.
+    Cancel,
+    /// `POST /acknowledge`.
+    Acknowledge,
+    /// `POST /execute-now`.
+    ExecuteNow,
+    /// `POST /pause`.
+    Pause,
+    /// `POST /resume`.
+    Resume,
+}
+
+impl Route {
+    /// The route for `method` and `path`, or the 404 or 405 response.
+    pub fn find(method: &str, path: &str) -> Result<Self, Response> {
+        let (route, expected) = match path {
+            "/status" => (Self::Status, "GET"),
+            "/logs" => (Self::Logs, "GET"),
+            "/cancel" => (Self::Cancel, "POST"),
+            "/acknowledge" => (Self::Acknowledge, "POST"),
+            "/execute-now" => (Self::ExecuteNow, "POST"),
+            "/pause" => (Self::Pause, "POST"),
+            "/resume" => (Self::Resume, "POST"),
+            _ => return Err(Response::error(404, &format!("No route {path}"))),
+        };
+        if method != expected {
+            return Err(Response::error(
+                405,
+                &format!("{path} only accepts {expected}"),
+            ));
+        }
+        Ok(route)
+    }
+}
+
+/// The body of `GET /status`, and of every successful `POST`.
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusBody {
+    /// Current monitoring status.
+    pub status: MonitoringStatus,
+    /// Latest reading of the monitoring loop, while it runs.
+    pub speed: Option<SpeedData>,
+}
+
+/// A JSON response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// HTTP status code.
+    pub status: u16,
+    /// Response body.
+    pub body: serde_json::Value,
+}
+
+impl Response {
+    /// A response with `body` serialized as JSON.
+    pub fn json(status: u16, body: &impl Serialize) -> Self {
+        match serde_json::to_value(body) {
+            Ok(body) => Self { status, body },
+            Err(e) => Self::error(500, &e.to_string()),
+        }
+    }
+
+    /// An `{"error": message}` response.
+    pub fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": message }),
+        }
+    }
+
+    /// The response as sent on the wire. The connection is closed after
+    /// each response.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let body = self.body.to_string();
+        let reason = match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            409 => "Conflict",
+            431 => "Request Header Fields Too Large",
+            _ => "Internal Server Error",
+        };
+        let mut head = format!(
+            "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.status,
+            body.len()
+        );
+        if self.status == 401 {
+            head.push_str("WWW-Authenticate: Bearer\r\n");
+        }
+        head.push_str("\r\n");
+        let mut bytes = head.into_bytes();
+        bytes.extend_from_slice(body.as_bytes());
+        bytes
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Server
+// ---------------------------------------------------------------------------
+
+/// A running API server, kept in [`AppState::http_api`].
+pub struct ApiServer {
+    /// The settings it was started with.
+    settings: HttpApiSettings,
+    /// The accept loop.
+    task: JoinHandle<()>,
+}
+
+/// Start, restart or stop the server to match `settings`.
+///
+/// Does nothing if the running server already matches; otherwise the old
+/// server stops before the new one binds, so changing only the token can
+/// reuse the port.
+pub async fn apply(app: &AppHandle, settings: &HttpApiSettings) -> Result<(), SettingsError> {
+    let state = app.state::<AppState>();
+    let mut server = state.http_api.lock().await;
+    let wanted = settings.enabled.then_some(settings);
+    if server.as_ref().map(|s| &s.settings) == wanted {
+        return Ok(());
+    }
+    if let Some(old) = server.take() {
+        old.task.abort();
+        // Wait until the aborted loop has dropped the listener.
+        let _ = old.task.await;
+        log::info!("HTTP API stopped");
+    }
+    if !settings.enabled {
+        return Ok(());
+    }
+    if settings.token.trim().is_empty() {
+        return Err(SettingsError::InvalidValue {
+            key: "http_api.token".into(),
+            message: "set a token before enabling the HTTP API".into(),
+        });
+    }
+
+    let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, settings.port));
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| SettingsError::InvalidValue {
+            key: "http_api.port".into(),
+            message: format!("cannot listen on {addr}: {e}"),
+        })?;
+    log::info!("HTTP API listening on {addr}");
+    let token: Arc<str> = settings.token.as_str().into();
+    let task = tauri::async_runtime::spawn(serve(listener, app.clone(), token));
+    *server = Some(ApiServer {
+        settings: settings.clone(),
+        task,
+    });
+    Ok(())
+}
+
+/// Accept connections until aborted, one task per connection.
+async fn serve(listener: TcpListener, app: AppHandle, token: Arc<str>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let app = app.clone();
+                let token = Arc::clone(&token);
+                tauri::async_runtime::spawn(async move {
+                    handle_connection(stream, &app, &token).await;
+                });
+            }
+            Err(e) => {
+                log::warn!("HTTP API accept failed: {e}");
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+    }
+}
+
+/// Answer the one request on `stream`, then close it.
+async fn handle_connection(mut stream: TcpStream, app: &AppHandle, token: &str) {
+    let response = match tokio::time::timeout(READ_TIMEOUT, read_head(&mut stream)).await {
+        Ok(Ok(head)) => respond(app, token, &head).await,
+        Ok(Err(response)) => response,
+        Err(_) => return,
+    };
+    let _ = stream.write_all(&response.to_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Read up to the blank line ending the request head. Request bodies are
+/// not used and never read.
+async fn read_head(stream: &mut TcpStream) -> Result<String, Response> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    loop {
+        let read = stream
+            .read(&mut buf)
+            .await
+            .map_err(|e| Response::error(400, &e.to_string()))?;
+        if read == 0 {
+            return Err(Response::error(400, "Incomplete request"));
+        }
+        head.extend_from_slice(&buf[..read]);
+        if let Some(end) = head.windows(4).position(|w| w == b"\r\n\r\n") {
+            head.truncate(end);
+            return String::from_utf8(head)
+                .map_err(|_| Response::error(400, "Request head is not UTF-8"));
+        }
+        if head.len() > MAX_HEAD_BYTES {
+            return Err(Response::error(431, "Request head too large"));
+        }
+    }
+}
+
+/// Authenticate and route one request.
+async fn respond(app: &AppHandle, token: &str, head: &str) -> Response {
+    let Some(request) = Request::parse(head) else {
+        return Response::error(400, "Malformed request line");
+    };
+    if !request.is_authorized(token) {
+        return Response::error(401, "Missing or wrong bearer token");
+    }
+    let route = match Route::find(&request.method, &request.path) {
+        Ok(route) => route,
+        Err(response) => return response,
+    };
+
+    let state = app.state::<AppState>();
+    let result = match route {
+        Route::Status => Ok(()),
+        Route::Logs => {
+            return match request.log_limit() {
+                Ok(limit) => {
+                    let filter = LogFilter {
+                        limit: Some(limit),
+                        ..LogFilter::default()
+                    };
+                    let page = state
+                        .activity_logger
+                        .lock()
+                        .await
+                        .get_filtered_advanced(&filter);
+                    Response::json(200, &page)
+                }
+                Err(e) => Response::error(400, &e),
+            };
+        }
+        Route::Cancel => match state.operations.try_begin() {
+            Ok(_permit) => commands::cancel_pending(app, &state, CancelReason::RemoteApi).await,
+            Err(e) => Err(e),
+        },
+        Route::Acknowledge => commands::acknowledge_pending(app, &state).await.map(|_| ()),
+        Route::ExecuteNow => match state.operations.try_begin() {
+            Ok(_permit) => commands::execute_pending(app, &state).await,
+            Err(e) => Err(e),
+        },
+        Route::Pause => commands::pause_session(app, &state).await,
+        Route::Resume => commands::resume_session(app, &state).await,
+    };
+    match result {
+        Ok(()) => {
+            let status = state.status.lock().await.clone();
+            let speed = state.last_speed.lock().await.clone();
+            Response::json(200, &StatusBody { status, speed })
+        }
+        Err(e) => {
+            log::info!("HTTP API {} {} failed: {e}", request.method, request.path);
+            Response::error(409, &e.to_string())
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_parsed_and_authorized() {
+        let request = Request::parse(
+            "GET /logs?limit=5 HTTP/1.1\r\nHost: 127.0.0.1\r\nauthorization: Bearer s3cret",
+        )
+        .unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/logs");
+        assert_eq!(request.log_limit(), Ok(5));
+        assert!(request.is_authorized("s3cret"));
+        assert!(!request.is_authorized("s3cre"));
+        assert!(!request.is_authorized("s3creT"));
+
+        let anonymous = Request::parse("POST /cancel HTTP/1.1\r\nHost: x").unwrap();
+        assert!(!anonymous.is_authorized("s3cret"));
+        assert_eq!(anonymous.log_limit(), Ok(DEFAULT_LOG_LIMIT));
+        // An empty configured token locks everyone out.
+        let empty = Request::parse("GET /status HTTP/1.1\r\nAuthorization: Bearer ").unwrap();
+        assert!(!empty.is_authorized(""));
+
+        assert_eq!(Request::parse("GET /status"), None);
+        assert_eq!(Request::parse("GET status HTTP/1.1"), None);
+        assert!(Request::parse("GET /logs?limit=lots HTTP/1.1")
+            .unwrap()
+            .log_limit()
+            .is_err());
+        assert_eq!(
+            Request::parse("GET /logs?limit=99999 HTTP/1.1")
+                .unwrap()
+                .log_limit(),
+            Ok(MAX_LOG_LIMIT)
+        );
+    }
+
+    #[test]
+    fn routes_check_path_and_method() {
+        assert_eq!(Route::find("GET", "/status"), Ok(Route::Status));
+        assert_eq!(Route::find("POST", "/execute-now"), Ok(Route::ExecuteNow));
+        assert_eq!(Route::find("POST", "/acknowledge"), Ok(Route::Acknowledge));
+        assert_eq!(Route::find("GET", "/cancel").unwrap_err().status, 405);
+        assert_eq!(Route::find("GET", "/shutdown").unwrap_err().status, 404);
+    }
+
+    #[test]
+    fn responses_carry_length_and_auth_challenge() {
+        let response = Response::error(401, "no");
+        let text = String::from_utf8(response.to_bytes()).unwrap();
+        assert!(text.starts_with("HTTP/1.1 401 Unauthorized\r\n"));
+        assert!(text.contains("WWW-Authenticate: Bearer\r\n"));
+        assert!(text.contains("Content-Length: 14\r\n"));
+        assert!(text.ends_with("\r\n\r\n{\"error\":\"no\"}"));
+    }
+}
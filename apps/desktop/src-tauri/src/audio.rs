@@ -0,0 +1,657 @@
+//! Audible countdown alerts.
+//!
+//! A toast is easy to miss, so the countdown start is also announced with a
+//! chime, optionally followed by one beep per second for the last
+//! [`BEEP_WINDOW_SECS`] seconds. Sounds are 16-bit PCM WAV buffers: the
+//! built-in ones are synthesized, a custom one can be loaded from a WAV file.
+//! Volume is applied to the samples before playback.
+//!
+//! Playback runs on its own thread and never reports errors to the caller;
+//! a missing audio device is logged once and otherwise ignored.
+
+use flowwatcher_engine::scheduler::SchedulerEvent;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Countdown seconds below which per-second beeps are played.
+pub const BEEP_WINDOW_SECS: u64 = 10;
+
+/// Largest custom sound file accepted.
+pub const MAX_SOUND_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Sample rate of the synthesized sounds.
+const SYNTH_SAMPLE_RATE: u32 = 22_050;
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+/// Errors from loading or playing sounds.
+#[derive(Debug, thiserror::Error)]
+pub enum AudioError {
+    /// The custom sound file does not exist.
+    #[error("sound file not found: {0}")]
+    NotFound(String),
+
+    /// The custom sound file exceeds [`MAX_SOUND_FILE_BYTES`].
+    #[error("sound file is too large ({0} bytes, limit is 5 MB)")]
+    TooLarge(u64),
+
+    /// The data is not a supported WAV file.
+    #[error("unsupported sound file: {0}")]
+    Undecodable(String),
+
+    /// The file could not be read.
+    #[error("failed to read sound file: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The audio device rejected playback.
+    #[error("audio playback failed: {0}")]
+    Device(String),
+}
+
+// ---------------------------------------------------------------------------
+// Settings
+// ---------------------------------------------------------------------------
+
+/// User preferences for audible alerts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    /// Play a chime when the countdown starts.
+    pub enabled: bool,
+    /// Beep every second during the last [`BEEP_WINDOW_SECS`] seconds.
+    pub countdown_beeps: bool,
+    /// Playback volume from 0.0 to 1.0.
+    pub volume: f32,
+    /// WAV file to play instead of the built-in chime.
+    pub custom_sound_path: Option<String>,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            countdown_beeps: false,
+            volume: 0.8,
+            custom_sound_path: None,
+        }
+    }
+}
+
+impl AudioSettings {
+    /// Volume clamped to `0.0..=1.0` (non-finite values mean full volume).
+    pub fn clamped_volume(&self) -> f32 {
+        if self.volume.is_finite() {
+            self.volume.clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Event mapping
+// ---------------------------------------------------------------------------
+
+/// Which sound to play.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sound {
+    /// Countdown started.
+    Chime,
+    /// One second of the final countdown elapsed.
+    Beep,
+}
+
+/// The sound a scheduler event should produce, if any.
+pub fn sound_for_event(event: &SchedulerEvent, settings: &AudioSettings) -> Option<Sound> {
+    if !settings.enabled {
+        return None;
+    }
+    match event {
+        SchedulerEvent::CountdownStarted { .. } => Some(Sound::Chime),
+        SchedulerEvent::CountdownTick { remaining_seconds }
+            if settings.countdown_beeps
+                && *remaining_seconds > 0
+                && *remaining_seconds <= BEEP_WINDOW_SECS =>
+        {
+            Some(Sound::Beep)
+        }
+        _ => None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// WAV handling
+// ---------------------------------------------------------------------------
+
+/// Decoded PCM audio.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wav {
+    /// Number of interleaved channels.
+    pub channels: u16,
+    /// Samples per second.
+    pub sample_rate: u32,
+    /// Bits per sample (8 or 16).
+    pub bits_per_sample: u16,
+    /// Raw interleaved sample data.
+    pub data: Vec<u8>,
+}
+
+impl Wav {
+    /// Parse a PCM WAV file (8- or 16-bit).
+    pub fn decode(bytes: &[u8]) -> Result<Self, AudioError> {
+        let undecodable = |reason: &str| AudioError::Undecodable(reason.to_string());
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err(undecodable("not a RIFF/WAVE file"));
+        }
+
+        let mut format = None;
+        let mut data = None;
+        let mut pos = 12;
+        while pos + 8 <= bytes.len() {
+            let id = &bytes[pos..pos + 4];
+            let len = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+            let body = bytes
+                .get(pos + 8..pos + 8 + len)
+                .ok_or_else(|| undecodable("truncated chunk"))?;
+            match id {
+                b"fmt " if body.len() >= 16 => {
+                    let u16_at = |i: usize| u16::from_le_bytes([body[i], body[i + 1]]);
+                    let rate = u32::from_le_bytes(body[4..8].try_into().unwrap());
+                    format = Some((u16_at(0), u16_at(2), rate, u16_at(14)));
+                }
+                b"data" => data = Some(body.to_vec()),
+                _ => {}
+            }
+            // Chunks are padded to an even length.
+            pos += 8 + len + (len & 1);
+        }
+
+        let (tag, channels, sample_rate, bits_per_sample) =
+            format.ok_or_else(|| undecodable("missing fmt chunk"))?;
+        if tag != 1 {
+            return Err(undecodable("only uncompressed PCM is supported"));
+        }
+        if bits_per_sample != 8 && bits_per_sample != 16 {
+            return Err(undecodable("only 8- and 16-bit samples are supported"));
+        }
+        if channels == 0 || channels > 8 || sample_rate == 0 {
+            return Err(undecodable("invalid channel count or sample rate"));
+        }
+        let data = data.ok_or_else(|| undecodable("missing data chunk"))?;
+        Ok(Self {
+            channels,
+            sample_rate,
+            bits_per_sample,
+            data,
+        })
+    }
+
+    /// Serialize as a canonical 44-byte-header WAV file.
+    pub fn encode(&self) -> Vec<u8> {
+        let block_align = self.channels * (self.bits_per_sample / 8);
+        let byte_rate = self.sample_rate * u32::from(block_align);
+        let data_len = self.data.len() as u32;
+
+        let mut out = Vec::with_capacity(44 + self.data.len());
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(36 + data_len).to_le_bytes());
+        out.extend_from_slice(b"WAVEfmt ");
+        out.extend_from_slice(&16u32.to_le_bytes());
+        out.extend_from_slice(&1u16.to_le_bytes());
+        out.extend_from_slice(&self.channels.to_le_bytes());
+        out.extend_from_slice(&self.sample_rate.to_le_bytes());
+        out.extend_from_slice(&byte_rate.to_le_bytes());
+        out.extend_from_slice(&block_align.to_le_bytes());
+        out.extend_from_slice(&self.bits_per_sample.to_le_bytes());
+        out.extend_from_slice(b"data");
+        out.extend_from_slice(&data_len.to_le_bytes());
+        out.extend_from_slice(&self.data);
+        out
+    }
+
+    /// Scale every sample by `volume` (`0.0..=1.0`).
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        if self.bits_per_sample == 16 {
+            for sample in self.data.chunks_exact_mut(2) {
+                let value = i16::from_le_bytes([sample[0], sample[1]]);
+                let scaled = (f32::from(value) * volume) as i16;
+                sample.copy_from_slice(&scaled.to_le_bytes());
+            }
+        } else {
+            // 8-bit PCM is unsigned with silence at 128.
+            for sample in &mut self.data {
+                let centered = f32::from(*sample) - 128.0;
+                *sample = (centered * volume + 128.0) as u8;
+            }
+        }
+        self
+    }
+}
+
+/// Synthesize a mono 16-bit sine tone with a short fade in and out.
+fn tone(frequency_hz: f32, duration_ms: u32) -> Vec<u8> {
+    let samples = SYNTH_SAMPLE_RATE * duration_ms / 1000;
+    let fade = (SYNTH_SAMPLE_RATE / 200).max(1); // 5 ms
+    let mut data = Vec::with_capacity(samples as usize * 2);
+    for i in 0..samples {
+        let t = i as f32 / SYNTH_SAMPLE_RATE as f32;
+        let envelope = (i.min(samples - 1 - i) as f32 / fade as f32).min(1.0);
+        let value = (t * frequency_hz * std::f32::consts::TAU).sin() * envelope * 0.6;
+        data.extend_from_slice(&((value * f32::from(i16::MAX)) as i16).to_le_bytes());
+    }
+    data
+}
+
+/// The built-in sound, before volume scaling.
+fn builtin(sound: Sound) -> Wav {
+    let data = match sound {
+        Sound::Chime => [tone(880.0, 180), tone(1320.0, 260)].concat(),
+        Sound::Beep => tone(1000.0, 90),
+    };
+    Wav {
+        channels: 1,
+        sample_rate: SYNTH_SAMPLE_RATE,
+        bits_per_sample: 16,
+        data,
+    }
+}
+
+/// Check that `path` names a playable WAV file under the size limit.
+pub fn load_sound_file(path: &Path) -> Result<Wav, AudioError> {
+    let metadata = std::fs::metadata(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => AudioError::NotFound(path.display().to_string()),
+        _ => AudioError::Io(e),
+    })?;
+    if !metadata.is_file() {
+        return Err(AudioError::NotFound(path.display().to_string()));
+    }
+    if metadata.len() > MAX_SOUND_FILE_BYTES {
+        return Err(AudioError::TooLarge(metadata.len()));
+    }
+    Wav::decode(&std::fs::read(path)?)
+}
+
+// ---------------------------------------------------------------------------
+// Sinks
+// ---------------------------------------------------------------------------
+
+/// Something that can play a WAV buffer to completion.
+pub trait AudioSink: Send + Sync {
+    /// Play `wav` (a complete WAV file), blocking until done.
+    fn play(&self, wav: &[u8]) -> Result<(), AudioError>;
+}
+
+/// Plays through the OS default output device.
+#[derive(Debug, Default)]
+pub struct SystemSink;
+
+impl AudioSink for SystemSink {
+    #[cfg(windows)]
+    fn play(&self, wav: &[u8]) -> Result<(), AudioError> {
+        use windows_sys::Win32::Media::Audio::{PlaySoundW, SND_MEMORY, SND_NODEFAULT, SND_SYNC};
+
+        // SAFETY: with SND_MEMORY the "name" points at an in-memory WAV
+        // image, which outlives this synchronous call.
+        let ok = unsafe {
+            PlaySoundW(
+                wav.as_ptr() as *const u16,
+                std::ptr::null_mut(),
+                SND_MEMORY | SND_SYNC | SND_NODEFAULT,
+            )
+        };
+        if ok != 0 {
+            Ok(())
+        } else {
+            Err(AudioError::Device("no audio output device".into()))
+        }
+    }
+
+    #[cfg(not(windows))]
+    fn play(&self, _wav: &[u8]) -> Result<(), AudioError> {
+        Err(AudioError::Device(
+            "audio playback is only supported on Windows".into(),
+        ))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// AudioAlert
+// ---------------------------------------------------------------------------
+
+/// Maps scheduler events to sounds and plays them in the background.
+pub struct AudioAlert {
+    /// Output device.
+    sink: Arc<dyn AudioSink>,
+    /// Current preferences.
+    settings: Mutex<AudioSettings>,
+    /// Decoded custom chime, if configured.
+    custom: Mutex<Option<Wav>>,
+    /// Set after the first playback failure has been logged.
+    warned: Arc<AtomicBool>,
+}
+
+impl AudioAlert {
+    /// Create an alert that plays through the system output device.
+    pub fn new() -> Self {
+        Self::with_sink(Arc::new(SystemSink))
+    }
+
+    /// Create an alert with an injected sink (e.g. a fake in tests).
+    pub fn with_sink(sink: Arc<dyn AudioSink>) -> Self {
+        Self {
+            sink,
+            settings: Mutex::new(AudioSettings::default()),
+            custom: Mutex::new(None),
+            warned: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Current preferences.
+    pub fn settings(&self) -> AudioSettings {
+        lock(&self.settings).clone()
+    }
+
+    /// Replace the preferences, loading the custom sound if one is set.
+    ///
+    /// On error the previous settings are kept.
+    pub fn set_settings(&self, settings: AudioSettings) -> Result<(), AudioError> {
+        let custom = match settings.custom_sound_path.as_deref() {
+            Some(path) if !path.trim().is_empty() => Some(load_sound_file(Path::new(path))?),
+            _ => None,
+        };
+        *lock(&self.custom) = custom;
+        *lock(&self.settings) = settings;
+        Ok(())
+    }
+
+    /// The WAV file to play for `sound`, with volume applied.
+    pub fn render(&self, sound: Sound) -> Vec<u8> {
+        let volume = lock(&self.settings).clamped_volume();
+        let wav = match (sound, lock(&self.custom).clone()) {
+            (Sound::Chime, Some(custom)) => custom,
+            _ => builtin(sound),
+        };
+        wav.with_volume(volume).encode()
+    }
+
+    /// Play whatever sounds `events` call for, without blocking.
+    pub fn handle_events(&self, events: &[SchedulerEvent]) {
+        let settings = self.settings();
+        for sound in events.iter().filter_map(|e| sound_for_event(e, &settings)) {
+            self.spawn(self.render(sound));
+        }
+    }
+
+    /// Play a sound on a background thread.
+    pub fn spawn(&self, wav: Vec<u8>) {
+        let sink = Arc::clone(&self.sink);
+        let warned = Arc::clone(&self.warned);
+        std::thread::spawn(move || play(&*sink, &warned, &wav));
+    }
+
+    /// Play a sound on the current thread (failures are only logged).
+    pub fn play_blocking(&self, wav: &[u8]) {
+        play(&*self.sink, &self.warned, wav);
+    }
+}
+
+impl Default for AudioAlert {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Play through `sink`, logging the first failure only.
+fn play(sink: &dyn AudioSink, warned: &AtomicBool, wav: &[u8]) {
+    if let Err(e) = sink.play(wav) {
+        if !warned.swap(true, Ordering::Relaxed) {
+            log::warn!("Audio alerts disabled for this session: {e}");
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    // Settings are replaced wholesale, so a poisoned value is still usable.
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_testing::ScratchDir;
+
+    /// Records played buffers; optionally fails every call.
+    #[derive(Default)]
+    struct FakeSink {
+        played: Mutex<Vec<Vec<u8>>>,
+        fail: bool,
+    }
+
+    impl AudioSink for FakeSink {
+        fn play(&self, wav: &[u8]) -> Result<(), AudioError> {
+            self.played.lock().unwrap().push(wav.to_vec());
+            if self.fail {
+                Err(AudioError::Device("no device".into()))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn sound_file(dir: &Path, name: &str, bytes: &[u8]) -> std::path::PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    fn tick(remaining_seconds: u64) -> SchedulerEvent {
+        SchedulerEvent::CountdownTick { remaining_seconds }
+    }
+
+    #[test]
+    fn countdown_start_plays_chime() {
+        let settings = AudioSettings::default();
+        let started = SchedulerEvent::CountdownStarted {
+            total_seconds: 30,
+            deadline_epoch_ms: 0,
+        };
+        assert_eq!(sound_for_event(&started, &settings), Some(Sound::Chime));
+        assert_eq!(sound_for_event(&SchedulerEvent::Executed, &settings), None);
+        let cancelled = SchedulerEvent::Cancelled {
+            reason: flowwatcher_engine::CancelReason::UserUi,
+        };
+        assert_eq!(sound_for_event(&cancelled, &settings), None);
+        assert_eq!(sound_for_event(&tick(5), &settings), None);
+    }
+
+    #[test]
+    fn beeps_only_in_final_window_when_enabled() {
+        let settings = AudioSettings {
+            countdown_beeps: true,
+            ..Default::default()
+        };
+        assert_eq!(sound_for_event(&tick(11), &settings), None);
+        assert_eq!(sound_for_event(&tick(10), &settings), Some(Sound::Beep));
+        assert_eq!(sound_for_event(&tick(1), &settings), Some(Sound::Beep));
+        assert_eq!(sound_for_event(&tick(0), &settings), None);
+    }
+
+    #[test]
+    fn disabled_alerts_are_silent() {
+        let settings = AudioSettings {
+            enabled: false,
+            countdown_beeps: true,
+            ..Default::default()
+        };
+        let started = SchedulerEvent::CountdownStarted {
+            total_seconds: 30,
+            deadline_epoch_ms: 0,
+        };
+        assert_eq!(sound_for_event(&started, &settings), None);
+        assert_eq!(sound_for_event(&tick(3), &settings), None);
+    }
+
+    #[test]
+    fn settings_deserialize_with_defaults() {
+        let settings: AudioSettings = serde_json::from_str(r#"{"volume":0.3}"#).unwrap();
+        assert!(settings.enabled);
+        assert_eq!(settings.volume, 0.3);
+
+        let loud = AudioSettings {
+            volume: 7.0,
+            ..Default::default()
+        };
+        assert_eq!(loud.clamped_volume(), 1.0);
+        let nan = AudioSettings {
+            volume: f32::NAN,
+            ..Default::default()
+        };
+        assert_eq!(nan.clamped_volume(), 1.0);
+    }
+
+    #[test]
+    fn wav_round_trips_and_scales() {
+        let wav = builtin(Sound::Beep);
+        let decoded = Wav::decode(&wav.encode()).unwrap();
+        assert_eq!(decoded, wav);
+
+        let silent = wav.clone().with_volume(0.0);
+        assert!(silent.data.iter().all(|&b| b == 0));
+
+        let eight_bit = Wav {
+            channels: 1,
+            sample_rate: 8000,
+            bits_per_sample: 8,
+            data: vec![0, 128, 255],
+        };
+        assert_eq!(eight_bit.with_volume(0.0).data, vec![128, 128, 128]);
+    }
+
+    #[test]
+    fn rejects_unsupported_wavs() {
+        assert!(Wav::decode(b"not a wav").is_err());
+        assert!(Wav::decode(b"RIFF\0\0\0\0WAVE").is_err());
+
+        let mut float = builtin(Sound::Beep).encode();
+        float[20] = 3; // IEEE float format tag
+        assert!(Wav::decode(&float).is_err());
+
+        let mut truncated = builtin(Sound::Beep).encode();
+        truncated.truncate(100);
+        assert!(Wav::decode(&truncated).is_err());
+    }
+
+    #[test]
+    fn validates_custom_sound_files() {
+        assert!(matches!(
+            load_sound_file(Path::new("/nonexistent/chime.wav")),
+            Err(AudioError::NotFound(_))
+        ));
+
+        let dir = ScratchDir::new("audio-load");
+        let garbage = sound_file(&dir, "garbage.wav", b"definitely not audio");
+        assert!(matches!(
+            load_sound_file(&garbage),
+            Err(AudioError::Undecodable(_))
+        ));
+
+        let good = sound_file(&dir, "good.wav", &builtin(Sound::Chime).encode());
+        assert!(load_sound_file(&good).is_ok());
+
+        let big = sound_file(&dir, "big.wav", &vec![0; MAX_SOUND_FILE_BYTES as usize + 1]);
+        assert!(matches!(
+            load_sound_file(&big),
+            Err(AudioError::TooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_custom_sound_keeps_previous_settings() {
+        let alert = AudioAlert::with_sink(Arc::new(FakeSink::default()));
+        let result = alert.set_settings(AudioSettings {
+            custom_sound_path: Some("/nonexistent/chime.wav".into()),
+            ..Default::default()
+        });
+        assert!(result.is_err());
+        assert_eq!(alert.settings(), AudioSettings::default());
+    }
+
+    #[test]
+    fn custom_sound_replaces_chime_only() {
+        let custom = Wav {
+            channels: 1,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            data: vec![0x00, 0x40, 0x00, 0xC0],
+        };
+        let dir = ScratchDir::new("audio-custom");
+        let path = sound_file(&dir, "custom.wav", &custom.encode());
+        let alert = AudioAlert::with_sink(Arc::new(FakeSink::default()));
+        alert
+            .set_settings(AudioSettings {
+                volume: 0.5,
+                custom_sound_path: Some(path.display().to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let chime = Wav::decode(&alert.render(Sound::Chime)).unwrap();
+        assert_eq!(chime.sample_rate, 8000);
+        assert_eq!(chime.data, vec![0x00, 0x20, 0x00, 0xE0]);
+        let beep = Wav::decode(&alert.render(Sound::Beep)).unwrap();
+        assert_eq!(beep.sample_rate, SYNTH_SAMPLE_RATE);
+    }
+
+    #[test]
+    fn events_are_played_through_the_sink() {
+        let sink = Arc::new(FakeSink::default());
+        let alert = AudioAlert::with_sink(sink.clone());
+        alert
+            .set_settings(AudioSettings {
+                countdown_beeps: true,
+                ..Default::default()
+            })
+            .unwrap();
+
+        alert.handle_events(&[
+            SchedulerEvent::PreWarning {
+                seconds_until_countdown: 60,
+            },
+            SchedulerEvent::CountdownStarted {
+                total_seconds: 30,
+                deadline_epoch_ms: 0,
+            },
+            tick(20),
+            tick(3),
+        ]);
+
+        // Playback is spawned; wait for both sounds to arrive.
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while sink.played.lock().unwrap().len() < 2 && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(sink.played.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn missing_device_does_not_panic() {
+        let sink = Arc::new(FakeSink {
+            fail: true,
+            ..Default::default()
+        });
+        let alert = AudioAlert::with_sink(sink.clone());
+        let wav = alert.render(Sound::Beep);
+        alert.play_blocking(&wav);
+        alert.play_blocking(&wav);
+        assert_eq!(sink.played.lock().unwrap().len(), 2);
+        assert!(alert.warned.load(Ordering::Relaxed));
+    }
+}
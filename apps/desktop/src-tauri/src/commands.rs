@@ -1,13 +1,52 @@
 //! Tauri command handlers — the bridge between frontend and core engine.
 
-use crate::state::{AppState, MonitoringConfig, MonitoringStatus, TriggerConfig};
-use flowwatcher_actions::ActionInfo;
-use flowwatcher_conditions::{MonitorMode, ThresholdCondition};
-use flowwatcher_engine::SpeedMonitor;
-use flowwatcher_platform::network::{InterfaceInfo, NetworkProvider};
-use flowwatcher_platform::process::{ProcessInfo, ProcessProvider};
+use crate::api::{self, HttpApiSettings};
+use crate::audio::{AudioSettings, Sound};
+use crate::config_export::{self, ConfigExport};
+use crate::countdown_alerts::CountdownAlertSettings;
+use crate::error::CommandError;
+use crate::events::{self, EventMetrics};
+use crate::monitor_loop;
+use crate::profiles::{self, Profile, ProfileError, ProfileStore};
+use crate::settings::{
+    self, AppSettings, LoadedSettings, SettingsError, UnknownFields, SETTINGS_FILE,
+};
+use crate::speed_feed;
+use crate::state::{
+    ActionSelection, ActiveRecording, AppState, InterfaceChoice, MemoryStats, MonitoringConfig,
+    MonitoringStatus, TriggerConfig,
+};
+use crate::tray;
+use crate::updater::{self, UpdateCheckRecord, UpdateInfo};
+use flowwatcher_actions::notify::NOTIFY_ACTION_ID;
+use flowwatcher_actions::{Action, ActionError, ActionInfo, RetryingAction, SequenceAction};
+use flowwatcher_conditions::ConditionResult;
+use flowwatcher_engine::scheduler::{SchedulerError, SchedulerEvent, SchedulerState};
+use flowwatcher_engine::speed::SpeedReading;
+use flowwatcher_engine::{
+    calibrate, parse_monitor_mode, persist, ActivityLogger, ActivityStats, ByteRate,
+    CalibrationParams, CalibrationResult, CancelReason, HookSet, LifecycleEvent, LinkSpeed,
+    LogEntry, LogFilter, LogPage, LogStatus, PowerGuard, RearmGate, SessionHeader, SessionId,
+    SessionOutcome, SessionRecord, SessionRecorder, SpeedMonitor, StatsRange, StepOutcome,
+    Transition, UserIdleGuard,
+};
+use flowwatcher_platform::autostart::{system_autostart_provider, AutostartError, LaunchAtLogin};
+use flowwatcher_platform::elevation::{HelperCommand, HelperRequest, HelperResponse};
+use flowwatcher_platform::network::{
+    InterfaceInfo, NetworkError, NetworkProvider, NetworkStatsSnapshot, TrafficSnapshot,
+};
+use flowwatcher_platform::process::{ProcessPage, ProcessProvider, ProcessQuery};
+use flowwatcher_platform::{ElevationWarning, SystemPowerProvider, SystemUserIdleProvider};
+use flowwatcher_triggers::{
+    ActivitySample, FailoverEvent, InterfaceWatchdog, Trigger, TriggerData, TriggerState,
+    TriggerValue,
+};
 use serde::{Deserialize, Serialize};
-use tauri::{Manager, State};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Manager, State};
 
 // ---------------------------------------------------------------------------
 // Response types
@@ -18,62 +57,514 @@ use tauri::{Manager, State};
 pub struct SpeedData {
     pub download_bps: u64,
     pub upload_bps: u64,
+    /// `download_bps` formatted as in the tray tooltip, e.g. "1.5 KB/s".
+    #[serde(default)]
+    pub download_text: String,
+    /// `upload_bps` formatted as in the tray tooltip.
+    #[serde(default)]
+    pub upload_text: String,
+}
+
+impl SpeedData {
+    /// Speed data with the rates formatted.
+    fn new(download_bps: u64, upload_bps: u64) -> Self {
+        Self {
+            download_bps,
+            upload_bps,
+            download_text: ByteRate::new(download_bps).to_string(),
+            upload_text: ByteRate::new(upload_bps).to_string(),
+        }
+    }
+}
+
+/// Raw traffic counters returned by [`get_raw_counters`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawCounters {
+    /// Counters of every interface, read now.
+    pub interfaces: Vec<NetworkStatsSnapshot>,
+    /// Counters the speed monitor measures its next reading against.
+    pub baseline: Vec<NetworkStatsSnapshot>,
+    /// How many times the monitored counters went backwards.
+    pub counter_resets: u64,
+}
+
+/// Payload for the `monitoring-state-change` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateChangeEvent {
+    pub previous: MonitoringStatus,
+    pub current: MonitoringStatus,
+    /// Why a pending action was cancelled, when this change is a cancel.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancel_reason: Option<CancelReason>,
+}
+
+/// Play alert sounds for scheduler events and forward them to the frontend.
+pub(crate) async fn dispatch_scheduler_events(
+    app: &AppHandle,
+    state: &AppState,
+    events: Vec<SchedulerEvent>,
+) {
+    if let Some(active) = state.recording.lock().await.as_mut() {
+        active.events.extend(events.iter().cloned());
+    }
+    state.audio.handle_events(&events);
+    let action_name = state
+        .template_vars
+        .get("action")
+        .unwrap_or_else(|| "The action".to_string());
+    state
+        .countdown_alerts
+        .handle_events(app, &state.audio, &events, &action_name);
+    state.events.emit_scheduler_events(app, events);
+}
+
+/// Update the monitoring status and notify the frontend.
+pub(crate) async fn set_status(app: &AppHandle, state: &AppState, status: MonitoringStatus) {
+    transition(app, state, status, None).await;
+}
+
+/// Update the monitoring status, attributing the change to a cancel.
+async fn transition(
+    app: &AppHandle,
+    state: &AppState,
+    status: MonitoringStatus,
+    cancel_reason: Option<CancelReason>,
+) {
+    let previous = std::mem::replace(&mut *state.status.lock().await, status.clone());
+    tray::update_tray_status(app, &status);
+    state.events.emit(
+        app,
+        events::MONITORING_STATE_CHANGE,
+        StateChangeEvent {
+            previous,
+            current: status,
+            cancel_reason,
+        },
+    );
+}
+
+/// Activity log file (NDJSON, appended to), in the app data directory.
+pub(crate) const LOG_FILE: &str = "activity_logs.ndjson";
+
+/// Whole-file JSON activity log written by older versions, migrated to
+/// [`LOG_FILE`] on startup.
+pub(crate) const LEGACY_LOG_FILE: &str = "activity_logs.json";
+
+/// The app data directory, where settings, profiles and logs are kept.
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, CommandError> {
+    app.path().app_data_dir().map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("app data directory unavailable: {e}"),
+        )
+        .into()
+    })
+}
+
+/// Path of the activity log file, if the app data directory is known.
+pub(crate) fn log_path(app: &AppHandle) -> Option<PathBuf> {
+    app.path().app_data_dir().ok().map(|dir| dir.join(LOG_FILE))
+}
+
+/// Record an activity log entry, notify the frontend, and append it to the
+/// log file (best-effort).
+pub(crate) async fn record_log(app: &AppHandle, state: &AppState, entry: LogEntry) {
+    state.events.emit(app, events::LOG_ADDED, &entry);
+    let mut logger = state.activity_logger.lock().await;
+    match log_path(app) {
+        Some(path) => {
+            if let Err(e) = logger.append_entry(entry, &path) {
+                log::warn!("Failed to append to the activity log: {e}");
+            }
+        }
+        None => logger.add_entry(entry),
+    }
+}
+
+/// Append the current state of log sessions `ids` to the log file
+/// (best-effort).
+fn persist_sessions(app: &AppHandle, logger: &mut ActivityLogger, ids: &[SessionId]) {
+    let Some(path) = log_path(app) else {
+        return;
+    };
+    for &id in ids {
+        if let Err(e) = logger.append_session(id, &path) {
+            log::warn!("Failed to append to the activity log: {e}");
+        }
+    }
+}
+
+/// The last speed reading, as details for a log session that ends.
+pub(crate) async fn final_speed_details(state: &AppState) -> Option<String> {
+    state.last_speed.lock().await.as_ref().map(|speed| {
+        format!(
+            "Final speed: {} B/s down, {} B/s up",
+            speed.download_bps, speed.upload_bps
+        )
+    })
+}
+
+/// Close the open log session with `outcome`, noting the last speed
+/// reading, and persist the log.
+pub(crate) async fn end_log_session(app: &AppHandle, state: &AppState, outcome: SessionOutcome) {
+    let details = final_speed_details(state).await;
+    let mut logger = state.activity_logger.lock().await;
+    if let Some(id) = logger.current_session() {
+        logger.end_session(id, outcome, details);
+        persist_sessions(app, &mut logger, &[id]);
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Network commands
 // ---------------------------------------------------------------------------
 
-/// Get list of network interfaces.
+/// A network interface with its recent activity.
+#[derive(Debug, Clone, Serialize)]
+pub struct InterfaceSummary {
+    /// Interface metadata.
+    #[serde(flatten)]
+    pub info: InterfaceInfo,
+    /// Bytes in both directions since the previous listing, if known.
+    pub recent_bytes: Option<u64>,
+    /// Whether the interface carried traffic since the previous listing.
+    pub active_now: bool,
+}
+
+/// Get list of network interfaces with recent-activity figures.
+///
+/// Each call also refreshes the provider's traffic snapshot, so the
+/// `"auto"` resolution in `start_monitoring` sees recent activity.
 #[tauri::command]
 pub async fn get_network_interfaces(
     state: State<'_, AppState>,
-) -> Result<Vec<InterfaceInfo>, String> {
-    let provider = state.network_provider.lock().await;
-    provider.list_interfaces().map_err(|e| e.to_string())
+) -> Result<Vec<InterfaceSummary>, CommandError> {
+    let mut provider = state.network_provider.lock().await;
+    let traffic = provider.get_interface_traffic_summary()?;
+    let interfaces = provider.list_interfaces()?;
+    Ok(interfaces
+        .into_iter()
+        .map(|info| {
+            let recent_bytes = traffic
+                .iter()
+                .find(|t| t.id == info.id)
+                .and_then(|t| t.recent_bytes());
+            InterfaceSummary {
+                info,
+                recent_bytes,
+                active_now: recent_bytes.is_some_and(|b| b > 0),
+            }
+        })
+        .collect())
+}
+
+/// Get the latest network speed.
+///
+/// Kept for compatibility: windows should subscribe to `speed-update`
+/// events instead. Returns the reading cached by the monitoring loop or
+/// the speed broadcaster without polling, or zeros before the first one.
+#[tauri::command]
+pub async fn get_current_speed(state: State<'_, AppState>) -> Result<SpeedData, CommandError> {
+    Ok(state
+        .last_speed
+        .lock()
+        .await
+        .clone()
+        .unwrap_or_else(|| SpeedData::new(0, 0)))
 }
 
-/// Get current network speed by actively polling the network provider.
+/// Read the raw cumulative counters, for debugging counter resets.
 ///
-/// Creates a SpeedMonitor on first call (establishes baseline).
-/// Subsequent calls compute real download/upload speed from deltas.
+/// Reads every interface without touching the speed monitor, so the next
+/// reading is unaffected.
+#[tauri::command]
+pub async fn get_raw_counters(state: State<'_, AppState>) -> Result<RawCounters, CommandError> {
+    let interfaces = snapshot_traffic(&state).await?.to_stats_snapshots();
+    let (baseline, counter_resets) = match state.speed_monitor.lock().await.as_ref() {
+        Some(monitor) => (monitor.last_counters(), monitor.counter_resets()),
+        None => (Vec::new(), 0),
+    };
+    Ok(RawCounters {
+        interfaces,
+        baseline,
+        counter_resets,
+    })
+}
+
+/// Send `speed-update` events to the calling window every second until it
+/// unsubscribes (or closes). Subscribing again is harmless.
+#[tauri::command]
+pub async fn subscribe_speed_updates(
+    app: AppHandle,
+    window: tauri::Window,
+) -> Result<(), CommandError> {
+    speed_feed::subscribe(&app, window.label()).await;
+    Ok(())
+}
+
+/// Stop sending `speed-update` events to the calling window. The speed is
+/// no longer polled once no window is subscribed.
 #[tauri::command]
-pub async fn get_current_speed(state: State<'_, AppState>) -> Result<SpeedData, String> {
+pub async fn unsubscribe_speed_updates(
+    state: State<'_, AppState>,
+    window: tauri::Window,
+) -> Result<(), CommandError> {
+    speed_feed::unsubscribe(&state, window.label()).await;
+    Ok(())
+}
+
+/// Poll the speed monitor once.
+///
+/// Returns the speed data and, once a baseline exists and the poll
+/// succeeded, the fresh reading.
+pub(crate) async fn poll_speed(
+    app: &AppHandle,
+    state: &AppState,
+) -> Result<(SpeedData, Option<SpeedReading>), CommandError> {
+    // Read the counters before taking the monitor, so the blocking
+    // refresh never holds it.
+    let snapshot = snapshot_traffic(state).await;
     let mut monitor_guard = state.speed_monitor.lock().await;
-    let mut provider = state.network_provider.lock().await;
 
     // Lazily create a SpeedMonitor if none exists yet.
     if monitor_guard.is_none() {
-        let iface = provider
-            .get_default_interface()
-            .map_err(|e| e.to_string())?
+        let iface = state
+            .network_provider
+            .lock()
+            .await
+            .get_default_interface()?
             .map(|i| i.id)
             .unwrap_or_else(|| "unknown".to_string());
-        *monitor_guard = Some(SpeedMonitor::new(iface, 3));
+        *monitor_guard = Some(
+            SpeedMonitor::new(iface, 3)
+                .with_interface_fallback(true)
+                .with_history_window(SPEED_HISTORY_WINDOW),
+        );
     }
 
     let monitor = monitor_guard.as_mut().unwrap();
 
-    // Poll the network provider to get fresh stats and calculate speed.
-    match monitor.poll(&mut *provider) {
-        Ok(Some(reading)) => Ok(SpeedData {
-            download_bps: reading.download_bps,
-            upload_bps: reading.upload_bps,
-        }),
+    // Calculate speed from the fresh stats.
+    let resets_before = monitor.counter_resets();
+    let (polled, failover) = match snapshot {
+        Ok(snapshot) => {
+            let polled = monitor.poll_snapshot(&snapshot);
+            let failover = match state.interface_watchdog.lock().await.as_mut() {
+                Some(watchdog) => watchdog.check(monitor, &snapshot, &polled),
+                None => None,
+            };
+            (polled, failover)
+        }
+        Err(e) => (Err(e.into()), None),
+    };
+    if monitor.counter_resets() > resets_before {
+        log::info!(
+            "Traffic counters of `{}` went backwards, re-establishing baseline",
+            monitor.interfaces()
+        );
+    }
+    state.template_vars.set("interface", monitor.interfaces());
+    let result = match polled {
+        Ok(Some(reading)) => {
+            let speed = SpeedData::new(reading.download_bps, reading.upload_bps);
+            Ok((speed, Some(reading)))
+        }
         Ok(None) => {
-            // First poll (baseline established) — no speed yet.
-            Ok(SpeedData {
-                download_bps: 0,
-                upload_bps: 0,
-            })
+            // First poll, counter reset or interface switch (baseline
+            // established) — no speed yet.
+            Ok((SpeedData::new(0, 0), None))
         }
         Err(_e) => {
             // Return last known speeds if available, else zeros.
-            Ok(SpeedData {
-                download_bps: monitor.current_download_speed(),
-                upload_bps: monitor.current_upload_speed(),
-            })
+            Ok((
+                SpeedData::new(
+                    monitor.current_download_speed(),
+                    monitor.current_upload_speed(),
+                ),
+                None,
+            ))
         }
+    };
+    drop(monitor_guard);
+    if let Some(event) = failover {
+        report_failover(app, state, event).await;
+    }
+    result
+}
+
+/// Read every interface's counters on a blocking thread.
+///
+/// sysinfo's refresh can block for tens of milliseconds. Running it off
+/// the async runtime, and before the speed monitor is locked, keeps
+/// commands that read the monitor or the status from waiting on it.
+pub(crate) async fn snapshot_traffic(state: &AppState) -> Result<TrafficSnapshot, NetworkError> {
+    let provider = Arc::clone(&state.network_provider);
+    tauri::async_runtime::spawn_blocking(move || provider.blocking_lock().snapshot_all())
+        .await
+        .map_err(|e| NetworkError::PlatformError(e.to_string()))?
+}
+
+/// Log a decision of the interface watchdog and tell the frontend.
+///
+/// A pre-warning or countdown started on the old interface's readings is
+/// cancelled when the watchdog switches or suspends monitoring.
+async fn report_failover(app: &AppHandle, state: &AppState, event: FailoverEvent) {
+    match &event {
+        FailoverEvent::Inactive { .. } => log::warn!("{event}"),
+        FailoverEvent::Switched { .. } | FailoverEvent::Recovered { .. } => log::info!("{event}"),
+    }
+    state.events.emit(app, events::INTERFACE_FAILOVER, &event);
+    let (trigger, action) = match state.config.lock().await.as_ref() {
+        Some(c) => (c.trigger_type.label(), c.action_type.to_string()),
+        None => ("Unknown trigger", "Unknown action".to_string()),
+    };
+    let entry = LogEntry::now(trigger, action, LogStatus::Info, Some(event.to_string()));
+    record_log(app, state, entry).await;
+
+    let scheduled = matches!(
+        state.scheduler.lock().await.state(),
+        SchedulerState::Pending | SchedulerState::Countdown
+    );
+    if scheduled && !matches!(event, FailoverEvent::Recovered { .. }) {
+        monitor_loop::cancel_automatically(app, state, CancelReason::InterfaceFailover).await;
+    }
+}
+
+/// How much speed history the backend keeps for the dashboard graph.
+const SPEED_HISTORY_WINDOW: std::time::Duration = flowwatcher_engine::speed::MAX_HISTORY_WINDOW;
+
+/// One point of the speed graph.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeedHistoryPoint {
+    /// When the reading was taken, in Unix milliseconds.
+    pub timestamp_ms: u64,
+    /// Download speed in bytes/sec.
+    pub download_bps: u64,
+    /// Upload speed in bytes/sec.
+    pub upload_bps: u64,
+}
+
+/// Get the recent speed history for the dashboard graph, oldest first.
+///
+/// With `since_ms` (Unix milliseconds) only newer points are returned, so
+/// the graph can fetch the full history once and then just the new points.
+#[tauri::command]
+pub async fn get_speed_history(
+    state: State<'_, AppState>,
+    since_ms: Option<u64>,
+) -> Result<Vec<SpeedHistoryPoint>, CommandError> {
+    let monitor = state.speed_monitor.lock().await;
+    let Some(monitor) = monitor.as_ref() else {
+        return Ok(Vec::new());
+    };
+
+    // Instants are only meaningful in this process; map them to wall-clock
+    // time through the current offset between the two clocks.
+    let now = Instant::now();
+    let now_ms = unix_millis();
+    // A `since` before the process's clock can represent covers everything.
+    let since = since_ms.and_then(|ms| {
+        now.checked_sub(std::time::Duration::from_millis(now_ms.saturating_sub(ms)))
+    });
+    Ok(monitor
+        .get_history(since)
+        .into_iter()
+        .map(|entry| SpeedHistoryPoint {
+            timestamp_ms: now_ms
+                .saturating_sub(now.saturating_duration_since(entry.at).as_millis() as u64),
+            download_bps: entry.reading.download_bps,
+            upload_bps: entry.reading.upload_bps,
+        })
+        .collect())
+}
+
+/// Build the speed monitor for a network trigger's interface choice (see
+/// [`InterfaceChoice::speed_monitor`]).
+pub(crate) async fn speed_monitor_for(
+    state: &AppState,
+    choice: &InterfaceChoice,
+    window_size: usize,
+) -> Result<SpeedMonitor, CommandError> {
+    let mut provider = state.network_provider.lock().await;
+    Ok(choice.speed_monitor(&mut **provider, window_size)?)
+}
+
+/// Points per process series included in trigger-data events.
+const TRIGGER_DATA_HISTORY_POINTS: usize = 60;
+
+/// Debug payload emitted on [`events::TRIGGER_DATA`].
+#[derive(Debug, Clone, Serialize)]
+pub struct TriggerDataEvent {
+    /// Recent activity per watched process, downsampled.
+    pub process_activity: BTreeMap<String, Vec<ActivitySample>>,
+}
+
+/// Publish the downsampled process activity history.
+pub(crate) async fn emit_trigger_data(app: &AppHandle, state: &AppState) {
+    let process_activity = state
+        .process_history
+        .lock()
+        .await
+        .downsampled(TRIGGER_DATA_HISTORY_POINTS);
+    state.events.emit(
+        app,
+        events::TRIGGER_DATA,
+        TriggerDataEvent { process_activity },
+    );
+}
+
+/// Milliseconds since the Unix epoch.
+pub(crate) fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Attach the trigger data that met the condition (or was last evaluated)
+/// to `entry` as its metadata.
+pub(crate) async fn with_trigger_snapshot(state: &AppState, entry: LogEntry) -> LogEntry {
+    match state.last_trigger_data.lock().await.as_ref() {
+        Some(data) => entry.with_metadata(data.to_json()),
+        None => entry,
+    }
+}
+
+/// Trigger data for a speed reading, as the network trigger reports it.
+pub(crate) fn speed_trigger_data(reading: &SpeedReading) -> TriggerData {
+    let mut data = TriggerData::new();
+    data.insert("download_bps", TriggerValue::U64(reading.download_bps));
+    data.insert("upload_bps", TriggerValue::U64(reading.upload_bps));
+    data
+}
+
+/// Append one monitoring loop step to the active session capture, if any.
+///
+/// `trigger` and `condition` are what the loop evaluated at clock time
+/// `now`; the sample carries the scheduler events dispatched since the
+/// previous one, so the capture holds the decisions the loop made.
+pub(crate) async fn record_step(
+    state: &AppState,
+    now: Instant,
+    trigger: Option<&TriggerState>,
+    condition: Option<ConditionResult>,
+    scheduler: SchedulerState,
+    executed: bool,
+) {
+    let mut recording = state.recording.lock().await;
+    let Some(active) = recording.as_mut() else {
+        return;
+    };
+    let outcome = StepOutcome {
+        condition,
+        scheduler,
+        events: std::mem::take(&mut active.events),
+        executed,
+        zone: None,
+    };
+    if let Err(e) = active.recorder.record(now, trigger, &outcome) {
+        log::warn!("Session recording stopped: {e}");
+        *recording = None;
     }
 }
 
@@ -81,131 +572,812 @@ pub async fn get_current_speed(state: State<'_, AppState>) -> Result<SpeedData,
 // Monitoring commands
 // ---------------------------------------------------------------------------
 
-/// Start monitoring with a generic trigger/condition/action config.
+/// Pre-flight warning returned by `start_monitoring`. Monitoring still
+/// starts, but the user should know.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StartWarning {
+    /// The selected action needs administrator rights the app lacks.
+    Elevation(ElevationWarning),
+    /// A selected network interface is disconnected and will read as idle.
+    InterfaceDown {
+        /// The disconnected interface.
+        interface_id: String,
+        /// Human-readable explanation.
+        message: String,
+    },
+}
+
+/// Result of `start_monitoring`.
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitoringStarted {
+    /// Token identifying this session; echoed by `cancel_action` and
+    /// `execute_action_now`.
+    pub session_token: u64,
+    /// Pre-flight warnings, e.g. when the selected action needs
+    /// administrator rights the app does not have.
+    pub warnings: Vec<StartWarning>,
+}
+
+/// Start monitoring with a generic trigger/condition/action config, given
+/// in full or as the name of a saved profile.
+///
+/// Fails with [`CommandError::Busy`] if another state-mutating command is
+/// still running (e.g. a double-clicked Start button), and with
+/// [`CommandError::AlreadyMonitoring`] while a session runs unless the
+/// config sets `restart`. A restart replaces the running session only once
+/// the new config has been checked, cancelling (and logging) an action
+/// that was pending.
 #[tauri::command]
 pub async fn start_monitoring(
+    app: AppHandle,
     state: State<'_, AppState>,
-    config: MonitoringConfig,
-) -> Result<(), String> {
-    // Determine interface to monitor.
-    let interface_id = match &config.trigger_type {
-        TriggerConfig::NetworkIdle { interface_id } => {
-            if interface_id == "auto" {
-                let provider = state.network_provider.lock().await;
-                provider
-                    .get_default_interface()
-                    .map_err(|e| e.to_string())?
-                    .map(|i| i.id)
-                    .ok_or_else(|| "No network interface found".to_string())?
-            } else {
-                interface_id.clone()
+    config: Option<MonitoringConfig>,
+    profile: Option<String>,
+) -> Result<MonitoringStarted, CommandError> {
+    let permit = state.operations.try_begin()?;
+
+    let config = match (config, profile) {
+        (Some(config), None) => config,
+        (None, Some(name)) => load_profile_config(&app, &name)?,
+        _ => {
+            return Err(CommandError::InvalidArgument(
+                "Pass either a config or a profile name".into(),
+            ))
+        }
+    };
+    ensure_can_start(&state.status.lock().await, config.restart)?;
+
+    // Reject unusable settings (e.g. a zero threshold, a too-short
+    // countdown, or an empty AND/OR group) before anything starts.
+    config.validate()?;
+
+    // Resolve the action before anything starts: an unknown id or invalid
+    // params fail here, and actions that take params (e.g. a webhook
+    // without a usable URL, or a sequence) must also validate. A sequence
+    // retries each step on its own.
+    let action: Box<dyn Action> = {
+        let registry = state.actions.lock().await;
+        match &config.action_type {
+            ActionSelection::Single(id) => registry.create(id, &config.action_params),
+            ActionSelection::Sequence(steps) => {
+                SequenceAction::from_specs(&registry, steps).map(|sequence| {
+                    Box::new(
+                        sequence
+                            .with_continue_on_error(config.continue_on_error)
+                            .with_step_log(state.step_log.clone())
+                            .with_step_retry(config.retry, state.attempt_log.clone()),
+                    ) as Box<dyn Action>
+                })
             }
+        }?
+    };
+    let action_info = action.info();
+    if action_info.params_schema.is_some() {
+        action.validate().await?;
+    }
+    // Hooks naming an unknown action or carrying bad params fail too.
+    let hooks = config.hooks.resolve(&*state.actions.lock().await)?;
+
+    // A dry run keeps the whole pipeline but only notifies at the end.
+    state.template_vars.clear();
+    state.template_vars.set("action", &action_info.name);
+    state.step_log.take();
+    state.attempt_log.take();
+    let action = if config.dry_run {
+        state
+            .actions
+            .lock()
+            .await
+            .create(NOTIFY_ACTION_ID, &serde_json::Value::Null)?
+    } else if config.retry.retries() && matches!(config.action_type, ActionSelection::Single(_)) {
+        Box::new(
+            RetryingAction::new(action, config.retry).with_attempt_log(state.attempt_log.clone()),
+        )
+    } else {
+        action
+    };
+
+    // Reject an unknown trigger type or an invalid trigger config. Process
+    // and CPU triggers are kept for the monitoring loop; network sessions
+    // are driven by the speed monitor below.
+    let trigger_config = serde_json::to_value(&config.trigger_type)?;
+    let mut trigger = state
+        .triggers
+        .create(config.trigger_type.registry_id(), &trigger_config)?;
+    let session_trigger = match &config.trigger_type {
+        TriggerConfig::NetworkIdle { .. } => None,
+        TriggerConfig::ProcessIdle { .. } | TriggerConfig::CpuIdle { .. } => {
+            trigger.start().await?;
+            Some(trigger)
+        }
+    };
+    let condition = config.session_condition()?;
+
+    // Everything that can reject the config has run; wind down the
+    // session being replaced.
+    let restart = stop_for_restart(&state).await;
+    dispatch_scheduler_events(&app, &state, restart.events).await;
+    if let Some(entry) = restart.cancelled {
+        record_log(&app, &state, entry).await;
+    }
+
+    // Create speed monitor. Unknown interfaces are rejected here rather
+    // than on the first poll.
+    let mut warnings = Vec::new();
+    let monitor = match &config.trigger_type {
+        TriggerConfig::NetworkIdle { interface_id } => {
+            warnings.extend(interface_warnings(&state, interface_id).await?);
+            speed_monitor_for(&state, interface_id, 3).await?
         }
-        TriggerConfig::ProcessIdle { .. } => {
-            // Process trigger doesn't need a network interface for speed,
-            // but we still set one up for the speed display.
+        TriggerConfig::ProcessIdle { .. } | TriggerConfig::CpuIdle { .. } => {
+            // Process and CPU triggers don't need a network interface for
+            // speed, but we still set one up for the speed display.
             let provider = state.network_provider.lock().await;
-            provider
-                .get_default_interface()
-                .map_err(|e| e.to_string())?
+            let interface_id = provider
+                .get_default_interface()?
                 .map(|i| i.id)
-                .unwrap_or_else(|| "unknown".to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            SpeedMonitor::new(interface_id, 3).with_interface_fallback(true)
+        }
+    }
+    .with_smoothing(config.smoothing)
+    .with_history_window(SPEED_HISTORY_WINDOW);
+    *state.speed_monitor.lock().await = Some(monitor);
+    *state.interface_watchdog.lock().await =
+        matches!(config.trigger_type, TriggerConfig::NetworkIdle { .. })
+            .then(|| InterfaceWatchdog::new(config.interface_failover));
+    state.idle_estimator.lock().await.reset();
+    {
+        let mut history = state.process_history.lock().await;
+        history.reset();
+        if let TriggerConfig::ProcessIdle {
+            history_samples, ..
+        } = &config.trigger_type
+        {
+            history.set_capacity(*history_samples);
+        }
+    }
+
+    *state.condition.lock().await = Some(condition);
+    *state.session_trigger.lock().await = session_trigger;
+    *state.rearm.lock().await = RearmGate::new();
+
+    // Reset scheduler with config values; it runs the action itself.
+    let mut scheduler =
+        flowwatcher_engine::ActionScheduler::new(config.pre_warning_secs, config.countdown_secs)
+            .with_ack_policy(config.ack_policy)
+            .with_max_snooze_secs(config.max_snooze_secs)
+            .with_pending_timeout_secs(config.pending_timeout_secs)
+            .with_action(action);
+    if config.power_guard.is_active() {
+        scheduler = scheduler.with_power_guard(PowerGuard::new(
+            config.power_guard,
+            Box::new(SystemPowerProvider::new()),
+        ));
+    }
+    if let Some(secs) = config.require_user_idle_secs.filter(|&secs| secs > 0) {
+        scheduler = scheduler.with_user_idle_guard(UserIdleGuard::new(
+            secs,
+            Box::new(SystemUserIdleProvider::new()),
+        ));
+    }
+    *state.scheduler.lock().await = scheduler;
+
+    // Pre-flight: warn early if the action will need elevation.
+    if !config.dry_run {
+        warnings.extend(preflight_warnings(&action_info));
+    }
+
+    // Open a log session; entries logged until it ends are tagged with it.
+    {
+        let mut logger = state.activity_logger.lock().await;
+        // Starting over an open session stops it; persist that too.
+        let previous = logger.current_session();
+        let id = logger.start_session(config.trigger_type.label(), config.action_type.to_string());
+        let ids: Vec<_> = previous.into_iter().chain([id]).collect();
+        persist_sessions(&app, &mut logger, &ids);
+    }
+
+    // Update status.
+    let hooks = Arc::new(hooks);
+    *state.hooks.lock().await = Arc::clone(&hooks);
+    state.tray.show_selected_action(match &config.action_type {
+        ActionSelection::Single(id) => Some(id.as_str()),
+        ActionSelection::Sequence(_) => None,
+    });
+    *state.config.lock().await = Some(config);
+    set_status(&app, &state, MonitoringStatus::Monitoring).await;
+    state.stop_monitor_task().await;
+    *state.monitor_task.lock().await = Some(monitor_loop::spawn(app.clone()));
+    spawn_lifecycle_hook(&app, hooks, LifecycleEvent::Start);
+
+    Ok(MonitoringStarted {
+        session_token: permit.begin_session(),
+        warnings,
+    })
+}
+
+/// Refuse to start over a running session unless `restart` is set.
+fn ensure_can_start(status: &MonitoringStatus, restart: bool) -> Result<(), CommandError> {
+    let running = !matches!(status, MonitoringStatus::Idle | MonitoringStatus::Executed);
+    if running && !restart {
+        return Err(CommandError::AlreadyMonitoring);
+    }
+    Ok(())
+}
+
+/// What [`stop_for_restart`] wound down.
+#[derive(Debug, Default)]
+pub(crate) struct Restart {
+    /// Log entry for the pending action it cancelled, if any.
+    pub cancelled: Option<LogEntry>,
+    /// Scheduler events from the cancel, to dispatch.
+    pub events: Vec<SchedulerEvent>,
+}
+
+/// Stop the running session's loop and trigger and cancel its
+/// pending action with [`CancelReason::Restarted`]. The open log session
+/// is left for the next one to close.
+pub(crate) async fn stop_for_restart(state: &AppState) -> Restart {
+    let mut restart = Restart::default();
+    state.stop_monitor_task().await;
+    if let Some(mut trigger) = state.session_trigger.lock().await.take() {
+        if let Err(e) = trigger.stop().await {
+            log::warn!("Failed to stop the session trigger: {e}");
         }
+    }
+    let mut scheduler = state.scheduler.lock().await;
+    if scheduler
+        .cancel_with_reason(CancelReason::Restarted)
+        .is_ok()
+    {
+        let (trigger, action) = match state.config.lock().await.as_ref() {
+            Some(c) => (c.trigger_type.label(), c.action_type.to_string()),
+            None => ("Unknown trigger", "Unknown action".to_string()),
+        };
+        restart.cancelled = Some(LogEntry::cancelled(
+            trigger,
+            action,
+            CancelReason::Restarted,
+        ));
+    }
+    restart.events = scheduler.take_events();
+    restart
+}
+
+/// Elevation warnings for the selected action.
+fn preflight_warnings(action: &ActionInfo) -> Vec<StartWarning> {
+    let status = flowwatcher_platform::current_elevation();
+    flowwatcher_platform::check_elevation(action, status)
+        .into_iter()
+        .map(StartWarning::Elevation)
+        .collect()
+}
+
+/// Reject unknown interfaces in `choice` and warn about disconnected ones
+/// (see [`InterfaceChoice::check`]).
+async fn interface_warnings(
+    state: &AppState,
+    choice: &InterfaceChoice,
+) -> Result<Vec<StartWarning>, CommandError> {
+    let provider = state.network_provider.lock().await;
+    let down = choice.check(&**provider)?;
+    Ok(down
+        .into_iter()
+        .map(|info| StartWarning::InterfaceDown {
+            message: format!(
+                "Network interface {} is disconnected; it will read as idle until it reconnects.",
+                info.name
+            ),
+            interface_id: info.id,
+        })
+        .collect())
+}
+
+/// Stop monitoring.
+#[tauri::command]
+pub async fn stop_monitoring(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), CommandError> {
+    let permit = state.operations.try_begin()?;
+    permit.end_session();
+    end_log_session(&app, &state, SessionOutcome::Stopped).await;
+    state.stop_monitor_task().await;
+    *state.speed_monitor.lock().await = None;
+    *state.interface_watchdog.lock().await = None;
+    *state.condition.lock().await = None;
+    if let Some(mut trigger) = state.session_trigger.lock().await.take() {
+        if let Err(e) = trigger.stop().await {
+            log::warn!("Failed to stop the session trigger: {e}");
+        }
+    }
+    state.scheduler.lock().await.reset();
+    state.idle_estimator.lock().await.reset();
+    state.config.lock().await.take();
+    let hooks = std::mem::take(&mut *state.hooks.lock().await);
+    set_status(&app, &state, MonitoringStatus::Idle).await;
+    spawn_lifecycle_hook(&app, hooks, LifecycleEvent::Stop);
+    Ok(())
+}
+
+/// Fire the hook for `event` from `hooks` in the background.
+///
+/// Best-effort: the hook never delays or fails the command that fired it.
+/// Its outcome is recorded as an info entry in the activity log.
+fn spawn_lifecycle_hook(app: &AppHandle, hooks: Arc<HookSet>, event: LifecycleEvent) {
+    if hooks.get(event).is_none() {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Some(entry) = hooks.fire(event).await {
+            record_log(&app, &app.state::<AppState>(), entry).await;
+        }
+    });
+}
+
+/// Pause monitoring (keeps state but stops polling).
+///
+/// During a pre-warning or countdown the scheduler is paused too, so the
+/// remaining time is kept until `resume_monitoring`.
+#[tauri::command]
+pub async fn pause_monitoring(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), CommandError> {
+    pause_session(&app, &state).await
+}
+
+/// Pause the running session. Shared by [`pause_monitoring`], the tray
+/// and the HTTP API.
+pub(crate) async fn pause_session(app: &AppHandle, state: &AppState) -> Result<(), CommandError> {
+    let status = state.status.lock().await.clone();
+    match status {
+        MonitoringStatus::Monitoring => {}
+        MonitoringStatus::TriggerPending { .. } | MonitoringStatus::Countdown { .. } => {
+            let mut scheduler = state.scheduler.lock().await;
+            scheduler.pause()?;
+            dispatch_scheduler_events(app, state, scheduler.take_events()).await;
+        }
+        _ => return Err(invalid_status("pause", &status)),
+    }
+    set_status(app, state, MonitoringStatus::Paused).await;
+    Ok(())
+}
+
+/// Pause the session (or keep it paused) and resume it automatically after
+/// `duration`; `None` pauses until resumed by hand.
+///
+/// Replaces the timer of an earlier timed pause.
+pub(crate) async fn pause_for(
+    app: &AppHandle,
+    state: &AppState,
+    duration: Option<Duration>,
+) -> Result<(), CommandError> {
+    if *state.status.lock().await != MonitoringStatus::Paused {
+        pause_session(app, state).await?;
+    }
+    state.cancel_auto_resume().await;
+    let Some(duration) = duration else {
+        return Ok(());
     };
+    let handle = app.clone();
+    *state.auto_resume.lock().await = Some(tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(duration).await;
+        let state = handle.state::<AppState>();
+        // Detach first, so resuming does not abort this task.
+        state.auto_resume.lock().await.take();
+        if let Err(e) = resume_session(&handle, &state).await {
+            log::warn!("Automatic resume failed: {e}");
+        }
+    }));
+    Ok(())
+}
 
-    // Create speed monitor.
-    let monitor = SpeedMonitor::new(interface_id, 3);
-    *state.speed_monitor.lock().await = Some(monitor);
+/// Resume monitoring from paused state.
+///
+/// A paused pre-warning or countdown continues with the seconds it had
+/// left.
+#[tauri::command]
+pub async fn resume_monitoring(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), CommandError> {
+    resume_session(&app, &state).await
+}
+
+/// Resume the paused session, cancelling any timed pause. Shared by
+/// [`resume_monitoring`], the tray, the HTTP API and the timed-pause
+/// timer.
+pub(crate) async fn resume_session(app: &AppHandle, state: &AppState) -> Result<(), CommandError> {
+    let status = state.status.lock().await.clone();
+    if status != MonitoringStatus::Paused {
+        return Err(invalid_status("resume", &status));
+    }
+    state.cancel_auto_resume().await;
+
+    let mut scheduler = state.scheduler.lock().await;
+    let resumed =
+        MonitoringStatus::for_scheduler(&scheduler).unwrap_or(MonitoringStatus::Monitoring);
+    if resumed != MonitoringStatus::Monitoring {
+        scheduler.resume()?;
+        dispatch_scheduler_events(app, state, scheduler.take_events()).await;
+    }
+    drop(scheduler);
+    set_status(app, state, resumed).await;
+    Ok(())
+}
+
+/// The error for a pause or resume the current status does not allow.
+fn invalid_status(action: &str, status: &MonitoringStatus) -> CommandError {
+    SchedulerError::InvalidState {
+        action: action.to_string(),
+        state: status.label().to_string(),
+    }
+    .into()
+}
 
-    // Create threshold condition.
-    let mode = match config.condition.monitor_mode.as_str() {
-        "upload_only" => MonitorMode::UploadOnly,
-        "both" => MonitorMode::Both,
-        _ => MonitorMode::DownloadOnly,
+/// Switch the session's action to the parameterless action `action_id`.
+///
+/// Updates the running config and the scheduler (a dry run keeps its
+/// notify action and only renames it). Returns the action's display name.
+pub(crate) async fn select_action(
+    state: &AppState,
+    action_id: &str,
+) -> Result<String, CommandError> {
+    let action = state
+        .actions
+        .lock()
+        .await
+        .create(action_id, &serde_json::Value::Null)?;
+    let name = action.info().name;
+
+    let dry_run = match state.config.lock().await.as_mut() {
+        Some(config) => {
+            config.action_type = ActionSelection::Single(action_id.to_string());
+            config.action_params = serde_json::Value::Null;
+            config.dry_run
+        }
+        None => return Ok(name),
     };
-    let condition = ThresholdCondition::new(
-        config.condition.threshold_bytes_per_sec,
-        config.condition.required_duration_secs,
-        mode,
-    );
-    *state.threshold_condition.lock().await = Some(condition);
+    if !dry_run {
+        state.scheduler.lock().await.replace_action(action);
+    }
+    state.template_vars.set("action", &name);
+    Ok(name)
+}
+
+/// Get current monitoring status.
+#[tauri::command]
+pub async fn get_monitoring_status(
+    state: State<'_, AppState>,
+) -> Result<MonitoringStatus, CommandError> {
+    Ok(state.status.lock().await.clone())
+}
+
+/// Cancel the pending action during countdown.
+///
+/// `session_token` must match the token returned by `start_monitoring`.
+/// `reason` says who or what cancelled (defaults to the app window) and is
+/// carried in the scheduler event, the state change, and the activity log.
+/// Besides the snake_case names, `"user_dialog"`, `"tray"` and `"api"` are
+/// accepted.
+#[tauri::command]
+pub async fn cancel_action(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    session_token: u64,
+    reason: Option<CancelReason>,
+) -> Result<(), CommandError> {
+    state.operations.check_session(session_token)?;
+    let _permit = state.operations.try_begin()?;
+    cancel_pending(&app, &state, reason.unwrap_or_default()).await
+}
 
-    // Reset scheduler with config values.
+/// Cancel the pending action, reset the condition, record why, and run
+/// the cancel hook.
+///
+/// Shared by [`cancel_action`], the tray menu, the HTTP API and the
+/// monitoring loop's automatic cancels; callers hold the operation permit.
+pub(crate) async fn cancel_pending(
+    app: &AppHandle,
+    state: &AppState,
+    reason: CancelReason,
+) -> Result<(), CommandError> {
     let mut scheduler = state.scheduler.lock().await;
-    *scheduler =
-        flowwatcher_engine::ActionScheduler::new(config.pre_warning_secs, config.countdown_secs);
+    scheduler.cancel_with_reason(reason.clone())?;
+    dispatch_scheduler_events(app, state, scheduler.take_events()).await;
+    drop(scheduler);
+    if let Some(condition) = state.condition.lock().await.as_mut() {
+        condition.reset();
+    }
+    transition(
+        app,
+        state,
+        MonitoringStatus::Monitoring,
+        Some(reason.clone()),
+    )
+    .await;
 
-    // Update status.
-    *state.status.lock().await = MonitoringStatus::Monitoring;
-    *state.config.lock().await = Some(config);
+    let config = state.config.lock().await.clone();
+    if reason.is_user() && config.as_ref().is_some_and(|c| c.rearm_requires_activity) {
+        state.rearm.lock().await.require_activity();
+    }
+    let (trigger, action) = match &config {
+        Some(c) => (c.trigger_type.label(), c.action_type.to_string()),
+        None => ("Unknown trigger", "Unknown action".to_string()),
+    };
+    let entry = with_trigger_snapshot(state, LogEntry::cancelled(trigger, action, reason)).await;
+    record_log(app, state, entry).await;
+    let hooks = Arc::clone(&*state.hooks.lock().await);
+    spawn_lifecycle_hook(app, hooks, LifecycleEvent::Cancel);
+    Ok(())
+}
+
+/// Acknowledge the pre-warning ("I'm aware").
+///
+/// `session_token` must match the token returned by `start_monitoring`.
+/// Returns `false` if no acknowledgment was outstanding.
+#[tauri::command]
+pub async fn acknowledge_pre_warning(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    session_token: u64,
+) -> Result<bool, CommandError> {
+    state.operations.check_session(session_token)?;
+    acknowledge_pending(&app, &state).await
+}
+
+/// Acknowledge the pre-warning, returning `false` if no acknowledgment
+/// was outstanding.
+///
+/// Shared by [`acknowledge_pre_warning`], the tray menu and the HTTP API.
+pub(crate) async fn acknowledge_pending(
+    app: &AppHandle,
+    state: &AppState,
+) -> Result<bool, CommandError> {
+    let mut scheduler = state.scheduler.lock().await;
+    let acknowledged = scheduler.acknowledge()?;
+    dispatch_scheduler_events(app, state, scheduler.take_events()).await;
+    Ok(acknowledged)
+}
+
+/// Add `extra_secs` to the pre-warning or countdown instead of cancelling.
+///
+/// `session_token` must match the token returned by `start_monitoring`.
+/// Fails once the session's total snooze would exceed `max_snooze_secs`.
+#[tauri::command]
+pub async fn snooze_action(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    session_token: u64,
+    extra_secs: u64,
+) -> Result<(), CommandError> {
+    state.operations.check_session(session_token)?;
+    let _permit = state.operations.try_begin()?;
+    let mut scheduler = state.scheduler.lock().await;
+    scheduler.snooze(extra_secs)?;
+    let remaining = scheduler.remaining_secs().unwrap_or(0);
+    let status = MonitoringStatus::for_scheduler(&scheduler);
+    dispatch_scheduler_events(&app, &state, scheduler.take_events()).await;
+    drop(scheduler);
+    if let Some(status) = status {
+        set_status(&app, &state, status).await;
+    }
+
+    let config = state.config.lock().await.clone();
+    let (trigger, action) = match &config {
+        Some(c) => (c.trigger_type.label(), c.action_type.to_string()),
+        None => ("Unknown trigger", "Unknown action".to_string()),
+    };
+    let details = format!("Snoozed {extra_secs}s; {remaining}s remaining");
+    record_log(
+        &app,
+        &state,
+        LogEntry::now(trigger, action, LogStatus::Info, Some(details)),
+    )
+    .await;
+    Ok(())
+}
+
+/// Execute the action immediately during countdown.
+///
+/// `session_token` must match the token returned by `start_monitoring`.
+#[tauri::command]
+pub async fn execute_action_now(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    session_token: u64,
+) -> Result<(), CommandError> {
+    state.operations.check_session(session_token)?;
+    let _permit = state.operations.try_begin()?;
+    execute_pending(&app, &state).await
+}
+
+/// Skip the rest of the pre-warning or countdown and run the action.
+///
+/// Shared by [`execute_action_now`] and the HTTP API; callers hold the
+/// operation permit. The outcome is logged either way; an action that
+/// failed or was skipped by the power guard is also returned as the
+/// scheduler's error (`action_failed` or `action_skipped`).
+pub(crate) async fn execute_pending(app: &AppHandle, state: &AppState) -> Result<(), CommandError> {
+    let mut scheduler = state.scheduler.lock().await;
+    scheduler.execute_now()?;
+    let has_action = scheduler.has_action();
+    dispatch_scheduler_events(app, state, scheduler.take_events()).await;
+    drop(scheduler);
+    if has_action {
+        if let Some(error) = monitor_loop::run_action(app, state).await.error {
+            return Err(error.into());
+        }
+    } else {
+        set_status(app, state, MonitoringStatus::Executed).await;
+        end_log_session(app, state, SessionOutcome::Executed).await;
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Calibration commands
+// ---------------------------------------------------------------------------
+
+/// Sample the network while the machine is idle and suggest a threshold.
+///
+/// Polls the configured interface (or the default one) once per second
+/// for `duration_secs`, clamped to 5–300 seconds. The user should leave
+/// the machine alone meanwhile.
+#[tauri::command]
+pub async fn calibrate_idle_baseline(
+    state: State<'_, AppState>,
+    duration_secs: u64,
+    monitor_mode: String,
+) -> Result<CalibrationResult, CommandError> {
+    let duration_secs = duration_secs.clamp(5, 300);
+    let mode = parse_monitor_mode(&monitor_mode)?;
+
+    let configured = match state.config.lock().await.as_ref() {
+        Some(MonitoringConfig {
+            trigger_type: TriggerConfig::NetworkIdle { interface_id },
+            ..
+        }) if !interface_id.is_auto() => Some(interface_id.clone()),
+        _ => None,
+    };
+    let mut monitor = match configured {
+        Some(choice) => speed_monitor_for(&state, &choice, 1).await?,
+        None => {
+            let interface_id = state
+                .network_provider
+                .lock()
+                .await
+                .get_default_interface()?
+                .map(|i| i.id)
+                .ok_or_else(|| NetworkError::InterfaceNotFound("default".into()))?;
+            SpeedMonitor::new(interface_id, 1)
+        }
+    };
+    let mut readings = Vec::with_capacity(duration_secs as usize);
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+    // The first poll only establishes the baseline snapshot.
+    for _ in 0..=duration_secs {
+        interval.tick().await;
+        let Ok(snapshot) = snapshot_traffic(&state).await else {
+            continue;
+        };
+        if let Ok(Some(reading)) = monitor.poll_snapshot(&snapshot) {
+            readings.push(reading);
+        }
+    }
+
+    calibrate(&readings, mode, &CalibrationParams::default()).ok_or(CommandError::NoSpeedSamples)
+}
 
+/// Apply a calibrated threshold to the active monitoring session, if any.
+/// The threshold is bytes per second or a rate such as `"200 KB/s"`. A
+/// session driven by a condition tree keeps evaluating the tree.
+///
+/// An action the old condition scheduled is cancelled with
+/// [`CancelReason::ConditionReArmed`], so the new threshold decides again.
+#[tauri::command]
+pub async fn apply_calibration(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    threshold_bytes_per_sec: ByteRate,
+) -> Result<(), CommandError> {
+    {
+        let mut config = state.config.lock().await;
+        let Some(config) = config.as_mut() else {
+            return Ok(());
+        };
+        config.condition.threshold_bytes_per_sec = threshold_bytes_per_sec.bytes_per_sec();
+        let mut condition = state.condition.lock().await;
+        if condition.is_none() {
+            return Ok(());
+        }
+        *condition = Some(config.session_condition()?);
+    }
+    let scheduled = matches!(
+        state.scheduler.lock().await.state(),
+        SchedulerState::Pending
+            | SchedulerState::Countdown
+            | SchedulerState::PausedPending
+            | SchedulerState::PausedCountdown
+    );
+    if scheduled {
+        cancel_pending(&app, &state, CancelReason::ConditionReArmed).await?;
+    }
     Ok(())
 }
 
-/// Stop monitoring.
-#[tauri::command]
-pub async fn stop_monitoring(state: State<'_, AppState>) -> Result<(), String> {
-    *state.speed_monitor.lock().await = None;
-    *state.threshold_condition.lock().await = None;
-    state.scheduler.lock().await.reset();
-    *state.status.lock().await = MonitoringStatus::Idle;
-    *state.config.lock().await = None;
-    Ok(())
+/// A threshold suggested from an interface's link speed.
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkThresholdSuggestion {
+    /// The link speed the suggestion is based on. When `nominal` is set,
+    /// the adapter's figure is not a real limit and the suggestion is a
+    /// rough guess.
+    pub link_speed: LinkSpeed,
+    /// Suggested threshold in bytes per second.
+    pub threshold_bps: u64,
 }
 
-/// Pause monitoring (keeps state but stops polling).
+/// Suggest a threshold of `percent` (e.g. `2.0` for 2%) of an interface's
+/// link speed. Returns `None` if the OS reports no link speed for it.
 #[tauri::command]
-pub async fn pause_monitoring(state: State<'_, AppState>) -> Result<(), String> {
-    let mut status = state.status.lock().await;
-    if *status == MonitoringStatus::Monitoring {
-        *status = MonitoringStatus::Paused;
-        Ok(())
-    } else {
-        Err(format!("Cannot pause: current status is {:?}", *status))
+pub async fn suggest_threshold_for_interface(
+    state: State<'_, AppState>,
+    interface_id: String,
+    percent: f64,
+) -> Result<Option<LinkThresholdSuggestion>, CommandError> {
+    if !(percent > 0.0 && percent <= 100.0) {
+        return Err(CommandError::InvalidArgument(format!(
+            "Percent must be above 0 and at most 100, got {percent}"
+        )));
     }
+    let info = state
+        .network_provider
+        .lock()
+        .await
+        .find_interface(&interface_id)?
+        .ok_or(NetworkError::InterfaceNotFound(interface_id))?;
+    Ok(
+        LinkSpeed::of(&info).map(|link_speed| LinkThresholdSuggestion {
+            threshold_bps: link_speed.suggest_threshold(percent),
+            link_speed,
+        }),
+    )
 }
 
-/// Resume monitoring from paused state.
-#[tauri::command]
-pub async fn resume_monitoring(state: State<'_, AppState>) -> Result<(), String> {
-    let mut status = state.status.lock().await;
-    if *status == MonitoringStatus::Paused {
-        *status = MonitoringStatus::Monitoring;
-        Ok(())
-    } else {
-        Err(format!("Cannot resume: current status is {:?}", *status))
-    }
-}
+// ---------------------------------------------------------------------------
+// Audio alert commands
+// ---------------------------------------------------------------------------
 
-/// Get current monitoring status.
+/// Apply audio alert preferences, validating the custom sound file if set.
 #[tauri::command]
-pub async fn get_monitoring_status(state: State<'_, AppState>) -> Result<MonitoringStatus, String> {
-    Ok(state.status.lock().await.clone())
+pub async fn set_audio_settings(
+    state: State<'_, AppState>,
+    settings: AudioSettings,
+) -> Result<(), CommandError> {
+    Ok(state.audio.set_settings(settings)?)
 }
 
-/// Cancel the pending action during countdown.
+/// Play the alert a scheduler event calls for, honouring the preferences.
+///
+/// Used by the frontend-driven countdown, which does not go through the
+/// backend scheduler.
 #[tauri::command]
-pub async fn cancel_action(state: State<'_, AppState>) -> Result<(), String> {
-    state
-        .scheduler
-        .lock()
-        .await
-        .cancel()
-        .map_err(|e| e.to_string())?;
-    *state.status.lock().await = MonitoringStatus::Monitoring;
+pub async fn play_alert_for_event(
+    state: State<'_, AppState>,
+    event: SchedulerEvent,
+) -> Result<(), CommandError> {
+    state.audio.handle_events(&[event]);
     Ok(())
 }
 
-/// Execute the action immediately during countdown.
+/// Play the countdown chime once so the user can check sound and volume.
 #[tauri::command]
-pub async fn execute_action_now(state: State<'_, AppState>) -> Result<(), String> {
-    state
-        .scheduler
-        .lock()
-        .await
-        .execute_now()
-        .map_err(|e| e.to_string())?;
-    *state.status.lock().await = MonitoringStatus::Executed;
+pub async fn preview_alert_sound(state: State<'_, AppState>) -> Result<(), CommandError> {
+    state.audio.spawn(state.audio.render(Sound::Chime));
     Ok(())
 }
 
@@ -213,52 +1385,165 @@ pub async fn execute_action_now(state: State<'_, AppState>) -> Result<(), String
 // Process commands
 // ---------------------------------------------------------------------------
 
-/// Get running processes sorted by network usage.
+/// Busiest processes marked as suggested in the process picker.
+const SUGGESTED_PROCESSES: usize = 10;
+
+/// System processes below this many bytes/sec are left out of the picker.
+const SYSTEM_PROCESS_ACTIVITY_FLOOR: u64 = 1024;
+
+/// Get one page of running processes, busiest first.
+///
+/// `filter` keeps names containing it (case-insensitive), `group` merges
+/// instances of the same executable, and `limit`/`offset` select the page;
+/// `total` in the response counts all matches. Quiet system processes are
+/// always left out.
 #[tauri::command]
-pub async fn get_running_processes(state: State<'_, AppState>) -> Result<Vec<ProcessInfo>, String> {
+pub async fn get_running_processes(
+    state: State<'_, AppState>,
+    filter: Option<String>,
+    group: Option<bool>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Result<ProcessPage, CommandError> {
+    let query = ProcessQuery {
+        filter,
+        group_by_name: group.unwrap_or(false),
+        system_activity_floor: SYSTEM_PROCESS_ACTIVITY_FLOOR,
+        suggest_top: SUGGESTED_PROCESSES,
+        offset: offset.unwrap_or(0),
+        limit,
+    };
     let mut provider = state.process_provider.lock().await;
-    provider.get_suggestions(10).map_err(|e| e.to_string())
+    provider.get_filtered(&query).map_err(CommandError::from)
+}
+
+/// Get the recorded activity of watched processes, keyed by lowercased
+/// name, as `(unix_ms, bytes)` samples oldest first.
+#[tauri::command]
+pub async fn get_process_activity_history(
+    state: State<'_, AppState>,
+) -> Result<BTreeMap<String, Vec<ActivitySample>>, CommandError> {
+    Ok(state.process_history.lock().await.snapshot())
 }
 
 // ---------------------------------------------------------------------------
 // Discovery commands
 // ---------------------------------------------------------------------------
 
-/// Get list of available trigger types.
+/// Get list of available trigger types, as registered in the trigger
+/// registry (network first, then alphabetically).
 #[tauri::command]
-pub async fn get_available_triggers() -> Result<Vec<TriggerInfo>, String> {
-    Ok(vec![
-        TriggerInfo {
-            id: "network_idle".to_string(),
-            name: "Network Idle".to_string(),
-            description: "Triggers when network speed falls below threshold".to_string(),
-        },
-        TriggerInfo {
-            id: "process_idle".to_string(),
-            name: "Process Monitor".to_string(),
-            description: "Triggers when selected processes have low network activity".to_string(),
-        },
-    ])
+pub async fn get_available_triggers(
+    state: State<'_, AppState>,
+) -> Result<Vec<TriggerInfo>, CommandError> {
+    let mut descriptors = state.triggers.descriptors();
+    descriptors.sort_by_key(|d| d.id != "network_idle");
+    Ok(descriptors
+        .into_iter()
+        .map(|d| TriggerInfo {
+            id: d.id,
+            name: d.name,
+            description: d.description,
+            config_schema: d.config_schema,
+        })
+        .collect())
 }
 
-/// Get list of available actions.
+/// Get list of available actions: system actions first, then plugins.
 #[tauri::command]
-pub async fn get_available_actions() -> Result<Vec<ActionInfo>, String> {
-    Ok(flowwatcher_platform::all_system_actions()
+pub async fn get_available_actions(
+    state: State<'_, AppState>,
+) -> Result<Vec<ActionInfo>, CommandError> {
+    let mut infos: Vec<ActionInfo> = flowwatcher_platform::all_system_actions()
         .iter()
         .map(|a| a.info())
-        .collect())
+        .collect();
+    let plugins: Vec<ActionInfo> = state
+        .actions
+        .lock()
+        .await
+        .infos()
+        .into_iter()
+        .filter(|info| !infos.iter().any(|i| i.id == info.id))
+        .collect();
+    infos.extend(plugins);
+    Ok(infos)
 }
 
 /// Trigger the countdown flow for testing — schedules the action.
 #[tauri::command]
-pub async fn trigger_countdown(state: State<'_, AppState>) -> Result<(), String> {
+pub async fn trigger_countdown(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), CommandError> {
     let mut scheduler = state.scheduler.lock().await;
-    scheduler.schedule().map_err(|e| e.to_string())?;
-    *state.status.lock().await = MonitoringStatus::TriggerPending;
+    scheduler.schedule()?;
+    let status = MonitoringStatus::for_scheduler(&scheduler);
+    dispatch_scheduler_events(&app, &state, scheduler.take_events()).await;
+    drop(scheduler);
+    if let Some(status) = status {
+        set_status(&app, &state, status).await;
+    }
     Ok(())
 }
 
+/// Run a built-in action through the elevated helper (shows a UAC prompt).
+///
+/// The helper is bundled as an `externalBin`, which installs it next to
+/// the application binary (see `scripts/build-helper.mjs`). The outcome
+/// is logged with how long it took, as for a scheduled action.
+#[tauri::command]
+pub async fn execute_action_elevated(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    action_id: String,
+) -> Result<(), CommandError> {
+    let started = Instant::now();
+    let result = run_elevated(action_id.clone()).await;
+
+    let action = state
+        .actions
+        .lock()
+        .await
+        .infos()
+        .into_iter()
+        .find(|info| info.id == action_id)
+        .map_or(action_id, |info| info.name);
+    let trigger = match state.config.lock().await.as_ref() {
+        Some(c) => c.trigger_type.label(),
+        None => "Unknown trigger",
+    };
+    let (status, details) = match &result {
+        Ok(()) => (
+            LogStatus::Executed,
+            "Ran with elevated privileges".to_string(),
+        ),
+        Err(e) => (LogStatus::Error, e.to_string()),
+    };
+    let entry =
+        LogEntry::now(trigger, action, status, Some(details)).with_duration(started.elapsed());
+    record_log(&app, &state, entry).await;
+    result
+}
+
+/// Ask the elevated helper to execute `action_id`.
+async fn run_elevated(action_id: String) -> Result<(), CommandError> {
+    let helper =
+        std::env::current_exe()?.with_file_name(flowwatcher_platform::elevation::HELPER_EXE_NAME);
+    let request = HelperRequest::new(HelperCommand::Execute { action_id });
+
+    let response = tauri::async_runtime::spawn_blocking(move || {
+        flowwatcher_platform::elevation::run_elevated(&helper, &request)
+    })
+    .await
+    .map_err(|e| CommandError::Failed(e.to_string()))??;
+
+    match response {
+        HelperResponse::Ok => Ok(()),
+        HelperResponse::Error { message } => Err(ActionError::ExecutionFailed(message).into()),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Logging commands
 // ---------------------------------------------------------------------------
@@ -267,23 +1552,56 @@ pub async fn trigger_countdown(state: State<'_, AppState>) -> Result<(), String>
 #[tauri::command]
 pub async fn get_activity_logs(
     state: State<'_, AppState>,
-) -> Result<Vec<flowwatcher_engine::LogEntry>, String> {
-    let logger = state.activity_logger.lock().await;
-    Ok(logger.get_all().to_vec())
+) -> Result<flowwatcher_engine::LogSnapshot, CommandError> {
+    Ok(state.activity_logger.lock().await.snapshot())
+}
+
+/// Get one page of the activity log entries matching `filter`, newest
+/// first, with the total number of matches.
+#[tauri::command]
+pub async fn query_activity_logs(
+    state: State<'_, AppState>,
+    filter: LogFilter,
+) -> Result<LogPage, CommandError> {
+    Ok(state
+        .activity_logger
+        .lock()
+        .await
+        .get_filtered_advanced(&filter))
+}
+
+/// Get counts and timings aggregated from the activity log within `range`
+/// (all of it when omitted).
+#[tauri::command]
+pub async fn get_activity_stats(
+    state: State<'_, AppState>,
+    range: Option<StatsRange>,
+) -> Result<ActivityStats, CommandError> {
+    Ok(state
+        .activity_logger
+        .lock()
+        .await
+        .stats(&range.unwrap_or_default()))
+}
+
+/// Get the recorded monitoring sessions, oldest first.
+#[tauri::command]
+pub async fn get_activity_sessions(
+    state: State<'_, AppState>,
+) -> Result<Vec<SessionRecord>, CommandError> {
+    Ok(state.activity_logger.lock().await.get_sessions().to_vec())
 }
 
 /// Add a new activity log entry.
 #[tauri::command]
 pub async fn add_activity_log(
-    app: tauri::AppHandle,
+    app: AppHandle,
     state: State<'_, AppState>,
     trigger_reason: String,
     action_name: String,
     status: String,
     details: Option<String>,
-) -> Result<(), String> {
-    use flowwatcher_engine::{LogEntry, LogStatus};
-
+) -> Result<(), CommandError> {
     let log_status = match status.as_str() {
         "executed" => LogStatus::Executed,
         "cancelled" => LogStatus::Cancelled,
@@ -292,13 +1610,7 @@ pub async fn add_activity_log(
     };
 
     let entry = LogEntry::now(trigger_reason, action_name, log_status, details);
-    let mut logger = state.activity_logger.lock().await;
-    logger.add_entry(entry);
-
-    // Persist to file (best-effort).
-    if let Ok(dir) = app.path().app_data_dir() {
-        let _ = logger.save_to_file(&dir.join("activity_logs.json"));
-    }
+    record_log(&app, &state, entry).await;
     Ok(())
 }
 
@@ -307,91 +1619,119 @@ pub async fn add_activity_log(
 pub async fn clear_activity_logs(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    state.activity_logger.lock().await.clear();
-    // Delete the persisted file too.
-    if let Ok(dir) = app.path().app_data_dir() {
-        let path = dir.join("activity_logs.json");
-        if path.exists() {
-            let _ = std::fs::remove_file(&path);
-        }
+) -> Result<(), CommandError> {
+    let mut logger = state.activity_logger.lock().await;
+    logger.clear();
+    // Rewrite the persisted file too (it keeps only an open session).
+    if let Some(path) = log_path(&app) {
+        logger.compact_file(&path).map_err(std::io::Error::other)?;
     }
     Ok(())
 }
 
-/// Export activity logs as JSON or TXT string.
+/// Render the activity log as `format`: "txt", "csv", or JSON otherwise.
+fn export_logs(logger: &ActivityLogger, format: &str) -> Result<String, CommandError> {
+    match format {
+        "txt" => Ok(logger.export_txt()),
+        "csv" => Ok(logger.export_csv()),
+        _ => logger.export_json().map_err(CommandError::from),
+    }
+}
+
+/// Export activity logs as a JSON, TXT or CSV string.
 #[tauri::command]
 pub async fn export_activity_logs(
     state: State<'_, AppState>,
     format: String,
-) -> Result<String, String> {
-    let logger = state.activity_logger.lock().await;
-    match format.as_str() {
-        "txt" => Ok(logger.export_txt()),
-        _ => logger.export_json().map_err(|e| e.to_string()),
+) -> Result<String, CommandError> {
+    export_logs(&*state.activity_logger.lock().await, &format)
+}
+
+/// Write the activity log to `path` as JSON, TXT or CSV, creating parent
+/// directories. Returns the number of bytes written.
+///
+/// CSV files start with a UTF-8 byte order mark so Excel reads non-ASCII
+/// details correctly.
+#[tauri::command]
+pub async fn export_activity_logs_to_file(
+    state: State<'_, AppState>,
+    path: String,
+    format: String,
+) -> Result<u64, CommandError> {
+    let mut contents = export_logs(&*state.activity_logger.lock().await, &format)?;
+    if format == "csv" {
+        contents.insert(0, '\u{feff}');
+    }
+    let path = PathBuf::from(path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
     }
+    std::fs::write(&path, &contents)?;
+    Ok(contents.len() as u64)
 }
 
 // ---------------------------------------------------------------------------
 // Settings commands
 // ---------------------------------------------------------------------------
 
+/// Read the persisted settings file (or its backup), if present and valid.
+///
+/// Used by backend tasks that need a setting outside a command call.
+pub fn read_settings_value(app: &AppHandle) -> Option<serde_json::Value> {
+    let path = app.path().app_data_dir().ok()?.join(SETTINGS_FILE);
+    settings::read_value(&path)
+}
+
 /// Get user settings from JSON file, or return defaults.
+///
+/// Files written by older versions are migrated: unknown keys are dropped,
+/// missing or invalid values take their defaults, and the file is
+/// rewritten. An unreadable file (e.g. truncated by a power loss) falls
+/// back to its backup and then to the defaults; `recovery` in the response
+/// says which, so the UI can tell the user.
 #[tauri::command]
-pub async fn get_settings(app: tauri::AppHandle) -> Result<serde_json::Value, String> {
-    let path = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| e.to_string())?
-        .join("settings.json");
-
-    if path.exists() {
-        let data = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&data).map_err(|e| e.to_string())
-    } else {
-        // Return default settings.
-        Ok(serde_json::json!({
-            "language": "en",
-            "theme": "dark",
-            "auto_start": false,
-            "minimize_to_tray": false,
-            "show_notifications": true,
-            "auto_save": true,
-            "pre_action_delay_mins": 0,
-            "keep_screen_on": false,
-            "default_config": null
-        }))
-    }
+pub async fn get_settings(app: tauri::AppHandle) -> Result<LoadedSettings, CommandError> {
+    let path = app_data_dir(&app)?.join(SETTINGS_FILE);
+    Ok(AppSettings::load_recovering(&path))
 }
 
 /// Save user settings to JSON file.
+///
+/// The settings are validated first: a value of the wrong type fails with
+/// the key named, and unknown keys are rejected unless `unknown_fields` is
+/// `"strip"`. The HTTP API is then started or stopped to match; if it
+/// cannot start, the settings stay saved and the error is returned.
 #[tauri::command]
 pub async fn save_settings(
     app: tauri::AppHandle,
+    state: State<'_, AppState>,
     settings: serde_json::Value,
-) -> Result<(), String> {
-    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-
-    // Ensure directory exists.
-    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
-
-    let path = dir.join("settings.json");
-    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-    std::fs::write(&path, json).map_err(|e| e.to_string())
+    unknown_fields: Option<UnknownFields>,
+) -> Result<(), CommandError> {
+    let _permit = state.operations.try_begin()?;
+    let settings = AppSettings::parse(settings, unknown_fields.unwrap_or_default())?;
+    let dir = app_data_dir(&app)?;
+    let value = serde_json::to_value(&settings)?;
+    settings.save(&dir.join(SETTINGS_FILE))?;
+    state
+        .countdown_alerts
+        .set_settings(CountdownAlertSettings::from_settings(&value));
+    api::apply(&app, &settings.http_api).await?;
+    Ok(())
 }
 
-/// Reset settings by deleting the file.
+/// Reset settings by deleting the file and its backup, which also stops
+/// the HTTP API.
 #[tauri::command]
-pub async fn reset_settings(app: tauri::AppHandle) -> Result<(), String> {
-    let path = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| e.to_string())?
-        .join("settings.json");
+pub async fn reset_settings(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), CommandError> {
+    let _permit = state.operations.try_begin()?;
+    let path = app_data_dir(&app)?.join(SETTINGS_FILE);
 
-    if path.exists() {
-        std::fs::remove_file(&path).map_err(|e| e.to_string())?;
-    }
+    persist::remove_with_backup(&path).map_err(SettingsError::Io)?;
+    api::apply(&app, &HttpApiSettings::default()).await?;
     Ok(())
 }
 
@@ -399,16 +1739,24 @@ pub async fn reset_settings(app: tauri::AppHandle) -> Result<(), String> {
 // Tray commands
 // ---------------------------------------------------------------------------
 
-/// Update whether the window close button should minimize to tray.
+/// Update whether the window close button should minimize to tray, and
+/// save it as `minimize_to_tray`.
 #[tauri::command]
-pub async fn set_close_to_tray(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+pub async fn set_close_to_tray(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), CommandError> {
     *state.close_to_tray.lock().await = enabled;
-    Ok(())
+    let dir = app_data_dir(&app)?;
+    Ok(settings::update_file(&dir, |s| {
+        s.minimize_to_tray = enabled
+    })?)
 }
 
 /// Get the current close-to-tray preference.
 #[tauri::command]
-pub async fn get_close_to_tray(state: State<'_, AppState>) -> Result<bool, String> {
+pub async fn get_close_to_tray(state: State<'_, AppState>) -> Result<bool, CommandError> {
     Ok(*state.close_to_tray.lock().await)
 }
 
@@ -418,19 +1766,32 @@ pub struct TriggerInfo {
     pub id: String,
     pub name: String,
     pub description: String,
+    /// JSON Schema of the trigger's config (`null` if undocumented).
+    pub config_schema: serde_json::Value,
 }
 
 // ---------------------------------------------------------------------------
 // Keep Screen On commands
 // ---------------------------------------------------------------------------
 
-/// Set whether the display should stay awake during active monitoring.
-///
-/// Uses Windows `SetThreadExecutionState` to prevent display/system sleep.
+/// Set whether the display should stay awake during active monitoring,
+/// and save it as `keep_screen_on`.
 #[tauri::command]
-pub async fn set_keep_screen_on(state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+pub async fn set_keep_screen_on(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), CommandError> {
     *state.keep_screen_on.lock().await = enabled;
+    apply_keep_screen_on(enabled);
+    let dir = app_data_dir(&app)?;
+    Ok(settings::update_file(&dir, |s| s.keep_screen_on = enabled)?)
+}
 
+/// Keep the display and system awake, or let them sleep again.
+///
+/// Uses Windows `SetThreadExecutionState`; does nothing elsewhere.
+pub(crate) fn apply_keep_screen_on(enabled: bool) {
     #[cfg(windows)]
     {
         use windows_sys::Win32::System::Power::SetThreadExecutionState;
@@ -446,53 +1807,441 @@ pub async fn set_keep_screen_on(state: State<'_, AppState>, enabled: bool) -> Re
             }
         }
     }
-
-    Ok(())
+    #[cfg(not(windows))]
+    let _ = enabled;
 }
 
 /// Get the current keep-screen-on preference.
 #[tauri::command]
-pub async fn get_keep_screen_on(state: State<'_, AppState>) -> Result<bool, String> {
+pub async fn get_keep_screen_on(state: State<'_, AppState>) -> Result<bool, CommandError> {
     Ok(*state.keep_screen_on.lock().await)
 }
 
 // ---------------------------------------------------------------------------
-// Config import/export commands
+// Launch at login commands
 // ---------------------------------------------------------------------------
 
-/// Export the current settings as a JSON string.
+/// The login item for this executable, named after the app.
+pub(crate) fn launch_at_login(app: &AppHandle) -> Result<LaunchAtLogin, AutostartError> {
+    let provider = system_autostart_provider(&app.package_info().name, &app.config().identifier)?;
+    let exe = std::env::current_exe().map_err(AutostartError::from)?;
+    Ok(LaunchAtLogin::new(provider, exe))
+}
+
+/// Start the app at login, or stop doing so, and save it as `auto_start`.
 #[tauri::command]
-pub async fn export_config(app: tauri::AppHandle) -> Result<String, String> {
-    let path = app
+pub async fn set_launch_at_login(app: AppHandle, enabled: bool) -> Result<(), CommandError> {
+    launch_at_login(&app)?.set_enabled(enabled)?;
+    let dir = app
         .path()
         .app_data_dir()
-        .map_err(|e| e.to_string())?
-        .join("settings.json");
+        .map_err(|e| AutostartError::PlatformError(e.to_string()))?;
+    settings::update_file(&dir, |s| s.auto_start = enabled)?;
+    Ok(())
+}
 
-    if path.exists() {
-        std::fs::read_to_string(&path).map_err(|e| e.to_string())
-    } else {
-        Ok("{}".to_string())
+/// Whether the app is registered to start at login.
+#[tauri::command]
+pub async fn get_launch_at_login(app: AppHandle) -> Result<bool, CommandError> {
+    launch_at_login(&app)?
+        .is_enabled()
+        .map_err(CommandError::from)
+}
+
+// ---------------------------------------------------------------------------
+// Config import/export commands
+// ---------------------------------------------------------------------------
+
+/// Export the current settings and the saved profiles as a JSON string in
+/// the current [`config_export`] format. The HTTP API token is left out.
+///
+/// The settings file is read like at startup: an unreadable file exports
+/// its backup, and without either the export carries no settings.
+#[tauri::command]
+pub async fn export_config(app: tauri::AppHandle) -> Result<String, CommandError> {
+    let path = app_data_dir(&app)?.join(SETTINGS_FILE);
+
+    let mut settings = match settings::read_value(&path) {
+        Some(serde_json::Value::Object(settings)) => settings,
+        Some(_) => return Err(SettingsError::NotAnObject.into()),
+        None => serde_json::Map::new(),
+    };
+    if let Some(http_api) = settings.get_mut("http_api").and_then(|v| v.as_object_mut()) {
+        http_api.remove("token");
     }
+    let profiles = load_profiles(&app)?.profiles().to_vec();
+    serde_json::to_string_pretty(&ConfigExport::new(settings, profiles)).map_err(CommandError::from)
 }
 
-/// Import settings from a JSON string.
+/// Import a config exported by [`export_config`]. Exports from older
+/// versions are migrated first; exports from newer versions are rejected.
+/// The settings are validated like [`save_settings`]; without an HTTP API
+/// token (exports leave it out) the current token is kept.
 #[tauri::command]
-pub async fn import_config(app: tauri::AppHandle, config_json: String) -> Result<(), String> {
-    // Validate the JSON first.
-    let _: serde_json::Value =
-        serde_json::from_str(&config_json).map_err(|e| format!("Invalid JSON: {e}"))?;
+pub async fn import_config(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    config_json: String,
+    unknown_fields: Option<UnknownFields>,
+) -> Result<(), CommandError> {
+    let _permit = state.operations.try_begin()?;
+    // Validate everything before changing anything.
+    let import = config_export::upgrade(serde_json::from_str(&config_json)?)?;
+    let mut settings = AppSettings::parse(
+        serde_json::Value::Object(import.settings),
+        unknown_fields.unwrap_or_default(),
+    )?;
 
-    let path = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| e.to_string())?
-        .join("settings.json");
+    // Profiles travel with the config but are stored separately, merged
+    // into the existing ones (same names are replaced).
+    if !import.profiles.is_empty() {
+        let profiles_path = profiles_path(&app)?;
+        let mut store = ProfileStore::load(&profiles_path)?;
+        store.merge(import.profiles);
+        store.save(&profiles_path)?;
+    }
+
+    let path = app_data_dir(&app)?.join(SETTINGS_FILE);
+    if settings.http_api.token.is_empty() {
+        settings.http_api.token = AppSettings::load(&path).http_api.token;
+    }
+    settings.save(&path)?;
+    if let Err(e) = api::apply(&app, &settings.http_api).await {
+        log::warn!("Failed to apply the imported HTTP API settings: {e}");
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Profile commands
+// ---------------------------------------------------------------------------
+
+/// Path of the profiles file.
+fn profiles_path(app: &AppHandle) -> Result<PathBuf, CommandError> {
+    Ok(app_data_dir(app)?.join(profiles::PROFILES_FILE))
+}
+
+/// Load the saved profiles.
+fn load_profiles(app: &AppHandle) -> Result<ProfileStore, CommandError> {
+    Ok(ProfileStore::load(&profiles_path(app)?)?)
+}
+
+/// The configuration saved as profile `name`.
+fn load_profile_config(app: &AppHandle, name: &str) -> Result<MonitoringConfig, CommandError> {
+    load_profiles(app)?
+        .get(name)
+        .map(|profile| profile.config.clone())
+        .ok_or_else(|| ProfileError::NotFound(name.trim().to_string()).into())
+}
+
+/// Save `config` as profile `name`. An existing profile with the same name
+/// (ignoring case) is only replaced when `overwrite` is set.
+#[tauri::command]
+pub async fn save_profile(
+    app: AppHandle,
+    name: String,
+    config: MonitoringConfig,
+    overwrite: Option<bool>,
+) -> Result<(), CommandError> {
+    let path = profiles_path(&app)?;
+    let mut store = ProfileStore::load(&path)?;
+    store
+        .insert(&name, config, overwrite.unwrap_or(false))
+        .and_then(|()| store.save(&path))
+        .map_err(CommandError::from)
+}
+
+/// List the saved profiles, in the order they were first saved.
+#[tauri::command]
+pub async fn list_profiles(app: AppHandle) -> Result<Vec<Profile>, CommandError> {
+    Ok(load_profiles(&app)?.profiles().to_vec())
+}
+
+/// Get the configuration saved as profile `name`.
+#[tauri::command]
+pub async fn load_profile(app: AppHandle, name: String) -> Result<MonitoringConfig, CommandError> {
+    load_profile_config(&app, &name)
+}
+
+/// Delete profile `name`.
+#[tauri::command]
+pub async fn delete_profile(app: AppHandle, name: String) -> Result<(), CommandError> {
+    let path = profiles_path(&app)?;
+    let mut store = ProfileStore::load(&path)?;
+    store
+        .remove(&name)
+        .and_then(|_| store.save(&path))
+        .map_err(CommandError::from)
+}
+
+// ---------------------------------------------------------------------------
+// Diagnostics commands
+// ---------------------------------------------------------------------------
+
+/// Get event batching counters (events in/out, coalesced).
+#[tauri::command]
+pub async fn get_event_metrics(state: State<'_, AppState>) -> Result<EventMetrics, CommandError> {
+    Ok(state.events.metrics())
+}
+
+/// Snapshot of runtime information for bug reports.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostics {
+    /// Running application version.
+    pub app_version: String,
+    /// Event batching counters.
+    pub event_metrics: EventMetrics,
+    /// Most recent update check, if one has run.
+    pub last_update_check: Option<UpdateCheckRecord>,
+}
+
+/// Collect a diagnostics bundle.
+#[tauri::command]
+pub async fn get_diagnostics(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Diagnostics, CommandError> {
+    let last_update_check = state
+        .updater
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .last_check();
+    Ok(Diagnostics {
+        app_version: app.package_info().version.to_string(),
+        event_metrics: state.events.metrics(),
+        last_update_check,
+    })
+}
+
+/// Report the size of every bounded in-memory buffer (debug aid for long
+/// sessions).
+#[tauri::command]
+pub async fn get_memory_stats(state: State<'_, AppState>) -> Result<MemoryStats, CommandError> {
+    Ok(state.memory_stats().await)
+}
+
+/// The scheduler's state and recent transitions, for bug reports.
+#[derive(Debug, Clone, Serialize)]
+pub struct SchedulerDebug {
+    /// Current scheduler state.
+    pub state: SchedulerState,
+    /// Seconds left in the pre-warning or countdown, if one is running.
+    pub remaining_secs: Option<u64>,
+    /// Why the last action was cancelled, while `Cancelled`.
+    pub cancel_reason: Option<CancelReason>,
+    /// Recent state transitions, oldest first.
+    pub history: Vec<Transition>,
+}
+
+/// Dump the scheduler's state and transition history.
+#[tauri::command]
+pub async fn get_scheduler_debug(
+    state: State<'_, AppState>,
+) -> Result<SchedulerDebug, CommandError> {
+    let scheduler = state.scheduler.lock().await;
+    Ok(SchedulerDebug {
+        state: scheduler.state(),
+        remaining_secs: scheduler.remaining_secs(),
+        cancel_reason: scheduler.cancel_reason().cloned(),
+        history: scheduler.transition_history().to_vec(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Recording commands
+// ---------------------------------------------------------------------------
+
+/// Start capturing monitoring samples for offline replay (development builds only).
+///
+/// Returns the path of the capture file. Replay it with
+/// `flowwatcher-cli replay <file>`.
+#[tauri::command]
+pub async fn start_recording(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, CommandError> {
+    if !cfg!(debug_assertions) {
+        return Err(CommandError::Failed(
+            "Session recording is only available in development builds".into(),
+        ));
+    }
+
+    let config = state
+        .config
+        .lock()
+        .await
+        .clone()
+        .ok_or(CommandError::NotMonitoring)?;
+    let header = SessionHeader::new(
+        config.trigger_type.registry_id(),
+        &config.condition.threshold_condition(),
+        config.pre_warning_secs,
+        config.countdown_secs,
+    );
+
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let path = app_data_dir(&app)?
+        .join("recordings")
+        .join(format!("session-{stamp}.jsonl"));
+    let recorder = SessionRecorder::create(&path, &header)?;
+
+    let display = path.display().to_string();
+    *state.recording.lock().await = Some(ActiveRecording {
+        path,
+        recorder,
+        events: Vec::new(),
+    });
+    Ok(display)
+}
+
+/// Stop the active capture and return its path, if one was running.
+#[tauri::command]
+pub async fn stop_recording(state: State<'_, AppState>) -> Result<Option<String>, CommandError> {
+    let Some(active) = state.recording.lock().await.take() else {
+        return Ok(None);
+    };
+    active.recorder.finish()?;
+    Ok(Some(active.path.display().to_string()))
+}
+
+// ---------------------------------------------------------------------------
+// Update commands
+// ---------------------------------------------------------------------------
 
-    // Ensure the directory exists.
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+/// Check for a newer release (at most once per day; failures are silent).
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<UpdateInfo, CommandError> {
+    Ok(updater::run_check(app).await)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_engine::scheduler::SchedulerState;
+    use flowwatcher_platform::network::NetworkStats;
+    use flowwatcher_testing::{ScriptedNetworkProvider, ScriptedProcessProvider};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn scripted_state() -> AppState {
+        AppState::with_providers(
+            Box::new(ScriptedNetworkProvider::from_speeds(&[(0, 0)])),
+            Box::new(ScriptedProcessProvider::fixed(Vec::new())),
+        )
+    }
+
+    fn config() -> MonitoringConfig {
+        serde_json::from_value(serde_json::json!({
+            "trigger_type": { "type": "network_idle", "interface_id": "auto" },
+            "condition": { "threshold_bytes_per_sec": 1024, "required_duration_secs": 60, "monitor_mode": "download_only" },
+            "action_type": "shutdown",
+            "pre_warning_secs": 30,
+            "countdown_secs": 10,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn starting_over_a_running_session_needs_restart() {
+        for idle in [MonitoringStatus::Idle, MonitoringStatus::Executed] {
+            assert_eq!(ensure_can_start(&idle, false), Ok(()));
+        }
+        for running in [
+            MonitoringStatus::Monitoring,
+            MonitoringStatus::Paused,
+            MonitoringStatus::Countdown {
+                remaining_secs: 5,
+                deadline_epoch_ms: None,
+            },
+        ] {
+            assert_eq!(
+                ensure_can_start(&running, false),
+                Err(CommandError::AlreadyMonitoring)
+            );
+            assert_eq!(ensure_can_start(&running, true), Ok(()));
+        }
+    }
+
+    #[tokio::test]
+    async fn restart_cancels_and_logs_the_pending_action() {
+        let state = scripted_state();
+        *state.config.lock().await = Some(config());
+        state.scheduler.lock().await.schedule().unwrap();
+        state.scheduler.lock().await.take_events();
+
+        let restart = stop_for_restart(&state).await;
+        let entry = restart.cancelled.unwrap();
+        assert_eq!(entry.status, LogStatus::Cancelled);
+        assert_eq!(entry.cancel_reason, Some(CancelReason::Restarted));
+        assert_eq!(entry.action_name, "shutdown");
+        assert!(restart.events.contains(&SchedulerEvent::Cancelled {
+            reason: CancelReason::Restarted
+        }));
+        assert_eq!(
+            state.scheduler.lock().await.state(),
+            SchedulerState::Cancelled
+        );
+
+        // Nothing was pending the second time.
+        let restart = stop_for_restart(&state).await;
+        assert!(restart.cancelled.is_none());
+        assert!(restart.events.is_empty());
+    }
+
+    /// A provider whose snapshot blocks like sysinfo's refresh.
+    struct SlowProvider {
+        inner: ScriptedNetworkProvider,
+        refreshing: Arc<AtomicBool>,
+    }
+
+    impl NetworkProvider for SlowProvider {
+        fn list_interfaces(&self) -> Result<Vec<InterfaceInfo>, NetworkError> {
+            self.inner.list_interfaces()
+        }
+
+        fn get_default_interface(&self) -> Result<Option<InterfaceInfo>, NetworkError> {
+            self.inner.get_default_interface()
+        }
+
+        fn get_stats(&mut self, interface_id: &str) -> Result<NetworkStats, NetworkError> {
+            self.inner.get_stats(interface_id)
+        }
+
+        fn snapshot_all(&mut self) -> Result<TrafficSnapshot, NetworkError> {
+            self.refreshing.store(true, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(300));
+            self.inner.snapshot_all()
+        }
     }
 
-    std::fs::write(&path, &config_json).map_err(|e| e.to_string())
+    #[tokio::test]
+    async fn traffic_refresh_does_not_hold_the_monitor_or_status() {
+        let refreshing = Arc::new(AtomicBool::new(false));
+        let state = Arc::new(AppState::with_providers(
+            Box::new(SlowProvider {
+                inner: ScriptedNetworkProvider::from_speeds(&[(0, 0)]),
+                refreshing: refreshing.clone(),
+            }),
+            Box::new(ScriptedProcessProvider::fixed(Vec::new())),
+        ));
+        let snapshot = tokio::spawn({
+            let state = state.clone();
+            async move { snapshot_traffic(&state).await }
+        });
+        while !refreshing.load(Ordering::SeqCst) {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+
+        let started = Instant::now();
+        drop(state.speed_monitor.lock().await);
+        drop(state.status.lock().await);
+        assert!(started.elapsed() < Duration::from_millis(100));
+        assert!(snapshot.await.unwrap().is_ok());
+    }
 }
@@ -0,0 +1,250 @@
+//! Versioned format of exported configs.
+//!
+//! `export_config` wraps the settings and saved profiles in a
+//! [`ConfigExport`] envelope stamped with [`FORMAT_VERSION`]. On import,
+//! [`upgrade`] reads any version this app knows, runs the migrations from
+//! that version up to the current one, and rejects files written by a
+//! newer app instead of silently dropping fields it does not understand.
+//!
+//! Format history:
+//!
+//! - **1** — the raw settings object, with the saved profiles under a
+//!   `"profiles"` key. Files without a `format_version` are version 1.
+//! - **2** — `{ "format_version": 2, "exported_at": ..., "settings": {...},
+//!   "profiles": [...] }`.
+//!
+//! To change the format, bump [`FORMAT_VERSION`] and append a migration
+//! from the previous version to [`MIGRATIONS`].
+
+use crate::profiles::Profile;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// Format version written by this app, and the newest it can import.
+pub const FORMAT_VERSION: u64 = 2;
+
+/// Version of files without a `format_version` field.
+const UNVERSIONED: u64 = 1;
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+/// Errors from reading an exported config.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ExportError {
+    /// The file was exported by a newer version of the app.
+    #[error(
+        "this config was exported by a newer FlowWatcher (format version {found}, this version \
+         reads up to {FORMAT_VERSION}); update FlowWatcher to import it"
+    )]
+    UnsupportedVersion {
+        /// Version found in the file.
+        found: u64,
+    },
+
+    /// The file is not a config export this app can read.
+    #[error("not a FlowWatcher config export: {0}")]
+    Malformed(String),
+}
+
+// ---------------------------------------------------------------------------
+// Envelope
+// ---------------------------------------------------------------------------
+
+/// An exported config in the current format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigExport {
+    /// Format version; always [`FORMAT_VERSION`] once upgraded.
+    pub format_version: u64,
+    /// When the file was exported (RFC 3339); unknown for version 1 files.
+    #[serde(default)]
+    pub exported_at: Option<String>,
+    /// The settings object, as stored in settings.json (validated on
+    /// import, not here).
+    pub settings: Map<String, Value>,
+    /// Saved profiles.
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+}
+
+impl ConfigExport {
+    /// Wrap `settings` and `profiles` in a current-format envelope stamped
+    /// with the current time.
+    pub fn new(settings: Map<String, Value>, profiles: Vec<Profile>) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            exported_at: Some(
+                chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            ),
+            settings,
+            profiles,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Migrations
+// ---------------------------------------------------------------------------
+
+/// A migration from one format version to the next.
+type Migration = fn(Map<String, Value>) -> Result<Map<String, Value>, ExportError>;
+
+/// Migrations in order: entry `i` upgrades version `i + 1` to `i + 2`.
+const MIGRATIONS: [Migration; (FORMAT_VERSION - 1) as usize] = [v1_to_v2];
+
+/// Read an exported config of any known version, upgraded to the current
+/// format.
+pub fn upgrade(value: Value) -> Result<ConfigExport, ExportError> {
+    let Value::Object(mut file) = value else {
+        return Err(ExportError::Malformed("expected a JSON object".into()));
+    };
+    let version = match file.get("format_version") {
+        None => UNVERSIONED,
+        Some(version) => version
+            .as_u64()
+            .filter(|&v| v >= 1)
+            .ok_or_else(|| ExportError::Malformed(format!("invalid format_version {version}")))?,
+    };
+    if version > FORMAT_VERSION {
+        return Err(ExportError::UnsupportedVersion { found: version });
+    }
+    for migration in &MIGRATIONS[(version - 1) as usize..] {
+        file = migration(file)?;
+    }
+    serde_json::from_value(Value::Object(file)).map_err(|e| ExportError::Malformed(e.to_string()))
+}
+
+/// Version 1 → 2: move the settings into `settings` and the profiles from
+/// their `"profiles"` key into `profiles`.
+fn v1_to_v2(mut settings: Map<String, Value>) -> Result<Map<String, Value>, ExportError> {
+    let profiles = settings.remove("profiles").unwrap_or_else(|| json!([]));
+    if !profiles.is_array() {
+        return Err(ExportError::Malformed("profiles must be a list".into()));
+    }
+    let Value::Object(file) = json!({
+        "format_version": 2,
+        "exported_at": null,
+        "settings": settings,
+        "profiles": profiles,
+    }) else {
+        unreachable!("json! of an object literal is an object");
+    };
+    Ok(file)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile_json(name: &str) -> Value {
+        json!({
+            "name": name,
+            "config": {
+                "trigger_type": { "type": "network_idle", "interface_id": "auto" },
+                "condition": {
+                    "threshold_bytes_per_sec": 1024,
+                    "required_duration_secs": 60,
+                    "monitor_mode": "download_only"
+                },
+                "action_type": "shutdown",
+                "pre_warning_secs": 30,
+                "countdown_secs": 10
+            }
+        })
+    }
+
+    fn object(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(map) => map,
+            other => panic!("not an object: {other}"),
+        }
+    }
+
+    #[test]
+    fn v1_to_v2_splits_settings_and_profiles() {
+        let v1 = object(json!({
+            "theme": "light",
+            "keep_screen_on": true,
+            "profiles": [profile_json("Overnight")]
+        }));
+        let v2 = v1_to_v2(v1).unwrap();
+        assert_eq!(v2["format_version"], 2);
+        assert_eq!(v2["exported_at"], Value::Null);
+        assert_eq!(
+            v2["settings"],
+            json!({ "theme": "light", "keep_screen_on": true })
+        );
+        assert_eq!(v2["profiles"], json!([profile_json("Overnight")]));
+
+        let without_profiles = v1_to_v2(object(json!({ "theme": "dark" }))).unwrap();
+        assert_eq!(without_profiles["settings"], json!({ "theme": "dark" }));
+        assert_eq!(without_profiles["profiles"], json!([]));
+
+        assert!(matches!(
+            v1_to_v2(object(json!({ "profiles": "none" }))),
+            Err(ExportError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn unversioned_files_are_upgraded_as_version_1() {
+        let export = upgrade(json!({
+            "language": "bn",
+            "profiles": [profile_json("Overnight")]
+        }))
+        .unwrap();
+        assert_eq!(export.format_version, FORMAT_VERSION);
+        assert_eq!(export.exported_at, None);
+        assert_eq!(Value::Object(export.settings), json!({ "language": "bn" }));
+        assert_eq!(export.profiles.len(), 1);
+        assert_eq!(export.profiles[0].name, "Overnight");
+    }
+
+    #[test]
+    fn current_exports_round_trip() {
+        let settings = object(json!({ "theme": "light" }));
+        let profile: Profile = serde_json::from_value(profile_json("Overnight")).unwrap();
+        let exported =
+            serde_json::to_value(ConfigExport::new(settings.clone(), vec![profile])).unwrap();
+        assert_eq!(exported["format_version"], FORMAT_VERSION);
+        assert!(exported["exported_at"].is_string());
+
+        let imported = upgrade(exported).unwrap();
+        assert_eq!(imported.settings, settings);
+        assert_eq!(imported.profiles[0].name, "Overnight");
+    }
+
+    #[test]
+    fn newer_and_malformed_files_are_rejected() {
+        let newer = upgrade(json!({
+            "format_version": FORMAT_VERSION + 1,
+            "settings": {}
+        }))
+        .unwrap_err();
+        assert_eq!(
+            newer,
+            ExportError::UnsupportedVersion {
+                found: FORMAT_VERSION + 1
+            }
+        );
+        assert!(newer.to_string().contains("update FlowWatcher"));
+
+        for bad in [
+            json!([1, 2]),
+            json!({ "format_version": 0, "settings": {} }),
+            json!({ "format_version": "2", "settings": {} }),
+            json!({ "format_version": 2 }),
+            json!({ "format_version": 2, "settings": {}, "profiles": [{ "name": "x" }] }),
+        ] {
+            assert!(
+                matches!(upgrade(bad.clone()), Err(ExportError::Malformed(_))),
+                "{bad}"
+            );
+        }
+    }
+}
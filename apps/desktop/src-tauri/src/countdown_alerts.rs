@@ -0,0 +1,243 @@
+//! OS notifications before the action runs.
+//!
+//! The window may be hidden in the tray while the countdown runs, so the
+//! backend announces it with a desktop notification when it starts and at
+//! the configured remaining-second marks (`notify_at_secs` in
+//! settings.json), optionally with a beep (`play_sound`). The countdown
+//! start already has its own chime in [`crate::audio`].
+//!
+//! A pre-warning that must be acknowledged is announced too. Desktop
+//! notifications cannot carry action buttons, so the body points at the
+//! tray menu's "Cancel Pending Action" and "I'm Aware" items, which go
+//! through the same paths as the app window.
+
+use crate::audio::{AudioAlert, Sound};
+use crate::notifier::TauriNotifier;
+use flowwatcher_actions::Notifier;
+use flowwatcher_engine::scheduler::SchedulerEvent;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+/// Notification title.
+const ALERT_TITLE: &str = "FlowWatcher";
+
+// ---------------------------------------------------------------------------
+// Settings
+// ---------------------------------------------------------------------------
+
+/// When to notify, read from the top level of settings.json.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CountdownAlertSettings {
+    /// Whether OS notifications are shown at all (`show_notifications`).
+    #[serde(rename = "show_notifications")]
+    pub enabled: bool,
+    /// Remaining seconds at which to notify during the countdown.
+    pub notify_at_secs: Vec<u64>,
+    /// Beep along with each remaining-seconds notification.
+    pub play_sound: bool,
+}
+
+impl Default for CountdownAlertSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            notify_at_secs: vec![30, 10],
+            play_sound: true,
+        }
+    }
+}
+
+impl CountdownAlertSettings {
+    /// Read the alert keys from a settings document, falling back to the
+    /// defaults if they are missing or malformed.
+    pub fn from_settings(settings: &serde_json::Value) -> Self {
+        serde_json::from_value(settings.clone()).unwrap_or_else(|e| {
+            log::warn!("Ignoring invalid countdown alert settings: {e}");
+            Self::default()
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Event mapping
+// ---------------------------------------------------------------------------
+
+/// A notification to show for a scheduler event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountdownAlert {
+    /// Notification title.
+    pub title: String,
+    /// Notification body.
+    pub body: String,
+    /// Whether to beep along with it.
+    pub sound: bool,
+}
+
+/// The notification a scheduler event calls for, if any.
+pub fn alert_for_event(
+    event: &SchedulerEvent,
+    settings: &CountdownAlertSettings,
+    action_name: &str,
+) -> Option<CountdownAlert> {
+    if !settings.enabled {
+        return None;
+    }
+    let (remaining, sound) = match event {
+        SchedulerEvent::AckRequested { timeout_secs } => {
+            return Some(CountdownAlert {
+                title: ALERT_TITLE.to_string(),
+                body: format!(
+                    "{action_name} is about to start its countdown. Choose \"I'm Aware\" \
+                     from the tray menu or the app window within {timeout_secs} seconds."
+                ),
+                sound: false,
+            });
+        }
+        SchedulerEvent::CountdownStarted { total_seconds, .. } => (*total_seconds, false),
+        SchedulerEvent::CountdownTick { remaining_seconds }
+            if settings.notify_at_secs.contains(remaining_seconds) =>
+        {
+            (*remaining_seconds, settings.play_sound)
+        }
+        _ => return None,
+    };
+    Some(CountdownAlert {
+        title: ALERT_TITLE.to_string(),
+        body: format!(
+            "{action_name} in {remaining} seconds. Cancel from the tray menu or the app window."
+        ),
+        sound,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// CountdownAlerts
+// ---------------------------------------------------------------------------
+
+/// Shows countdown notifications according to the current settings.
+#[derive(Default)]
+pub struct CountdownAlerts {
+    /// Current preferences.
+    settings: Mutex<CountdownAlertSettings>,
+}
+
+impl CountdownAlerts {
+    /// Current preferences.
+    pub fn settings(&self) -> CountdownAlertSettings {
+        self.lock().clone()
+    }
+
+    /// Replace the preferences.
+    pub fn set_settings(&self, settings: CountdownAlertSettings) {
+        *self.lock() = settings;
+    }
+
+    /// Show whatever notifications `events` call for, without blocking.
+    /// Failures are only logged.
+    pub fn handle_events(
+        &self,
+        app: &AppHandle,
+        audio: &AudioAlert,
+        events: &[SchedulerEvent],
+        action_name: &str,
+    ) {
+        let settings = self.settings();
+        for alert in events
+            .iter()
+            .filter_map(|e| alert_for_event(e, &settings, action_name))
+        {
+            if let Err(e) = TauriNotifier::new(app.clone()).notify(&alert.title, &alert.body) {
+                log::warn!("Countdown notification failed: {e}");
+            }
+            if alert.sound {
+                audio.spawn(audio.render(Sound::Beep));
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CountdownAlertSettings> {
+        // Settings are replaced wholesale, so a poisoned value is still usable.
+        self.settings.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tick(remaining_seconds: u64) -> SchedulerEvent {
+        SchedulerEvent::CountdownTick { remaining_seconds }
+    }
+
+    #[test]
+    fn notifies_at_start_and_configured_marks() {
+        let settings = CountdownAlertSettings::default();
+        let started = SchedulerEvent::CountdownStarted {
+            total_seconds: 60,
+            deadline_epoch_ms: 0,
+        };
+        let alert = alert_for_event(&started, &settings, "Shut Down").unwrap();
+        assert!(alert.body.starts_with("Shut Down in 60 seconds"));
+        assert!(!alert.sound);
+
+        assert!(
+            alert_for_event(&tick(30), &settings, "Shut Down")
+                .unwrap()
+                .sound
+        );
+        assert!(alert_for_event(&tick(10), &settings, "Shut Down").is_some());
+        assert_eq!(alert_for_event(&tick(29), &settings, "Shut Down"), None);
+        assert_eq!(
+            alert_for_event(&SchedulerEvent::Executed, &settings, "Shut Down"),
+            None
+        );
+    }
+
+    #[test]
+    fn ack_request_points_at_the_tray() {
+        let settings = CountdownAlertSettings::default();
+        let request = SchedulerEvent::AckRequested { timeout_secs: 45 };
+        let alert = alert_for_event(&request, &settings, "Sleep").unwrap();
+        assert!(alert.body.contains("I'm Aware"));
+        assert!(alert.body.contains("45 seconds"));
+        assert!(!alert.sound);
+    }
+
+    #[test]
+    fn disabled_notifications_are_silent() {
+        let settings = CountdownAlertSettings {
+            enabled: false,
+            ..Default::default()
+        };
+        let started = SchedulerEvent::CountdownStarted {
+            total_seconds: 60,
+            deadline_epoch_ms: 0,
+        };
+        assert_eq!(alert_for_event(&started, &settings, "Sleep"), None);
+        assert_eq!(alert_for_event(&tick(10), &settings, "Sleep"), None);
+        let request = SchedulerEvent::AckRequested { timeout_secs: 45 };
+        assert_eq!(alert_for_event(&request, &settings, "Sleep"), None);
+    }
+
+    #[test]
+    fn settings_are_read_from_the_settings_document() {
+        let settings = CountdownAlertSettings::from_settings(&serde_json::json!({
+            "theme": "dark",
+            "show_notifications": true,
+            "notify_at_secs": [5],
+            "play_sound": false
+        }));
+        assert_eq!(settings.notify_at_secs, vec![5]);
+        assert!(!settings.play_sound);
+
+        let fallback =
+            CountdownAlertSettings::from_settings(&serde_json::json!({ "notify_at_secs": "x" }));
+        assert_eq!(fallback, CountdownAlertSettings::default());
+    }
+}
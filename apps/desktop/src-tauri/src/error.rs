@@ -0,0 +1,457 @@
+//! Error type returned by every Tauri command.
+//!
+//! A [`CommandError`] reaches the frontend as `{ "code": ..., "message": ... }`.
+//! The frontend branches on `code` (e.g. `"interface_not_found"` versus
+//! `"already_monitoring"`), so codes are part of the command interface:
+//! once shipped, a code is never renamed or reused. `message` is for
+//! display only. Config errors also carry the offending `field`.
+
+use crate::audio::AudioError;
+use crate::config_export::ExportError;
+use crate::profiles::ProfileError;
+use crate::settings::SettingsError;
+use flowwatcher_actions::ActionError;
+use flowwatcher_engine::scheduler::SchedulerError;
+use flowwatcher_engine::ConfigError;
+use flowwatcher_platform::autostart::AutostartError;
+use flowwatcher_platform::elevation::ElevationError;
+use flowwatcher_platform::network::NetworkError;
+use flowwatcher_platform::process::ProcessError;
+use flowwatcher_triggers::speed::SpeedError;
+use flowwatcher_triggers::TriggerError;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+// ---------------------------------------------------------------------------
+// CommandError
+// ---------------------------------------------------------------------------
+
+/// Error returned by Tauri commands.
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    /// Another state-mutating command is still running.
+    #[error("Another operation is in progress")]
+    Busy,
+    /// `start_monitoring` was called while a session is running, without
+    /// `restart` set.
+    #[error("Already monitoring — call stop_monitoring first or pass restart: true")]
+    AlreadyMonitoring,
+    /// The caller's session token does not match the active session.
+    #[error("Stale session token {received} (active session is {active})")]
+    StaleSession {
+        /// Token of the active session (0 when none is running).
+        active: u64,
+        /// Token sent by the caller.
+        received: u64,
+    },
+    /// Reading network interfaces failed.
+    #[error(transparent)]
+    Network(#[from] NetworkError),
+    /// Reading or controlling processes failed.
+    #[error(transparent)]
+    Process(#[from] ProcessError),
+    /// The action scheduler rejected the request or the action failed.
+    #[error(transparent)]
+    Scheduler(#[from] SchedulerError),
+    /// The speed monitor could not produce a reading.
+    #[error(transparent)]
+    Speed(#[from] SpeedError),
+    /// The login item could not be read or changed.
+    #[error(transparent)]
+    Autostart(#[from] AutostartError),
+    /// A trigger could not be created or started.
+    #[error(transparent)]
+    Trigger(#[from] TriggerError),
+    /// A monitoring config field has an unusable value.
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    /// An action could not be created, validated or run.
+    #[error(transparent)]
+    Action(#[from] ActionError),
+    /// The elevated helper could not run the action.
+    #[error(transparent)]
+    Elevation(#[from] ElevationError),
+    /// Settings failed validation.
+    #[error(transparent)]
+    Settings(#[from] SettingsError),
+    /// A profile could not be saved, found or removed.
+    #[error(transparent)]
+    Profile(#[from] ProfileError),
+    /// A file or directory could not be read or written.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// JSON passed in or read from disk is malformed.
+    #[error("Invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// An imported config is malformed or from a newer version.
+    #[error(transparent)]
+    Export(#[from] ExportError),
+    /// The custom alert sound could not be used.
+    #[error(transparent)]
+    Audio(#[from] AudioError),
+    /// A command argument is missing, conflicting or out of range.
+    #[error("{0}")]
+    InvalidArgument(String),
+    /// The command needs a running session and none is active.
+    #[error("Not monitoring — start monitoring first")]
+    NotMonitoring,
+    /// Calibration ended without a single speed reading.
+    #[error("No speed samples were collected")]
+    NoSpeedSamples,
+    /// The command failed for a reason no other variant covers (e.g. a
+    /// background task panicked).
+    #[error("{0}")]
+    Failed(String),
+}
+
+impl CommandError {
+    /// The stable code the frontend branches on.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Busy => "busy",
+            Self::AlreadyMonitoring => "already_monitoring",
+            Self::StaleSession { .. } => "stale_session",
+            Self::Network(e) => network_code(e),
+            Self::Process(ProcessError::ProcessNotFound(_)) => "process_not_found",
+            Self::Process(ProcessError::PlatformError(_)) => "process_error",
+            Self::Scheduler(SchedulerError::InvalidState { .. }) => "invalid_state",
+            Self::Scheduler(SchedulerError::SnoozeLimit { .. }) => "snooze_limit",
+            Self::Scheduler(SchedulerError::ActionError(_)) => "action_failed",
+            Self::Scheduler(SchedulerError::ExecutionSkipped(_)) => "action_skipped",
+            Self::Speed(SpeedError::NoPreviousSnapshot) => "no_speed_baseline",
+            Self::Speed(SpeedError::NetworkError(e)) => network_code(e),
+            Self::Autostart(AutostartError::AccessDenied(_)) => "access_denied",
+            Self::Autostart(AutostartError::NotSupported(_)) => "not_supported",
+            Self::Autostart(AutostartError::PlatformError(_)) => "autostart_error",
+            Self::Trigger(TriggerError::InterfaceNotFound { .. }) => "interface_not_found",
+            Self::Trigger(TriggerError::InvalidConfig(_)) => "invalid_trigger_config",
+            Self::Trigger(TriggerError::UnknownType(_)) => "unknown_trigger",
+            Self::Trigger(TriggerError::StartFailed(_)) => "trigger_start_failed",
+            Self::Trigger(TriggerError::StopFailed(_)) => "trigger_stop_failed",
+            Self::Trigger(TriggerError::EvaluationError(_)) => "trigger_error",
+            Self::Config(_) => "invalid_config",
+            Self::Action(ActionError::ExecutionFailed(_) | ActionError::OsError(_)) => {
+                "action_failed"
+            }
+            Self::Action(ActionError::Timeout(_)) => "action_timeout",
+            Self::Action(ActionError::NotSupported(_)) => "action_not_supported",
+            Self::Action(ActionError::InsufficientPrivileges(_)) => "insufficient_privileges",
+            Self::Action(ActionError::UnknownType(_)) => "unknown_action",
+            Self::Action(ActionError::InvalidConfig(_)) => "invalid_action_config",
+            Self::Elevation(ElevationError::Cancelled) => "elevation_cancelled",
+            Self::Elevation(ElevationError::NotSupported) => "not_supported",
+            Self::Elevation(
+                ElevationError::Protocol(_)
+                | ElevationError::VersionMismatch { .. }
+                | ElevationError::Launch(_),
+            ) => "elevation_error",
+            Self::Settings(SettingsError::Io(_)) => "io_error",
+            Self::Settings(_) => "invalid_settings",
+            Self::Profile(ProfileError::EmptyName | ProfileError::NameTooLong) => {
+                "invalid_profile_name"
+            }
+            Self::Profile(ProfileError::Exists(_)) => "profile_exists",
+            Self::Profile(ProfileError::Full) => "profile_limit",
+            Self::Profile(ProfileError::NotFound(_)) => "profile_not_found",
+            Self::Profile(ProfileError::Io(_)) | Self::Io(_) => "io_error",
+            Self::Json(_) => "invalid_json",
+            Self::Export(ExportError::UnsupportedVersion { .. }) => "unsupported_format_version",
+            Self::Export(ExportError::Malformed(_)) => "invalid_export",
+            Self::Audio(AudioError::NotFound(_)) => "sound_not_found",
+            Self::Audio(AudioError::TooLarge(_) | AudioError::Undecodable(_)) => {
+                "invalid_sound_file"
+            }
+            Self::Audio(AudioError::Io(_)) => "io_error",
+            Self::Audio(AudioError::Device(_)) => "audio_error",
+            Self::InvalidArgument(_) => "invalid_argument",
+            Self::NotMonitoring => "not_monitoring",
+            Self::NoSpeedSamples => "no_speed_samples",
+            Self::Failed(_) => "failed",
+        }
+    }
+}
+
+/// Code of a network error, whether it came directly or through the
+/// speed monitor.
+fn network_code(error: &NetworkError) -> &'static str {
+    match error {
+        NetworkError::InterfaceNotFound(_) => "interface_not_found",
+        NetworkError::PlatformError(_) => "network_error",
+    }
+}
+
+/// Errors are equal when they carry the same code and message; the
+/// wrapped core errors are not comparable themselves.
+impl PartialEq for CommandError {
+    fn eq(&self, other: &Self) -> bool {
+        self.code() == other.code() && self.to_string() == other.to_string()
+    }
+}
+
+impl Eq for CommandError {}
+
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let field = match self {
+            Self::Config(e) => Some(&e.field),
+            _ => None,
+        };
+        let mut error =
+            serializer.serialize_struct("CommandError", 2 + field.is_some() as usize)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.to_string())?;
+        if let Some(field) = field {
+            error.serialize_field("field", field)?;
+        } else {
+            error.skip_field("field")?;
+        }
+        error.end()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_error() -> ConfigError {
+        ConfigError {
+            field: "condition.threshold_bytes_per_sec".into(),
+            message: "must be greater than 0".into(),
+        }
+    }
+
+    /// Every error the commands can return, with the code the frontend
+    /// expects. Changing a code here breaks the frontend.
+    fn mapping() -> Vec<(CommandError, &'static str)> {
+        let json = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        vec![
+            (CommandError::Busy, "busy"),
+            (CommandError::AlreadyMonitoring, "already_monitoring"),
+            (
+                CommandError::StaleSession {
+                    active: 2,
+                    received: 1,
+                },
+                "stale_session",
+            ),
+            (
+                NetworkError::InterfaceNotFound("eth9".into()).into(),
+                "interface_not_found",
+            ),
+            (
+                NetworkError::PlatformError("refresh failed".into()).into(),
+                "network_error",
+            ),
+            (
+                ProcessError::ProcessNotFound(42).into(),
+                "process_not_found",
+            ),
+            (
+                ProcessError::PlatformError("access denied".into()).into(),
+                "process_error",
+            ),
+            (
+                SchedulerError::InvalidState {
+                    action: "pause".into(),
+                    state: "Idle".into(),
+                }
+                .into(),
+                "invalid_state",
+            ),
+            (
+                SchedulerError::SnoozeLimit {
+                    requested: 60,
+                    available: 30,
+                }
+                .into(),
+                "snooze_limit",
+            ),
+            (
+                SchedulerError::ActionError("exit code 1".into()).into(),
+                "action_failed",
+            ),
+            (
+                SchedulerError::ExecutionSkipped("on battery".into()).into(),
+                "action_skipped",
+            ),
+            (SpeedError::NoPreviousSnapshot.into(), "no_speed_baseline"),
+            (
+                SpeedError::NetworkError(NetworkError::InterfaceNotFound("eth9".into())).into(),
+                "interface_not_found",
+            ),
+            (
+                SpeedError::NetworkError(NetworkError::PlatformError("gone".into())).into(),
+                "network_error",
+            ),
+            (
+                AutostartError::AccessDenied("registry".into()).into(),
+                "access_denied",
+            ),
+            (
+                AutostartError::NotSupported("linux".into()).into(),
+                "not_supported",
+            ),
+            (
+                AutostartError::PlatformError("io".into()).into(),
+                "autostart_error",
+            ),
+            (json.into(), "invalid_json"),
+            (
+                TriggerError::InterfaceNotFound {
+                    id: "eth9".into(),
+                    available: "eth0".into(),
+                }
+                .into(),
+                "interface_not_found",
+            ),
+            (
+                TriggerError::InvalidConfig("no network interface selected".into()).into(),
+                "invalid_trigger_config",
+            ),
+            (
+                TriggerError::UnknownType("nope".into()).into(),
+                "unknown_trigger",
+            ),
+            (
+                TriggerError::StartFailed("no processes".into()).into(),
+                "trigger_start_failed",
+            ),
+            (
+                TriggerError::StopFailed("busy".into()).into(),
+                "trigger_stop_failed",
+            ),
+            (
+                TriggerError::EvaluationError("read failed".into()).into(),
+                "trigger_error",
+            ),
+            (config_error().into(), "invalid_config"),
+            (
+                ActionError::ExecutionFailed("exit code 1".into()).into(),
+                "action_failed",
+            ),
+            (
+                ActionError::OsError("access denied".into()).into(),
+                "action_failed",
+            ),
+            (ActionError::Timeout("30 s".into()).into(), "action_timeout"),
+            (
+                ActionError::NotSupported("hibernate".into()).into(),
+                "action_not_supported",
+            ),
+            (
+                ActionError::InsufficientPrivileges("shutdown".into()).into(),
+                "insufficient_privileges",
+            ),
+            (
+                ActionError::UnknownType("nope".into()).into(),
+                "unknown_action",
+            ),
+            (
+                ActionError::InvalidConfig("url: missing".into()).into(),
+                "invalid_action_config",
+            ),
+            (ElevationError::Cancelled.into(), "elevation_cancelled"),
+            (ElevationError::NotSupported.into(), "not_supported"),
+            (
+                ElevationError::Launch("not found".into()).into(),
+                "elevation_error",
+            ),
+            (SettingsError::NotAnObject.into(), "invalid_settings"),
+            (SettingsError::Io("disk full".into()).into(), "io_error"),
+            (ProfileError::EmptyName.into(), "invalid_profile_name"),
+            (
+                ProfileError::Exists("Overnight".into()).into(),
+                "profile_exists",
+            ),
+            (ProfileError::Full.into(), "profile_limit"),
+            (
+                ProfileError::NotFound("Overnight".into()).into(),
+                "profile_not_found",
+            ),
+            (ProfileError::Io("disk full".into()).into(), "io_error"),
+            (
+                std::io::Error::from(std::io::ErrorKind::PermissionDenied).into(),
+                "io_error",
+            ),
+            (
+                ExportError::UnsupportedVersion { found: 3 }.into(),
+                "unsupported_format_version",
+            ),
+            (
+                ExportError::Malformed("expected a JSON object".into()).into(),
+                "invalid_export",
+            ),
+            (
+                AudioError::NotFound("alert.wav".into()).into(),
+                "sound_not_found",
+            ),
+            (AudioError::TooLarge(6_000_000).into(), "invalid_sound_file"),
+            (
+                AudioError::Undecodable("not a WAV file".into()).into(),
+                "invalid_sound_file",
+            ),
+            (AudioError::Device("no output".into()).into(), "audio_error"),
+            (
+                CommandError::InvalidArgument("percent out of range".into()),
+                "invalid_argument",
+            ),
+            (CommandError::NotMonitoring, "not_monitoring"),
+            (CommandError::NoSpeedSamples, "no_speed_samples"),
+            (CommandError::Failed("task panicked".into()), "failed"),
+        ]
+    }
+
+    #[test]
+    fn every_error_maps_to_a_stable_code() {
+        for (error, code) in mapping() {
+            assert_eq!(error.code(), code, "{error:?}");
+        }
+    }
+
+    #[test]
+    fn errors_serialize_with_code_and_message() {
+        for (error, code) in mapping() {
+            if matches!(error, CommandError::Config(_)) {
+                continue;
+            }
+            let json = serde_json::to_value(&error).unwrap();
+            assert_eq!(
+                json,
+                serde_json::json!({ "code": code, "message": error.to_string() })
+            );
+        }
+        let json = serde_json::to_string(&CommandError::Busy).unwrap();
+        assert_eq!(
+            json,
+            r#"{"code":"busy","message":"Another operation is in progress"}"#
+        );
+    }
+
+    #[test]
+    fn config_errors_carry_the_field() {
+        let json = serde_json::to_value(CommandError::from(config_error())).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "code": "invalid_config",
+                "message": "condition.threshold_bytes_per_sec: must be greater than 0",
+                "field": "condition.threshold_bytes_per_sec",
+            })
+        );
+    }
+
+    #[test]
+    fn speed_errors_keep_the_network_message() {
+        let error = CommandError::from(SpeedError::NetworkError(NetworkError::InterfaceNotFound(
+            "eth9".into(),
+        )));
+        assert_eq!(
+            error.to_string(),
+            "network provider error: network interface not found: eth9"
+        );
+    }
+}
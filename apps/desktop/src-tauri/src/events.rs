@@ -0,0 +1,512 @@
+//! Backend → frontend event emission with per-topic batching.
+//!
+//! High-frequency updates (speed samples, trigger data, countdown ticks)
+//! are coalesced per topic within a short window so the webview only
+//! receives the latest value, while state-changing events (status
+//! changes, countdown start/cancel/execute) are always flushed
+//! immediately.
+//!
+//! [`EventBatcher`] is the pure coalescing core and has no Tauri
+//! dependency. [`BatchedEmitter`] wraps it for use from commands.
+
+use flowwatcher_engine::scheduler::SchedulerEvent;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+// ---------------------------------------------------------------------------
+// Event topics
+// ---------------------------------------------------------------------------
+
+/// Real-time download/upload speed sample.
+pub const SPEED_UPDATE: &str = "speed-update";
+/// One iteration of the backend monitoring loop.
+pub const MONITORING_TICK: &str = "monitoring-tick";
+/// Latest trigger evaluation data.
+pub const TRIGGER_DATA: &str = "trigger-data";
+/// Countdown tick with remaining seconds.
+pub const COUNTDOWN_TICK: &str = "countdown-tick";
+/// Monitoring status transition.
+pub const MONITORING_STATE_CHANGE: &str = "monitoring-state-change";
+/// Pre-warning before the countdown begins.
+pub const PRE_WARNING: &str = "pre-warning";
+/// The pre-warning needs an acknowledgment.
+pub const ACK_REQUESTED: &str = "ack-requested";
+/// The pre-warning was acknowledged.
+pub const PRE_WARNING_ACKNOWLEDGED: &str = "pre-warning-acknowledged";
+/// Countdown has started.
+pub const COUNTDOWN_STARTED: &str = "countdown-started";
+/// Pre-warning or countdown was snoozed.
+pub const COUNTDOWN_SNOOZED: &str = "countdown-snoozed";
+/// Pre-warning or countdown was paused.
+pub const COUNTDOWN_PAUSED: &str = "countdown-paused";
+/// Pre-warning or countdown was resumed.
+pub const COUNTDOWN_RESUMED: &str = "countdown-resumed";
+/// Countdown was cancelled.
+pub const COUNTDOWN_CANCELLED: &str = "countdown-cancelled";
+/// The scheduled action was executed.
+pub const ACTION_EXECUTED: &str = "action-executed";
+/// The scheduled action failed.
+pub const ACTION_FAILED: &str = "action-failed";
+/// The power guard kept the scheduled action from running.
+pub const ACTION_SKIPPED: &str = "action-skipped";
+/// The countdown was postponed because the user is active.
+pub const ACTION_POSTPONED: &str = "action-postponed";
+/// The action stayed pending too long and was cancelled.
+pub const ACTION_TIMED_OUT: &str = "action-timed-out";
+/// The monitored interface went dead: monitoring switched interfaces or
+/// idle detection was suspended (or resumed).
+pub const INTERFACE_FAILOVER: &str = "interface-failover";
+/// A new activity log entry was recorded.
+pub const LOG_ADDED: &str = "log-added";
+/// A newer application version is available.
+pub const UPDATE_AVAILABLE: &str = "update-available";
+
+/// Default coalescing window for tick-like events.
+pub const DEFAULT_BATCH_WINDOW: Duration = Duration::from_millis(100);
+
+// ---------------------------------------------------------------------------
+// Flush policy
+// ---------------------------------------------------------------------------
+
+/// How an event topic is delivered to the frontend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// Delivered as soon as it is pushed.
+    Immediate,
+    /// Only the latest payload within the batch window is delivered.
+    Coalesce,
+}
+
+/// Classify a topic. Unknown topics are treated as state-changing.
+pub fn policy_for(topic: &str) -> FlushPolicy {
+    match topic {
+        SPEED_UPDATE | MONITORING_TICK | TRIGGER_DATA | COUNTDOWN_TICK => FlushPolicy::Coalesce,
+        _ => FlushPolicy::Immediate,
+    }
+}
+
+/// Map a scheduler event to the topic it is emitted on.
+pub fn scheduler_topic(event: &SchedulerEvent) -> &'static str {
+    match event {
+        SchedulerEvent::PreWarning { .. } => PRE_WARNING,
+        SchedulerEvent::AckRequested { .. } => ACK_REQUESTED,
+        SchedulerEvent::Acknowledged => PRE_WARNING_ACKNOWLEDGED,
+        SchedulerEvent::CountdownStarted { .. } => COUNTDOWN_STARTED,
+        SchedulerEvent::CountdownTick { .. } => COUNTDOWN_TICK,
+        SchedulerEvent::Snoozed { .. } => COUNTDOWN_SNOOZED,
+        SchedulerEvent::Paused => COUNTDOWN_PAUSED,
+        SchedulerEvent::Resumed => COUNTDOWN_RESUMED,
+        SchedulerEvent::Cancelled { .. } => COUNTDOWN_CANCELLED,
+        SchedulerEvent::Executed => ACTION_EXECUTED,
+        SchedulerEvent::ExecutionFailed { .. } => ACTION_FAILED,
+        SchedulerEvent::ExecutionSkipped { .. } => ACTION_SKIPPED,
+        SchedulerEvent::Postponed { .. } => ACTION_POSTPONED,
+        SchedulerEvent::TimedOut { .. } => ACTION_TIMED_OUT,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Metrics
+// ---------------------------------------------------------------------------
+
+/// Counters describing batching effectiveness (for diagnostics).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventMetrics {
+    /// Events pushed into the batcher.
+    pub events_in: u64,
+    /// Events handed out for emission.
+    pub events_out: u64,
+    /// Events replaced by a newer payload on the same topic.
+    pub coalesced: u64,
+}
+
+// ---------------------------------------------------------------------------
+// EventBatcher
+// ---------------------------------------------------------------------------
+
+/// An event ready to be emitted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingEvent {
+    /// Event topic name.
+    pub topic: String,
+    /// Serialized payload.
+    pub payload: serde_json::Value,
+}
+
+/// Pure per-topic event coalescer.
+///
+/// Callers pass the current instant explicitly so the batching behaviour
+/// is deterministic and testable without a runtime.
+#[derive(Debug)]
+pub struct EventBatcher {
+    /// How long coalesced events may be held before flushing.
+    window: Duration,
+    /// Pending coalesced events, in order of first arrival.
+    pending: Vec<PendingEvent>,
+    /// When the oldest pending event arrived.
+    window_started: Option<Instant>,
+    /// Running counters.
+    metrics: EventMetrics,
+}
+
+impl EventBatcher {
+    /// Create a batcher with the given coalescing window.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: Vec::new(),
+            window_started: None,
+            metrics: EventMetrics::default(),
+        }
+    }
+
+    /// Push an event and return any events that should be emitted now.
+    ///
+    /// Immediate events flush all pending coalesced events first, so the
+    /// frontend never sees a stale tick after a state change.
+    pub fn push(
+        &mut self,
+        topic: impl Into<String>,
+        payload: serde_json::Value,
+        now: Instant,
+    ) -> Vec<PendingEvent> {
+        let topic = topic.into();
+        self.metrics.events_in += 1;
+
+        match policy_for(&topic) {
+            FlushPolicy::Immediate => {
+                let mut out = self.drain();
+                out.push(PendingEvent { topic, payload });
+                self.metrics.events_out += 1;
+                out
+            }
+            FlushPolicy::Coalesce => {
+                if let Some(existing) = self.pending.iter_mut().find(|e| e.topic == topic) {
+                    existing.payload = payload;
+                    self.metrics.coalesced += 1;
+                } else {
+                    self.pending.push(PendingEvent { topic, payload });
+                }
+                let started = *self.window_started.get_or_insert(now);
+                if now.duration_since(started) >= self.window {
+                    self.drain()
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+    }
+
+    /// Return pending events if the batch window has elapsed.
+    pub fn flush_due(&mut self, now: Instant) -> Vec<PendingEvent> {
+        match self.window_started {
+            Some(started) if now.duration_since(started) >= self.window => self.drain(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Current batching counters.
+    pub fn metrics(&self) -> EventMetrics {
+        self.metrics
+    }
+
+    /// Number of coalesced events waiting to be flushed (at most one per
+    /// coalesced topic).
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Take all pending events and count them as emitted.
+    fn drain(&mut self) -> Vec<PendingEvent> {
+        self.window_started = None;
+        let out = std::mem::take(&mut self.pending);
+        self.metrics.events_out += out.len() as u64;
+        out
+    }
+}
+
+impl Default for EventBatcher {
+    fn default() -> Self {
+        Self::new(DEFAULT_BATCH_WINDOW)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// BatchedEmitter (Tauri integration)
+// ---------------------------------------------------------------------------
+
+/// Thread-safe wrapper that routes events through an [`EventBatcher`].
+///
+/// Uses a `std::sync::Mutex` because the critical section never awaits.
+#[derive(Debug, Default)]
+pub struct BatchedEmitter {
+    batcher: std::sync::Mutex<EventBatcher>,
+}
+
+impl BatchedEmitter {
+    /// Queue or emit an event depending on its topic's flush policy.
+    pub fn emit<S: Serialize>(&self, app: &AppHandle, topic: &str, payload: S) {
+        let payload = match serde_json::to_value(payload) {
+            Ok(v) => v,
+            Err(e) => {
+                log::warn!("Failed to serialize {topic} event: {e}");
+                return;
+            }
+        };
+        let ready = self.lock().push(topic, payload, Instant::now());
+        send(app, ready);
+    }
+
+    /// Emit any coalesced events whose window has elapsed.
+    pub fn flush_due(&self, app: &AppHandle) {
+        let ready = self.lock().flush_due(Instant::now());
+        send(app, ready);
+    }
+
+    /// Forward drained scheduler events to their topics.
+    pub fn emit_scheduler_events(&self, app: &AppHandle, events: Vec<SchedulerEvent>) {
+        for event in events {
+            self.emit(app, scheduler_topic(&event), &event);
+        }
+    }
+
+    /// Current batching counters.
+    pub fn metrics(&self) -> EventMetrics {
+        self.lock().metrics()
+    }
+
+    /// Number of coalesced events waiting to be flushed.
+    pub fn pending_len(&self) -> usize {
+        self.lock().pending_len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, EventBatcher> {
+        // A poisoned batcher only holds transient events — keep going.
+        self.batcher.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Emit a batch of ready events to the frontend (best-effort).
+fn send(app: &AppHandle, events: Vec<PendingEvent>) {
+    for event in events {
+        let _ = app.emit(&event.topic, event.payload);
+    }
+}
+
+/// Spawn the background task that flushes coalesced events every window.
+pub fn spawn_flush_loop(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        use tauri::Manager;
+
+        let mut interval = tokio::time::interval(DEFAULT_BATCH_WINDOW);
+        loop {
+            interval.tick().await;
+            let state = app.state::<crate::state::AppState>();
+            state.events.flush_due(&app);
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_engine::CancelReason;
+    use serde_json::json;
+
+    const WINDOW: Duration = Duration::from_millis(100);
+
+    fn topics(events: &[PendingEvent]) -> Vec<&str> {
+        events.iter().map(|e| e.topic.as_str()).collect()
+    }
+
+    #[test]
+    fn policy_classifies_known_topics() {
+        assert_eq!(policy_for(SPEED_UPDATE), FlushPolicy::Coalesce);
+        assert_eq!(policy_for(MONITORING_TICK), FlushPolicy::Coalesce);
+        assert_eq!(policy_for(TRIGGER_DATA), FlushPolicy::Coalesce);
+        assert_eq!(policy_for(COUNTDOWN_TICK), FlushPolicy::Coalesce);
+        assert_eq!(policy_for(MONITORING_STATE_CHANGE), FlushPolicy::Immediate);
+        assert_eq!(policy_for(COUNTDOWN_STARTED), FlushPolicy::Immediate);
+        assert_eq!(policy_for(COUNTDOWN_CANCELLED), FlushPolicy::Immediate);
+        assert_eq!(policy_for(ACTION_EXECUTED), FlushPolicy::Immediate);
+    }
+
+    #[test]
+    fn unknown_topics_are_immediate() {
+        assert_eq!(policy_for("something-new"), FlushPolicy::Immediate);
+    }
+
+    #[test]
+    fn immediate_event_is_emitted_at_once() {
+        let mut batcher = EventBatcher::new(WINDOW);
+        let out = batcher.push(ACTION_EXECUTED, json!(null), Instant::now());
+        assert_eq!(topics(&out), vec![ACTION_EXECUTED]);
+        assert!(batcher.flush_due(Instant::now() + WINDOW).is_empty());
+    }
+
+    #[test]
+    fn tick_event_is_held_within_window() {
+        let mut batcher = EventBatcher::new(WINDOW);
+        let t0 = Instant::now();
+        let out = batcher.push(SPEED_UPDATE, json!({ "download_bps": 1 }), t0);
+        assert!(out.is_empty());
+        assert_eq!(batcher.flush_due(t0 + WINDOW).len(), 1);
+    }
+
+    #[test]
+    fn coalescing_keeps_only_latest_payload() {
+        let mut batcher = EventBatcher::new(WINDOW);
+        let t0 = Instant::now();
+        batcher.push(SPEED_UPDATE, json!(1), t0);
+        batcher.push(SPEED_UPDATE, json!(2), t0 + Duration::from_millis(10));
+        batcher.push(SPEED_UPDATE, json!(3), t0 + Duration::from_millis(20));
+        assert_eq!(batcher.pending_len(), 1);
+
+        let out = batcher.flush_due(t0 + WINDOW);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].payload, json!(3));
+        assert_eq!(batcher.pending_len(), 0);
+    }
+
+    #[test]
+    fn different_topics_are_batched_separately_in_arrival_order() {
+        let mut batcher = EventBatcher::new(WINDOW);
+        let t0 = Instant::now();
+        batcher.push(TRIGGER_DATA, json!("a"), t0);
+        batcher.push(SPEED_UPDATE, json!("b"), t0);
+        batcher.push(TRIGGER_DATA, json!("c"), t0);
+
+        let out = batcher.flush_due(t0 + WINDOW);
+        assert_eq!(topics(&out), vec![TRIGGER_DATA, SPEED_UPDATE]);
+        assert_eq!(out[0].payload, json!("c"));
+    }
+
+    #[test]
+    fn flush_due_waits_for_window() {
+        let mut batcher = EventBatcher::new(WINDOW);
+        let t0 = Instant::now();
+        batcher.push(COUNTDOWN_TICK, json!(5), t0);
+
+        assert!(batcher.flush_due(t0 + Duration::from_millis(50)).is_empty());
+        assert_eq!(batcher.flush_due(t0 + WINDOW).len(), 1);
+        assert!(batcher.flush_due(t0 + WINDOW * 2).is_empty());
+    }
+
+    #[test]
+    fn flush_due_with_nothing_pending_is_empty() {
+        let mut batcher = EventBatcher::new(WINDOW);
+        assert!(batcher.flush_due(Instant::now() + WINDOW).is_empty());
+    }
+
+    #[test]
+    fn push_after_window_elapsed_flushes_inline() {
+        let mut batcher = EventBatcher::new(WINDOW);
+        let t0 = Instant::now();
+        batcher.push(SPEED_UPDATE, json!(1), t0);
+        let out = batcher.push(SPEED_UPDATE, json!(2), t0 + WINDOW);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].payload, json!(2));
+    }
+
+    #[test]
+    fn window_restarts_after_flush() {
+        let mut batcher = EventBatcher::new(WINDOW);
+        let t0 = Instant::now();
+        batcher.push(SPEED_UPDATE, json!(1), t0);
+        batcher.flush_due(t0 + WINDOW);
+
+        let t1 = t0 + Duration::from_millis(500);
+        batcher.push(SPEED_UPDATE, json!(2), t1);
+        assert!(batcher.flush_due(t1 + Duration::from_millis(50)).is_empty());
+        assert_eq!(batcher.flush_due(t1 + WINDOW).len(), 1);
+    }
+
+    #[test]
+    fn immediate_event_flushes_pending_ticks_first() {
+        let mut batcher = EventBatcher::new(WINDOW);
+        let t0 = Instant::now();
+        batcher.push(COUNTDOWN_TICK, json!(3), t0);
+        let out = batcher.push(COUNTDOWN_CANCELLED, json!(null), t0);
+
+        assert_eq!(topics(&out), vec![COUNTDOWN_TICK, COUNTDOWN_CANCELLED]);
+        assert!(batcher.flush_due(t0 + WINDOW).is_empty());
+    }
+
+    #[test]
+    fn metrics_count_in_out_and_coalesced() {
+        let mut batcher = EventBatcher::new(WINDOW);
+        let t0 = Instant::now();
+        batcher.push(SPEED_UPDATE, json!(1), t0);
+        batcher.push(SPEED_UPDATE, json!(2), t0);
+        batcher.push(SPEED_UPDATE, json!(3), t0);
+        batcher.push(MONITORING_STATE_CHANGE, json!("Idle"), t0);
+
+        assert_eq!(
+            batcher.metrics(),
+            EventMetrics {
+                events_in: 4,
+                events_out: 2,
+                coalesced: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn scheduler_events_map_to_topics() {
+        assert_eq!(
+            scheduler_topic(&SchedulerEvent::CountdownTick {
+                remaining_seconds: 1
+            }),
+            COUNTDOWN_TICK
+        );
+        assert_eq!(
+            scheduler_topic(&SchedulerEvent::Cancelled {
+                reason: CancelReason::UserTray
+            }),
+            COUNTDOWN_CANCELLED
+        );
+        assert_eq!(scheduler_topic(&SchedulerEvent::Executed), ACTION_EXECUTED);
+        assert_eq!(
+            scheduler_topic(&SchedulerEvent::ExecutionFailed {
+                error: "boom".into()
+            }),
+            ACTION_FAILED
+        );
+        assert_eq!(
+            scheduler_topic(&SchedulerEvent::ExecutionSkipped {
+                reason: "on battery".into()
+            }),
+            ACTION_SKIPPED
+        );
+        assert_eq!(
+            scheduler_topic(&SchedulerEvent::Postponed {
+                seconds_since_input: 3
+            }),
+            ACTION_POSTPONED
+        );
+        assert_eq!(
+            scheduler_topic(&SchedulerEvent::TimedOut { pending_secs: 300 }),
+            ACTION_TIMED_OUT
+        );
+        assert_eq!(
+            scheduler_topic(&SchedulerEvent::AckRequested { timeout_secs: 60 }),
+            ACK_REQUESTED
+        );
+        assert_eq!(
+            scheduler_topic(&SchedulerEvent::Acknowledged),
+            PRE_WARNING_ACKNOWLEDGED
+        );
+    }
+
+    #[test]
+    fn zero_window_never_holds_events() {
+        let mut batcher = EventBatcher::new(Duration::ZERO);
+        let out = batcher.push(SPEED_UPDATE, json!(1), Instant::now());
+        assert_eq!(out.len(), 1);
+    }
+}
@@ -0,0 +1,182 @@
+//! Serialization of state-mutating commands.
+//!
+//! A rapid double-click can invoke `start_monitoring` twice before the
+//! first call finishes its awaits. Mutating commands therefore take a
+//! non-blocking [`OperationPermit`] and fail fast with
+//! [`CommandError::Busy`] instead of queueing behind each other.
+//!
+//! Each successful start also bumps a monotonically increasing session
+//! token. Commands that act on a running session (cancel, execute now)
+//! must echo it, so a stale window cannot act on a session it never saw.
+//! Stopping clears it, so a window that missed the stop cannot act on a
+//! later state with the old token either.
+
+use crate::error::CommandError;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+// ---------------------------------------------------------------------------
+// Guard
+// ---------------------------------------------------------------------------
+
+/// Tracks the in-flight mutating command and the current session token.
+#[derive(Debug, Default)]
+pub struct OperationGuard {
+    /// Whether a mutating command currently holds the permit.
+    in_flight: AtomicBool,
+    /// Token of the running session (0 = none).
+    session: AtomicU64,
+    /// Token handed to the most recently started session. Never reused.
+    issued: AtomicU64,
+}
+
+impl OperationGuard {
+    /// Create a guard with no operation in flight and no session.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take the permit, or fail with [`CommandError::Busy`] if it is held.
+    pub fn try_begin(&self) -> Result<OperationPermit<'_>, CommandError> {
+        self.in_flight
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .map(|_| OperationPermit { guard: self })
+            .map_err(|_| CommandError::Busy)
+    }
+
+    /// Whether a mutating command is running.
+    pub fn is_busy(&self) -> bool {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    /// Token of the current session (0 when none is running).
+    pub fn current_session(&self) -> u64 {
+        self.session.load(Ordering::Acquire)
+    }
+
+    /// Verify that `token` names the current session.
+    pub fn check_session(&self, token: u64) -> Result<(), CommandError> {
+        let active = self.current_session();
+        if token == active && active != 0 {
+            Ok(())
+        } else {
+            Err(CommandError::StaleSession {
+                active,
+                received: token,
+            })
+        }
+    }
+}
+
+/// Held by a running mutating command; releases the guard when dropped.
+#[derive(Debug)]
+pub struct OperationPermit<'a> {
+    guard: &'a OperationGuard,
+}
+
+impl OperationPermit<'_> {
+    /// Start a new session and return its token.
+    ///
+    /// Only a permit holder can do this, so tokens are handed out in the
+    /// same order sessions are started.
+    pub fn begin_session(&self) -> u64 {
+        let token = self.guard.issued.fetch_add(1, Ordering::AcqRel) + 1;
+        self.guard.session.store(token, Ordering::Release);
+        token
+    }
+
+    /// End the current session; its token is rejected from now on.
+    pub fn end_session(&self) {
+        self.guard.session.store(0, Ordering::Release);
+    }
+}
+
+impl Drop for OperationPermit<'_> {
+    fn drop(&mut self) {
+        self.guard.in_flight.store(false, Ordering::Release);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stand-in for a mutating command with an await point mid-setup.
+    async fn start(guard: &OperationGuard) -> Result<u64, CommandError> {
+        let permit = guard.try_begin()?;
+        tokio::task::yield_now().await;
+        Ok(permit.begin_session())
+    }
+
+    #[tokio::test]
+    async fn overlapping_starts_admit_exactly_one() {
+        let guard = OperationGuard::new();
+        let (a, b) = tokio::join!(start(&guard), start(&guard));
+
+        let results = [a, b];
+        let won: Vec<_> = results.iter().filter(|r| r.is_ok()).collect();
+        assert_eq!(won.len(), 1);
+        assert!(results.contains(&Err(CommandError::Busy)));
+        assert_eq!(guard.current_session(), 1);
+        assert!(!guard.is_busy());
+    }
+
+    #[tokio::test]
+    async fn permit_is_released_after_completion() {
+        let guard = OperationGuard::new();
+        assert_eq!(start(&guard).await, Ok(1));
+        assert_eq!(start(&guard).await, Ok(2));
+    }
+
+    #[test]
+    fn permit_is_released_on_drop() {
+        let guard = OperationGuard::new();
+        let permit = guard.try_begin().unwrap();
+        assert!(guard.is_busy());
+        assert_eq!(guard.try_begin().unwrap_err(), CommandError::Busy);
+        drop(permit);
+        assert!(guard.try_begin().is_ok());
+    }
+
+    #[test]
+    fn stale_tokens_are_rejected() {
+        let guard = OperationGuard::new();
+        assert!(guard.check_session(0).is_err());
+
+        let first = guard.try_begin().unwrap().begin_session();
+        assert_eq!(guard.check_session(first), Ok(()));
+
+        let second = guard.try_begin().unwrap().begin_session();
+        assert_eq!(
+            guard.check_session(first),
+            Err(CommandError::StaleSession {
+                active: second,
+                received: first,
+            })
+        );
+        assert_eq!(guard.check_session(second), Ok(()));
+    }
+
+    #[test]
+    fn ending_a_session_invalidates_its_token() {
+        let guard = OperationGuard::new();
+        let first = guard.try_begin().unwrap().begin_session();
+        guard.try_begin().unwrap().end_session();
+        assert_eq!(guard.current_session(), 0);
+        assert_eq!(
+            guard.check_session(first),
+            Err(CommandError::StaleSession {
+                active: 0,
+                received: first,
+            })
+        );
+
+        // Tokens are not handed out again after a stop.
+        let second = guard.try_begin().unwrap().begin_session();
+        assert_eq!(second, first + 1);
+        assert!(guard.check_session(first).is_err());
+    }
+}
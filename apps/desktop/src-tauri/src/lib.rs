@@ -1,13 +1,33 @@
+mod api;
+mod audio;
 mod commands;
+mod config_export;
+mod countdown_alerts;
+mod error;
+mod events;
+mod guard;
+mod monitor_loop;
+mod notifier;
+mod profiles;
+mod settings;
+mod shutdown;
+mod speed_feed;
 mod state;
 mod tray;
+mod updater;
 
+use flowwatcher_actions::notify::NOTIFY_ACTION_ID;
+use flowwatcher_actions::{Notifier, NotifyAction};
+use flowwatcher_engine::{persist, ActivityLogger, LogEntry, LogStatus, Recovery};
+use notifier::TauriNotifier;
 use state::AppState;
+use std::sync::Arc;
 use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        .plugin(tauri_plugin_notification::init())
         .manage(AppState::new())
         .setup(|app| {
             if cfg!(debug_assertions) {
@@ -21,18 +41,131 @@ pub fn run() {
             // Set up system tray icon with context menu.
             tray::setup_tray(app)?;
 
-            // Load persisted activity logs from file.
+            // Flush coalesced high-frequency events on a fixed cadence.
+            events::spawn_flush_loop(app.handle().clone());
+
+            // Daily update check, only if the user opted in.
+            updater::spawn_background_check(app.handle().clone());
+
+            // Notify action: shows a desktop notification, and stands in
+            // for the real action during dry runs.
             {
                 let state = app.state::<AppState>();
-                if let Ok(dir) = app.path().app_data_dir() {
-                    let log_path = dir.join("activity_logs.json");
-                    if let Ok(mut logger) =
-                        flowwatcher_engine::ActivityLogger::load_from_file(&log_path)
+                let notifier: Arc<dyn Notifier> =
+                    Arc::new(TauriNotifier::new(app.handle().clone()));
+                let vars = state.template_vars.clone();
+                state
+                    .actions
+                    .blocking_lock()
+                    .register(NOTIFY_ACTION_ID, move |params| {
+                        Ok(Box::new(
+                            NotifyAction::from_config(params, Arc::clone(&notifier))?
+                                .with_vars(vars.clone()),
+                        ))
+                    });
+            }
+
+            // Preferences the backend keeps in state, from settings.json.
+            if let Ok(dir) = app.path().app_data_dir() {
+                let settings = settings::init_state(&app.state::<AppState>(), &dir);
+                if settings.keep_screen_on {
+                    commands::apply_keep_screen_on(true);
+                }
+                // Serve the HTTP API if it was left on.
+                if settings.http_api.enabled {
+                    let app = app.handle().clone();
+                    let http_api = settings.http_api.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = api::apply(&app, &http_api).await {
+                            log::warn!("Failed to start the HTTP API: {e}");
+                        }
+                    });
+                }
+                // Re-register so the login item follows the app if it moved.
+                if settings.auto_start {
+                    if let Err(e) = commands::launch_at_login(app.handle())
+                        .and_then(|login| login.set_enabled(true))
                     {
-                        // Enforce 30-day retention on load.
-                        logger.prune_older_than(30);
-                        *state.activity_logger.blocking_lock() = logger;
+                        log::warn!("Failed to refresh the login item: {e}");
+                    }
+                }
+            }
+
+            // Register external action plugins from <app data>/plugins.
+            {
+                let state = app.state::<AppState>();
+                if let Ok(dir) = app.path().app_data_dir() {
+                    let discovery = flowwatcher_actions::discover_plugins(&dir.join("plugins"));
+                    let mut actions = state.actions.blocking_lock();
+                    let skipped = flowwatcher_actions::register_plugins(
+                        &mut actions,
+                        discovery.plugins,
+                        flowwatcher_actions::external::DEFAULT_TIMEOUT,
+                    );
+                    for warning in discovery.warnings.iter().chain(&skipped) {
+                        log::warn!("{warning}");
+                    }
+                }
+            }
+
+            // Load persisted activity logs from file, migrating the legacy
+            // JSON log to the appended NDJSON one. An unreadable log falls
+            // back to its backup (or starts empty), noted in the log itself,
+            // and is kept as `<name>.corrupt` before the log is rewritten.
+            {
+                let state = app.state::<AppState>();
+                if let Ok(dir) = app.path().app_data_dir() {
+                    let log_path = dir.join(commands::LOG_FILE);
+                    let legacy_path = dir.join(commands::LEGACY_LOG_FILE);
+                    let source = if log_path.exists() {
+                        &log_path
+                    } else {
+                        &legacy_path
+                    };
+                    let loaded = persist::load_with_backup(source, ActivityLogger::load_from_file);
+                    let mut logger = loaded.value.unwrap_or_else(ActivityLogger::new);
+                    let mut rewrite = true;
+                    if let Some(recovery) = loaded.recovery {
+                        let details = match recovery {
+                            Recovery::Backup => {
+                                "The activity log was unreadable; restored the last backup"
+                            }
+                            Recovery::Defaults => {
+                                "The activity log and its backup were unreadable; started a new log"
+                            }
+                        };
+                        let details = match persist::set_aside(source, recovery) {
+                            Ok(moved) => match moved.first() {
+                                Some(kept) => format!(
+                                    "{details}; the unreadable file was kept as {}",
+                                    kept.display()
+                                ),
+                                None => details.to_string(),
+                            },
+                            Err(e) => {
+                                log::warn!("Not rewriting the unreadable activity log: {e}");
+                                rewrite = false;
+                                details.to_string()
+                            }
+                        };
+                        logger.add_entry(LogEntry::now(
+                            "Activity log",
+                            "Recovery",
+                            LogStatus::Info,
+                            Some(details),
+                        ));
                     }
+                    // Enforce 30-day retention on load and compact.
+                    logger.prune_older_than(30);
+                    if rewrite {
+                        match logger.compact_file(&log_path) {
+                            Ok(()) => {
+                                let _ = std::fs::remove_file(&legacy_path);
+                            }
+                            Err(e) => log::warn!("Failed to compact the activity log: {e}"),
+                        }
+                    }
+                    *state.activity_logger.blocking_lock() = logger;
                 }
             }
 
@@ -53,25 +186,51 @@ pub fn run() {
                     let _ = window.hide();
                 }
             }
+            // Closing the main window for good ends the app.
+            if let tauri::WindowEvent::Destroyed = event {
+                let state = window.app_handle().state::<AppState>();
+                tauri::async_runtime::block_on(speed_feed::unsubscribe(&state, window.label()));
+                if window.label() == "main" {
+                    shutdown::shutdown(window.app_handle());
+                }
+            }
         })
         .invoke_handler(tauri::generate_handler![
             commands::get_network_interfaces,
             commands::get_current_speed,
+            commands::get_raw_counters,
+            commands::subscribe_speed_updates,
+            commands::unsubscribe_speed_updates,
+            commands::get_speed_history,
             commands::start_monitoring,
             commands::stop_monitoring,
             commands::pause_monitoring,
             commands::resume_monitoring,
             commands::get_monitoring_status,
             commands::cancel_action,
+            commands::acknowledge_pre_warning,
+            commands::snooze_action,
             commands::execute_action_now,
+            commands::calibrate_idle_baseline,
+            commands::apply_calibration,
+            commands::suggest_threshold_for_interface,
             commands::get_running_processes,
+            commands::get_process_activity_history,
             commands::get_available_triggers,
             commands::get_available_actions,
             commands::trigger_countdown,
+            commands::set_audio_settings,
+            commands::play_alert_for_event,
+            commands::preview_alert_sound,
+            commands::execute_action_elevated,
             commands::get_activity_logs,
             commands::add_activity_log,
+            commands::query_activity_logs,
+            commands::get_activity_sessions,
+            commands::get_activity_stats,
             commands::clear_activity_logs,
             commands::export_activity_logs,
+            commands::export_activity_logs_to_file,
             commands::get_settings,
             commands::save_settings,
             commands::reset_settings,
@@ -79,8 +238,21 @@ pub fn run() {
             commands::get_close_to_tray,
             commands::set_keep_screen_on,
             commands::get_keep_screen_on,
+            commands::set_launch_at_login,
+            commands::get_launch_at_login,
             commands::export_config,
             commands::import_config,
+            commands::save_profile,
+            commands::list_profiles,
+            commands::load_profile,
+            commands::delete_profile,
+            commands::get_event_metrics,
+            commands::get_diagnostics,
+            commands::get_memory_stats,
+            commands::get_scheduler_debug,
+            commands::check_for_updates,
+            commands::start_recording,
+            commands::stop_recording,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
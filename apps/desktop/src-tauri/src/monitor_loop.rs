@@ -0,0 +1,675 @@
+//! Backend-driven monitoring loop.
+//!
+//! `start_monitoring` spawns [`spawn`], which polls the speed monitor once
+//! per second, feeds the reading to the threshold condition, drives the
+//! action scheduler, and runs the action when the countdown ends. The
+//! frontend only renders the events it emits, so monitoring keeps going
+//! while the webview is suspended or hidden in the tray.
+//!
+//! Network triggers are evaluated against the speed reading, process
+//! triggers against the watched processes' activity and CPU triggers
+//! against CPU usage; the speed reading is still polled for display. A
+//! running pre-warning or countdown is cancelled on its own when traffic
+//! resumes, when the interface watchdog suspends monitoring or switches
+//! interfaces, or after the system slept.
+
+use crate::commands::{self, SpeedData};
+use crate::events;
+use crate::speed_feed;
+use crate::state::{AppState, MonitoringStatus, TriggerConfig};
+use crate::tray;
+use flowwatcher_actions::{StepOutcome, StepResult};
+use flowwatcher_conditions::{ConditionResult, Zone};
+use flowwatcher_engine::scheduler::{SchedulerError, SchedulerEvent, SchedulerState};
+use flowwatcher_engine::speed::SpeedReading;
+use flowwatcher_engine::{
+    auto_cancel_reason, CancelReason, Confidence, LogEntry, LogStatus, SessionOutcome,
+};
+use flowwatcher_triggers::{Trigger, TriggerData, TriggerState};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager};
+
+/// Time between loop iterations.
+pub const MONITOR_INTERVAL: Duration = Duration::from_secs(1);
+
+// ---------------------------------------------------------------------------
+// Pausable clock
+// ---------------------------------------------------------------------------
+
+/// A monotonic clock that stands still while monitoring is paused, so the
+/// condition's duration timer neither gains nor loses the paused time.
+#[derive(Debug, Clone)]
+pub struct PausableClock {
+    /// Clock time at creation.
+    origin: Instant,
+    /// Unpaused time since creation.
+    active: Duration,
+    /// Real time of the last `advance`, or `None` after a pause.
+    last: Option<Instant>,
+    /// Real time between the last two advances (zero after a pause).
+    gap: Duration,
+}
+
+impl PausableClock {
+    /// Start the clock at real time `now`.
+    pub fn new(now: Instant) -> Self {
+        Self {
+            origin: now,
+            active: Duration::ZERO,
+            last: Some(now),
+            gap: Duration::ZERO,
+        }
+    }
+
+    /// Add the real time since the last advance and return the clock time.
+    pub fn advance(&mut self, now: Instant) -> Instant {
+        self.gap = self
+            .last
+            .map_or(Duration::ZERO, |last| now.saturating_duration_since(last));
+        self.active += self.gap;
+        self.last = Some(now);
+        self.origin + self.active
+    }
+
+    /// Real time added by the last `advance`. Much more than
+    /// [`MONITOR_INTERVAL`] means the system slept in between.
+    pub fn last_gap(&self) -> Duration {
+        self.gap
+    }
+
+    /// Stop the clock until the next `advance`.
+    pub fn pause(&mut self) {
+        self.last = None;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Loop
+// ---------------------------------------------------------------------------
+
+/// Payload for the `monitoring-tick` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitoringTick {
+    /// The latest speed reading.
+    pub speed: SpeedData,
+    /// The condition result, while waiting for the trigger.
+    pub condition: Option<ConditionResult>,
+    /// Where the monitored value sits relative to the threshold, as the
+    /// condition classified it this iteration (hysteresis included).
+    pub zone: Option<Zone>,
+    /// When a steadily declining transfer is expected to go idle, in
+    /// milliseconds since the Unix epoch, if known.
+    pub estimated_idle_at_ms: Option<u64>,
+    /// How reliable `estimated_idle_at_ms` is.
+    pub estimate_confidence: Option<Confidence>,
+    /// The scheduler state after this iteration.
+    pub scheduler: SchedulerState,
+    /// Seconds left in the pre-warning or countdown, if one is running.
+    pub remaining_secs: Option<u64>,
+}
+
+/// Spawn the monitoring loop. It ends on its own once the session is
+/// stopped or the action has executed; `stop_monitoring` aborts it.
+pub fn spawn(app: AppHandle) -> JoinHandle<()> {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(MONITOR_INTERVAL);
+        let mut clock = PausableClock::new(Instant::now());
+        loop {
+            interval.tick().await;
+            let state = app.state::<AppState>();
+            if !step(&app, &state, &mut clock).await {
+                *state.last_speed.lock().await = None;
+                break;
+            }
+        }
+    })
+}
+
+/// One iteration. Returns `false` when the loop should end.
+async fn step(app: &AppHandle, state: &AppState, clock: &mut PausableClock) -> bool {
+    match *state.status.lock().await {
+        MonitoringStatus::Paused => {
+            clock.pause();
+            return true;
+        }
+        MonitoringStatus::Idle | MonitoringStatus::Executed => return false,
+        _ => {}
+    }
+    let now = clock.advance(Instant::now());
+
+    let (speed, reading) = match commands::poll_speed(app, state).await {
+        Ok(polled) => polled,
+        Err(e) => {
+            log::warn!("Monitoring poll failed: {e}");
+            return true;
+        }
+    };
+    *state.last_speed.lock().await = Some(speed.clone());
+    speed_feed::publish(app, state, speed.clone()).await;
+    let mut estimate = None;
+    if let Some(reading) = &reading {
+        state
+            .template_vars
+            .set("download_bps", reading.download_bps);
+        state.template_vars.set("upload_bps", reading.upload_bps);
+        estimate = state.observe_speed(reading, Instant::now()).await;
+    }
+
+    let scheduled = state.scheduler.lock().await.state();
+    let evaluation = match scheduled {
+        SchedulerState::Idle | SchedulerState::Cancelled | SchedulerState::Failed => {
+            gated_condition(app, state, reading.as_ref(), now).await
+        }
+        SchedulerState::Pending | SchedulerState::Countdown => {
+            let (reason, evaluation) =
+                auto_cancel(state, reading.as_ref(), now, clock.last_gap()).await;
+            if let Some(reason) = reason {
+                cancel_automatically(app, state, reason).await;
+            }
+            evaluation
+        }
+        _ => None,
+    };
+    // A process trigger evaluated above keeps what it listed; record that
+    // rather than listing processes again.
+    if state.sample_process_activity(commands::unix_millis()).await {
+        commands::emit_trigger_data(app, state).await;
+    }
+    let zone = match (&evaluation, state.condition.lock().await.as_ref()) {
+        (Some(_), Some(condition)) => condition.zone(),
+        _ => None,
+    };
+    // The tick reports the result only while waiting for the trigger.
+    let condition = match scheduled {
+        SchedulerState::Pending | SchedulerState::Countdown => None,
+        _ => evaluation
+            .as_ref()
+            .map(|evaluation| evaluation.result.clone()),
+    };
+
+    let mut scheduler = state.scheduler.lock().await;
+    let mut due = false;
+    if condition == Some(ConditionResult::Met) {
+        if let Err(e) = scheduler.schedule() {
+            log::warn!("Failed to schedule action: {e}");
+        }
+        // The condition keeps its state through the countdown, so resumed
+        // traffic only cancels once past the disarm threshold and spike
+        // budget; every way out of the countdown resets it.
+        if let Some(condition) = state.condition.lock().await.as_ref() {
+            // Kept for the log entry and as `{met_bps}` in notify templates.
+            if let Some(bps) = condition.value() {
+                state.template_vars.set("met_bps", bps);
+            }
+        }
+    } else if matches!(
+        scheduler.state(),
+        SchedulerState::Pending | SchedulerState::Countdown
+    ) {
+        match scheduler.tick() {
+            Ok(ready) => due = ready,
+            Err(e) => log::warn!("Scheduler tick failed: {e}"),
+        }
+    }
+    let scheduler_state = scheduler.state();
+    let remaining_secs = scheduler.remaining_secs();
+    let status = MonitoringStatus::for_scheduler(&scheduler);
+    let scheduler_events = scheduler.take_events();
+    drop(scheduler);
+
+    let timed_out = scheduler_events
+        .iter()
+        .any(|event| matches!(event, SchedulerEvent::TimedOut { .. }));
+    commands::dispatch_scheduler_events(app, state, scheduler_events).await;
+    if timed_out {
+        time_out(app, state).await;
+    } else if let Some(status) = status {
+        sync_status(app, state, status).await;
+    }
+    // The tray follows every second: live speed and countdown seconds.
+    let current = state.status.lock().await.clone();
+    tray::update_tray_status(app, &current);
+    state.events.emit(
+        app,
+        events::MONITORING_TICK,
+        MonitoringTick {
+            speed,
+            condition,
+            zone,
+            estimated_idle_at_ms: estimate
+                .map(|e| commands::unix_millis().saturating_add(e.remaining.as_millis() as u64)),
+            estimate_confidence: estimate.map(|e| e.confidence),
+            scheduler: scheduler_state,
+            remaining_secs,
+        },
+    );
+
+    let (trigger, result) = match evaluation {
+        Some(Evaluation { trigger, result }) => (Some(trigger), Some(result)),
+        None => (None, None),
+    };
+    if due {
+        let run = run_action(app, state).await;
+        let scheduler_state = state.scheduler.lock().await.state();
+        commands::record_step(
+            state,
+            now,
+            trigger.as_ref(),
+            result,
+            scheduler_state,
+            run.error.is_none(),
+        )
+        .await;
+        return run.keep_monitoring;
+    }
+    commands::record_step(state, now, trigger.as_ref(), result, scheduler_state, false).await;
+    true
+}
+
+/// What the loop evaluated in one step.
+struct Evaluation {
+    /// The trigger output the condition was fed.
+    trigger: TriggerState,
+    /// The condition result.
+    result: ConditionResult,
+}
+
+/// What running the action did.
+pub(crate) struct ActionRun {
+    /// Whether the loop keeps monitoring (see [`run_action`]).
+    pub keep_monitoring: bool,
+    /// Why the action did not run or failed; `None` if it succeeded.
+    pub error: Option<SchedulerError>,
+}
+
+/// Evaluate the condition unless the re-arm gate holds it back (during a
+/// repeat cooldown, or until traffic resumes after a cancel), logging each
+/// re-arm.
+async fn gated_condition(
+    app: &AppHandle,
+    state: &AppState,
+    reading: Option<&SpeedReading>,
+    now: Instant,
+) -> Option<Evaluation> {
+    let (cooled_down, cooling) = {
+        let mut gate = state.rearm.lock().await;
+        (gate.poll(now), gate.is_cooling_down())
+    };
+    if cooled_down.is_some() {
+        if let Some(condition) = state.condition.lock().await.as_mut() {
+            condition.reset();
+        }
+    }
+    let mut result = None;
+    let mut resumed = None;
+    if !cooling {
+        if let Some(evaluated) = evaluate_condition(state, reading, now, true).await {
+            let (filtered, rearm) = state.rearm.lock().await.filter(evaluated.result);
+            result = Some(Evaluation {
+                trigger: evaluated.trigger,
+                result: filtered,
+            });
+            resumed = rearm;
+        }
+    }
+    for rearm in cooled_down.into_iter().chain(resumed) {
+        log::info!("{rearm}");
+        let (trigger, action) = match state.config.lock().await.as_ref() {
+            Some(c) => (c.trigger_type.label(), c.action_type.to_string()),
+            None => ("Unknown trigger", "Unknown action".to_string()),
+        };
+        let entry = LogEntry::now(trigger, action, LogStatus::Info, Some(rearm.to_string()));
+        commands::record_log(app, state, entry).await;
+    }
+    result
+}
+
+/// Why a running pre-warning or countdown should cancel on its own: the
+/// interface watchdog suspended monitoring
+/// ([`CancelReason::InterfaceFailover`]), the loop slept, or traffic
+/// resumed (see [`auto_cancel_reason`]). A busy watched process counts as resumed
+/// traffic, as it resets the timer while waiting. Also returns what was
+/// evaluated, for the session capture.
+async fn auto_cancel(
+    state: &AppState,
+    reading: Option<&SpeedReading>,
+    now: Instant,
+    gap: Duration,
+) -> (Option<CancelReason>, Option<Evaluation>) {
+    let suspended = state
+        .interface_watchdog
+        .lock()
+        .await
+        .as_ref()
+        .is_some_and(|watchdog| watchdog.is_suspended());
+    if suspended {
+        return (Some(CancelReason::InterfaceFailover), None);
+    }
+    let evaluation = evaluate_condition(state, reading, now, false).await;
+    let reason = auto_cancel_reason(gap, evaluation.as_ref().map(|e| &e.result));
+    (reason, evaluation)
+}
+
+/// Cancel the pending action for `reason` like a user cancel would (log
+/// entry, status, cancel hook), unless a command holds the operation
+/// permit; the next iteration checks again then.
+pub(crate) async fn cancel_automatically(app: &AppHandle, state: &AppState, reason: CancelReason) {
+    let Ok(_permit) = state.operations.try_begin() else {
+        return;
+    };
+    log::info!("Cancelling the pending action: {reason}");
+    if let Err(e) = commands::cancel_pending(app, state, reason).await {
+        log::warn!("Failed to cancel the pending action: {e}");
+    }
+}
+
+/// Evaluate the threshold condition for the session's trigger.
+///
+/// Process and CPU triggers are evaluated directly; network sessions use
+/// the speed reading. A process trigger that reports `Idle` (a watched
+/// process is busy) resets the condition and reads as `Waiting`, while a
+/// CPU trigger's `Idle` (no reading yet) evaluates nothing. `Triggered`
+/// counts as met. With `snapshot`, the evaluated data is kept for log entries (see
+/// [`commands::with_trigger_snapshot`]); the countdown passes `false` so
+/// the data that met the condition stays.
+async fn evaluate_condition(
+    state: &AppState,
+    reading: Option<&SpeedReading>,
+    now: Instant,
+    snapshot: bool,
+) -> Option<Evaluation> {
+    let data = match state.session_trigger.lock().await.as_mut() {
+        Some(trigger) => match trigger.evaluate().await {
+            Ok(TriggerState::Active(data)) => data,
+            Ok(TriggerState::Triggered) => {
+                return Some(Evaluation {
+                    trigger: TriggerState::Triggered,
+                    result: ConditionResult::Met,
+                });
+            }
+            Ok(TriggerState::Idle) if trigger.trigger_type() == "process_idle" => {
+                if let Some(condition) = state.condition.lock().await.as_mut() {
+                    condition.reset();
+                }
+                return Some(Evaluation {
+                    trigger: TriggerState::Idle,
+                    result: ConditionResult::Waiting,
+                });
+            }
+            Ok(TriggerState::Idle) => return None,
+            Err(e) => {
+                log::warn!("Trigger evaluation failed: {e}");
+                return None;
+            }
+        },
+        None => {
+            let suspended = state
+                .interface_watchdog
+                .lock()
+                .await
+                .as_ref()
+                .is_some_and(|watchdog| watchdog.is_suspended());
+            if suspended {
+                // The monitored interface is inactive, not idle.
+                if let Some(condition) = state.condition.lock().await.as_mut() {
+                    condition.reset();
+                }
+                return None;
+            }
+            let network = matches!(
+                state.config.lock().await.as_ref().map(|c| &c.trigger_type),
+                Some(TriggerConfig::NetworkIdle { .. })
+            );
+            commands::speed_trigger_data(reading.filter(|_| network)?)
+        }
+    };
+    if snapshot {
+        *state.last_trigger_data.lock().await = Some(data.clone());
+    }
+    let mut condition = state.condition.lock().await;
+    match condition.as_mut()?.evaluate_at(&data, now) {
+        Ok(result) => Some(Evaluation {
+            trigger: TriggerState::Active(data),
+            result,
+        }),
+        Err(e) => {
+            log::warn!("Condition evaluation failed: {e}");
+            None
+        }
+    }
+}
+
+/// Return to monitoring after the pending timeout cancelled the action,
+/// logging the cancel.
+async fn time_out(app: &AppHandle, state: &AppState) {
+    let (trigger, action) = match state.config.lock().await.as_ref() {
+        Some(c) => (c.trigger_type.label(), c.action_type.to_string()),
+        None => ("Unknown trigger", "Unknown action".to_string()),
+    };
+    log::info!("Action `{action}` timed out while pending; monitoring continues");
+    if let Some(condition) = state.condition.lock().await.as_mut() {
+        condition.reset();
+    }
+    commands::set_status(app, state, MonitoringStatus::Monitoring).await;
+    let entry = LogEntry::cancelled(trigger, action, CancelReason::PendingTimeout);
+    let entry = commands::with_trigger_snapshot(state, entry).await;
+    commands::record_log(app, state, entry).await;
+}
+
+/// Set the status, notifying the frontend only when its kind changes
+/// (not on every countdown second).
+async fn sync_status(app: &AppHandle, state: &AppState, status: MonitoringStatus) {
+    let mut current = state.status.lock().await;
+    if std::mem::discriminant(&*current) == std::mem::discriminant(&status) {
+        *current = status;
+        return;
+    }
+    drop(current);
+    commands::set_status(app, state, status).await;
+}
+
+/// Run the scheduler's injected action and record the outcome.
+///
+/// Monitoring stops once the action has executed, unless the repeat mode
+/// resumes monitoring after a cooldown. A failed action is logged and
+/// monitoring continues, so the returning condition can schedule it
+/// again. The same goes for an action the power guard skipped, which is
+/// logged as info.
+///
+/// A sequence gets one log entry per step instead of one overall. Each
+/// retried attempt of a failing action is logged as info before the
+/// outcome, which records how long the action ran.
+///
+/// The scheduler lock is released while the action runs, so a retrying
+/// action's backoff does not block the tray, the API or a stop.
+pub(crate) async fn run_action(app: &AppHandle, state: &AppState) -> ActionRun {
+    let begun = state.scheduler.lock().await.begin_pending_action();
+    let result = match begun {
+        Ok(mut run) => {
+            run.run().await;
+            state.scheduler.lock().await.finish_pending_action(run)
+        }
+        Err(e) => Err(e),
+    };
+    let mut scheduler = state.scheduler.lock().await;
+    let duration = scheduler.last_run_duration();
+    let scheduler_events = scheduler.take_events();
+    drop(scheduler);
+    commands::dispatch_scheduler_events(app, state, scheduler_events).await;
+    let steps = state.step_log.take();
+    let attempts = state.attempt_log.take();
+
+    let (trigger, action, dry_run, cooldown) = match state.config.lock().await.as_ref() {
+        Some(c) => (
+            c.trigger_type.label(),
+            c.action_type.to_string(),
+            c.dry_run,
+            c.repeat_mode.cooldown(),
+        ),
+        None => ("Unknown trigger", "Unknown action".to_string(), false, None),
+    };
+    // Entries name the action as `ActionInfo::name` reported it at start,
+    // also on a dry run, where the scheduler runs a notification instead.
+    let action = state.template_vars.get("action").unwrap_or(action);
+    let metadata = state
+        .last_trigger_data
+        .lock()
+        .await
+        .as_ref()
+        .map(TriggerData::to_json);
+    let timed = |mut entry: LogEntry| {
+        entry.metadata = metadata.clone();
+        match duration.filter(|_| !dry_run) {
+            Some(duration) => entry.with_duration(duration),
+            None => entry,
+        }
+    };
+    // The last failed attempt is reported by the error entry below.
+    for attempt in attempts.iter().filter(|a| a.retry_in_ms.is_some()) {
+        log::warn!("Action `{action}`: {attempt}");
+        let entry = LogEntry::now(
+            trigger,
+            action.clone(),
+            LogStatus::Info,
+            Some(attempt.to_string()),
+        );
+        commands::record_log(app, state, entry).await;
+    }
+    let keep_monitoring = match &result {
+        Ok(()) => {
+            if cooldown.is_none() {
+                commands::set_status(app, state, MonitoringStatus::Executed).await;
+            }
+            let dry_run = dry_run.then(|| {
+                log::info!("Dry run: would have executed {action}");
+                format!("Dry run: would have executed {action}")
+            });
+            let met = state
+                .template_vars
+                .get("met_bps")
+                .map(|bps| format!("Condition met at {bps} B/s"));
+            let details = match (dry_run, met) {
+                (Some(dry_run), Some(met)) => Some(format!("{dry_run}; {met}")),
+                (dry_run, met) => dry_run.or(met),
+            };
+            if steps.is_empty() {
+                let entry = timed(LogEntry::now(trigger, action, LogStatus::Executed, details));
+                commands::record_log(app, state, entry).await;
+            }
+            record_steps(app, state, trigger, steps).await;
+            if let Some(cooldown) = cooldown {
+                // Repeat mode: keep monitoring once the cooldown is over.
+                state.scheduler.lock().await.reset();
+                if let Some(condition) = state.condition.lock().await.as_mut() {
+                    condition.reset();
+                }
+                state.rearm.lock().await.start_cooldown(cooldown);
+                commands::set_status(app, state, MonitoringStatus::Monitoring).await;
+                true
+            } else {
+                commands::end_log_session(app, state, SessionOutcome::Executed).await;
+                false
+            }
+        }
+        Err(SchedulerError::ExecutionSkipped(reason)) => {
+            log::info!("Action `{action}` skipped: {reason}");
+            if let Some(condition) = state.condition.lock().await.as_mut() {
+                condition.reset();
+            }
+            commands::set_status(app, state, MonitoringStatus::Monitoring).await;
+            let entry = LogEntry::skipped(trigger, action, reason.clone());
+            commands::record_log(app, state, entry).await;
+            true
+        }
+        Err(e) => {
+            log::warn!("Action `{action}` failed: {e}");
+            if let Some(condition) = state.condition.lock().await.as_mut() {
+                condition.reset();
+            }
+            commands::set_status(app, state, MonitoringStatus::Monitoring).await;
+            if steps.is_empty() {
+                let entry = timed(LogEntry::now(
+                    trigger,
+                    action,
+                    LogStatus::Error,
+                    Some(e.to_string()),
+                ));
+                commands::record_log(app, state, entry).await;
+            }
+            record_steps(app, state, trigger, steps).await;
+            true
+        }
+    };
+    ActionRun {
+        keep_monitoring,
+        error: result.err(),
+    }
+}
+
+/// Record one activity log entry per step of a sequence run.
+async fn record_steps(app: &AppHandle, state: &AppState, trigger: &str, steps: Vec<StepResult>) {
+    for step in steps {
+        let position = format!("Step {} of {}", step.step, step.total);
+        let (status, details) = match step.outcome {
+            StepOutcome::Succeeded { details } => (
+                LogStatus::Executed,
+                Some(match details {
+                    Some(details) => format!("{position}: {details}"),
+                    None => position,
+                }),
+            ),
+            StepOutcome::Failed { error } => {
+                (LogStatus::Error, Some(format!("{position}: {error}")))
+            }
+            StepOutcome::Skipped => (
+                LogStatus::Info,
+                Some(format!("{position}: skipped after an earlier step failed")),
+            ),
+        };
+        let entry = LogEntry::now(trigger, step.action_type, status, details);
+        commands::record_log(app, state, entry).await;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_follows_real_time_while_running() {
+        let start = Instant::now();
+        let mut clock = PausableClock::new(start);
+        assert_eq!(
+            clock.advance(start + Duration::from_secs(3)),
+            start + Duration::from_secs(3)
+        );
+        assert_eq!(
+            clock.advance(start + Duration::from_secs(5)),
+            start + Duration::from_secs(5)
+        );
+        assert_eq!(clock.last_gap(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn clock_skips_paused_time() {
+        let start = Instant::now();
+        let mut clock = PausableClock::new(start);
+        clock.advance(start + Duration::from_secs(10));
+        clock.pause();
+
+        // Ten minutes paused: the first advance afterwards adds nothing.
+        let resumed = start + Duration::from_secs(610);
+        assert_eq!(clock.advance(resumed), start + Duration::from_secs(10));
+        assert_eq!(clock.last_gap(), Duration::ZERO);
+        assert_eq!(
+            clock.advance(resumed + Duration::from_secs(1)),
+            start + Duration::from_secs(11)
+        );
+    }
+}
@@ -0,0 +1,33 @@
+//! Desktop notifications for the notify action.
+//!
+//! Implements the core [`Notifier`] trait on top of the Tauri notification
+//! plugin, so the action itself stays UI-free.
+
+use flowwatcher_actions::{ActionError, Notifier};
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+/// Shows notifications through `tauri-plugin-notification`.
+pub struct TauriNotifier {
+    /// Handle used to reach the notification plugin.
+    app: AppHandle,
+}
+
+impl TauriNotifier {
+    /// Create a notifier for the given app.
+    pub fn new(app: AppHandle) -> Self {
+        Self { app }
+    }
+}
+
+impl Notifier for TauriNotifier {
+    fn notify(&self, title: &str, body: &str) -> Result<(), ActionError> {
+        self.app
+            .notification()
+            .builder()
+            .title(title)
+            .body(body)
+            .show()
+            .map_err(|e| ActionError::OsError(format!("failed to show notification: {e}")))
+    }
+}
@@ -0,0 +1,233 @@
+//! Named monitoring configurations.
+//!
+//! A profile stores a full [`MonitoringConfig`] under a name so a setup
+//! such as "Big download overnight → Shutdown" can be started again
+//! without re-entering thresholds. Profiles are kept in [`PROFILES_FILE`]
+//! in the app data directory; names are unique ignoring case and
+//! surrounding whitespace, and at most [`MAX_PROFILES`] are kept.
+
+use crate::state::MonitoringConfig;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Profiles file, in the app data directory.
+pub const PROFILES_FILE: &str = "profiles.json";
+
+/// Maximum number of saved profiles.
+pub const MAX_PROFILES: usize = 50;
+
+/// Maximum profile name length, in characters.
+pub const MAX_NAME_CHARS: usize = 64;
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+/// Errors from managing profiles.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ProfileError {
+    /// The name is empty or only whitespace.
+    #[error("profile name must not be empty")]
+    EmptyName,
+
+    /// The name exceeds [`MAX_NAME_CHARS`].
+    #[error("profile name is too long (limit is {MAX_NAME_CHARS} characters)")]
+    NameTooLong,
+
+    /// A profile with this name exists and overwriting was not requested.
+    #[error("a profile named \"{0}\" already exists")]
+    Exists(String),
+
+    /// [`MAX_PROFILES`] profiles are already saved.
+    #[error("at most {MAX_PROFILES} profiles can be saved")]
+    Full,
+
+    /// No profile has this name.
+    #[error("no profile named \"{0}\"")]
+    NotFound(String),
+
+    /// The profiles file could not be read or written.
+    #[error("failed to access profiles: {0}")]
+    Io(String),
+}
+
+// ---------------------------------------------------------------------------
+// Profiles
+// ---------------------------------------------------------------------------
+
+/// A named monitoring configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    /// Display name, unique ignoring case.
+    pub name: String,
+    /// The configuration started by this profile.
+    pub config: MonitoringConfig,
+}
+
+/// Saved profiles, in the order they were first saved.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ProfileStore {
+    profiles: Vec<Profile>,
+}
+
+impl ProfileStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the store from `path`; a missing file is an empty store.
+    pub fn load(path: &Path) -> Result<Self, ProfileError> {
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+        let data = std::fs::read_to_string(path).map_err(|e| ProfileError::Io(e.to_string()))?;
+        serde_json::from_str(&data).map_err(|e| ProfileError::Io(e.to_string()))
+    }
+
+    /// Write the store to `path`, creating parent directories.
+    pub fn save(&self, path: &Path) -> Result<(), ProfileError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| ProfileError::Io(e.to_string()))?;
+        }
+        let json =
+            serde_json::to_string_pretty(self).map_err(|e| ProfileError::Io(e.to_string()))?;
+        std::fs::write(path, json).map_err(|e| ProfileError::Io(e.to_string()))
+    }
+
+    /// Every profile, in the order they were first saved.
+    pub fn profiles(&self) -> &[Profile] {
+        &self.profiles
+    }
+
+    /// The profile called `name` (ignoring case and surrounding
+    /// whitespace).
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.position(name).map(|i| &self.profiles[i])
+    }
+
+    /// Save `config` as `name`. An existing profile with that name is
+    /// replaced in place if `overwrite` is set, and rejected otherwise.
+    pub fn insert(
+        &mut self,
+        name: &str,
+        config: MonitoringConfig,
+        overwrite: bool,
+    ) -> Result<(), ProfileError> {
+        let name = validate_name(name)?;
+        let profile = Profile {
+            name: name.to_string(),
+            config,
+        };
+        match self.position(name) {
+            Some(i) if overwrite => self.profiles[i] = profile,
+            Some(i) => return Err(ProfileError::Exists(self.profiles[i].name.clone())),
+            None if self.profiles.len() >= MAX_PROFILES => return Err(ProfileError::Full),
+            None => self.profiles.push(profile),
+        }
+        Ok(())
+    }
+
+    /// Delete the profile called `name`.
+    pub fn remove(&mut self, name: &str) -> Result<Profile, ProfileError> {
+        let i = self
+            .position(name)
+            .ok_or_else(|| ProfileError::NotFound(name.trim().to_string()))?;
+        Ok(self.profiles.remove(i))
+    }
+
+    /// Add `imported` profiles, replacing those with the same name. Profiles
+    /// beyond [`MAX_PROFILES`] or with invalid names are dropped; returns
+    /// how many were saved.
+    pub fn merge(&mut self, imported: Vec<Profile>) -> usize {
+        imported
+            .into_iter()
+            .filter(|p| self.insert(&p.name, p.config.clone(), true).is_ok())
+            .count()
+    }
+
+    /// Index of the profile called `name`.
+    fn position(&self, name: &str) -> Option<usize> {
+        let name = name.trim().to_lowercase();
+        self.profiles
+            .iter()
+            .position(|p| p.name.to_lowercase() == name)
+    }
+}
+
+/// `name` without surrounding whitespace, if it is a valid profile name.
+fn validate_name(name: &str) -> Result<&str, ProfileError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ProfileError::EmptyName);
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(ProfileError::NameTooLong);
+    }
+    Ok(name)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(countdown_secs: u64) -> MonitoringConfig {
+        serde_json::from_value(serde_json::json!({
+            "trigger_type": { "type": "network_idle", "interface_id": "auto" },
+            "condition": { "threshold_bytes_per_sec": 1024, "required_duration_secs": 60, "monitor_mode": "download_only" },
+            "action_type": "shutdown",
+            "pre_warning_secs": 30,
+            "countdown_secs": countdown_secs,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn names_are_unique_ignoring_case() {
+        let mut store = ProfileStore::new();
+        store.insert("Overnight", config(60), false).unwrap();
+        assert_eq!(
+            store.insert(" overnight ", config(30), false).unwrap_err(),
+            ProfileError::Exists("Overnight".into())
+        );
+
+        store.insert("OVERNIGHT", config(30), true).unwrap();
+        assert_eq!(store.profiles().len(), 1);
+        assert_eq!(store.profiles()[0].name, "OVERNIGHT");
+        assert_eq!(store.get("overnight").unwrap().config.countdown_secs, 30);
+
+        assert_eq!(
+            store.insert("  ", config(30), false).unwrap_err(),
+            ProfileError::EmptyName
+        );
+        store.remove("Overnight").unwrap();
+        assert_eq!(
+            store.remove("Overnight").unwrap_err(),
+            ProfileError::NotFound("Overnight".into())
+        );
+    }
+
+    #[test]
+    fn store_is_capped() {
+        let mut store = ProfileStore::new();
+        for i in 0..MAX_PROFILES {
+            store
+                .insert(&format!("Profile {i}"), config(60), false)
+                .unwrap();
+        }
+        assert_eq!(
+            store.insert("One more", config(60), false).unwrap_err(),
+            ProfileError::Full
+        );
+        // Replacing an existing profile is still allowed.
+        store.insert("Profile 0", config(30), true).unwrap();
+
+        let mut other = ProfileStore::new();
+        assert_eq!(other.merge(store.profiles().to_vec()), MAX_PROFILES);
+    }
+}
@@ -0,0 +1,490 @@
+//! Typed user settings.
+//!
+//! settings.json used to be passed through as arbitrary JSON, so a typo'd
+//! key or a string where a bool belongs was saved silently and broke the
+//! frontend later. [`AppSettings`] is now the single definition of the
+//! file: [`AppSettings::parse`] validates what the frontend (or an
+//! imported config) sends, naming the offending key, and
+//! [`AppSettings::migrate`] reads older files leniently, filling in
+//! defaults for missing or invalid values.
+//!
+//! The file is written atomically with a `settings.json.bak` copy (see
+//! [`persist`]), and an unreadable file falls back to that copy and then to
+//! the defaults instead of failing every load.
+//!
+//! A few preferences also live in [`AppState`] for the backend's use;
+//! [`init_state`] copies them over on startup, and the commands that change
+//! them write them back, so the two cannot drift.
+
+use crate::api::HttpApiSettings;
+use crate::audio::AudioSettings;
+use crate::countdown_alerts::CountdownAlertSettings;
+use crate::state::{AppState, MonitoringConfig};
+use flowwatcher_engine::persist;
+use flowwatcher_engine::Recovery;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Settings file, in the app data directory.
+pub const SETTINGS_FILE: &str = "settings.json";
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+/// Errors from validating, saving or applying settings.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SettingsError {
+    /// The settings are not a JSON object.
+    #[error("settings must be a JSON object")]
+    NotAnObject,
+
+    /// The settings contain keys [`AppSettings`] does not define.
+    #[error("unknown setting(s) {unknown}; expected one of: {expected}")]
+    UnknownKeys {
+        /// The unknown keys, comma-separated.
+        unknown: String,
+        /// Every known key, comma-separated.
+        expected: String,
+    },
+
+    /// A known key holds a value of the wrong type or range.
+    #[error("invalid value for setting \"{key}\": {message}")]
+    InvalidValue {
+        /// The offending key.
+        key: String,
+        /// What is wrong with the value.
+        message: String,
+    },
+
+    /// The settings file could not be written or removed.
+    #[error("failed to save settings: {0}")]
+    Io(String),
+}
+
+// ---------------------------------------------------------------------------
+// Settings
+// ---------------------------------------------------------------------------
+
+/// What [`AppSettings::parse`] does with keys it does not know.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownFields {
+    /// Fail with [`SettingsError::UnknownKeys`].
+    #[default]
+    Reject,
+    /// Drop them.
+    Strip,
+}
+
+/// UI colour scheme.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    /// Dark theme.
+    #[default]
+    Dark,
+    /// Light theme.
+    Light,
+    /// Follow the system setting.
+    Auto,
+}
+
+/// The contents of settings.json. Missing keys take their defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Language code (e.g. "en", "bn").
+    pub language: String,
+    /// UI colour scheme.
+    pub theme: Theme,
+    /// Start with the operating system.
+    pub auto_start: bool,
+    /// Minimize to the tray instead of closing.
+    pub minimize_to_tray: bool,
+    /// Show desktop notifications before the action.
+    pub show_notifications: bool,
+    /// Remaining countdown seconds at which to notify.
+    pub notify_at_secs: Vec<u64>,
+    /// Beep along with each countdown notification.
+    pub play_sound: bool,
+    /// Save settings on every change.
+    pub auto_save: bool,
+    /// Minutes to wait after detection before the countdown.
+    pub pre_action_delay_mins: u64,
+    /// Keep the screen on while monitoring.
+    pub keep_screen_on: bool,
+    /// Check daily for a newer release.
+    pub check_for_updates: bool,
+    /// Release manifest to check instead of the default one.
+    pub update_manifest_url: Option<String>,
+    /// Whether activity logging is enabled.
+    pub activity_logging: bool,
+    /// Audible countdown alerts.
+    pub audio: AudioSettings,
+    /// Local HTTP API for integrations.
+    pub http_api: HttpApiSettings,
+    /// Monitoring configuration the dashboard starts with.
+    pub default_config: Option<MonitoringConfig>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            language: "en".to_string(),
+            theme: Theme::default(),
+            auto_start: false,
+            minimize_to_tray: false,
+            show_notifications: true,
+            notify_at_secs: vec![30, 10],
+            play_sound: true,
+            auto_save: true,
+            pre_action_delay_mins: 0,
+            keep_screen_on: false,
+            check_for_updates: false,
+            update_manifest_url: None,
+            activity_logging: true,
+            audio: AudioSettings::default(),
+            http_api: HttpApiSettings::default(),
+            default_config: None,
+        }
+    }
+}
+
+impl AppSettings {
+    /// Validate `value` as settings. Unknown keys are handled per
+    /// `unknown`; a missing key takes its default, and a value of the
+    /// wrong type fails with the key named.
+    pub fn parse(value: serde_json::Value, unknown: UnknownFields) -> Result<Self, SettingsError> {
+        let serde_json::Value::Object(mut map) = value else {
+            return Err(SettingsError::NotAnObject);
+        };
+        let known = known_keys();
+        let unknown_keys: Vec<&str> = map
+            .keys()
+            .map(String::as_str)
+            .filter(|k| !known.contains(k))
+            .collect();
+        if !unknown_keys.is_empty() && unknown == UnknownFields::Reject {
+            return Err(SettingsError::UnknownKeys {
+                unknown: unknown_keys
+                    .iter()
+                    .map(|k| format!("\"{k}\""))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                expected: known.join(", "),
+            });
+        }
+        map.retain(|k, _| known.contains(&k.as_str()));
+        for (key, value) in &map {
+            check_key(key, value)?;
+        }
+        serde_json::from_value(serde_json::Value::Object(map)).map_err(|e| {
+            SettingsError::InvalidValue {
+                key: "settings".to_string(),
+                message: e.to_string(),
+            }
+        })
+    }
+
+    /// Read settings written by any version: unknown keys are dropped and
+    /// missing or invalid values take their defaults. Also returns whether
+    /// the result differs from `value`, i.e. the file should be rewritten.
+    pub fn migrate(value: &serde_json::Value) -> (Self, bool) {
+        let known = known_keys();
+        let valid: serde_json::Map<_, _> = value
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(key, value)| {
+                let keep = known.contains(&key.as_str()) && check_key(key, value).is_ok();
+                if !keep {
+                    log::warn!("Dropping invalid or unknown setting \"{key}\"");
+                }
+                keep
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        let settings: Self =
+            serde_json::from_value(serde_json::Value::Object(valid)).unwrap_or_default();
+        // Compare as written to disk: `to_value` would widen the f32 volume.
+        let written = serde_json::to_string(&settings)
+            .ok()
+            .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok());
+        let changed = written.as_ref() != Some(value);
+        (settings, changed)
+    }
+
+    /// Load the settings file at `path` as [`migrate`](Self::migrate)
+    /// does. A missing file gives the defaults; an unreadable one its
+    /// backup or the defaults.
+    pub fn load(path: &Path) -> Self {
+        Self::read(path).0.settings
+    }
+
+    /// Load the settings like [`load`](Self::load), reporting whether the
+    /// file had to be recovered. A recovered or migrated file is written
+    /// back, so the next load reads it cleanly; the unreadable copies are
+    /// first kept as `settings.json.corrupt` (see [`persist::set_aside`]),
+    /// and nothing is rewritten if that fails.
+    pub fn load_recovering(path: &Path) -> LoadedSettings {
+        let (loaded, changed) = Self::read(path);
+        if let Some(recovery) = loaded.recovery {
+            match persist::set_aside(path, recovery) {
+                Ok(moved) => {
+                    for file in moved {
+                        log::warn!("Kept the unreadable settings as {}", file.display());
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Not rewriting the unreadable settings file: {e}");
+                    return loaded;
+                }
+            }
+        }
+        if changed || loaded.recovery.is_some() {
+            if let Err(e) = loaded.settings.save(path) {
+                log::warn!("Failed to rewrite the settings file: {e}");
+            }
+        }
+        loaded
+    }
+
+    /// The settings at `path` and whether migrating them changed anything.
+    fn read(path: &Path) -> (LoadedSettings, bool) {
+        let loaded = persist::load_with_backup(path, read_json);
+        let (settings, changed) = match &loaded.value {
+            Some(value) => Self::migrate(value),
+            None => (Self::default(), false),
+        };
+        let loaded = LoadedSettings {
+            settings,
+            recovery: loaded.recovery,
+        };
+        (loaded, changed)
+    }
+
+    /// Write the settings to `path` atomically, keeping a backup and
+    /// creating parent directories.
+    pub fn save(&self, path: &Path) -> Result<(), SettingsError> {
+        let json =
+            serde_json::to_string_pretty(self).map_err(|e| SettingsError::Io(e.to_string()))?;
+        persist::write_with_backup(path, json.as_bytes()).map_err(SettingsError::Io)
+    }
+
+    /// The countdown notification preferences.
+    pub fn countdown_alerts(&self) -> CountdownAlertSettings {
+        CountdownAlertSettings {
+            enabled: self.show_notifications,
+            notify_at_secs: self.notify_at_secs.clone(),
+            play_sound: self.play_sound,
+        }
+    }
+}
+
+/// Settings returned by `get_settings`.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadedSettings {
+    /// The settings in effect.
+    pub settings: AppSettings,
+    /// Set when the settings file was unreadable and its backup or the
+    /// defaults were loaded instead, so the UI can tell the user.
+    pub recovery: Option<Recovery>,
+}
+
+/// Load the settings file in `dir` and copy the preferences the backend
+/// keeps in `state` (close to tray, keep screen on, countdown alerts).
+/// Returns the loaded settings.
+pub fn init_state(state: &AppState, dir: &Path) -> AppSettings {
+    let settings = AppSettings::load(&dir.join(SETTINGS_FILE));
+    *state.close_to_tray.blocking_lock() = settings.minimize_to_tray;
+    *state.keep_screen_on.blocking_lock() = settings.keep_screen_on;
+    state
+        .countdown_alerts
+        .set_settings(settings.countdown_alerts());
+    settings
+}
+
+/// Read the settings file at `path` as raw JSON, falling back to its
+/// backup if it is unreadable. `None` if neither can be read.
+pub fn read_value(path: &Path) -> Option<serde_json::Value> {
+    persist::load_with_backup(path, read_json).value
+}
+
+/// Read and parse the JSON file at `path`.
+fn read_json(path: &Path) -> Result<serde_json::Value, String> {
+    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+/// Load the settings file in `dir`, apply `change`, and write it back.
+pub fn update_file(dir: &Path, change: impl FnOnce(&mut AppSettings)) -> Result<(), SettingsError> {
+    let path = dir.join(SETTINGS_FILE);
+    let mut settings = AppSettings::load(&path);
+    change(&mut settings);
+    settings.save(&path)
+}
+
+/// Every key of the settings file, in declaration order.
+fn known_keys() -> Vec<&'static str> {
+    vec![
+        "language",
+        "theme",
+        "auto_start",
+        "minimize_to_tray",
+        "show_notifications",
+        "notify_at_secs",
+        "play_sound",
+        "auto_save",
+        "pre_action_delay_mins",
+        "keep_screen_on",
+        "check_for_updates",
+        "update_manifest_url",
+        "activity_logging",
+        "audio",
+        "http_api",
+        "default_config",
+    ]
+}
+
+/// Check that `value` is valid for the known `key`.
+fn check_key(key: &str, value: &serde_json::Value) -> Result<(), SettingsError> {
+    let single =
+        serde_json::Value::Object([(key.to_string(), value.clone())].into_iter().collect());
+    serde_json::from_value::<AppSettings>(single)
+        .map(drop)
+        .map_err(|e| SettingsError::InvalidValue {
+            key: key.to_string(),
+            message: e.to_string(),
+        })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_testing::{ScratchDir, ScriptedNetworkProvider, ScriptedProcessProvider};
+    use serde_json::json;
+
+    #[test]
+    fn known_keys_match_the_struct() {
+        let value = serde_json::to_value(AppSettings::default()).unwrap();
+        let mut keys: Vec<_> = value.as_object().unwrap().keys().cloned().collect();
+        let mut known = known_keys();
+        keys.sort();
+        known.sort_unstable();
+        assert_eq!(keys, known);
+    }
+
+    #[test]
+    fn parse_names_bad_keys_and_values() {
+        let err =
+            AppSettings::parse(json!({ "them": "light" }), UnknownFields::Reject).unwrap_err();
+        assert!(matches!(
+            &err,
+            SettingsError::UnknownKeys { unknown, .. } if unknown == "\"them\""
+        ));
+
+        let settings = AppSettings::parse(
+            json!({ "them": "light", "auto_save": false }),
+            UnknownFields::Strip,
+        )
+        .unwrap();
+        assert!(!settings.auto_save);
+        assert_eq!(settings.theme, Theme::Dark);
+
+        let err = AppSettings::parse(json!({ "keep_screen_on": "yes" }), UnknownFields::Reject)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SettingsError::InvalidValue { key, .. } if key == "keep_screen_on"
+        ));
+        assert_eq!(
+            AppSettings::parse(json!([]), UnknownFields::Strip).unwrap_err(),
+            SettingsError::NotAnObject
+        );
+    }
+
+    #[test]
+    fn startup_state_follows_the_settings_file() {
+        let dir = ScratchDir::new("settings");
+        let state = AppState::with_providers(
+            Box::new(ScriptedNetworkProvider::from_speeds(&[(0, 0)])),
+            Box::new(ScriptedProcessProvider::fixed(Vec::new())),
+        );
+
+        // No file yet: defaults.
+        let settings = init_state(&state, &dir);
+        assert!(!settings.keep_screen_on);
+        assert!(!*state.close_to_tray.blocking_lock());
+
+        update_file(&dir, |s| {
+            s.minimize_to_tray = true;
+            s.keep_screen_on = true;
+        })
+        .unwrap();
+        let settings = init_state(&state, &dir);
+        assert!(settings.keep_screen_on);
+        assert!(*state.close_to_tray.blocking_lock());
+        assert!(*state.keep_screen_on.blocking_lock());
+    }
+
+    #[test]
+    fn migration_fills_defaults_and_drops_bad_values() {
+        let old = json!({
+            "language": "bn",
+            "theme": "purple",
+            "auto_start": true,
+            "legacy_flag": 1
+        });
+        let (settings, changed) = AppSettings::migrate(&old);
+        assert!(changed);
+        assert_eq!(settings.language, "bn");
+        assert!(settings.auto_start);
+        assert_eq!(settings.theme, Theme::Dark);
+        assert_eq!(settings.notify_at_secs, vec![30, 10]);
+
+        let current: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&settings).unwrap()).unwrap();
+        assert!(!AppSettings::migrate(&current).1);
+    }
+
+    #[test]
+    fn a_truncated_file_is_recovered_from_the_backup() {
+        let dir = ScratchDir::new("settings-recovery");
+        let path = dir.join(SETTINGS_FILE);
+        let saved = AppSettings {
+            language: "bn".into(),
+            ..AppSettings::default()
+        };
+        saved.save(&path).unwrap();
+        let clean = AppSettings::load_recovering(&path);
+
+        // What a power loss mid-write used to leave behind.
+        let json = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, &json[..json.len() / 2]).unwrap();
+        let from_backup = AppSettings::load_recovering(&path);
+        let kept = std::fs::read_to_string(persist::corrupt_path(&path)).unwrap();
+        let rewritten = AppSettings::load_recovering(&path);
+
+        std::fs::write(&path, "{").unwrap();
+        std::fs::write(persist::backup_path(&path), "").unwrap();
+        let defaults = AppSettings::load_recovering(&path);
+        let kept_file = std::fs::read_to_string(persist::corrupt_path(&path)).unwrap();
+        let backup = persist::corrupt_path(&persist::backup_path(&path));
+        let kept_backup = std::fs::read_to_string(backup).unwrap();
+
+        assert_eq!(clean.recovery, None);
+        assert_eq!(from_backup.recovery, Some(Recovery::Backup));
+        assert_eq!(from_backup.settings.language, "bn");
+        assert_eq!(kept, json[..json.len() / 2]);
+        assert_eq!(rewritten.recovery, None);
+        assert_eq!(rewritten.settings.language, "bn");
+        assert_eq!(defaults.recovery, Some(Recovery::Defaults));
+        assert_eq!(defaults.settings.language, AppSettings::default().language);
+        assert_eq!((kept_file.as_str(), kept_backup.as_str()), ("{", ""));
+    }
+}
@@ -0,0 +1,163 @@
+//! Orderly exit.
+//!
+//! Quitting from the tray or closing the main window runs
+//! [`shutdown_sequence`]: a pending action is cancelled and logged, the open
+//! log session ends as [`SessionOutcome::Interrupted`], and the activity log
+//! and settings are written out before the process goes away. [`shutdown`]
+//! wires it to the app handle and gives up after [`SHUTDOWN_TIMEOUT`] so a
+//! wedged lock cannot keep the app from exiting.
+
+use crate::commands::{self, LOG_FILE};
+use crate::settings;
+use crate::state::AppState;
+use flowwatcher_engine::{CancelReason, LogEntry, SessionId, SessionOutcome};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How long [`shutdown`] waits for the sequence before exiting anyway.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Set once [`shutdown`] has run, so later exit paths skip it.
+static SHUT_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// What [`shutdown_sequence`] did.
+#[derive(Debug, Default)]
+pub struct ShutdownReport {
+    /// A pending action was cancelled.
+    pub cancelled_action: bool,
+    /// The log session that was ended, if one was open.
+    pub ended_session: Option<SessionId>,
+    /// Files that could not be written.
+    pub errors: Vec<String>,
+}
+
+/// Stop monitoring and write everything the app keeps in memory to `dir`
+/// (the app data directory, or `None` to skip writing).
+pub async fn shutdown_sequence(state: &AppState, dir: Option<&Path>) -> ShutdownReport {
+    let mut report = ShutdownReport::default();
+
+    let keep_screen_on = *state.keep_screen_on.lock().await;
+    if keep_screen_on {
+        commands::apply_keep_screen_on(false);
+    }
+
+    // Stopping the monitor task clears the last reading.
+    let details = commands::final_speed_details(state).await;
+    state.stop_monitor_task().await;
+    if let Some(mut trigger) = state.session_trigger.lock().await.take() {
+        if let Err(e) = trigger.stop().await {
+            log::warn!("Failed to stop the session trigger: {e}");
+        }
+    }
+
+    let config = state.config.lock().await.clone();
+    {
+        let mut scheduler = state.scheduler.lock().await;
+        report.cancelled_action = scheduler.cancel_with_reason(CancelReason::Shutdown).is_ok();
+        scheduler.take_events();
+    }
+
+    let mut logger = state.activity_logger.lock().await;
+    if report.cancelled_action {
+        let (trigger, action) = match &config {
+            Some(c) => (c.trigger_type.label(), c.action_type.to_string()),
+            None => ("Unknown trigger", "Unknown action".to_string()),
+        };
+        logger.add_entry(LogEntry::cancelled(trigger, action, CancelReason::Shutdown));
+    }
+    if let Some(id) = logger.current_session() {
+        logger.end_session(id, SessionOutcome::Interrupted, details);
+        report.ended_session = Some(id);
+    }
+
+    let Some(dir) = dir else {
+        return report;
+    };
+    if let Err(e) = logger.compact_file(&dir.join(LOG_FILE)) {
+        report.errors.push(format!("activity log: {e}"));
+    }
+    drop(logger);
+
+    let close_to_tray = *state.close_to_tray.lock().await;
+    if let Err(e) = settings::update_file(dir, |s| {
+        s.minimize_to_tray = close_to_tray;
+        s.keep_screen_on = keep_screen_on;
+    }) {
+        report.errors.push(format!("settings: {e}"));
+    }
+    report
+}
+
+/// Run [`shutdown_sequence`] for the app, once, waiting at most
+/// [`SHUTDOWN_TIMEOUT`]. Call before the process exits.
+pub fn shutdown(app: &AppHandle) {
+    if SHUT_DOWN.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let state = app.state::<AppState>();
+    let dir = app.path().app_data_dir().ok();
+    let sequence = shutdown_sequence(&state, dir.as_deref());
+    match tauri::async_runtime::block_on(tokio::time::timeout(SHUTDOWN_TIMEOUT, sequence)) {
+        Ok(report) => {
+            for error in &report.errors {
+                log::warn!("Failed to save on exit: {error}");
+            }
+        }
+        Err(_) => log::warn!(
+            "Shutdown did not finish within {}s; exiting anyway",
+            SHUTDOWN_TIMEOUT.as_secs()
+        ),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{AppSettings, SETTINGS_FILE};
+    use flowwatcher_engine::{ActivityLogger, LogStatus};
+    use flowwatcher_testing::{ScratchDir, ScriptedNetworkProvider, ScriptedProcessProvider};
+
+    #[tokio::test]
+    async fn pending_action_is_cancelled_and_everything_is_saved() {
+        let state = AppState::with_providers(
+            Box::new(ScriptedNetworkProvider::from_speeds(&[(0, 0)])),
+            Box::new(ScriptedProcessProvider::fixed(Vec::new())),
+        );
+        *state.close_to_tray.lock().await = true;
+        state.scheduler.lock().await.schedule().unwrap();
+        let session = state
+            .activity_logger
+            .lock()
+            .await
+            .start_session("Network idle", "shutdown");
+
+        let dir = ScratchDir::new("shutdown");
+        let report = shutdown_sequence(&state, Some(dir.path())).await;
+        assert!(report.cancelled_action);
+        assert_eq!(report.ended_session, Some(session));
+        assert!(report.errors.is_empty());
+
+        let logger = ActivityLogger::load_from_file(&dir.join(LOG_FILE)).unwrap();
+        let settings = AppSettings::load(&dir.join(SETTINGS_FILE));
+
+        let entry = logger.get_all().last().unwrap();
+        assert_eq!(entry.status, LogStatus::Cancelled);
+        assert_eq!(entry.cancel_reason, Some(CancelReason::Shutdown));
+        assert_eq!(
+            logger.get_sessions()[0].outcome,
+            Some(SessionOutcome::Interrupted)
+        );
+        assert!(settings.minimize_to_tray);
+
+        // Nothing is left to cancel the second time round.
+        let report = shutdown_sequence(&state, None).await;
+        assert!(!report.cancelled_action);
+        assert_eq!(report.ended_session, None);
+    }
+}
@@ -0,0 +1,178 @@
+//! Push-based speed updates for the dashboard.
+//!
+//! Instead of the webview polling `get_current_speed` (which contended with
+//! the monitoring loop for the provider lock and drifted when background
+//! timers were throttled), windows subscribe with
+//! `subscribe_speed_updates` and receive a [`SpeedUpdate`] on
+//! [`events::SPEED_UPDATE`] every second. During a session the monitoring
+//! loop publishes its own reading (and nothing while paused); otherwise a
+//! broadcaster task polls the speed monitor, and only while at least one
+//! window is subscribed.
+
+use crate::commands::{self, SpeedData};
+use crate::events;
+use crate::monitor_loop::MONITOR_INTERVAL;
+use crate::state::{AppState, MonitoringStatus};
+use flowwatcher_engine::speed::SpeedReading;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+/// Window of the short rolling average.
+pub const SHORT_AVERAGE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Window of the long rolling average.
+pub const LONG_AVERAGE_WINDOW: Duration = Duration::from_secs(60);
+
+// ---------------------------------------------------------------------------
+// Payload
+// ---------------------------------------------------------------------------
+
+/// Payload for the `speed-update` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct SpeedUpdate {
+    /// The latest speed, as `get_current_speed` returns it.
+    #[serde(flatten)]
+    pub speed: SpeedData,
+    /// Mean speed over the last [`SHORT_AVERAGE_WINDOW`].
+    pub average_10s: Option<SpeedReading>,
+    /// Mean speed over the last [`LONG_AVERAGE_WINDOW`].
+    pub average_60s: Option<SpeedReading>,
+}
+
+// ---------------------------------------------------------------------------
+// Subscribers
+// ---------------------------------------------------------------------------
+
+/// Which windows want speed updates, and the broadcaster serving them.
+#[derive(Default)]
+pub struct SpeedFeed {
+    /// Labels of the subscribed windows.
+    subscribers: std::sync::Mutex<BTreeSet<String>>,
+    /// The broadcaster task, while anyone is subscribed.
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl SpeedFeed {
+    /// Subscribe `label`. Returns `true` if it is the first subscriber.
+    /// Subscribing twice (e.g. after a reload) counts once.
+    pub fn add(&self, label: &str) -> bool {
+        let mut subscribers = self.subscribers.lock().unwrap_or_else(|e| e.into_inner());
+        subscribers.insert(label.to_string()) && subscribers.len() == 1
+    }
+
+    /// Unsubscribe `label`. Returns `true` if no subscriber is left.
+    pub fn remove(&self, label: &str) -> bool {
+        let mut subscribers = self.subscribers.lock().unwrap_or_else(|e| e.into_inner());
+        subscribers.remove(label) && subscribers.is_empty()
+    }
+
+    /// Whether any window is subscribed.
+    pub fn has_subscribers(&self) -> bool {
+        !self
+            .subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_empty()
+    }
+}
+
+/// Subscribe window `label`, starting the broadcaster for the first one.
+pub async fn subscribe(app: &AppHandle, label: &str) {
+    let state = app.state::<AppState>();
+    state.speed_feed.add(label);
+    let mut task = state.speed_feed.task.lock().await;
+    if task.is_none() {
+        *task = Some(spawn(app.clone()));
+    }
+}
+
+/// Unsubscribe window `label`, stopping the broadcaster after the last one.
+pub async fn unsubscribe(state: &AppState, label: &str) {
+    if state.speed_feed.remove(label) {
+        if let Some(task) = state.speed_feed.task.lock().await.take() {
+            task.abort();
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Broadcaster
+// ---------------------------------------------------------------------------
+
+/// Poll and publish once per [`MONITOR_INTERVAL`] while no session is
+/// running.
+fn spawn(app: AppHandle) -> JoinHandle<()> {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(MONITOR_INTERVAL);
+        loop {
+            interval.tick().await;
+            let state = app.state::<AppState>();
+            let idle = matches!(
+                *state.status.lock().await,
+                MonitoringStatus::Idle | MonitoringStatus::Executed
+            );
+            if !idle {
+                continue;
+            }
+            match commands::poll_speed(&app, &state).await {
+                Ok((speed, _)) => {
+                    *state.last_speed.lock().await = Some(speed.clone());
+                    publish(&app, &state, speed).await;
+                }
+                Err(e) => log::warn!("Speed poll failed: {e}"),
+            }
+        }
+    })
+}
+
+/// Emit `speed` with the rolling averages, if anyone is subscribed.
+pub async fn publish(app: &AppHandle, state: &AppState, speed: SpeedData) {
+    if !state.speed_feed.has_subscribers() {
+        return;
+    }
+    let (average_10s, average_60s) = match state.speed_monitor.lock().await.as_ref() {
+        Some(monitor) => (
+            monitor.average_over(SHORT_AVERAGE_WINDOW),
+            monitor.average_over(LONG_AVERAGE_WINDOW),
+        ),
+        None => (None, None),
+    };
+    state.events.emit(
+        app,
+        events::SPEED_UPDATE,
+        SpeedUpdate {
+            speed,
+            average_10s,
+            average_60s,
+        },
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broadcaster_runs_while_any_window_listens() {
+        let feed = SpeedFeed::default();
+        assert!(!feed.has_subscribers());
+        assert!(feed.add("main"));
+        // A reloaded window subscribes again without counting twice.
+        assert!(!feed.add("main"));
+        assert!(!feed.add("mini"));
+        assert!(!feed.remove("main"));
+        assert!(feed.has_subscribers());
+        assert!(feed.remove("mini"));
+        assert!(!feed.has_subscribers());
+        // Unknown windows change nothing.
+        assert!(!feed.remove("main"));
+    }
+}
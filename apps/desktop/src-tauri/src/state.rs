@@ -2,11 +2,40 @@
 //!
 //! Uses `tokio::sync::Mutex` for async-safe shared state across commands.
 
-use flowwatcher_conditions::ThresholdCondition;
-use flowwatcher_engine::{ActionScheduler, ActivityLogger, SpeedMonitor};
-use flowwatcher_platform::network::SysinfoNetworkProvider;
-use flowwatcher_platform::process::SysinfoProcessProvider;
+use crate::api::ApiServer;
+use crate::audio::AudioAlert;
+use crate::commands::SpeedData;
+use crate::countdown_alerts::CountdownAlerts;
+use crate::events::BatchedEmitter;
+use crate::guard::OperationGuard;
+use crate::speed_feed::SpeedFeed;
+use crate::tray::TrayState;
+use crate::updater::UpdateChecker;
+use flowwatcher_actions::{ActionRegistry, AttemptLog, StepLog, TemplateVars};
+use flowwatcher_conditions::Condition;
+use flowwatcher_engine::calibration::relevant_speed;
+pub use flowwatcher_engine::config::{
+    ActionSelection, ConditionConfig, MonitoringConfig, TriggerConfig,
+};
+use flowwatcher_engine::scheduler::{SchedulerEvent, SchedulerState};
+use flowwatcher_engine::speed::SpeedReading;
+use flowwatcher_engine::{
+    ActionScheduler, ActivityLogger, HookSet, IdleEstimate, IdleEstimator, RearmGate,
+    SessionRecorder, SpeedMonitor,
+};
+use flowwatcher_platform::cpu::SysinfoCpuProvider;
+use flowwatcher_platform::network::{NetworkProvider, SysinfoNetworkProvider};
+use flowwatcher_platform::process::{ProcessProvider, SysinfoProcessProvider};
+pub use flowwatcher_triggers::InterfaceChoice;
+use flowwatcher_triggers::{
+    builtin_registry, InterfaceWatchdog, ProcessActivityHistory, SharedProcessProvider, Trigger,
+    TriggerData, TriggerRegistry,
+};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::async_runtime::JoinHandle;
 use tokio::sync::Mutex;
 
 // ---------------------------------------------------------------------------
@@ -22,61 +51,60 @@ pub enum MonitoringStatus {
     /// Actively monitoring network/process activity.
     Monitoring,
     /// A trigger condition is pending (pre-warning phase).
-    TriggerPending,
+    TriggerPending { seconds_until_countdown: u64 },
     /// Countdown is active before action execution.
-    Countdown { remaining_secs: u64 },
+    Countdown {
+        remaining_secs: u64,
+        /// When the countdown ends, in milliseconds since the Unix epoch
+        /// (None while paused).
+        #[serde(default)]
+        deadline_epoch_ms: Option<u64>,
+    },
     /// The action was executed.
     Executed,
     /// Monitoring was paused.
     Paused,
 }
 
-/// Configuration for starting a monitoring session.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MonitoringConfig {
-    /// Which trigger type to use.
-    pub trigger_type: TriggerConfig,
-    /// Condition parameters.
-    pub condition: ConditionConfig,
-    /// Which action to execute when triggered.
-    pub action_type: String,
-    /// Pre-warning duration in seconds.
-    pub pre_warning_secs: u64,
-    /// Countdown duration in seconds.
-    pub countdown_secs: u64,
-}
+impl MonitoringStatus {
+    /// The status name, as serialised in the `status` tag.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Idle => "Idle",
+            Self::Monitoring => "Monitoring",
+            Self::TriggerPending { .. } => "TriggerPending",
+            Self::Countdown { .. } => "Countdown",
+            Self::Executed => "Executed",
+            Self::Paused => "Paused",
+        }
+    }
 
-/// Trigger-specific configuration (Strategic Shift: NOT hardcoded params).
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
-pub enum TriggerConfig {
-    /// Network idle trigger.
-    #[serde(rename = "network_idle")]
-    NetworkIdle {
-        /// Network interface to monitor (or "auto" for default).
-        interface_id: String,
-    },
-    /// Process-based trigger.
-    #[serde(rename = "process_idle")]
-    ProcessIdle {
-        /// Process names to watch.
-        watched_processes: Vec<String>,
-        /// Process names to exclude.
-        excluded_processes: Vec<String>,
-        /// Activity threshold in bytes.
-        threshold_bytes: u64,
-    },
+    /// The status for the scheduler's pre-warning or countdown, with its
+    /// remaining seconds, or `None` outside those phases. Paused phases
+    /// map to the phase they will resume into.
+    pub fn for_scheduler(scheduler: &ActionScheduler) -> Option<Self> {
+        let remaining = scheduler.remaining_secs().unwrap_or(0);
+        match scheduler.state() {
+            SchedulerState::Pending | SchedulerState::PausedPending => Some(Self::TriggerPending {
+                seconds_until_countdown: remaining,
+            }),
+            SchedulerState::Countdown | SchedulerState::PausedCountdown => Some(Self::Countdown {
+                remaining_secs: remaining,
+                deadline_epoch_ms: scheduler.deadline_epoch_ms(),
+            }),
+            _ => None,
+        }
+    }
 }
 
-/// Condition configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ConditionConfig {
-    /// Speed threshold in bytes per second.
-    pub threshold_bytes_per_sec: u64,
-    /// Required duration in seconds.
-    pub required_duration_secs: u64,
-    /// Monitor mode: "download_only", "upload_only", or "both".
-    pub monitor_mode: String,
+/// An in-progress session capture (development builds only).
+pub struct ActiveRecording {
+    /// Where the capture is being written.
+    pub path: PathBuf,
+    /// The capture writer.
+    pub recorder: SessionRecorder,
+    /// Scheduler events dispatched since the last recorded sample.
+    pub events: Vec<SchedulerEvent>,
 }
 
 // ---------------------------------------------------------------------------
@@ -84,17 +112,37 @@ pub struct ConditionConfig {
 // ---------------------------------------------------------------------------
 
 /// The managed application state shared across all Tauri commands.
+///
+/// Lock order: code that holds [`speed_monitor`](Self::speed_monitor)
+/// may take `scheduler`, `config` and `idle_estimator`
+/// (the speed poll does), never the other way round. Code that only reads
+/// a few fields should take each lock on its own instead of nesting them.
 pub struct AppState {
-    /// Network provider for speed monitoring.
-    pub network_provider: Mutex<SysinfoNetworkProvider>,
-    /// Process provider for process listing.
-    pub process_provider: Mutex<SysinfoProcessProvider>,
+    /// Network provider for speed monitoring. Shared so its blocking
+    /// refresh can run on a blocking thread (see
+    /// [`commands::snapshot_traffic`](crate::commands::snapshot_traffic)).
+    pub network_provider: Arc<Mutex<Box<dyn NetworkProvider>>>,
+    /// Process provider for process listing, shared with the process
+    /// trigger.
+    pub process_provider: SharedProcessProvider,
     /// Speed monitor (created when monitoring starts).
     pub speed_monitor: Mutex<Option<SpeedMonitor>>,
-    /// Threshold condition (created when monitoring starts).
-    pub threshold_condition: Mutex<Option<ThresholdCondition>>,
+    /// Fails the speed monitor over when its interface goes dead (network
+    /// trigger sessions only).
+    pub interface_watchdog: Mutex<Option<InterfaceWatchdog>>,
+    /// The session's condition: the config's condition tree, or its
+    /// threshold (created when monitoring starts).
+    pub condition: Mutex<Option<Box<dyn Condition>>>,
+    /// Started trigger evaluated by the monitoring loop (process and CPU
+    /// sessions; network sessions are driven by the speed monitor).
+    pub session_trigger: Mutex<Option<Box<dyn Trigger>>>,
+    /// Lifecycle hook actions of the session, resolved when it starts.
+    pub hooks: Mutex<Arc<HookSet>>,
     /// Action scheduler.
     pub scheduler: Mutex<ActionScheduler>,
+    /// Holds evaluation back after the action ran (repeat mode) or was
+    /// cancelled.
+    pub rearm: Mutex<RearmGate>,
     /// Current monitoring status.
     pub status: Mutex<MonitoringStatus>,
     /// Current monitoring configuration.
@@ -105,22 +153,264 @@ pub struct AppState {
     pub close_to_tray: Mutex<bool>,
     /// Whether to prevent display sleep during active monitoring.
     pub keep_screen_on: Mutex<bool>,
+    /// Batched event emitter for backend → frontend updates.
+    pub events: BatchedEmitter,
+    /// Rate-limited update checker (blocking; used from `spawn_blocking`).
+    pub updater: std::sync::Mutex<UpdateChecker>,
+    /// Active session capture, if recording.
+    pub recording: Mutex<Option<ActiveRecording>>,
+    /// Serializes state-mutating commands and issues session tokens.
+    pub operations: OperationGuard,
+    /// Action factories: system actions plus plugins loaded at startup.
+    pub actions: Mutex<ActionRegistry>,
+    /// Trigger factories by type id, used to validate trigger configs and
+    /// to build the process trigger.
+    pub triggers: TriggerRegistry,
+    /// Values interpolated into notify action templates (e.g. `{download_bps}`).
+    pub template_vars: TemplateVars,
+    /// Step results of the last sequence action run, drained into the log.
+    pub step_log: StepLog,
+    /// Failed attempts of the last retried action run, drained into the log.
+    pub attempt_log: AttemptLog,
+    /// Audible countdown alerts.
+    pub audio: AudioAlert,
+    /// OS notifications during the countdown.
+    pub countdown_alerts: CountdownAlerts,
+    /// Speed trend used to estimate when the transfer will go idle.
+    pub idle_estimator: Mutex<IdleEstimator>,
+    /// Recent activity of watched processes (process mode only).
+    pub process_history: Mutex<ProcessActivityHistory>,
+    /// Background monitoring loop, while a session runs.
+    pub monitor_task: Mutex<Option<JoinHandle<()>>>,
+    /// Latest speed polled by the monitoring loop or the speed
+    /// broadcaster.
+    pub last_speed: Mutex<Option<SpeedData>>,
+    /// Trigger data last fed to the condition; while an action is
+    /// scheduled, the data that met it.
+    pub last_trigger_data: Mutex<Option<TriggerData>>,
+    /// Timer that resumes a "Pause for…" pause, while one is pending.
+    pub auto_resume: Mutex<Option<JoinHandle<()>>>,
+    /// Tray icon and menu handles, once the tray is built.
+    pub tray: TrayState,
+    /// The local HTTP API server, while enabled.
+    pub http_api: Mutex<Option<ApiServer>>,
+    /// Windows subscribed to speed updates.
+    pub speed_feed: SpeedFeed,
 }
 
 impl AppState {
-    /// Create a new app state with defaults.
+    /// Create a new app state backed by the real `sysinfo` providers.
     pub fn new() -> Self {
+        Self::with_providers(
+            Box::new(SysinfoNetworkProvider::new()),
+            Box::new(SysinfoProcessProvider::new()),
+        )
+    }
+
+    /// Create an app state with injected providers (e.g. scripted mocks in tests).
+    pub fn with_providers(
+        network_provider: Box<dyn NetworkProvider>,
+        process_provider: Box<dyn ProcessProvider>,
+    ) -> Self {
+        let process_provider: SharedProcessProvider = Arc::new(Mutex::new(process_provider));
         Self {
-            network_provider: Mutex::new(SysinfoNetworkProvider::new()),
-            process_provider: Mutex::new(SysinfoProcessProvider::new()),
+            network_provider: Arc::new(Mutex::new(network_provider)),
+            process_provider: process_provider.clone(),
             speed_monitor: Mutex::new(None),
-            threshold_condition: Mutex::new(None),
+            interface_watchdog: Mutex::new(None),
+            condition: Mutex::new(None),
+            session_trigger: Mutex::new(None),
+            hooks: Mutex::new(Arc::new(HookSet::new())),
             scheduler: Mutex::new(ActionScheduler::new(60, 30)),
+            rearm: Mutex::new(RearmGate::new()),
             status: Mutex::new(MonitoringStatus::Idle),
             config: Mutex::new(None),
             activity_logger: Mutex::new(ActivityLogger::new()),
             close_to_tray: Mutex::new(false),
             keep_screen_on: Mutex::new(false),
+            events: BatchedEmitter::default(),
+            updater: std::sync::Mutex::new(UpdateChecker::default()),
+            recording: Mutex::new(None),
+            operations: OperationGuard::new(),
+            actions: Mutex::new(flowwatcher_platform::system_action_registry()),
+            triggers: builtin_registry(
+                || Box::new(SysinfoNetworkProvider::new()),
+                || Box::new(SysinfoCpuProvider::new()),
+                process_provider,
+            ),
+            template_vars: TemplateVars::new(),
+            step_log: StepLog::new(),
+            attempt_log: AttemptLog::new(),
+            audio: AudioAlert::new(),
+            countdown_alerts: CountdownAlerts::default(),
+            idle_estimator: Mutex::new(IdleEstimator::default()),
+            process_history: Mutex::new(ProcessActivityHistory::default()),
+            monitor_task: Mutex::new(None),
+            last_speed: Mutex::new(None),
+            last_trigger_data: Mutex::new(None),
+            auto_resume: Mutex::new(None),
+            tray: TrayState::default(),
+            http_api: Mutex::new(None),
+            speed_feed: SpeedFeed::default(),
+        }
+    }
+
+    /// Abort the background monitoring loop, if running, along with any
+    /// pending automatic resume.
+    pub async fn stop_monitor_task(&self) {
+        if let Some(task) = self.monitor_task.lock().await.take() {
+            task.abort();
+        }
+        self.cancel_auto_resume().await;
+        *self.last_speed.lock().await = None;
+        *self.last_trigger_data.lock().await = None;
+    }
+
+    /// Drop the timer of a timed pause, if one is pending.
+    pub async fn cancel_auto_resume(&self) {
+        if let Some(task) = self.auto_resume.lock().await.take() {
+            task.abort();
+        }
+    }
+
+    /// Feed a speed reading to the idle estimator and estimate when the
+    /// monitored direction falls below the configured threshold.
+    ///
+    /// Returns `None` while no monitoring session is configured.
+    pub async fn observe_speed(&self, reading: &SpeedReading, at: Instant) -> Option<IdleEstimate> {
+        let config = self.config.lock().await;
+        let condition = &config.as_ref()?.condition;
+        let bps = relevant_speed(reading, condition.mode());
+
+        let mut estimator = self.idle_estimator.lock().await;
+        estimator.push(at, bps);
+        estimator.estimate(condition.threshold_bytes_per_sec)
+    }
+
+    /// Record the watched processes' activity at `at_ms` (Unix millis).
+    ///
+    /// Reuses the processes the session trigger listed in its last
+    /// evaluation rather than listing them again, so each listing is
+    /// recorded once. Returns `false` when there is none to record: no
+    /// process-mode session, or the trigger was not evaluated since the
+    /// last sample. Logs once per session when the series cap turns
+    /// processes away.
+    pub async fn sample_process_activity(&self, at_ms: u64) -> bool {
+        let processes = match self.session_trigger.lock().await.as_mut() {
+            Some(trigger) => match trigger.take_watched_processes() {
+                Some(processes) => processes,
+                None => return false,
+            },
+            None => return false,
+        };
+
+        let mut history = self.process_history.lock().await;
+        let was_capped = history.cap_reached();
+        let skipped = history.record(at_ms, &processes);
+        if skipped > 0 && !was_capped {
+            log::warn!(
+                "Process activity history is tracking its maximum of {} processes; \
+                 {skipped} more are not recorded",
+                history.max_series()
+            );
+        }
+        true
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Memory statistics
+// ---------------------------------------------------------------------------
+
+/// Current size and cap of one bounded buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BufferStats {
+    /// Items currently held.
+    pub len: usize,
+    /// Maximum number of items.
+    pub capacity: usize,
+}
+
+/// Sizes of the long-lived in-memory buffers, for spotting leaks in long
+/// sessions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryStats {
+    /// Activity log entries.
+    pub activity_log: BufferStats,
+    /// Scheduler events not yet forwarded to the frontend.
+    pub scheduler_events: BufferStats,
+    /// Scheduler events dropped from a full buffer.
+    pub scheduler_dropped_events: u64,
+    /// Speed samples held by the idle estimator.
+    pub idle_estimator: BufferStats,
+    /// Tracked process activity series.
+    pub process_series: BufferStats,
+    /// Process activity samples across all series.
+    pub process_samples: BufferStats,
+    /// Readings in the speed graph history.
+    pub speed_history: BufferStats,
+    /// Coalesced events waiting to be flushed.
+    pub pending_events: usize,
+}
+
+impl AppState {
+    /// Report the size of every bounded buffer.
+    ///
+    /// Each lock is taken and released on its own, so this never nests
+    /// locks against the [`AppState`] lock order.
+    pub async fn memory_stats(&self) -> MemoryStats {
+        let speed_history = match self.speed_monitor.lock().await.as_ref() {
+            Some(monitor) => BufferStats {
+                len: monitor.history_len(),
+                capacity: monitor.history_capacity(),
+            },
+            None => BufferStats {
+                len: 0,
+                capacity: 0,
+            },
+        };
+        let (scheduler_events, scheduler_dropped_events) = {
+            let scheduler = self.scheduler.lock().await;
+            (
+                BufferStats {
+                    len: scheduler.buffered_events(),
+                    capacity: flowwatcher_engine::scheduler::MAX_BUFFERED_EVENTS,
+                },
+                scheduler.dropped_events(),
+            )
+        };
+        let idle_estimator = {
+            let estimator = self.idle_estimator.lock().await;
+            BufferStats {
+                len: estimator.len(),
+                capacity: estimator.window(),
+            }
+        };
+        let (process_series, process_samples) = {
+            let history = self.process_history.lock().await;
+            (
+                BufferStats {
+                    len: history.len(),
+                    capacity: history.max_series(),
+                },
+                BufferStats {
+                    len: history.sample_count(),
+                    capacity: history.max_series() * history.capacity(),
+                },
+            )
+        };
+        MemoryStats {
+            activity_log: BufferStats {
+                len: self.activity_logger.lock().await.len(),
+                capacity: flowwatcher_engine::logger::MAX_ENTRIES,
+            },
+            scheduler_events,
+            scheduler_dropped_events,
+            idle_estimator,
+            process_series,
+            process_samples,
+            speed_history,
+            pending_events: self.events.pending_len(),
         }
     }
 }
@@ -130,3 +420,306 @@ impl Default for AppState {
         Self::new()
     }
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_engine::{
+        AckPolicy, LifecycleHooks, LogEntry, LogStatus, PowerRules, SmoothingStrategy,
+    };
+    use flowwatcher_testing::{
+        sample_processes, ScriptedNetworkProvider, ScriptedProcessProvider, MOCK_INTERFACE_ID,
+    };
+    use flowwatcher_triggers::{CpuMode, ProcessMatch};
+
+    #[test]
+    fn status_labels_match_the_serialised_tag() {
+        let statuses = [
+            MonitoringStatus::Idle,
+            MonitoringStatus::Monitoring,
+            MonitoringStatus::TriggerPending {
+                seconds_until_countdown: 5,
+            },
+            MonitoringStatus::Countdown {
+                remaining_secs: 3,
+                deadline_epoch_ms: None,
+            },
+            MonitoringStatus::Executed,
+            MonitoringStatus::Paused,
+        ];
+        for status in statuses {
+            let json = serde_json::to_value(&status).unwrap();
+            assert_eq!(json["status"], status.label());
+        }
+    }
+
+    fn scripted_state() -> AppState {
+        AppState::with_providers(
+            Box::new(ScriptedNetworkProvider::from_speeds(&[(2048, 1024)])),
+            Box::new(ScriptedProcessProvider::fixed(sample_processes())),
+        )
+    }
+
+    #[tokio::test]
+    async fn injected_network_provider_drives_speed_monitor() {
+        let state = scripted_state();
+        let mut provider = state.network_provider.lock().await;
+        let mut monitor = SpeedMonitor::new(MOCK_INTERFACE_ID, 3);
+
+        monitor.poll(&mut **provider).unwrap();
+        let reading = monitor.poll(&mut **provider).unwrap().unwrap();
+        assert_eq!(reading.download_bps, 2048);
+        assert_eq!(reading.upload_bps, 1024);
+    }
+
+    #[tokio::test]
+    async fn injected_process_provider_is_used() {
+        let state = scripted_state();
+        let mut provider = state.process_provider.lock().await;
+        let suggestions = provider.get_suggestions(1).unwrap();
+        assert_eq!(suggestions.len(), 4);
+        assert_eq!(suggestions[0].name, "steam.exe");
+    }
+
+    fn monitoring_config(threshold: u64) -> MonitoringConfig {
+        MonitoringConfig {
+            trigger_type: TriggerConfig::NetworkIdle {
+                interface_id: InterfaceChoice::One(MOCK_INTERFACE_ID.to_string()),
+            },
+            condition: ConditionConfig {
+                threshold_bytes_per_sec: threshold,
+                required_duration_secs: 60,
+                monitor_mode: "download_only".to_string(),
+                warning_multiplier: 0.0,
+                hysteresis_bytes_per_sec: None,
+                max_spike_samples: 0,
+                max_spike_duration_secs: None,
+            },
+            condition_tree: None,
+            action_type: ActionSelection::Single("shutdown".to_string()),
+            action_params: serde_json::Value::Null,
+            continue_on_error: false,
+            retry: Default::default(),
+            pre_warning_secs: 60,
+            countdown_secs: 30,
+            ack_policy: AckPolicy::None,
+            max_snooze_secs: flowwatcher_engine::scheduler::DEFAULT_MAX_SNOOZE_SECS,
+            smoothing: SmoothingStrategy::default(),
+            interface_failover: Default::default(),
+            dry_run: false,
+            restart: false,
+            power_guard: PowerRules::default(),
+            require_user_idle_secs: None,
+            pending_timeout_secs: None,
+            repeat_mode: Default::default(),
+            rearm_requires_activity: false,
+            hooks: LifecycleHooks::default(),
+        }
+    }
+
+    fn reading(download_bps: u64) -> SpeedReading {
+        SpeedReading {
+            download_bps,
+            upload_bps: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn declining_speed_yields_idle_estimate() {
+        let state = scripted_state();
+        let start = Instant::now();
+        let at = |i: u64| start + std::time::Duration::from_secs(i);
+
+        // Not monitoring: nothing is recorded.
+        assert!(state
+            .observe_speed(&reading(1_000_000), at(0))
+            .await
+            .is_none());
+        assert!(state.idle_estimator.lock().await.is_empty());
+
+        *state.config.lock().await = Some(monitoring_config(100_000));
+        let mut estimate = None;
+        for i in 0..40 {
+            estimate = state
+                .observe_speed(&reading(1_000_000 - i * 10_000), at(i))
+                .await;
+        }
+        assert_eq!(estimate.unwrap().remaining.as_secs(), 51);
+    }
+
+    #[tokio::test]
+    async fn flat_speed_has_no_idle_estimate() {
+        let state = scripted_state();
+        *state.config.lock().await = Some(monitoring_config(100_000));
+        let start = Instant::now();
+        let mut estimate = None;
+        for i in 0..40 {
+            let at = start + std::time::Duration::from_secs(i);
+            estimate = state.observe_speed(&reading(500_000), at).await;
+        }
+        assert!(estimate.is_none());
+    }
+
+    #[tokio::test]
+    async fn process_mode_records_watched_activity() {
+        let state = scripted_state();
+        assert!(!state.sample_process_activity(0).await);
+
+        let config = serde_json::to_value(TriggerConfig::ProcessIdle {
+            watched_processes: vec!["Steam.exe".into(), "chrome.exe".into()],
+            excluded_processes: vec!["chrome.exe".into()],
+            // svchost.exe
+            watched_matches: vec![ProcessMatch::Pid(4)],
+            excluded_matches: vec![],
+            threshold_bytes: 1_000,
+            history_samples: 2,
+        })
+        .unwrap();
+        let mut trigger = state.triggers.create("process_idle", &config).unwrap();
+        trigger.start().await.unwrap();
+        *state.session_trigger.lock().await = Some(trigger);
+        state.process_history.lock().await.set_capacity(2);
+        for at in [0, 1_000, 2_000] {
+            let mut session = state.session_trigger.lock().await;
+            session.as_mut().unwrap().evaluate().await.unwrap();
+            drop(session);
+            assert!(state.sample_process_activity(at).await);
+            // Each listing is recorded once.
+            assert!(!state.sample_process_activity(at).await);
+        }
+
+        let history = state.process_history.lock().await.snapshot();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history["steam.exe"], vec![(1_000, 50_000), (2_000, 50_000)]);
+        assert_eq!(
+            history["svchost.exe"],
+            vec![(1_000, 30_000), (2_000, 30_000)]
+        );
+    }
+
+    #[tokio::test]
+    async fn long_sessions_keep_buffers_at_their_caps() {
+        let state = scripted_state();
+        *state.config.lock().await = Some(monitoring_config(100_000));
+        let start = Instant::now();
+        for i in 0..100_000u64 {
+            let at = start + std::time::Duration::from_secs(i);
+            state.observe_speed(&reading(i % 1_000), at).await;
+            state.activity_logger.lock().await.add_entry(LogEntry::now(
+                "Network idle",
+                "Shutdown",
+                LogStatus::Info,
+                None,
+            ));
+            // Scheduler events are never forwarded.
+            let mut scheduler = state.scheduler.lock().await;
+            scheduler.schedule().unwrap();
+            scheduler.cancel().unwrap();
+        }
+
+        let stats = state.memory_stats().await;
+        assert_eq!(stats.activity_log.len, stats.activity_log.capacity);
+        assert_eq!(stats.scheduler_events.len, stats.scheduler_events.capacity);
+        assert!(stats.scheduler_dropped_events > 0);
+        assert_eq!(stats.idle_estimator.len, stats.idle_estimator.capacity);
+        assert!(stats.process_samples.len <= stats.process_samples.capacity);
+        assert!(stats.speed_history.len <= stats.speed_history.capacity);
+        assert_eq!(stats.pending_events, 0);
+    }
+
+    #[tokio::test]
+    async fn new_state_starts_idle() {
+        let state = scripted_state();
+        assert_eq!(*state.status.lock().await, MonitoringStatus::Idle);
+        assert!(state.speed_monitor.lock().await.is_none());
+    }
+
+    #[test]
+    fn cpu_trigger_config_uses_defaults() {
+        let config: TriggerConfig = serde_json::from_str(r#"{ "type": "cpu_idle" }"#).unwrap();
+        let TriggerConfig::CpuIdle {
+            mode,
+            sample_interval_ms,
+        } = config
+        else {
+            panic!("expected a CPU trigger config");
+        };
+        assert_eq!(mode, CpuMode::Aggregate);
+        assert_eq!(sample_interval_ms, 1_000);
+
+        let config: TriggerConfig =
+            serde_json::from_str(r#"{ "type": "cpu_idle", "mode": "per_core" }"#).unwrap();
+        assert!(matches!(
+            config,
+            TriggerConfig::CpuIdle {
+                mode: CpuMode::PerCore,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn trigger_configs_are_accepted_by_the_registry() {
+        use flowwatcher_triggers::Trigger;
+
+        let state = scripted_state();
+        let process = TriggerConfig::ProcessIdle {
+            watched_processes: vec!["steam.exe".into()],
+            excluded_processes: vec![],
+            watched_matches: vec![],
+            excluded_matches: vec![],
+            threshold_bytes: 1_000,
+            history_samples: 2,
+        };
+        for trigger in [monitoring_config(100_000).trigger_type, process] {
+            let json = serde_json::to_value(&trigger).unwrap();
+            let created = state.triggers.create(trigger.registry_id(), &json).unwrap();
+            assert_eq!(created.trigger_type(), trigger.registry_id());
+        }
+
+        let broken = serde_json::json!({ "type": "process_idle", "threshold_bytes": 1 });
+        assert!(state.triggers.create("process_idle", &broken).is_err());
+    }
+
+    #[tokio::test]
+    async fn process_trigger_reads_the_shared_provider() {
+        let state = scripted_state();
+        let config = serde_json::to_value(TriggerConfig::ProcessIdle {
+            watched_processes: vec!["chrome.exe".into()],
+            excluded_processes: vec![],
+            watched_matches: vec![],
+            excluded_matches: vec![],
+            threshold_bytes: 1_000,
+            history_samples: 2,
+        })
+        .unwrap();
+        let mut trigger = state.triggers.create("process_idle", &config).unwrap();
+        trigger.start().await.unwrap();
+
+        // chrome.exe moves 100 B/s in the scripted snapshot.
+        match trigger.evaluate().await.unwrap() {
+            flowwatcher_triggers::TriggerState::Active(data) => assert_eq!(
+                data.get("watched_count"),
+                Some(&flowwatcher_triggers::TriggerValue::U64(1))
+            ),
+            other => panic!("expected Active, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn action_type_accepts_a_single_id_or_a_sequence() {
+        let single: ActionSelection = serde_json::from_str(r#""hibernate""#).unwrap();
+        assert_eq!(single, ActionSelection::Single("hibernate".into()));
+
+        let sequence: ActionSelection = serde_json::from_str(
+            r#"[{ "action_type": "run_script", "params": { "program": "backup" } },
+                { "action_type": "hibernate" }]"#,
+        )
+        .unwrap();
+        assert_eq!(sequence.to_string(), "run_script → hibernate");
+    }
+}
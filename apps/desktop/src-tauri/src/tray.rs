@@ -2,37 +2,412 @@
 //!
 //! Sets up a tray icon with a right-click context menu and
 //! left-click window restore. Used for background operation.
+//! The tooltip, icon and menu follow the monitoring status: the tooltip
+//! shows the live speed or countdown seconds (and the clock time the
+//! countdown ends), the icon switches between
+//! the idle/monitoring/countdown variants in `icons/tray`, and menu items
+//! that do not apply to the current status are disabled.
+//!
+//! The "Action" and "Pause for…" submenus work entirely in the backend, so
+//! they keep working while the main window is closed to the tray.
 
+use crate::commands;
+use crate::state::{ActionSelection, AppState, MonitoringStatus};
+use flowwatcher_engine::{ByteRate, CancelReason};
+use std::sync::Mutex;
+use std::time::Duration;
 use tauri::{
-    menu::{Menu, MenuItem, PredefinedMenuItem},
-    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    App, Emitter, Manager,
+    image::Image,
+    include_image,
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
+    tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent},
+    App, AppHandle, Emitter, Manager, Wry,
 };
 
+/// Id of the app's tray icon.
+const TRAY_ID: &str = "main";
+
+/// Menu id prefix of the "Action" submenu items, followed by the action id.
+const ACTION_ITEM_PREFIX: &str = "action:";
+
+/// "Pause for…" submenu items: menu id, label, and how long to pause
+/// (`None` until resumed by hand).
+const PAUSE_OPTIONS: [(&str, &str, Option<Duration>); 3] = [
+    (
+        "pause_15m",
+        "15 Minutes",
+        Some(Duration::from_secs(15 * 60)),
+    ),
+    ("pause_1h", "1 Hour", Some(Duration::from_secs(60 * 60))),
+    ("pause_indefinitely", "Until Resumed", None),
+];
+
+/// Tray icon while nothing is being monitored.
+const IDLE_ICON: Image<'_> = include_image!("./icons/tray/idle.png");
+/// Tray icon while monitoring or waiting for the countdown.
+const MONITORING_ICON: Image<'_> = include_image!("./icons/tray/monitoring.png");
+/// Tray icon while the countdown runs.
+const COUNTDOWN_ICON: Image<'_> = include_image!("./icons/tray/countdown.png");
+
+// ---------------------------------------------------------------------------
+// Status presentation
+// ---------------------------------------------------------------------------
+
+/// Live values shown in the tooltip while monitoring.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrayContext {
+    /// Display name of the configured action.
+    pub action_name: Option<String>,
+    /// Interfaces being monitored, e.g. "eth0".
+    pub interface: Option<String>,
+    /// Latest download speed in bytes per second.
+    pub download_bps: Option<u64>,
+}
+
+impl TrayContext {
+    /// Read the context from the template variables the monitoring loop
+    /// keeps current.
+    fn from_state(state: &AppState) -> Self {
+        let vars = &state.template_vars;
+        Self {
+            action_name: vars.get("action"),
+            interface: vars.get("interface"),
+            download_bps: vars.get("download_bps").and_then(|v| v.parse().ok()),
+        }
+    }
+}
+
+/// Which bundled icon variant a status shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayIconKind {
+    /// Not monitoring, paused, or the action has run.
+    Idle,
+    /// Monitoring, including the pre-warning.
+    Monitoring,
+    /// The countdown to the action is running.
+    Countdown,
+}
+
+impl TrayIconKind {
+    /// The icon variant for a status.
+    pub fn for_status(status: &MonitoringStatus) -> Self {
+        match status {
+            MonitoringStatus::Idle | MonitoringStatus::Executed | MonitoringStatus::Paused => {
+                Self::Idle
+            }
+            MonitoringStatus::Monitoring | MonitoringStatus::TriggerPending { .. } => {
+                Self::Monitoring
+            }
+            MonitoringStatus::Countdown { .. } => Self::Countdown,
+        }
+    }
+
+    fn image(self) -> Image<'static> {
+        match self {
+            Self::Idle => IDLE_ICON,
+            Self::Monitoring => MONITORING_ICON,
+            Self::Countdown => COUNTDOWN_ICON,
+        }
+    }
+}
+
+/// Which tray menu items are enabled for a status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MenuAvailability {
+    /// "Start Monitoring".
+    pub start: bool,
+    /// "Stop Monitoring".
+    pub stop: bool,
+    /// "Cancel Pending Action".
+    pub cancel: bool,
+    /// "I'm Aware", which acknowledges the pre-warning.
+    pub acknowledge: bool,
+    /// The "Pause for…" submenu.
+    pub pause: bool,
+    /// "Resume Monitoring".
+    pub resume: bool,
+}
+
+impl MenuAvailability {
+    /// The enabled items for a status.
+    pub fn for_status(status: &MonitoringStatus) -> Self {
+        let running = !matches!(status, MonitoringStatus::Idle | MonitoringStatus::Executed);
+        Self {
+            start: !running,
+            stop: running,
+            // A paused session may be holding a pending action.
+            cancel: matches!(
+                status,
+                MonitoringStatus::TriggerPending { .. }
+                    | MonitoringStatus::Countdown { .. }
+                    | MonitoringStatus::Paused
+            ),
+            acknowledge: matches!(status, MonitoringStatus::TriggerPending { .. }),
+            // While paused, a timed pause can still be changed.
+            pause: running,
+            resume: *status == MonitoringStatus::Paused,
+        }
+    }
+}
+
+/// Tooltip text for a monitoring status, e.g. "FlowWatcher\nCountdown: 00:25 — Shut Down".
+pub fn tooltip_for(status: &MonitoringStatus, context: &TrayContext) -> String {
+    let action_name = context.action_name.as_deref().unwrap_or("Action");
+    let detail = match status {
+        MonitoringStatus::Idle => "Idle".to_string(),
+        MonitoringStatus::Monitoring => {
+            let mut detail = "Monitoring".to_string();
+            if let Some(interface) = &context.interface {
+                detail = format!("{detail} {interface}");
+            }
+            if let Some(bps) = context.download_bps {
+                detail = format!("{detail} — {} down", ByteRate::new(bps));
+            }
+            detail
+        }
+        MonitoringStatus::TriggerPending {
+            seconds_until_countdown,
+        } => format!(
+            "{action_name} countdown in {}",
+            clock(*seconds_until_countdown)
+        ),
+        MonitoringStatus::Countdown {
+            remaining_secs,
+            deadline_epoch_ms,
+        } => {
+            let mut detail = format!("Countdown: {} — {action_name}", clock(*remaining_secs));
+            if let Some(deadline) = deadline_epoch_ms.and_then(local_time) {
+                detail = format!("{detail} at {deadline}");
+            }
+            detail
+        }
+        MonitoringStatus::Executed => format!("{action_name} executed"),
+        MonitoringStatus::Paused => "Paused".to_string(),
+    };
+    format!("FlowWatcher\n{detail}")
+}
+
+/// Local `HH:MM:SS` of a time in milliseconds since the Unix epoch.
+fn local_time(epoch_ms: u64) -> Option<String> {
+    let at = chrono::DateTime::from_timestamp_millis(i64::try_from(epoch_ms).ok()?)?;
+    Some(
+        at.with_timezone(&chrono::Local)
+            .format("%H:%M:%S")
+            .to_string(),
+    )
+}
+
+/// `mm:ss`, with minutes growing past 59 as needed.
+fn clock(secs: u64) -> String {
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+// ---------------------------------------------------------------------------
+// TrayState
+// ---------------------------------------------------------------------------
+
+/// The tray icon and the menu items whose state follows the status.
+struct TrayHandles {
+    tray: TrayIcon,
+    start: MenuItem,
+    stop: MenuItem,
+    cancel: MenuItem,
+    acknowledge: MenuItem,
+    pause: Submenu,
+    resume: MenuItem,
+    /// "Action" submenu items by action id.
+    actions: Vec<(String, CheckMenuItem)>,
+    /// Icon variant currently shown, to skip redundant swaps.
+    icon: TrayIconKind,
+    /// Menu state currently shown, to skip redundant updates.
+    menu: MenuAvailability,
+}
+
+/// Handles to the tray, kept in [`AppState`] once the tray is built.
+#[derive(Default)]
+pub struct TrayState {
+    handles: Mutex<Option<TrayHandles>>,
+}
+
+impl TrayState {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<TrayHandles>> {
+        // The handles stay valid even if a holder panicked.
+        self.handles.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Check the "Action" submenu item of `action_id` and uncheck the rest
+    /// (all of them for `None`, e.g. a sequence).
+    pub fn show_selected_action(&self, action_id: Option<&str>) {
+        let guard = self.lock();
+        let Some(handles) = guard.as_ref() else {
+            return;
+        };
+        for (id, item) in &handles.actions {
+            if let Err(e) = item.set_checked(Some(id.as_str()) == action_id) {
+                log::warn!("Failed to update tray action menu: {e}");
+            }
+        }
+    }
+}
+
+/// Show `status` in the tray: tooltip, icon variant and enabled menu
+/// items (best-effort; failures are only logged).
+pub fn update_tray_status(app: &AppHandle, status: &MonitoringStatus) {
+    let state = app.state::<AppState>();
+    let context = TrayContext::from_state(&state);
+    let mut guard = state.tray.lock();
+    let Some(handles) = guard.as_mut() else {
+        return;
+    };
+    if let Err(e) = handles
+        .tray
+        .set_tooltip(Some(tooltip_for(status, &context)))
+    {
+        log::warn!("Failed to update tray tooltip: {e}");
+    }
+
+    let icon = TrayIconKind::for_status(status);
+    if icon != handles.icon {
+        match handles.tray.set_icon(Some(icon.image())) {
+            Ok(()) => handles.icon = icon,
+            Err(e) => log::warn!("Failed to update tray icon: {e}"),
+        }
+    }
+
+    let menu = MenuAvailability::for_status(status);
+    if menu != handles.menu {
+        let results = [
+            handles.start.set_enabled(menu.start),
+            handles.stop.set_enabled(menu.stop),
+            handles.cancel.set_enabled(menu.cancel),
+            handles.acknowledge.set_enabled(menu.acknowledge),
+            handles.pause.set_enabled(menu.pause),
+            handles.resume.set_enabled(menu.resume),
+        ];
+        match results.into_iter().find_map(Result::err) {
+            Some(e) => log::warn!("Failed to update tray menu: {e}"),
+            None => handles.menu = menu,
+        }
+    }
+}
+
+/// Make `action_id` the session's action from the tray menu.
+///
+/// The frontend is told through `tray-action-selected` so the next start
+/// uses it too; the check marks follow whatever actually took effect.
+async fn select_action(app: &AppHandle, action_id: &str) {
+    let state = app.state::<AppState>();
+    let result = match state.operations.try_begin() {
+        Ok(_permit) => commands::select_action(&state, action_id).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(name) => {
+            log::info!("Tray selected action: {name}");
+            state.tray.show_selected_action(Some(action_id));
+            let _ = app.emit("tray-action-selected", action_id);
+        }
+        Err(e) => {
+            log::warn!("Tray action selection failed: {e}");
+            let current = state
+                .config
+                .lock()
+                .await
+                .as_ref()
+                .and_then(|c| match &c.action_type {
+                    ActionSelection::Single(id) => Some(id.clone()),
+                    ActionSelection::Sequence(_) => None,
+                });
+            state.tray.show_selected_action(current.as_deref());
+        }
+    }
+}
+
 /// Create and configure the system tray icon.
 ///
-/// - Right-click: context menu with Start/Stop Monitoring, Open Dashboard, Exit
+/// - Right-click: context menu with Start/Stop Monitoring, Cancel Pending
+///   Action, I'm Aware, Pause for…, Resume Monitoring, Action, Open
+///   Dashboard, Exit
 /// - Left-click: show and focus the main window
+///
+/// Starts in the idle state; [`update_tray_status`] takes over from there.
 pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
+    let idle = MenuAvailability::for_status(&MonitoringStatus::Idle);
+
     // ── Menu items ──
     let start_monitoring = MenuItem::with_id(
         app,
         "start_monitoring",
         "Start Monitoring",
-        true,
+        idle.start,
         None::<&str>,
     )?;
     let stop_monitoring = MenuItem::with_id(
         app,
         "stop_monitoring",
         "Stop Monitoring",
-        true,
+        idle.stop,
         None::<&str>,
     )?;
+    let cancel_action = MenuItem::with_id(
+        app,
+        "cancel_action",
+        "Cancel Pending Action",
+        idle.cancel,
+        None::<&str>,
+    )?;
+    let acknowledge = MenuItem::with_id(
+        app,
+        "acknowledge_pre_warning",
+        "I'm Aware",
+        idle.acknowledge,
+        None::<&str>,
+    )?;
+    let pause_items = PAUSE_OPTIONS
+        .iter()
+        .map(|(id, label, _)| MenuItem::with_id(app, *id, *label, true, None::<&str>))
+        .collect::<Result<Vec<_>, _>>()?;
+    let pause_refs: Vec<&dyn IsMenuItem<Wry>> = pause_items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<Wry>)
+        .collect();
+    let pause_for = Submenu::with_items(app, "Pause for…", idle.pause, &pause_refs)?;
+    let resume_monitoring = MenuItem::with_id(
+        app,
+        "resume_monitoring",
+        "Resume Monitoring",
+        idle.resume,
+        None::<&str>,
+    )?;
+
+    // Parameterless actions only; the rest need the window to configure.
+    let mut action_items = Vec::new();
+    for info in flowwatcher_platform::all_system_actions()
+        .iter()
+        .map(|action| action.info())
+        .filter(|info| info.available && info.params_schema.is_none())
+    {
+        let item = CheckMenuItem::with_id(
+            app,
+            format!("{ACTION_ITEM_PREFIX}{}", info.id),
+            &info.name,
+            true,
+            false,
+            None::<&str>,
+        )?;
+        action_items.push((info.id, item));
+    }
+    let action_refs: Vec<&dyn IsMenuItem<Wry>> = action_items
+        .iter()
+        .map(|(_, item)| item as &dyn IsMenuItem<Wry>)
+        .collect();
+    let action_menu = Submenu::with_items(app, "Action", true, &action_refs)?;
+
     let open_dashboard =
         MenuItem::with_id(app, "open_dashboard", "Open Dashboard", true, None::<&str>)?;
     let separator = PredefinedMenuItem::separator(app)?;
     let separator2 = PredefinedMenuItem::separator(app)?;
+    let separator3 = PredefinedMenuItem::separator(app)?;
     let quit = MenuItem::with_id(app, "quit", "Exit", true, None::<&str>)?;
 
     // ── Context menu ──
@@ -41,17 +416,26 @@ pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
         &[
             &start_monitoring,
             &stop_monitoring,
+            &cancel_action,
+            &acknowledge,
             &separator,
-            &open_dashboard,
+            &pause_for,
+            &resume_monitoring,
+            &action_menu,
             &separator2,
+            &open_dashboard,
+            &separator3,
             &quit,
         ],
     )?;
 
     // ── Build tray icon ──
-    let _tray = TrayIconBuilder::new()
-        .icon(app.default_window_icon().unwrap().clone())
-        .tooltip("FlowWatcher — Idle")
+    let tray = TrayIconBuilder::with_id(TRAY_ID)
+        .icon(TrayIconKind::Idle.image())
+        .tooltip(tooltip_for(
+            &MonitoringStatus::Idle,
+            &TrayContext::default(),
+        ))
         .menu(&menu)
         .show_menu_on_left_click(false)
         .on_menu_event(|app, event| match event.id.as_ref() {
@@ -62,6 +446,63 @@ pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
             "stop_monitoring" => {
                 let _ = app.emit("tray-stop-monitoring", ());
             }
+            "cancel_action" => {
+                // Countdown notifications point here, since desktop
+                // notifications cannot carry a Cancel button.
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let state = app.state::<AppState>();
+                    let result = match state.operations.try_begin() {
+                        Ok(_permit) => {
+                            commands::cancel_pending(&app, &state, CancelReason::UserTray).await
+                        }
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = result {
+                        log::warn!("Tray cancel failed: {e}");
+                    }
+                });
+            }
+            "acknowledge_pre_warning" => {
+                // The pre-warning notification points here, for the same
+                // reason as "cancel_action".
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let state = app.state::<AppState>();
+                    if let Err(e) = commands::acknowledge_pending(&app, &state).await {
+                        log::warn!("Tray acknowledge failed: {e}");
+                    }
+                });
+            }
+            "resume_monitoring" => {
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let state = app.state::<AppState>();
+                    if let Err(e) = commands::resume_session(&app, &state).await {
+                        log::warn!("Tray resume failed: {e}");
+                    }
+                });
+            }
+            id if PAUSE_OPTIONS.iter().any(|(option, ..)| *option == id) => {
+                let duration = PAUSE_OPTIONS
+                    .iter()
+                    .find(|(option, ..)| *option == id)
+                    .and_then(|(_, _, duration)| *duration);
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    let state = app.state::<AppState>();
+                    if let Err(e) = commands::pause_for(&app, &state, duration).await {
+                        log::warn!("Tray pause failed: {e}");
+                    }
+                });
+            }
+            id if id.starts_with(ACTION_ITEM_PREFIX) => {
+                let action_id = id[ACTION_ITEM_PREFIX.len()..].to_string();
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    select_action(&app, &action_id).await;
+                });
+            }
             "open_dashboard" => {
                 if let Some(window) = app.get_webview_window("main") {
                     let _ = window.unminimize();
@@ -70,6 +511,7 @@ pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
             "quit" => {
+                crate::shutdown::shutdown(app);
                 app.exit(0);
             }
             _ => {}
@@ -92,5 +534,118 @@ pub fn setup_tray(app: &App) -> Result<(), Box<dyn std::error::Error>> {
         })
         .build(app)?;
 
+    *app.state::<AppState>().tray.lock() = Some(TrayHandles {
+        tray,
+        start: start_monitoring,
+        stop: stop_monitoring,
+        cancel: cancel_action,
+        acknowledge,
+        pause: pause_for,
+        resume: resume_monitoring,
+        actions: action_items,
+        icon: TrayIconKind::Idle,
+        menu: idle,
+    });
     Ok(())
 }
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(action_name: &str) -> TrayContext {
+        TrayContext {
+            action_name: Some(action_name.to_string()),
+            ..TrayContext::default()
+        }
+    }
+
+    #[test]
+    fn tooltip_shows_live_seconds() {
+        let countdown = MonitoringStatus::Countdown {
+            remaining_secs: 42,
+            deadline_epoch_ms: None,
+        };
+        assert_eq!(
+            tooltip_for(&countdown, &context("Shut Down")),
+            "FlowWatcher\nCountdown: 00:42 — Shut Down"
+        );
+        let deadline_epoch_ms = 1_700_000_042_000;
+        let countdown = MonitoringStatus::Countdown {
+            remaining_secs: 42,
+            deadline_epoch_ms: Some(deadline_epoch_ms),
+        };
+        let at = local_time(deadline_epoch_ms).unwrap();
+        assert_eq!(at.len(), "23:41:12".len());
+        assert_eq!(
+            tooltip_for(&countdown, &context("Shut Down")),
+            format!("FlowWatcher\nCountdown: 00:42 — Shut Down at {at}")
+        );
+        let pending = MonitoringStatus::TriggerPending {
+            seconds_until_countdown: 125,
+        };
+        assert_eq!(
+            tooltip_for(&pending, &context("Sleep")),
+            "FlowWatcher\nSleep countdown in 02:05"
+        );
+        assert_eq!(
+            tooltip_for(&MonitoringStatus::Idle, &context("Sleep")),
+            "FlowWatcher\nIdle"
+        );
+    }
+
+    #[test]
+    fn tooltip_shows_interface_and_speed_while_monitoring() {
+        let context = TrayContext {
+            interface: Some("eth0".into()),
+            download_bps: Some(1_258_291),
+            ..TrayContext::default()
+        };
+        assert_eq!(
+            tooltip_for(&MonitoringStatus::Monitoring, &context),
+            "FlowWatcher\nMonitoring eth0 — 1.20 MB/s down"
+        );
+        assert_eq!(
+            tooltip_for(&MonitoringStatus::Monitoring, &TrayContext::default()),
+            "FlowWatcher\nMonitoring"
+        );
+    }
+
+    #[test]
+    fn icon_and_menu_follow_status() {
+        let countdown = MonitoringStatus::Countdown {
+            remaining_secs: 5,
+            deadline_epoch_ms: None,
+        };
+        assert_eq!(
+            TrayIconKind::for_status(&countdown),
+            TrayIconKind::Countdown
+        );
+        assert_eq!(
+            TrayIconKind::for_status(&MonitoringStatus::Paused),
+            TrayIconKind::Idle
+        );
+        assert_eq!(
+            TrayIconKind::for_status(&MonitoringStatus::Monitoring),
+            TrayIconKind::Monitoring
+        );
+
+        let idle = MenuAvailability::for_status(&MonitoringStatus::Idle);
+        assert!(idle.start && !idle.stop && !idle.cancel && !idle.pause && !idle.resume);
+        let paused = MenuAvailability::for_status(&MonitoringStatus::Paused);
+        assert!(paused.pause && paused.resume && !paused.start);
+        let monitoring = MenuAvailability::for_status(&MonitoringStatus::Monitoring);
+        assert!(!monitoring.start && monitoring.stop && !monitoring.cancel);
+        assert!(!monitoring.acknowledge);
+        let countdown_menu = MenuAvailability::for_status(&countdown);
+        assert!(countdown_menu.cancel && !countdown_menu.acknowledge);
+        let pending = MenuAvailability::for_status(&MonitoringStatus::TriggerPending {
+            seconds_until_countdown: 10,
+        });
+        assert!(pending.cancel && pending.acknowledge);
+    }
+}
@@ -0,0 +1,406 @@
+//! Opt-in update check against a small release manifest.
+//!
+//! Fetches a JSON manifest (`{ "version": "0.2.0", "notes_url": "…" }`)
+//! and compares it against the running version. Nothing is downloaded.
+//! Checks are rate-limited to once per day and every failure is silent:
+//! the caller simply gets "no update available".
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Default manifest location (latest GitHub release asset).
+pub const DEFAULT_MANIFEST_URL: &str =
+    "https://github.com/IamAshrafee/FlowWatcher/releases/latest/download/latest.json";
+
+/// Minimum time between two network checks.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Timeout for the manifest request.
+pub const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+// ---------------------------------------------------------------------------
+// Data types
+// ---------------------------------------------------------------------------
+
+/// Result of an update check, sent to the frontend.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UpdateInfo {
+    /// Whether a newer version than the running one exists.
+    pub available: bool,
+    /// Latest published version, if the manifest could be read.
+    pub latest: Option<String>,
+    /// Link to the release notes, if provided by the manifest.
+    pub notes_url: Option<String>,
+}
+
+/// The release manifest as published.
+#[derive(Debug, Clone, Deserialize)]
+struct ReleaseManifest {
+    version: String,
+    #[serde(default)]
+    notes_url: Option<String>,
+}
+
+/// When the last check ran and what it found (for diagnostics).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpdateCheckRecord {
+    /// Seconds since the Unix epoch.
+    pub checked_at: u64,
+    /// The check result.
+    pub result: UpdateInfo,
+}
+
+// ---------------------------------------------------------------------------
+// Version comparison
+// ---------------------------------------------------------------------------
+
+/// A parsed `MAJOR.MINOR.PATCH[-PRE]` version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Version {
+    core: (u64, u64, u64),
+    pre: Option<String>,
+}
+
+/// Parse a semver-style version, tolerating a leading `v` and build metadata.
+fn parse_version(s: &str) -> Option<Version> {
+    let s = s.trim().trim_start_matches('v');
+    let s = s.split('+').next()?;
+    let (core, pre) = match s.split_once('-') {
+        Some((core, pre)) => (core, Some(pre.to_string())),
+        None => (s, None),
+    };
+    let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(Version {
+        core: (major, minor, patch),
+        pre,
+    })
+}
+
+/// Compare two versions semver-wise.
+///
+/// A pre-release sorts before its release (`1.0.0-beta < 1.0.0`);
+/// pre-release tags are compared lexically. Returns `None` if either
+/// string is not a valid version.
+pub fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    let a = parse_version(a)?;
+    let b = parse_version(b)?;
+    let ordering = a.core.cmp(&b.core).then_with(|| match (&a.pre, &b.pre) {
+        (None, None) => Ordering::Equal,
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(x), Some(y)) => x.cmp(y),
+    });
+    Some(ordering)
+}
+
+/// Whether `latest` is strictly newer than `current`.
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    compare_versions(latest, current) == Some(Ordering::Greater)
+}
+
+// ---------------------------------------------------------------------------
+// Fetching
+// ---------------------------------------------------------------------------
+
+/// Fetches the raw manifest body. Abstracted so checks can be tested offline.
+pub trait ManifestFetcher: Send + Sync {
+    /// Return the response body for `url`, or a description of the failure.
+    fn fetch(&self, url: &str) -> Result<String, String>;
+}
+
+/// Evaluate a manifest body against the running version.
+fn evaluate_manifest(body: &str, current: &str) -> Option<UpdateInfo> {
+    let manifest: ReleaseManifest = serde_json::from_str(body).ok()?;
+    parse_version(&manifest.version)?;
+    Some(UpdateInfo {
+        available: is_newer(&manifest.version, current),
+        latest: Some(manifest.version),
+        notes_url: manifest.notes_url,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// UpdateChecker
+// ---------------------------------------------------------------------------
+
+/// Rate-limited update checker.
+pub struct UpdateChecker {
+    fetcher: Arc<dyn ManifestFetcher>,
+    last: Option<(SystemTime, UpdateInfo)>,
+}
+
+impl UpdateChecker {
+    /// Create a checker that fetches through `fetcher`.
+    pub fn new(fetcher: Arc<dyn ManifestFetcher>) -> Self {
+        Self {
+            fetcher,
+            last: None,
+        }
+    }
+
+    /// Check for updates, at most once per [`CHECK_INTERVAL`].
+    ///
+    /// Within the interval the cached result is returned without touching
+    /// the network. Fetch or parse failures yield `UpdateInfo::default()`
+    /// and still count as a check, so a broken endpoint is not hammered.
+    pub fn check(&mut self, url: &str, current: &str, now: SystemTime) -> UpdateInfo {
+        if let Some((at, info)) = &self.last {
+            let elapsed = now.duration_since(*at).unwrap_or_default();
+            if elapsed < CHECK_INTERVAL {
+                return info.clone();
+            }
+        }
+
+        let info = match self.fetcher.fetch(url) {
+            Ok(body) => evaluate_manifest(&body, current).unwrap_or_else(|| {
+                log::debug!("Ignoring malformed update manifest from {url}");
+                UpdateInfo::default()
+            }),
+            Err(e) => {
+                log::debug!("Update check failed: {e}");
+                UpdateInfo::default()
+            }
+        };
+        self.last = Some((now, info.clone()));
+        info
+    }
+
+    /// The most recent check, if any.
+    pub fn last_check(&self) -> Option<UpdateCheckRecord> {
+        self.last.as_ref().map(|(at, info)| UpdateCheckRecord {
+            checked_at: at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            result: info.clone(),
+        })
+    }
+}
+
+impl Default for UpdateChecker {
+    fn default() -> Self {
+        Self::new(Arc::new(HttpFetcher))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HTTP fetcher
+// ---------------------------------------------------------------------------
+
+/// Fetches manifests over HTTPS with a short timeout.
+pub struct HttpFetcher;
+
+impl ManifestFetcher for HttpFetcher {
+    fn fetch(&self, url: &str) -> Result<String, String> {
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(FETCH_TIMEOUT))
+            .build()
+            .into();
+        agent
+            .get(url)
+            .call()
+            .map_err(|e| e.to_string())?
+            .body_mut()
+            .read_to_string()
+            .map_err(|e| e.to_string())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri integration
+// ---------------------------------------------------------------------------
+
+/// Run a (rate-limited) check and emit `update-available` if one is found.
+pub async fn run_check(app: tauri::AppHandle) -> UpdateInfo {
+    use tauri::Manager;
+
+    let url = crate::commands::read_settings_value(&app)
+        .and_then(|s| {
+            s.get("update_manifest_url")
+                .and_then(|v| v.as_str())
+                .map(String::from)
+        })
+        .unwrap_or_else(|| DEFAULT_MANIFEST_URL.to_string());
+    let current = app.package_info().version.to_string();
+
+    let handle = app.clone();
+    let info = tauri::async_runtime::spawn_blocking(move || {
+        let state = handle.state::<crate::state::AppState>();
+        let mut checker = state.updater.lock().unwrap_or_else(|e| e.into_inner());
+        checker.check(&url, &current, SystemTime::now())
+    })
+    .await
+    .unwrap_or_default();
+
+    if info.available {
+        let state = app.state::<crate::state::AppState>();
+        state
+            .events
+            .emit(&app, crate::events::UPDATE_AVAILABLE, &info);
+    }
+    info
+}
+
+/// Spawn the background task that checks daily when the user opted in.
+pub fn spawn_background_check(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        // Re-evaluate the setting hourly; the checker itself limits network
+        // access to once per day.
+        let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+        loop {
+            interval.tick().await;
+            let enabled = crate::commands::read_settings_value(&app)
+                .and_then(|s| s.get("check_for_updates").and_then(|v| v.as_bool()))
+                .unwrap_or(false);
+            if enabled {
+                run_check(app.clone()).await;
+            }
+        }
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+    /// Returns a fixed response and counts calls.
+    struct MockFetcher {
+        response: Result<String, String>,
+        calls: AtomicUsize,
+    }
+
+    impl MockFetcher {
+        fn new(response: Result<&str, &str>) -> Arc<Self> {
+            Arc::new(Self {
+                response: response.map(String::from).map_err(String::from),
+                calls: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    impl ManifestFetcher for MockFetcher {
+        fn fetch(&self, _url: &str) -> Result<String, String> {
+            self.calls.fetch_add(1, AtomicOrdering::SeqCst);
+            self.response.clone()
+        }
+    }
+
+    const MANIFEST: &str = r#"{ "version": "0.2.0", "notes_url": "https://example.com/0.2.0" }"#;
+
+    #[test]
+    fn compares_core_versions() {
+        assert_eq!(compare_versions("0.2.0", "0.1.9"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("1.0.0", "1.0.0"), Some(Ordering::Equal));
+        assert_eq!(compare_versions("0.10.0", "0.9.0"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("1.2.3", "1.10.0"), Some(Ordering::Less));
+    }
+
+    #[test]
+    fn tolerates_prefix_and_short_forms() {
+        assert_eq!(compare_versions("v1.2", "1.2.0"), Some(Ordering::Equal));
+        assert_eq!(compare_versions("2", "1.9.9"), Some(Ordering::Greater));
+        assert_eq!(
+            compare_versions("1.0.0+build.5", "1.0.0"),
+            Some(Ordering::Equal)
+        );
+    }
+
+    #[test]
+    fn pre_release_sorts_before_release() {
+        assert!(is_newer("1.0.0", "1.0.0-beta.1"));
+        assert!(!is_newer("1.0.0-beta.1", "1.0.0"));
+        assert!(is_newer("1.0.0-rc.1", "1.0.0-beta.2"));
+    }
+
+    #[test]
+    fn invalid_versions_are_never_newer() {
+        assert_eq!(compare_versions("latest", "1.0.0"), None);
+        assert_eq!(compare_versions("1.x.0", "1.0.0"), None);
+        assert_eq!(compare_versions("1.2.3.4", "1.0.0"), None);
+        assert!(!is_newer("garbage", "0.1.0"));
+    }
+
+    #[test]
+    fn reports_available_update() {
+        let mut checker = UpdateChecker::new(MockFetcher::new(Ok(MANIFEST)));
+        let info = checker.check("url", "0.1.0", SystemTime::now());
+        assert_eq!(
+            info,
+            UpdateInfo {
+                available: true,
+                latest: Some("0.2.0".to_string()),
+                notes_url: Some("https://example.com/0.2.0".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn up_to_date_is_not_available() {
+        let mut checker = UpdateChecker::new(MockFetcher::new(Ok(MANIFEST)));
+        let info = checker.check("url", "0.2.0", SystemTime::now());
+        assert!(!info.available);
+        assert_eq!(info.latest.as_deref(), Some("0.2.0"));
+    }
+
+    #[test]
+    fn failures_are_silent() {
+        let mut checker = UpdateChecker::new(MockFetcher::new(Err("timed out")));
+        assert_eq!(
+            checker.check("url", "0.1.0", SystemTime::now()),
+            UpdateInfo::default()
+        );
+
+        let mut checker = UpdateChecker::new(MockFetcher::new(Ok("<html>")));
+        assert_eq!(
+            checker.check("url", "0.1.0", SystemTime::now()),
+            UpdateInfo::default()
+        );
+    }
+
+    #[test]
+    fn checks_are_rate_limited_to_once_per_day() {
+        let fetcher = MockFetcher::new(Ok(MANIFEST));
+        let mut checker = UpdateChecker::new(fetcher.clone());
+        let t0 = SystemTime::now();
+
+        checker.check("url", "0.1.0", t0);
+        let cached = checker.check("url", "0.1.0", t0 + Duration::from_secs(3600));
+        assert!(cached.available);
+        assert_eq!(fetcher.calls.load(AtomicOrdering::SeqCst), 1);
+
+        checker.check("url", "0.1.0", t0 + CHECK_INTERVAL);
+        assert_eq!(fetcher.calls.load(AtomicOrdering::SeqCst), 2);
+    }
+
+    #[test]
+    fn failed_check_is_also_rate_limited() {
+        let fetcher = MockFetcher::new(Err("offline"));
+        let mut checker = UpdateChecker::new(fetcher.clone());
+        let t0 = SystemTime::now();
+
+        checker.check("url", "0.1.0", t0);
+        checker.check("url", "0.1.0", t0 + Duration::from_secs(60));
+        assert_eq!(fetcher.calls.load(AtomicOrdering::SeqCst), 1);
+    }
+
+    #[test]
+    fn last_check_is_recorded() {
+        let mut checker = UpdateChecker::new(MockFetcher::new(Ok(MANIFEST)));
+        assert!(checker.last_check().is_none());
+
+        checker.check("url", "0.1.0", UNIX_EPOCH + Duration::from_secs(1_000));
+        let record = checker.last_check().unwrap();
+        assert_eq!(record.checked_at, 1_000);
+        assert!(record.result.available);
+    }
+}
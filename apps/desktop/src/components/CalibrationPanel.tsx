@@ -0,0 +1,108 @@
+/**
+ * CalibrationPanel — suggest a threshold from the machine's idle traffic.
+ *
+ * Asks the backend to sample the network for a short period while the
+ * user leaves the machine alone, then offers the suggested threshold
+ * with a one-click "Apply".
+ */
+
+import { useState } from 'react';
+import { useTranslation } from 'react-i18next';
+import { invoke } from '@tauri-apps/api/core';
+import { useMonitoringStore } from '@/stores/monitoringStore';
+import { formatSpeed } from '@/lib/format';
+import { errorMessage } from '@/lib/errors';
+import type { CalibrationResult } from '@/types';
+
+/** How long to sample idle traffic, in seconds. */
+const CALIBRATION_SECS = 30;
+
+function speedLabel(bps: number): string {
+  const { value, unit } = formatSpeed(bps);
+  return `${value} ${unit}`;
+}
+
+export function CalibrationPanel() {
+  const { t } = useTranslation();
+  const { config, updateCondition } = useMonitoringStore();
+  const [isRunning, setIsRunning] = useState(false);
+  const [result, setResult] = useState<CalibrationResult | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  async function handleCalibrate() {
+    setIsRunning(true);
+    setResult(null);
+    setError(null);
+    try {
+      const res = await invoke<CalibrationResult>('calibrate_idle_baseline', {
+        durationSecs: CALIBRATION_SECS,
+        monitorMode: config.condition.monitor_mode,
+      });
+      setResult(res);
+    } catch (err) {
+      setError(errorMessage(err));
+    } finally {
+      setIsRunning(false);
+    }
+  }
+
+  async function handleApply() {
+    if (!result) return;
+    updateCondition({ threshold_bytes_per_sec: result.suggested_threshold_bps });
+    try {
+      await invoke('apply_calibration', {
+        thresholdBytesPerSec: result.suggested_threshold_bps,
+      });
+    } catch {
+      // Silent fail in dev mode.
+    }
+    setResult(null);
+  }
+
+  return (
+    <div className="mt-3 flex flex-wrap items-center gap-3 text-sm">
+      <button
+        type="button"
+        onClick={handleCalibrate}
+        disabled={isRunning}
+        className="rounded-md px-3 py-1.5 font-medium transition-colors"
+        style={{
+          backgroundColor: 'var(--color-surface)',
+          color: isRunning ? 'var(--color-text-muted)' : 'var(--color-accent)',
+          border: '1px solid var(--color-border-default)',
+          cursor: isRunning ? 'default' : 'pointer',
+        }}
+      >
+        {isRunning
+          ? t('calibration.running', { seconds: CALIBRATION_SECS })
+          : t('calibration.start')}
+      </button>
+
+      {result && (
+        <>
+          <span style={{ color: 'var(--color-text-secondary)' }}>
+            {t('calibration.result', {
+              baseline: speedLabel(result.baseline_bps),
+              suggested: speedLabel(result.suggested_threshold_bps),
+              confidence: t(`calibration.confidence_${result.confidence}`),
+            })}
+          </span>
+          <button
+            type="button"
+            onClick={handleApply}
+            className="rounded-md px-3 py-1.5 font-medium"
+            style={{
+              backgroundColor: 'var(--color-accent)',
+              color: 'var(--color-text-inverse)',
+              cursor: 'pointer',
+            }}
+          >
+            {t('calibration.apply')}
+          </button>
+        </>
+      )}
+
+      {error && <span style={{ color: 'var(--color-warning)' }}>{error}</span>}
+    </div>
+  );
+}
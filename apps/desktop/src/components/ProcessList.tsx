@@ -1,15 +1,21 @@
 /**
  * ProcessList — Searchable, scrollable checklist of running processes.
  *
- * Shows processes sorted by network usage with "Suggested" badges.
- * Users check/uncheck processes to add to the watched list.
+ * Shows processes sorted by network usage with "Suggested" badges,
+ * one entry per executable. The search runs on the backend and the list
+ * is fetched a page at a time. Users check/uncheck processes to add to
+ * the watched list.
  */
 
-import { useMemo } from 'react';
+import { useEffect, useMemo } from 'react';
 import { useTranslation } from 'react-i18next';
 import { useProcessStore } from '@/stores/processStore';
+import { useProcesses } from '@/hooks/useTauri';
 import { formatSpeed } from '@/lib/format';
 
+/** Delay after the last keystroke before searching on the backend. */
+const SEARCH_DEBOUNCE_MS = 300;
+
 // ---------------------------------------------------------------------------
 // Component
 // ---------------------------------------------------------------------------
@@ -23,10 +29,21 @@ export function ProcessList() {
     setSearchQuery,
     toggleWatched,
     isLoading,
+    processTotal,
+    processLimit,
+    showMoreProcesses,
   } = useProcessStore();
   const { t } = useTranslation();
+  const { fetchProcesses } = useProcesses();
+
+  // Refetch when the search or the page size changes.
+  useEffect(() => {
+    const timer = setTimeout(fetchProcesses, SEARCH_DEBOUNCE_MS);
+    return () => clearTimeout(timer);
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [searchQuery, processLimit]);
 
-  // Filter and sort: suggested first, then by estimated bytes descending.
+  // Filter and sort: suggested first, then by activity descending.
   const filteredProcesses = useMemo(() => {
     const query = searchQuery.toLowerCase();
     return processList
@@ -41,7 +58,7 @@ export function ProcessList() {
           return a.is_suggested ? -1 : 1;
         }
         // Then by usage descending.
-        return b.estimated_network_bytes - a.estimated_network_bytes;
+        return b.activity_bps - a.activity_bps;
       });
   }, [processList, excludedProcesses, searchQuery]);
 
@@ -123,7 +140,8 @@ export function ProcessList() {
 
         {filteredProcesses.map((proc) => {
           const isChecked = watchedProcesses.includes(proc.name);
-          const { value, unit } = formatSpeed(proc.estimated_network_bytes);
+          const isNetwork = proc.network_bytes_per_sec !== null;
+          const { value, unit } = formatSpeed(proc.activity_bps);
 
           return (
             <label
@@ -176,22 +194,36 @@ export function ProcessList() {
                     color: 'var(--color-text-muted)',
                   }}
                 >
-                  {t('processList.pid', { pid: proc.pid })}
+                  {proc.instance_count > 1
+                    ? t('processList.instances', { count: proc.instance_count })
+                    : t('processList.pid', { pid: proc.pid })}
                 </span>
               </div>
 
-              {/* Network usage */}
+              {/* Network usage, or disk I/O when it cannot be measured */}
               <span
                 className="shrink-0 text-xs tabular-nums"
                 style={{
                   color: 'var(--color-text-secondary)',
                 }}
+                title={isNetwork ? undefined : t('processList.diskHint')}
               >
-                {value} {unit} ↓
+                {t(isNetwork ? 'processList.network' : 'processList.disk', { value, unit })}
               </span>
             </label>
           );
         })}
+
+        {processList.length < processTotal && (
+          <button
+            type="button"
+            onClick={showMoreProcesses}
+            className="w-full px-3 py-2 text-xs font-medium"
+            style={{ color: 'var(--color-accent)', cursor: 'pointer' }}
+          >
+            {t('processList.showMore', { shown: processList.length, total: processTotal })}
+          </button>
+        )}
       </div>
     </div>
   );
@@ -16,9 +16,21 @@ interface SpeedCardProps {
   history?: number[];
   /** Detected network interface name. */
   interfaceName?: string;
+  /** Speed is approaching the idle threshold (shown in amber). */
+  warning?: boolean;
+  /** Tooltip explaining the warning state. */
+  warningLabel?: string;
 }
 
-export function SpeedCard({ label, bps, icon, history = [], interfaceName }: SpeedCardProps) {
+export function SpeedCard({
+  label,
+  bps,
+  icon,
+  history = [],
+  interfaceName,
+  warning = false,
+  warningLabel,
+}: SpeedCardProps) {
   const { value, unit } = formatSpeed(bps);
 
   return (
@@ -58,7 +70,11 @@ export function SpeedCard({ label, bps, icon, history = [], interfaceName }: Spe
       </div>
 
       {/* Speed value */}
-      <p className="mt-2 text-3xl font-bold tabular-nums" style={{ color: 'var(--color-accent)' }}>
+      <p
+        className="mt-2 text-3xl font-bold tabular-nums"
+        style={{ color: warning ? 'var(--color-warning)' : 'var(--color-accent)' }}
+        title={warning ? warningLabel : undefined}
+      >
         {value}
       </p>
 
@@ -14,11 +14,18 @@ import { useEffect, useState } from 'react';
 
 export type ToastType = 'info' | 'warning' | 'success' | 'error';
 
+/** A button shown in the toast; clicking it also dismisses the toast. */
+export interface ToastAction {
+  label: string;
+  onClick: () => void;
+}
+
 export interface Toast {
   id: string;
   message: string;
   type: ToastType;
   duration?: number;
+  action?: ToastAction;
 }
 
 // ---------------------------------------------------------------------------
@@ -35,10 +42,15 @@ function notify() {
   listeners.forEach((l) => l([...toasts]));
 }
 
-/** Add a toast notification. */
-export function showToast(message: string, type: ToastType = 'info', duration = 5000) {
+/** Add a toast notification. Returns its id for `dismissToast`. */
+export function showToast(
+  message: string,
+  type: ToastType = 'info',
+  duration = 5000,
+  action?: ToastAction
+): string {
   const id = `toast-${++nextId}`;
-  toasts = [...toasts, { id, message, type, duration }];
+  toasts = [...toasts, { id, message, type, duration, action }];
   notify();
 
   if (duration > 0) {
@@ -46,6 +58,7 @@ export function showToast(message: string, type: ToastType = 'info', duration =
       dismissToast(id);
     }, duration);
   }
+  return id;
 }
 
 /** Remove a toast by id. */
@@ -161,6 +174,28 @@ export function ToastContainer() {
             >
               {toast.message}
             </p>
+            {toast.action && (
+              <button
+                type="button"
+                onClick={() => {
+                  toast.action?.onClick();
+                  dismissToast(toast.id);
+                }}
+                style={{
+                  background: 'none',
+                  border: `1px solid ${style.border}`,
+                  borderRadius: '6px',
+                  color: style.color,
+                  cursor: 'pointer',
+                  fontSize: '12px',
+                  fontWeight: 600,
+                  padding: '2px 8px',
+                  flexShrink: 0,
+                }}
+              >
+                {toast.action.label}
+              </button>
+            )}
             <button
               type="button"
               onClick={() => dismissToast(toast.id)}
@@ -20,6 +20,7 @@ import {
   durationToSeconds,
 } from '@/lib/format';
 import { InlineSelect, InlineNumberInput } from './InlineSelect';
+import { CalibrationPanel } from './CalibrationPanel';
 
 // ---------------------------------------------------------------------------
 // Static options (these don't come from backend)
@@ -29,6 +30,7 @@ const MONITOR_MODE_OPTIONS = [
   { value: 'download_only' as const, label: 'Download' },
   { value: 'upload_only' as const, label: 'Upload' },
   { value: 'both' as const, label: 'Download + Upload' },
+  { value: 'either' as const, label: 'Download or Upload' },
 ];
 
 const THRESHOLD_UNIT_OPTIONS = [
@@ -49,6 +51,7 @@ const FALLBACK_ACTION_OPTIONS = [
   { value: 'hibernate', label: 'Hibernate' },
   { value: 'sign_out', label: 'Sign Out' },
   { value: 'lock_screen', label: 'Lock Screen' },
+  { value: 'turn_off_display', label: 'Turn Off Display' },
 ];
 
 // ---------------------------------------------------------------------------
@@ -157,12 +160,14 @@ export function TriggerBuilder() {
         />
         , {t('trigger.then')}{' '}
         <InlineSelect
-          value={config.action_type}
+          value={typeof config.action_type === 'string' ? config.action_type : 'sequence'}
           options={actionOptions}
           onChange={(action) => updateConfig({ action_type: action })}
         />{' '}
         {t('trigger.thePC')}
       </p>
+
+      {!(isProcessModeEnabled && watchedProcesses.length > 0) && <CalibrationPanel />}
     </div>
   );
 }
@@ -4,13 +4,17 @@
  * States: idle → pre-warning → countdown → executed/cancelled
  *
  * The hook manages the countdown timer on the frontend side,
- * calling Tauri commands for cancel/execute actions.
+ * calling Tauri commands for cancel/execute actions. Acknowledging the
+ * pre-warning is left to the backend scheduler, which emits
+ * 'ack-requested' (see the dashboard page).
  */
 
 import { useState, useRef, useCallback, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { showToast } from '@/components/ToastNotification';
 import { useMonitoringStore } from '@/stores/monitoringStore';
+import { errorMessage } from '@/lib/errors';
+import type { CancelReason } from '@/types';
 
 // ---------------------------------------------------------------------------
 // Types
@@ -29,6 +33,13 @@ interface CountdownState {
   actionName: string;
 }
 
+/** Ask the backend to play the sound for a countdown event (best-effort). */
+function playAlert(event: Record<string, unknown>) {
+  invoke('play_alert_for_event', { event }).catch(() => {
+    // Audio is optional.
+  });
+}
+
 // ---------------------------------------------------------------------------
 // Hook
 // ---------------------------------------------------------------------------
@@ -47,13 +58,17 @@ export function useCountdown() {
 
   // Resolve action name from config.
   const resolveActionName = useCallback((): string => {
-    const actionType = config.action_type;
+    const actionType =
+      typeof config.action_type === 'string' ? config.action_type : 'sequence';
     const names: Record<string, string> = {
       shutdown: 'Shutting down...',
       restart: 'Restarting...',
       sleep: 'Sleeping...',
       hibernate: 'Hibernating...',
       lock_screen: 'Locking screen...',
+      turn_off_display: 'Turning off display...',
+      kill_process: 'Closing processes...',
+      sequence: 'Running actions...',
       log_off: 'Logging off...',
     };
     return names[actionType] || `Executing ${actionType}...`;
@@ -75,17 +90,51 @@ export function useCountdown() {
   const handleExecute = useCallback(
     async (actionName: string) => {
       clearTimers();
+      // The backend logs the outcome and reports the new status.
+      try {
+        const sessionToken = useMonitoringStore.getState().sessionToken;
+        await invoke('execute_action_now', { sessionToken });
+        showToast(`${actionName.replace('...', '')} executed.`, 'info', 4000);
+      } catch (e) {
+        showToast(`${actionName.replace('...', '')} failed: ${errorMessage(e)}`, 'error', 6000);
+      }
+    },
+    [clearTimers]
+  );
+
+  // Cancel the countdown, recording why.
+  const cancelWithReason = useCallback(
+    async (reason: CancelReason, message: string) => {
+      clearTimers();
+      setState((s) => ({ ...s, phase: 'cancelled', remainingSeconds: 0 }));
       try {
-        await invoke('execute_action_now');
+        const sessionToken = useMonitoringStore.getState().sessionToken;
+        await invoke('cancel_action', { sessionToken, reason });
       } catch {
-        // May fail if no action is actually scheduled on the backend.
+        // May fail if nothing to cancel.
       }
-      showToast(`${actionName.replace('...', '')} executed.`, 'info', 4000);
-      useMonitoringStore.getState().setStatus({ status: 'Executed' });
+      showToast(message, 'success', 4000);
+      useMonitoringStore.getState().setStatus({ status: 'Paused' });
+
+      // Reset to idle after a brief moment.
+      setTimeout(() => {
+        setState({
+          phase: 'idle',
+          remainingSeconds: 0,
+          totalSeconds: 0,
+          actionName: '',
+        });
+      }, 1000);
     },
     [clearTimers]
   );
 
+  // Cancel the countdown from the app window.
+  const cancel = useCallback(
+    () => cancelWithReason('user_ui', 'Action cancelled. Monitoring paused.'),
+    [cancelWithReason]
+  );
+
   // Start the countdown flow (pre-warning → countdown).
   const startCountdown = useCallback(() => {
     const actionName = resolveActionName();
@@ -108,13 +157,14 @@ export function useCountdown() {
       preWarningSecs * 1000
     );
 
-    // After pre-warning period, start countdown.
+    // Phase 2: Countdown.
     preWarningTimerRef.current = setTimeout(() => {
       setState((s) => ({
         ...s,
         phase: 'countdown',
         remainingSeconds: countdownSecs,
       }));
+      playAlert({ CountdownStarted: { total_seconds: countdownSecs } });
 
       // Tick down every second.
       timerRef.current = setInterval(() => {
@@ -125,6 +175,7 @@ export function useCountdown() {
             handleExecute(actionName);
             return { ...prev, phase: 'executed', remainingSeconds: 0 };
           }
+          playAlert({ CountdownTick: { remaining_seconds: prev.remainingSeconds - 1 } });
           return {
             ...prev,
             remainingSeconds: prev.remainingSeconds - 1,
@@ -134,29 +185,6 @@ export function useCountdown() {
     }, preWarningSecs * 1000);
   }, [config, resolveActionName, clearTimers, handleExecute]);
 
-  // Cancel the countdown.
-  const cancel = useCallback(async () => {
-    clearTimers();
-    setState((s) => ({ ...s, phase: 'cancelled', remainingSeconds: 0 }));
-    try {
-      await invoke('cancel_action');
-    } catch {
-      // May fail if nothing to cancel.
-    }
-    showToast('Action cancelled. Monitoring paused.', 'success', 4000);
-    useMonitoringStore.getState().setStatus({ status: 'Paused' });
-
-    // Reset to idle after a brief moment.
-    setTimeout(() => {
-      setState({
-        phase: 'idle',
-        remainingSeconds: 0,
-        totalSeconds: 0,
-        actionName: '',
-      });
-    }, 1000);
-  }, [clearTimers]);
-
   // Execute now (skip remaining countdown).
   const executeNow = useCallback(async () => {
     const actionName = state.actionName;
@@ -1,48 +1,101 @@
 /**
  * Tauri integration hooks — bridges the Zustand store with the Rust backend.
  *
- * - `useSpeedPolling()` — Polls `get_current_speed` every 1s.
+ * - `useSpeedUpdates()` — Subscribes to the backend's 1s `speed-update` and
+ *   `monitoring-tick` events.
  * - `useAppInit()` — Fetches available triggers, actions, and interface name on mount.
  */
 
-import { useEffect, useRef } from 'react';
+import { useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { useMonitoringStore } from '@/stores/monitoringStore';
 import { useProcessStore } from '@/stores/processStore';
-import type { SpeedData, ActionInfo, TriggerInfo, NetworkInterface, ProcessInfo } from '@/types';
+import type {
+  SpeedUpdateEvent,
+  MonitoringTick,
+  ActionInfo,
+  TriggerInfo,
+  NetworkInterface,
+  ProcessPage,
+} from '@/types';
 
 // ---------------------------------------------------------------------------
-// Speed polling (1-second interval)
+// Speed updates (pushed every second by the backend)
 // ---------------------------------------------------------------------------
 
 /**
- * Polls the backend for current network speed every second and updates
- * the Zustand store. The polling runs only while the component is mounted.
+ * Subscribes to the backend's `speed-update` events and updates the Zustand
+ * store. The backend only polls while a window is subscribed, so the
+ * subscription is dropped while the page is hidden and on unmount. The
+ * threshold zone comes from the monitoring loop's `monitoring-tick`.
  */
-export function useSpeedPolling() {
+export function useSpeedUpdates() {
   const setCurrentSpeed = useMonitoringStore((s) => s.setCurrentSpeed);
   const addSpeedSample = useMonitoringStore((s) => s.addSpeedSample);
-  const intervalRef = useRef<ReturnType<typeof setInterval> | null>(null);
+  const setZone = useMonitoringStore((s) => s.setZone);
+  const setIdleEstimate = useMonitoringStore((s) => s.setIdleEstimate);
 
   useEffect(() => {
-    async function poll() {
-      try {
-        const speed = await invoke<SpeedData>('get_current_speed');
-        setCurrentSpeed(speed);
-        addSpeedSample(speed);
-      } catch {
-        // Silently ignore — backend may not be ready yet.
+    let unlisten: (() => void) | null = null;
+    let unlistenTick: (() => void) | null = null;
+    let cancelled = false;
+
+    function subscribe() {
+      invoke('subscribe_speed_updates').catch(() => {
+        // Backend may not be ready yet.
+      });
+    }
+
+    function unsubscribe() {
+      invoke('unsubscribe_speed_updates').catch(() => {});
+    }
+
+    function onVisibilityChange() {
+      if (document.hidden) {
+        unsubscribe();
+      } else {
+        subscribe();
       }
     }
 
-    // Fire immediately, then every 1 second.
-    poll();
-    intervalRef.current = setInterval(poll, 1000);
+    listen<SpeedUpdateEvent>('speed-update', (event) => {
+      setCurrentSpeed(event.payload);
+      addSpeedSample(event.payload);
+    })
+      .then((fn) => {
+        if (cancelled) {
+          fn();
+        } else {
+          unlisten = fn;
+        }
+      })
+      .catch(() => {});
+    listen<MonitoringTick>('monitoring-tick', (event) => {
+      setZone(event.payload.zone);
+      setIdleEstimate(event.payload.estimated_idle_at_ms, event.payload.estimate_confidence);
+    })
+      .then((fn) => {
+        if (cancelled) {
+          fn();
+        } else {
+          unlistenTick = fn;
+        }
+      })
+      .catch(() => {});
+    if (!document.hidden) {
+      subscribe();
+    }
+    document.addEventListener('visibilitychange', onVisibilityChange);
 
     return () => {
-      if (intervalRef.current) clearInterval(intervalRef.current);
+      cancelled = true;
+      document.removeEventListener('visibilitychange', onVisibilityChange);
+      unsubscribe();
+      if (unlisten) unlisten();
+      if (unlistenTick) unlistenTick();
     };
-  }, [setCurrentSpeed, addSpeedSample]);
+  }, [setCurrentSpeed, addSpeedSample, setZone, setIdleEstimate]);
 }
 
 // ---------------------------------------------------------------------------
@@ -70,8 +123,9 @@ export function useAppInit() {
         setAvailableTriggers(triggers);
         setAvailableActions(actions);
 
-        // Pick the first "up" interface, or the first one overall.
-        const active = interfaces.find((i) => i.is_up) ?? interfaces[0];
+        // Prefer an interface with traffic right now, then any "up" one.
+        const active =
+          interfaces.find((i) => i.active_now) ?? interfaces.find((i) => i.is_up) ?? interfaces[0];
         if (active) {
           setInterfaceName(active.name);
         }
@@ -91,14 +145,22 @@ export function useAppInit() {
 /**
  * Fetches running processes from the backend and updates the process store.
  * Call this when the Advanced tab is active or when a refresh is needed.
+ *
+ * Instances of the same executable are grouped, the store's search query is
+ * applied on the backend, and only the first `processLimit` entries are
+ * fetched.
  */
 export function useProcesses() {
   async function fetchProcesses() {
     const store = useProcessStore.getState();
     store.setIsLoading(true);
     try {
-      const processes = await invoke<ProcessInfo[]>('get_running_processes');
-      useProcessStore.getState().setProcessList(processes);
+      const page = await invoke<ProcessPage>('get_running_processes', {
+        filter: store.searchQuery || null,
+        group: true,
+        limit: store.processLimit,
+      });
+      useProcessStore.getState().setProcessPage(page);
     } catch {
       // Backend may not be ready.
     } finally {
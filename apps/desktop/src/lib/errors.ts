@@ -0,0 +1,25 @@
+/**
+ * Helpers for errors rejected by Tauri commands.
+ */
+
+import type { CommandError, CommandErrorCode } from '@/types';
+
+/** Whether `err` is a structured error from a Tauri command. */
+export function isCommandError(err: unknown): err is CommandError {
+  return (
+    typeof err === 'object' &&
+    err !== null &&
+    typeof (err as CommandError).code === 'string' &&
+    typeof (err as CommandError).message === 'string'
+  );
+}
+
+/** The stable code of a command error, or `undefined` for other errors. */
+export function errorCode(err: unknown): CommandErrorCode | undefined {
+  return isCommandError(err) ? err.code : undefined;
+}
+
+/** A message suitable for display, whatever was thrown. */
+export function errorMessage(err: unknown): string {
+  return isCommandError(err) ? err.message : String(err);
+}
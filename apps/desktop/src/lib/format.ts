@@ -78,6 +78,9 @@ export function actionLabel(actionType: string): string {
     hibernate: 'Hibernate',
     sign_out: 'Sign Out',
     lock_screen: 'Lock Screen',
+    turn_off_display: 'Turn Off Display',
+    kill_process: 'Kill Process',
+    notify: 'Notify',
   };
   return labels[actionType] ?? actionType;
 }
@@ -13,16 +13,24 @@ import { listen } from '@tauri-apps/api/event';
 import { SpeedCard } from '@/components/SpeedCard';
 import { TriggerBuilder } from '@/components/TriggerBuilder';
 import { CountdownDialog } from '@/components/CountdownDialog';
-import { ToastContainer } from '@/components/ToastNotification';
+import { ToastContainer, showToast } from '@/components/ToastNotification';
 import { ProcessList } from '@/components/ProcessList';
 import { ExclusionList } from '@/components/ExclusionList';
 import { useMonitoringStore } from '@/stores/monitoringStore';
 import { useProcessStore } from '@/stores/processStore';
-import { useSpeedPolling, useAppInit, useProcesses } from '@/hooks/useTauri';
+import { useSpeedUpdates, useAppInit, useProcesses } from '@/hooks/useTauri';
 import { useCountdown } from '@/hooks/useCountdown';
 import { useSettingsStore } from '@/stores/settingsStore';
 import { useTheme } from '@/components/ThemeProvider';
-import type { LogEntry } from '@/types';
+import { errorCode, errorMessage } from '@/lib/errors';
+import type {
+  AckRequestedEvent,
+  AudioSettings,
+  FailoverEvent,
+  HttpApiSettings,
+  LogEntry,
+  MonitoringStarted,
+} from '@/types';
 
 // ---------------------------------------------------------------------------
 // Dashboard Page (Phase 6)
@@ -30,24 +38,43 @@ import type { LogEntry } from '@/types';
 
 export function DashboardPage() {
   const { t } = useTranslation();
-  const { currentSpeed, speedHistory, interfaceName, status, config, setStatus } =
-    useMonitoringStore();
+  const {
+    currentSpeed,
+    speedHistory,
+    zone,
+    estimatedIdleAtMs,
+    estimateConfidence,
+    interfaceName,
+    status,
+    config,
+    setStatus,
+    setSessionToken,
+    updateConfig,
+  } = useMonitoringStore();
   const isMonitoring = status.status === 'Monitoring';
   const isIdle = status.status === 'Idle';
+  const approachingIdle = !isIdle && zone === 'warning';
 
   // ── Tauri integration hooks ──
-  useSpeedPolling();
+  useSpeedUpdates();
   useAppInit();
 
   // ── Phase 11: Tray event listeners ──
   useEffect(() => {
     let unlistenStart: (() => void) | null = null;
     let unlistenStop: (() => void) | null = null;
+    let unlistenAction: (() => void) | null = null;
+    let unlistenPostponed: (() => void) | null = null;
+    let unlistenTimedOut: (() => void) | null = null;
+    let unlistenFailover: (() => void) | null = null;
+    let unlistenAck: (() => void) | null = null;
+    let unlistenFailed: (() => void) | null = null;
 
     (async () => {
       unlistenStart = await listen('tray-start-monitoring', async () => {
         try {
-          await invoke('start_monitoring', { config });
+          const started = await invoke<MonitoringStarted>('start_monitoring', { config });
+          setSessionToken(started.session_token);
           setStatus({ status: 'Monitoring' });
         } catch (err) {
           console.error('Tray start monitoring failed:', err);
@@ -56,18 +83,83 @@ export function DashboardPage() {
       unlistenStop = await listen('tray-stop-monitoring', async () => {
         try {
           await invoke('stop_monitoring');
+          setSessionToken(null);
           setStatus({ status: 'Idle' });
         } catch (err) {
           console.error('Tray stop monitoring failed:', err);
         }
       });
+      // The backend already switched a running session; keep the config in step.
+      unlistenAction = await listen<string>('tray-action-selected', (event) => {
+        updateConfig({ action_type: event.payload, action_params: null });
+      });
+      unlistenPostponed = await listen('action-postponed', () => {
+        showToast('Countdown postponed: user active', 'info', 5000);
+      });
+      unlistenTimedOut = await listen('action-timed-out', () => {
+        showToast('Action cancelled: still pending after the timeout', 'info', 5000);
+      });
+      // The backend scheduler holds the pre-warning until it is acknowledged.
+      unlistenAck = await listen<AckRequestedEvent>('ack-requested', (event) => {
+        const acknowledge = async () => {
+          try {
+            const sessionToken = useMonitoringStore.getState().sessionToken;
+            await invoke('acknowledge_pre_warning', { sessionToken });
+          } catch (err) {
+            showToast(`Acknowledge failed: ${errorMessage(err)}`, 'error', 6000);
+          }
+        };
+        showToast(
+          `Action pending. Acknowledge within ${event.payload.timeout_secs} seconds to continue.`,
+          'warning',
+          event.payload.timeout_secs * 1000,
+          { label: "I'm aware", onClick: acknowledge }
+        );
+      });
+      // A privileged action that failed can be run again through the
+      // elevated helper, which asks for administrator rights via UAC.
+      unlistenFailed = await listen('action-failed', () => {
+        const { config: current, availableActions } = useMonitoringStore.getState();
+        const actionId = current.action_type;
+        if (typeof actionId !== 'string') return;
+        const info = availableActions.find((a) => a.id === actionId);
+        if (!info?.requires_elevation) return;
+        const runElevated = async () => {
+          try {
+            await invoke('execute_action_elevated', { actionId });
+          } catch (err) {
+            if (errorCode(err) === 'elevation_cancelled') return;
+            showToast(`${info.name} failed: ${errorMessage(err)}`, 'error', 8000);
+          }
+        };
+        showToast(`${info.name} needs administrator rights.`, 'warning', 15000, {
+          label: 'Run as administrator',
+          onClick: runElevated,
+        });
+      });
+      unlistenFailover = await listen<FailoverEvent>('interface-failover', (event) => {
+        const e = event.payload;
+        if (e.kind === 'switched') {
+          showToast(`Interface ${e.from} went inactive; now monitoring ${e.to}`, 'info', 8000);
+        } else if (e.kind === 'inactive') {
+          showToast(`Monitored interface ${e.interface} inactive; idle detection paused`, 'warning', 8000);
+        } else {
+          showToast(`Interface ${e.interface} active again`, 'info', 5000);
+        }
+      });
     })();
 
     return () => {
       if (unlistenStart) unlistenStart();
       if (unlistenStop) unlistenStop();
+      if (unlistenAction) unlistenAction();
+      if (unlistenPostponed) unlistenPostponed();
+      if (unlistenTimedOut) unlistenTimedOut();
+      if (unlistenFailover) unlistenFailover();
+      if (unlistenAck) unlistenAck();
+      if (unlistenFailed) unlistenFailed();
     };
-  }, [config, setStatus]);
+  }, [config, setStatus, setSessionToken, updateConfig]);
 
   // ── Phase 8: Safety countdown ──
   const { countdownState, startCountdown, cancelCountdown, executeNow, isCountdownActive } =
@@ -76,14 +168,19 @@ export function DashboardPage() {
   async function handleToggleMonitoring() {
     try {
       if (isIdle) {
-        await invoke('start_monitoring', { config });
+        const started = await invoke<MonitoringStarted>('start_monitoring', { config });
+        started.warnings.forEach((w) => showToast(w.message, 'warning', 8000));
+        setSessionToken(started.session_token);
         setStatus({ status: 'Monitoring' });
       } else {
         await invoke('stop_monitoring');
+        setSessionToken(null);
         setStatus({ status: 'Idle' });
       }
     } catch (err) {
       console.error('Monitoring toggle failed:', err);
+      // A second click while the first start/stop is still in flight.
+      if (errorCode(err) === 'busy') return;
       // Fallback: update status locally even if invoke fails
       // (e.g. running in browser dev mode without Tauri runtime)
       if (isIdle) {
@@ -104,15 +201,32 @@ export function DashboardPage() {
           icon="download"
           history={speedHistory.map((s) => s.download_bps)}
           interfaceName={interfaceName}
+          warning={approachingIdle && config.condition.monitor_mode !== 'upload_only'}
+          warningLabel={t('dashboard.approachingIdle')}
         />
         <SpeedCard
           label={t('dashboard.upload')}
           bps={currentSpeed.upload_bps}
           icon="upload"
           history={speedHistory.map((s) => s.upload_bps)}
+          warning={approachingIdle && config.condition.monitor_mode !== 'download_only'}
+          warningLabel={t('dashboard.approachingIdle')}
         />
       </div>
 
+      {/* Time-remaining estimate for a declining transfer */}
+      {!isIdle && estimatedIdleAtMs !== null && (
+        <p
+          className="text-center text-sm"
+          style={{ color: 'var(--color-text-secondary)' }}
+          title={t(`dashboard.estimateConfidence.${estimateConfidence ?? 'low'}`)}
+        >
+          {t('dashboard.estimatedIdle', {
+            minutes: Math.max(1, Math.round((estimatedIdleAtMs - Date.now()) / 60000)),
+          })}
+        </p>
+      )}
+
       {/* Natural Language Trigger Builder */}
       <TriggerBuilder />
 
@@ -630,6 +744,21 @@ export function LogsPage() {
         >
           {t('logs.exportTXT')}
         </button>
+        <button
+          type="button"
+          onClick={() => handleExport('csv')}
+          disabled={logs.length === 0}
+          className="rounded-md px-3 py-1.5 text-xs font-medium transition-colors"
+          style={{
+            backgroundColor: 'var(--color-surface)',
+            color: 'var(--color-text-secondary)',
+            border: '1px solid var(--color-border-default)',
+            cursor: logs.length > 0 ? 'pointer' : 'not-allowed',
+            opacity: logs.length > 0 ? 1 : 0.5,
+          }}
+        >
+          {t('logs.exportCSV')}
+        </button>
       </div>
     </div>
   );
@@ -673,6 +802,27 @@ export function SettingsPage() {
     alert(t('settings.resetSuccess'));
   }
 
+  // Validate audio preferences in the backend before persisting them.
+  async function handleAudioChange(partial: Partial<AudioSettings>) {
+    const audio = { ...settings.audio, ...partial };
+    try {
+      await invoke('set_audio_settings', { settings: audio });
+    } catch (err) {
+      showToast(errorMessage(err), 'warning', 5000);
+      return;
+    }
+    updateSettings({ audio });
+  }
+
+  // Enabling the HTTP API without a token generates one.
+  function handleHttpApiChange(partial: Partial<HttpApiSettings>) {
+    const http_api = { ...settings.http_api, ...partial };
+    if (http_api.enabled && !http_api.token) {
+      http_api.token = generateApiToken();
+    }
+    updateSettings({ http_api });
+  }
+
   const LANGUAGE_OPTIONS = [{ code: 'en', label: 'English' }];
 
   return (
@@ -757,7 +907,15 @@ export function SettingsPage() {
         >
           <ToggleSwitch
             checked={settings.auto_start}
-            onChange={(v) => updateSettings({ auto_start: v })}
+            onChange={async (v) => {
+              updateSettings({ auto_start: v });
+              try {
+                await invoke('set_launch_at_login', { enabled: v });
+              } catch {
+                // Registration failed (e.g. access denied); keep the old state.
+                updateSettings({ auto_start: !v });
+              }
+            }}
           />
         </SettingsRow>
         <SettingsRow
@@ -792,6 +950,15 @@ export function SettingsPage() {
             }}
           />
         </SettingsRow>
+        <SettingsRow
+          label={t('settings.checkForUpdatesLabel')}
+          description={t('settings.checkForUpdatesDescription')}
+        >
+          <ToggleSwitch
+            checked={settings.check_for_updates}
+            onChange={(v) => updateSettings({ check_for_updates: v })}
+          />
+        </SettingsRow>
         <SettingsRow
           label={t('settings.autoSaveLabel')}
           description={t('settings.autoSaveDescription')}
@@ -821,6 +988,76 @@ export function SettingsPage() {
         </SettingsRow>
       </SettingsSection>
 
+      {/* Sound */}
+      <SettingsSection title={t('settings.sound')}>
+        <SettingsRow
+          label={t('settings.alertSoundLabel')}
+          description={t('settings.alertSoundDescription')}
+        >
+          <ToggleSwitch
+            checked={settings.audio.enabled}
+            onChange={(v) => handleAudioChange({ enabled: v })}
+          />
+        </SettingsRow>
+        <SettingsRow
+          label={t('settings.countdownBeepsLabel')}
+          description={t('settings.countdownBeepsDescription')}
+        >
+          <ToggleSwitch
+            checked={settings.audio.countdown_beeps}
+            onChange={(v) => handleAudioChange({ countdown_beeps: v })}
+          />
+        </SettingsRow>
+        <SettingsRow
+          label={t('settings.volumeLabel')}
+          description={t('settings.volumeDescription')}
+        >
+          <div className="flex items-center gap-2">
+            <input
+              type="range"
+              min={0}
+              max={100}
+              value={Math.round(settings.audio.volume * 100)}
+              onChange={(e) => handleAudioChange({ volume: parseInt(e.target.value) / 100 })}
+              aria-label={t('settings.volumeLabel')}
+              className="w-24"
+              style={{ accentColor: 'var(--color-accent)' }}
+            />
+            <button
+              type="button"
+              onClick={() => invoke('preview_alert_sound').catch(() => {})}
+              className="rounded-md px-2 py-1 text-xs font-medium"
+              style={{
+                backgroundColor: 'var(--color-surface)',
+                color: 'var(--color-text-secondary)',
+                border: '1px solid var(--color-border-default)',
+                cursor: 'pointer',
+              }}
+            >
+              {t('settings.testSound')}
+            </button>
+          </div>
+        </SettingsRow>
+        <SettingsRow
+          label={t('settings.customSoundLabel')}
+          description={t('settings.customSoundDescription')}
+        >
+          <input
+            type="text"
+            defaultValue={settings.audio.custom_sound_path ?? ''}
+            placeholder="C:\Sounds\chime.wav"
+            onBlur={(e) => handleAudioChange({ custom_sound_path: e.target.value.trim() || null })}
+            className="w-48 rounded-md px-2 py-1 text-xs"
+            style={{
+              backgroundColor: 'var(--color-surface)',
+              color: 'var(--color-text-primary)',
+              border: '1px solid var(--color-border-default)',
+              outline: 'none',
+            }}
+          />
+        </SettingsRow>
+      </SettingsSection>
+
       {/* Delays */}
       <SettingsSection title={t('settings.delays')}>
         <SettingsRow
@@ -853,6 +1090,78 @@ export function SettingsPage() {
         </SettingsRow>
       </SettingsSection>
 
+      {/* Integrations */}
+      <SettingsSection title={t('settings.integrations')}>
+        <SettingsRow
+          label={t('settings.httpApiLabel')}
+          description={t('settings.httpApiDescription')}
+        >
+          <ToggleSwitch
+            checked={settings.http_api.enabled}
+            onChange={(v) => handleHttpApiChange({ enabled: v })}
+          />
+        </SettingsRow>
+        <SettingsRow
+          label={t('settings.httpApiPortLabel')}
+          description={t('settings.httpApiPortDescription')}
+        >
+          <input
+            type="number"
+            min={1024}
+            max={65535}
+            defaultValue={settings.http_api.port}
+            onBlur={(e) => {
+              const port = parseInt(e.target.value);
+              if (port >= 1024 && port <= 65535) {
+                handleHttpApiChange({ port });
+              } else {
+                e.target.value = String(settings.http_api.port);
+              }
+            }}
+            className="w-20 rounded-md px-2 py-1 text-sm text-center"
+            style={{
+              backgroundColor: 'var(--color-surface)',
+              color: 'var(--color-text-primary)',
+              border: '1px solid var(--color-border-default)',
+              outline: 'none',
+            }}
+          />
+        </SettingsRow>
+        <SettingsRow
+          label={t('settings.httpApiTokenLabel')}
+          description={t('settings.httpApiTokenDescription')}
+        >
+          <div className="flex items-center gap-2">
+            <input
+              type="text"
+              readOnly
+              value={settings.http_api.token}
+              aria-label={t('settings.httpApiTokenLabel')}
+              className="w-48 rounded-md px-2 py-1 text-xs font-mono"
+              style={{
+                backgroundColor: 'var(--color-surface)',
+                color: 'var(--color-text-primary)',
+                border: '1px solid var(--color-border-default)',
+                outline: 'none',
+              }}
+            />
+            <button
+              type="button"
+              onClick={() => handleHttpApiChange({ token: generateApiToken() })}
+              className="rounded-md px-2 py-1 text-xs font-medium"
+              style={{
+                backgroundColor: 'var(--color-surface)',
+                color: 'var(--color-text-secondary)',
+                border: '1px solid var(--color-border-default)',
+                cursor: 'pointer',
+              }}
+            >
+              {t('settings.httpApiRegenerate')}
+            </button>
+          </div>
+        </SettingsRow>
+      </SettingsSection>
+
       {/* Data */}
       <SettingsSection title={t('settings.data')}>
         <div className="flex gap-2 pt-1">
@@ -911,8 +1220,14 @@ export function SettingsPage() {
                 await invoke('import_config', { configJson: json });
                 alert(t('logs.configImported'));
                 loadSettings();
-              } catch {
-                alert(t('logs.configImportFailed'));
+              } catch (err) {
+                // A newer export needs an app update; say so instead of a
+                // generic failure.
+                alert(
+                  errorCode(err) === 'unsupported_format_version'
+                    ? errorMessage(err)
+                    : t('logs.configImportFailed'),
+                );
               }
             }}
             className="rounded-md px-3 py-1.5 text-xs font-medium transition-colors"
@@ -966,6 +1281,12 @@ export function SettingsPage() {
 // Settings helper components
 // ---------------------------------------------------------------------------
 
+/** A random 32-character hex token for the HTTP API. */
+function generateApiToken(): string {
+  const bytes = crypto.getRandomValues(new Uint8Array(16));
+  return Array.from(bytes, (b) => b.toString(16).padStart(2, '0')).join('');
+}
+
 function SettingsSection({ title, children }: { title: string; children: React.ReactNode }) {
   return (
     <div
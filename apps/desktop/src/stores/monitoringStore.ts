@@ -13,6 +13,8 @@ import type {
   SpeedData,
   ActionInfo,
   TriggerInfo,
+  Zone,
+  EstimateConfidence,
 } from '@/types';
 
 // ---------------------------------------------------------------------------
@@ -28,10 +30,12 @@ const DEFAULT_CONFIG: MonitoringConfig = {
     threshold_bytes_per_sec: 200 * 1024, // 200 KB/s
     required_duration_secs: 120, // 2 minutes
     monitor_mode: 'download_only',
+    warning_multiplier: 2, // "approaching idle" below 2× the threshold
   },
   action_type: 'shutdown',
   pre_warning_secs: 60,
   countdown_secs: 30,
+  ack_policy: { policy: 'none' },
 };
 
 // ---------------------------------------------------------------------------
@@ -50,12 +54,20 @@ interface MonitoringState {
   currentSpeed: SpeedData;
   /** Historical speed samples for sparkline (most recent last). */
   speedHistory: SpeedData[];
+  /** Zone of the latest monitoring tick (null when not evaluated). */
+  zone: Zone | null;
+  /** When the declining transfer should go idle, from the latest tick (Unix ms). */
+  estimatedIdleAtMs: number | null;
+  /** How reliable `estimatedIdleAtMs` is. */
+  estimateConfidence: EstimateConfidence | null;
   /** Detected network interface name. */
   interfaceName: string;
   /** Available action types from backend. */
   availableActions: ActionInfo[];
   /** Available trigger types from backend. */
   availableTriggers: TriggerInfo[];
+  /** Token of the backend session started by this window (null when idle). */
+  sessionToken: number | null;
 
   // Actions
   updateConfig: (partial: Partial<MonitoringConfig>) => void;
@@ -63,9 +75,12 @@ interface MonitoringState {
   setStatus: (status: MonitoringStatus) => void;
   setCurrentSpeed: (speed: SpeedData) => void;
   addSpeedSample: (speed: SpeedData) => void;
+  setZone: (zone: Zone | null) => void;
+  setIdleEstimate: (atMs: number | null, confidence: EstimateConfidence | null) => void;
   setInterfaceName: (name: string) => void;
   setAvailableActions: (actions: ActionInfo[]) => void;
   setAvailableTriggers: (triggers: TriggerInfo[]) => void;
+  setSessionToken: (token: number | null) => void;
   resetConfig: () => void;
 }
 
@@ -78,9 +93,13 @@ export const useMonitoringStore = create<MonitoringState>((set) => ({
   status: { status: 'Idle' },
   currentSpeed: { download_bps: 0, upload_bps: 0 },
   speedHistory: [],
+  zone: null,
+  estimatedIdleAtMs: null,
+  estimateConfidence: null,
   interfaceName: 'Auto-detect',
   availableActions: [],
   availableTriggers: [],
+  sessionToken: null,
 
   updateConfig: (partial) => set((state) => ({ config: { ...state.config, ...partial } })),
 
@@ -98,8 +117,12 @@ export const useMonitoringStore = create<MonitoringState>((set) => ({
     set((state) => ({
       speedHistory: [...state.speedHistory.slice(-(MAX_SPEED_HISTORY - 1)), speed],
     })),
+  setZone: (zone) => set({ zone }),
+  setIdleEstimate: (estimatedIdleAtMs, estimateConfidence) =>
+    set({ estimatedIdleAtMs, estimateConfidence }),
   setInterfaceName: (interfaceName) => set({ interfaceName }),
   setAvailableActions: (availableActions) => set({ availableActions }),
   setAvailableTriggers: (availableTriggers) => set({ availableTriggers }),
+  setSessionToken: (sessionToken) => set({ sessionToken }),
   resetConfig: () => set({ config: DEFAULT_CONFIG }),
 }));
@@ -6,7 +6,7 @@
  */
 
 import { create } from 'zustand';
-import type { ProcessInfo } from '@/types';
+import type { ProcessInfo, ProcessPage } from '@/types';
 
 // ---------------------------------------------------------------------------
 // Store interface
@@ -21,6 +21,10 @@ interface ProcessState {
   excludedProcesses: string[];
   /** Processes fetched from the backend. */
   processList: ProcessInfo[];
+  /** Processes matching the search on the backend, across all pages. */
+  processTotal: number;
+  /** How many processes to fetch; grows with "show more". */
+  processLimit: number;
   /** Search/filter query for the process list. */
   searchQuery: string;
   /** Whether processes are currently being fetched. */
@@ -34,12 +38,16 @@ interface ProcessState {
   toggleWatched: (name: string) => void;
   addExcluded: (name: string) => void;
   removeExcluded: (name: string) => void;
-  setProcessList: (list: ProcessInfo[]) => void;
+  setProcessPage: (page: ProcessPage) => void;
+  showMoreProcesses: () => void;
   setSearchQuery: (query: string) => void;
   setIsLoading: (loading: boolean) => void;
   clearWatched: () => void;
 }
 
+/** Processes fetched per page of the process list. */
+export const PROCESS_PAGE_SIZE = 50;
+
 // ---------------------------------------------------------------------------
 // Store
 // ---------------------------------------------------------------------------
@@ -49,6 +57,8 @@ export const useProcessStore = create<ProcessState>((set) => ({
   watchedProcesses: [],
   excludedProcesses: [],
   processList: [],
+  processTotal: 0,
+  processLimit: PROCESS_PAGE_SIZE,
   searchQuery: '',
   isLoading: false,
 
@@ -85,8 +95,10 @@ export const useProcessStore = create<ProcessState>((set) => ({
       excludedProcesses: s.excludedProcesses.filter((n) => n !== name),
     })),
 
-  setProcessList: (list) => set({ processList: list }),
-  setSearchQuery: (query) => set({ searchQuery: query }),
+  setProcessPage: (page) => set({ processList: page.processes, processTotal: page.total }),
+  showMoreProcesses: () => set((s) => ({ processLimit: s.processLimit + PROCESS_PAGE_SIZE })),
+  // A new search starts again from the first page.
+  setSearchQuery: (query) => set({ searchQuery: query, processLimit: PROCESS_PAGE_SIZE }),
   setIsLoading: (loading) => set({ isLoading: loading }),
   clearWatched: () => set({ watchedProcesses: [] }),
 }));
@@ -8,7 +8,8 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
 import i18n from '@/i18n';
-import type { AppSettings } from '@/types';
+import { showToast } from '@/components/ToastNotification';
+import type { AppSettings, LoadedSettings } from '@/types';
 
 // ---------------------------------------------------------------------------
 // Defaults
@@ -20,10 +21,24 @@ const DEFAULT_SETTINGS: AppSettings = {
   auto_start: false,
   minimize_to_tray: false,
   show_notifications: true,
+  notify_at_secs: [30, 10],
+  play_sound: true,
   auto_save: true,
   pre_action_delay_mins: 0,
   keep_screen_on: false,
+  check_for_updates: false,
   activity_logging: true,
+  audio: {
+    enabled: true,
+    countdown_beeps: false,
+    volume: 0.8,
+    custom_sound_path: null,
+  },
+  http_api: {
+    enabled: false,
+    port: 47474,
+    token: '',
+  },
   default_config: null,
 };
 
@@ -58,8 +73,13 @@ export const useSettingsStore = create<SettingsStore>((set, get) => ({
 
   loadSettings: async () => {
     try {
-      const data = await invoke('get_settings');
-      const loaded = { ...DEFAULT_SETTINGS, ...(data as AppSettings) };
+      const { settings: data, recovery } = await invoke<LoadedSettings>('get_settings');
+      const loaded = { ...DEFAULT_SETTINGS, ...data };
+      if (recovery === 'backup') {
+        showToast('Settings file was damaged; restored the last saved settings.', 'warning', 8000);
+      } else if (recovery === 'defaults') {
+        showToast('Settings file was damaged and has been reset to defaults.', 'warning', 8000);
+      }
       set({
         settings: loaded,
         isLoaded: true,
@@ -76,6 +96,12 @@ export const useSettingsStore = create<SettingsStore>((set, get) => ({
       } catch {
         // Silent fail.
       }
+      // Sync audio alert preferences to backend.
+      try {
+        await invoke('set_audio_settings', { settings: loaded.audio });
+      } catch {
+        // Silent fail — e.g. the custom sound file was removed.
+      }
       // Sync language to i18n.
       if (loaded.language && loaded.language !== i18n.language) {
         i18n.changeLanguage(loaded.language);
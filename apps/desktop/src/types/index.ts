@@ -9,18 +9,94 @@
 // Network types
 // ---------------------------------------------------------------------------
 
+/** Broad category of a network interface. */
+export type InterfaceKind = 'ethernet' | 'wifi' | 'loopback' | 'virtual' | 'other';
+
 /** Information about a network interface. */
 export interface NetworkInterface {
   id: string;
   name: string;
   mac: string;
   is_up: boolean;
+  kind: InterfaceKind;
+  /** Adapter description from the OS, if known. */
+  description: string | null;
+  /** Negotiated link speed in Mbps, if the OS reports one. */
+  link_speed_mbps: number | null;
+  /** Bytes in both directions since the previous listing (null if unknown). */
+  recent_bytes: number | null;
+  /** Whether the interface carried traffic since the previous listing. */
+  active_now: boolean;
 }
 
 /** Real-time speed data. */
 export interface SpeedData {
   download_bps: number;
   upload_bps: number;
+  /** `download_bps` formatted as in the tray tooltip, e.g. "1.5 KB/s". */
+  download_text?: string;
+  /** `upload_bps` formatted as in the tray tooltip. */
+  upload_text?: string;
+}
+
+/** Cumulative counters of one interface, as returned by get_raw_counters. */
+export interface NetworkStatsSnapshot {
+  interface_id: string;
+  bytes_sent: number;
+  bytes_received: number;
+  /** Unix milliseconds when the counters were read. */
+  timestamp_epoch_ms: number;
+}
+
+/** Result of get_raw_counters, for debugging counter resets. */
+export interface RawCounters {
+  /** Every interface, read now. */
+  interfaces: NetworkStatsSnapshot[];
+  /** What the speed monitor measures its next reading against. */
+  baseline: NetworkStatsSnapshot[];
+  counter_resets: number;
+}
+
+/** One point of get_speed_history. */
+export interface SpeedHistoryPoint {
+  /** Unix milliseconds. */
+  timestamp_ms: number;
+  download_bps: number;
+  upload_bps: number;
+}
+
+/** How far an in-progress condition has got. */
+export interface ConditionProgress {
+  elapsed_secs: number;
+  /** Seconds required to be met (0 if unknown). */
+  required_secs: number;
+  /** Percentage of the required time elapsed (0–100). */
+  progress: number;
+  /** Measured speed in bytes per second, if reported. */
+  current_value: number | null;
+}
+
+/** Where the monitored value sits relative to the threshold. */
+export type Zone = 'above' | 'warning' | 'below';
+
+/** How reliable an idle estimate is. */
+export type EstimateConfidence = 'low' | 'medium' | 'high';
+
+/** Payload of the `monitoring-tick` event from the backend monitoring loop. */
+export interface MonitoringTick {
+  speed: SpeedData;
+  /** Condition result while waiting for the trigger. */
+  condition: 'Waiting' | 'Met' | { InProgress: ConditionProgress } | null;
+  /** Zone the condition classified this iteration (hysteresis included). */
+  zone: Zone | null;
+  /** When a steadily declining transfer is expected to go idle (Unix ms). */
+  estimated_idle_at_ms: number | null;
+  /** How reliable `estimated_idle_at_ms` is. */
+  estimate_confidence: EstimateConfidence | null;
+  /** Scheduler state after this iteration. */
+  scheduler: string;
+  /** Seconds left in the pre-warning or countdown, if one is running. */
+  remaining_secs: number | null;
 }
 
 // ---------------------------------------------------------------------------
@@ -32,10 +108,33 @@ export interface ProcessInfo {
   pid: number;
   name: string;
   path: string | null;
-  estimated_network_bytes: number;
+  /** Bytes/sec used for triggers and sorting: network if measured, else disk. */
+  activity_bps: number;
+  /** Measured network bytes/sec, or null when unavailable. */
+  network_bytes_per_sec: number | null;
+  /** Disk read+write bytes/sec. */
+  disk_bytes_per_sec: number;
   is_suggested: boolean;
+  /** Instances this entry stands for (more than 1 when grouped by name). */
+  instance_count: number;
 }
 
+/** Selects processes for a watch or exclusion list. */
+export type ProcessMatch =
+  | { kind: 'name'; value: string }
+  | { kind: 'path_contains'; value: string }
+  | { kind: 'pid'; value: number };
+
+/** One page of processes from `get_running_processes`. */
+export interface ProcessPage {
+  processes: ProcessInfo[];
+  /** Matching processes across all pages. */
+  total: number;
+}
+
+/** Recent activity per watched process: `[unix_ms, bytes]` samples, oldest first. */
+export type ProcessActivityHistory = Record<string, [number, number][]>;
+
 // ---------------------------------------------------------------------------
 // Monitoring types
 // ---------------------------------------------------------------------------
@@ -44,8 +143,12 @@ export interface ProcessInfo {
 export type MonitoringStatus =
   | { status: 'Idle' }
   | { status: 'Monitoring' }
-  | { status: 'TriggerPending' }
-  | { status: 'Countdown'; data: { remaining_secs: number } }
+  | { status: 'TriggerPending'; data: { seconds_until_countdown: number } }
+  | {
+      status: 'Countdown';
+      /** `deadline_epoch_ms` is when the countdown ends (null while paused). */
+      data: { remaining_secs: number; deadline_epoch_ms?: number | null };
+    }
   | { status: 'Executed' }
   | { status: 'Paused' };
 
@@ -53,29 +156,238 @@ export type MonitoringStatus =
 export type TriggerConfig =
   | {
       type: 'network_idle';
-      interface_id: string;
+      /** An interface id, 'auto', 'all', or a list of ids to sum. */
+      interface_id: string | string[];
     }
   | {
       type: 'process_idle';
       watched_processes: string[];
       excluded_processes: string[];
+      /** Further processes to watch, by name, path, or PID. */
+      watched_matches?: ProcessMatch[];
+      /** Further processes to exclude, by name, path, or PID. */
+      excluded_matches?: ProcessMatch[];
+      /** Bytes per second below which a watched process counts as idle. */
       threshold_bytes: number;
+      /** Activity samples kept per watched process (default 300). */
+      history_samples?: number;
+    }
+  | {
+      /** The condition threshold is read as a CPU percentage. */
+      type: 'cpu_idle';
+      mode?: CpuMode;
+      /** Milliseconds between CPU samples (default 1000). */
+      sample_interval_ms?: number;
     };
 
+/** Which CPU figure the CPU idle trigger compares: the average or the busiest core. */
+export type CpuMode = 'aggregate' | 'per_core';
+
 /** Condition configuration. */
 export interface ConditionConfig {
   threshold_bytes_per_sec: number;
   required_duration_secs: number;
-  monitor_mode: 'download_only' | 'upload_only' | 'both';
+  monitor_mode: 'download_only' | 'upload_only' | 'both' | 'either';
+  /** "Approaching idle" zone up to this multiple of the threshold (0 = off). */
+  warning_multiplier?: number;
+  /** Dead band above the threshold that keeps a running timer going. */
+  hysteresis_bytes_per_sec?: number | null;
+  /** Consecutive spikes tolerated without resetting the timer (0 = none). */
+  max_spike_samples?: number;
+  /** Longest tolerated spike in seconds. */
+  max_spike_duration_secs?: number | null;
+}
+
+/** Weekday time windows, e.g. `{ "mon-fri": ["23:00-07:00"] }`. */
+export type WeeklySchedule = Record<string, string[]>;
+
+/** A (possibly nested) AND/OR condition tree. */
+export type ConditionSpec =
+  | {
+      type: 'threshold';
+      threshold_bytes_per_sec: number;
+      required_duration_secs: number;
+      monitor_mode: 'download_only' | 'upload_only' | 'both' | 'either';
+      warning_multiplier?: number;
+      hysteresis_bytes_per_sec?: number;
+      max_spike_samples?: number;
+      max_spike_duration_secs?: number | null;
+    }
+  | {
+      type: 'cumulative_volume';
+      limit_bytes: number;
+      direction?: 'download' | 'upload' | 'combined';
+    }
+  | {
+      type: 'composite';
+      operator: 'all' | 'any';
+      conditions: ConditionSpec[];
+    }
+  | {
+      type: 'schedule';
+      schedule: WeeklySchedule;
+      condition?: ConditionSpec | null;
+    };
+
+/** Delay between attempts of a retried action. */
+export type Backoff =
+  | { kind: 'fixed'; delay_ms: number }
+  | { kind: 'exponential'; initial_ms: number; max_ms: number };
+
+/** Attempts and backoff for a failing action. */
+export interface RetryPolicy {
+  /** Attempts in total, including the first. */
+  max_attempts: number;
+  backoff?: Backoff;
 }
 
 /** Full monitoring configuration sent to start_monitoring. */
 export interface MonitoringConfig {
   trigger_type: TriggerConfig;
   condition: ConditionConfig;
-  action_type: string;
+  /** Optional AND/OR combination of conditions. */
+  condition_tree?: ConditionSpec | null;
+  /** One action type id, or steps run in order. */
+  action_type: string | ActionSpec[];
+  /** Parameters for a single action, e.g. a `WebhookConfig` (null for system actions). */
+  action_params?: WebhookConfig | ScriptConfig | null;
+  /** Whether a sequence keeps running after a step fails (default false). */
+  continue_on_error?: boolean;
+  /** How often a failed action is retried (default: 1 attempt). */
+  retry?: RetryPolicy;
   pre_warning_secs: number;
   countdown_secs: number;
+  /** Whether the pre-warning must be acknowledged (default: none). */
+  ack_policy?: AckPolicy;
+  /** Cap on the total seconds the action can be snoozed (default: 3600). */
+  max_snooze_secs?: number;
+  /** How speed readings are smoothed (default: 3-sample average). */
+  smoothing?: SmoothingStrategy;
+  /** What to do when the monitored interface goes dead (default: off). */
+  interface_failover?: FailoverPolicy;
+  /** Only notify instead of running the action (default false). */
+  dry_run?: boolean;
+  /** Replace a running session instead of failing (never saved). */
+  restart?: boolean;
+  /** Power source requirements checked right before the action runs. */
+  power_guard?: PowerRules;
+  /** Postpone the countdown until there was no input for this many seconds. */
+  require_user_idle_secs?: number | null;
+  /** Cancel an action whose countdown has not started this many seconds after it was scheduled. */
+  pending_timeout_secs?: number | null;
+  /** Whether the session ends after the action or resumes monitoring after a cooldown. */
+  repeat_mode?: RepeatMode;
+  /** After a user cancel, wait for traffic above the threshold before re-arming. */
+  rearm_requires_activity?: boolean;
+  /** Optional action run after monitoring starts. */
+  on_start_action?: HookConfig | null;
+  /** Optional action run after monitoring stops. */
+  on_stop_action?: HookConfig | null;
+  /** Optional action run after a pending action is cancelled. */
+  on_cancel_action?: HookConfig | null;
+}
+
+/** A monitoring configuration saved under a name. */
+export interface Profile {
+  /** Unique ignoring case. */
+  name: string;
+  config: MonitoringConfig;
+}
+
+/** When the action may run, judged by the power source. */
+export interface PowerRules {
+  /** Only run while on AC power (default false). */
+  require_ac_power?: boolean;
+  /** While on battery, only run with at least this much charge. */
+  min_battery_percent?: number | null;
+}
+
+/** One step of an action sequence. */
+export interface ActionSpec {
+  action_type: string;
+  /** Parameters for the step's action (null for system actions). */
+  params?: unknown;
+}
+
+/** Parameters of the `webhook` action. */
+export interface WebhookConfig {
+  url: string;
+  method?: 'GET' | 'POST' | 'PUT' | 'PATCH' | 'DELETE';
+  headers?: Record<string, string>;
+  /** JSON body; `{{timestamp}}` in string values becomes the Unix time. */
+  body?: unknown;
+  /** Request timeout in seconds (default 10). */
+  timeout_secs?: number;
+  /** Send a HEAD request when validating (default false). */
+  probe?: boolean;
+}
+
+/** Parameters of the `run_script` action. */
+export interface ScriptConfig {
+  /** Program path, or a bare name looked up on PATH. */
+  program: string;
+  args?: string[];
+  working_dir?: string | null;
+  /** Time limit in seconds when waiting (default 60). */
+  timeout_secs?: number;
+  mode?: 'wait' | 'fire_and_forget';
+}
+
+/** Parameters of the `notify` action; `{name}` interpolates a trigger value. */
+export interface NotifyConfig {
+  /** Title template (default 'FlowWatcher'). */
+  title?: string;
+  /** Body template, e.g. 'Idle at {download_bps} B/s'. */
+  body?: string;
+}
+
+/** Parameters of the `kill_process` action. */
+export interface KillProcessConfig {
+  /** Executable names (case-insensitive) or PIDs to terminate. */
+  targets: (string | number)[];
+  /** Which instances of a name are terminated (default 'all'). */
+  instances?: 'all' | 'first';
+  /** Seconds to wait for a clean exit before force-killing (default 5). */
+  grace_period_secs?: number;
+}
+
+/** How the backend smooths raw speed readings. */
+export type SmoothingStrategy =
+  | { type: 'simple_moving_average'; window: number }
+  | { type: 'exponential_moving_average'; alpha: number }
+  | { type: 'none' };
+
+/** Response to the monitored interface going dead mid-session. */
+export type FailoverMode = 'off' | 'switch' | 'suspend';
+
+/** When and how to fail over from a dead interface. */
+export interface FailoverPolicy {
+  mode: FailoverMode;
+  /** Consecutive dead polls before acting (default 5). */
+  after_polls?: number;
+  /** Bytes/s another interface must carry (default 10 KiB/s). */
+  min_active_bps?: number;
+}
+
+/** Payload of the `interface-failover` event. */
+export type FailoverEvent =
+  | { kind: 'switched'; from: string; to: string }
+  | { kind: 'inactive'; interface: string; active: string }
+  | { kind: 'recovered'; interface: string };
+
+/** Whether the pre-warning must be acknowledged before the countdown. */
+export type AckPolicy =
+  | { policy: 'none' }
+  | { policy: 'extend_once'; extra_secs: number }
+  | { policy: 'require_ack'; timeout_behavior: AckTimeout };
+
+/** What a required acknowledgment does when the pre-warning times out. */
+export type AckTimeout = 'cancel' | { extend_then_cancel: { extra_secs: number } };
+
+/** A lifecycle hook: action id plus params, run best-effort. */
+export interface HookConfig {
+  action_id: string;
+  params?: unknown;
 }
 
 // ---------------------------------------------------------------------------
@@ -87,6 +399,8 @@ export interface TriggerInfo {
   id: string;
   name: string;
   description: string;
+  /** JSON Schema of the trigger's config (null if undocumented). */
+  config_schema: Record<string, unknown> | null;
 }
 
 /** Action type metadata. */
@@ -95,22 +409,161 @@ export interface ActionInfo {
   name: string;
   description: string;
   available: boolean;
+  /** Whether the action needs administrator rights. */
+  requires_elevation: boolean;
+  /** JSON Schema of the action's params, for actions that take any. */
+  params_schema?: Record<string, unknown>;
+}
+
+/** Result of calibrate_idle_baseline. */
+export interface CalibrationResult {
+  /** Idle baseline (95th percentile) in bytes/sec. */
+  baseline_bps: number;
+  /** Typical burst level (99th percentile) in bytes/sec. */
+  burst_bps: number;
+  /** Suggested threshold in bytes/sec. */
+  suggested_threshold_bps: number;
+  confidence: 'low' | 'medium' | 'high';
+  sample_count: number;
+}
+
+/** An adapter's link speed, as a basis for threshold suggestions. */
+export interface LinkSpeed {
+  /** Link speed in Mbps, clamped to 10 Gbps. */
+  mbps: number;
+  /** Not a real limit: a virtual or loopback adapter, or an implausible claim. */
+  nominal: boolean;
+}
+
+/** Result of suggest_threshold_for_interface. */
+export interface LinkThresholdSuggestion {
+  link_speed: LinkSpeed;
+  /** Suggested threshold in bytes/sec. */
+  threshold_bps: number;
+}
+
+/** Stable code of a CommandError; new codes may be added, none are renamed. */
+export type CommandErrorCode =
+  | 'busy'
+  | 'already_monitoring'
+  | 'stale_session'
+  | 'interface_not_found'
+  | 'network_error'
+  | 'process_not_found'
+  | 'process_error'
+  | 'invalid_state'
+  | 'snooze_limit'
+  | 'action_failed'
+  | 'action_skipped'
+  | 'no_speed_baseline'
+  | 'access_denied'
+  | 'not_supported'
+  | 'autostart_error'
+  | 'invalid_trigger_config'
+  | 'unknown_trigger'
+  | 'trigger_start_failed'
+  | 'trigger_stop_failed'
+  | 'trigger_error'
+  | 'invalid_config'
+  | 'action_timeout'
+  | 'action_not_supported'
+  | 'insufficient_privileges'
+  | 'unknown_action'
+  | 'invalid_action_config'
+  | 'elevation_cancelled'
+  | 'elevation_error'
+  | 'invalid_settings'
+  | 'invalid_profile_name'
+  | 'profile_exists'
+  | 'profile_limit'
+  | 'profile_not_found'
+  | 'io_error'
+  | 'invalid_json'
+  | 'unsupported_format_version'
+  | 'invalid_export'
+  | 'sound_not_found'
+  | 'invalid_sound_file'
+  | 'audio_error'
+  | 'invalid_argument'
+  | 'not_monitoring'
+  | 'no_speed_samples'
+  | 'failed';
+
+/** Error every Tauri command rejects with. Branch on `code`; show `message`. */
+export interface CommandError {
+  code: CommandErrorCode;
+  message: string;
+  /** Offending config field, e.g. `condition.threshold_bytes_per_sec` (invalid_config only). */
+  field?: string;
+}
+
+/** Pre-flight warning returned by start_monitoring. */
+export type StartWarning =
+  | { kind: 'elevation'; action_id: string; message: string }
+  | { kind: 'interface_down'; interface_id: string; message: string };
+
+/** Result of start_monitoring. */
+export interface MonitoringStarted {
+  /** Session token to echo in cancel_action / execute_action_now. */
+  session_token: number;
+  warnings: StartWarning[];
 }
 
 // ---------------------------------------------------------------------------
 // Event payload types
 // ---------------------------------------------------------------------------
 
-/** Payload for 'speed-update' event. */
-export interface SpeedUpdateEvent {
+/** Mean speed over a rolling window. */
+export interface SpeedAverage {
   download_bps: number;
   upload_bps: number;
 }
 
+/** Payload for 'speed-update' event: the latest speed plus rolling averages. */
+export interface SpeedUpdateEvent extends SpeedData {
+  /** Mean over the last 10 seconds. */
+  average_10s: SpeedAverage | null;
+  /** Mean over the last 60 seconds. */
+  average_60s: SpeedAverage | null;
+}
+
+/** Payload for 'trigger-data' event. */
+export interface TriggerDataEvent {
+  /** Downsampled activity of watched processes (process mode only). */
+  process_activity: ProcessActivityHistory;
+}
+
+/** Payload for 'ack-requested' event. */
+export interface AckRequestedEvent {
+  timeout_secs: number;
+}
+
+/** What happens after the action has executed. */
+export type RepeatMode = { mode: 'one_shot' } | { mode: 'repeat'; cooldown_secs?: number };
+
+/** Why a pending action was cancelled. */
+export type CancelReason =
+  | 'user_ui'
+  | 'user_tray'
+  | 'user_hotkey'
+  | 'remote_api'
+  | 'auto_traffic_resumed'
+  | 'system_resume'
+  | 'interface_failover'
+  | 'shutdown'
+  | 'restarted'
+  | 'not_acknowledged'
+  | 'pending_timeout'
+  | 'condition_re_armed'
+  | { guard_failed: string }
+  | { other: string };
+
 /** Payload for 'monitoring-state-change' event. */
 export interface StateChangeEvent {
   previous: MonitoringStatus;
   current: MonitoringStatus;
+  /** Set when the change is a cancelled action. */
+  cancel_reason?: CancelReason;
 }
 
 /** Payload for 'countdown-tick' event. */
@@ -125,43 +578,219 @@ export interface PreWarningEvent {
   action_name: string;
 }
 
+// ---------------------------------------------------------------------------
+// Debug types
+// ---------------------------------------------------------------------------
+
+/** Current size and cap of one bounded buffer. */
+export interface BufferStats {
+  len: number;
+  capacity: number;
+}
+
+/** Sizes of the backend's in-memory buffers (get_memory_stats). */
+export interface MemoryStats {
+  activity_log: BufferStats;
+  scheduler_events: BufferStats;
+  scheduler_dropped_events: number;
+  idle_estimator: BufferStats;
+  process_series: BufferStats;
+  process_samples: BufferStats;
+  speed_history: BufferStats;
+  pending_events: number;
+}
+
+/** One scheduler state change and the call that caused it. */
+export interface SchedulerTransition {
+  from: string;
+  to: string;
+  /** Scheduler ticks since the session started. */
+  tick: number;
+  trigger:
+    | 'schedule'
+    | 'tick'
+    | 'pause'
+    | 'resume'
+    | 'cancel'
+    | 'execute_now'
+    | 'run_action'
+    | 'reset';
+}
+
+/** Scheduler state and recent transitions (get_scheduler_debug). */
+export interface SchedulerDebug {
+  state: string;
+  remaining_secs: number | null;
+  cancel_reason: CancelReason | null;
+  history: SchedulerTransition[];
+}
+
 // ---------------------------------------------------------------------------
 // Settings types
 // ---------------------------------------------------------------------------
 
+/** Preferences for audible countdown alerts. */
+export interface AudioSettings {
+  /** Play a chime when the countdown starts. */
+  enabled: boolean;
+  /** Beep every second during the last 10 seconds. */
+  countdown_beeps: boolean;
+  /** Playback volume from 0 to 1. */
+  volume: number;
+  /** WAV file to play instead of the built-in chime. */
+  custom_sound_path: string | null;
+}
+
+/** The local HTTP API for integrations (Stream Deck, scripts). */
+export interface HttpApiSettings {
+  /** Serve the API on 127.0.0.1. */
+  enabled: boolean;
+  /** Loopback port to listen on. */
+  port: number;
+  /** Bearer token every request must carry. */
+  token: string;
+}
+
 /** Application settings. */
 export interface AppSettings {
   /** Language code (e.g., "en", "bn"). */
   language: string;
   /** Theme: "dark", "light", or "auto". */
   theme: 'dark' | 'light' | 'auto';
-  /** Start with Windows. */
+  /** Start the app at login. */
   auto_start: boolean;
   /** Minimize to tray on close. */
   minimize_to_tray: boolean;
   /** Show desktop notification before action. */
   show_notifications: boolean;
+  /** Remaining countdown seconds at which to show an OS notification. */
+  notify_at_secs: number[];
+  /** Beep along with each countdown notification. */
+  play_sound: boolean;
   /** Auto-save settings on every change. */
   auto_save: boolean;
   /** Minutes to wait after detection before countdown. */
   pre_action_delay_mins: number;
   /** Keep screen on during monitoring. */
   keep_screen_on: boolean;
+  /** Check daily for a newer release (opt-in). */
+  check_for_updates: boolean;
+  /** Release manifest to check instead of the default one. */
+  update_manifest_url?: string | null;
   /** Whether activity logging is enabled. */
   activity_logging: boolean;
+  /** Audible countdown alerts. */
+  audio: AudioSettings;
+  /** Local HTTP API for integrations. */
+  http_api: HttpApiSettings;
   /** Default monitoring config. */
   default_config: MonitoringConfig | null;
 }
 
+/** How get_settings recovered an unreadable settings file. */
+export type SettingsRecovery = 'backup' | 'defaults';
+
+/** Result of get_settings. */
+export interface LoadedSettings {
+  settings: AppSettings;
+  /** Set when settings.json was unreadable and its backup or the defaults were used. */
+  recovery: SettingsRecovery | null;
+}
+
 // ---------------------------------------------------------------------------
 // Log types
 // ---------------------------------------------------------------------------
 
 /** A log entry from the activity log. */
 export interface LogEntry {
+  /** RFC 3339 local time with offset, e.g. `2025-01-31T12:00:00+02:00`. */
   timestamp: string;
   trigger_reason: string;
   action_name: string;
   status: 'executed' | 'cancelled' | 'error' | 'info';
   details: string | null;
+  /** Why the action was cancelled, for cancelled entries. */
+  cancel_reason?: CancelReason;
+  /** The monitoring session this entry belongs to. */
+  session_id?: number;
+  /** How long the action ran, for execution entries. */
+  duration_ms?: number;
+  /** Structured context, e.g. the trigger data when the condition was met. */
+  metadata?: Record<string, unknown>;
+}
+
+/** Narrows and pages the activity log for `query_activity_logs`. */
+export interface LogFilter {
+  /** Statuses to keep (empty for all). */
+  statuses?: LogEntry['status'][];
+  /** Earliest timestamp, as an ISO date or date-time prefix (inclusive). */
+  from?: string | null;
+  /** Latest timestamp, as an ISO date or date-time prefix (inclusive). */
+  to?: string | null;
+  query?: string | null;
+  offset?: number;
+  limit?: number | null;
+}
+
+/** One page of log entries, newest first. */
+export interface LogPage {
+  entries: LogEntry[];
+  /** Matching entries across all pages. */
+  total: number;
+}
+
+/** Which part of the log `get_activity_stats` aggregates. */
+export interface StatsRange {
+  /** Earliest timestamp, as an ISO date or date-time prefix (inclusive). */
+  from?: string | null;
+  /** Latest timestamp, as an ISO date or date-time prefix (inclusive). */
+  to?: string | null;
+}
+
+/** Log entry counts by status. */
+export interface StatusCounts {
+  executed: number;
+  cancelled: number;
+  error: number;
+  info: number;
+  total: number;
+}
+
+/** How the monitoring sessions in a range ended and how long they ran. */
+export interface SessionStats {
+  count: number;
+  executed: number;
+  stopped: number;
+  interrupted: number;
+  running: number;
+  average_secs_to_execute: number | null;
+  average_secs_to_stop: number | null;
+}
+
+/** Activity aggregated over a `StatsRange`. */
+export interface ActivityStats {
+  totals: StatusCounts;
+  by_action: Record<string, StatusCounts>;
+  by_trigger: Record<string, StatusCounts>;
+  /** Keyed by local date (`YYYY-MM-DD`). */
+  by_day: Record<string, StatusCounts>;
+  sessions: SessionStats;
+}
+
+/** How a monitoring session ended. */
+export type SessionOutcome = 'executed' | 'stopped' | 'interrupted';
+
+/** One monitoring session, from start to stop or execution. */
+export interface SessionRecord {
+  id: number;
+  started_at: string;
+  started_unix_secs: number;
+  /** Set once the session has ended normally. */
+  ended_at: string | null;
+  duration_secs: number | null;
+  trigger_reason: string;
+  action_name: string;
+  /** `null` while the session runs. */
+  outcome: SessionOutcome | null;
+  details: string | null;
 }
@@ -0,0 +1,664 @@
+//! External actions — custom actions shipped as standalone executables.
+//!
+//! A plugin is a directory under the app's `plugins` folder containing a
+//! `manifest.json` and an executable. FlowWatcher talks to the executable
+//! through a tiny command-line protocol:
+//!
+//! ```text
+//! <exe> validate                          # can the action run here?
+//! <exe> execute --payload '<params json>' # perform the action
+//! ```
+//!
+//! Exit code 0 means success. On success the executable may print a single
+//! JSON line to stdout, parsed as an [`ActionOutcome`]. On failure, stderr
+//! is surfaced in the error. Every call is bounded by a timeout and the
+//! child is killed if it overruns.
+//!
+//! Discovery never fails as a whole: unreadable or malformed plugins are
+//! skipped and reported as warnings for the caller to log.
+
+use crate::{Action, ActionError, ActionInfo, ActionRegistry};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+/// Name of the manifest file inside each plugin directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Default limit for a single `validate` or `execute` call.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest accepted plugin id.
+const MAX_ID_LEN: usize = 64;
+
+/// Largest manifest file that will be read.
+const MAX_MANIFEST_BYTES: u64 = 64 * 1024;
+
+// ---------------------------------------------------------------------------
+// Manifest
+// ---------------------------------------------------------------------------
+
+/// Errors in a plugin manifest.
+#[derive(Debug, thiserror::Error)]
+pub enum ManifestError {
+    /// The manifest is not valid JSON or is missing fields.
+    #[error("invalid manifest: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    /// A field has an unacceptable value.
+    #[error("invalid manifest field `{field}`: {reason}")]
+    Invalid {
+        /// The offending field.
+        field: &'static str,
+        /// Why it was rejected.
+        reason: String,
+    },
+}
+
+/// Contents of a plugin's `manifest.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginManifest {
+    /// Action type id (lowercase letters, digits, `_` and `-`).
+    pub id: String,
+    /// Human-readable display name.
+    pub name: String,
+    /// Description of what the action does.
+    #[serde(default)]
+    pub description: String,
+    /// Executable path, relative to the plugin directory.
+    pub executable: String,
+    /// JSON Schema describing the params the action accepts.
+    #[serde(default)]
+    pub params_schema: serde_json::Value,
+    /// Whether the action needs administrator rights.
+    #[serde(default)]
+    pub requires_elevation: bool,
+}
+
+impl PluginManifest {
+    /// Parse and validate a manifest.
+    pub fn from_json(json: &str) -> Result<Self, ManifestError> {
+        let manifest: Self = serde_json::from_str(json)?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    /// Check field values that serde cannot express.
+    fn validate(&self) -> Result<(), ManifestError> {
+        let invalid = |field, reason: &str| ManifestError::Invalid {
+            field,
+            reason: reason.to_string(),
+        };
+
+        if self.id.is_empty() || self.id.len() > MAX_ID_LEN {
+            return Err(invalid("id", "must be 1-64 characters"));
+        }
+        if !self
+            .id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+        {
+            return Err(invalid("id", "may only contain a-z, 0-9, '_' and '-'"));
+        }
+        if self.name.trim().is_empty() {
+            return Err(invalid("name", "must not be empty"));
+        }
+
+        // The executable must stay inside the plugin directory.
+        let exe = Path::new(&self.executable);
+        if self.executable.is_empty()
+            || !exe
+                .components()
+                .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(invalid(
+                "executable",
+                "must be a relative path inside the plugin directory",
+            ));
+        }
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Protocol
+// ---------------------------------------------------------------------------
+
+/// Optional result printed by a plugin's `execute` call.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActionOutcome {
+    /// Short message for the activity log.
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Arbitrary extra data.
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+impl ActionOutcome {
+    /// Parse stdout of a successful call: empty, or one JSON object line.
+    pub fn from_stdout(stdout: &str) -> Result<Self, ActionError> {
+        let mut lines = stdout.lines().map(str::trim).filter(|l| !l.is_empty());
+        let Some(line) = lines.next() else {
+            return Ok(Self::default());
+        };
+        if lines.next().is_some() {
+            return Err(ActionError::ExecutionFailed(
+                "plugin printed more than one line of output".into(),
+            ));
+        }
+        serde_json::from_str(line)
+            .map_err(|e| ActionError::ExecutionFailed(format!("invalid plugin result: {e}")))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// ExternalAction
+// ---------------------------------------------------------------------------
+
+/// An action backed by a plugin executable.
+#[derive(Debug, Clone)]
+pub struct ExternalAction {
+    /// The plugin's manifest.
+    manifest: PluginManifest,
+    /// Resolved path of the executable.
+    executable: PathBuf,
+    /// Params passed as `--payload`.
+    params: serde_json::Value,
+    /// Limit for each call.
+    timeout: Duration,
+}
+
+impl ExternalAction {
+    /// Wrap an executable described by `manifest`.
+    pub fn new(manifest: PluginManifest, executable: impl Into<PathBuf>) -> Self {
+        Self {
+            manifest,
+            executable: executable.into(),
+            params: serde_json::Value::Null,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Set the params passed to `execute`.
+    pub fn with_params(mut self, params: serde_json::Value) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Set the per-call timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The plugin's manifest.
+    pub fn manifest(&self) -> &PluginManifest {
+        &self.manifest
+    }
+
+    /// Run `execute` and return the plugin's reported outcome.
+    pub async fn execute_with_outcome(&self) -> Result<ActionOutcome, ActionError> {
+        let payload = self.params.to_string();
+        let stdout = self.call(&["execute", "--payload", &payload]).await?;
+        ActionOutcome::from_stdout(&stdout)
+    }
+
+    /// Invoke the executable and return its stdout on exit code 0.
+    async fn call(&self, args: &[&str]) -> Result<String, ActionError> {
+        let child = tokio::process::Command::new(&self.executable)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                ActionError::ExecutionFailed(format!(
+                    "failed to start plugin {}: {e}",
+                    self.executable.display()
+                ))
+            })?;
+
+        // Dropping the future on timeout kills the child.
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| {
+                ActionError::Timeout(format!(
+                    "plugin `{}` did not finish `{}` within {:?}",
+                    self.manifest.id, args[0], self.timeout
+                ))
+            })?
+            .map_err(|e| ActionError::ExecutionFailed(e.to_string()))?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stderr = stderr.trim();
+            let status = match output.status.code() {
+                Some(code) => format!("exit code {code}"),
+                None => "termination by signal".to_string(),
+            };
+            Err(ActionError::ExecutionFailed(if stderr.is_empty() {
+                format!("plugin `{}` failed with {status}", self.manifest.id)
+            } else {
+                format!(
+                    "plugin `{}` failed with {status}: {stderr}",
+                    self.manifest.id
+                )
+            }))
+        }
+    }
+}
+
+#[async_trait]
+impl Action for ExternalAction {
+    fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    fn action_type(&self) -> &str {
+        &self.manifest.id
+    }
+
+    fn info(&self) -> ActionInfo {
+        ActionInfo {
+            id: self.manifest.id.clone(),
+            name: self.manifest.name.clone(),
+            description: self.manifest.description.clone(),
+            available: self.executable.is_file(),
+            requires_elevation: self.manifest.requires_elevation,
+            params_schema: None,
+        }
+    }
+
+    async fn validate(&self) -> Result<(), ActionError> {
+        self.call(&["validate"]).await.map(|_| ())
+    }
+
+    async fn execute(&self) -> Result<(), ActionError> {
+        self.execute_with_outcome().await.map(|_| ())
+    }
+
+    async fn execute_with_details(&self) -> Result<Option<String>, ActionError> {
+        self.execute_with_outcome()
+            .await
+            .map(|outcome| outcome.message)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Discovery
+// ---------------------------------------------------------------------------
+
+/// A plugin found on disk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveredPlugin {
+    /// The parsed manifest.
+    pub manifest: PluginManifest,
+    /// Resolved path of the executable.
+    pub executable: PathBuf,
+}
+
+/// Result of scanning a plugins directory.
+#[derive(Debug, Default)]
+pub struct PluginDiscovery {
+    /// Plugins that loaded successfully, sorted by id.
+    pub plugins: Vec<DiscoveredPlugin>,
+    /// One message per skipped plugin.
+    pub warnings: Vec<String>,
+}
+
+/// Scan `dir` for plugin subdirectories.
+///
+/// A missing directory yields an empty result. Subdirectories without a
+/// manifest, with a malformed manifest, with a missing executable, or with
+/// an id already seen are skipped with a warning.
+pub fn discover_plugins(dir: &Path) -> PluginDiscovery {
+    let mut discovery = PluginDiscovery::default();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return discovery,
+        Err(e) => {
+            discovery.warnings.push(format!(
+                "cannot read plugins directory {}: {e}",
+                dir.display()
+            ));
+            return discovery;
+        }
+    };
+
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+
+    for plugin_dir in dirs {
+        match load_plugin(&plugin_dir) {
+            Ok(plugin) => {
+                if discovery
+                    .plugins
+                    .iter()
+                    .any(|p| p.manifest.id == plugin.manifest.id)
+                {
+                    discovery.warnings.push(format!(
+                        "skipping plugin {}: duplicate id `{}`",
+                        plugin_dir.display(),
+                        plugin.manifest.id
+                    ));
+                } else {
+                    discovery.plugins.push(plugin);
+                }
+            }
+            Err(reason) => discovery.warnings.push(format!(
+                "skipping plugin {}: {reason}",
+                plugin_dir.display()
+            )),
+        }
+    }
+
+    discovery
+        .plugins
+        .sort_by(|a, b| a.manifest.id.cmp(&b.manifest.id));
+    discovery
+}
+
+/// Load one plugin directory.
+fn load_plugin(dir: &Path) -> Result<DiscoveredPlugin, String> {
+    let manifest_path = dir.join(MANIFEST_FILE);
+    let size = std::fs::metadata(&manifest_path)
+        .map_err(|e| format!("cannot read {MANIFEST_FILE}: {e}"))?
+        .len();
+    if size > MAX_MANIFEST_BYTES {
+        return Err(format!("{MANIFEST_FILE} is too large ({size} bytes)"));
+    }
+    let json = std::fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("cannot read {MANIFEST_FILE}: {e}"))?;
+    let manifest = PluginManifest::from_json(&json).map_err(|e| e.to_string())?;
+
+    let executable = dir.join(&manifest.executable);
+    if !executable.is_file() {
+        return Err(format!("executable `{}` not found", manifest.executable));
+    }
+    Ok(DiscoveredPlugin {
+        manifest,
+        executable,
+    })
+}
+
+/// Register discovered plugins into an action registry.
+///
+/// Plugins may not replace already-registered action types; such
+/// collisions are skipped and returned as warnings.
+pub fn register_plugins(
+    registry: &mut ActionRegistry,
+    plugins: Vec<DiscoveredPlugin>,
+    timeout: Duration,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for plugin in plugins {
+        let id = plugin.manifest.id.clone();
+        if registry.contains(&id) {
+            warnings.push(format!(
+                "skipping plugin `{id}`: action type is already registered"
+            ));
+            continue;
+        }
+        registry.register(id, move |params| {
+            Ok(Box::new(
+                ExternalAction::new(plugin.manifest.clone(), plugin.executable.clone())
+                    .with_params(params.clone())
+                    .with_timeout(timeout),
+            ))
+        });
+    }
+    warnings
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_testing::ScratchDir;
+
+    fn manifest_json(id: &str, executable: &str) -> String {
+        serde_json::json!({
+            "id": id,
+            "name": "Test Plugin",
+            "description": "For tests",
+            "executable": executable,
+            "params_schema": { "type": "object" },
+        })
+        .to_string()
+    }
+
+    /// Write a plugin directory containing a manifest and a stub script.
+    fn write_plugin(root: &Path, dir: &str, manifest: &str, script: &str) -> PathBuf {
+        let plugin_dir = root.join(dir);
+        std::fs::create_dir_all(&plugin_dir).unwrap();
+        std::fs::write(plugin_dir.join(MANIFEST_FILE), manifest).unwrap();
+        let exe = plugin_dir.join("plugin.sh");
+        std::fs::write(&exe, format!("#!/bin/sh\n{script}\n")).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&exe, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        exe
+    }
+
+    // -- Manifest --------------------------------------------------------
+
+    #[test]
+    fn parses_valid_manifest() {
+        let manifest = PluginManifest::from_json(&manifest_json("notify", "plugin.sh")).unwrap();
+        assert_eq!(manifest.id, "notify");
+        assert_eq!(manifest.params_schema["type"], "object");
+        assert!(!manifest.requires_elevation);
+    }
+
+    #[test]
+    fn optional_manifest_fields_default() {
+        let manifest =
+            PluginManifest::from_json(r#"{"id":"x","name":"X","executable":"x.exe"}"#).unwrap();
+        assert_eq!(manifest.description, "");
+        assert!(manifest.params_schema.is_null());
+    }
+
+    #[test]
+    fn rejects_bad_manifests() {
+        let cases = [
+            "not json",
+            "[]",
+            r#"{"name":"X","executable":"x"}"#,
+            &manifest_json("", "x"),
+            &manifest_json("Bad Id", "x"),
+            &manifest_json(&"a".repeat(65), "x"),
+            &manifest_json("ok", ""),
+            &manifest_json("ok", "../escape.sh"),
+            &manifest_json("ok", "/bin/sh"),
+            r#"{"id":"ok","name":"  ","executable":"x"}"#,
+        ];
+        for json in cases {
+            assert!(PluginManifest::from_json(json).is_err(), "accepted: {json}");
+        }
+    }
+
+    // -- Outcome parsing -------------------------------------------------
+
+    #[test]
+    fn parses_outcome_lines() {
+        assert_eq!(
+            ActionOutcome::from_stdout("").unwrap(),
+            ActionOutcome::default()
+        );
+        assert_eq!(
+            ActionOutcome::from_stdout("\n  \n").unwrap(),
+            ActionOutcome::default()
+        );
+
+        let outcome =
+            ActionOutcome::from_stdout("{\"message\":\"sent\",\"data\":{\"n\":1}}\n").unwrap();
+        assert_eq!(outcome.message.as_deref(), Some("sent"));
+        assert_eq!(outcome.data["n"], 1);
+
+        assert!(ActionOutcome::from_stdout("hello").is_err());
+        assert!(ActionOutcome::from_stdout("{}\n{}").is_err());
+    }
+
+    // -- Discovery -------------------------------------------------------
+
+    #[test]
+    fn missing_directory_is_empty() {
+        let discovery = discover_plugins(Path::new("/nonexistent/flowwatcher/plugins"));
+        assert!(discovery.plugins.is_empty());
+        assert!(discovery.warnings.is_empty());
+    }
+
+    #[test]
+    fn discovery_skips_broken_plugins_with_warnings() {
+        let root = ScratchDir::new("plugins-discovery");
+        write_plugin(
+            &root,
+            "b-good",
+            &manifest_json("good", "plugin.sh"),
+            "exit 0",
+        );
+        write_plugin(
+            &root,
+            "a-other",
+            &manifest_json("other", "plugin.sh"),
+            "exit 0",
+        );
+        write_plugin(&root, "garbage", "\u{0}\u{1}garbage{{", "exit 0");
+        write_plugin(&root, "escape", &manifest_json("escape", "../x"), "exit 0");
+        write_plugin(
+            &root,
+            "no-exe",
+            &manifest_json("noexe", "missing.sh"),
+            "exit 0",
+        );
+        write_plugin(&root, "dup", &manifest_json("good", "plugin.sh"), "exit 0");
+        std::fs::create_dir_all(root.join("empty")).unwrap();
+        std::fs::write(root.join("stray-file.txt"), "ignored").unwrap();
+        let big = root.join("huge");
+        std::fs::create_dir_all(&big).unwrap();
+        std::fs::write(big.join(MANIFEST_FILE), vec![b' '; 100 * 1024]).unwrap();
+
+        let discovery = discover_plugins(&root);
+        let ids: Vec<_> = discovery
+            .plugins
+            .iter()
+            .map(|p| p.manifest.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["good", "other"]);
+        assert_eq!(discovery.warnings.len(), 6, "{:?}", discovery.warnings);
+        assert!(discovery
+            .warnings
+            .iter()
+            .any(|w| w.contains("duplicate id")));
+    }
+
+    #[test]
+    fn registration_does_not_shadow_existing_types() {
+        let root = ScratchDir::new("plugins-register");
+        write_plugin(
+            &root,
+            "p",
+            &manifest_json("shutdown", "plugin.sh"),
+            "exit 0",
+        );
+        write_plugin(&root, "q", &manifest_json("notify", "plugin.sh"), "exit 0");
+
+        let mut registry = ActionRegistry::new();
+        registry.register("shutdown", |_| {
+            Err(ActionError::NotSupported("built-in".into()))
+        });
+        let discovery = discover_plugins(&root);
+        let warnings = register_plugins(&mut registry, discovery.plugins, DEFAULT_TIMEOUT);
+
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(registry.ids(), vec!["notify", "shutdown"]);
+        let action = registry
+            .create("notify", &serde_json::json!({ "to": "me" }))
+            .unwrap();
+        assert_eq!(action.action_type(), "notify");
+        assert!(action.info().available);
+    }
+
+    // -- Protocol client -------------------------------------------------
+
+    #[cfg(unix)]
+    fn stub(name: &str, script: &str) -> (ExternalAction, ScratchDir) {
+        let root = ScratchDir::new(&format!("plugins-{name}"));
+        let manifest = manifest_json(name, "plugin.sh");
+        let exe = write_plugin(&root, "p", &manifest, script);
+        let action = ExternalAction::new(PluginManifest::from_json(&manifest).unwrap(), exe);
+        (action, root)
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn passes_subcommand_and_payload() {
+        let (action, _root) = stub(
+            "echo",
+            r#"[ "$1" = validate ] && exit 0
+[ "$1" = execute ] && [ "$2" = --payload ] || exit 3
+printf '{"message":"%s"}\n' "$(echo "$3" | tr -d '"{}')""#,
+        );
+        let action = action.with_params(serde_json::json!({ "to": "me" }));
+
+        action.validate().await.unwrap();
+        let outcome = action.execute_with_outcome().await.unwrap();
+        assert_eq!(outcome.message.as_deref(), Some("to:me"));
+        action.execute().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn failure_surfaces_stderr_and_exit_code() {
+        let (action, _root) = stub("fail", "echo 'printer on fire' >&2; exit 4");
+
+        let err = action.validate().await.unwrap_err().to_string();
+        assert!(err.contains("exit code 4"), "{err}");
+        assert!(err.contains("printer on fire"), "{err}");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn garbage_output_is_an_error() {
+        let (action, _root) = stub("garbage", "echo 'not json'");
+
+        action.validate().await.unwrap();
+        assert!(action.execute().await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn slow_plugin_times_out() {
+        let (action, _root) = stub("slow", "sleep 5");
+        let action = action.with_timeout(Duration::from_millis(200));
+
+        let started = std::time::Instant::now();
+        let err = action.execute().await.unwrap_err();
+        assert!(matches!(err, ActionError::Timeout(_)), "{err}");
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[tokio::test]
+    async fn missing_executable_fails_to_start() {
+        let manifest = PluginManifest::from_json(&manifest_json("gone", "gone.sh")).unwrap();
+        let action = ExternalAction::new(manifest, "/nonexistent/gone.sh");
+        assert!(!action.info().available);
+        let err = action.validate().await.unwrap_err().to_string();
+        assert!(err.contains("failed to start plugin"), "{err}");
+    }
+}
@@ -10,11 +10,37 @@
 //! This crate is the extensibility point for the Action Engine.
 //! New action types are added by implementing [`Action`] — never by
 //! modifying existing engine dispatch code.
+//!
+//! # Features
+//!
+//! - `webhook`: [`WebhookAction`](webhook::WebhookAction), backed by the
+//!   `ureq` HTTP client. Off by default so the engine crates stay free of
+//!   networking dependencies; the platform crate enables it.
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+pub mod external;
+pub mod notify;
+pub mod registry;
+pub mod retry;
+pub mod script;
+pub mod sequence;
+#[cfg(feature = "webhook")]
+pub mod webhook;
+pub use external::{
+    discover_plugins, register_plugins, ActionOutcome, DiscoveredPlugin, ExternalAction,
+    PluginDiscovery, PluginManifest,
+};
+pub use notify::{Notifier, NotifyAction, NotifyConfig, TemplateVars};
+pub use registry::{ActionFactory, ActionRegistry};
+pub use retry::{Attempt, AttemptLog, Backoff, RetryPolicy, RetryingAction};
+pub use script::{RunScriptAction, ScriptConfig, ScriptMode, ScriptOutput};
+pub use sequence::{ActionSpec, SequenceAction, StepLog, StepOutcome, StepResult};
+#[cfg(feature = "webhook")]
+pub use webhook::{HttpMethod, WebhookAction, WebhookConfig};
+
 // ---------------------------------------------------------------------------
 // Error types
 // ---------------------------------------------------------------------------
@@ -37,6 +63,43 @@ pub enum ActionError {
     /// The OS rejected the action request.
     #[error("os error: {0}")]
     OsError(String),
+
+    /// The action did not finish within its time limit.
+    #[error("action timed out: {0}")]
+    Timeout(String),
+
+    /// No action is registered under the requested type id.
+    #[error("unknown action type: {0}")]
+    UnknownType(String),
+
+    /// The action parameters are invalid.
+    #[error("invalid action config: {0}")]
+    InvalidConfig(String),
+}
+
+impl ActionError {
+    /// Whether trying again might succeed: a failed run, an OS error, or a
+    /// timeout. Unsupported actions, missing privileges and bad configs
+    /// fail the same way every time.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Self::ExecutionFailed(_) | Self::OsError(_) | Self::Timeout(_)
+        )
+    }
+
+    /// Rewrite the message, keeping the kind.
+    pub(crate) fn map_message(self, f: impl FnOnce(String) -> String) -> Self {
+        match self {
+            Self::NotSupported(m) => Self::NotSupported(f(m)),
+            Self::ExecutionFailed(m) => Self::ExecutionFailed(f(m)),
+            Self::InsufficientPrivileges(m) => Self::InsufficientPrivileges(f(m)),
+            Self::OsError(m) => Self::OsError(f(m)),
+            Self::Timeout(m) => Self::Timeout(f(m)),
+            Self::UnknownType(m) => Self::UnknownType(f(m)),
+            Self::InvalidConfig(m) => Self::InvalidConfig(f(m)),
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -54,6 +117,13 @@ pub struct ActionInfo {
     pub description: String,
     /// Whether this action is available on the current platform.
     pub available: bool,
+    /// Whether this action needs administrator rights to execute.
+    #[serde(default)]
+    pub requires_elevation: bool,
+    /// JSON Schema of the accepted params, for actions that take any, so
+    /// the frontend can render a form.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params_schema: Option<serde_json::Value>,
 }
 
 // ---------------------------------------------------------------------------
@@ -90,6 +160,14 @@ pub trait Action: Send + Sync {
     /// This performs the actual side effect (e.g., initiating shutdown).
     /// Call `validate()` first to ensure the action is possible.
     async fn execute(&self) -> Result<(), ActionError>;
+
+    /// Execute the action and return optional details for the activity
+    /// log (e.g. a script's exit code and output).
+    ///
+    /// The default runs [`execute`](Self::execute) and reports nothing.
+    async fn execute_with_details(&self) -> Result<Option<String>, ActionError> {
+        self.execute().await.map(|()| None)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -122,6 +200,8 @@ mod tests {
                 name: "Mock Action".to_string(),
                 description: "A test action".to_string(),
                 available: !self.should_fail_validate,
+                requires_elevation: false,
+                params_schema: None,
             }
         }
 
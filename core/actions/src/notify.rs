@@ -0,0 +1,345 @@
+//! Notify action — raises a desktop notification instead of acting.
+//!
+//! Used for dry runs: the full trigger/condition/countdown pipeline runs,
+//! but the final step only tells the user what would have happened. The
+//! notification itself goes through an injected [`Notifier`], so this
+//! crate stays free of UI dependencies.
+//!
+//! Title and body are templates: `{name}` is replaced with the current
+//! value of `name` in the shared [`TemplateVars`] (e.g. `{download_bps}`).
+//! Unknown placeholders are left as written.
+
+use crate::{Action, ActionError, ActionInfo};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Action type id.
+pub const NOTIFY_ACTION_ID: &str = "notify";
+
+/// Default notification title.
+pub const DEFAULT_NOTIFY_TITLE: &str = "FlowWatcher";
+
+/// Default notification body.
+pub const DEFAULT_NOTIFY_BODY: &str = "Would have executed {action}";
+
+// ---------------------------------------------------------------------------
+// Notifier
+// ---------------------------------------------------------------------------
+
+/// Shows a notification to the user. Implemented by the host UI.
+pub trait Notifier: Send + Sync {
+    /// Show a notification with the given title and body.
+    fn notify(&self, title: &str, body: &str) -> Result<(), ActionError>;
+}
+
+// ---------------------------------------------------------------------------
+// Template variables
+// ---------------------------------------------------------------------------
+
+/// Values interpolated into notification templates.
+///
+/// Cheap to clone; clones share the same values, so the monitoring loop
+/// can keep them current while the action holds a handle.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateVars(Arc<Mutex<BTreeMap<String, String>>>);
+
+impl TemplateVars {
+    /// Create an empty set of variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a variable, replacing any previous value.
+    pub fn set(&self, name: impl Into<String>, value: impl ToString) {
+        self.lock().insert(name.into(), value.to_string());
+    }
+
+    /// Current value of a variable.
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.lock().get(name).cloned()
+    }
+
+    /// Remove every variable.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Replace each `{name}` in `template` with the variable's value.
+    pub fn render(&self, template: &str) -> String {
+        let vars = self.lock();
+        let mut out = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            out.push_str(&rest[..open]);
+            let tail = &rest[open..];
+            let value = tail
+                .find('}')
+                .and_then(|close| vars.get(&tail[1..close]).map(|v| (close, v)));
+            match value {
+                Some((close, value)) => {
+                    out.push_str(value);
+                    rest = &tail[close + 1..];
+                }
+                None => {
+                    out.push('{');
+                    rest = &tail[1..];
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, String>> {
+        // The map stays consistent even if a holder panicked.
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+/// Notify parameters, as passed to the action registry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotifyConfig {
+    /// Notification title template.
+    #[serde(default = "default_title")]
+    pub title: String,
+    /// Notification body template.
+    #[serde(default = "default_body")]
+    pub body: String,
+}
+
+fn default_title() -> String {
+    DEFAULT_NOTIFY_TITLE.to_string()
+}
+
+fn default_body() -> String {
+    DEFAULT_NOTIFY_BODY.to_string()
+}
+
+impl NotifyConfig {
+    /// JSON Schema of the notify params, for [`ActionInfo::params_schema`].
+    pub fn schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "title": {
+                    "type": "string",
+                    "minLength": 1,
+                    "default": DEFAULT_NOTIFY_TITLE,
+                    "description": "Template; {name} is replaced with a trigger value"
+                },
+                "body": {
+                    "type": "string",
+                    "default": DEFAULT_NOTIFY_BODY,
+                    "description": "Template, e.g. \"Idle at {download_bps} B/s\""
+                }
+            }
+        })
+    }
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            title: default_title(),
+            body: default_body(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// NotifyAction
+// ---------------------------------------------------------------------------
+
+/// An action that shows a notification built from templates.
+#[derive(Clone)]
+pub struct NotifyAction {
+    /// Title and body templates.
+    config: NotifyConfig,
+    /// Shows the notification.
+    notifier: Arc<dyn Notifier>,
+    /// Values interpolated into the templates.
+    vars: TemplateVars,
+}
+
+impl NotifyAction {
+    /// Create a notify action with no template variables.
+    pub fn new(config: NotifyConfig, notifier: Arc<dyn Notifier>) -> Self {
+        Self {
+            config,
+            notifier,
+            vars: TemplateVars::new(),
+        }
+    }
+
+    /// Build from registry params. `null` uses the default templates.
+    pub fn from_config(
+        params: &serde_json::Value,
+        notifier: Arc<dyn Notifier>,
+    ) -> Result<Self, ActionError> {
+        if params.is_null() {
+            return Ok(Self::new(NotifyConfig::default(), notifier));
+        }
+        serde_json::from_value(params.clone())
+            .map(|config| Self::new(config, notifier))
+            .map_err(|e| ActionError::InvalidConfig(format!("{NOTIFY_ACTION_ID}: {e}")))
+    }
+
+    /// Interpolate templates from a shared set of variables.
+    pub fn with_vars(mut self, vars: TemplateVars) -> Self {
+        self.vars = vars;
+        self
+    }
+
+    /// The template parameters.
+    pub fn config(&self) -> &NotifyConfig {
+        &self.config
+    }
+
+    /// The title and body with the current variables filled in.
+    pub fn render(&self) -> (String, String) {
+        (
+            self.vars.render(&self.config.title),
+            self.vars.render(&self.config.body),
+        )
+    }
+}
+
+#[async_trait]
+impl Action for NotifyAction {
+    fn name(&self) -> &str {
+        "Notify"
+    }
+
+    fn action_type(&self) -> &str {
+        NOTIFY_ACTION_ID
+    }
+
+    fn info(&self) -> ActionInfo {
+        ActionInfo {
+            id: NOTIFY_ACTION_ID.to_string(),
+            name: self.name().to_string(),
+            description: "Shows a desktop notification without changing the system".to_string(),
+            available: true,
+            requires_elevation: false,
+            params_schema: Some(NotifyConfig::schema()),
+        }
+    }
+
+    /// Check that the title template is not blank.
+    async fn validate(&self) -> Result<(), ActionError> {
+        if self.config.title.trim().is_empty() {
+            return Err(ActionError::InvalidConfig(
+                "notification title is empty".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn execute(&self) -> Result<(), ActionError> {
+        self.execute_with_details().await.map(|_| ())
+    }
+
+    /// Show the notification and report the rendered body.
+    async fn execute_with_details(&self) -> Result<Option<String>, ActionError> {
+        let (title, body) = self.render();
+        self.notifier.notify(&title, &body)?;
+        Ok(Some(body))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records every notification it is asked to show.
+    #[derive(Default)]
+    struct RecordingNotifier(Mutex<Vec<(String, String)>>);
+
+    impl Notifier for RecordingNotifier {
+        fn notify(&self, title: &str, body: &str) -> Result<(), ActionError> {
+            self.0
+                .lock()
+                .unwrap()
+                .push((title.to_string(), body.to_string()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn render_replaces_known_placeholders_only() {
+        let vars = TemplateVars::new();
+        vars.set("download_bps", 1200);
+        vars.set("action", "Shut Down");
+        assert_eq!(
+            vars.render("{action} at {download_bps} B/s, {unknown} {"),
+            "Shut Down at 1200 B/s, {unknown} {"
+        );
+    }
+
+    #[test]
+    fn null_params_use_default_templates() {
+        let notifier: Arc<dyn Notifier> = Arc::new(RecordingNotifier::default());
+        let action = NotifyAction::from_config(&serde_json::Value::Null, notifier).unwrap();
+        assert_eq!(action.config(), &NotifyConfig::default());
+        assert!(NotifyAction::from_config(
+            &serde_json::json!({ "title": 3 }),
+            Arc::new(RecordingNotifier::default())
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn execute_renders_current_vars() {
+        let notifier = Arc::new(RecordingNotifier::default());
+        let vars = TemplateVars::new();
+        let action = NotifyAction::new(
+            NotifyConfig {
+                title: "Dry run".into(),
+                body: "Would have executed {action} at {download_bps} B/s".into(),
+            },
+            notifier.clone(),
+        )
+        .with_vars(vars.clone());
+
+        vars.set("action", "Shut Down");
+        vars.set("download_bps", 0);
+        let details = action.execute_with_details().await.unwrap();
+
+        assert_eq!(
+            details.as_deref(),
+            Some("Would have executed Shut Down at 0 B/s")
+        );
+        assert_eq!(
+            notifier.0.lock().unwrap().as_slice(),
+            &[(
+                "Dry run".to_string(),
+                "Would have executed Shut Down at 0 B/s".to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn blank_title_fails_validation() {
+        let action = NotifyAction::new(
+            NotifyConfig {
+                title: " ".into(),
+                ..NotifyConfig::default()
+            },
+            Arc::new(RecordingNotifier::default()),
+        );
+        assert!(matches!(
+            action.validate().await,
+            Err(ActionError::InvalidConfig(_))
+        ));
+    }
+}
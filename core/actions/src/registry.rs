@@ -0,0 +1,137 @@
+//! Action registry — construct actions by type id.
+//!
+//! Factories are registered under an `action_type` string and build a
+//! boxed [`Action`] from a JSON params blob, so the engine can resolve
+//! configured actions without string-matching concrete types.
+
+use crate::{Action, ActionError, ActionInfo};
+use std::collections::BTreeMap;
+
+/// Factory closure that builds an action from its JSON params.
+pub type ActionFactory =
+    Box<dyn Fn(&serde_json::Value) -> Result<Box<dyn Action>, ActionError> + Send + Sync>;
+
+/// Maps action type ids to factories.
+#[derive(Default)]
+pub struct ActionRegistry {
+    factories: BTreeMap<String, ActionFactory>,
+}
+
+impl ActionRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an action type. Replaces any existing factory with the same id.
+    pub fn register<F>(&mut self, action_type: impl Into<String>, factory: F)
+    where
+        F: Fn(&serde_json::Value) -> Result<Box<dyn Action>, ActionError> + Send + Sync + 'static,
+    {
+        self.factories.insert(action_type.into(), Box::new(factory));
+    }
+
+    /// Construct an action of the given type from its params.
+    pub fn create(
+        &self,
+        action_type: &str,
+        params: &serde_json::Value,
+    ) -> Result<Box<dyn Action>, ActionError> {
+        let factory = self
+            .factories
+            .get(action_type)
+            .ok_or_else(|| ActionError::UnknownType(action_type.to_string()))?;
+        factory(params)
+    }
+
+    /// Whether an action type is registered.
+    pub fn contains(&self, action_type: &str) -> bool {
+        self.factories.contains_key(action_type)
+    }
+
+    /// Registered action type ids, sorted.
+    pub fn ids(&self) -> Vec<String> {
+        self.factories.keys().cloned().collect()
+    }
+
+    /// Metadata for every registered action, built with default (null) params.
+    ///
+    /// Types whose factory rejects null params are skipped.
+    pub fn infos(&self) -> Vec<ActionInfo> {
+        self.factories
+            .values()
+            .filter_map(|f| f(&serde_json::Value::Null).ok())
+            .map(|a| a.info())
+            .collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    struct NoopAction;
+
+    #[async_trait]
+    impl Action for NoopAction {
+        fn name(&self) -> &str {
+            "Noop"
+        }
+
+        fn action_type(&self) -> &str {
+            "noop"
+        }
+
+        fn info(&self) -> ActionInfo {
+            ActionInfo {
+                id: "noop".to_string(),
+                name: "Noop".to_string(),
+                description: "Does nothing".to_string(),
+                available: true,
+                requires_elevation: false,
+                params_schema: None,
+            }
+        }
+
+        async fn validate(&self) -> Result<(), ActionError> {
+            Ok(())
+        }
+
+        async fn execute(&self) -> Result<(), ActionError> {
+            Ok(())
+        }
+    }
+
+    fn registry() -> ActionRegistry {
+        let mut registry = ActionRegistry::new();
+        registry.register("noop", |_| Ok(Box::new(NoopAction)));
+        registry
+    }
+
+    #[test]
+    fn creates_registered_action() {
+        let action = registry()
+            .create("noop", &serde_json::Value::Null)
+            .expect("should create");
+        assert_eq!(action.action_type(), "noop");
+    }
+
+    #[test]
+    fn unknown_type_is_an_error() {
+        let result = registry().create("teleport", &serde_json::Value::Null);
+        assert!(matches!(result, Err(ActionError::UnknownType(t)) if t == "teleport"));
+    }
+
+    #[test]
+    fn lists_ids_and_infos() {
+        let registry = registry();
+        assert!(registry.contains("noop"));
+        assert_eq!(registry.ids(), vec!["noop".to_string()]);
+        assert_eq!(registry.infos()[0].id, "noop");
+    }
+}
@@ -0,0 +1,409 @@
+//! Retrying action — runs an action again when it fails transiently.
+//!
+//! A webhook whose endpoint times out now and then should not end the
+//! whole flow. [`RetryingAction`] wraps any action and, per its
+//! [`RetryPolicy`], runs `execute()` up to `max_attempts` times with a
+//! fixed or exponential delay in between. Only errors the retry predicate
+//! accepts are retried ([`ActionError::is_retryable`] by default); an
+//! unsupported action or missing privileges fail at once.
+//!
+//! Each failed attempt is pushed to an optional shared [`AttemptLog`] so
+//! the host can record it. The final error keeps its kind and says how
+//! many attempts were made.
+
+use crate::{Action, ActionError, ActionInfo};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// ---------------------------------------------------------------------------
+// Policy
+// ---------------------------------------------------------------------------
+
+/// Delay between attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Backoff {
+    /// The same delay before every retry.
+    Fixed {
+        /// Delay in milliseconds.
+        delay_ms: u64,
+    },
+    /// A delay that doubles after every retry, up to `max_ms`.
+    Exponential {
+        /// Delay before the first retry, in milliseconds.
+        initial_ms: u64,
+        /// Longest delay, in milliseconds.
+        max_ms: u64,
+    },
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::Exponential {
+            initial_ms: 1_000,
+            max_ms: 30_000,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before retry number `retry` (1 for the second attempt).
+    pub fn delay(&self, retry: u32) -> Duration {
+        let ms = match *self {
+            Self::Fixed { delay_ms } => delay_ms,
+            Self::Exponential { initial_ms, max_ms } => {
+                let factor = 1u64
+                    .checked_shl(retry.saturating_sub(1))
+                    .unwrap_or(u64::MAX);
+                initial_ms.saturating_mul(factor).min(max_ms)
+            }
+        };
+        Duration::from_millis(ms)
+    }
+}
+
+/// How often, and how patiently, a failed action is retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first; 1 means no retries.
+    pub max_attempts: u32,
+    /// Delay between attempts.
+    #[serde(default)]
+    pub backoff: Backoff,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff: Backoff::default(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Up to `max_attempts` attempts with the default backoff.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Self::default()
+        }
+    }
+
+    /// Set the delay between attempts.
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Whether a failure is ever retried.
+    pub fn retries(&self) -> bool {
+        self.max_attempts > 1
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Attempt log
+// ---------------------------------------------------------------------------
+
+/// One failed attempt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attempt {
+    /// 1-based attempt number.
+    pub attempt: u32,
+    /// Attempts allowed by the policy.
+    pub max_attempts: u32,
+    /// The attempt's error message.
+    pub error: String,
+    /// Delay before the next attempt, or `None` if this was the last.
+    pub retry_in_ms: Option<u64>,
+}
+
+impl std::fmt::Display for Attempt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Attempt {} of {} failed: {}",
+            self.attempt, self.max_attempts, self.error
+        )?;
+        match self.retry_in_ms {
+            Some(ms) => write!(f, "; retrying in {ms} ms"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Collects failed attempts of retrying actions.
+///
+/// Cheap to clone; clones share the same attempts, so the host can drain
+/// them after the scheduler has run the action.
+#[derive(Debug, Clone, Default)]
+pub struct AttemptLog(Arc<Mutex<Vec<Attempt>>>);
+
+impl AttemptLog {
+    /// Create an empty log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an attempt.
+    pub fn push(&self, attempt: Attempt) {
+        self.lock().push(attempt);
+    }
+
+    /// Remove and return every attempt collected so far.
+    pub fn take(&self) -> Vec<Attempt> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Attempt>> {
+        // A plain list stays consistent even if a holder panicked.
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// RetryingAction
+// ---------------------------------------------------------------------------
+
+/// Wraps an action and retries its execution per a [`RetryPolicy`].
+///
+/// `validate()` is passed through once; only `execute()` is retried.
+pub struct RetryingAction {
+    /// The wrapped action.
+    inner: Box<dyn Action>,
+    /// Attempts and backoff.
+    policy: RetryPolicy,
+    /// Which errors are worth another attempt.
+    retryable: fn(&ActionError) -> bool,
+    /// Where failed attempts are reported, if anywhere.
+    attempt_log: Option<AttemptLog>,
+}
+
+impl RetryingAction {
+    /// Wrap `inner`, retrying per `policy`.
+    pub fn new(inner: Box<dyn Action>, policy: RetryPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            retryable: ActionError::is_retryable,
+            attempt_log: None,
+        }
+    }
+
+    /// Retry only errors for which `retryable` returns `true`.
+    pub fn with_retryable(mut self, retryable: fn(&ActionError) -> bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+
+    /// Report each failed attempt to `log`.
+    pub fn with_attempt_log(mut self, log: AttemptLog) -> Self {
+        self.attempt_log = Some(log);
+        self
+    }
+
+    /// Run `execute_with_details` until it succeeds, fails permanently, or
+    /// the attempts run out.
+    async fn run(&self) -> Result<Option<String>, ActionError> {
+        let max_attempts = self.policy.max_attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            let error = match self.inner.execute_with_details().await {
+                Ok(details) => return Ok(details),
+                Err(e) => e,
+            };
+            let last = attempt >= max_attempts || !(self.retryable)(&error);
+            let delay = (!last).then(|| self.policy.backoff.delay(attempt));
+            if let Some(log) = &self.attempt_log {
+                log.push(Attempt {
+                    attempt,
+                    max_attempts,
+                    error: error.to_string(),
+                    retry_in_ms: delay.map(|d| d.as_millis() as u64),
+                });
+            }
+            match delay {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None if attempt > 1 => {
+                    return Err(error.map_message(|m| format!("{m} (after {attempt} attempts)")));
+                }
+                None => return Err(error),
+            }
+            attempt += 1;
+        }
+    }
+}
+
+#[async_trait]
+impl Action for RetryingAction {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn action_type(&self) -> &str {
+        self.inner.action_type()
+    }
+
+    fn info(&self) -> ActionInfo {
+        self.inner.info()
+    }
+
+    async fn validate(&self) -> Result<(), ActionError> {
+        self.inner.validate().await
+    }
+
+    async fn execute(&self) -> Result<(), ActionError> {
+        self.run().await.map(|_| ())
+    }
+
+    async fn execute_with_details(&self) -> Result<Option<String>, ActionError> {
+        self.run().await
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Fails with `error` for the first `failures` runs, then succeeds.
+    struct Flaky {
+        failures: u32,
+        error: fn(String) -> ActionError,
+        runs: Arc<AtomicU32>,
+    }
+
+    impl Flaky {
+        fn new(failures: u32, error: fn(String) -> ActionError) -> (Self, Arc<AtomicU32>) {
+            let runs = Arc::new(AtomicU32::new(0));
+            let flaky = Self {
+                failures,
+                error,
+                runs: runs.clone(),
+            };
+            (flaky, runs)
+        }
+    }
+
+    #[async_trait]
+    impl Action for Flaky {
+        fn name(&self) -> &str {
+            "Flaky"
+        }
+
+        fn action_type(&self) -> &str {
+            "flaky"
+        }
+
+        fn info(&self) -> ActionInfo {
+            ActionInfo {
+                id: "flaky".to_string(),
+                name: "Flaky".to_string(),
+                description: "Fails a few times".to_string(),
+                available: true,
+                requires_elevation: false,
+                params_schema: None,
+            }
+        }
+
+        async fn validate(&self) -> Result<(), ActionError> {
+            Ok(())
+        }
+
+        async fn execute(&self) -> Result<(), ActionError> {
+            let run = self.runs.fetch_add(1, Ordering::SeqCst) + 1;
+            if run <= self.failures {
+                Err((self.error)(format!("run {run}")))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn quick(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy::new(max_attempts).with_backoff(Backoff::Fixed { delay_ms: 1 })
+    }
+
+    #[tokio::test]
+    async fn fails_twice_then_succeeds() {
+        let (flaky, runs) = Flaky::new(2, ActionError::ExecutionFailed);
+        let log = AttemptLog::new();
+        let action = RetryingAction::new(Box::new(flaky), quick(3)).with_attempt_log(log.clone());
+
+        assert!(action.execute().await.is_ok());
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        let attempts = log.take();
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(attempts[0].attempt, 1);
+        assert_eq!(attempts[0].max_attempts, 3);
+        assert_eq!(attempts[0].retry_in_ms, Some(1));
+        assert_eq!(attempts[1].error, "action execution failed: run 2");
+        assert_eq!(
+            attempts[0].to_string(),
+            "Attempt 1 of 3 failed: action execution failed: run 1; retrying in 1 ms"
+        );
+    }
+
+    #[tokio::test]
+    async fn final_failure_reports_the_attempt_count() {
+        let (flaky, runs) = Flaky::new(5, ActionError::OsError);
+        let log = AttemptLog::new();
+        let action = RetryingAction::new(Box::new(flaky), quick(3)).with_attempt_log(log.clone());
+
+        let err = action.execute().await.unwrap_err();
+        assert!(matches!(err, ActionError::OsError(_)));
+        assert_eq!(err.to_string(), "os error: run 3 (after 3 attempts)");
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        let attempts = log.take();
+        assert_eq!(attempts.len(), 3);
+        assert_eq!(attempts[2].retry_in_ms, None);
+    }
+
+    #[tokio::test]
+    async fn permanent_errors_are_not_retried() {
+        let (flaky, runs) = Flaky::new(1, ActionError::InsufficientPrivileges);
+        let action = RetryingAction::new(Box::new(flaky), quick(3));
+        let err = action.execute().await.unwrap_err();
+        assert_eq!(err.to_string(), "insufficient privileges: run 1");
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        // A custom predicate can refuse what the default would retry.
+        let (flaky, runs) = Flaky::new(1, ActionError::ExecutionFailed);
+        let action = RetryingAction::new(Box::new(flaky), quick(3)).with_retryable(|_| false);
+        assert!(action.execute().await.is_err());
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn backoff_delays() {
+        let fixed = Backoff::Fixed { delay_ms: 500 };
+        assert_eq!(fixed.delay(1), Duration::from_millis(500));
+        assert_eq!(fixed.delay(4), Duration::from_millis(500));
+
+        let exponential = Backoff::Exponential {
+            initial_ms: 1_000,
+            max_ms: 5_000,
+        };
+        assert_eq!(exponential.delay(1), Duration::from_secs(1));
+        assert_eq!(exponential.delay(2), Duration::from_secs(2));
+        assert_eq!(exponential.delay(3), Duration::from_secs(4));
+        assert_eq!(exponential.delay(4), Duration::from_secs(5));
+        assert_eq!(exponential.delay(100), Duration::from_secs(5));
+
+        let policy: RetryPolicy =
+            serde_json::from_str(r#"{"max_attempts":3,"backoff":{"kind":"fixed","delay_ms":250}}"#)
+                .unwrap();
+        assert_eq!(
+            policy,
+            quick(3).with_backoff(Backoff::Fixed { delay_ms: 250 })
+        );
+        assert!(!RetryPolicy::default().retries());
+    }
+}
@@ -0,0 +1,468 @@
+//! Run-script action — runs a user-provided program or script.
+//!
+//! In [`ScriptMode::Wait`] the program runs to completion within a time
+//! limit (it is killed if it overruns) and a non-zero exit fails the
+//! action with the captured stderr. In [`ScriptMode::FireAndForget`] the
+//! program is started and left running.
+
+use crate::{Action, ActionError, ActionInfo};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+/// Action type id.
+pub const RUN_SCRIPT_ACTION_ID: &str = "run_script";
+
+/// Default time limit in seconds for [`ScriptMode::Wait`].
+pub const DEFAULT_SCRIPT_TIMEOUT_SECS: u64 = 60;
+
+/// Longest output (in characters) kept per stream in a [`ScriptOutput`].
+pub const MAX_OUTPUT_CHARS: usize = 500;
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+/// Whether the action waits for the program to finish.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptMode {
+    /// Wait for exit (up to the timeout) and check the exit code.
+    #[default]
+    Wait,
+    /// Start the program and return immediately.
+    FireAndForget,
+}
+
+/// Run-script parameters, as passed to the action registry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptConfig {
+    /// Program to run: a path, or a bare name looked up on `PATH`.
+    pub program: PathBuf,
+    /// Arguments passed to the program.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Working directory (the app's own when absent).
+    #[serde(default)]
+    pub working_dir: Option<PathBuf>,
+    /// Time limit in seconds for [`ScriptMode::Wait`].
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Whether to wait for completion.
+    #[serde(default)]
+    pub mode: ScriptMode,
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_SCRIPT_TIMEOUT_SECS
+}
+
+impl ScriptConfig {
+    /// JSON Schema of the run-script params, for [`ActionInfo::params_schema`].
+    pub fn schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "program": { "type": "string", "description": "Path, or a bare name on PATH" },
+                "args": { "type": "array", "items": { "type": "string" } },
+                "working_dir": { "type": ["string", "null"] },
+                "timeout_secs": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "default": DEFAULT_SCRIPT_TIMEOUT_SECS
+                },
+                "mode": { "enum": ["wait", "fire_and_forget"], "default": "wait" }
+            },
+            "required": ["program"]
+        })
+    }
+}
+
+impl Default for ScriptConfig {
+    fn default() -> Self {
+        Self {
+            program: PathBuf::new(),
+            args: Vec::new(),
+            working_dir: None,
+            timeout_secs: DEFAULT_SCRIPT_TIMEOUT_SECS,
+            mode: ScriptMode::default(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Output
+// ---------------------------------------------------------------------------
+
+/// What a finished (or started) program reported.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptOutput {
+    /// Exit code (None when started fire-and-forget or killed by a signal).
+    pub exit_code: Option<i32>,
+    /// Trimmed stdout, capped at [`MAX_OUTPUT_CHARS`].
+    pub stdout: String,
+    /// Trimmed stderr, capped at [`MAX_OUTPUT_CHARS`].
+    pub stderr: String,
+}
+
+impl ScriptOutput {
+    /// One-line summary for an activity log entry.
+    pub fn summary(&self) -> String {
+        let mut summary = match self.exit_code {
+            Some(code) => format!("exit code {code}"),
+            None => "started".to_string(),
+        };
+        if !self.stdout.is_empty() {
+            summary.push_str(&format!("; output: {}", self.stdout));
+        }
+        summary
+    }
+}
+
+/// Decode and trim captured output, capped at [`MAX_OUTPUT_CHARS`] characters.
+fn trimmed(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim();
+    match text.char_indices().nth(MAX_OUTPUT_CHARS) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// RunScriptAction
+// ---------------------------------------------------------------------------
+
+/// An action that runs a program or script.
+#[derive(Debug, Clone, Default)]
+pub struct RunScriptAction {
+    /// Run parameters.
+    config: ScriptConfig,
+}
+
+impl RunScriptAction {
+    /// Create a run-script action from its parameters.
+    pub fn new(config: ScriptConfig) -> Self {
+        Self { config }
+    }
+
+    /// Build from registry params. `null` yields an unconfigured action
+    /// whose `validate()` fails, so the type can still be listed.
+    pub fn from_config(params: &serde_json::Value) -> Result<Self, ActionError> {
+        if params.is_null() {
+            return Ok(Self::default());
+        }
+        serde_json::from_value(params.clone())
+            .map(Self::new)
+            .map_err(|e| ActionError::InvalidConfig(format!("run_script: {e}")))
+    }
+
+    /// The run parameters.
+    pub fn config(&self) -> &ScriptConfig {
+        &self.config
+    }
+
+    /// Resolve the program to an existing executable file.
+    fn resolve_program(&self) -> Result<PathBuf, ActionError> {
+        let program = &self.config.program;
+        if program.as_os_str().is_empty() {
+            return Err(ActionError::InvalidConfig("no program configured".into()));
+        }
+
+        // A bare name is looked up on PATH, like a shell would.
+        let found = if program.components().count() == 1 && !program.is_absolute() {
+            std::env::var_os("PATH")
+                .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+                .unwrap_or_default()
+                .into_iter()
+                .find_map(|dir| executable_in(&dir.join(program)))
+        } else {
+            executable_in(program)
+        };
+        found.ok_or_else(|| {
+            ActionError::NotSupported(format!(
+                "program {} does not exist or is not executable",
+                program.display()
+            ))
+        })
+    }
+
+    /// Run the program and report its exit code and output.
+    pub async fn execute_with_output(&self) -> Result<ScriptOutput, ActionError> {
+        let program = self.resolve_program()?;
+        let wait = self.config.mode == ScriptMode::Wait;
+
+        let mut command = tokio::process::Command::new(&program);
+        command.args(&self.config.args).stdin(Stdio::null());
+        if let Some(dir) = &self.config.working_dir {
+            command.current_dir(dir);
+        }
+        if wait {
+            command
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true);
+        } else {
+            command.stdout(Stdio::null()).stderr(Stdio::null());
+        }
+
+        let child = command.spawn().map_err(|e| {
+            ActionError::ExecutionFailed(format!("failed to start {}: {e}", program.display()))
+        })?;
+        if !wait {
+            return Ok(ScriptOutput::default());
+        }
+
+        // Dropping the future on timeout kills the child.
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        let output = tokio::time::timeout(timeout, child.wait_with_output())
+            .await
+            .map_err(|_| {
+                ActionError::Timeout(format!(
+                    "{} did not finish within {timeout:?}",
+                    program.display()
+                ))
+            })?
+            .map_err(|e| ActionError::ExecutionFailed(e.to_string()))?;
+
+        let result = ScriptOutput {
+            exit_code: output.status.code(),
+            stdout: trimmed(&output.stdout),
+            stderr: trimmed(&output.stderr),
+        };
+        if output.status.success() {
+            return Ok(result);
+        }
+
+        let status = match result.exit_code {
+            Some(code) => format!("exit code {code}"),
+            None => "termination by signal".to_string(),
+        };
+        Err(ActionError::ExecutionFailed(if result.stderr.is_empty() {
+            format!("{} failed with {status}", program.display())
+        } else {
+            format!(
+                "{} failed with {status}: {}",
+                program.display(),
+                result.stderr
+            )
+        }))
+    }
+}
+
+/// `path` if it is an executable file.
+fn executable_in(path: &Path) -> Option<PathBuf> {
+    if is_executable(path) {
+        return Some(path.to_path_buf());
+    }
+    // Windows resolves `tool` to `tool.exe` and friends.
+    if cfg!(windows) && path.extension().is_none() {
+        return ["exe", "cmd", "bat", "com"]
+            .iter()
+            .map(|ext| path.with_extension(ext))
+            .find(|candidate| is_executable(candidate));
+    }
+    None
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[async_trait]
+impl Action for RunScriptAction {
+    fn name(&self) -> &str {
+        "Run Script"
+    }
+
+    fn action_type(&self) -> &str {
+        RUN_SCRIPT_ACTION_ID
+    }
+
+    fn info(&self) -> ActionInfo {
+        ActionInfo {
+            id: RUN_SCRIPT_ACTION_ID.to_string(),
+            name: self.name().to_string(),
+            description: "Runs a program or script with the given arguments".to_string(),
+            available: true,
+            requires_elevation: false,
+            params_schema: Some(ScriptConfig::schema()),
+        }
+    }
+
+    /// Check that the program exists and is executable, and that the
+    /// working directory (if set) exists.
+    async fn validate(&self) -> Result<(), ActionError> {
+        self.resolve_program()?;
+        if let Some(dir) = &self.config.working_dir {
+            if !dir.is_dir() {
+                return Err(ActionError::NotSupported(format!(
+                    "working directory {} does not exist",
+                    dir.display()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    async fn execute(&self) -> Result<(), ActionError> {
+        self.execute_with_output().await.map(|_| ())
+    }
+
+    async fn execute_with_details(&self) -> Result<Option<String>, ActionError> {
+        self.execute_with_output()
+            .await
+            .map(|output| Some(output.summary()))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use flowwatcher_testing::ScratchDir;
+
+    /// Write an executable shell script.
+    fn write_script(dir: &Path, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join("script.sh");
+        std::fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn action(program: PathBuf) -> RunScriptAction {
+        RunScriptAction::new(ScriptConfig {
+            program,
+            ..ScriptConfig::default()
+        })
+    }
+
+    #[test]
+    fn config_defaults() {
+        let action =
+            RunScriptAction::from_config(&serde_json::json!({ "program": "unmount.ps1" })).unwrap();
+        assert_eq!(action.config().mode, ScriptMode::Wait);
+        assert_eq!(action.config().timeout_secs, DEFAULT_SCRIPT_TIMEOUT_SECS);
+        assert!(action.config().args.is_empty());
+
+        assert!(matches!(
+            RunScriptAction::from_config(&serde_json::json!({ "args": [] })),
+            Err(ActionError::InvalidConfig(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn validate_checks_program_and_working_dir() {
+        let dir = ScratchDir::new("script-validate");
+        let script = write_script(&dir, "exit 0");
+        action(script.clone()).validate().await.unwrap();
+        action("sh".into()).validate().await.expect("found on PATH");
+
+        assert!(RunScriptAction::default().validate().await.is_err());
+        assert!(action(dir.join("missing.sh")).validate().await.is_err());
+
+        let plain = dir.join("plain.txt");
+        std::fs::write(&plain, "not executable").unwrap();
+        assert!(action(plain).validate().await.is_err());
+
+        let mut bad_dir = action(script);
+        bad_dir.config.working_dir = Some(dir.join("nope"));
+        assert!(bad_dir.validate().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn wait_mode_reports_exit_code_and_output() {
+        let dir = ScratchDir::new("script-wait");
+        let script = write_script(&dir, "echo \"  $1 in $(basename \"$PWD\")  \"");
+        let action = RunScriptAction::new(ScriptConfig {
+            program: script,
+            args: vec!["unmounted".into()],
+            working_dir: Some(dir.to_path_buf()),
+            ..ScriptConfig::default()
+        });
+
+        let output = action.execute_with_output().await.unwrap();
+        let dir_name = dir.file_name().unwrap().to_string_lossy();
+        assert_eq!(output.exit_code, Some(0));
+        assert_eq!(output.stdout, format!("unmounted in {dir_name}"));
+        assert_eq!(
+            action.execute_with_details().await.unwrap().unwrap(),
+            format!("exit code 0; output: {}", output.stdout)
+        );
+    }
+
+    #[tokio::test]
+    async fn non_zero_exit_fails_with_stderr() {
+        let dir = ScratchDir::new("script-fail");
+        let script = write_script(&dir, "echo 'drive busy' >&2\nexit 3");
+        match action(script).execute().await {
+            Err(ActionError::ExecutionFailed(message)) => {
+                assert!(message.contains("exit code 3"), "{message}");
+                assert!(message.contains("drive busy"), "{message}");
+            }
+            other => panic!("expected ExecutionFailed, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn overrunning_script_times_out() {
+        let dir = ScratchDir::new("script-timeout");
+        let mut action = action(write_script(&dir, "sleep 5"));
+        action.config.timeout_secs = 0;
+        assert!(matches!(
+            action.execute().await,
+            Err(ActionError::Timeout(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn fire_and_forget_returns_immediately() {
+        let dir = ScratchDir::new("script-forget");
+        let marker = dir.join("done");
+        let script = write_script(&dir, &format!("sleep 1\ntouch '{}'", marker.display()));
+        let mut action = action(script);
+        action.config.mode = ScriptMode::FireAndForget;
+
+        let started = std::time::Instant::now();
+        let output = action.execute_with_output().await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(output.exit_code, None);
+        assert_eq!(output.summary(), "started");
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn long_output_is_capped() {
+        let long = "x".repeat(MAX_OUTPUT_CHARS + 50);
+        let capped = trimmed(format!("\n{long}\n").as_bytes());
+        assert_eq!(capped.chars().count(), MAX_OUTPUT_CHARS + 1);
+        assert!(capped.ends_with('…'));
+    }
+
+    #[test]
+    fn schema_covers_every_config_field() {
+        let schema = ScriptConfig::schema();
+        let config = serde_json::to_value(ScriptConfig::default()).unwrap();
+        for field in config.as_object().unwrap().keys() {
+            assert!(schema["properties"].get(field).is_some(), "{field}");
+        }
+        assert_eq!(
+            RunScriptAction::default().info().params_schema,
+            Some(schema)
+        );
+    }
+}
@@ -0,0 +1,538 @@
+//! Sequence action — runs several actions one after another.
+//!
+//! "Run the backup script, then hibernate" is one [`SequenceAction`]
+//! wrapping both steps. `validate()` validates every step up front;
+//! `execute()` runs them in order and stops at the first failure unless
+//! `continue_on_error` is set. The error names the failing step and what
+//! had already completed.
+//!
+//! Each step's outcome is pushed to an optional shared [`StepLog`] so the
+//! host can record one activity log entry per step. A retry policy applies
+//! to each step on its own, so a flaky upload does not re-run the backup
+//! that preceded it.
+
+use crate::{
+    Action, ActionError, ActionInfo, ActionRegistry, AttemptLog, RetryPolicy, RetryingAction,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+/// Action type id.
+pub const SEQUENCE_ACTION_ID: &str = "sequence";
+
+// ---------------------------------------------------------------------------
+// Specs
+// ---------------------------------------------------------------------------
+
+/// One step of a sequence, as configured: an action type and its params.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActionSpec {
+    /// Registered action type id (e.g. "run_script").
+    pub action_type: String,
+    /// Parameters for the action; `null` for system actions.
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
+impl ActionSpec {
+    /// A step with no params.
+    pub fn new(action_type: impl Into<String>) -> Self {
+        Self {
+            action_type: action_type.into(),
+            params: serde_json::Value::Null,
+        }
+    }
+
+    /// Set the step's params.
+    pub fn with_params(mut self, params: serde_json::Value) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// JSON Schema of a list of steps, for [`ActionInfo::params_schema`].
+    pub fn list_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "array",
+            "minItems": 1,
+            "items": {
+                "type": "object",
+                "properties": {
+                    "action_type": { "type": "string" },
+                    "params": { "description": "Params of the step's action" }
+                },
+                "required": ["action_type"]
+            }
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Step results
+// ---------------------------------------------------------------------------
+
+/// How one step of a sequence ended.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum StepOutcome {
+    /// The step executed, with any details it reported.
+    Succeeded {
+        /// Details for the activity log.
+        details: Option<String>,
+    },
+    /// The step failed.
+    Failed {
+        /// The step's error message.
+        error: String,
+    },
+    /// The step did not run because an earlier step failed.
+    Skipped,
+}
+
+/// The outcome of one step of a sequence run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepResult {
+    /// 1-based position in the sequence.
+    pub step: usize,
+    /// Number of steps in the sequence.
+    pub total: usize,
+    /// The step's action type id.
+    pub action_type: String,
+    /// The step's display name.
+    pub name: String,
+    /// How the step ended.
+    pub outcome: StepOutcome,
+}
+
+/// Collects step results of sequence runs.
+///
+/// Cheap to clone; clones share the same results, so the host can drain
+/// them after the scheduler has run the sequence.
+#[derive(Debug, Clone, Default)]
+pub struct StepLog(Arc<Mutex<Vec<StepResult>>>);
+
+impl StepLog {
+    /// Create an empty log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a step result.
+    pub fn push(&self, result: StepResult) {
+        self.lock().push(result);
+    }
+
+    /// Remove and return every result collected so far.
+    pub fn take(&self) -> Vec<StepResult> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<StepResult>> {
+        // A plain list stays consistent even if a holder panicked.
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Prefix an error's message with `context`, keeping its kind.
+fn in_step(context: &str, e: ActionError) -> ActionError {
+    e.map_message(|m| format!("{context}: {m}"))
+}
+
+// ---------------------------------------------------------------------------
+// SequenceAction
+// ---------------------------------------------------------------------------
+
+/// An action that runs its steps in order.
+pub struct SequenceAction {
+    /// Steps, in execution order.
+    steps: Vec<Box<dyn Action>>,
+    /// Whether later steps still run after one fails.
+    continue_on_error: bool,
+    /// Receives each step's result, if set.
+    step_log: Option<StepLog>,
+}
+
+impl SequenceAction {
+    /// Create a sequence from already-built steps.
+    pub fn new(steps: Vec<Box<dyn Action>>) -> Self {
+        Self {
+            steps,
+            continue_on_error: false,
+            step_log: None,
+        }
+    }
+
+    /// Build each step through the registry.
+    pub fn from_specs(
+        registry: &ActionRegistry,
+        specs: &[ActionSpec],
+    ) -> Result<Self, ActionError> {
+        if specs.is_empty() {
+            return Err(ActionError::InvalidConfig("sequence has no steps".into()));
+        }
+        let total = specs.len();
+        let steps = specs
+            .iter()
+            .enumerate()
+            .map(|(i, spec)| {
+                registry
+                    .create(&spec.action_type, &spec.params)
+                    .map_err(|e| in_step(&format!("step {} of {total}", i + 1), e))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(steps))
+    }
+
+    /// Keep running later steps after one fails.
+    pub fn with_continue_on_error(mut self, continue_on_error: bool) -> Self {
+        self.continue_on_error = continue_on_error;
+        self
+    }
+
+    /// Push each step's result to a shared log.
+    pub fn with_step_log(mut self, step_log: StepLog) -> Self {
+        self.step_log = Some(step_log);
+        self
+    }
+
+    /// Retry each step per `policy`, reporting failed attempts to
+    /// `attempt_log`. Does nothing if the policy never retries.
+    pub fn with_step_retry(mut self, policy: RetryPolicy, attempt_log: AttemptLog) -> Self {
+        if policy.retries() {
+            self.steps = std::mem::take(&mut self.steps)
+                .into_iter()
+                .map(|step| {
+                    Box::new(
+                        RetryingAction::new(step, policy).with_attempt_log(attempt_log.clone()),
+                    ) as Box<dyn Action>
+                })
+                .collect();
+        }
+        self
+    }
+
+    /// The steps, in execution order.
+    pub fn steps(&self) -> &[Box<dyn Action>] {
+        &self.steps
+    }
+
+    /// Run the steps and return every step's result.
+    ///
+    /// Unlike [`execute`](Action::execute) this never fails as a whole;
+    /// failed and skipped steps are reported in the results.
+    pub async fn execute_steps(&self) -> Vec<StepResult> {
+        let total = self.steps.len();
+        let mut results = Vec::with_capacity(total);
+        let mut stopped = false;
+
+        for (i, step) in self.steps.iter().enumerate() {
+            let outcome = if stopped {
+                StepOutcome::Skipped
+            } else {
+                match step.execute_with_details().await {
+                    Ok(details) => StepOutcome::Succeeded { details },
+                    Err(e) => {
+                        stopped = !self.continue_on_error;
+                        StepOutcome::Failed {
+                            error: e.to_string(),
+                        }
+                    }
+                }
+            };
+            let result = StepResult {
+                step: i + 1,
+                total,
+                action_type: step.action_type().to_string(),
+                name: step.name().to_string(),
+                outcome,
+            };
+            if let Some(log) = &self.step_log {
+                log.push(result.clone());
+            }
+            results.push(result);
+        }
+        results
+    }
+}
+
+/// Summarize failed steps as an execution error, if any failed.
+fn failure(results: &[StepResult]) -> Option<ActionError> {
+    let failed: Vec<String> = results
+        .iter()
+        .filter_map(|r| match &r.outcome {
+            StepOutcome::Failed { error } => Some(format!(
+                "step {} of {} ({}) failed: {error}",
+                r.step, r.total, r.name
+            )),
+            _ => None,
+        })
+        .collect();
+    if failed.is_empty() {
+        return None;
+    }
+
+    let completed: Vec<&str> = results
+        .iter()
+        .filter(|r| matches!(r.outcome, StepOutcome::Succeeded { .. }))
+        .map(|r| r.name.as_str())
+        .collect();
+    let completed = if completed.is_empty() {
+        "none".to_string()
+    } else {
+        completed.join(", ")
+    };
+    Some(ActionError::ExecutionFailed(format!(
+        "{}; completed: {completed}",
+        failed.join("; ")
+    )))
+}
+
+#[async_trait]
+impl Action for SequenceAction {
+    fn name(&self) -> &str {
+        "Sequence"
+    }
+
+    fn action_type(&self) -> &str {
+        SEQUENCE_ACTION_ID
+    }
+
+    /// Named after its steps, e.g. "Run Script → Hibernate"; available
+    /// and elevated if any step is.
+    fn info(&self) -> ActionInfo {
+        let infos: Vec<ActionInfo> = self.steps.iter().map(|s| s.info()).collect();
+        ActionInfo {
+            id: SEQUENCE_ACTION_ID.to_string(),
+            name: infos
+                .iter()
+                .map(|i| i.name.as_str())
+                .collect::<Vec<_>>()
+                .join(" → "),
+            description: "Runs several actions one after another".to_string(),
+            available: !infos.is_empty() && infos.iter().all(|i| i.available),
+            requires_elevation: infos.iter().any(|i| i.requires_elevation),
+            params_schema: Some(ActionSpec::list_schema()),
+        }
+    }
+
+    /// Validate every step; the error names the first invalid one.
+    async fn validate(&self) -> Result<(), ActionError> {
+        if self.steps.is_empty() {
+            return Err(ActionError::InvalidConfig("sequence has no steps".into()));
+        }
+        let total = self.steps.len();
+        for (i, step) in self.steps.iter().enumerate() {
+            step.validate()
+                .await
+                .map_err(|e| in_step(&format!("step {} of {total} ({})", i + 1, step.name()), e))?;
+        }
+        Ok(())
+    }
+
+    async fn execute(&self) -> Result<(), ActionError> {
+        self.execute_with_details().await.map(|_| ())
+    }
+
+    async fn execute_with_details(&self) -> Result<Option<String>, ActionError> {
+        let results = self.execute_steps().await;
+        match failure(&results) {
+            Some(e) => Err(e),
+            None => Ok(Some(format!("{} steps completed", results.len()))),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A step that records its runs and optionally fails.
+    struct Step {
+        name: &'static str,
+        fail_validate: bool,
+        fail_execute: bool,
+        runs: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    #[async_trait]
+    impl Action for Step {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn action_type(&self) -> &str {
+            "step"
+        }
+
+        fn info(&self) -> ActionInfo {
+            ActionInfo {
+                id: "step".to_string(),
+                name: self.name.to_string(),
+                description: "A test step".to_string(),
+                available: true,
+                requires_elevation: self.name == "Elevated",
+                params_schema: None,
+            }
+        }
+
+        async fn validate(&self) -> Result<(), ActionError> {
+            if self.fail_validate {
+                Err(ActionError::NotSupported("disabled".into()))
+            } else {
+                Ok(())
+            }
+        }
+
+        async fn execute(&self) -> Result<(), ActionError> {
+            self.runs.lock().unwrap().push(self.name);
+            if self.fail_execute {
+                Err(ActionError::OsError("boom".into()))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    /// Build a sequence of steps; names starting with `!` fail to execute.
+    fn sequence(names: &[&'static str]) -> (SequenceAction, Arc<Mutex<Vec<&'static str>>>) {
+        let runs = Arc::new(Mutex::new(Vec::new()));
+        let steps = names
+            .iter()
+            .map(|&name| {
+                Box::new(Step {
+                    name,
+                    fail_validate: false,
+                    fail_execute: name.starts_with('!'),
+                    runs: Arc::clone(&runs),
+                }) as Box<dyn Action>
+            })
+            .collect();
+        (SequenceAction::new(steps), runs)
+    }
+
+    #[tokio::test]
+    async fn runs_steps_in_order() {
+        let (action, runs) = sequence(&["Backup", "Hibernate"]);
+        action.validate().await.unwrap();
+        action.execute().await.unwrap();
+        assert_eq!(*runs.lock().unwrap(), vec!["Backup", "Hibernate"]);
+    }
+
+    #[tokio::test]
+    async fn stops_at_first_failure() {
+        let log = StepLog::new();
+        let (action, runs) = sequence(&["Backup", "!Upload", "Hibernate"]);
+        let action = action.with_step_log(log.clone());
+
+        let err = action.execute().await.unwrap_err().to_string();
+        assert!(
+            err.contains("step 2 of 3 (!Upload) failed") && err.contains("completed: Backup"),
+            "{err}"
+        );
+        assert_eq!(*runs.lock().unwrap(), vec!["Backup", "!Upload"]);
+
+        let outcomes: Vec<StepOutcome> = log.take().into_iter().map(|r| r.outcome).collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                StepOutcome::Succeeded { details: None },
+                StepOutcome::Failed {
+                    error: "os error: boom".into()
+                },
+                StepOutcome::Skipped,
+            ]
+        );
+        assert!(log.take().is_empty());
+    }
+
+    #[tokio::test]
+    async fn continue_on_error_runs_remaining_steps() {
+        let (action, runs) = sequence(&["!Backup", "Hibernate"]);
+        let err = action
+            .with_continue_on_error(true)
+            .execute()
+            .await
+            .unwrap_err()
+            .to_string();
+        assert_eq!(*runs.lock().unwrap(), vec!["!Backup", "Hibernate"]);
+        assert!(err.contains("completed: Hibernate"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn validate_names_the_invalid_step() {
+        let runs = Arc::new(Mutex::new(Vec::new()));
+        let action = SequenceAction::new(vec![Box::new(Step {
+            name: "Hibernate",
+            fail_validate: true,
+            fail_execute: false,
+            runs,
+        })]);
+        let err = action.validate().await.unwrap_err();
+        assert!(
+            matches!(&err, ActionError::NotSupported(m) if m == "step 1 of 1 (Hibernate): disabled"),
+            "{err}"
+        );
+        assert!(SequenceAction::new(Vec::new()).validate().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn step_retry_repeats_only_the_failing_step() {
+        let attempts = AttemptLog::new();
+        let (action, runs) = sequence(&["Backup", "!Upload"]);
+        let policy = RetryPolicy::new(3).with_backoff(crate::Backoff::Fixed { delay_ms: 0 });
+        let action = action.with_step_retry(policy, attempts.clone());
+
+        let err = action.execute().await.unwrap_err().to_string();
+        assert!(err.contains("(after 3 attempts)"), "{err}");
+        assert_eq!(
+            *runs.lock().unwrap(),
+            vec!["Backup", "!Upload", "!Upload", "!Upload"]
+        );
+        assert_eq!(attempts.take().len(), 3);
+        assert_eq!(action.info().name, "Backup → !Upload");
+    }
+
+    #[test]
+    fn info_combines_steps() {
+        let (action, _) = sequence(&["Backup", "Elevated"]);
+        let info = action.info();
+        assert_eq!(info.name, "Backup → Elevated");
+        assert!(info.requires_elevation);
+    }
+
+    #[test]
+    fn from_specs_resolves_through_registry() {
+        let mut registry = ActionRegistry::new();
+        registry.register("noop", |_| {
+            Ok(Box::new(Step {
+                name: "Noop",
+                fail_validate: false,
+                fail_execute: false,
+                runs: Arc::default(),
+            }))
+        });
+        let specs: Vec<ActionSpec> = serde_json::from_value(serde_json::json!([
+            { "action_type": "noop" },
+            { "action_type": "noop", "params": { "x": 1 } }
+        ]))
+        .unwrap();
+        assert_eq!(
+            SequenceAction::from_specs(&registry, &specs)
+                .unwrap()
+                .steps()
+                .len(),
+            2
+        );
+
+        let unknown = [ActionSpec::new("noop"), ActionSpec::new("teleport")];
+        assert!(matches!(
+            SequenceAction::from_specs(&registry, &unknown),
+            Err(ActionError::UnknownType(m)) if m == "step 2 of 2: teleport"
+        ));
+        assert!(SequenceAction::from_specs(&registry, &[]).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+//! Elevated helper for privileged FlowWatcher actions.
+//!
+//! ```text
+//! flowwatcher-elevated                                  # request on stdin, response on stdout
+//! flowwatcher-elevated --request <file> --response <file>
+//! ```
+//!
+//! Reads one JSON [`HelperRequest`], performs it, and writes one JSON
+//! [`HelperResponse`]. The file form is used when launched through UAC,
+//! where standard streams cannot be redirected; the response file must
+//! not exist yet. Exits with 0 when the
+//! request succeeded and 1 otherwise.
+
+use flowwatcher_platform::elevation::{
+    handle_request, write_exchange_file, HelperRequest, HelperResponse,
+};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let (request_path, response_path) = match args.as_slice() {
+        [] => (None, None),
+        ["--request", request, "--response", response] => (Some(*request), Some(*response)),
+        _ => {
+            eprintln!("usage: flowwatcher-elevated [--request <file> --response <file>]");
+            return ExitCode::from(2);
+        }
+    };
+
+    let input = match request_path {
+        Some(path) => std::fs::read_to_string(path),
+        None => {
+            let mut buf = String::new();
+            std::io::stdin().read_to_string(&mut buf).map(|_| buf)
+        }
+    };
+
+    let response = match input {
+        Ok(json) => respond(&json),
+        Err(e) => HelperResponse::Error {
+            message: format!("failed to read request: {e}"),
+        },
+    };
+    let ok = response == HelperResponse::Ok;
+
+    let output = response.to_json();
+    let written = match response_path {
+        Some(path) => write_exchange_file(Path::new(path), &output),
+        None => writeln!(std::io::stdout(), "{output}"),
+    };
+    if written.is_err() {
+        return ExitCode::from(2);
+    }
+
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(1)
+    }
+}
+
+/// Decode and perform a request.
+fn respond(json: &str) -> HelperResponse {
+    let request = match HelperRequest::from_json(json) {
+        Ok(request) => request,
+        Err(e) => {
+            return HelperResponse::Error {
+                message: e.to_string(),
+            }
+        }
+    };
+    match tokio::runtime::Builder::new_current_thread().build() {
+        Ok(runtime) => runtime.block_on(handle_request(&request)),
+        Err(e) => HelperResponse::Error {
+            message: format!("failed to start runtime: {e}"),
+        },
+    }
+}
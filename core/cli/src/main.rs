@@ -0,0 +1,187 @@
+//! FlowWatcher command-line tools.
+//!
+//! ```text
+//! flowwatcher-cli run <config.toml|config.json> [--dry-run] [--once] [--log <file>]
+//! flowwatcher-cli replay <file>
+//! ```
+//!
+//! `run` monitors the network headlessly with a monitoring config in the
+//! desktop app's format (see [`run`]). `--dry-run` reports the action
+//! instead of running it, `--once` exits after the first action, and
+//! `--log` appends to an activity log file. Exits with 0 when monitoring
+//! ends and 2 on usage, config, or setup errors.
+//!
+//! `replay` re-runs a session capture through the engine and reports
+//! whether the decision sequence matches the recording. Exits with 0 when
+//! identical, 1 on divergence, and 2 on usage or read errors.
+
+mod run;
+
+use flowwatcher_actions::AttemptLog;
+use flowwatcher_engine::{RecordedSample, SessionReplayer};
+use run::{RunOptions, Runner};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: flowwatcher-cli run <config> [--dry-run] [--once] [--log <file>]
+       flowwatcher-cli replay <file>";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["run", config, options @ ..] => match parse_run_options(options) {
+            Some(options) => run_headless(Path::new(config), options),
+            None => {
+                eprintln!("{USAGE}");
+                ExitCode::from(2)
+            }
+        },
+        ["replay", file] => replay(Path::new(file)),
+        _ => {
+            eprintln!("{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// run
+// ---------------------------------------------------------------------------
+
+/// Parse the flags after `run <config>`, or `None` if any is unknown.
+fn parse_run_options(args: &[&str]) -> Option<RunOptions> {
+    let mut options = RunOptions::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match *arg {
+            "--dry-run" => options.dry_run = true,
+            "--once" => options.once = true,
+            "--log" => options.log_path = Some(PathBuf::from(args.next()?)),
+            _ => return None,
+        }
+    }
+    Some(options)
+}
+
+fn run_headless(path: &Path, options: RunOptions) -> ExitCode {
+    let config = match run::load_config(path).and_then(|c| {
+        c.validate().map_err(|e| e.to_string())?;
+        Ok(c)
+    }) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: {}: {e}", path.display());
+            return ExitCode::from(2);
+        }
+    };
+    let attempts = AttemptLog::new();
+    let action = match run::create_action(&config, &attempts) {
+        Ok(action) => action,
+        Err(e) => {
+            eprintln!("error: invalid action: {e}");
+            return ExitCode::from(2);
+        }
+    };
+    let provider = Box::new(flowwatcher_platform::SysinfoNetworkProvider::new());
+    let runner = match Runner::new(config, options, provider, action) {
+        Ok(runner) => runner.with_attempt_log(attempts),
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(rt) => rt,
+        Err(e) => {
+            eprintln!("error: failed to start runtime: {e}");
+            return ExitCode::from(2);
+        }
+    };
+    println!("Monitoring. Press Ctrl+C to stop.");
+    runtime.block_on(run::run(runner));
+    ExitCode::SUCCESS
+}
+
+// ---------------------------------------------------------------------------
+// replay
+// ---------------------------------------------------------------------------
+
+fn replay(path: &Path) -> ExitCode {
+    let replayer = match SessionReplayer::open(path) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("error: {}: {e}", path.display());
+            return ExitCode::from(2);
+        }
+    };
+
+    let header = replayer.header();
+    println!(
+        "trigger={} threshold={} B/s for {}s mode={:?} pre-warning={}s countdown={}s",
+        header.trigger_type,
+        header.threshold_bytes_per_sec,
+        header.required_duration_secs,
+        header.monitor_mode,
+        header.pre_warning_secs,
+        header.countdown_secs,
+    );
+
+    let runtime = match tokio::runtime::Builder::new_current_thread().build() {
+        Ok(rt) => rt,
+        Err(e) => {
+            eprintln!("error: failed to start runtime: {e}");
+            return ExitCode::from(2);
+        }
+    };
+    let report = match runtime.block_on(replayer.replay()) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("error: replay failed: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    for sample in &report.replayed {
+        println!("{}", describe(sample));
+    }
+
+    match report.divergence {
+        None => {
+            println!("identical: {} samples replayed", report.replayed.len());
+            ExitCode::SUCCESS
+        }
+        Some(index) => {
+            println!("diverged at sample {index}");
+            print_sample("  recorded", replayer.samples().get(index));
+            print_sample("  replayed", report.replayed.get(index));
+            ExitCode::from(1)
+        }
+    }
+}
+
+/// One-line summary of a sample.
+fn describe(sample: &RecordedSample) -> String {
+    let mut line = format!("t={}ms scheduler={}", sample.t_ms, sample.scheduler);
+    if let Some(condition) = &sample.condition {
+        line.push_str(&format!(" condition={condition:?}"));
+    }
+    for event in &sample.events {
+        line.push_str(&format!(" event={event:?}"));
+    }
+    if sample.executed {
+        line.push_str(" executed");
+    }
+    line
+}
+
+fn print_sample(label: &str, sample: Option<&RecordedSample>) {
+    match sample.map(serde_json::to_string) {
+        Some(Ok(json)) => println!("{label}: {json}"),
+        _ => println!("{label}: <none>"),
+    }
+}
@@ -0,0 +1,1023 @@
+//! Headless monitoring.
+//!
+//! `flowwatcher-cli run` drives the same pipeline as the desktop app — a
+//! [`SpeedMonitor`] feeding the session condition (a threshold or the
+//! config's condition tree) that schedules the action on an
+//! [`ActionScheduler`] — from a [`MonitoringConfig`] file,
+//! printing progress to stdout. Only network idle triggers are supported.
+//! With `interface_failover` set, an [`InterfaceWatchdog`] checks every
+//! poll and a switched or inactive interface cancels a running
+//! pre-warning or countdown.
+//!
+//! Ctrl+C during the pre-warning or countdown cancels the pending action
+//! and monitoring continues; otherwise it stops monitoring and exits.
+
+use flowwatcher_actions::{Action, AttemptLog, RetryingAction, SequenceAction};
+use flowwatcher_conditions::{Condition, ConditionResult};
+use flowwatcher_engine::scheduler::{SchedulerError, SchedulerEvent, SchedulerState};
+use flowwatcher_engine::speed::SpeedReading;
+use flowwatcher_engine::{
+    auto_cancel_reason, ActionScheduler, ActionSelection, ActivityLogger, CancelReason, LogEntry,
+    LogStatus, MonitoringConfig, PowerGuard, Rearm, RearmGate, SessionOutcome, SpeedMonitor,
+    TriggerConfig, UserIdleGuard,
+};
+use flowwatcher_platform::{NetworkProvider, SystemPowerProvider, SystemUserIdleProvider};
+use flowwatcher_triggers::{
+    FailoverEvent, FailoverMode, InterfaceWatchdog, TriggerData, TriggerValue,
+};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Time between polls.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+// ---------------------------------------------------------------------------
+// Options
+// ---------------------------------------------------------------------------
+
+/// Command-line options for `run`.
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Print what would run instead of running the action.
+    pub dry_run: bool,
+    /// Exit after the action has run once instead of monitoring again.
+    pub once: bool,
+    /// Activity log file, in the desktop app's NDJSON format.
+    pub log_path: Option<PathBuf>,
+}
+
+/// Read a monitoring config from a TOML (`.toml`) or JSON file.
+pub fn load_config(path: &Path) -> Result<MonitoringConfig, String> {
+    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let is_toml = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+    if is_toml {
+        toml::from_str(&data).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_str(&data).map_err(|e| e.to_string())
+    }
+}
+
+/// Create the configured action from the system action registry.
+///
+/// The config's retry policy applies to a single action as a whole and to
+/// each step of a sequence on its own; failed attempts go to `attempts`.
+pub fn create_action(
+    config: &MonitoringConfig,
+    attempts: &AttemptLog,
+) -> Result<Box<dyn Action>, String> {
+    let registry = flowwatcher_platform::system_action_registry();
+    match &config.action_type {
+        ActionSelection::Single(id) => registry.create(id, &config.action_params).map(|action| {
+            if config.retry.retries() {
+                Box::new(
+                    RetryingAction::new(action, config.retry).with_attempt_log(attempts.clone()),
+                )
+            } else {
+                action
+            }
+        }),
+        ActionSelection::Sequence(steps) => SequenceAction::from_specs(&registry, steps).map(|s| {
+            Box::new(
+                s.with_continue_on_error(config.continue_on_error)
+                    .with_step_retry(config.retry, attempts.clone()),
+            ) as _
+        }),
+    }
+    .map_err(|e| e.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Runner
+// ---------------------------------------------------------------------------
+
+/// Whether the runner should keep polling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// Poll again after [`POLL_INTERVAL`].
+    Continue,
+    /// Monitoring is over.
+    Finished,
+}
+
+/// One headless monitoring session.
+pub struct Runner {
+    /// The session being run.
+    config: MonitoringConfig,
+    /// Command-line options.
+    options: RunOptions,
+    /// Source of interface counters.
+    provider: Box<dyn NetworkProvider>,
+    /// Smoothed speed of the monitored interface(s).
+    monitor: SpeedMonitor,
+    /// Fails the monitor over from a dead interface, if the config asks.
+    watchdog: Option<InterfaceWatchdog>,
+    /// Decides when the network has been idle long enough: the config's
+    /// condition tree, or its threshold.
+    condition: Box<dyn Condition>,
+    /// Pre-warning, countdown, and the action itself.
+    scheduler: ActionScheduler,
+    /// Activity log, persisted to `options.log_path` if set.
+    logger: ActivityLogger,
+    /// The last condition result printed, to print only changes.
+    last_condition: Option<ConditionResult>,
+    /// Holds evaluation back during a repeat cooldown or after a cancel.
+    rearm: RearmGate,
+    /// Failed attempts of a retrying action or step, drained after each
+    /// run.
+    attempts: AttemptLog,
+    /// The trigger data that last met the condition, logged with the
+    /// outcome of the action it scheduled.
+    met_data: Option<TriggerData>,
+    /// Clock time of the previous step.
+    last_step: Option<Instant>,
+}
+
+impl Runner {
+    /// Set up monitoring for `config`. `action` is what runs when the
+    /// countdown ends, already wrapped for retries (see [`create_action`]);
+    /// it is not run on a dry run.
+    pub fn new(
+        config: MonitoringConfig,
+        options: RunOptions,
+        mut provider: Box<dyn NetworkProvider>,
+        action: Box<dyn Action>,
+    ) -> Result<Self, String> {
+        let TriggerConfig::NetworkIdle { interface_id } = &config.trigger_type else {
+            return Err(format!(
+                "{} triggers cannot run headless; use network_idle",
+                config.trigger_type.registry_id()
+            ));
+        };
+        let monitor = interface_id
+            .speed_monitor(provider.as_mut(), 3)
+            .map_err(|e| e.to_string())?
+            .with_smoothing(config.smoothing);
+
+        let mut scheduler = ActionScheduler::new(config.pre_warning_secs, config.countdown_secs)
+            .with_max_snooze_secs(config.max_snooze_secs)
+            .with_pending_timeout_secs(config.pending_timeout_secs)
+            .with_action(action);
+        if config.power_guard.is_active() {
+            scheduler = scheduler.with_power_guard(PowerGuard::new(
+                config.power_guard,
+                Box::new(SystemPowerProvider::new()),
+            ));
+        }
+        if let Some(secs) = config.require_user_idle_secs.filter(|&secs| secs > 0) {
+            scheduler = scheduler.with_user_idle_guard(UserIdleGuard::new(
+                secs,
+                Box::new(SystemUserIdleProvider::new()),
+            ));
+        }
+
+        let mut logger = match &options.log_path {
+            Some(path) if path.exists() => ActivityLogger::load_from_file(path)?,
+            _ => ActivityLogger::new(),
+        };
+        let session =
+            logger.start_session(config.trigger_type.label(), config.action_type.to_string());
+        if let Some(path) = &options.log_path {
+            logger.append_session(session, path)?;
+        }
+
+        Ok(Self {
+            condition: config.session_condition().map_err(|e| e.to_string())?,
+            watchdog: (config.interface_failover.mode != FailoverMode::Off)
+                .then(|| InterfaceWatchdog::new(config.interface_failover)),
+            config,
+            options,
+            provider,
+            monitor,
+            scheduler,
+            logger,
+            last_condition: None,
+            rearm: RearmGate::new(),
+            attempts: AttemptLog::new(),
+            met_data: None,
+            last_step: None,
+        })
+    }
+
+    /// Log the failed attempts reported to `attempts`, the log given to
+    /// [`create_action`].
+    pub fn with_attempt_log(mut self, attempts: AttemptLog) -> Self {
+        self.attempts = attempts;
+        self
+    }
+
+    /// Poll once at clock time `now`: feed the speed to the condition while
+    /// waiting, otherwise advance the pre-warning or countdown and run the
+    /// action when it ends. A pre-warning or countdown is cancelled instead
+    /// if the system slept since the last step or the condition, still
+    /// running from before the action was scheduled, reads `Waiting`.
+    pub async fn step(&mut self, now: Instant) -> Flow {
+        let gap = self
+            .last_step
+            .replace(now)
+            .map_or(Duration::ZERO, |last| now.saturating_duration_since(last));
+        let polled = self.poll();
+        let suspended = self
+            .watchdog
+            .as_ref()
+            .is_some_and(InterfaceWatchdog::is_suspended);
+        let reading = match polled {
+            Ok(reading) => reading,
+            Err(e) => {
+                eprintln!("warning: failed to read network speed: {e}");
+                return Flow::Continue;
+            }
+        };
+
+        let flow = match self.scheduler.state() {
+            SchedulerState::Idle | SchedulerState::Cancelled | SchedulerState::Failed => {
+                if suspended {
+                    // The monitored interface is inactive, not idle.
+                    self.condition.reset();
+                } else if let Some(reading) = reading {
+                    self.evaluate(&reading, now);
+                }
+                Flow::Continue
+            }
+            SchedulerState::Pending | SchedulerState::Countdown if suspended => {
+                self.auto_cancel(CancelReason::InterfaceFailover);
+                Flow::Continue
+            }
+            SchedulerState::Pending | SchedulerState::Countdown => {
+                let result = reading.and_then(|reading| {
+                    self.condition
+                        .evaluate_at(&trigger_data(&reading), now)
+                        .ok()
+                });
+                if let Some(reason) = auto_cancel_reason(gap, result.as_ref()) {
+                    self.auto_cancel(reason);
+                    return Flow::Continue;
+                }
+                match self.scheduler.tick() {
+                    Ok(true) => self.execute().await,
+                    Ok(false) => {
+                        self.print_progress();
+                        Flow::Continue
+                    }
+                    Err(e) => {
+                        eprintln!("warning: scheduler tick failed: {e}");
+                        Flow::Continue
+                    }
+                }
+            }
+            _ => Flow::Continue,
+        };
+        for event in self.scheduler.take_events() {
+            if let SchedulerEvent::TimedOut { pending_secs } = event {
+                self.time_out(pending_secs);
+            }
+        }
+        flow
+    }
+
+    /// Poll the monitor. With a watchdog, every interface is read so it
+    /// can spot traffic moving elsewhere; a switch cancels a running
+    /// pre-warning or countdown.
+    fn poll(&mut self) -> Result<Option<SpeedReading>, String> {
+        let Some(watchdog) = self.watchdog.as_mut() else {
+            return self
+                .monitor
+                .poll(self.provider.as_mut())
+                .map_err(|e| e.to_string());
+        };
+        let snapshot = self.provider.snapshot_all().map_err(|e| e.to_string())?;
+        let polled = self.monitor.poll_snapshot(&snapshot);
+        if let Some(event) = watchdog.check(&mut self.monitor, &snapshot, &polled) {
+            self.report_failover(event);
+        }
+        polled.map_err(|e| e.to_string())
+    }
+
+    /// Log a decision of the interface watchdog.
+    fn report_failover(&mut self, event: FailoverEvent) {
+        match &event {
+            FailoverEvent::Inactive { .. } => eprintln!("warning: {event}"),
+            FailoverEvent::Switched { .. } | FailoverEvent::Recovered { .. } => {
+                println!("{event}")
+            }
+        }
+        self.record(LogEntry::now(
+            self.config.trigger_type.label(),
+            self.config.action_type.to_string(),
+            LogStatus::Info,
+            Some(event.to_string()),
+        ));
+        let scheduled = matches!(
+            self.scheduler.state(),
+            SchedulerState::Pending | SchedulerState::Countdown
+        );
+        if scheduled && matches!(event, FailoverEvent::Switched { .. }) {
+            self.auto_cancel(CancelReason::InterfaceFailover);
+        }
+    }
+
+    /// Go back to monitoring after the pending timeout cancelled the
+    /// action.
+    fn time_out(&mut self, pending_secs: u64) {
+        self.condition.reset();
+        println!(
+            "{} still pending after {pending_secs}s; cancelled, monitoring continues.",
+            self.config.action_type
+        );
+        let entry = self.with_met_data(LogEntry::cancelled(
+            self.config.trigger_type.label(),
+            self.config.action_type.to_string(),
+            CancelReason::PendingTimeout,
+        ));
+        self.record(entry);
+    }
+
+    /// Go back to monitoring after the system slept, traffic resumed or
+    /// the monitored interface went inactive during the pre-warning or
+    /// countdown.
+    fn auto_cancel(&mut self, reason: CancelReason) {
+        if let Err(e) = self.scheduler.cancel_with_reason(reason.clone()) {
+            eprintln!("warning: failed to cancel the action: {e}");
+            return;
+        }
+        self.scheduler.take_events();
+        self.condition.reset();
+        println!(
+            "Cancelled {} ({reason}); monitoring continues.",
+            self.config.action_type
+        );
+        let entry = self.with_met_data(LogEntry::cancelled(
+            self.config.trigger_type.label(),
+            self.config.action_type.to_string(),
+            reason,
+        ));
+        self.record(entry);
+    }
+
+    /// Handle Ctrl+C: cancel the pending action if there is one, otherwise
+    /// stop monitoring.
+    pub fn interrupt(&mut self) -> Flow {
+        if self
+            .scheduler
+            .cancel_with_reason(CancelReason::UserHotkey)
+            .is_err()
+        {
+            println!("Stopped monitoring.");
+            self.finish(SessionOutcome::Stopped, None);
+            return Flow::Finished;
+        }
+        self.scheduler.take_events();
+        self.condition.reset();
+        if self.config.rearm_requires_activity {
+            self.rearm.require_activity();
+        }
+        println!(
+            "Cancelled {}; monitoring continues.",
+            self.config.action_type
+        );
+        let entry = self.with_met_data(LogEntry::cancelled(
+            self.config.trigger_type.label(),
+            self.config.action_type.to_string(),
+            CancelReason::UserHotkey,
+        ));
+        self.record(entry);
+        Flow::Continue
+    }
+
+    /// Attach the trigger data that met the condition, if any.
+    fn with_met_data(&self, entry: LogEntry) -> LogEntry {
+        match &self.met_data {
+            Some(data) => entry.with_metadata(data.to_json()),
+            None => entry,
+        }
+    }
+
+    /// Feed a reading to the condition and schedule the action once met,
+    /// unless the re-arm gate holds it back.
+    fn evaluate(&mut self, reading: &SpeedReading, now: Instant) {
+        if let Some(rearm) = self.rearm.poll(now) {
+            self.condition.reset();
+            self.rearmed(rearm);
+        }
+        if self.rearm.is_cooling_down() {
+            return;
+        }
+        let data = trigger_data(reading);
+        let result = match self.condition.evaluate_at(&data, now) {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("warning: condition evaluation failed: {e}");
+                return;
+            }
+        };
+        let (result, resumed) = self.rearm.filter(result);
+        if let Some(rearm) = resumed {
+            self.rearmed(rearm);
+        }
+        if result == ConditionResult::Met {
+            // The condition keeps its state through the countdown, so
+            // resumed traffic only cancels once past the disarm threshold
+            // and spike budget; every way out of the countdown resets it.
+            self.last_condition = None;
+            self.met_data = Some(data);
+            match self.scheduler.schedule() {
+                Ok(()) => println!(
+                    "Network idle for {}s. {} in {}s (Ctrl+C to cancel).",
+                    self.config.condition.required_duration_secs,
+                    self.config.action_type,
+                    self.config.pre_warning_secs + self.config.countdown_secs
+                ),
+                Err(e) => eprintln!("warning: failed to schedule the action: {e}"),
+            }
+            return;
+        }
+
+        let changed = self.last_condition.as_ref().map(std::mem::discriminant)
+            != Some(std::mem::discriminant(&result));
+        if changed {
+            match result {
+                ConditionResult::InProgress { .. } => println!(
+                    "Idle: {} B/s down, {} B/s up. Waiting {}s.",
+                    reading.download_bps,
+                    reading.upload_bps,
+                    self.config.condition.required_duration_secs
+                ),
+                _ => println!(
+                    "Active: {} B/s down, {} B/s up.",
+                    reading.download_bps, reading.upload_bps
+                ),
+            }
+        }
+        self.last_condition = Some(result);
+    }
+
+    /// Print the seconds left in the pre-warning or countdown.
+    fn print_progress(&self) {
+        let Some(remaining) = self.scheduler.remaining_secs() else {
+            return;
+        };
+        match self.scheduler.state() {
+            SchedulerState::Pending => {
+                println!("Pre-warning: countdown starts in {remaining}s (Ctrl+C to cancel).")
+            }
+            _ => println!(
+                "Countdown: {} in {remaining}s (Ctrl+C to cancel).",
+                self.config.action_type
+            ),
+        }
+    }
+
+    /// Run the action (or report it on a dry run) and log the outcome.
+    async fn execute(&mut self) -> Flow {
+        let dry_run = self.options.dry_run || self.config.dry_run;
+        let result = if dry_run {
+            Ok(())
+        } else {
+            self.scheduler.run_pending_action().await
+        };
+        let trigger = self.config.trigger_type.label();
+        let action = self
+            .scheduler
+            .action_name()
+            .unwrap_or_else(|| self.config.action_type.to_string());
+        let duration = self.scheduler.last_run_duration();
+        let metadata = self.met_data.as_ref().map(TriggerData::to_json);
+        let timed = |mut entry: LogEntry| {
+            entry.metadata = metadata.clone();
+            match duration {
+                Some(duration) => entry.with_duration(duration),
+                None => entry,
+            }
+        };
+        // Retried attempts get an entry each; the last failure is the
+        // error entry below.
+        for attempt in self.attempts.take() {
+            if attempt.retry_in_ms.is_some() {
+                eprintln!("warning: {attempt}");
+                self.record(LogEntry::now(
+                    trigger,
+                    action.clone(),
+                    LogStatus::Info,
+                    Some(attempt.to_string()),
+                ));
+            }
+        }
+        match result {
+            Ok(()) => {
+                let details = dry_run.then(|| format!("Dry run: would have executed {action}"));
+                println!(
+                    "{}",
+                    details.as_deref().unwrap_or(&format!("Executed {action}."))
+                );
+                let entry = timed(LogEntry::now(trigger, action, LogStatus::Executed, details));
+                self.record(entry);
+                if self.options.once {
+                    self.finish(SessionOutcome::Executed, None);
+                    return Flow::Finished;
+                }
+            }
+            Err(SchedulerError::ExecutionSkipped(reason)) => {
+                println!("Skipped {action}: {reason}");
+                self.record(LogEntry::skipped(trigger, action, reason));
+            }
+            Err(e) => {
+                eprintln!("error: {action} failed: {e}");
+                let entry = timed(LogEntry::now(
+                    trigger,
+                    action,
+                    LogStatus::Error,
+                    Some(e.to_string()),
+                ));
+                self.record(entry);
+            }
+        }
+        self.scheduler.reset();
+        self.condition.reset();
+        if let Some(cooldown) = self.config.repeat_mode.cooldown() {
+            self.rearm.start_cooldown(cooldown);
+        }
+        println!("Monitoring again.");
+        Flow::Continue
+    }
+
+    /// Print and log that evaluation resumed.
+    fn rearmed(&mut self, rearm: Rearm) {
+        println!("{rearm}.");
+        self.record(LogEntry::now(
+            self.config.trigger_type.label(),
+            self.config.action_type.to_string(),
+            LogStatus::Info,
+            Some(rearm.to_string()),
+        ));
+    }
+
+    /// Add `entry` to the log and its file.
+    fn record(&mut self, entry: LogEntry) {
+        let result = match &self.options.log_path {
+            Some(path) => self.logger.append_entry(entry, path),
+            None => {
+                self.logger.add_entry(entry);
+                Ok(())
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("warning: failed to write the activity log: {e}");
+        }
+    }
+
+    /// End the log session with `outcome`.
+    fn finish(&mut self, outcome: SessionOutcome, details: Option<String>) {
+        let Some(id) = self.logger.current_session() else {
+            return;
+        };
+        self.logger.end_session(id, outcome, details);
+        if let Some(path) = &self.options.log_path {
+            if let Err(e) = self.logger.append_session(id, path) {
+                eprintln!("warning: failed to write the activity log: {e}");
+            }
+        }
+    }
+}
+
+/// Trigger data for a speed reading, as the network trigger reports it.
+fn trigger_data(reading: &SpeedReading) -> TriggerData {
+    let mut data = TriggerData::new();
+    data.insert("download_bps", TriggerValue::U64(reading.download_bps));
+    data.insert("upload_bps", TriggerValue::U64(reading.upload_bps));
+    data
+}
+
+/// Poll every [`POLL_INTERVAL`] until the runner finishes, handing Ctrl+C
+/// to [`Runner::interrupt`].
+pub async fn run(mut runner: Runner) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        let flow = tokio::select! {
+            _ = interval.tick() => runner.step(Instant::now()).await,
+            signal = tokio::signal::ctrl_c() => match signal {
+                Ok(()) => runner.interrupt(),
+                Err(e) => {
+                    eprintln!("warning: cannot listen for Ctrl+C: {e}");
+                    runner.step(Instant::now()).await
+                }
+            },
+        };
+        if flow == Flow::Finished {
+            return;
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_actions::{Backoff, RetryPolicy};
+    use flowwatcher_engine::RepeatMode;
+    use flowwatcher_platform::network::{
+        InterfaceInfo, NetworkError, NetworkStats, TrafficSnapshot,
+    };
+    use flowwatcher_testing::{
+        MockAction, MultiInterfaceProvider, ScriptedNetworkProvider, MOCK_INTERFACE_ID,
+    };
+
+    fn config(pre_warning_secs: u64, countdown_secs: u64) -> MonitoringConfig {
+        toml::from_str(&format!(
+            r#"
+            action_type = "shutdown"
+            pre_warning_secs = {pre_warning_secs}
+            countdown_secs = {countdown_secs}
+            smoothing = {{ type = "none" }}
+
+            [trigger_type]
+            type = "network_idle"
+            interface_id = "{MOCK_INTERFACE_ID}"
+
+            [condition]
+            threshold_bytes_per_sec = 1024
+            required_duration_secs = 2
+            monitor_mode = "download_only"
+            "#
+        ))
+        .unwrap()
+    }
+
+    fn runner(config: MonitoringConfig, options: RunOptions, action: &MockAction) -> Runner {
+        let provider = ScriptedNetworkProvider::from_speeds(&[(0, 0); 20]);
+        Runner::new(
+            config,
+            options,
+            Box::new(provider),
+            Box::new(action.clone()),
+        )
+        .unwrap()
+    }
+
+    /// Step once per second until the runner finishes or `limit` steps pass.
+    async fn run_steps(runner: &mut Runner, limit: u64) -> u64 {
+        let start = Instant::now();
+        for second in 0..limit {
+            if runner.step(start + Duration::from_secs(second)).await == Flow::Finished {
+                return second;
+            }
+        }
+        limit
+    }
+
+    #[tokio::test]
+    async fn once_runs_the_action_and_finishes() {
+        let action = MockAction::new();
+        let options = RunOptions {
+            once: true,
+            ..RunOptions::default()
+        };
+        let mut runner = runner(config(1, 2), options, &action);
+        assert!(run_steps(&mut runner, 15).await < 15);
+
+        assert_eq!(action.executions(), 1);
+        let entries = runner.logger.get_all();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].status, LogStatus::Executed);
+        assert_eq!(entries[0].action_name, "Mock Action");
+        assert!(entries[0].duration_ms.is_some());
+        assert_eq!(
+            entries[0].metadata,
+            Some(serde_json::json!({ "download_bps": 0, "upload_bps": 0 }))
+        );
+        assert_eq!(
+            runner.logger.get_sessions()[0].outcome,
+            Some(SessionOutcome::Executed)
+        );
+    }
+
+    #[tokio::test]
+    async fn retried_attempts_are_logged_before_the_result() {
+        let mut config = config(0, 1);
+        config.retry = RetryPolicy::new(3).with_backoff(Backoff::Fixed { delay_ms: 0 });
+        let once = RunOptions {
+            once: true,
+            ..RunOptions::default()
+        };
+
+        let retrying = |config: &MonitoringConfig, options: RunOptions, action: &MockAction| {
+            let attempts = AttemptLog::new();
+            let action = RetryingAction::new(Box::new(action.clone()), config.retry)
+                .with_attempt_log(attempts.clone());
+            let provider = ScriptedNetworkProvider::from_speeds(&[(0, 0); 20]);
+            Runner::new(
+                config.clone(),
+                options,
+                Box::new(provider),
+                Box::new(action),
+            )
+            .unwrap()
+            .with_attempt_log(attempts)
+        };
+
+        let action = MockAction::failing_first(2);
+        let mut retried = retrying(&config, once, &action);
+        assert!(run_steps(&mut retried, 15).await < 15);
+        assert_eq!(action.executions(), 3);
+        let statuses: Vec<_> = retried
+            .logger
+            .get_all()
+            .iter()
+            .map(|e| e.status.clone())
+            .collect();
+        assert_eq!(
+            statuses,
+            [LogStatus::Info, LogStatus::Info, LogStatus::Executed]
+        );
+
+        let action = MockAction::failing();
+        let mut failed = retrying(&config, RunOptions::default(), &action);
+        run_steps(&mut failed, 8).await;
+        let entries = failed.logger.get_all();
+        let errors: Vec<_> = entries
+            .iter()
+            .filter(|e| e.status == LogStatus::Error)
+            .collect();
+        assert_eq!(errors.len(), 1, "{entries:?}");
+        assert!(errors[0].duration_ms.is_some());
+        let details = errors[0].details.as_deref().unwrap();
+        assert!(details.ends_with("(after 3 attempts)"), "{details}");
+    }
+
+    #[tokio::test]
+    async fn dry_run_logs_without_running_the_action() {
+        let action = MockAction::new();
+        let options = RunOptions {
+            dry_run: true,
+            once: true,
+            ..RunOptions::default()
+        };
+        let mut runner = runner(config(0, 1), options, &action);
+        assert!(run_steps(&mut runner, 15).await < 15);
+
+        assert_eq!(action.executions(), 0);
+        let details = runner.logger.get_all()[0].details.clone().unwrap();
+        assert!(details.starts_with("Dry run"), "{details}");
+    }
+
+    #[tokio::test]
+    async fn interrupt_cancels_the_countdown_then_stops() {
+        let action = MockAction::new();
+        let mut runner = runner(config(0, 60), RunOptions::default(), &action);
+        run_steps(&mut runner, 6).await;
+        assert_eq!(runner.scheduler.state(), SchedulerState::Countdown);
+
+        assert_eq!(runner.interrupt(), Flow::Continue);
+        assert_eq!(runner.logger.get_all()[0].status, LogStatus::Cancelled);
+        assert_eq!(runner.interrupt(), Flow::Finished);
+        assert_eq!(
+            runner.logger.get_sessions()[0].outcome,
+            Some(SessionOutcome::Stopped)
+        );
+        assert_eq!(action.executions(), 0);
+    }
+
+    #[tokio::test]
+    async fn repeat_cooldown_delays_the_next_action() {
+        let executions = |cooldown_secs| async move {
+            let action = MockAction::new();
+            let mut config = config(0, 1);
+            config.repeat_mode = RepeatMode::Repeat { cooldown_secs };
+            let mut runner = runner(config, RunOptions::default(), &action);
+            run_steps(&mut runner, 15).await;
+            let rearms = runner
+                .logger
+                .get_all()
+                .iter()
+                .filter(|e| e.status == LogStatus::Info)
+                .count();
+            (action.executions(), rearms)
+        };
+
+        let (without, _) = executions(0).await;
+        let (with, rearms) = executions(5).await;
+        assert!(with >= 1);
+        assert!(with < without, "{with} vs {without}");
+        assert_eq!(rearms, with);
+    }
+
+    #[tokio::test]
+    async fn cancel_waits_for_traffic_before_rearming() {
+        let action = MockAction::new();
+        let mut config = config(0, 60);
+        config.rearm_requires_activity = true;
+        let mut runner = runner(config, RunOptions::default(), &action);
+        run_steps(&mut runner, 6).await;
+        assert_eq!(runner.interrupt(), Flow::Continue);
+
+        // The network stays quiet, so the action is not scheduled again.
+        run_steps(&mut runner, 10).await;
+        assert_eq!(runner.scheduler.state(), SchedulerState::Cancelled);
+        assert_eq!(action.executions(), 0);
+    }
+
+    #[tokio::test]
+    async fn resumed_traffic_cancels_the_countdown() {
+        let action = MockAction::new();
+        let mut speeds = vec![(0, 0); 6];
+        speeds.extend([(1_000_000, 0); 4]);
+        let mut runner = Runner::new(
+            config(0, 60),
+            RunOptions::default(),
+            Box::new(ScriptedNetworkProvider::from_speeds(&speeds)),
+            Box::new(action.clone()),
+        )
+        .unwrap();
+        run_steps(&mut runner, 5).await;
+        assert_eq!(runner.scheduler.state(), SchedulerState::Countdown);
+
+        run_steps(&mut runner, 3).await;
+        assert_eq!(runner.scheduler.state(), SchedulerState::Cancelled);
+        let entries = runner.logger.get_all();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].status, LogStatus::Cancelled);
+        assert_eq!(
+            entries[0].cancel_reason,
+            Some(CancelReason::AutoTrafficResumed)
+        );
+        assert_eq!(action.executions(), 0);
+    }
+
+    #[tokio::test]
+    async fn spikes_and_the_dead_band_do_not_cancel_the_countdown() {
+        let action = MockAction::new();
+        let mut config = config(0, 60);
+        config.condition.hysteresis_bytes_per_sec = Some(1024);
+        config.condition.max_spike_samples = 1;
+        let mut speeds = vec![(0, 0); 6];
+        // One spike within the budget, then speeds in the dead band.
+        speeds.extend([(100_000, 0), (0, 0), (1500, 0), (1500, 0)]);
+        let mut runner = Runner::new(
+            config,
+            RunOptions::default(),
+            Box::new(ScriptedNetworkProvider::from_speeds(&speeds)),
+            Box::new(action.clone()),
+        )
+        .unwrap();
+        run_steps(&mut runner, 5).await;
+        assert_eq!(runner.scheduler.state(), SchedulerState::Countdown);
+
+        run_steps(&mut runner, 5).await;
+        assert_eq!(runner.scheduler.state(), SchedulerState::Countdown);
+        assert!(runner.logger.get_all().is_empty());
+    }
+
+    #[tokio::test]
+    async fn suspend_gap_cancels_the_countdown() {
+        let action = MockAction::new();
+        let mut runner = runner(config(0, 60), RunOptions::default(), &action);
+        let start = Instant::now();
+        for second in 0..5 {
+            runner.step(start + Duration::from_secs(second)).await;
+        }
+        assert_eq!(runner.scheduler.state(), SchedulerState::Countdown);
+
+        runner.step(start + Duration::from_secs(3600)).await;
+        assert_eq!(
+            runner.scheduler.cancel_reason(),
+            Some(&CancelReason::SystemResume)
+        );
+        assert_eq!(
+            runner.logger.get_all()[0].cancel_reason,
+            Some(CancelReason::SystemResume)
+        );
+    }
+
+    #[tokio::test]
+    async fn the_condition_tree_decides_when_set() {
+        // 2 KB/s is above the plain threshold but below the tree's.
+        let speeds = [(2048, 0); 10];
+        let action = MockAction::new();
+        let mut config = config(0, 60);
+        config.condition_tree = Some(
+            serde_json::from_value(serde_json::json!({
+                "type": "composite", "operator": "all", "conditions": [
+                    { "type": "threshold", "threshold_bytes_per_sec": 4096,
+                      "required_duration_secs": 2, "monitor_mode": "download_only" },
+                ],
+            }))
+            .unwrap(),
+        );
+        let mut runner = Runner::new(
+            config,
+            RunOptions::default(),
+            Box::new(ScriptedNetworkProvider::from_speeds(&speeds)),
+            Box::new(action.clone()),
+        )
+        .unwrap();
+        run_steps(&mut runner, 6).await;
+        assert_eq!(runner.scheduler.state(), SchedulerState::Countdown);
+    }
+
+    /// A [`MultiInterfaceProvider`] that moves to its next step after
+    /// every snapshot, as one poll per second would.
+    struct Stepping(MultiInterfaceProvider);
+
+    impl NetworkProvider for Stepping {
+        fn list_interfaces(&self) -> Result<Vec<InterfaceInfo>, NetworkError> {
+            self.0.list_interfaces()
+        }
+
+        fn get_default_interface(&self) -> Result<Option<InterfaceInfo>, NetworkError> {
+            self.0.get_default_interface()
+        }
+
+        fn get_stats(&mut self, interface_id: &str) -> Result<NetworkStats, NetworkError> {
+            self.0.get_stats(interface_id)
+        }
+
+        fn snapshot_all(&mut self) -> Result<TrafficSnapshot, NetworkError> {
+            let snapshot = self.0.snapshot_all();
+            self.0.advance();
+            snapshot
+        }
+    }
+
+    /// Wi-Fi stays silent while Ethernet picks up traffic after the
+    /// countdown has started.
+    fn failover_runner(mode: &str, action: &MockAction) -> Runner {
+        const QUIET: Option<(u64, u64)> = Some((0, 0));
+        const BUSY: Option<(u64, u64)> = Some((50_000, 1_000));
+        let mut config = config(0, 60);
+        config.trigger_type = serde_json::from_value(serde_json::json!({
+            "type": "network_idle", "interface_id": "wlan0",
+        }))
+        .unwrap();
+        config.interface_failover = serde_json::from_value(serde_json::json!({
+            "mode": mode, "after_polls": 2,
+        }))
+        .unwrap();
+        let mut eth0 = vec![QUIET; 6];
+        eth0.extend([BUSY; 14]);
+        let provider = MultiInterfaceProvider::new()
+            .with_interface("wlan0", &[QUIET; 20])
+            .with_interface("eth0", &eth0);
+        Runner::new(
+            config,
+            RunOptions::default(),
+            Box::new(Stepping(provider)),
+            Box::new(action.clone()),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn an_interface_failover_cancels_the_countdown() {
+        for mode in ["switch", "suspend"] {
+            let action = MockAction::new();
+            let mut runner = failover_runner(mode, &action);
+            run_steps(&mut runner, 5).await;
+            assert_eq!(
+                runner.scheduler.state(),
+                SchedulerState::Countdown,
+                "{mode}"
+            );
+
+            run_steps(&mut runner, 6).await;
+            assert_eq!(
+                runner.scheduler.cancel_reason(),
+                Some(&CancelReason::InterfaceFailover),
+                "{mode}"
+            );
+            let entries = runner.logger.get_all();
+            assert!(
+                entries.iter().any(|e| e.status == LogStatus::Info
+                    && e.details.as_deref().is_some_and(|d| d.contains("wlan0"))),
+                "{mode}: {entries:?}"
+            );
+            // Busy Ethernet (switch) or a held-back condition (suspend)
+            // does not schedule the action again.
+            run_steps(&mut runner, 8).await;
+            assert_eq!(
+                runner.scheduler.state(),
+                SchedulerState::Cancelled,
+                "{mode}"
+            );
+            assert_eq!(action.executions(), 0);
+        }
+    }
+
+    #[test]
+    fn only_network_triggers_run_headless() {
+        let mut config = config(0, 1);
+        config.trigger_type = serde_json::from_str(r#"{ "type": "cpu_idle" }"#).unwrap();
+        let provider = ScriptedNetworkProvider::from_speeds(&[(0, 0)]);
+        let error = Runner::new(
+            config,
+            RunOptions::default(),
+            Box::new(provider),
+            Box::new(MockAction::new()),
+        )
+        .err()
+        .unwrap();
+        assert!(error.contains("cpu_idle"), "{error}");
+    }
+}
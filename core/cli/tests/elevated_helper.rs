@@ -0,0 +1,90 @@
+//! End-to-end tests for the `flowwatcher-elevated` helper.
+//!
+//! The UAC tests show a real elevation prompt and need someone to answer
+//! it, so they are ignored by default. Run them on Windows with:
+//! `cargo test -p flowwatcher-cli -- --ignored`
+
+use flowwatcher_platform::elevation::{HelperCommand, HelperRequest, HelperResponse};
+use flowwatcher_testing::ScratchDir;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const HELPER: &str = env!("CARGO_BIN_EXE_flowwatcher-elevated");
+
+/// Run the helper unelevated, exchanging JSON over stdin/stdout.
+fn run_over_stdio(request: &str) -> (HelperResponse, i32) {
+    let mut child = Command::new(HELPER)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("helper should start");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(request.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    let response = HelperResponse::from_json(&String::from_utf8_lossy(&output.stdout)).unwrap();
+    (response, output.status.code().unwrap_or(-1))
+}
+
+#[test]
+fn ping_round_trips_over_stdio() {
+    let request = HelperRequest::new(HelperCommand::Ping).to_json();
+    assert_eq!(run_over_stdio(&request), (HelperResponse::Ok, 0));
+}
+
+#[test]
+fn protocol_errors_are_reported_as_responses() {
+    let (response, code) = run_over_stdio(r#"{"version":99,"op":"ping"}"#);
+    assert!(matches!(response, HelperResponse::Error { message } if message.contains("version")));
+    assert_eq!(code, 1);
+}
+
+#[test]
+fn file_form_never_overwrites_the_response() {
+    let dir = ScratchDir::new("helper-test");
+    let request = dir.join("request.json");
+    let response = dir.join("response.json");
+    std::fs::write(&request, HelperRequest::new(HelperCommand::Ping).to_json()).unwrap();
+    let run = || {
+        Command::new(HELPER)
+            .args(["--request".as_ref(), request.as_os_str()])
+            .args(["--response".as_ref(), response.as_os_str()])
+            .status()
+            .unwrap()
+            .code()
+    };
+
+    assert_eq!(run(), Some(0));
+    let answer = std::fs::read_to_string(&response).unwrap();
+    assert_eq!(
+        HelperResponse::from_json(&answer).unwrap(),
+        HelperResponse::Ok
+    );
+    // A response file that already exists is never trusted or replaced.
+    assert_eq!(run(), Some(2));
+}
+
+#[cfg(windows)]
+#[test]
+#[ignore = "shows a UAC prompt; accept it"]
+fn uac_accepted_runs_helper_elevated() {
+    use flowwatcher_platform::elevation::run_elevated;
+
+    let request = HelperRequest::new(HelperCommand::Ping);
+    let response = run_elevated(std::path::Path::new(HELPER), &request).unwrap();
+    assert_eq!(response, HelperResponse::Ok);
+}
+
+#[cfg(windows)]
+#[test]
+#[ignore = "shows a UAC prompt; decline it"]
+fn uac_declined_is_reported_as_cancelled() {
+    use flowwatcher_platform::elevation::{run_elevated, ElevationError};
+
+    let request = HelperRequest::new(HelperCommand::Ping);
+    let result = run_elevated(std::path::Path::new(HELPER), &request);
+    assert!(matches!(result, Err(ElevationError::Cancelled)));
+}
@@ -0,0 +1,490 @@
+//! Composite AND/OR condition.
+//!
+//! Combines child conditions that all see the same trigger data, e.g.
+//! "network below 200 KB/s AND CPU below 10%". [`ConditionSpec`] is the
+//! serde form, so configs can describe nested combinations.
+
+use crate::{
+    Condition, ConditionError, ConditionResult, CumulativeVolumeCondition, MonitorMode,
+    ScheduleCondition, ThresholdCondition, VolumeDirection, WeeklySchedule, Zone,
+};
+use flowwatcher_triggers::TriggerData;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+// ---------------------------------------------------------------------------
+// Operator
+// ---------------------------------------------------------------------------
+
+/// How child results are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompositeOperator {
+    /// Met only when every child is met (AND).
+    All,
+    /// Met as soon as any child is met (OR).
+    Any,
+}
+
+// ---------------------------------------------------------------------------
+// CompositeCondition
+// ---------------------------------------------------------------------------
+
+/// A condition combining child conditions with [`CompositeOperator`].
+///
+/// Every child is evaluated on every call, so each keeps its own duration
+/// timer running. Results combine as follows:
+///
+/// | Operator | `Met` when   | `InProgress` elapsed   | `Waiting` when      |
+/// |----------|--------------|------------------------|---------------------|
+/// | `All`    | all are Met  | minimum over children  | any child waits     |
+/// | `Any`    | any is Met   | maximum over children  | every child waits   |
+///
+/// For `All`, Met children are left out of the elapsed time, so it tracks
+/// the slowest child still in progress. The combined result is that
+/// child's, with its required time and current value. A composite with no
+/// children is always `Waiting`.
+pub struct CompositeCondition {
+    /// How results are combined.
+    operator: CompositeOperator,
+    /// Child conditions.
+    children: Vec<Box<dyn Condition>>,
+}
+
+impl CompositeCondition {
+    /// Combine `children` with `operator`.
+    pub fn new(operator: CompositeOperator, children: Vec<Box<dyn Condition>>) -> Self {
+        Self { operator, children }
+    }
+
+    /// The combining operator.
+    pub fn operator(&self) -> CompositeOperator {
+        self.operator
+    }
+
+    /// Number of direct children.
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Whether there are no children.
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Combine child results.
+    fn combine(&self, results: &[ConditionResult]) -> ConditionResult {
+        if results.is_empty() {
+            return ConditionResult::Waiting;
+        }
+        let in_progress = || results.iter().filter(|r| r.elapsed_secs().is_some());
+
+        match self.operator {
+            CompositeOperator::All => {
+                if results.contains(&ConditionResult::Waiting) {
+                    ConditionResult::Waiting
+                } else if results.iter().all(|r| *r == ConditionResult::Met) {
+                    ConditionResult::Met
+                } else {
+                    in_progress()
+                        .min_by_key(|r| r.elapsed_secs())
+                        .cloned()
+                        .unwrap_or_else(|| ConditionResult::in_progress(0, 0))
+                }
+            }
+            CompositeOperator::Any => {
+                if results.contains(&ConditionResult::Met) {
+                    ConditionResult::Met
+                } else if let Some(result) = in_progress().max_by_key(|r| r.elapsed_secs()) {
+                    result.clone()
+                } else {
+                    ConditionResult::Waiting
+                }
+            }
+        }
+    }
+}
+
+/// Rank zones from idlest to busiest.
+fn zone_rank(zone: Zone) -> u8 {
+    match zone {
+        Zone::Below => 0,
+        Zone::Warning => 1,
+        Zone::Above => 2,
+    }
+}
+
+impl Condition for CompositeCondition {
+    fn evaluate(&mut self, data: &TriggerData) -> Result<ConditionResult, ConditionError> {
+        self.evaluate_at(data, Instant::now())
+    }
+
+    fn evaluate_at(
+        &mut self,
+        data: &TriggerData,
+        now: Instant,
+    ) -> Result<ConditionResult, ConditionError> {
+        let results = self
+            .children
+            .iter_mut()
+            .map(|child| child.evaluate_at(data, now))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.combine(&results))
+    }
+
+    /// The busiest child zone for `All`, the idlest for `Any`, over the
+    /// children that report one.
+    fn zone(&self) -> Option<Zone> {
+        let zones = self.children.iter().filter_map(|child| child.zone());
+        match self.operator {
+            CompositeOperator::All => zones.max_by_key(|z| zone_rank(*z)),
+            CompositeOperator::Any => zones.min_by_key(|z| zone_rank(*z)),
+        }
+    }
+
+    /// The first child's value; children may measure different things,
+    /// so they are not combined.
+    fn value(&self) -> Option<u64> {
+        self.children.iter().find_map(|child| child.value())
+    }
+
+    fn reset(&mut self) {
+        for child in &mut self.children {
+            child.reset();
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+/// Serializable description of a (possibly nested) condition.
+///
+/// ```json
+/// { "type": "composite", "operator": "all", "conditions": [
+///     { "type": "threshold", "threshold_bytes_per_sec": 204800,
+///       "required_duration_secs": 120, "monitor_mode": "download_only" },
+///     { "type": "composite", "operator": "any", "conditions": [ ... ] }
+/// ] }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConditionSpec {
+    /// A [`ThresholdCondition`].
+    Threshold {
+        /// Speed threshold in bytes per second.
+        threshold_bytes_per_sec: u64,
+        /// Seconds the speed must stay below the threshold.
+        required_duration_secs: u64,
+        /// Which direction(s) to check.
+        monitor_mode: MonitorMode,
+        /// Warning zone multiple (0 = disabled).
+        #[serde(default)]
+        warning_multiplier: f64,
+        /// Dead band above the threshold (0 = no hysteresis).
+        #[serde(default)]
+        hysteresis_bytes_per_sec: u64,
+        /// Consecutive spikes tolerated while the timer runs.
+        #[serde(default)]
+        max_spike_samples: u32,
+        /// Longest tolerated spike in seconds.
+        #[serde(default)]
+        max_spike_duration_secs: Option<u64>,
+    },
+    /// A [`CumulativeVolumeCondition`].
+    CumulativeVolume {
+        /// Bytes to transfer before the condition is met.
+        limit_bytes: u64,
+        /// Which traffic counts.
+        #[serde(default)]
+        direction: VolumeDirection,
+    },
+    /// A [`CompositeCondition`].
+    Composite {
+        /// How child results are combined.
+        operator: CompositeOperator,
+        /// Child conditions.
+        conditions: Vec<ConditionSpec>,
+    },
+    /// A [`ScheduleCondition`], gating `condition` (if given) to
+    /// `schedule`.
+    Schedule {
+        /// Day-to-windows map, e.g. `{ "mon-fri": ["23:00-07:00"] }`.
+        schedule: WeeklySchedule,
+        /// The gated condition; without one the schedule alone decides.
+        #[serde(default)]
+        condition: Option<Box<ConditionSpec>>,
+    },
+}
+
+impl ConditionSpec {
+    /// Build the described condition.
+    ///
+    /// Fails if any composite in the tree has no children or a volume
+    /// limit is 0.
+    pub fn build(&self) -> Result<Box<dyn Condition>, ConditionError> {
+        Ok(match self {
+            Self::Threshold {
+                threshold_bytes_per_sec,
+                required_duration_secs,
+                monitor_mode,
+                warning_multiplier,
+                hysteresis_bytes_per_sec,
+                max_spike_samples,
+                max_spike_duration_secs,
+            } => Box::new(
+                ThresholdCondition::new(
+                    *threshold_bytes_per_sec,
+                    *required_duration_secs,
+                    *monitor_mode,
+                )
+                .with_warning_multiplier(*warning_multiplier)
+                .with_hysteresis(*hysteresis_bytes_per_sec)
+                .with_max_spike_samples(*max_spike_samples)
+                .with_max_spike_duration_secs(*max_spike_duration_secs),
+            ),
+            Self::CumulativeVolume {
+                limit_bytes,
+                direction,
+            } => {
+                if *limit_bytes == 0 {
+                    return Err(ConditionError::InvalidConfig(
+                        "cumulative volume limit must be greater than 0".into(),
+                    ));
+                }
+                Box::new(CumulativeVolumeCondition::new(*limit_bytes, *direction))
+            }
+            Self::Composite {
+                operator,
+                conditions,
+            } => {
+                if conditions.is_empty() {
+                    return Err(ConditionError::InvalidConfig(
+                        "composite condition needs at least one child".into(),
+                    ));
+                }
+                let children = conditions
+                    .iter()
+                    .map(ConditionSpec::build)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Box::new(CompositeCondition::new(*operator, children))
+            }
+            Self::Schedule {
+                schedule,
+                condition,
+            } => match condition {
+                Some(inner) => Box::new(ScheduleCondition::new(schedule.clone(), inner.build()?)),
+                None => Box::new(ScheduleCondition::standalone(schedule.clone())),
+            },
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_triggers::TriggerValue;
+    use std::time::Duration;
+
+    /// A child that replays a fixed result and counts resets.
+    struct Fixed {
+        result: ConditionResult,
+        resets: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl Condition for Fixed {
+        fn evaluate(&mut self, _: &TriggerData) -> Result<ConditionResult, ConditionError> {
+            Ok(self.result.clone())
+        }
+
+        fn reset(&mut self) {
+            self.resets
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    fn fixed(result: ConditionResult) -> Box<dyn Condition> {
+        Box::new(Fixed {
+            result,
+            resets: Default::default(),
+        })
+    }
+
+    fn progress(elapsed_secs: u64) -> ConditionResult {
+        ConditionResult::in_progress(elapsed_secs, 60)
+    }
+
+    fn combine(operator: CompositeOperator, results: &[ConditionResult]) -> ConditionResult {
+        let children = results.iter().cloned().map(fixed).collect();
+        CompositeCondition::new(operator, children)
+            .evaluate(&TriggerData::new())
+            .unwrap()
+    }
+
+    fn speed_data(download_bps: u64) -> TriggerData {
+        let mut data = TriggerData::new();
+        data.insert("download_bps", TriggerValue::U64(download_bps));
+        data.insert("upload_bps", TriggerValue::U64(0));
+        data
+    }
+
+    #[test]
+    fn all_combines_results() {
+        use ConditionResult::{Met, Waiting};
+        let all = CompositeOperator::All;
+        assert_eq!(combine(all, &[Met, Met]), Met);
+        assert_eq!(combine(all, &[Met, progress(5), progress(30)]), progress(5));
+        assert_eq!(combine(all, &[Met, progress(5), Waiting]), Waiting);
+        assert_eq!(combine(all, &[]), Waiting);
+    }
+
+    #[test]
+    fn any_combines_results() {
+        use ConditionResult::{Met, Waiting};
+        let any = CompositeOperator::Any;
+        assert_eq!(combine(any, &[Waiting, Met]), Met);
+        assert_eq!(
+            combine(any, &[Waiting, progress(5), progress(30)]),
+            progress(30)
+        );
+        assert_eq!(combine(any, &[Waiting, Waiting]), Waiting);
+        assert_eq!(combine(any, &[]), Waiting);
+    }
+
+    #[test]
+    fn reset_reaches_every_child() {
+        let resets: std::sync::Arc<std::sync::atomic::AtomicUsize> = Default::default();
+        let children: Vec<Box<dyn Condition>> = (0..3)
+            .map(|_| {
+                Box::new(Fixed {
+                    result: ConditionResult::Met,
+                    resets: resets.clone(),
+                }) as Box<dyn Condition>
+            })
+            .collect();
+        let mut composite = CompositeCondition::new(CompositeOperator::Any, children);
+        composite.reset();
+        assert_eq!(resets.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn children_share_data_and_clock() {
+        // Both thresholds see the same data; the stricter one gates `All`.
+        let mut composite = CompositeCondition::new(
+            CompositeOperator::All,
+            vec![
+                Box::new(ThresholdCondition::new(200, 10, MonitorMode::DownloadOnly)),
+                Box::new(ThresholdCondition::new(100, 30, MonitorMode::DownloadOnly)),
+            ],
+        );
+        let start = Instant::now();
+        let quiet = speed_data(50);
+
+        assert_eq!(
+            composite.evaluate_at(&quiet, start).unwrap().elapsed_secs(),
+            Some(0)
+        );
+        // The slower child's required time and speed come through.
+        let later = start + Duration::from_secs(20);
+        assert_eq!(
+            composite.evaluate_at(&quiet, later).unwrap(),
+            ConditionResult::in_progress(20, 30).with_current_value(50)
+        );
+        let done = start + Duration::from_secs(30);
+        assert_eq!(
+            composite.evaluate_at(&quiet, done).unwrap(),
+            ConditionResult::Met
+        );
+
+        // Between the thresholds only the looser child is satisfied.
+        assert_eq!(
+            composite.evaluate_at(&speed_data(150), done).unwrap(),
+            ConditionResult::Waiting
+        );
+        assert_eq!(composite.zone(), Some(Zone::Above));
+        assert_eq!(composite.value(), Some(150));
+    }
+
+    #[test]
+    fn missing_data_propagates() {
+        let mut composite = CompositeCondition::new(
+            CompositeOperator::Any,
+            vec![Box::new(ThresholdCondition::new(
+                100,
+                0,
+                MonitorMode::DownloadOnly,
+            ))],
+        );
+        assert!(matches!(
+            composite.evaluate(&TriggerData::new()),
+            Err(ConditionError::MissingData(_))
+        ));
+    }
+
+    #[test]
+    fn spec_builds_nested_tree() {
+        let json = r#"{
+            "type": "composite",
+            "operator": "all",
+            "conditions": [
+                { "type": "threshold", "threshold_bytes_per_sec": 204800,
+                  "required_duration_secs": 0, "monitor_mode": "download_only" },
+                { "type": "composite", "operator": "any", "conditions": [
+                    { "type": "threshold", "threshold_bytes_per_sec": 10,
+                      "required_duration_secs": 0, "monitor_mode": "Both" }
+                ] }
+            ]
+        }"#;
+        let spec: ConditionSpec = serde_json::from_str(json).unwrap();
+        let mut condition = spec.build().unwrap();
+        assert_eq!(
+            condition.evaluate(&speed_data(5)).unwrap(),
+            ConditionResult::Met
+        );
+
+        let round_trip: ConditionSpec =
+            serde_json::from_str(&serde_json::to_string(&spec).unwrap()).unwrap();
+        assert_eq!(round_trip, spec);
+
+        let empty = ConditionSpec::Composite {
+            operator: CompositeOperator::All,
+            conditions: vec![],
+        };
+        assert!(matches!(
+            empty.build(),
+            Err(ConditionError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn spec_builds_cumulative_volume() {
+        let json = r#"{ "type": "cumulative_volume", "limit_bytes": 1000 }"#;
+        let spec: ConditionSpec = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            spec,
+            ConditionSpec::CumulativeVolume {
+                limit_bytes: 1000,
+                direction: VolumeDirection::Download,
+            }
+        );
+        let mut condition = spec.build().unwrap();
+        let mut data = TriggerData::new();
+        data.insert("download_bytes_delta", TriggerValue::U64(1000));
+        data.insert("upload_bytes_delta", TriggerValue::U64(0));
+        assert_eq!(condition.evaluate(&data).unwrap(), ConditionResult::Met);
+
+        let zero = ConditionSpec::CumulativeVolume {
+            limit_bytes: 0,
+            direction: VolumeDirection::Combined,
+        };
+        assert!(matches!(
+            zero.build(),
+            Err(ConditionError::InvalidConfig(_))
+        ));
+    }
+}
@@ -6,13 +6,19 @@
 //!
 //! # Strategic Shift
 //!
-//! The `Condition` trait is generic. Future conditions (composite AND/OR,
-//! schedule-based) implement the same trait.
+//! The `Condition` trait is generic. [`CompositeCondition`] combines
+//! conditions with AND/OR, and [`ScheduleCondition`] gates a condition to
+//! weekday time windows. [`CumulativeVolumeCondition`] is met once enough
+//! data has been transferred.
 
+pub mod composite;
+pub mod schedule;
 pub mod threshold;
+pub mod volume;
 
-use flowwatcher_triggers::TriggerData;
+use flowwatcher_triggers::{TriggerData, TriggerDataError};
 use serde::{Deserialize, Serialize};
+use std::time::Instant;
 use thiserror::Error;
 
 // ---------------------------------------------------------------------------
@@ -29,6 +35,21 @@ pub enum ConditionError {
     /// Invalid configuration.
     #[error("invalid condition config: {0}")]
     InvalidConfig(String),
+
+    /// Trigger data holds a value of the wrong type.
+    #[error(transparent)]
+    InvalidData(TriggerDataError),
+}
+
+/// A missing key stays [`ConditionError::MissingData`]; anything else is
+/// [`ConditionError::InvalidData`].
+impl From<TriggerDataError> for ConditionError {
+    fn from(e: TriggerDataError) -> Self {
+        match e {
+            TriggerDataError::Missing(key) => Self::MissingData(key),
+            other => Self::InvalidData(other),
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -42,15 +63,76 @@ pub enum ConditionResult {
     Waiting,
 
     /// The condition is currently met but has not persisted long enough.
+    ///
+    /// Fields after `elapsed_secs` were added later and default when
+    /// missing, so payloads from older versions still deserialize.
     InProgress {
         /// Seconds elapsed since the condition started being met.
         elapsed_secs: u64,
+        /// Seconds the condition must hold to be met (0 if unknown).
+        #[serde(default)]
+        required_secs: u64,
+        /// `elapsed_secs` as a percentage of `required_secs` (0–100).
+        #[serde(default)]
+        progress: f64,
+        /// The measured value, e.g. the monitored speed in bytes per
+        /// second, if the condition reports one.
+        #[serde(default)]
+        current_value: Option<u64>,
     },
 
     /// The condition has been met for the full required duration.
     Met,
 }
 
+impl ConditionResult {
+    /// An `InProgress` result `elapsed_secs` into `required_secs`, with
+    /// the progress computed and no current value.
+    pub fn in_progress(elapsed_secs: u64, required_secs: u64) -> Self {
+        let progress = if required_secs == 0 {
+            100.0
+        } else {
+            (elapsed_secs as f64 / required_secs as f64 * 100.0).min(100.0)
+        };
+        Self::InProgress {
+            elapsed_secs,
+            required_secs,
+            progress,
+            current_value: None,
+        }
+    }
+
+    /// Attach the measured value to an `InProgress` result. Other results
+    /// are returned unchanged.
+    pub fn with_current_value(mut self, value: u64) -> Self {
+        if let Self::InProgress { current_value, .. } = &mut self {
+            *current_value = Some(value);
+        }
+        self
+    }
+
+    /// Seconds elapsed, for an `InProgress` result.
+    pub fn elapsed_secs(&self) -> Option<u64> {
+        match self {
+            Self::InProgress { elapsed_secs, .. } => Some(*elapsed_secs),
+            _ => None,
+        }
+    }
+
+    /// Seconds left until the condition is met, for an `InProgress`
+    /// result that knows its required duration.
+    pub fn remaining_secs(&self) -> Option<u64> {
+        match self {
+            Self::InProgress {
+                elapsed_secs,
+                required_secs,
+                ..
+            } if *required_secs > 0 => Some(required_secs.saturating_sub(*elapsed_secs)),
+            _ => None,
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Condition trait
 // ---------------------------------------------------------------------------
@@ -59,14 +141,44 @@ pub enum ConditionResult {
 ///
 /// # Strategic Shift
 ///
-/// `ThresholdCondition` is the first implementation. Future conditions
-/// (composite AND/OR, schedule-based) implement this same trait.
+/// `ThresholdCondition` is the first implementation and
+/// `CompositeCondition` and `ScheduleCondition` build on it. Future
+/// conditions implement this same trait.
 pub trait Condition: Send + Sync {
     /// Evaluate the condition against the latest trigger data.
     fn evaluate(&mut self, data: &TriggerData) -> Result<ConditionResult, ConditionError>;
 
+    /// Evaluate as if the current time were `now`.
+    ///
+    /// Used to drive conditions from a virtual clock (e.g. session replay).
+    /// Time-independent conditions can keep the default, which ignores `now`.
+    fn evaluate_at(
+        &mut self,
+        data: &TriggerData,
+        now: Instant,
+    ) -> Result<ConditionResult, ConditionError> {
+        let _ = now;
+        self.evaluate(data)
+    }
+
+    /// Zone of the most recent evaluation, for conditions that compare
+    /// against a threshold. Others keep the default `None`.
+    fn zone(&self) -> Option<Zone> {
+        None
+    }
+
+    /// Monitored value at the most recent evaluation (e.g. the speed a
+    /// threshold compared), for conditions that track one. Others keep the
+    /// default `None`.
+    fn value(&self) -> Option<u64> {
+        None
+    }
+
     /// Reset internal state (e.g., duration timer).
     fn reset(&mut self);
 }
 
-pub use threshold::{MonitorMode, ThresholdCondition};
+pub use composite::{CompositeCondition, CompositeOperator, ConditionSpec};
+pub use schedule::{ScheduleCondition, WallTime, WeeklySchedule};
+pub use threshold::{GapPolicy, MonitorMode, ThresholdCondition, Zone, DEFAULT_MAX_EVALUATION_GAP};
+pub use volume::{CumulativeVolumeCondition, VolumeDirection};
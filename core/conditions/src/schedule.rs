@@ -0,0 +1,480 @@
+//! Schedule-based condition — only allows triggering inside time windows.
+//!
+//! A [`WeeklySchedule`] lists local-time windows per weekday, written as
+//!
+//! ```json
+//! { "mon-fri": ["23:00-07:00"], "sat": ["00:00-24:00"] }
+//! ```
+//!
+//! Keys are a day (`mon` … `sun`), a range (`mon-fri`, or wrapping like
+//! `fri-mon`), `daily`, or a comma-separated list of those. A window whose
+//! end is not after its start crosses midnight and continues into the next
+//! day, so `"fri": ["23:00-07:00"]` covers Friday 23:00 to Saturday 07:00.
+//! Start times are inclusive and end times exclusive; overlapping windows
+//! simply merge.
+
+use crate::{Condition, ConditionError, ConditionResult, Zone};
+use chrono::{Datelike, Timelike};
+use flowwatcher_triggers::TriggerData;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// Minutes in a day.
+const DAY_MINUTES: u32 = 24 * 60;
+
+/// Minutes in a week.
+const WEEK_MINUTES: u32 = 7 * DAY_MINUTES;
+
+/// Weekday names, Monday first.
+const DAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+// ---------------------------------------------------------------------------
+// Wall time
+// ---------------------------------------------------------------------------
+
+/// A local weekday and time of day, to minute precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WallTime {
+    /// Day of the week, 0 = Monday … 6 = Sunday.
+    pub weekday: u8,
+    /// Minutes since local midnight, 0–1439.
+    pub minute: u16,
+}
+
+impl WallTime {
+    /// Build from a weekday (0 = Monday), hour, and minute.
+    pub fn new(weekday: u8, hour: u8, minute: u8) -> Self {
+        Self {
+            weekday: weekday % 7,
+            minute: (u16::from(hour) * 60 + u16::from(minute)) % DAY_MINUTES as u16,
+        }
+    }
+
+    /// The current local wall time.
+    pub fn now() -> Self {
+        let now = chrono::Local::now();
+        Self::new(
+            now.weekday().num_days_from_monday() as u8,
+            now.hour() as u8,
+            now.minute() as u8,
+        )
+    }
+
+    /// Minutes since Monday 00:00.
+    fn minute_of_week(self) -> u32 {
+        u32::from(self.weekday) * DAY_MINUTES + u32::from(self.minute)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// WeeklySchedule
+// ---------------------------------------------------------------------------
+
+/// Local-time windows per weekday.
+///
+/// Serializes as the day-to-windows map it was parsed from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    try_from = "BTreeMap<String, Vec<String>>",
+    into = "BTreeMap<String, Vec<String>>"
+)]
+pub struct WeeklySchedule {
+    /// The map as written.
+    source: BTreeMap<String, Vec<String>>,
+    /// Half-open `[start, end)` intervals in minutes since Monday 00:00.
+    /// `end` may exceed a week for windows wrapping into Monday.
+    intervals: Vec<(u32, u32)>,
+}
+
+impl WeeklySchedule {
+    /// Parse a day-to-windows map.
+    pub fn parse(source: BTreeMap<String, Vec<String>>) -> Result<Self, ConditionError> {
+        let mut intervals = Vec::new();
+        for (days, windows) in &source {
+            let days = parse_days(days)?;
+            for window in windows {
+                let (start, end) = parse_window(window)?;
+                let length = if end > start {
+                    end - start
+                } else {
+                    end + DAY_MINUTES - start
+                };
+                for day in &days {
+                    let from = u32::from(*day) * DAY_MINUTES + start;
+                    intervals.push((from, from + length));
+                }
+            }
+        }
+        Ok(Self { source, intervals })
+    }
+
+    /// Whether `time` falls inside any window.
+    pub fn contains(&self, time: WallTime) -> bool {
+        let minute = time.minute_of_week();
+        self.intervals.iter().any(|&(start, end)| {
+            (start..end).contains(&minute) || (start..end).contains(&(minute + WEEK_MINUTES))
+        })
+    }
+}
+
+impl TryFrom<BTreeMap<String, Vec<String>>> for WeeklySchedule {
+    type Error = ConditionError;
+
+    fn try_from(source: BTreeMap<String, Vec<String>>) -> Result<Self, Self::Error> {
+        Self::parse(source)
+    }
+}
+
+impl From<WeeklySchedule> for BTreeMap<String, Vec<String>> {
+    fn from(schedule: WeeklySchedule) -> Self {
+        schedule.source
+    }
+}
+
+fn invalid(message: String) -> ConditionError {
+    ConditionError::InvalidConfig(message)
+}
+
+/// Parse a day key into weekday indices (0 = Monday).
+fn parse_days(key: &str) -> Result<Vec<u8>, ConditionError> {
+    let day = |name: &str| {
+        DAY_NAMES
+            .iter()
+            .position(|d| name.trim().eq_ignore_ascii_case(d))
+            .map(|i| i as u8)
+            .ok_or_else(|| invalid(format!("unknown day `{name}` in `{key}`")))
+    };
+
+    let mut days = Vec::new();
+    for part in key.split(',') {
+        if part.trim().eq_ignore_ascii_case("daily") {
+            days.extend(0..7);
+        } else if let Some((from, to)) = part.split_once('-') {
+            let (from, to) = (day(from)?, day(to)?);
+            let span = (to + 7 - from) % 7;
+            days.extend((0..=span).map(|offset| (from + offset) % 7));
+        } else {
+            days.push(day(part)?);
+        }
+    }
+    days.sort_unstable();
+    days.dedup();
+    Ok(days)
+}
+
+/// Parse `"HH:MM-HH:MM"` into minutes since midnight. The end may be
+/// `24:00`; an end not after the start crosses midnight.
+fn parse_window(window: &str) -> Result<(u32, u32), ConditionError> {
+    let bad = || invalid(format!("invalid window `{window}` (expected HH:MM-HH:MM)"));
+    let (start, end) = window.split_once('-').ok_or_else(bad)?;
+    let minutes = |time: &str| -> Result<u32, ConditionError> {
+        let (h, m) = time.trim().split_once(':').ok_or_else(bad)?;
+        let (h, m): (u32, u32) = (h.parse().map_err(|_| bad())?, m.parse().map_err(|_| bad())?);
+        if m >= 60 || h * 60 + m > DAY_MINUTES {
+            return Err(bad());
+        }
+        Ok(h * 60 + m)
+    };
+    let (start, end) = (minutes(start)?, minutes(end)?);
+    if start == DAY_MINUTES || start == end {
+        return Err(invalid(format!("window `{window}` is empty")));
+    }
+    Ok((start, end))
+}
+
+// ---------------------------------------------------------------------------
+// ScheduleCondition
+// ---------------------------------------------------------------------------
+
+/// Gates a condition (or stands alone) to the windows of a
+/// [`WeeklySchedule`].
+///
+/// Outside the schedule the result is `Waiting` and the inner condition is
+/// reset, so a duration timer can never carry across a window boundary.
+/// Inside, the inner condition decides; without one the result is `Met`.
+pub struct ScheduleCondition {
+    /// When triggering is allowed.
+    schedule: WeeklySchedule,
+    /// The gated condition, if any.
+    inner: Option<Box<dyn Condition>>,
+    /// Source of the local wall time.
+    clock: fn() -> WallTime,
+    /// Whether the most recent evaluation was inside the schedule.
+    inside: bool,
+}
+
+impl ScheduleCondition {
+    /// Gate `inner` to `schedule`.
+    pub fn new(schedule: WeeklySchedule, inner: Box<dyn Condition>) -> Self {
+        Self {
+            schedule,
+            inner: Some(inner),
+            clock: WallTime::now,
+            inside: false,
+        }
+    }
+
+    /// A condition that is met whenever the schedule is open.
+    pub fn standalone(schedule: WeeklySchedule) -> Self {
+        Self {
+            schedule,
+            inner: None,
+            clock: WallTime::now,
+            inside: false,
+        }
+    }
+
+    /// Read the wall time from `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: fn() -> WallTime) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The schedule.
+    pub fn schedule(&self) -> &WeeklySchedule {
+        &self.schedule
+    }
+
+    /// Evaluate at monotonic time `now` and local wall time `wall`.
+    pub fn evaluate_in(
+        &mut self,
+        data: &TriggerData,
+        now: Instant,
+        wall: WallTime,
+    ) -> Result<ConditionResult, ConditionError> {
+        self.inside = self.schedule.contains(wall);
+        if !self.inside {
+            if let Some(inner) = &mut self.inner {
+                inner.reset();
+            }
+            return Ok(ConditionResult::Waiting);
+        }
+        match &mut self.inner {
+            Some(inner) => inner.evaluate_at(data, now),
+            None => Ok(ConditionResult::Met),
+        }
+    }
+}
+
+impl Condition for ScheduleCondition {
+    fn evaluate(&mut self, data: &TriggerData) -> Result<ConditionResult, ConditionError> {
+        self.evaluate_at(data, Instant::now())
+    }
+
+    fn evaluate_at(
+        &mut self,
+        data: &TriggerData,
+        now: Instant,
+    ) -> Result<ConditionResult, ConditionError> {
+        let wall = (self.clock)();
+        self.evaluate_in(data, now, wall)
+    }
+
+    /// The inner condition's zone while inside the schedule.
+    fn zone(&self) -> Option<Zone> {
+        if self.inside {
+            self.inner.as_ref().and_then(|inner| inner.zone())
+        } else {
+            None
+        }
+    }
+
+    /// The inner condition's value while inside the schedule.
+    fn value(&self) -> Option<u64> {
+        if self.inside {
+            self.inner.as_ref().and_then(|inner| inner.value())
+        } else {
+            None
+        }
+    }
+
+    fn reset(&mut self) {
+        self.inside = false;
+        if let Some(inner) = &mut self.inner {
+            inner.reset();
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MonitorMode, ThresholdCondition};
+    use flowwatcher_triggers::TriggerValue;
+    use std::time::Duration;
+
+    const MON: u8 = 0;
+    const FRI: u8 = 4;
+    const SAT: u8 = 5;
+    const SUN: u8 = 6;
+
+    fn schedule(json: &str) -> WeeklySchedule {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn at(weekday: u8, hour: u8, minute: u8) -> WallTime {
+        WallTime::new(weekday, hour, minute)
+    }
+
+    fn quiet() -> TriggerData {
+        let mut data = TriggerData::new();
+        data.insert("download_bps", TriggerValue::U64(0));
+        data.insert("upload_bps", TriggerValue::U64(0));
+        data
+    }
+
+    #[test]
+    fn overnight_window_boundaries() {
+        let s = schedule(r#"{ "mon-fri": ["23:00-07:00"], "sat": ["00:00-24:00"] }"#);
+
+        assert!(!s.contains(at(MON, 22, 59)));
+        assert!(s.contains(at(MON, 23, 0)));
+        assert!(s.contains(at(MON, 23, 59)));
+        // Tuesday morning is Monday night's window.
+        assert!(s.contains(at(MON + 1, 0, 0)));
+        assert!(s.contains(at(MON + 1, 6, 59)));
+        assert!(!s.contains(at(MON + 1, 7, 0)));
+
+        // Monday 00:00–07:00 belongs to Sunday night, which is not listed.
+        assert!(!s.contains(at(MON, 0, 0)));
+        assert!(!s.contains(at(MON, 6, 59)));
+
+        // Friday night runs into Saturday, which is open all day.
+        assert!(s.contains(at(SAT, 0, 0)));
+        assert!(s.contains(at(SAT, 23, 59)));
+        assert!(!s.contains(at(SUN, 0, 0)));
+        assert!(!s.contains(at(FRI, 12, 0)));
+    }
+
+    #[test]
+    fn sunday_night_wraps_into_monday() {
+        let s = schedule(r#"{ "sun": ["22:00-02:00"] }"#);
+        assert!(!s.contains(at(SUN, 21, 59)));
+        assert!(s.contains(at(SUN, 22, 0)));
+        assert!(s.contains(at(MON, 1, 59)));
+        assert!(!s.contains(at(MON, 2, 0)));
+    }
+
+    #[test]
+    fn overlapping_windows_merge() {
+        let s = schedule(r#"{ "daily": ["20:00-22:00"], "sat,sun": ["21:00-23:30"] }"#);
+        assert!(s.contains(at(SAT, 20, 0)));
+        assert!(s.contains(at(SAT, 22, 30)));
+        assert!(!s.contains(at(SAT, 23, 30)));
+        assert!(!s.contains(at(FRI, 22, 0)));
+        assert!(s.contains(at(FRI, 21, 59)));
+    }
+
+    #[test]
+    fn wrapping_day_ranges() {
+        let s = schedule(r#"{ "fri-mon": ["12:00-13:00"] }"#);
+        for day in [FRI, SAT, SUN, MON] {
+            assert!(s.contains(at(day, 12, 30)), "day {day}");
+        }
+        assert!(!s.contains(at(MON + 1, 12, 30)));
+    }
+
+    #[test]
+    fn rejects_bad_config() {
+        for json in [
+            r#"{ "funday": ["01:00-02:00"] }"#,
+            r#"{ "mon": ["1am-2am"] }"#,
+            r#"{ "mon": ["01:60-02:00"] }"#,
+            r#"{ "mon": ["24:00-02:00"] }"#,
+            r#"{ "mon": ["25:00-02:00"] }"#,
+            r#"{ "mon": ["03:00-03:00"] }"#,
+        ] {
+            assert!(
+                serde_json::from_str::<WeeklySchedule>(json).is_err(),
+                "accepted {json}"
+            );
+        }
+    }
+
+    #[test]
+    fn serializes_as_written() {
+        let json = r#"{"mon-fri":["23:00-07:00"]}"#;
+        assert_eq!(serde_json::to_string(&schedule(json)).unwrap(), json);
+    }
+
+    #[test]
+    fn leaving_the_window_resets_the_inner_timer() {
+        let s = schedule(r#"{ "mon": ["23:00-24:00"] }"#);
+        let inner = ThresholdCondition::new(100, 60, MonitorMode::DownloadOnly);
+        let mut condition = ScheduleCondition::new(s, Box::new(inner));
+        let start = Instant::now();
+        let secs = |n| start + Duration::from_secs(n);
+
+        assert_eq!(
+            condition
+                .evaluate_in(&quiet(), secs(0), at(MON, 23, 58))
+                .unwrap()
+                .elapsed_secs(),
+            Some(0)
+        );
+        assert_eq!(condition.zone(), Some(Zone::Below));
+        assert_eq!(
+            condition
+                .evaluate_in(&quiet(), secs(60), at(MON, 23, 59))
+                .unwrap(),
+            ConditionResult::Met
+        );
+
+        // Midnight closes the window and drops the accumulated time.
+        assert_eq!(
+            condition
+                .evaluate_in(&quiet(), secs(120), at(MON + 1, 0, 0))
+                .unwrap(),
+            ConditionResult::Waiting
+        );
+        assert_eq!(condition.zone(), None);
+
+        // Back inside next week, the timer starts over.
+        assert_eq!(
+            condition
+                .evaluate_in(&quiet(), secs(200), at(MON, 23, 0))
+                .unwrap()
+                .elapsed_secs(),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn standalone_schedule_uses_clock() {
+        let s = schedule(r#"{ "sat": ["00:00-24:00"] }"#);
+        let mut open = ScheduleCondition::standalone(s.clone()).with_clock(|| at(SAT, 9, 0));
+        assert_eq!(open.evaluate(&quiet()).unwrap(), ConditionResult::Met);
+
+        let mut closed = ScheduleCondition::standalone(s).with_clock(|| at(FRI, 9, 0));
+        assert_eq!(closed.evaluate(&quiet()).unwrap(), ConditionResult::Waiting);
+    }
+
+    #[test]
+    fn spec_builds_gated_and_standalone() {
+        let gated: crate::ConditionSpec = serde_json::from_str(
+            r#"{
+                "type": "schedule",
+                "schedule": { "sat": ["00:00-24:00"] },
+                "condition": {
+                    "type": "threshold",
+                    "threshold_bytes_per_sec": 100,
+                    "required_duration_secs": 60,
+                    "monitor_mode": "download_only"
+                }
+            }"#,
+        )
+        .unwrap();
+        assert!(gated.build().is_ok());
+
+        let standalone: crate::ConditionSpec = serde_json::from_str(
+            r#"{ "type": "schedule", "schedule": { "daily": ["09:00-17:00"] } }"#,
+        )
+        .unwrap();
+        assert!(standalone.build().is_ok());
+    }
+}
@@ -3,11 +3,19 @@
 //! Evaluates whether the monitored speed has stayed below a threshold
 //! for a configurable duration. Resets when speed goes back above the
 //! threshold.
+//!
+//! Time below the threshold is accumulated from the gaps between
+//! evaluations rather than read off a start instant, so a gap longer than
+//! [`DEFAULT_MAX_EVALUATION_GAP`] (e.g. a laptop suspend) is handled by a
+//! [`GapPolicy`] instead of counting as idle time.
 
 use crate::{Condition, ConditionError, ConditionResult};
-use flowwatcher_triggers::{TriggerData, TriggerValue};
+use flowwatcher_triggers::TriggerData;
 use serde::{Deserialize, Serialize};
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Longest gap between evaluations counted in full by default.
+pub const DEFAULT_MAX_EVALUATION_GAP: Duration = Duration::from_secs(60);
 
 // ---------------------------------------------------------------------------
 // Monitor mode
@@ -17,11 +25,50 @@ use std::time::Instant;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MonitorMode {
     /// Only monitor download speed.
+    #[serde(alias = "download_only")]
     DownloadOnly,
     /// Only monitor upload speed.
+    #[serde(alias = "upload_only")]
     UploadOnly,
     /// Monitor both — the condition is met only when BOTH are below threshold.
+    #[serde(alias = "both")]
     Both,
+    /// Monitor both — the condition is met when EITHER is below threshold.
+    #[serde(alias = "either")]
+    Either,
+}
+
+// ---------------------------------------------------------------------------
+// Zone
+// ---------------------------------------------------------------------------
+
+/// Where the monitored speed sits relative to the threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Zone {
+    /// Above the warning zone (or above the threshold if it is disabled).
+    Above,
+    /// Between the threshold and `threshold × warning_multiplier` —
+    /// approaching idle, but the duration timer is not running yet.
+    Warning,
+    /// Below the threshold; the duration timer is running.
+    Below,
+}
+
+// ---------------------------------------------------------------------------
+// Gap policy
+// ---------------------------------------------------------------------------
+
+/// What happens to the accumulated time when evaluations are further
+/// apart than the maximum gap, as after a system suspend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GapPolicy {
+    /// Count only the maximum gap and keep the accumulated time.
+    Cap,
+    /// Start the timer over from the evaluation after the gap.
+    #[default]
+    Reset,
 }
 
 // ---------------------------------------------------------------------------
@@ -34,9 +81,30 @@ pub enum MonitorMode {
 ///
 /// 1. Each `evaluate()` call receives `TriggerData` with `download_bps` and `upload_bps`.
 /// 2. The relevant speed(s) are compared against `threshold_bytes_per_sec`.
-/// 3. If below threshold, a timer starts (or continues).
-/// 4. If the timer exceeds `required_duration_secs`, the result is `Met`.
-/// 5. If speed goes back above threshold, the timer resets.
+/// 3. If below threshold, the time since the previous evaluation is added
+///    to the time spent below (the first evaluation below adds nothing).
+/// 4. If that time reaches `required_duration_secs`, the result is `Met`.
+/// 5. If speed goes back above threshold, the time resets.
+///
+/// Time comes from [`Condition::evaluate_at`] or, tick-based, from
+/// [`evaluate_with_elapsed`](Self::evaluate_with_elapsed). A step longer
+/// than the maximum gap is handled per [`GapPolicy`].
+///
+/// With `hysteresis_bytes_per_sec` above 0 the timer starts below the
+/// threshold (the *arm* threshold) but only resets above
+/// `threshold + hysteresis` (the *disarm* threshold), so speeds hovering
+/// around the threshold do not restart it. While the timer runs, speeds in
+/// that dead band are reported as [`Zone::Below`].
+///
+/// With `max_spike_samples` above 0, up to that many consecutive
+/// evaluations above the disarm threshold (and, if set, lasting at most
+/// `max_spike_duration_secs`) are tolerated while the timer runs. They
+/// report `InProgress` without adding time — neither the spike nor the
+/// step back below it counts — and one more resets the timer fully.
+///
+/// With a `warning_multiplier` above 1, speeds between the threshold and
+/// `threshold × warning_multiplier` are reported as [`Zone::Warning`] via
+/// [`last_zone`](Self::last_zone). The result is still `Waiting` there.
 pub struct ThresholdCondition {
     /// Speed threshold in bytes per second.
     pub threshold_bytes_per_sec: u64,
@@ -44,8 +112,33 @@ pub struct ThresholdCondition {
     pub required_duration_secs: u64,
     /// Which traffic direction(s) to monitor.
     pub monitor_mode: MonitorMode,
-    /// When the speed first dropped below threshold (None if currently above).
-    below_since: Option<Instant>,
+    /// Upper edge of the warning zone as a multiple of the threshold
+    /// (values of 1 or less, including the default 0, disable it).
+    pub warning_multiplier: f64,
+    /// Width of the dead band above the threshold in which a running
+    /// timer keeps running (0 = no hysteresis).
+    pub hysteresis_bytes_per_sec: u64,
+    /// Consecutive above-threshold evaluations tolerated while the timer
+    /// runs (0 = none).
+    pub max_spike_samples: u32,
+    /// Longest tolerated spike in seconds (None = limited by count only).
+    pub max_spike_duration_secs: Option<u64>,
+    /// Longest step counted in full.
+    max_gap: Duration,
+    /// What to do with a longer step.
+    gap_policy: GapPolicy,
+    /// Time accumulated below threshold (None if currently above).
+    below_for: Option<Duration>,
+    /// When the previous `evaluate_at` happened.
+    last_evaluated: Option<Instant>,
+    /// Consecutive tolerated spike evaluations so far.
+    spike_samples: u32,
+    /// Time spent in the current tolerated spike.
+    spike_for: Duration,
+    /// Zone of the most recent evaluation.
+    last_zone: Option<Zone>,
+    /// Monitored speed at the most recent evaluation.
+    last_value: Option<u64>,
 }
 
 impl ThresholdCondition {
@@ -64,63 +157,241 @@ impl ThresholdCondition {
             threshold_bytes_per_sec,
             required_duration_secs,
             monitor_mode,
-            below_since: None,
+            warning_multiplier: 0.0,
+            hysteresis_bytes_per_sec: 0,
+            max_spike_samples: 0,
+            max_spike_duration_secs: None,
+            max_gap: DEFAULT_MAX_EVALUATION_GAP,
+            gap_policy: GapPolicy::default(),
+            below_for: None,
+            last_evaluated: None,
+            spike_samples: 0,
+            spike_for: Duration::ZERO,
+            last_zone: None,
+            last_value: None,
         }
     }
 
-    /// Check if the relevant speed(s) are below the threshold.
-    fn is_below_threshold(&self, data: &TriggerData) -> Result<bool, ConditionError> {
-        let download = self.extract_u64(data, "download_bps")?;
-        let upload = self.extract_u64(data, "upload_bps")?;
+    /// Tolerate up to `samples` consecutive evaluations above the
+    /// threshold before the timer resets.
+    pub fn with_max_spike_samples(mut self, samples: u32) -> Self {
+        self.max_spike_samples = samples;
+        self
+    }
 
-        let below = match self.monitor_mode {
-            MonitorMode::DownloadOnly => download < self.threshold_bytes_per_sec,
-            MonitorMode::UploadOnly => upload < self.threshold_bytes_per_sec,
-            MonitorMode::Both => {
-                download < self.threshold_bytes_per_sec && upload < self.threshold_bytes_per_sec
+    /// Also reset the timer once a spike lasts longer than `secs`.
+    pub fn with_max_spike_duration_secs(mut self, secs: Option<u64>) -> Self {
+        self.max_spike_duration_secs = secs;
+        self
+    }
+
+    /// Keep a running timer going until the speed exceeds
+    /// `threshold + bytes`.
+    pub fn with_hysteresis(mut self, bytes: u64) -> Self {
+        self.hysteresis_bytes_per_sec = bytes;
+        self
+    }
+
+    /// Speed below which the timer starts.
+    pub fn arm_threshold(&self) -> u64 {
+        self.threshold_bytes_per_sec
+    }
+
+    /// Speed above which a running timer resets.
+    pub fn disarm_threshold(&self) -> u64 {
+        self.threshold_bytes_per_sec
+            .saturating_add(self.hysteresis_bytes_per_sec)
+    }
+
+    /// Handle steps longer than `max_gap` with `policy` instead of the
+    /// defaults ([`DEFAULT_MAX_EVALUATION_GAP`], [`GapPolicy::Reset`]).
+    pub fn with_gap_policy(mut self, max_gap: Duration, policy: GapPolicy) -> Self {
+        self.max_gap = max_gap;
+        self.gap_policy = policy;
+        self
+    }
+
+    /// Evaluate `data`, `delta` after the previous evaluation.
+    ///
+    /// The tick-based counterpart of [`Condition::evaluate_at`]: callers
+    /// that advance in fixed steps pass the step instead of an instant.
+    pub fn evaluate_with_elapsed(
+        &mut self,
+        data: &TriggerData,
+        delta: Duration,
+    ) -> Result<ConditionResult, ConditionError> {
+        let mut zone = self.zone_for(data)?;
+        if zone != Zone::Below
+            && self.below_for.is_some()
+            && self.all_below(data, self.disarm_threshold() as f64)?
+        {
+            // In the dead band with the timer running: keep it going.
+            zone = Zone::Below;
+        }
+        self.last_zone = Some(zone);
+        let value = self.measured(data)?;
+        self.last_value = Some(value);
+
+        if zone != Zone::Below {
+            if let Some(so_far) = self.below_for.filter(|_| self.tolerate_spike(delta)) {
+                return Ok(self.in_progress(so_far, value));
             }
+            // Speed went back above threshold — reset timer.
+            self.below_for = None;
+            self.end_spike();
+            return Ok(ConditionResult::Waiting);
+        }
+
+        let after_spike = self.spike_samples > 0;
+        self.end_spike();
+        let below_for = match self.below_for {
+            None => Duration::ZERO,
+            Some(so_far) if after_spike => so_far,
+            Some(so_far) if delta <= self.max_gap => so_far + delta,
+            Some(so_far) => match self.gap_policy {
+                GapPolicy::Cap => so_far + self.max_gap,
+                GapPolicy::Reset => Duration::ZERO,
+            },
         };
+        self.below_for = Some(below_for);
 
-        Ok(below)
+        let elapsed = below_for.as_secs();
+        if elapsed >= self.required_duration_secs {
+            Ok(ConditionResult::Met)
+        } else {
+            Ok(self.in_progress(below_for, value))
+        }
+    }
+
+    /// An `InProgress` result `below_for` into the required duration.
+    fn in_progress(&self, below_for: Duration, value: u64) -> ConditionResult {
+        ConditionResult::in_progress(below_for.as_secs(), self.required_duration_secs)
+            .with_current_value(value)
     }
 
-    /// Extract a u64 value from trigger data.
-    fn extract_u64(&self, data: &TriggerData, key: &str) -> Result<u64, ConditionError> {
-        match data.get(key) {
-            Some(TriggerValue::U64(v)) => Ok(*v),
-            Some(_) => Err(ConditionError::MissingData(format!(
-                "{key} is not a u64 value"
-            ))),
-            None => Err(ConditionError::MissingData(key.to_string())),
+    /// Count one more spike evaluation; `true` if it is still within the
+    /// spike budget.
+    fn tolerate_spike(&mut self, delta: Duration) -> bool {
+        self.spike_samples += 1;
+        if self.spike_samples > 1 {
+            self.spike_for += delta;
         }
+        self.spike_samples <= self.max_spike_samples
+            && self
+                .max_spike_duration_secs
+                .is_none_or(|max| self.spike_for <= Duration::from_secs(max))
+    }
+
+    /// Forget the current spike.
+    fn end_spike(&mut self) {
+        self.spike_samples = 0;
+        self.spike_for = Duration::ZERO;
+    }
+
+    /// Report a warning zone up to `threshold × multiplier`.
+    pub fn with_warning_multiplier(mut self, multiplier: f64) -> Self {
+        self.warning_multiplier = multiplier;
+        self
+    }
+
+    /// Zone of the most recent evaluation (None before the first one or
+    /// after a reset).
+    pub fn last_zone(&self) -> Option<Zone> {
+        self.last_zone
+    }
+
+    /// Monitored speed in bytes per second at the most recent evaluation
+    /// (None before the first one or after a reset). In
+    /// [`MonitorMode::Both`] this is the faster direction, in
+    /// [`MonitorMode::Either`] the slower one.
+    ///
+    /// Read it on `Met` to record the speed the condition was met at.
+    pub fn last_value(&self) -> Option<u64> {
+        self.last_value
+    }
+
+    /// Classify `data` without touching the duration timer.
+    ///
+    /// In [`MonitorMode::Both`] a zone applies only when both directions
+    /// are inside it, in [`MonitorMode::Either`] when one of them is,
+    /// mirroring how the threshold itself is checked.
+    ///
+    /// This ignores hysteresis, which depends on whether the timer runs.
+    pub fn zone_for(&self, data: &TriggerData) -> Result<Zone, ConditionError> {
+        let threshold = self.threshold_bytes_per_sec as f64;
+        let warning_limit = if self.warning_multiplier > 1.0 {
+            threshold * self.warning_multiplier
+        } else {
+            threshold
+        };
+
+        Ok(if self.all_below(data, threshold)? {
+            Zone::Below
+        } else if self.all_below(data, warning_limit)? {
+            Zone::Warning
+        } else {
+            Zone::Above
+        })
+    }
+
+    /// Whether the monitored directions are below `limit`: every one of
+    /// them, or any one in [`MonitorMode::Either`].
+    fn all_below(&self, data: &TriggerData, limit: f64) -> Result<bool, ConditionError> {
+        let download = data.get_as_f64("download_bps")?;
+        let upload = data.get_as_f64("upload_bps")?;
+        Ok(match self.monitor_mode {
+            MonitorMode::DownloadOnly => download < limit,
+            MonitorMode::UploadOnly => upload < limit,
+            MonitorMode::Both => download < limit && upload < limit,
+            MonitorMode::Either => download < limit || upload < limit,
+        })
+    }
+
+    /// The monitored speed: the faster direction in [`MonitorMode::Both`],
+    /// the slower one in [`MonitorMode::Either`].
+    fn measured(&self, data: &TriggerData) -> Result<u64, ConditionError> {
+        let download = data.get_as_u64("download_bps")?;
+        let upload = data.get_as_u64("upload_bps")?;
+        Ok(match self.monitor_mode {
+            MonitorMode::DownloadOnly => download,
+            MonitorMode::UploadOnly => upload,
+            MonitorMode::Both => download.max(upload),
+            MonitorMode::Either => download.min(upload),
+        })
     }
 }
 
 impl Condition for ThresholdCondition {
     fn evaluate(&mut self, data: &TriggerData) -> Result<ConditionResult, ConditionError> {
-        let below = self.is_below_threshold(data)?;
-
-        if below {
-            let now = Instant::now();
-            let since = *self.below_since.get_or_insert(now);
-            let elapsed = now.duration_since(since).as_secs();
-
-            if elapsed >= self.required_duration_secs {
-                Ok(ConditionResult::Met)
-            } else {
-                Ok(ConditionResult::InProgress {
-                    elapsed_secs: elapsed,
-                })
-            }
-        } else {
-            // Speed went back above threshold — reset timer.
-            self.below_since = None;
-            Ok(ConditionResult::Waiting)
-        }
+        self.evaluate_at(data, Instant::now())
+    }
+
+    fn evaluate_at(
+        &mut self,
+        data: &TriggerData,
+        now: Instant,
+    ) -> Result<ConditionResult, ConditionError> {
+        let delta = self
+            .last_evaluated
+            .map_or(Duration::ZERO, |last| now.saturating_duration_since(last));
+        self.last_evaluated = Some(now);
+        self.evaluate_with_elapsed(data, delta)
+    }
+
+    fn zone(&self) -> Option<Zone> {
+        self.last_zone
+    }
+
+    fn value(&self) -> Option<u64> {
+        self.last_value
     }
 
     fn reset(&mut self) {
-        self.below_since = None;
+        self.below_for = None;
+        self.last_evaluated = None;
+        self.end_spike();
+        self.last_zone = None;
+        self.last_value = None;
     }
 }
 
@@ -131,6 +402,8 @@ impl Condition for ThresholdCondition {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use flowwatcher_triggers::{TriggerDataError, TriggerValue};
+    use std::time::Duration;
 
     /// Helper: create TriggerData with download and upload speeds.
     fn speed_data(download_bps: u64, upload_bps: u64) -> TriggerData {
@@ -156,10 +429,55 @@ mod tests {
         // Should be InProgress with 0 elapsed (just started)
         assert!(matches!(
             result,
-            ConditionResult::InProgress { elapsed_secs: 0 }
+            ConditionResult::InProgress {
+                elapsed_secs: 0,
+                ..
+            }
         ));
     }
 
+    #[test]
+    fn in_progress_reports_required_time_and_speed() {
+        let mut cond = ThresholdCondition::new(204_800, 120, MonitorMode::Both);
+        let tick = Duration::from_secs(1);
+        let quiet = speed_data(10_000, 58_000);
+        for _ in 0..30 {
+            cond.evaluate_with_elapsed(&quiet, tick).unwrap();
+        }
+        let result = cond.evaluate_with_elapsed(&quiet, tick).unwrap();
+        assert_eq!(
+            result,
+            ConditionResult::InProgress {
+                elapsed_secs: 30,
+                required_secs: 120,
+                progress: 25.0,
+                current_value: Some(58_000),
+            }
+        );
+        assert_eq!(result.remaining_secs(), Some(90));
+
+        for _ in 30..119 {
+            cond.evaluate_with_elapsed(&quiet, tick).unwrap();
+        }
+        let result = cond
+            .evaluate_with_elapsed(&speed_data(57_000, 0), tick)
+            .unwrap();
+        assert_eq!(result, ConditionResult::Met);
+        assert_eq!(cond.last_value(), Some(57_000));
+        cond.reset();
+        assert_eq!(cond.last_value(), None);
+    }
+
+    #[test]
+    fn in_progress_without_new_fields_still_deserializes() {
+        let result: ConditionResult =
+            serde_json::from_str(r#"{"InProgress":{"elapsed_secs":5}}"#).unwrap();
+        assert_eq!(result.elapsed_secs(), Some(5));
+        assert_eq!(result.remaining_secs(), None);
+        let met: ConditionResult = serde_json::from_str(r#""Met""#).unwrap();
+        assert_eq!(met, ConditionResult::Met);
+    }
+
     #[test]
     fn threshold_resets_on_spike() {
         let mut cond = ThresholdCondition::new(204_800, 120, MonitorMode::DownloadOnly);
@@ -177,7 +495,10 @@ mod tests {
         let result = cond.evaluate(&low).unwrap();
         assert!(matches!(
             result,
-            ConditionResult::InProgress { elapsed_secs: 0 }
+            ConditionResult::InProgress {
+                elapsed_secs: 0,
+                ..
+            }
         ));
     }
 
@@ -214,11 +535,465 @@ mod tests {
         assert!(matches!(result, ConditionResult::InProgress { .. }));
     }
 
+    #[test]
+    fn either_mode_requires_one_below() {
+        // (download, upload, tracks)
+        let cases = [
+            (100_000, 100_000, true),
+            (100_000, 500_000, true),
+            (500_000, 100_000, true),
+            (500_000, 500_000, false),
+        ];
+        for (download, upload, tracks) in cases {
+            let mut cond = ThresholdCondition::new(204_800, 120, MonitorMode::Either);
+            let result = cond.evaluate(&speed_data(download, upload)).unwrap();
+            assert_eq!(
+                matches!(result, ConditionResult::InProgress { .. }),
+                tracks,
+                "download {download}, upload {upload}: {result:?}"
+            );
+            assert_eq!(cond.last_value(), Some(download.min(upload)));
+        }
+    }
+
+    #[test]
+    fn monitor_mode_round_trips_through_serde() {
+        for mode in [
+            MonitorMode::DownloadOnly,
+            MonitorMode::UploadOnly,
+            MonitorMode::Both,
+            MonitorMode::Either,
+        ] {
+            let json = serde_json::to_string(&mode).unwrap();
+            assert_eq!(serde_json::from_str::<MonitorMode>(&json).unwrap(), mode);
+        }
+        assert_eq!(
+            serde_json::from_str::<MonitorMode>(r#""either""#).unwrap(),
+            MonitorMode::Either
+        );
+    }
+
     #[test]
     fn missing_data_returns_error() {
         let mut cond = ThresholdCondition::new(204_800, 120, MonitorMode::DownloadOnly);
         let empty = TriggerData::new();
-        let result = cond.evaluate(&empty);
-        assert!(result.is_err());
+        assert!(matches!(
+            cond.evaluate(&empty),
+            Err(ConditionError::MissingData(key)) if key == "download_bps"
+        ));
+
+        let mut data = speed_data(0, 0);
+        data.insert("upload_bps", TriggerValue::String("fast".into()));
+        let err = cond.evaluate(&data).unwrap_err();
+        assert!(matches!(
+            &err,
+            ConditionError::InvalidData(TriggerDataError::WrongType {
+                found: "string",
+                ..
+            })
+        ));
+        assert_eq!(
+            err.to_string(),
+            "trigger data key upload_bps holds string, expected f64"
+        );
+    }
+
+    #[test]
+    fn float_speeds_are_accepted() {
+        let mut cond = ThresholdCondition::new(1_000, 60, MonitorMode::Both);
+        let mut data = TriggerData::new();
+        data.insert("download_bps", TriggerValue::f64(999.4));
+        data.insert("upload_bps", TriggerValue::U64(10));
+        assert!(matches!(
+            cond.evaluate(&data).unwrap(),
+            ConditionResult::InProgress {
+                current_value: Some(999),
+                ..
+            }
+        ));
+
+        data.insert("download_bps", TriggerValue::f64(1_000.0));
+        assert!(!matches!(
+            cond.evaluate(&data).unwrap(),
+            ConditionResult::InProgress { .. }
+        ));
+    }
+
+    /// Zone after evaluating `(download, upload)`, with a fresh condition.
+    fn zone(mode: MonitorMode, download: u64, upload: u64) -> (Zone, ConditionResult) {
+        let mut cond = ThresholdCondition::new(1_000, 60, mode).with_warning_multiplier(2.0);
+        let result = cond.evaluate(&speed_data(download, upload)).unwrap();
+        (cond.last_zone().unwrap(), result)
+    }
+
+    #[test]
+    fn warning_zone_boundaries_download_only() {
+        use ConditionResult::Waiting;
+        assert_eq!(
+            zone(MonitorMode::DownloadOnly, 2_000, 0),
+            (Zone::Above, Waiting)
+        );
+        assert_eq!(
+            zone(MonitorMode::DownloadOnly, 1_999, 0),
+            (Zone::Warning, Waiting)
+        );
+        assert_eq!(
+            zone(MonitorMode::DownloadOnly, 1_000, 0),
+            (Zone::Warning, Waiting)
+        );
+        assert_eq!(zone(MonitorMode::DownloadOnly, 999, 9_999).0, Zone::Below);
+    }
+
+    #[test]
+    fn warning_zone_boundaries_upload_only() {
+        use ConditionResult::Waiting;
+        assert_eq!(
+            zone(MonitorMode::UploadOnly, 0, 2_000),
+            (Zone::Above, Waiting)
+        );
+        assert_eq!(
+            zone(MonitorMode::UploadOnly, 0, 1_999),
+            (Zone::Warning, Waiting)
+        );
+        assert_eq!(
+            zone(MonitorMode::UploadOnly, 0, 1_000),
+            (Zone::Warning, Waiting)
+        );
+        assert_eq!(zone(MonitorMode::UploadOnly, 9_999, 999).0, Zone::Below);
+    }
+
+    #[test]
+    fn warning_zone_boundaries_both() {
+        use ConditionResult::Waiting;
+        // Both directions must be inside a zone for it to apply.
+        assert_eq!(
+            zone(MonitorMode::Both, 1_500, 2_000),
+            (Zone::Above, Waiting)
+        );
+        assert_eq!(
+            zone(MonitorMode::Both, 1_999, 1_999),
+            (Zone::Warning, Waiting)
+        );
+        assert_eq!(
+            zone(MonitorMode::Both, 500, 1_000),
+            (Zone::Warning, Waiting)
+        );
+        assert_eq!(zone(MonitorMode::Both, 999, 999).0, Zone::Below);
+    }
+
+    #[test]
+    fn warning_zone_disabled_by_default() {
+        let mut cond = ThresholdCondition::new(1_000, 60, MonitorMode::DownloadOnly);
+        assert_eq!(cond.last_zone(), None);
+        cond.evaluate(&speed_data(1_000, 0)).unwrap();
+        assert_eq!(cond.last_zone(), Some(Zone::Above));
+
+        let cond = cond.with_warning_multiplier(0.5);
+        assert_eq!(cond.zone_for(&speed_data(1_000, 0)).unwrap(), Zone::Above);
+    }
+
+    #[test]
+    fn warning_zone_does_not_start_timer() {
+        let mut cond = ThresholdCondition::new(1_000, 60, MonitorMode::DownloadOnly)
+            .with_warning_multiplier(2.0);
+        let t0 = Instant::now();
+        cond.evaluate_at(&speed_data(1_500, 0), t0).unwrap();
+        let result = cond
+            .evaluate_at(&speed_data(500, 0), t0 + Duration::from_secs(30))
+            .unwrap();
+        assert_eq!(result.elapsed_secs(), Some(0));
+        assert_eq!(Condition::zone(&cond), Some(Zone::Below));
+
+        cond.reset();
+        assert_eq!(cond.last_zone(), None);
+    }
+
+    #[test]
+    fn evaluate_at_uses_supplied_clock() {
+        let mut cond = ThresholdCondition::new(100, 60, MonitorMode::DownloadOnly);
+        let data = speed_data(10, 0);
+        let t0 = Instant::now();
+
+        let result = cond.evaluate_at(&data, t0).unwrap();
+        assert_eq!(result.elapsed_secs(), Some(0));
+
+        let result = cond
+            .evaluate_at(&data, t0 + Duration::from_secs(30))
+            .unwrap();
+        assert_eq!(result.elapsed_secs(), Some(30));
+
+        let result = cond
+            .evaluate_at(&data, t0 + Duration::from_secs(60))
+            .unwrap();
+        assert_eq!(result, ConditionResult::Met);
+    }
+
+    #[test]
+    fn two_minutes_of_ticks_meet_the_condition() {
+        let mut cond = ThresholdCondition::new(100, 120, MonitorMode::DownloadOnly);
+        let quiet = speed_data(10, 0);
+        let tick = Duration::from_secs(1);
+
+        // The first reading below only starts the timer.
+        assert_eq!(
+            cond.evaluate_with_elapsed(&quiet, tick)
+                .unwrap()
+                .elapsed_secs(),
+            Some(0)
+        );
+        for second in 1..120 {
+            assert_eq!(
+                cond.evaluate_with_elapsed(&quiet, tick)
+                    .unwrap()
+                    .elapsed_secs(),
+                Some(second)
+            );
+        }
+        assert_eq!(
+            cond.evaluate_with_elapsed(&quiet, tick).unwrap(),
+            ConditionResult::Met
+        );
+    }
+
+    #[test]
+    fn two_minutes_on_a_virtual_clock_meet_the_condition() {
+        let mut cond = ThresholdCondition::new(100, 120, MonitorMode::DownloadOnly);
+        let quiet = speed_data(10, 0);
+        let t0 = Instant::now();
+        for second in 0..120 {
+            let result = cond
+                .evaluate_at(&quiet, t0 + Duration::from_secs(second))
+                .unwrap();
+            assert_eq!(result.elapsed_secs(), Some(second));
+        }
+        let result = cond
+            .evaluate_at(&quiet, t0 + Duration::from_secs(120))
+            .unwrap();
+        assert_eq!(result, ConditionResult::Met);
+    }
+
+    #[test]
+    fn suspend_gap_resets_by_default() {
+        let mut cond = ThresholdCondition::new(100, 120, MonitorMode::DownloadOnly);
+        let quiet = speed_data(10, 0);
+        let t0 = Instant::now();
+        cond.evaluate_at(&quiet, t0).unwrap();
+        cond.evaluate_at(&quiet, t0 + Duration::from_secs(30))
+            .unwrap();
+
+        // Eight hours asleep must not trip the condition on wake.
+        let wake = t0 + Duration::from_secs(8 * 3600);
+        assert_eq!(
+            cond.evaluate_at(&quiet, wake).unwrap().elapsed_secs(),
+            Some(0)
+        );
+        assert_eq!(
+            cond.evaluate_at(&quiet, wake + Duration::from_secs(1))
+                .unwrap()
+                .elapsed_secs(),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn suspend_gap_can_be_capped() {
+        let mut cond = ThresholdCondition::new(100, 120, MonitorMode::DownloadOnly)
+            .with_gap_policy(Duration::from_secs(5), GapPolicy::Cap);
+        let quiet = speed_data(10, 0);
+        cond.evaluate_with_elapsed(&quiet, Duration::ZERO).unwrap();
+        cond.evaluate_with_elapsed(&quiet, Duration::from_secs(30))
+            .unwrap();
+        assert_eq!(
+            cond.evaluate_with_elapsed(&quiet, Duration::from_secs(5))
+                .unwrap()
+                .elapsed_secs(),
+            Some(10)
+        );
+        assert_eq!(
+            cond.evaluate_with_elapsed(&quiet, Duration::from_secs(3600))
+                .unwrap()
+                .elapsed_secs(),
+            Some(15)
+        );
+    }
+
+    #[test]
+    fn gap_policy_serializes_snake_case() {
+        assert_eq!(serde_json::to_string(&GapPolicy::Cap).unwrap(), r#""cap""#);
+    }
+
+    #[test]
+    fn hysteresis_dead_band_keeps_timer_running() {
+        let mut cond =
+            ThresholdCondition::new(200, 60, MonitorMode::DownloadOnly).with_hysteresis(50);
+        assert_eq!((cond.arm_threshold(), cond.disarm_threshold()), (200, 250));
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+
+        // The dead band alone does not start the timer.
+        assert_eq!(
+            cond.evaluate_at(&speed_data(220, 0), at(0)).unwrap(),
+            ConditionResult::Waiting
+        );
+        assert_eq!(
+            cond.evaluate_at(&speed_data(199, 0), at(1))
+                .unwrap()
+                .elapsed_secs(),
+            Some(0)
+        );
+        // Flapping between 199 and 249 keeps it going.
+        for (secs, bps) in [(2, 201), (3, 249), (4, 199), (5, 230)] {
+            assert_eq!(
+                cond.evaluate_at(&speed_data(bps, 0), at(secs))
+                    .unwrap()
+                    .elapsed_secs(),
+                Some(secs - 1),
+                "{bps} B/s at {secs}s"
+            );
+            assert_eq!(cond.last_zone(), Some(Zone::Below));
+        }
+        // At the disarm threshold it resets.
+        assert_eq!(
+            cond.evaluate_at(&speed_data(250, 0), at(6)).unwrap(),
+            ConditionResult::Waiting
+        );
+        assert_eq!(
+            cond.evaluate_at(&speed_data(230, 0), at(7)).unwrap(),
+            ConditionResult::Waiting
+        );
+    }
+
+    #[test]
+    fn hysteresis_applies_to_both_directions() {
+        let mut cond = ThresholdCondition::new(200, 60, MonitorMode::Both).with_hysteresis(50);
+        let t0 = Instant::now();
+        cond.evaluate_at(&speed_data(100, 100), t0).unwrap();
+        assert_eq!(
+            cond.evaluate_at(&speed_data(240, 240), t0 + Duration::from_secs(1))
+                .unwrap()
+                .elapsed_secs(),
+            Some(1)
+        );
+        assert_eq!(
+            cond.evaluate_at(&speed_data(240, 260), t0 + Duration::from_secs(2))
+                .unwrap(),
+            ConditionResult::Waiting
+        );
+    }
+
+    #[test]
+    fn zero_hysteresis_flaps_like_a_single_threshold() {
+        let mut cond = ThresholdCondition::new(200, 60, MonitorMode::DownloadOnly);
+        assert_eq!(cond.disarm_threshold(), cond.arm_threshold());
+        let t0 = Instant::now();
+        cond.evaluate_at(&speed_data(199, 0), t0).unwrap();
+        assert_eq!(
+            cond.evaluate_at(&speed_data(201, 0), t0 + Duration::from_secs(1))
+                .unwrap(),
+            ConditionResult::Waiting
+        );
+    }
+
+    #[test]
+    fn brief_spikes_are_tolerated_without_counting() {
+        let mut cond =
+            ThresholdCondition::new(100, 600, MonitorMode::UploadOnly).with_max_spike_samples(2);
+        let tick = Duration::from_secs(1);
+        let quiet = speed_data(0, 10);
+        let announce = speed_data(0, 5_000);
+
+        cond.evaluate_with_elapsed(&quiet, tick).unwrap();
+        for _ in 0..10 {
+            cond.evaluate_with_elapsed(&quiet, tick).unwrap();
+        }
+        // A two-second tracker announce is tolerated...
+        for _ in 0..2 {
+            assert_eq!(
+                cond.evaluate_with_elapsed(&announce, tick)
+                    .unwrap()
+                    .elapsed_secs(),
+                Some(10)
+            );
+            assert_eq!(cond.last_zone(), Some(Zone::Above));
+        }
+        // ...and the spike, including the step back down, adds no time.
+        assert_eq!(
+            cond.evaluate_with_elapsed(&quiet, tick)
+                .unwrap()
+                .elapsed_secs(),
+            Some(10)
+        );
+        assert_eq!(
+            cond.evaluate_with_elapsed(&quiet, tick)
+                .unwrap()
+                .elapsed_secs(),
+            Some(11)
+        );
+    }
+
+    #[test]
+    fn exceeding_the_spike_budget_resets_fully() {
+        let mut cond =
+            ThresholdCondition::new(100, 600, MonitorMode::UploadOnly).with_max_spike_samples(2);
+        let tick = Duration::from_secs(1);
+        let quiet = speed_data(0, 10);
+        let busy = speed_data(0, 5_000);
+
+        cond.evaluate_with_elapsed(&quiet, tick).unwrap();
+        cond.evaluate_with_elapsed(&quiet, tick).unwrap();
+        cond.evaluate_with_elapsed(&busy, tick).unwrap();
+        cond.evaluate_with_elapsed(&busy, tick).unwrap();
+        assert_eq!(
+            cond.evaluate_with_elapsed(&busy, tick).unwrap(),
+            ConditionResult::Waiting
+        );
+        assert_eq!(
+            cond.evaluate_with_elapsed(&quiet, tick)
+                .unwrap()
+                .elapsed_secs(),
+            Some(0)
+        );
+
+        // The budget is per spike: separate spikes each get the full count.
+        for _ in 0..3 {
+            cond.evaluate_with_elapsed(&busy, tick).unwrap();
+            cond.evaluate_with_elapsed(&busy, tick).unwrap();
+            assert!(matches!(
+                cond.evaluate_with_elapsed(&quiet, tick).unwrap(),
+                ConditionResult::InProgress { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn spike_duration_limit_resets_long_spikes() {
+        let mut cond = ThresholdCondition::new(100, 600, MonitorMode::UploadOnly)
+            .with_max_spike_samples(10)
+            .with_max_spike_duration_secs(Some(3));
+        let busy = speed_data(0, 5_000);
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+
+        cond.evaluate_at(&speed_data(0, 10), at(0)).unwrap();
+        for secs in 1..=4 {
+            assert!(matches!(
+                cond.evaluate_at(&busy, at(secs)).unwrap(),
+                ConditionResult::InProgress { .. }
+            ));
+        }
+        assert_eq!(
+            cond.evaluate_at(&busy, at(5)).unwrap(),
+            ConditionResult::Waiting
+        );
+    }
+
+    #[test]
+    fn spikes_do_not_start_a_timer() {
+        let mut cond =
+            ThresholdCondition::new(100, 600, MonitorMode::UploadOnly).with_max_spike_samples(2);
+        assert_eq!(
+            cond.evaluate(&speed_data(0, 5_000)).unwrap(),
+            ConditionResult::Waiting
+        );
     }
 }
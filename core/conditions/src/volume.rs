@@ -0,0 +1,174 @@
+//! Cumulative transfer volume condition.
+//!
+//! Adds up the bytes transferred since monitoring started and is met once
+//! they reach a limit, e.g. "run the action after 50 GB downloaded" as a
+//! data-cap guard. The byte counts come from the network trigger's
+//! `download_bytes_delta` / `upload_bytes_delta` keys, so nothing is lost
+//! to smoothing.
+
+use crate::{Condition, ConditionError, ConditionResult};
+use flowwatcher_triggers::TriggerData;
+use serde::{Deserialize, Serialize};
+
+// ---------------------------------------------------------------------------
+// Volume direction
+// ---------------------------------------------------------------------------
+
+/// Which traffic counts towards the limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeDirection {
+    /// Only received bytes.
+    #[default]
+    Download,
+    /// Only sent bytes.
+    Upload,
+    /// Received and sent bytes together.
+    Combined,
+}
+
+// ---------------------------------------------------------------------------
+// CumulativeVolumeCondition
+// ---------------------------------------------------------------------------
+
+/// Met once the bytes transferred since the last reset reach a limit.
+///
+/// Every evaluation adds that sample's byte deltas and reports
+/// `InProgress` with the running total as its current value and the
+/// share of the limit as its progress. It has no duration, so
+/// `elapsed_secs` and `required_secs` are 0.
+#[derive(Debug, Clone)]
+pub struct CumulativeVolumeCondition {
+    /// Bytes to transfer before the condition is met.
+    pub limit_bytes: u64,
+    /// Which traffic counts.
+    pub direction: VolumeDirection,
+    /// Bytes counted since the last reset.
+    transferred: u64,
+}
+
+impl CumulativeVolumeCondition {
+    /// Create a condition met after `limit_bytes` in `direction`.
+    pub fn new(limit_bytes: u64, direction: VolumeDirection) -> Self {
+        Self {
+            limit_bytes,
+            direction,
+            transferred: 0,
+        }
+    }
+
+    /// Bytes counted since the last reset.
+    pub fn transferred(&self) -> u64 {
+        self.transferred
+    }
+
+    /// The bytes `data` adds for this condition's direction.
+    fn delta(&self, data: &TriggerData) -> Result<u64, ConditionError> {
+        Ok(match self.direction {
+            VolumeDirection::Download => data.get_u64("download_bytes_delta")?,
+            VolumeDirection::Upload => data.get_u64("upload_bytes_delta")?,
+            VolumeDirection::Combined => data
+                .get_u64("download_bytes_delta")?
+                .saturating_add(data.get_u64("upload_bytes_delta")?),
+        })
+    }
+}
+
+impl Condition for CumulativeVolumeCondition {
+    fn evaluate(&mut self, data: &TriggerData) -> Result<ConditionResult, ConditionError> {
+        self.transferred = self.transferred.saturating_add(self.delta(data)?);
+        if self.transferred >= self.limit_bytes {
+            return Ok(ConditionResult::Met);
+        }
+        Ok(ConditionResult::InProgress {
+            elapsed_secs: 0,
+            required_secs: 0,
+            progress: self.transferred as f64 / self.limit_bytes as f64 * 100.0,
+            current_value: Some(self.transferred),
+        })
+    }
+
+    fn reset(&mut self) {
+        self.transferred = 0;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_triggers::TriggerValue;
+
+    fn deltas(download: u64, upload: u64) -> TriggerData {
+        let mut data = TriggerData::new();
+        data.insert("download_bytes_delta", TriggerValue::U64(download));
+        data.insert("upload_bytes_delta", TriggerValue::U64(upload));
+        data
+    }
+
+    #[test]
+    fn accumulates_until_the_limit() {
+        let mut cond = CumulativeVolumeCondition::new(1_000, VolumeDirection::Download);
+        let result = cond.evaluate(&deltas(400, 900)).unwrap();
+        assert_eq!(
+            result,
+            ConditionResult::InProgress {
+                elapsed_secs: 0,
+                required_secs: 0,
+                progress: 40.0,
+                current_value: Some(400),
+            }
+        );
+        assert!(matches!(
+            cond.evaluate(&deltas(599, 0)).unwrap(),
+            ConditionResult::InProgress {
+                current_value: Some(999),
+                ..
+            }
+        ));
+        assert_eq!(cond.evaluate(&deltas(1, 0)).unwrap(), ConditionResult::Met);
+        assert_eq!(cond.transferred(), 1_000);
+    }
+
+    #[test]
+    fn direction_selects_the_counted_bytes() {
+        let data = deltas(300, 200);
+        for (direction, expected) in [
+            (VolumeDirection::Download, 300),
+            (VolumeDirection::Upload, 200),
+            (VolumeDirection::Combined, 500),
+        ] {
+            let mut cond = CumulativeVolumeCondition::new(10_000, direction);
+            cond.evaluate(&data).unwrap();
+            assert_eq!(cond.transferred(), expected, "{direction:?}");
+        }
+    }
+
+    #[test]
+    fn reset_zeroes_the_total() {
+        let mut cond = CumulativeVolumeCondition::new(1_000, VolumeDirection::Combined);
+        cond.evaluate(&deltas(600, 600)).unwrap();
+        assert_eq!(cond.transferred(), 1_200);
+        cond.reset();
+        assert_eq!(cond.transferred(), 0);
+        assert!(matches!(
+            cond.evaluate(&deltas(10, 0)).unwrap(),
+            ConditionResult::InProgress { .. }
+        ));
+    }
+
+    #[test]
+    fn missing_deltas_are_an_error() {
+        let mut cond = CumulativeVolumeCondition::new(1_000, VolumeDirection::Upload);
+        let mut data = TriggerData::new();
+        data.insert("upload_bps", TriggerValue::U64(100));
+        assert!(matches!(
+            cond.evaluate(&data),
+            Err(ConditionError::MissingData(key)) if key == "upload_bytes_delta"
+        ));
+        assert_eq!(cond.transferred(), 0);
+    }
+}
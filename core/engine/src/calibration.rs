@@ -0,0 +1,387 @@
+//! Idle-baseline calibration for threshold suggestions.
+//!
+//! While the machine is left alone, the desktop app samples network speed
+//! and passes the readings to [`calibrate`], which derives the idle
+//! baseline (p95) and burst level (p99) and suggests a threshold a few
+//! times above the baseline, together with a [`Confidence`] rating.
+//! Without a calibration run, a threshold can instead be suggested as a
+//! share of the adapter's [`LinkSpeed`].
+//! Everything here is pure so it can be tested on synthetic sample sets.
+
+use crate::speed::SpeedReading;
+use flowwatcher_conditions::MonitorMode;
+use flowwatcher_platform::network::{InterfaceInfo, InterfaceKind};
+use serde::{Deserialize, Serialize};
+
+/// Fewer samples than this always yield [`Confidence::Low`].
+pub const MIN_SAMPLES: usize = 10;
+
+/// At least this many samples are required for [`Confidence::High`].
+pub const HIGH_CONFIDENCE_SAMPLES: usize = 30;
+
+/// Link speeds above this (10 Gbps) are clamped and marked nominal:
+/// virtual adapters routinely claim 10–100 Gbps they never carry.
+pub const MAX_LINK_SPEED_MBPS: u64 = 10_000;
+
+// ---------------------------------------------------------------------------
+// Parameters
+// ---------------------------------------------------------------------------
+
+/// Tunables for turning a baseline into a suggested threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationParams {
+    /// Suggested threshold = baseline × multiplier (before clamping).
+    pub multiplier: f64,
+    /// Lower bound for the suggestion in bytes per second.
+    pub min_threshold_bps: u64,
+    /// Upper bound for the suggestion in bytes per second.
+    pub max_threshold_bps: u64,
+}
+
+impl Default for CalibrationParams {
+    fn default() -> Self {
+        Self {
+            multiplier: 3.0,
+            min_threshold_bps: 10 * 1024,
+            max_threshold_bps: 10 * 1024 * 1024,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Result
+// ---------------------------------------------------------------------------
+
+/// How much the suggestion can be trusted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Confidence {
+    /// Too few samples, or idle traffic was too bursty to judge.
+    Low,
+    /// Usable, but bursts were noticeably above the baseline.
+    Medium,
+    /// Plenty of samples and a steady baseline.
+    High,
+}
+
+/// Outcome of a calibration run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationResult {
+    /// Observed idle baseline (95th percentile) in bytes per second.
+    pub baseline_bps: u64,
+    /// Typical burst level (99th percentile) in bytes per second.
+    pub burst_bps: u64,
+    /// Suggested threshold in bytes per second.
+    pub suggested_threshold_bps: u64,
+    /// How reliable the suggestion is.
+    pub confidence: Confidence,
+    /// Number of samples the result is based on.
+    pub sample_count: usize,
+}
+
+// ---------------------------------------------------------------------------
+// Statistics
+// ---------------------------------------------------------------------------
+
+/// Nearest-rank percentile of `samples` (`p` in `0.0..=100.0`).
+///
+/// Returns `None` for an empty set. `p` outside the range is clamped.
+pub fn percentile(samples: &[u64], p: f64) -> Option<u64> {
+    if samples.is_empty() {
+        return None;
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    let p = if p.is_nan() { 0.0 } else { p.clamp(0.0, 100.0) };
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.saturating_sub(1).min(sorted.len() - 1)])
+}
+
+/// Baseline × multiplier, clamped to the configured bounds.
+pub fn suggest_threshold(baseline_bps: u64, params: &CalibrationParams) -> u64 {
+    let raw = baseline_bps as f64 * params.multiplier;
+    let raw = if raw.is_finite() {
+        raw as u64
+    } else {
+        u64::MAX
+    };
+    raw.clamp(params.min_threshold_bps, params.max_threshold_bps)
+}
+
+/// Rate confidence from sample count and how far bursts exceed the baseline.
+pub fn confidence(sample_count: usize, baseline_bps: u64, burst_bps: u64) -> Confidence {
+    if sample_count < MIN_SAMPLES {
+        return Confidence::Low;
+    }
+    // Treat anything under 1 KB/s as the same quiet floor so near-zero
+    // baselines don't turn tiny blips into huge ratios.
+    let floor = baseline_bps.max(1024);
+    if burst_bps <= floor.saturating_mul(2) && sample_count >= HIGH_CONFIDENCE_SAMPLES {
+        Confidence::High
+    } else if burst_bps <= floor.saturating_mul(5) {
+        Confidence::Medium
+    } else {
+        Confidence::Low
+    }
+}
+
+/// The speed the condition compares for `mode`.
+///
+/// In [`MonitorMode::Both`] both directions must be below the threshold,
+/// so the larger one decides; in [`MonitorMode::Either`] one below is
+/// enough, so the smaller one does.
+pub fn relevant_speed(reading: &SpeedReading, mode: MonitorMode) -> u64 {
+    match mode {
+        MonitorMode::DownloadOnly => reading.download_bps,
+        MonitorMode::UploadOnly => reading.upload_bps,
+        MonitorMode::Both => reading.download_bps.max(reading.upload_bps),
+        MonitorMode::Either => reading.download_bps.min(reading.upload_bps),
+    }
+}
+
+/// Derive a calibration result from idle readings.
+///
+/// Returns `None` if there are no readings.
+pub fn calibrate(
+    readings: &[SpeedReading],
+    mode: MonitorMode,
+    params: &CalibrationParams,
+) -> Option<CalibrationResult> {
+    let samples: Vec<u64> = readings.iter().map(|r| relevant_speed(r, mode)).collect();
+    let baseline_bps = percentile(&samples, 95.0)?;
+    let burst_bps = percentile(&samples, 99.0)?;
+
+    Some(CalibrationResult {
+        baseline_bps,
+        burst_bps,
+        suggested_threshold_bps: suggest_threshold(baseline_bps, params),
+        confidence: confidence(samples.len(), baseline_bps, burst_bps),
+        sample_count: samples.len(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Link speed
+// ---------------------------------------------------------------------------
+
+/// An adapter's link speed, as a basis for threshold suggestions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkSpeed {
+    /// Link speed in megabits per second, at most [`MAX_LINK_SPEED_MBPS`].
+    pub mbps: u64,
+    /// The figure is not a real limit: the adapter is virtual or loopback,
+    /// or claimed more than [`MAX_LINK_SPEED_MBPS`].
+    pub nominal: bool,
+}
+
+impl LinkSpeed {
+    /// The link speed of `interface`, or `None` if the OS reports none.
+    pub fn of(interface: &InterfaceInfo) -> Option<Self> {
+        let reported = interface.link_speed_mbps.filter(|&mbps| mbps > 0)?;
+        Some(Self {
+            mbps: reported.min(MAX_LINK_SPEED_MBPS),
+            nominal: reported > MAX_LINK_SPEED_MBPS
+                || matches!(
+                    interface.kind,
+                    InterfaceKind::Virtual | InterfaceKind::Loopback
+                ),
+        })
+    }
+
+    /// Link capacity in bytes per second.
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.mbps.saturating_mul(1_000_000) / 8
+    }
+
+    /// `percent` of the link capacity in bytes per second, e.g. `2.0` for
+    /// "2% of link speed". `percent` outside 0–100 is clamped.
+    pub fn suggest_threshold(&self, percent: f64) -> u64 {
+        let percent = if percent.is_nan() {
+            0.0
+        } else {
+            percent.clamp(0.0, 100.0)
+        };
+        (self.bytes_per_sec() as f64 * percent / 100.0).round() as u64
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn downloads(values: &[u64]) -> Vec<SpeedReading> {
+        values
+            .iter()
+            .map(|&d| SpeedReading {
+                download_bps: d,
+                upload_bps: 0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let samples: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&samples, 95.0), Some(95));
+        assert_eq!(percentile(&samples, 99.0), Some(99));
+        assert_eq!(percentile(&samples, 100.0), Some(100));
+        assert_eq!(percentile(&samples, 0.0), Some(1));
+        assert_eq!(percentile(&[7, 3, 5], 50.0), Some(5));
+    }
+
+    #[test]
+    fn percentile_handles_edge_inputs() {
+        assert_eq!(percentile(&[], 95.0), None);
+        assert_eq!(percentile(&[42], 95.0), Some(42));
+        assert_eq!(percentile(&[1, 2, 3], 250.0), Some(3));
+        assert_eq!(percentile(&[1, 2, 3], -5.0), Some(1));
+        assert_eq!(percentile(&[1, 2, 3], f64::NAN), Some(1));
+    }
+
+    #[test]
+    fn suggestion_is_clamped() {
+        let params = CalibrationParams::default();
+        assert_eq!(suggest_threshold(20_000, &params), 60_000);
+        assert_eq!(suggest_threshold(0, &params), params.min_threshold_bps);
+        assert_eq!(
+            suggest_threshold(u64::MAX, &params),
+            params.max_threshold_bps
+        );
+    }
+
+    #[test]
+    fn steady_idle_is_high_confidence() {
+        // 60 s of ~2 KB/s background chatter.
+        let values: Vec<u64> = (0..60).map(|i| 2_000 + (i % 5) * 100).collect();
+        let result = calibrate(
+            &downloads(&values),
+            MonitorMode::DownloadOnly,
+            &Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!(result.baseline_bps, 2_400);
+        assert_eq!(result.suggested_threshold_bps, 10 * 1024);
+        assert_eq!(result.confidence, Confidence::High);
+        assert_eq!(result.sample_count, 60);
+    }
+
+    #[test]
+    fn occasional_bursts_lower_confidence() {
+        // Mostly 10 KB/s with one 45 KB/s burst per 20 samples.
+        let values: Vec<u64> = (0..40)
+            .map(|i| if i % 20 == 0 { 45_000 } else { 10_000 })
+            .collect();
+        let result = calibrate(
+            &downloads(&values),
+            MonitorMode::DownloadOnly,
+            &Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!(result.baseline_bps, 10_000);
+        assert_eq!(result.burst_bps, 45_000);
+        assert_eq!(result.suggested_threshold_bps, 30_000);
+        assert_eq!(result.confidence, Confidence::Medium);
+    }
+
+    #[test]
+    fn single_outlier_does_not_move_baseline() {
+        let mut values = vec![1_000; 99];
+        values.push(5_000_000);
+        let result = calibrate(
+            &downloads(&values),
+            MonitorMode::DownloadOnly,
+            &Default::default(),
+        )
+        .unwrap();
+        assert_eq!(result.baseline_bps, 1_000);
+        assert_eq!(result.burst_bps, 1_000);
+        assert_eq!(result.confidence, Confidence::High);
+    }
+
+    #[test]
+    fn short_or_bursty_runs_are_low_confidence() {
+        assert_eq!(confidence(5, 1_000, 1_000), Confidence::Low);
+
+        let mut values = vec![1_000; 96];
+        values.extend([5_000_000; 4]);
+        let result = calibrate(
+            &downloads(&values),
+            MonitorMode::DownloadOnly,
+            &Default::default(),
+        )
+        .unwrap();
+        assert_eq!(result.baseline_bps, 1_000);
+        assert_eq!(result.burst_bps, 5_000_000);
+        assert_eq!(result.confidence, Confidence::Low);
+    }
+
+    #[test]
+    fn mode_selects_direction() {
+        let reading = SpeedReading {
+            download_bps: 300,
+            upload_bps: 900,
+        };
+        assert_eq!(relevant_speed(&reading, MonitorMode::DownloadOnly), 300);
+        assert_eq!(relevant_speed(&reading, MonitorMode::UploadOnly), 900);
+        assert_eq!(relevant_speed(&reading, MonitorMode::Both), 900);
+        assert_eq!(relevant_speed(&reading, MonitorMode::Either), 300);
+    }
+
+    fn adapter(kind: InterfaceKind, link_speed_mbps: Option<u64>) -> InterfaceInfo {
+        InterfaceInfo {
+            id: "eth0".into(),
+            name: "eth0".into(),
+            mac: String::new(),
+            is_up: true,
+            kind,
+            description: None,
+            link_speed_mbps,
+        }
+    }
+
+    #[test]
+    fn link_speed_suggests_a_share_of_capacity() {
+        let gigabit = LinkSpeed::of(&adapter(InterfaceKind::Ethernet, Some(1_000))).unwrap();
+        assert_eq!(
+            gigabit,
+            LinkSpeed {
+                mbps: 1_000,
+                nominal: false
+            }
+        );
+        assert_eq!(gigabit.bytes_per_sec(), 125_000_000);
+        assert_eq!(gigabit.suggest_threshold(2.0), 2_500_000);
+        assert_eq!(gigabit.suggest_threshold(250.0), 125_000_000);
+        assert_eq!(gigabit.suggest_threshold(-1.0), 0);
+        assert_eq!(gigabit.suggest_threshold(f64::NAN), 0);
+
+        assert_eq!(LinkSpeed::of(&adapter(InterfaceKind::Wifi, None)), None);
+        assert_eq!(LinkSpeed::of(&adapter(InterfaceKind::Wifi, Some(0))), None);
+    }
+
+    #[test]
+    fn implausible_link_speeds_are_clamped_and_flagged() {
+        let claimed = LinkSpeed::of(&adapter(InterfaceKind::Ethernet, Some(100_000))).unwrap();
+        assert_eq!(
+            claimed,
+            LinkSpeed {
+                mbps: MAX_LINK_SPEED_MBPS,
+                nominal: true
+            }
+        );
+        let virtual_switch = LinkSpeed::of(&adapter(InterfaceKind::Virtual, Some(1_000))).unwrap();
+        assert!(virtual_switch.nominal);
+        assert_eq!(virtual_switch.mbps, 1_000);
+    }
+
+    #[test]
+    fn no_readings_no_result() {
+        assert!(calibrate(&[], MonitorMode::Both, &Default::default()).is_none());
+    }
+}
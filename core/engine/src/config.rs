@@ -0,0 +1,699 @@
+//! Monitoring session configuration.
+//!
+//! [`MonitoringConfig`] describes one session — trigger, condition, action,
+//! pre-warning and countdown — in the JSON shape the desktop app sends to
+//! `start_monitoring` and saves in profiles. The command-line runner reads
+//! the same structure from a config file, so both front ends agree on
+//! field names and defaults. [`MonitoringConfig::validate`] rejects
+//! settings that would act too early or never trigger, naming the field.
+
+use crate::hooks::LifecycleHooks;
+use crate::power::PowerRules;
+use crate::rate::deserialize_bytes_per_sec;
+#[cfg(doc)]
+use crate::rate::ByteRate;
+use crate::rearm::RepeatMode;
+use crate::scheduler::AckPolicy;
+use flowwatcher_actions::{ActionSpec, RetryPolicy};
+use flowwatcher_conditions::{Condition, ConditionSpec, MonitorMode, ThresholdCondition};
+use flowwatcher_triggers::speed::SmoothingStrategy;
+use flowwatcher_triggers::{
+    CpuMode, FailoverPolicy, InterfaceChoice, ProcessMatch, ProcessTrigger,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Shortest countdown allowed, so there is always time to cancel.
+pub const MIN_COUNTDOWN_SECS: u64 = 5;
+
+/// Most attempts a retry policy may make, so backoff cannot keep a
+/// session's action running for hours.
+pub const MAX_RETRY_ATTEMPTS: u32 = 10;
+
+/// Monitor mode strings accepted by [`ConditionConfig::monitor_mode`].
+pub const MONITOR_MODES: [&str; 4] = ["download_only", "upload_only", "both", "either"];
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+/// A monitoring config field with an unusable value.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{field}: {message}")]
+pub struct ConfigError {
+    /// Path of the field, e.g. `condition.threshold_bytes_per_sec`.
+    pub field: String,
+    /// What is wrong with it.
+    pub message: String,
+}
+
+impl ConfigError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Session configuration
+// ---------------------------------------------------------------------------
+
+/// Configuration for starting a monitoring session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitoringConfig {
+    /// Which trigger type to use.
+    pub trigger_type: TriggerConfig,
+    /// Condition parameters.
+    pub condition: ConditionConfig,
+    /// Optional AND/OR combination of conditions. When set it replaces
+    /// `condition` as the session's condition (see
+    /// [`MonitoringConfig::session_condition`]); `condition` still drives
+    /// the idle estimate and calibration.
+    #[serde(default)]
+    pub condition_tree: Option<ConditionSpec>,
+    /// Which action to execute when triggered: one action type id, or a
+    /// list of steps run in order.
+    pub action_type: ActionSelection,
+    /// Parameters for a single action (e.g. the webhook URL); `null` for
+    /// system actions. Sequence steps carry their own params.
+    #[serde(default)]
+    pub action_params: serde_json::Value,
+    /// Whether a sequence keeps running after a step fails.
+    #[serde(default)]
+    pub continue_on_error: bool,
+    /// How often a failed action is retried (default: once, no retries).
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// Pre-warning duration in seconds.
+    pub pre_warning_secs: u64,
+    /// Countdown duration in seconds.
+    pub countdown_secs: u64,
+    /// Whether the pre-warning must be acknowledged before the countdown.
+    #[serde(default)]
+    pub ack_policy: AckPolicy,
+    /// Cap on the total seconds the action can be snoozed.
+    #[serde(default = "default_max_snooze_secs")]
+    pub max_snooze_secs: u64,
+    /// How speed readings are smoothed (default: 3-sample average).
+    #[serde(default)]
+    pub smoothing: SmoothingStrategy,
+    /// What to do when the monitored interface goes dead while another
+    /// one carries the traffic (default: nothing).
+    #[serde(default)]
+    pub interface_failover: FailoverPolicy,
+    /// Run the whole pipeline but replace the action with a notification.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Replace a session that is already running instead of refusing to
+    /// start. Applies to one start only, so it is never saved.
+    #[serde(default, skip_serializing)]
+    pub restart: bool,
+    /// Power source requirements checked right before the action runs.
+    #[serde(default)]
+    pub power_guard: PowerRules,
+    /// Postpone the countdown until there has been no keyboard or mouse
+    /// input for this many seconds.
+    #[serde(default)]
+    pub require_user_idle_secs: Option<u64>,
+    /// Cancel an action whose countdown has not started this many seconds
+    /// after it was scheduled (None = wait as long as it takes).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_timeout_secs: Option<u64>,
+    /// Whether the session ends after the action or keeps monitoring.
+    #[serde(default)]
+    pub repeat_mode: RepeatMode,
+    /// After a user cancel, wait for the speed to go above the threshold
+    /// once before the condition can be met again.
+    #[serde(default)]
+    pub rearm_requires_activity: bool,
+    /// Optional actions run on start, stop, and cancel.
+    #[serde(default, flatten)]
+    pub hooks: LifecycleHooks,
+}
+
+impl MonitoringConfig {
+    /// Check every field, returning the first unusable one: a zero
+    /// threshold or duration, a countdown shorter than
+    /// [`MIN_COUNTDOWN_SECS`], an unknown monitor mode, a CPU threshold
+    /// above 100%, a process trigger watching nothing, an empty action
+    /// sequence, an invalid condition tree, a retry policy without
+    /// attempts, an interface failover after zero polls, or a pending
+    /// timeout that would end before the pre-warning.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.condition.validate(&self.trigger_type)?;
+        self.trigger_type.validate()?;
+        self.session_condition()?;
+        match &self.action_type {
+            ActionSelection::Single(id) if id.trim().is_empty() => {
+                return Err(ConfigError::new("action_type", "must not be empty"));
+            }
+            ActionSelection::Sequence(steps) if steps.is_empty() => {
+                return Err(ConfigError::new("action_type", "needs at least one step"));
+            }
+            _ => {}
+        }
+        if self.retry.max_attempts == 0 {
+            return Err(ConfigError::new("retry.max_attempts", "must be at least 1"));
+        }
+        if self.retry.max_attempts > MAX_RETRY_ATTEMPTS {
+            return Err(ConfigError::new(
+                "retry.max_attempts",
+                format!("must be at most {MAX_RETRY_ATTEMPTS}"),
+            ));
+        }
+        if self.interface_failover.after_polls == 0 {
+            return Err(ConfigError::new(
+                "interface_failover.after_polls",
+                "must be at least 1",
+            ));
+        }
+        if self.countdown_secs < MIN_COUNTDOWN_SECS {
+            return Err(ConfigError::new(
+                "countdown_secs",
+                format!("must be at least {MIN_COUNTDOWN_SECS} seconds"),
+            ));
+        }
+        if self
+            .pending_timeout_secs
+            .is_some_and(|secs| secs <= self.pre_warning_secs)
+        {
+            return Err(ConfigError::new(
+                "pending_timeout_secs",
+                "must be longer than pre_warning_secs",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Build the condition the session evaluates: the
+    /// [`condition_tree`](Self::condition_tree) if set, otherwise the
+    /// threshold described by [`condition`](Self::condition).
+    pub fn session_condition(&self) -> Result<Box<dyn Condition>, ConfigError> {
+        match &self.condition_tree {
+            Some(tree) => tree
+                .build()
+                .map_err(|e| ConfigError::new("condition_tree", e.to_string())),
+            None => Ok(Box::new(self.condition.threshold_condition())),
+        }
+    }
+}
+
+/// Trigger-specific configuration (Strategic Shift: NOT hardcoded params).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum TriggerConfig {
+    /// Network idle trigger.
+    #[serde(rename = "network_idle")]
+    NetworkIdle {
+        /// Network interface(s) to monitor: an id, "auto" for the
+        /// default, "all", or a list of ids whose speeds are summed.
+        interface_id: InterfaceChoice,
+    },
+    /// Process-based trigger.
+    #[serde(rename = "process_idle")]
+    ProcessIdle {
+        /// Process names to watch.
+        watched_processes: Vec<String>,
+        /// Process names to exclude.
+        excluded_processes: Vec<String>,
+        /// Further processes to watch, by name, path, or PID.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        watched_matches: Vec<ProcessMatch>,
+        /// Further processes to exclude, by name, path, or PID.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        excluded_matches: Vec<ProcessMatch>,
+        /// Activity threshold in bytes per second; also accepts a string
+        /// such as `"50 KB/s"`.
+        #[serde(deserialize_with = "deserialize_bytes_per_sec")]
+        threshold_bytes: u64,
+        /// Activity samples kept per watched process for sparklines.
+        #[serde(default = "default_history_samples")]
+        history_samples: usize,
+    },
+    /// CPU idle trigger; the condition threshold is read as a percentage.
+    #[serde(rename = "cpu_idle")]
+    CpuIdle {
+        /// Aggregate utilization or the busiest core.
+        #[serde(default)]
+        mode: CpuMode,
+        /// Milliseconds between CPU samples.
+        #[serde(default = "default_cpu_sample_interval_ms")]
+        sample_interval_ms: u64,
+    },
+}
+
+impl TriggerConfig {
+    /// The trigger registry type id, e.g. "network_idle".
+    pub fn registry_id(&self) -> &'static str {
+        match self {
+            Self::NetworkIdle { .. } => "network_idle",
+            Self::ProcessIdle { .. } => "process_idle",
+            Self::CpuIdle { .. } => "cpu_idle",
+        }
+    }
+
+    /// Human-readable trigger name for log entries.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::NetworkIdle { .. } => "Network idle",
+            Self::ProcessIdle { .. } => "Process idle",
+            Self::CpuIdle { .. } => "CPU idle",
+        }
+    }
+
+    /// Check the trigger's own settings.
+    fn validate(&self) -> Result<(), ConfigError> {
+        match self {
+            Self::NetworkIdle { .. } => Ok(()),
+            Self::ProcessIdle {
+                watched_processes,
+                watched_matches,
+                history_samples,
+                ..
+            } => {
+                if watched_processes.is_empty() && watched_matches.is_empty() {
+                    return Err(ConfigError::new(
+                        "trigger_type.watched_processes",
+                        "watch at least one process",
+                    ));
+                }
+                if *history_samples == 0 {
+                    return Err(ConfigError::new(
+                        "trigger_type.history_samples",
+                        "must be greater than 0",
+                    ));
+                }
+                Ok(())
+            }
+            Self::CpuIdle {
+                sample_interval_ms, ..
+            } => {
+                if *sample_interval_ms == 0 {
+                    return Err(ConfigError::new(
+                        "trigger_type.sample_interval_ms",
+                        "must be greater than 0",
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// A process trigger matching the watched and excluded processes, or
+    /// `None` for other trigger types. Names come before the further match
+    /// specs, as the process trigger combines them.
+    pub fn process_trigger(&self) -> Option<ProcessTrigger> {
+        match self {
+            Self::ProcessIdle {
+                watched_processes,
+                excluded_processes,
+                watched_matches,
+                excluded_matches,
+                threshold_bytes,
+                ..
+            } => Some(ProcessTrigger::with_matches(
+                names_then(watched_processes, watched_matches),
+                names_then(excluded_processes, excluded_matches),
+                *threshold_bytes,
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Name specs for `names` followed by `matches`.
+fn names_then(names: &[String], matches: &[ProcessMatch]) -> Vec<ProcessMatch> {
+    names
+        .iter()
+        .cloned()
+        .map(ProcessMatch::Name)
+        .chain(matches.iter().cloned())
+        .collect()
+}
+
+fn default_history_samples() -> usize {
+    flowwatcher_triggers::history::DEFAULT_HISTORY_CAPACITY
+}
+
+fn default_max_snooze_secs() -> u64 {
+    crate::scheduler::DEFAULT_MAX_SNOOZE_SECS
+}
+
+fn default_cpu_sample_interval_ms() -> u64 {
+    flowwatcher_triggers::cpu::DEFAULT_CPU_SAMPLE_INTERVAL.as_millis() as u64
+}
+
+/// Condition configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConditionConfig {
+    /// Speed threshold in bytes per second; also accepts a string such as
+    /// `"200 KB/s"` or `"500 kbps"` (see [`ByteRate`]).
+    #[serde(deserialize_with = "deserialize_bytes_per_sec")]
+    pub threshold_bytes_per_sec: u64,
+    /// Required duration in seconds.
+    pub required_duration_secs: u64,
+    /// Monitor mode: "download_only", "upload_only", "both", or "either".
+    pub monitor_mode: String,
+    /// Report an "approaching idle" zone up to this multiple of the
+    /// threshold (0 = disabled).
+    #[serde(default)]
+    pub warning_multiplier: f64,
+    /// Dead band above the threshold: once the timer runs it only resets
+    /// above `threshold + hysteresis` (None = no hysteresis).
+    #[serde(default)]
+    pub hysteresis_bytes_per_sec: Option<u64>,
+    /// Consecutive above-threshold samples tolerated without resetting
+    /// the timer (0 = none).
+    #[serde(default)]
+    pub max_spike_samples: u32,
+    /// Longest tolerated spike in seconds (None = limited by count only).
+    #[serde(default)]
+    pub max_spike_duration_secs: Option<u64>,
+}
+
+impl ConditionConfig {
+    /// Check the condition against the trigger it applies to; CPU
+    /// thresholds are percentages.
+    fn validate(&self, trigger: &TriggerConfig) -> Result<(), ConfigError> {
+        if self.threshold_bytes_per_sec == 0 {
+            return Err(ConfigError::new(
+                "condition.threshold_bytes_per_sec",
+                "must be greater than 0",
+            ));
+        }
+        if matches!(trigger, TriggerConfig::CpuIdle { .. }) && self.threshold_bytes_per_sec > 100 {
+            return Err(ConfigError::new(
+                "condition.threshold_bytes_per_sec",
+                "is a CPU percentage and must be at most 100",
+            ));
+        }
+        if self.required_duration_secs < 1 {
+            return Err(ConfigError::new(
+                "condition.required_duration_secs",
+                "must be at least 1 second",
+            ));
+        }
+        parse_monitor_mode(&self.monitor_mode)?;
+        if !self.warning_multiplier.is_finite() || self.warning_multiplier < 0.0 {
+            return Err(ConfigError::new(
+                "condition.warning_multiplier",
+                "must be 0 or a positive number",
+            ));
+        }
+        Ok(())
+    }
+
+    /// The parsed [`monitor_mode`](Self::monitor_mode). Validation rejects
+    /// unknown modes, so this only falls back to download only for a
+    /// config that skipped [`validate`](Self::validate).
+    pub fn mode(&self) -> MonitorMode {
+        parse_monitor_mode(&self.monitor_mode).unwrap_or(MonitorMode::DownloadOnly)
+    }
+
+    /// Build the threshold condition this config describes.
+    pub fn threshold_condition(&self) -> ThresholdCondition {
+        ThresholdCondition::new(
+            self.threshold_bytes_per_sec,
+            self.required_duration_secs,
+            self.mode(),
+        )
+        .with_warning_multiplier(self.warning_multiplier)
+        .with_hysteresis(self.hysteresis_bytes_per_sec.unwrap_or(0))
+        .with_max_spike_samples(self.max_spike_samples)
+        .with_max_spike_duration_secs(self.max_spike_duration_secs)
+    }
+}
+
+/// Parse a monitor mode string ("download_only", "upload_only", "both",
+/// "either").
+pub fn parse_monitor_mode(mode: &str) -> Result<MonitorMode, ConfigError> {
+    match mode {
+        "download_only" => Ok(MonitorMode::DownloadOnly),
+        "upload_only" => Ok(MonitorMode::UploadOnly),
+        "both" => Ok(MonitorMode::Both),
+        "either" => Ok(MonitorMode::Either),
+        _ => Err(ConfigError::new(
+            "condition.monitor_mode",
+            format!(
+                "unknown mode \"{mode}\" (expected one of {})",
+                MONITOR_MODES.join(", ")
+            ),
+        )),
+    }
+}
+
+/// The configured action: a single type id (the original format) or a
+/// sequence of steps.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ActionSelection {
+    /// One action type id, e.g. "shutdown".
+    Single(String),
+    /// Steps run in order, e.g. a backup script then hibernate.
+    Sequence(Vec<ActionSpec>),
+}
+
+impl std::fmt::Display for ActionSelection {
+    /// The type id, or the step ids joined with arrows.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Single(id) => f.write_str(id),
+            Self::Sequence(steps) => {
+                let ids: Vec<&str> = steps.iter().map(|s| s.action_type.as_str()).collect();
+                f.write_str(&ids.join(" → "))
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thresholds_accept_rate_strings() {
+        let config: MonitoringConfig = serde_json::from_value(serde_json::json!({
+            "trigger_type": {
+                "type": "process_idle",
+                "watched_processes": ["steam.exe"],
+                "excluded_processes": [],
+                "threshold_bytes": "50 KB/s",
+            },
+            "condition": {
+                "threshold_bytes_per_sec": "200KB/s",
+                "required_duration_secs": 60,
+                "monitor_mode": "both",
+            },
+            "action_type": "shutdown",
+            "pre_warning_secs": 30,
+            "countdown_secs": 10,
+        }))
+        .unwrap();
+        assert_eq!(config.condition.threshold_bytes_per_sec, 204_800);
+        assert!(matches!(
+            config.trigger_type,
+            TriggerConfig::ProcessIdle {
+                threshold_bytes: 51_200,
+                ..
+            }
+        ));
+        // Saved configs keep plain numbers.
+        let saved = serde_json::to_value(&config).unwrap();
+        assert_eq!(saved["condition"]["threshold_bytes_per_sec"], 204_800);
+
+        let bad = serde_json::from_value::<ConditionConfig>(serde_json::json!({
+            "threshold_bytes_per_sec": "200 KB/fortnight",
+            "required_duration_secs": 60,
+            "monitor_mode": "both",
+        }));
+        assert!(bad.is_err());
+    }
+
+    #[test]
+    fn minimal_config_takes_defaults() {
+        let config: MonitoringConfig = serde_json::from_value(serde_json::json!({
+            "trigger_type": { "type": "network_idle", "interface_id": "auto" },
+            "condition": {
+                "threshold_bytes_per_sec": 1024,
+                "required_duration_secs": 60,
+                "monitor_mode": "both",
+            },
+            "action_type": "shutdown",
+            "pre_warning_secs": 30,
+            "countdown_secs": 10,
+        }))
+        .unwrap();
+
+        assert_eq!(config.trigger_type.registry_id(), "network_idle");
+        assert_eq!(config.trigger_type.label(), "Network idle");
+        assert!(config.trigger_type.process_trigger().is_none());
+        assert_eq!(config.condition.mode(), MonitorMode::Both);
+        assert_eq!(
+            config.max_snooze_secs,
+            crate::scheduler::DEFAULT_MAX_SNOOZE_SECS
+        );
+        assert_eq!(config.action_type.to_string(), "shutdown");
+        assert!(!config.dry_run);
+        assert_eq!(config.repeat_mode, RepeatMode::OneShot);
+        assert!(!config.rearm_requires_activity);
+        assert!(!config.retry.retries());
+        assert_eq!(config.interface_failover, FailoverPolicy::default());
+    }
+
+    #[test]
+    fn configs_round_trip_through_json() {
+        let config: MonitoringConfig = serde_json::from_value(serde_json::json!({
+            "trigger_type": {
+                "type": "process_idle",
+                "watched_processes": ["steam.exe"],
+                "excluded_processes": [],
+                "threshold_bytes": 2048,
+            },
+            "condition": {
+                "threshold_bytes_per_sec": 1024,
+                "required_duration_secs": 60,
+                "monitor_mode": "download_only",
+                "hysteresis_bytes_per_sec": 256,
+            },
+            "action_type": [
+                { "action_type": "lock_screen" },
+                { "action_type": "sleep" },
+            ],
+            "pre_warning_secs": 30,
+            "countdown_secs": 10,
+            "dry_run": true,
+            "restart": true,
+        }))
+        .unwrap();
+        assert!(config.restart);
+
+        // The restart flag is for one start only and is not written back.
+        let json = serde_json::to_value(&config).unwrap();
+        assert!(json.get("restart").is_none());
+        let again: MonitoringConfig = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&again).unwrap(), json);
+        assert_eq!(again.action_type.to_string(), "lock_screen → sleep");
+        assert_eq!(again.condition.hysteresis_bytes_per_sec, Some(256));
+        assert!(again.validate().is_ok());
+    }
+
+    #[test]
+    fn validation_names_the_offending_field() {
+        let valid = serde_json::json!({
+            "trigger_type": { "type": "cpu_idle" },
+            "condition": {
+                "threshold_bytes_per_sec": 10,
+                "required_duration_secs": 60,
+                "monitor_mode": "both",
+            },
+            "action_type": "shutdown",
+            "pre_warning_secs": 0,
+            "countdown_secs": MIN_COUNTDOWN_SECS,
+        });
+        let field = |path: &[&str], value: serde_json::Value| {
+            let mut json = valid.clone();
+            let (last, parents) = path.split_last().unwrap();
+            let mut target = &mut json;
+            for key in parents {
+                target = &mut target[*key];
+            }
+            target[*last] = value;
+            let config: MonitoringConfig = serde_json::from_value(json).unwrap();
+            config.validate().err().map(|e| e.field)
+        };
+
+        assert_eq!(field(&["pre_warning_secs"], 0.into()), None);
+        let cases = [
+            (&["condition", "threshold_bytes_per_sec"][..], 0.into()),
+            (&["condition", "threshold_bytes_per_sec"], 101.into()),
+            (&["condition", "required_duration_secs"], 0.into()),
+            (&["condition", "monitor_mode"], "sideways".into()),
+            (&["countdown_secs"], (MIN_COUNTDOWN_SECS - 1).into()),
+            (&["action_type"], serde_json::json!([])),
+            (&["trigger_type", "sample_interval_ms"], 0.into()),
+            (&["pending_timeout_secs"], 0.into()),
+            (&["retry", "max_attempts"], 0.into()),
+            (&["retry", "max_attempts"], (MAX_RETRY_ATTEMPTS + 1).into()),
+            (&["interface_failover", "after_polls"], 0.into()),
+        ];
+        for (path, value) in cases {
+            assert_eq!(
+                field(path, value).as_deref(),
+                Some(path.join(".").as_str()),
+                "{path:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn the_condition_tree_replaces_the_threshold() {
+        let mut config: MonitoringConfig = serde_json::from_value(serde_json::json!({
+            "trigger_type": { "type": "network_idle", "interface_id": "auto" },
+            "condition": {
+                "threshold_bytes_per_sec": 1024,
+                "required_duration_secs": 60,
+                "monitor_mode": "download_only",
+            },
+            "action_type": "shutdown",
+            "pre_warning_secs": 30,
+            "countdown_secs": 10,
+        }))
+        .unwrap();
+        let mut data = flowwatcher_triggers::TriggerData::new();
+        data.insert(
+            "download_bps",
+            flowwatcher_triggers::TriggerValue::U64(2048),
+        );
+        data.insert("upload_bps", flowwatcher_triggers::TriggerValue::U64(0));
+
+        let mut threshold = config.session_condition().unwrap();
+        assert_eq!(
+            threshold.evaluate(&data).unwrap(),
+            flowwatcher_conditions::ConditionResult::Waiting
+        );
+
+        config.condition_tree = Some(
+            serde_json::from_value(serde_json::json!({
+                "type": "composite", "operator": "any", "conditions": [
+                    { "type": "threshold", "threshold_bytes_per_sec": 4096,
+                      "required_duration_secs": 0, "monitor_mode": "download_only" },
+                ],
+            }))
+            .unwrap(),
+        );
+        let mut tree = config.session_condition().unwrap();
+        assert_eq!(
+            tree.evaluate(&data).unwrap(),
+            flowwatcher_conditions::ConditionResult::Met
+        );
+
+        config.condition_tree = Some(
+            serde_json::from_value(serde_json::json!({
+                "type": "composite", "operator": "all", "conditions": [],
+            }))
+            .unwrap(),
+        );
+        assert_eq!(config.validate().unwrap_err().field, "condition_tree");
+    }
+
+    #[test]
+    fn unknown_monitor_modes_are_rejected() {
+        assert_eq!(
+            parse_monitor_mode("upload_only"),
+            Ok(MonitorMode::UploadOnly)
+        );
+        assert_eq!(parse_monitor_mode("either"), Ok(MonitorMode::Either));
+        for mode in MONITOR_MODES {
+            assert!(parse_monitor_mode(mode).is_ok(), "{mode}");
+        }
+        let err = parse_monitor_mode("sideways").unwrap_err();
+        assert_eq!(err.field, "condition.monitor_mode");
+        assert!(err.to_string().contains("sideways"), "{err}");
+    }
+}
@@ -0,0 +1,314 @@
+//! Time-remaining estimation for active transfers.
+//!
+//! Fits a least-squares line through recent speed samples. When speeds are
+//! declining steadily, the line is extended to the configured threshold to
+//! estimate when the connection will go idle. Flat, rising, or erratic
+//! series produce no estimate. The math is pure so it can be tested on
+//! synthetic series; [`IdleEstimator`] only buffers timestamped samples.
+
+use crate::calibration::{Confidence, HIGH_CONFIDENCE_SAMPLES};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Fewer samples than this never produce an estimate.
+pub const MIN_TREND_SAMPLES: usize = 10;
+
+/// Fits explaining less of the variance than this are treated as noise.
+pub const MIN_R_SQUARED: f64 = 0.5;
+
+/// Estimates further out than this are not reported.
+pub const MAX_HORIZON: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Default number of samples kept by [`IdleEstimator`].
+pub const DEFAULT_WINDOW: usize = 60;
+
+// ---------------------------------------------------------------------------
+// Trend
+// ---------------------------------------------------------------------------
+
+/// A least-squares line through `(seconds, bytes per second)` points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trend {
+    /// Change in speed per second (negative = declining).
+    pub slope: f64,
+    /// Fitted speed at `t = 0`.
+    pub intercept: f64,
+    /// Share of the variance explained by the line (0.0–1.0).
+    pub r_squared: f64,
+}
+
+impl Trend {
+    /// The fitted speed at `t` seconds.
+    pub fn value_at(&self, t: f64) -> f64 {
+        self.intercept + self.slope * t
+    }
+}
+
+/// Fit a line through `points`.
+///
+/// Returns `None` for fewer than two points or when all points share the
+/// same time. A perfectly flat series has an `r_squared` of 0.
+pub fn linear_trend(points: &[(f64, f64)]) -> Option<Trend> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_t = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_v = points.iter().map(|p| p.1).sum::<f64>() / n;
+
+    let (mut stt, mut stv, mut svv) = (0.0, 0.0, 0.0);
+    for &(t, v) in points {
+        let (dt, dv) = (t - mean_t, v - mean_v);
+        stt += dt * dt;
+        stv += dt * dv;
+        svv += dv * dv;
+    }
+    if stt == 0.0 {
+        return None;
+    }
+
+    let slope = stv / stt;
+    let r_squared = if svv == 0.0 {
+        0.0
+    } else {
+        (stv * stv) / (stt * svv)
+    };
+    Some(Trend {
+        slope,
+        intercept: mean_v - slope * mean_t,
+        r_squared,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Estimate
+// ---------------------------------------------------------------------------
+
+/// Estimated time until the speed drops below the threshold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IdleEstimate {
+    /// Time from the latest sample until the threshold is crossed.
+    pub remaining: Duration,
+    /// How well the trend fits the samples.
+    pub confidence: Confidence,
+}
+
+impl IdleEstimate {
+    /// Wall-clock time of the crossing, given the latest sample was taken
+    /// at `now`.
+    pub fn idle_at(&self, now: SystemTime) -> SystemTime {
+        now + self.remaining
+    }
+}
+
+/// Rate confidence from sample count and goodness of fit.
+fn trend_confidence(sample_count: usize, r_squared: f64) -> Confidence {
+    if r_squared >= 0.9 && sample_count >= HIGH_CONFIDENCE_SAMPLES {
+        Confidence::High
+    } else if r_squared >= 0.75 {
+        Confidence::Medium
+    } else {
+        Confidence::Low
+    }
+}
+
+/// Estimate when `(seconds, bytes per second)` samples will fall to
+/// `threshold_bps`.
+///
+/// Returns `None` when there are too few samples, the trend is flat or
+/// rising, the fit is too noisy, or the crossing lies beyond
+/// [`MAX_HORIZON`]. If the fitted speed is already at or below the
+/// threshold, the estimate is zero.
+pub fn estimate_idle(points: &[(f64, u64)], threshold_bps: u64) -> Option<IdleEstimate> {
+    if points.len() < MIN_TREND_SAMPLES {
+        return None;
+    }
+    let series: Vec<(f64, f64)> = points.iter().map(|&(t, v)| (t, v as f64)).collect();
+    let trend = linear_trend(&series)?;
+    if trend.slope >= 0.0 || trend.r_squared < MIN_R_SQUARED {
+        return None;
+    }
+
+    let last_t = series.last()?.0;
+    let current = trend.value_at(last_t);
+    let secs = ((threshold_bps as f64 - current) / trend.slope).max(0.0);
+    if secs > MAX_HORIZON.as_secs_f64() {
+        return None;
+    }
+
+    Some(IdleEstimate {
+        remaining: Duration::from_secs_f64(secs),
+        confidence: trend_confidence(points.len(), trend.r_squared),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// IdleEstimator
+// ---------------------------------------------------------------------------
+
+/// Rolling window of timestamped speed samples for [`estimate_idle`].
+#[derive(Debug, Clone)]
+pub struct IdleEstimator {
+    /// Recent samples, oldest first.
+    samples: VecDeque<(Instant, u64)>,
+    /// Maximum number of samples kept.
+    window: usize,
+}
+
+impl Default for IdleEstimator {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW)
+    }
+}
+
+impl IdleEstimator {
+    /// Create an estimator keeping the latest `window` samples.
+    pub fn new(window: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(window),
+            window: window.max(2),
+        }
+    }
+
+    /// Add a speed sample taken at `at`.
+    pub fn push(&mut self, at: Instant, bps: u64) {
+        if self.samples.len() >= self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((at, bps));
+    }
+
+    /// Estimate the time until the speed falls below `threshold_bps`.
+    pub fn estimate(&self, threshold_bps: u64) -> Option<IdleEstimate> {
+        let origin = self.samples.front()?.0;
+        let points: Vec<(f64, u64)> = self
+            .samples
+            .iter()
+            .map(|&(at, bps)| (at.duration_since(origin).as_secs_f64(), bps))
+            .collect();
+        estimate_idle(&points, threshold_bps)
+    }
+
+    /// Maximum number of samples kept.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Number of buffered samples.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Whether no samples are buffered.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Drop all samples.
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One sample per second.
+    fn series(values: impl IntoIterator<Item = u64>) -> Vec<(f64, u64)> {
+        values
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| (i as f64, v))
+            .collect()
+    }
+
+    #[test]
+    fn trend_fits_a_line() {
+        let points: Vec<(f64, f64)> = (0..5).map(|i| (i as f64, 10.0 - 2.0 * i as f64)).collect();
+        let trend = linear_trend(&points).unwrap();
+        assert!((trend.slope + 2.0).abs() < 1e-9);
+        assert!((trend.intercept - 10.0).abs() < 1e-9);
+        assert!((trend.r_squared - 1.0).abs() < 1e-9);
+        assert!(linear_trend(&[(1.0, 5.0)]).is_none());
+        assert!(linear_trend(&[(1.0, 5.0), (1.0, 7.0)]).is_none());
+    }
+
+    #[test]
+    fn steady_decline_estimates_crossing() {
+        // 1 MB/s falling by 10 KB/s each second for 40 s.
+        let points = series((0..40).map(|i| 1_000_000 - i * 10_000));
+        let estimate = estimate_idle(&points, 100_000).unwrap();
+
+        // Last sample is 610 KB/s; 510 KB to go at 10 KB/s.
+        assert_eq!(estimate.remaining.as_secs(), 51);
+        assert_eq!(estimate.confidence, Confidence::High);
+    }
+
+    #[test]
+    fn noisy_decline_has_lower_confidence() {
+        let points = series((0..20).map(|i| {
+            let jitter = if i % 2 == 0 { 60_000 } else { 0 };
+            1_000_000 - i * 20_000 + jitter
+        }));
+        let estimate = estimate_idle(&points, 100_000).unwrap();
+        assert_eq!(estimate.confidence, Confidence::Medium);
+        assert!(estimate.remaining > Duration::ZERO);
+    }
+
+    #[test]
+    fn flat_and_rising_series_have_no_estimate() {
+        assert!(estimate_idle(&series([500_000; 30]), 100_000).is_none());
+        assert!(estimate_idle(&series((0..30).map(|i| 100_000 + i * 5_000)), 50_000).is_none());
+    }
+
+    #[test]
+    fn bursty_series_has_no_estimate() {
+        let points = series((0..40).map(|i| if i % 7 == 0 { 2_000_000 } else { 50_000 }));
+        assert!(estimate_idle(&points, 100_000).is_none());
+    }
+
+    #[test]
+    fn short_or_distant_series_have_no_estimate() {
+        assert!(estimate_idle(&series([900, 800, 700]), 100).is_none());
+        // Declining by 1 B/s per second from 10 MB/s: ~115 days.
+        let slow = series((0..30).map(|i| 10_000_000 - i));
+        assert!(estimate_idle(&slow, 0).is_none());
+    }
+
+    #[test]
+    fn below_threshold_is_zero() {
+        let points = series((0..20).map(|i| 50_000 - i * 1_000));
+        let estimate = estimate_idle(&points, 100_000).unwrap();
+        assert_eq!(estimate.remaining, Duration::ZERO);
+    }
+
+    #[test]
+    fn estimator_keeps_a_rolling_window() {
+        let start = Instant::now();
+        let mut estimator = IdleEstimator::new(15);
+        for i in 0..30u64 {
+            // Rising first, then declining: only the decline stays in the window.
+            let bps = if i < 15 {
+                i * 10_000
+            } else {
+                300_000 - i * 10_000
+            };
+            estimator.push(start + Duration::from_secs(i), bps);
+        }
+        assert_eq!(estimator.len(), 15);
+
+        let estimate = estimator.estimate(0).unwrap();
+        assert_eq!(estimate.remaining.as_secs(), 1);
+        let now = SystemTime::UNIX_EPOCH;
+        assert_eq!(estimate.idle_at(now), now + estimate.remaining);
+
+        estimator.reset();
+        assert!(estimator.is_empty());
+        assert!(estimator.estimate(0).is_none());
+    }
+}
@@ -0,0 +1,215 @@
+//! Lifecycle hooks — actions fired when monitoring starts, stops, or a
+//! pending action is cancelled.
+//!
+//! Hooks are configured as an action id plus params and resolved through
+//! an [`ActionRegistry`]. They are best-effort: a failing hook is logged
+//! and never interrupts the monitoring flow. Every run produces a
+//! [`LogStatus::Info`] entry tagged with the hook name.
+
+use crate::logger::{LogEntry, LogStatus};
+use flowwatcher_actions::{Action, ActionError, ActionRegistry};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+/// One hook: which action to run and with what params.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HookConfig {
+    /// Registered action type id (e.g. a webhook plugin).
+    pub action_id: String,
+    /// Params passed to the action factory.
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
+/// Optional hooks for each lifecycle transition.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifecycleHooks {
+    /// Runs after monitoring starts.
+    pub on_start_action: Option<HookConfig>,
+    /// Runs after monitoring stops.
+    pub on_stop_action: Option<HookConfig>,
+    /// Runs after a pending action is cancelled.
+    pub on_cancel_action: Option<HookConfig>,
+}
+
+/// A lifecycle transition that can fire a hook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    /// Monitoring started.
+    Start,
+    /// Monitoring stopped.
+    Stop,
+    /// A pending action was cancelled.
+    Cancel,
+}
+
+impl LifecycleEvent {
+    /// The hook name used in log entries.
+    pub fn hook_name(self) -> &'static str {
+        match self {
+            Self::Start => "on_start",
+            Self::Stop => "on_stop",
+            Self::Cancel => "on_cancel",
+        }
+    }
+}
+
+impl LifecycleHooks {
+    /// The hook configured for `event`, if any.
+    pub fn get(&self, event: LifecycleEvent) -> Option<&HookConfig> {
+        match event {
+            LifecycleEvent::Start => self.on_start_action.as_ref(),
+            LifecycleEvent::Stop => self.on_stop_action.as_ref(),
+            LifecycleEvent::Cancel => self.on_cancel_action.as_ref(),
+        }
+    }
+
+    /// Build the configured actions through `registry`.
+    ///
+    /// Fails if any hook names an unknown action or has invalid params, so
+    /// configuration mistakes surface when monitoring starts.
+    pub fn resolve(&self, registry: &ActionRegistry) -> Result<HookSet, ActionError> {
+        let build = |hook: Option<&HookConfig>| {
+            hook.map(|h| registry.create(&h.action_id, &h.params))
+                .transpose()
+        };
+        Ok(HookSet {
+            on_start: build(self.on_start_action.as_ref())?,
+            on_stop: build(self.on_stop_action.as_ref())?,
+            on_cancel: build(self.on_cancel_action.as_ref())?,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Resolved hooks
+// ---------------------------------------------------------------------------
+
+/// Hook actions ready to run.
+#[derive(Default)]
+pub struct HookSet {
+    /// Runs after monitoring starts.
+    pub on_start: Option<Box<dyn Action>>,
+    /// Runs after monitoring stops.
+    pub on_stop: Option<Box<dyn Action>>,
+    /// Runs after a pending action is cancelled.
+    pub on_cancel: Option<Box<dyn Action>>,
+}
+
+impl HookSet {
+    /// An empty set (no hooks).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The action for `event`, if any.
+    pub fn get(&self, event: LifecycleEvent) -> Option<&dyn Action> {
+        match event {
+            LifecycleEvent::Start => self.on_start.as_deref(),
+            LifecycleEvent::Stop => self.on_stop.as_deref(),
+            LifecycleEvent::Cancel => self.on_cancel.as_deref(),
+        }
+    }
+
+    /// Run the hook for `event`, if configured, and return its log entry.
+    pub async fn fire(&self, event: LifecycleEvent) -> Option<LogEntry> {
+        let action = self.get(event)?;
+        Some(run_hook(event, action).await)
+    }
+}
+
+/// Execute one hook action. Failures are logged, never returned.
+pub async fn run_hook(event: LifecycleEvent, action: &dyn Action) -> LogEntry {
+    let started = Instant::now();
+    let details = match action.execute_with_details().await {
+        Ok(None) => "completed".to_string(),
+        Ok(Some(details)) => format!("completed: {details}"),
+        Err(e) => {
+            tracing::warn!("{} hook `{}` failed: {e}", event.hook_name(), action.name());
+            format!("failed: {e}")
+        }
+    };
+    LogEntry::now(
+        format!("Lifecycle hook: {}", event.hook_name()),
+        action.name(),
+        LogStatus::Info,
+        Some(details),
+    )
+    .with_duration(started.elapsed())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_testing::MockAction;
+
+    fn hook(action_id: &str) -> Option<HookConfig> {
+        Some(HookConfig {
+            action_id: action_id.to_string(),
+            params: serde_json::Value::Null,
+        })
+    }
+
+    #[test]
+    fn deserializes_with_missing_hooks() {
+        let hooks: LifecycleHooks =
+            serde_json::from_str(r#"{"on_start_action":{"action_id":"webhook"}}"#).unwrap();
+        assert_eq!(hooks.on_start_action, hook("webhook"));
+        assert!(hooks.on_stop_action.is_none());
+        assert!(hooks.get(LifecycleEvent::Cancel).is_none());
+    }
+
+    #[test]
+    fn resolve_rejects_unknown_actions() {
+        let mut registry = ActionRegistry::new();
+        registry.register("mock", |_| Ok(Box::new(MockAction::new())));
+
+        let hooks = LifecycleHooks {
+            on_stop_action: hook("mock"),
+            ..Default::default()
+        };
+        let set = hooks.resolve(&registry).unwrap();
+        assert!(set.on_start.is_none());
+        assert!(set.on_stop.is_some());
+
+        let bad = LifecycleHooks {
+            on_cancel_action: hook("teleport"),
+            ..Default::default()
+        };
+        assert!(matches!(
+            bad.resolve(&registry),
+            Err(ActionError::UnknownType(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn fire_logs_success_and_failure_as_info() {
+        let ok = MockAction::new();
+        let set = HookSet {
+            on_start: Some(Box::new(ok.clone())),
+            on_stop: Some(Box::new(MockAction::failing())),
+            on_cancel: None,
+        };
+
+        let started = set.fire(LifecycleEvent::Start).await.unwrap();
+        assert_eq!(started.status, LogStatus::Info);
+        assert_eq!(started.trigger_reason, "Lifecycle hook: on_start");
+        assert_eq!(started.details.as_deref(), Some("completed"));
+        assert_eq!(ok.executions(), 1);
+
+        let stopped = set.fire(LifecycleEvent::Stop).await.unwrap();
+        assert_eq!(stopped.status, LogStatus::Info);
+        assert!(stopped.details.unwrap().starts_with("failed:"));
+
+        assert!(set.fire(LifecycleEvent::Cancel).await.is_none());
+    }
+}
@@ -2,12 +2,61 @@
 //!
 //! Contains the [`SpeedMonitor`] for network speed calculations and the
 //! [`ActionScheduler`] for safely scheduling and executing actions with
-//! countdown, pre-warning, and cancellation support.
+//! countdown, pre-warning, and cancellation support. The
+//! [`MonitoringEngine`] ties a trigger, condition, and action together
+//! into a single steppable pipeline, the [`IdleEstimator`] predicts when a
+//! declining transfer will go idle, a [`PowerGuard`] can hold the action
+//! back on battery power, a [`UserIdleGuard`] postpones the countdown while
+//! someone is typing, and sessions can be captured with a
+//! [`SessionRecorder`] and replayed offline with a [`SessionReplayer`].
+//! A session is described by a [`MonitoringConfig`], shared by the desktop
+//! app and the command-line runner. Thresholds can be written as a
+//! [`ByteRate`] such as `"200 KB/s"`, and a [`RearmGate`] lets a session
+//! keep monitoring after its action per the [`RepeatMode`]. Files are
+//! persisted crash-safely through the [`persist`] helpers.
 
+pub mod calibration;
+pub mod clock;
+pub mod config;
+pub mod estimate;
+pub mod hooks;
 pub mod logger;
+pub mod monitor;
+pub mod persist;
+pub mod power;
+pub mod rate;
+pub mod rearm;
+pub mod recording;
 pub mod scheduler;
-pub mod speed;
+pub mod stats;
+pub mod user_idle;
+/// Speed monitoring, shared with [`flowwatcher_triggers::NetworkIdleTrigger`].
+pub use flowwatcher_triggers::speed;
 
-pub use logger::{ActivityLogger, LogEntry, LogStatus};
-pub use scheduler::ActionScheduler;
-pub use speed::SpeedMonitor;
+pub use calibration::{calibrate, CalibrationParams, CalibrationResult, Confidence, LinkSpeed};
+pub use clock::{Clock, SystemClock, VirtualClock};
+pub use config::{
+    parse_monitor_mode, ActionSelection, ConditionConfig, ConfigError, MonitoringConfig,
+    TriggerConfig, MAX_RETRY_ATTEMPTS, MIN_COUNTDOWN_SECS,
+};
+pub use estimate::{estimate_idle, IdleEstimate, IdleEstimator};
+pub use hooks::{HookConfig, HookSet, LifecycleEvent, LifecycleHooks};
+pub use logger::{
+    ActivityLogger, LogEntry, LogFilter, LogPage, LogSnapshot, LogStatus, SessionId,
+    SessionOutcome, SessionRecord,
+};
+pub use monitor::{auto_cancel_reason, EngineError, MonitoringEngine, StepOutcome, MAX_HOOK_LOG};
+pub use persist::Recovery;
+pub use power::{PowerGuard, PowerRules};
+pub use rate::{ByteRate, ByteRateError};
+pub use rearm::{Rearm, RearmGate, RepeatMode};
+pub use recording::{
+    RecordedSample, RecordingError, ReplayReport, SessionHeader, SessionRecorder, SessionReplayer,
+};
+pub use scheduler::{
+    AckPolicy, AckState, AckTimeout, ActionScheduler, CancelReason, PendingRun, Transition,
+    TransitionTrigger,
+};
+pub use speed::{InterfaceSelection, SmoothingStrategy, SpeedMonitor, TimestampedReading};
+pub use stats::{ActivityStats, SessionStats, StatsRange, StatusCounts};
+pub use user_idle::UserIdleGuard;
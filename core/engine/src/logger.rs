@@ -2,8 +2,31 @@
 //!
 //! Provides an in-memory log of monitoring sessions, trigger events,
 //! and action executions with methods to query, clear, and export.
+//!
+//! A monitoring session is opened with [`ActivityLogger::start_session`]
+//! and closed with [`ActivityLogger::end_session`]; entries added while it
+//! is open are tagged with its [`SessionId`], and
+//! [`ActivityLogger::get_sessions`] reports each session's span and
+//! outcome. Log files written before sessions existed (a plain array of
+//! entries) still load, as session-less entries.
+//!
+//! Besides whole-file JSON ([`ActivityLogger::save_to_file`]), the log can
+//! be kept as NDJSON, one record per line: [`ActivityLogger::append_entry`]
+//! and [`ActivityLogger::append_session`] append and fsync a single line,
+//! and [`ActivityLogger::compact_file`] atomically rewrites the file with
+//! what is kept in memory. [`ActivityLogger::load_from_file`] reads every
+//! format and skips a torn last line.
 
-use serde::{Deserialize, Serialize};
+use crate::persist;
+use crate::scheduler::CancelReason;
+use crate::stats::{ActivityStats, StatsRange};
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
 // ---------------------------------------------------------------------------
 // Log entry
@@ -26,7 +49,9 @@ pub enum LogStatus {
 /// A single activity log entry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
-    /// ISO-8601 timestamp string.
+    /// RFC 3339 timestamp in local time with its offset (e.g.
+    /// `2025-01-31T12:00:00+02:00`). Entries written by older versions
+    /// used `YYYY-MM-DD HH:MM:SS` in UTC and are converted on load.
     pub timestamp: String,
     /// What triggered this event (e.g. "Network idle", "Process idle").
     pub trigger_reason: String,
@@ -36,6 +61,185 @@ pub struct LogEntry {
     pub status: LogStatus,
     /// Optional details or error message.
     pub details: Option<String>,
+    /// Why the action was cancelled, for [`LogStatus::Cancelled`] entries
+    /// and power guard skips.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancel_reason: Option<CancelReason>,
+    /// The monitoring session this entry belongs to, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<SessionId>,
+    /// How long the action ran, for entries recording an execution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Structured context, e.g. the trigger data when the condition was
+    /// met. Older entries have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+// ---------------------------------------------------------------------------
+// Sessions
+// ---------------------------------------------------------------------------
+
+/// Identifies a monitoring session in the activity log.
+pub type SessionId = u64;
+
+/// How a monitoring session ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionOutcome {
+    /// The action ran (or, in a dry run, would have).
+    Executed,
+    /// Monitoring was stopped before the action ran.
+    Stopped,
+    /// The app exited while the session was still running.
+    Interrupted,
+}
+
+/// One monitoring session, from start to stop or execution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    /// Session id, also found on the session's entries.
+    pub id: SessionId,
+    /// Start time, in the format of [`LogEntry::timestamp`].
+    pub started_at: String,
+    /// Start time in Unix seconds.
+    pub started_unix_secs: u64,
+    /// End time, once the session has ended normally.
+    pub ended_at: Option<String>,
+    /// How long the session ran, once it has ended normally.
+    pub duration_secs: Option<u64>,
+    /// What was monitored (e.g. "Network idle").
+    pub trigger_reason: String,
+    /// The action that was armed (e.g. "Shutdown").
+    pub action_name: String,
+    /// How the session ended, or `None` while it runs.
+    pub outcome: Option<SessionOutcome>,
+    /// Details recorded at the end (e.g. the final speed).
+    pub details: Option<String>,
+}
+
+/// Maximum number of sessions kept in memory.
+pub const MAX_SESSIONS: usize = 200;
+
+/// On-disk log format.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum LogFile {
+    /// Entries plus session records.
+    Sessions {
+        entries: Vec<LogEntry>,
+        #[serde(default)]
+        sessions: Vec<SessionRecord>,
+    },
+    /// A plain array of entries, as written before sessions existed.
+    Entries(Vec<LogEntry>),
+}
+
+/// One line of an NDJSON log file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LogLine {
+    /// The latest state of a session; later lines for the same id win.
+    Session { session: SessionRecord },
+    /// A log entry.
+    Entry(LogEntry),
+}
+
+/// How a session is written as an NDJSON line.
+#[derive(Serialize)]
+struct SessionLine<'a> {
+    session: &'a SessionRecord,
+}
+
+/// An NDJSON log file with more lines than this is compacted on the next
+/// append.
+const COMPACT_AFTER_LINES: usize = 2 * MAX_ENTRIES;
+
+// ---------------------------------------------------------------------------
+// Filtering
+// ---------------------------------------------------------------------------
+
+/// How [`ActivityLogger::get_filtered_advanced`] narrows and pages the
+/// log. The default matches every entry.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogFilter {
+    /// Statuses to keep (empty for all).
+    pub statuses: Vec<LogStatus>,
+    /// Earliest timestamp to keep, as an ISO date or date-time prefix in
+    /// local time (e.g. `2025-01-01`), inclusive.
+    pub from: Option<String>,
+    /// Latest timestamp to keep, as an ISO date or date-time prefix,
+    /// inclusive: `2025-01-31` keeps the whole day.
+    pub to: Option<String>,
+    /// Case-insensitive text the trigger, action or details must contain.
+    pub query: Option<String>,
+    /// Entries to skip.
+    pub offset: usize,
+    /// Maximum number of entries to return (`None` for all).
+    pub limit: Option<usize>,
+}
+
+impl LogFilter {
+    /// Whether `entry` passes the status, date and text filters.
+    fn matches(&self, entry: &LogEntry, query: Option<&str>) -> bool {
+        if !self.statuses.is_empty() && !self.statuses.contains(&entry.status) {
+            return false;
+        }
+        within_dates(&entry.timestamp, self.from.as_deref(), self.to.as_deref())
+            && query.is_none_or(|q| entry_contains(entry, q))
+    }
+}
+
+/// Whether `timestamp` lies between the inclusive ISO date or date-time
+/// prefixes `from` and `to` (either may be absent).
+pub(crate) fn within_dates(timestamp: &str, from: Option<&str>, to: Option<&str>) -> bool {
+    // Timestamps are zero-padded local times, so string order is time
+    // order.
+    let timestamp = iso_prefix(timestamp);
+    let timestamp = timestamp.as_str();
+    if let Some(from) = from {
+        if timestamp < iso_prefix(from).as_str() {
+            return false;
+        }
+    }
+    if let Some(to) = to {
+        let to = iso_prefix(to);
+        let prefix = timestamp.get(..to.len()).unwrap_or(timestamp);
+        if prefix > to.as_str() {
+            return false;
+        }
+    }
+    true
+}
+
+/// One page of entries from [`ActivityLogger::get_filtered_advanced`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LogPage {
+    /// Entries on this page, newest first.
+    pub entries: Vec<LogEntry>,
+    /// Number of matching entries across all pages.
+    pub total: usize,
+}
+
+/// An ISO date-time with a space instead of `T`, so bounds and
+/// timestamps written either way compare as strings.
+fn iso_prefix(time: &str) -> String {
+    time.trim().replacen('T', " ", 1)
+}
+
+/// Whether the trigger, action or details contain `query`, which must be
+/// lowercase.
+fn entry_contains(entry: &LogEntry, query: &str) -> bool {
+    entry.trigger_reason.to_lowercase().contains(query)
+        || entry.action_name.to_lowercase().contains(query)
+        || entry
+            .details
+            .as_deref()
+            .unwrap_or("")
+            .to_lowercase()
+            .contains(query)
 }
 
 // ---------------------------------------------------------------------------
@@ -43,28 +247,127 @@ pub struct LogEntry {
 // ---------------------------------------------------------------------------
 
 /// Maximum number of log entries kept in memory.
-const MAX_ENTRIES: usize = 1000;
+pub const MAX_ENTRIES: usize = 1000;
+
+/// Immutable, cheaply cloneable view of the log at one point in time.
+///
+/// Serializes as a plain array of entries.
+#[derive(Debug, Clone)]
+pub struct LogSnapshot(Arc<[LogEntry]>);
+
+impl std::ops::Deref for LogSnapshot {
+    type Target = [LogEntry];
+
+    fn deref(&self) -> &[LogEntry] {
+        &self.0
+    }
+}
+
+impl Serialize for LogSnapshot {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
 
 /// In-memory activity logger with FIFO eviction.
 #[derive(Debug, Default)]
 pub struct ActivityLogger {
     entries: Vec<LogEntry>,
+    /// Shared copy of `entries`, built on demand and dropped on change.
+    snapshot: OnceLock<LogSnapshot>,
+    /// Monitoring sessions, oldest first.
+    sessions: Vec<SessionRecord>,
+    /// The open session new entries are tagged with.
+    current_session: Option<SessionId>,
+    /// Lines in the NDJSON log file, or `None` if the file is not known to
+    /// be NDJSON (the next append compacts it).
+    file_lines: Option<usize>,
 }
 
 impl ActivityLogger {
     /// Create a new empty logger.
     pub fn new() -> Self {
-        Self {
-            entries: Vec::new(),
-        }
+        Self::default()
     }
 
     /// Add a log entry. Evicts oldest entries if over capacity.
-    pub fn add_entry(&mut self, entry: LogEntry) {
+    ///
+    /// An entry without a session id is tagged with the open session.
+    pub fn add_entry(&mut self, mut entry: LogEntry) {
+        if entry.session_id.is_none() {
+            entry.session_id = self.current_session;
+        }
         if self.entries.len() >= MAX_ENTRIES {
             self.entries.remove(0);
         }
         self.entries.push(entry);
+        self.snapshot.take();
+    }
+
+    /// Open a monitoring session and return its id. A session that is
+    /// still open ends as [`SessionOutcome::Stopped`].
+    pub fn start_session(
+        &mut self,
+        trigger_reason: impl Into<String>,
+        action_name: impl Into<String>,
+    ) -> SessionId {
+        if let Some(open) = self.current_session {
+            self.end_session(open, SessionOutcome::Stopped, None);
+        }
+        let id = self.sessions.last().map_or(1, |s| s.id + 1);
+        let started = Local::now();
+        if self.sessions.len() >= MAX_SESSIONS {
+            self.sessions.remove(0);
+        }
+        self.sessions.push(SessionRecord {
+            id,
+            started_at: format_timestamp(started),
+            started_unix_secs: unix_secs(started),
+            ended_at: None,
+            duration_secs: None,
+            trigger_reason: trigger_reason.into(),
+            action_name: action_name.into(),
+            outcome: None,
+            details: None,
+        });
+        self.current_session = Some(id);
+        id
+    }
+
+    /// Close session `id` with `outcome`. Returns `false` if it is unknown
+    /// or already ended.
+    pub fn end_session(
+        &mut self,
+        id: SessionId,
+        outcome: SessionOutcome,
+        details: Option<String>,
+    ) -> bool {
+        let Some(session) = self
+            .sessions
+            .iter_mut()
+            .find(|s| s.id == id && s.outcome.is_none())
+        else {
+            return false;
+        };
+        let ended = Local::now();
+        session.ended_at = Some(format_timestamp(ended));
+        session.duration_secs = Some(unix_secs(ended).saturating_sub(session.started_unix_secs));
+        session.outcome = Some(outcome);
+        session.details = details;
+        if self.current_session == Some(id) {
+            self.current_session = None;
+        }
+        true
+    }
+
+    /// The open session, if any.
+    pub fn current_session(&self) -> Option<SessionId> {
+        self.current_session
+    }
+
+    /// All sessions (newest last).
+    pub fn get_sessions(&self) -> &[SessionRecord] {
+        &self.sessions
     }
 
     /// Get all log entries (newest last).
@@ -72,23 +375,55 @@ impl ActivityLogger {
         &self.entries
     }
 
+    /// All entries as a shared snapshot (newest last).
+    ///
+    /// Repeated calls between changes share one copy instead of cloning
+    /// the log each time.
+    pub fn snapshot(&self) -> LogSnapshot {
+        self.snapshot
+            .get_or_init(|| LogSnapshot(self.entries.as_slice().into()))
+            .clone()
+    }
+
     /// Get entries filtered by a case-insensitive query on trigger/action/details.
     pub fn get_filtered(&self, query: &str) -> Vec<&LogEntry> {
         let q = query.to_lowercase();
         self.entries
             .iter()
-            .filter(|e| {
-                e.trigger_reason.to_lowercase().contains(&q)
-                    || e.action_name.to_lowercase().contains(&q)
-                    || e.details
-                        .as_deref()
-                        .unwrap_or("")
-                        .to_lowercase()
-                        .contains(&q)
-            })
+            .filter(|e| entry_contains(e, &q))
             .collect()
     }
 
+    /// Counts and timings of the entries and sessions within `range`.
+    pub fn stats(&self, range: &StatsRange) -> ActivityStats {
+        ActivityStats::collect(&self.entries, &self.sessions, range)
+    }
+
+    /// Get one page of the entries matching `filter`, newest first, with
+    /// the total number of matches.
+    pub fn get_filtered_advanced(&self, filter: &LogFilter) -> LogPage {
+        let query = filter
+            .query
+            .as_deref()
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .map(str::to_lowercase);
+        let matched: Vec<&LogEntry> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|e| filter.matches(e, query.as_deref()))
+            .collect();
+        let total = matched.len();
+        let entries = matched
+            .into_iter()
+            .skip(filter.offset)
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+        LogPage { entries, total }
+    }
+
     /// Get the number of entries.
     pub fn len(&self) -> usize {
         self.entries.len()
@@ -99,9 +434,12 @@ impl ActivityLogger {
         self.entries.is_empty()
     }
 
-    /// Clear all entries.
+    /// Clear all entries and every session except the open one.
     pub fn clear(&mut self) {
         self.entries.clear();
+        let current = self.current_session;
+        self.sessions.retain(|s| Some(s.id) == current);
+        self.snapshot.take();
     }
 
     /// Export all entries as a JSON string.
@@ -127,53 +465,174 @@ impl ActivityLogger {
             .join("\n")
     }
 
-    /// Save all log entries to a JSON file.
-    pub fn save_to_file(&self, path: &std::path::Path) -> Result<(), String> {
+    /// Export all entries as CSV (RFC 4180: CRLF line endings, fields
+    /// holding commas, quotes or line breaks quoted, quotes doubled), with
+    /// a header row.
+    pub fn export_csv(&self) -> String {
+        let mut csv = String::from(
+            "timestamp,status,trigger_reason,action_name,details,cancel_reason,session_id,metadata\r\n",
+        );
+        for e in &self.entries {
+            let fields = [
+                e.timestamp.clone(),
+                serde_name(&e.status),
+                e.trigger_reason.clone(),
+                e.action_name.clone(),
+                e.details.clone().unwrap_or_default(),
+                e.cancel_reason.as_ref().map(serde_name).unwrap_or_default(),
+                e.session_id.map(|id| id.to_string()).unwrap_or_default(),
+                e.metadata
+                    .as_ref()
+                    .map(|m| m.to_string())
+                    .unwrap_or_default(),
+            ];
+            let row: Vec<_> = fields.iter().map(|f| csv_field(f)).collect();
+            csv.push_str(&row.join(","));
+            csv.push_str("\r\n");
+        }
+        csv
+    }
+
+    /// Save all log entries and sessions to a JSON file.
+    pub fn save_to_file(&self, path: &Path) -> Result<(), String> {
+        let file = LogFile::Sessions {
+            entries: self.entries.clone(),
+            sessions: self.sessions.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+        persist::write_with_backup(path, json.as_bytes())
+    }
+
+    /// Add `entry` and append it to the NDJSON log at `path`, compacting
+    /// the file instead if it has grown well past what is kept in memory
+    /// or is not NDJSON yet.
+    pub fn append_entry(&mut self, entry: LogEntry, path: &Path) -> Result<(), String> {
+        self.add_entry(entry);
+        let entry = self.entries.last().expect("entry was just added");
+        let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        self.append_line(path, line)
+    }
+
+    /// Append the current state of session `id` to the NDJSON log at
+    /// `path`. Does nothing if the session is unknown.
+    pub fn append_session(&mut self, id: SessionId, path: &Path) -> Result<(), String> {
+        let Some(session) = self.sessions.iter().find(|s| s.id == id) else {
+            return Ok(());
+        };
+        let line = serde_json::to_string(&SessionLine { session }).map_err(|e| e.to_string())?;
+        self.append_line(path, line)
+    }
+
+    /// Append one line and fsync it, or compact when due.
+    fn append_line(&mut self, path: &Path, line: String) -> Result<(), String> {
+        let lines = match self.file_lines {
+            Some(lines) if lines < COMPACT_AFTER_LINES => lines,
+            _ => return self.compact_file(path),
+        };
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
-        let json = serde_json::to_string_pretty(&self.entries).map_err(|e| e.to_string())?;
-        std::fs::write(path, json).map_err(|e| e.to_string())
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| e.to_string())?;
+        file.write_all(format!("{line}\n").as_bytes())
+            .and_then(|()| file.sync_data())
+            .map_err(|e| e.to_string())?;
+        self.file_lines = Some(lines + 1);
+        Ok(())
+    }
+
+    /// Atomically rewrite the NDJSON log at `path` with the sessions and
+    /// entries kept in memory (write a temporary file, then rename it over
+    /// `path`), and refresh its backup (see [`persist::write_with_backup`]).
+    /// Call after [`prune_older_than`](Self::prune_older_than) to
+    /// apply retention to the file.
+    pub fn compact_file(&mut self, path: &Path) -> Result<(), String> {
+        let mut ndjson = String::new();
+        for session in &self.sessions {
+            ndjson +=
+                &serde_json::to_string(&SessionLine { session }).map_err(|e| e.to_string())?;
+            ndjson.push('\n');
+        }
+        for entry in &self.entries {
+            ndjson += &serde_json::to_string(entry).map_err(|e| e.to_string())?;
+            ndjson.push('\n');
+        }
+        persist::write_with_backup(path, ndjson.as_bytes())?;
+        self.file_lines = Some(self.sessions.len() + self.entries.len());
+        Ok(())
     }
 
-    /// Load log entries from a JSON file. Replaces current entries.
-    pub fn load_from_file(path: &std::path::Path) -> Result<Self, String> {
+    /// Load log entries and sessions from a JSON or NDJSON file. Replaces
+    /// current entries. Sessions that were still open end as
+    /// [`SessionOutcome::Interrupted`].
+    ///
+    /// Unreadable NDJSON lines are skipped, but a file with no readable
+    /// line at all is an error, so the caller can fall back to the backup
+    /// (see [`persist::load_with_backup`]).
+    pub fn load_from_file(path: &Path) -> Result<Self, String> {
         if !path.exists() {
             return Ok(Self::new());
         }
         let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-        let entries: Vec<LogEntry> = serde_json::from_str(&data).map_err(|e| e.to_string())?;
-        // Cap at MAX_ENTRIES, keeping newest.
-        let start = if entries.len() > MAX_ENTRIES {
-            entries.len() - MAX_ENTRIES
-        } else {
-            0
+        let (mut entries, mut sessions, file_lines) = match serde_json::from_str::<LogFile>(&data) {
+            Ok(LogFile::Sessions { entries, sessions }) => (entries, sessions, None),
+            Ok(LogFile::Entries(entries)) => (entries, Vec::new(), None),
+            Err(e) if data.trim_start().starts_with('[') => return Err(e.to_string()),
+            Err(_) => {
+                let (entries, sessions, lines) = parse_ndjson(&data);
+                if lines > 0 && entries.is_empty() && sessions.is_empty() {
+                    return Err(format!("no readable record in {}", path.display()));
+                }
+                (entries, sessions, Some(lines))
+            }
         };
+        // Cap at MAX_ENTRIES and MAX_SESSIONS, keeping newest.
+        let start = entries.len().saturating_sub(MAX_ENTRIES);
+        sessions.drain(..sessions.len().saturating_sub(MAX_SESSIONS));
+        for session in sessions.iter_mut().filter(|s| s.outcome.is_none()) {
+            session.outcome = Some(SessionOutcome::Interrupted);
+        }
+        for entry in &mut entries[start..] {
+            upgrade_timestamp(&mut entry.timestamp);
+        }
+        for session in &mut sessions {
+            upgrade_timestamp(&mut session.started_at);
+            if let Some(ended_at) = &mut session.ended_at {
+                upgrade_timestamp(ended_at);
+            }
+        }
         Ok(Self {
             entries: entries[start..].to_vec(),
+            snapshot: OnceLock::new(),
+            sessions,
+            current_session: None,
+            file_lines,
         })
     }
 
-    /// Remove entries older than `days` days.
-    ///
-    /// Uses the timestamp prefix (YYYY-MM-DD) for comparison.
+    /// Remove entries and sessions older than `days` days. Entries whose
+    /// timestamp cannot be parsed are kept.
     pub fn prune_older_than(&mut self, days: u64) {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
-        let cutoff_secs = now.saturating_sub(days * 86400);
-        let (cutoff_year, cutoff_month, cutoff_day) = days_to_date(cutoff_secs / 86400);
-        let cutoff_str = format!("{:04}-{:02}-{:02}", cutoff_year, cutoff_month, cutoff_day);
-
-        self.entries.retain(|e| {
-            // Compare the date portion of the timestamp.
-            if e.timestamp.len() >= 10 {
-                e.timestamp[..10] >= *cutoff_str
-            } else {
-                true // Keep entries with unexpected timestamp format.
-            }
-        });
+        let days = i64::try_from(days).unwrap_or(i64::MAX);
+        let cutoff = chrono::Duration::try_days(days)
+            .and_then(|age| Utc::now().checked_sub_signed(age))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        self.prune_before(cutoff);
+    }
+
+    /// Remove entries and sessions from before `cutoff`, keeping the open
+    /// session and entries with unparseable timestamps.
+    fn prune_before(&mut self, cutoff: DateTime<Utc>) {
+        self.snapshot.take();
+        self.entries
+            .retain(|e| parse_timestamp(&e.timestamp).is_none_or(|time| time >= cutoff));
+        let cutoff_secs = u64::try_from(cutoff.timestamp()).unwrap_or(0);
+        let current = self.current_session;
+        self.sessions
+            .retain(|s| s.started_unix_secs >= cutoff_secs || Some(s.id) == current);
     }
 }
 
@@ -189,66 +648,133 @@ impl LogEntry {
         status: LogStatus,
         details: Option<String>,
     ) -> Self {
-        // Simple ISO-like timestamp without chrono dependency.
-        let timestamp = {
-            let now = std::time::SystemTime::now();
-            let duration = now
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default();
-            let secs = duration.as_secs();
-            // Format as "YYYY-MM-DD HH:MM:SS" (UTC approximation).
-            let days = secs / 86400;
-            let time_secs = secs % 86400;
-            let hours = time_secs / 3600;
-            let minutes = (time_secs % 3600) / 60;
-            let seconds = time_secs % 60;
-            // Approximate year/month/day from days since epoch.
-            let (year, month, day) = days_to_date(days);
-            format!(
-                "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
-                year, month, day, hours, minutes, seconds
-            )
-        };
-
         Self {
-            timestamp,
+            timestamp: format_timestamp(Local::now()),
             trigger_reason: trigger_reason.into(),
             action_name: action_name.into(),
             status,
             details,
+            cancel_reason: None,
+            session_id: None,
+            duration_ms: None,
+            metadata: None,
         }
     }
-}
 
-/// Convert days since Unix epoch to (year, month, day).
-fn days_to_date(mut days: u64) -> (u64, u64, u64) {
-    let mut year = 1970u64;
-    loop {
-        let days_in_year = if is_leap(year) { 366 } else { 365 };
-        if days < days_in_year {
-            break;
+    /// Attach structured context.
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Record how long the action ran.
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration_ms = Some(duration.as_millis() as u64);
+        self
+    }
+
+    /// A [`LogStatus::Cancelled`] entry attributed to `reason`.
+    pub fn cancelled(
+        trigger_reason: impl Into<String>,
+        action_name: impl Into<String>,
+        reason: CancelReason,
+    ) -> Self {
+        Self {
+            cancel_reason: Some(reason.clone()),
+            ..Self::now(
+                trigger_reason,
+                action_name,
+                LogStatus::Cancelled,
+                Some(reason.to_string()),
+            )
         }
-        days -= days_in_year;
-        year += 1;
     }
-    let month_days: [u64; 12] = if is_leap(year) {
-        [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
-    } else {
-        [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
-    };
-    let mut month = 1u64;
-    for &md in &month_days {
-        if days < md {
-            break;
+
+    /// A [`LogStatus::Info`] entry for an action the power guard skipped.
+    /// The scheduler ends that session `Cancelled`, so the entry carries
+    /// [`CancelReason::GuardFailed`] too.
+    pub fn skipped(
+        trigger_reason: impl Into<String>,
+        action_name: impl Into<String>,
+        reason: String,
+    ) -> Self {
+        Self {
+            details: Some(format!("Skipped by power guard: {reason}")),
+            cancel_reason: Some(CancelReason::GuardFailed(reason)),
+            ..Self::now(trigger_reason, action_name, LogStatus::Info, None)
+        }
+    }
+}
+
+/// Entries, sessions (latest state of each, by id) and the line count of
+/// an NDJSON log. Unreadable lines, such as a write torn by a crash, are
+/// skipped.
+fn parse_ndjson(data: &str) -> (Vec<LogEntry>, Vec<SessionRecord>, usize) {
+    let mut entries = Vec::new();
+    let mut sessions = BTreeMap::new();
+    let mut lines = 0;
+    let mut skipped = 0;
+    for line in data.lines().filter(|l| !l.trim().is_empty()) {
+        lines += 1;
+        match serde_json::from_str(line) {
+            Ok(LogLine::Entry(entry)) => entries.push(entry),
+            Ok(LogLine::Session { session }) => {
+                sessions.insert(session.id, session);
+            }
+            Err(_) => skipped += 1,
         }
-        days -= md;
-        month += 1;
     }
-    (year, month, days + 1)
+    if skipped > 0 {
+        tracing::warn!("Skipped {skipped} unreadable activity log line(s)");
+    }
+    (entries, sessions.into_values().collect(), lines)
 }
 
-fn is_leap(y: u64) -> bool {
-    (y.is_multiple_of(4) && !y.is_multiple_of(100)) || y.is_multiple_of(400)
+/// Seconds since the Unix epoch at `time`.
+fn unix_secs(time: DateTime<Local>) -> u64 {
+    u64::try_from(time.timestamp()).unwrap_or(0)
+}
+
+/// RFC 3339 timestamp with whole seconds and the local offset.
+fn format_timestamp(time: DateTime<Local>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+/// Format of timestamps written by older versions, in UTC.
+const LEGACY_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Parse an RFC 3339 or legacy timestamp.
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(timestamp).ok().or_else(|| {
+        NaiveDateTime::parse_from_str(timestamp, LEGACY_TIMESTAMP_FORMAT)
+            .ok()
+            .map(|time| time.and_utc().fixed_offset())
+    })
+}
+
+/// Rewrite a legacy timestamp as RFC 3339 in local time. Other strings
+/// are left alone.
+fn upgrade_timestamp(timestamp: &mut String) {
+    if let Ok(time) = NaiveDateTime::parse_from_str(timestamp, LEGACY_TIMESTAMP_FORMAT) {
+        *timestamp = format_timestamp(time.and_utc().with_timezone(&Local));
+    }
+}
+
+/// The serialized name of a unit enum variant (e.g. `executed`).
+fn serde_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+/// Quote a CSV field if it holds a comma, quote or line break.
+fn csv_field(field: &str) -> std::borrow::Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -258,6 +784,7 @@ fn is_leap(y: u64) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use flowwatcher_testing::ScratchDir;
 
     #[test]
     fn logger_starts_empty() {
@@ -287,6 +814,81 @@ mod tests {
         assert_eq!(logger.get_all()[1].status, LogStatus::Cancelled);
     }
 
+    #[test]
+    fn snapshot_is_shared_until_the_log_changes() {
+        let mut logger = ActivityLogger::new();
+        logger.add_entry(LogEntry::now(
+            "Network idle",
+            "Shutdown",
+            LogStatus::Info,
+            None,
+        ));
+
+        let first = logger.snapshot();
+        let second = logger.snapshot();
+        assert!(Arc::ptr_eq(&first.0, &second.0));
+        assert_eq!(
+            serde_json::to_string(&first).unwrap(),
+            serde_json::to_string(logger.get_all()).unwrap()
+        );
+
+        logger.add_entry(LogEntry::now(
+            "Process idle",
+            "Sleep",
+            LogStatus::Info,
+            None,
+        ));
+        let third = logger.snapshot();
+        assert_eq!(first.len(), 1);
+        assert_eq!(third.len(), 2);
+
+        logger.clear();
+        assert!(logger.snapshot().is_empty());
+    }
+
+    #[test]
+    fn cancelled_entries_carry_their_reason() {
+        let entry = LogEntry::cancelled("Network idle", "Shutdown", CancelReason::UserTray);
+        assert_eq!(entry.status, LogStatus::Cancelled);
+        assert_eq!(entry.cancel_reason, Some(CancelReason::UserTray));
+        assert_eq!(
+            entry.details.as_deref(),
+            Some("cancelled from the tray menu")
+        );
+
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(json.contains(r#""cancel_reason":"user_tray""#));
+        let back: LogEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.cancel_reason, Some(CancelReason::UserTray));
+    }
+
+    #[test]
+    fn power_guard_skips_are_info_with_their_reason() {
+        let entry = LogEntry::skipped("Network idle", "Hibernate", "battery at 12%".into());
+        assert_eq!(entry.status, LogStatus::Info);
+        assert_eq!(
+            entry.details.as_deref(),
+            Some("Skipped by power guard: battery at 12%")
+        );
+        assert_eq!(
+            entry.cancel_reason,
+            Some(CancelReason::GuardFailed("battery at 12%".into()))
+        );
+    }
+
+    #[test]
+    fn entries_without_reason_stay_compatible() {
+        let entry = LogEntry::now("Test", "Action", LogStatus::Info, None);
+        assert!(!serde_json::to_string(&entry)
+            .unwrap()
+            .contains("cancel_reason"));
+
+        let old = r#"{"timestamp":"2025-01-01 00:00:00","trigger_reason":"Network idle",
+            "action_name":"Shutdown","status":"cancelled","details":null}"#;
+        let entry: LogEntry = serde_json::from_str(old).unwrap();
+        assert_eq!(entry.cancel_reason, None);
+    }
+
     #[test]
     fn fifo_eviction_at_capacity() {
         let mut logger = ActivityLogger::new();
@@ -324,6 +926,59 @@ mod tests {
         assert_eq!(results[0].trigger_reason, "Network idle");
     }
 
+    #[test]
+    fn advanced_filter_combines_status_dates_and_paging() {
+        let mut logger = ActivityLogger::new();
+        let entries = [
+            ("2025-01-30 23:59:59", LogStatus::Error, "disk full"),
+            ("2025-01-31 08:00:00", LogStatus::Info, "started"),
+            ("2025-01-31 12:00:00", LogStatus::Error, "Access denied"),
+            ("2025-01-31 23:00:00", LogStatus::Error, "timeout"),
+            ("2025-02-01 00:00:00", LogStatus::Error, "access denied"),
+        ];
+        for (timestamp, status, details) in entries {
+            logger.add_entry(LogEntry {
+                timestamp: timestamp.into(),
+                trigger_reason: "Network idle".into(),
+                action_name: "Shutdown".into(),
+                status,
+                details: Some(details.into()),
+                cancel_reason: None,
+                session_id: None,
+                duration_ms: None,
+                metadata: None,
+            });
+        }
+
+        let filter = LogFilter {
+            statuses: vec![LogStatus::Error],
+            from: Some("2025-01-31".into()),
+            to: Some("2025-01-31".into()),
+            limit: Some(1),
+            ..LogFilter::default()
+        };
+        let page = logger.get_filtered_advanced(&filter);
+        assert_eq!(page.total, 2);
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.entries[0].details.as_deref(), Some("timeout"));
+
+        let page = logger.get_filtered_advanced(&LogFilter {
+            offset: 1,
+            ..filter
+        });
+        assert_eq!(page.entries[0].details.as_deref(), Some("Access denied"));
+
+        let page = logger.get_filtered_advanced(&LogFilter {
+            from: Some("2025-01-31T12:00".into()),
+            query: Some("ACCESS".into()),
+            ..LogFilter::default()
+        });
+        assert_eq!(page.total, 2);
+        assert_eq!(page.entries[0].timestamp, "2025-02-01 00:00:00");
+
+        assert_eq!(logger.get_filtered_advanced(&LogFilter::default()).total, 5);
+    }
+
     #[test]
     fn clear_entries() {
         let mut logger = ActivityLogger::new();
@@ -339,6 +994,249 @@ mod tests {
         let json = logger.export_json().unwrap();
         assert!(json.contains("\"trigger_reason\": \"Test\""));
         assert!(json.contains("\"executed\""));
+        assert!(!json.contains("metadata"));
+    }
+
+    #[test]
+    fn metadata_round_trips_and_is_optional() {
+        let entry = LogEntry::now("Network idle", "Shutdown", LogStatus::Executed, None)
+            .with_metadata(serde_json::json!({ "download_bps": 120, "upload_bps": 40 }));
+        let json = serde_json::to_string(&entry).unwrap();
+        let back: LogEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.metadata, entry.metadata);
+
+        let old: LogEntry = serde_json::from_str(
+            r#"{"timestamp":"2025-01-01 00:00:00","trigger_reason":"Network idle",
+                "action_name":"Shutdown","status":"executed","details":null}"#,
+        )
+        .unwrap();
+        assert_eq!(old.metadata, None);
+    }
+
+    #[test]
+    fn sessions_pair_start_and_end_and_tag_entries() {
+        let mut logger = ActivityLogger::new();
+        logger.add_entry(LogEntry::now("Before", "Action", LogStatus::Info, None));
+        let first = logger.start_session("Network idle", "Shutdown");
+        logger.add_entry(LogEntry::now(
+            "Network idle",
+            "Shutdown",
+            LogStatus::Executed,
+            None,
+        ));
+        assert!(logger.end_session(
+            first,
+            SessionOutcome::Executed,
+            Some("final speed 0 B/s".into())
+        ));
+        assert!(!logger.end_session(first, SessionOutcome::Stopped, None));
+
+        // Starting over an open session stops it.
+        let second = logger.start_session("Process idle", "Sleep");
+        let third = logger.start_session("Process idle", "Sleep");
+        assert_eq!(logger.current_session(), Some(third));
+
+        let entries = logger.get_all();
+        assert_eq!(entries[0].session_id, None);
+        assert_eq!(entries[1].session_id, Some(first));
+
+        let sessions = logger.get_sessions();
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(sessions[0].outcome, Some(SessionOutcome::Executed));
+        assert_eq!(sessions[0].duration_secs, Some(0));
+        assert_eq!(sessions[0].details.as_deref(), Some("final speed 0 B/s"));
+        assert_eq!(sessions[1].id, second);
+        assert_eq!(sessions[1].outcome, Some(SessionOutcome::Stopped));
+        assert_eq!(sessions[2].outcome, None);
+    }
+
+    #[test]
+    fn sessions_survive_a_save_and_open_ones_load_as_interrupted() {
+        let dir = ScratchDir::new("log-sessions");
+        let path = dir.join("activity_logs.json");
+        let mut logger = ActivityLogger::new();
+        let done = logger.start_session("Network idle", "Shutdown");
+        logger.end_session(done, SessionOutcome::Stopped, None);
+        logger.start_session("Network idle", "Shutdown");
+        logger.add_entry(LogEntry::now(
+            "Network idle",
+            "Shutdown",
+            LogStatus::Info,
+            None,
+        ));
+        logger.save_to_file(&path).unwrap();
+
+        let loaded = ActivityLogger::load_from_file(&path).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.current_session(), None);
+        let outcomes: Vec<_> = loaded.get_sessions().iter().map(|s| s.outcome).collect();
+        assert_eq!(
+            outcomes,
+            [
+                Some(SessionOutcome::Stopped),
+                Some(SessionOutcome::Interrupted)
+            ]
+        );
+    }
+
+    #[test]
+    fn legacy_log_files_load_without_sessions() {
+        let dir = ScratchDir::new("log-legacy");
+        let path = dir.join("activity_logs.json");
+        std::fs::write(
+            &path,
+            r#"[{"timestamp":"2025-01-01 00:00:00","trigger_reason":"Network idle",
+                "action_name":"Shutdown","status":"executed","details":null}]"#,
+        )
+        .unwrap();
+
+        let loaded = ActivityLogger::load_from_file(&path).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.get_all()[0].session_id, None);
+        assert!(loaded.get_sessions().is_empty());
+    }
+
+    fn entry_at(timestamp: &str) -> LogEntry {
+        LogEntry {
+            timestamp: timestamp.into(),
+            ..LogEntry::now("Network idle", "Shutdown", LogStatus::Info, None)
+        }
+    }
+
+    #[test]
+    fn new_entries_have_rfc3339_local_timestamps() {
+        let entry = LogEntry::now("Test", "Action", LogStatus::Info, None);
+        let parsed = DateTime::parse_from_rfc3339(&entry.timestamp).unwrap();
+        assert_eq!(
+            parsed.offset().local_minus_utc(),
+            Local::now().offset().local_minus_utc()
+        );
+    }
+
+    #[test]
+    fn pruning_compares_instants_across_month_boundaries() {
+        let mut logger = ActivityLogger::new();
+        for timestamp in [
+            // 2025-03-01 03:00 UTC, although the local date is in February.
+            "2025-02-28T21:00:00-06:00",
+            // 2025-02-28 23:00 UTC, although the local date is in March.
+            "2025-03-01T01:00:00+02:00",
+            // Legacy UTC timestamps.
+            "2025-02-28 23:59:59",
+            "2025-03-01 02:00:00",
+            "not a timestamp",
+        ] {
+            logger.add_entry(entry_at(timestamp));
+        }
+
+        let cutoff = DateTime::parse_from_rfc3339("2025-03-01T02:00:00Z").unwrap();
+        logger.prune_before(cutoff.with_timezone(&Utc));
+        let kept: Vec<_> = logger
+            .get_all()
+            .iter()
+            .map(|e| e.timestamp.as_str())
+            .collect();
+        assert_eq!(
+            kept,
+            [
+                "2025-02-28T21:00:00-06:00",
+                "2025-03-01 02:00:00",
+                "not a timestamp"
+            ]
+        );
+    }
+
+    #[test]
+    fn mixed_timestamp_files_load_as_rfc3339() {
+        let dir = ScratchDir::new("log-mixed");
+        let path = dir.join("activity_logs.json");
+        std::fs::write(
+            &path,
+            r#"[{"timestamp":"2025-01-31 23:30:00","trigger_reason":"Network idle",
+                "action_name":"Shutdown","status":"executed","details":null},
+               {"timestamp":"2025-02-01T08:00:00+05:30","trigger_reason":"Network idle",
+                "action_name":"Shutdown","status":"info","details":null}]"#,
+        )
+        .unwrap();
+
+        let loaded = ActivityLogger::load_from_file(&path).unwrap();
+        let entries = loaded.get_all();
+        let legacy = DateTime::parse_from_rfc3339(&entries[0].timestamp).unwrap();
+        assert_eq!(
+            legacy,
+            DateTime::parse_from_rfc3339("2025-01-31T23:30:00Z").unwrap()
+        );
+        assert_eq!(entries[1].timestamp, "2025-02-01T08:00:00+05:30");
+    }
+
+    #[test]
+    fn appended_ndjson_round_trips_and_skips_a_torn_line() {
+        let dir = ScratchDir::new("log-ndjson");
+        let path = dir.join("activity_logs.ndjson");
+        let mut logger = ActivityLogger::new();
+        let id = logger.start_session("Network idle", "Shutdown");
+        // The first append writes the whole file.
+        logger.append_session(id, &path).unwrap();
+        logger
+            .append_entry(entry_at("2025-01-01T00:00:00Z"), &path)
+            .unwrap();
+        logger.end_session(id, SessionOutcome::Executed, None);
+        logger.append_session(id, &path).unwrap();
+        logger
+            .append_entry(entry_at("2025-01-01T00:01:00Z"), &path)
+            .unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(br#"{"timestamp":"2025-01-01T00:02"#)
+            .unwrap();
+
+        let mut loaded = ActivityLogger::load_from_file(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.get_all()[0].session_id, Some(id));
+        assert_eq!(loaded.get_sessions().len(), 1);
+        assert_eq!(
+            loaded.get_sessions()[0].outcome,
+            Some(SessionOutcome::Executed)
+        );
+        assert_eq!(loaded.file_lines, Some(5));
+
+        loaded.compact_file(&path).unwrap();
+        let data = std::fs::read_to_string(&path).unwrap();
+        let backup = std::fs::read_to_string(persist::backup_path(&path)).unwrap();
+
+        // A file with nothing readable left falls back to that backup.
+        std::fs::write(&path, "\u{0}\u{0}\u{0}\n").unwrap();
+        let unreadable = ActivityLogger::load_from_file(&path);
+        let recovered = persist::load_with_backup(&path, ActivityLogger::load_from_file);
+        assert_eq!(data.lines().count(), 3);
+        assert_eq!(backup, data);
+        assert!(!dir.join("activity_logs.ndjson.tmp").exists());
+        assert!(unreadable.is_err());
+        assert_eq!(recovered.recovery, Some(persist::Recovery::Backup));
+        assert_eq!(recovered.value.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn appending_to_a_json_log_converts_it_to_ndjson() {
+        let dir = ScratchDir::new("log-ndjson-convert");
+        let path = dir.join("activity_logs.json");
+        let mut logger = ActivityLogger::new();
+        logger.add_entry(entry_at("2025-01-01T00:00:00Z"));
+        logger.save_to_file(&path).unwrap();
+
+        let mut loaded = ActivityLogger::load_from_file(&path).unwrap();
+        loaded
+            .append_entry(entry_at("2025-01-01T00:01:00Z"), &path)
+            .unwrap();
+        loaded
+            .append_entry(entry_at("2025-01-01T00:02:00Z"), &path)
+            .unwrap();
+        let data = std::fs::read_to_string(&path).unwrap();
+        let reloaded = ActivityLogger::load_from_file(&path).unwrap();
+        assert_eq!(data.lines().count(), 3);
+        assert_eq!(reloaded.len(), 3);
     }
 
     #[test]
@@ -354,4 +1252,47 @@ mod tests {
         assert!(txt.contains("Test"));
         assert!(txt.contains("details"));
     }
+
+    #[test]
+    fn export_csv_quotes_special_fields() {
+        let mut logger = ActivityLogger::new();
+        logger.add_entry(LogEntry {
+            timestamp: "2025-01-01 00:00:00".into(),
+            trigger_reason: "Network idle".into(),
+            action_name: "Shutdown".into(),
+            status: LogStatus::Cancelled,
+            details: Some("said \"wait\", then\nleft".into()),
+            cancel_reason: Some(CancelReason::UserTray),
+            session_id: Some(3),
+            duration_ms: None,
+            metadata: Some(serde_json::json!({ "download_bps": 120 })),
+        });
+        logger.add_entry(LogEntry {
+            timestamp: "2025-01-01 00:01:00".into(),
+            trigger_reason: "Process idle".into(),
+            action_name: "Sleep".into(),
+            status: LogStatus::Info,
+            details: Some("Télécharger — 下载 ✓".into()),
+            cancel_reason: None,
+            session_id: None,
+            duration_ms: None,
+            metadata: None,
+        });
+
+        let csv = logger.export_csv();
+        let lines: Vec<_> = csv.split("\r\n").collect();
+        assert_eq!(
+            lines[0],
+            "timestamp,status,trigger_reason,action_name,details,cancel_reason,session_id,metadata"
+        );
+        assert_eq!(
+            lines[1],
+            "2025-01-01 00:00:00,cancelled,Network idle,Shutdown,\"said \"\"wait\"\", then\nleft\",user_tray,3,\"{\"\"download_bps\"\":120}\""
+        );
+        assert_eq!(
+            lines[2],
+            "2025-01-01 00:01:00,info,Process idle,Sleep,Télécharger — 下载 ✓,,,"
+        );
+        assert_eq!(lines[3], "");
+    }
 }
@@ -0,0 +1,658 @@
+//! Monitoring engine — drives trigger → condition → scheduler → action.
+//!
+//! [`MonitoringEngine`] owns one boxed [`Trigger`], [`Condition`], and
+//! [`Action`] and advances the whole pipeline one step per call, so it
+//! works identically for built-in and third-party implementations.
+
+use crate::clock::{Clock, SystemClock};
+use crate::hooks::{HookSet, LifecycleEvent};
+use crate::logger::LogEntry;
+use crate::recording::{RecordingError, SessionRecorder};
+use crate::scheduler::{
+    ActionScheduler, CancelReason, SchedulerError, SchedulerEvent, SchedulerState,
+};
+use flowwatcher_actions::{Action, ActionError};
+use flowwatcher_conditions::{
+    Condition, ConditionError, ConditionResult, Zone, DEFAULT_MAX_EVALUATION_GAP,
+};
+use flowwatcher_triggers::{Trigger, TriggerError, TriggerState};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+// ---------------------------------------------------------------------------
+// Error types
+// ---------------------------------------------------------------------------
+
+/// Errors from any stage of the monitoring pipeline.
+#[derive(Debug, Error)]
+pub enum EngineError {
+    /// The trigger failed.
+    #[error(transparent)]
+    Trigger(#[from] TriggerError),
+
+    /// The condition failed.
+    #[error(transparent)]
+    Condition(#[from] ConditionError),
+
+    /// The scheduler rejected a transition.
+    #[error(transparent)]
+    Scheduler(#[from] SchedulerError),
+
+    /// The action failed to execute.
+    #[error(transparent)]
+    Action(#[from] ActionError),
+
+    /// A session capture could not be written or read.
+    #[error(transparent)]
+    Recording(#[from] RecordingError),
+}
+
+// ---------------------------------------------------------------------------
+// Step outcome
+// ---------------------------------------------------------------------------
+
+/// What happened during one [`MonitoringEngine::step`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepOutcome {
+    /// Condition result, if the trigger was evaluated this step.
+    pub condition: Option<ConditionResult>,
+    /// Scheduler state after the step.
+    pub scheduler: SchedulerState,
+    /// Scheduler events emitted during this step.
+    pub events: Vec<SchedulerEvent>,
+    /// Whether the action was executed during this step.
+    pub executed: bool,
+    /// Threshold zone reported by the condition this step, if any.
+    pub zone: Option<Zone>,
+}
+
+// ---------------------------------------------------------------------------
+// Automatic cancel
+// ---------------------------------------------------------------------------
+
+/// Why a running pre-warning or countdown should cancel on its own, if at
+/// all.
+///
+/// A `gap` since the previous step longer than
+/// [`DEFAULT_MAX_EVALUATION_GAP`] means the system slept, which cancels
+/// with [`CancelReason::SystemResume`]. Otherwise a condition back at
+/// `Waiting` (traffic above the threshold) cancels with
+/// [`CancelReason::AutoTrafficResumed`].
+pub fn auto_cancel_reason(
+    gap: Duration,
+    condition: Option<&ConditionResult>,
+) -> Option<CancelReason> {
+    if gap > DEFAULT_MAX_EVALUATION_GAP {
+        Some(CancelReason::SystemResume)
+    } else if condition == Some(&ConditionResult::Waiting) {
+        Some(CancelReason::AutoTrafficResumed)
+    } else {
+        None
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MonitoringEngine
+// ---------------------------------------------------------------------------
+
+/// Maximum number of untaken hook log entries kept by [`MonitoringEngine`].
+pub const MAX_HOOK_LOG: usize = 100;
+
+/// Runs a complete trigger → condition → action pipeline.
+///
+/// While the scheduler is idle, each step evaluates the trigger and feeds
+/// active data into the condition; once the condition is met (or the
+/// trigger reports [`TriggerState::Triggered`]) the action is scheduled.
+/// While a pre-warning or countdown is running, each step still evaluates
+/// the trigger and cancels if [`auto_cancel_reason`] gives a reason (the
+/// system slept, or traffic resumed); otherwise it ticks the scheduler and
+/// runs the action when the countdown reaches zero. The condition keeps
+/// its state from before the action was scheduled, so hysteresis and the
+/// spike budget apply to resumed traffic too; it is reset once the
+/// scheduler leaves the pre-warning or countdown.
+///
+/// Time is read from a [`Clock`] (the system clock by default), and every
+/// step can optionally be captured by a [`SessionRecorder`]. Optional
+/// lifecycle [`HookSet`] actions run on start, stop, and cancel; their log
+/// entries are collected for [`take_hook_log`](Self::take_hook_log), up to
+/// [`MAX_HOOK_LOG`].
+pub struct MonitoringEngine {
+    trigger: Box<dyn Trigger>,
+    condition: Box<dyn Condition>,
+    action: Box<dyn Action>,
+    scheduler: ActionScheduler,
+    clock: Arc<dyn Clock>,
+    recorder: Option<SessionRecorder>,
+    hooks: HookSet,
+    hook_log: Vec<LogEntry>,
+    /// Clock time of the previous step.
+    last_step: Option<Instant>,
+}
+
+impl MonitoringEngine {
+    /// Create an engine from its pipeline stages.
+    pub fn new(
+        trigger: Box<dyn Trigger>,
+        condition: Box<dyn Condition>,
+        action: Box<dyn Action>,
+        scheduler: ActionScheduler,
+    ) -> Self {
+        Self {
+            trigger,
+            condition,
+            action,
+            scheduler,
+            clock: Arc::new(SystemClock),
+            recorder: None,
+            hooks: HookSet::new(),
+            hook_log: Vec::new(),
+            last_step: None,
+        }
+    }
+
+    /// Run `hooks` at lifecycle transitions.
+    pub fn with_hooks(mut self, hooks: HookSet) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Use a custom time source (e.g. a [`VirtualClock`](crate::VirtualClock) for replay).
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Capture every subsequent step with `recorder`, replacing any active one.
+    pub fn start_recording(&mut self, recorder: SessionRecorder) {
+        self.recorder = Some(recorder);
+    }
+
+    /// Stop capturing and hand back the recorder (flush it with `finish()`).
+    pub fn stop_recording(&mut self) -> Option<SessionRecorder> {
+        self.recorder.take()
+    }
+
+    /// Whether a recorder is attached.
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Start the underlying trigger, then fire the `on_start` hook.
+    pub async fn start(&mut self) -> Result<(), EngineError> {
+        self.trigger.start().await?;
+        self.fire_hook(LifecycleEvent::Start).await;
+        Ok(())
+    }
+
+    /// Stop the trigger and reset the condition and scheduler, then fire
+    /// the `on_stop` hook.
+    pub async fn stop(&mut self) -> Result<(), EngineError> {
+        self.trigger.stop().await?;
+        self.condition.reset();
+        self.scheduler.reset();
+        self.fire_hook(LifecycleEvent::Stop).await;
+        Ok(())
+    }
+
+    /// Cancel the pending action from the app window, then fire the
+    /// `on_cancel` hook.
+    pub async fn cancel(&mut self) -> Result<(), EngineError> {
+        self.cancel_with_reason(CancelReason::UserUi).await
+    }
+
+    /// Cancel the pending action for `reason` and reset the condition,
+    /// then fire the `on_cancel` hook.
+    pub async fn cancel_with_reason(&mut self, reason: CancelReason) -> Result<(), EngineError> {
+        self.scheduler.cancel_with_reason(reason)?;
+        self.condition.reset();
+        self.fire_hook(LifecycleEvent::Cancel).await;
+        Ok(())
+    }
+
+    /// Log entries produced by hooks since the last call.
+    pub fn take_hook_log(&mut self) -> Vec<LogEntry> {
+        std::mem::take(&mut self.hook_log)
+    }
+
+    /// Number of hook log entries waiting to be taken.
+    pub fn hook_log_len(&self) -> usize {
+        self.hook_log.len()
+    }
+
+    /// Run a lifecycle hook (best-effort) and keep its log entry, dropping
+    /// the oldest one if the log is full.
+    async fn fire_hook(&mut self, event: LifecycleEvent) {
+        if let Some(entry) = self.hooks.fire(event).await {
+            if self.hook_log.len() >= MAX_HOOK_LOG {
+                self.hook_log.remove(0);
+            }
+            self.hook_log.push(entry);
+        }
+    }
+
+    /// Advance the pipeline by one step (typically one second).
+    pub async fn step(&mut self) -> Result<StepOutcome, EngineError> {
+        let now = self.clock.now();
+        let gap = self
+            .last_step
+            .replace(now)
+            .map_or(Duration::ZERO, |last| now.saturating_duration_since(last));
+        let mut trigger_state = None;
+        let mut condition = None;
+        let mut zone = None;
+        let mut executed = false;
+
+        match self.scheduler.state() {
+            SchedulerState::Idle | SchedulerState::Cancelled => {
+                let (state, result) = self.evaluate(now).await?;
+                zone = self.zone_of(&state);
+                trigger_state = Some(state);
+                if result == Some(ConditionResult::Met) {
+                    self.scheduler.schedule()?;
+                }
+                condition = result;
+            }
+            SchedulerState::Pending | SchedulerState::Countdown => {
+                let (state, result) = self.evaluate(now).await?;
+                zone = self.zone_of(&state);
+                trigger_state = Some(state);
+                condition = result;
+                if let Some(reason) = auto_cancel_reason(gap, condition.as_ref()) {
+                    self.cancel_with_reason(reason).await?;
+                } else if self.scheduler.tick()? {
+                    self.condition.reset();
+                    self.action.execute().await?;
+                    executed = true;
+                } else if self.scheduler.state() == SchedulerState::Cancelled {
+                    // The pending timeout ran out.
+                    self.condition.reset();
+                }
+            }
+            SchedulerState::PausedPending
+            | SchedulerState::PausedCountdown
+            | SchedulerState::Executed
+            | SchedulerState::Failed => {}
+        }
+
+        let outcome = StepOutcome {
+            condition,
+            scheduler: self.scheduler.state(),
+            events: self.scheduler.take_events(),
+            executed,
+            zone,
+        };
+
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.record(now, trigger_state.as_ref(), &outcome) {
+                tracing::warn!("session recording stopped: {e}");
+                self.recorder = None;
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// Evaluate the trigger and feed active data into the condition.
+    async fn evaluate(
+        &mut self,
+        now: Instant,
+    ) -> Result<(TriggerState, Option<ConditionResult>), EngineError> {
+        let state = self.trigger.evaluate().await?;
+        let result = match &state {
+            TriggerState::Idle => {
+                self.condition.reset();
+                None
+            }
+            TriggerState::Active(data) => Some(self.condition.evaluate_at(data, now)?),
+            TriggerState::Triggered => Some(ConditionResult::Met),
+        };
+        Ok((state, result))
+    }
+
+    /// The condition's zone, if `state` carried data for it.
+    fn zone_of(&self, state: &TriggerState) -> Option<Zone> {
+        match state {
+            TriggerState::Active(_) => self.condition.zone(),
+            _ => None,
+        }
+    }
+
+    /// The action scheduler (for inspecting state).
+    pub fn scheduler(&self) -> &ActionScheduler {
+        &self.scheduler
+    }
+
+    /// Mutable access to the scheduler (e.g. for `execute_now()`).
+    ///
+    /// Prefer [`cancel`](Self::cancel) over `scheduler_mut().cancel()` so
+    /// the `on_cancel` hook fires.
+    pub fn scheduler_mut(&mut self) -> &mut ActionScheduler {
+        &mut self.scheduler
+    }
+
+    /// The configured action.
+    pub fn action(&self) -> &dyn Action {
+        self.action.as_ref()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logger::LogStatus;
+    use async_trait::async_trait;
+    use flowwatcher_conditions::{MonitorMode, ThresholdCondition};
+    use flowwatcher_testing::MockAction;
+    use flowwatcher_triggers::{TriggerData, TriggerValue};
+
+    /// A trigger that replays a fixed sequence of download speeds.
+    struct ScriptedTrigger {
+        speeds: Vec<u64>,
+        index: usize,
+    }
+
+    #[async_trait]
+    impl Trigger for ScriptedTrigger {
+        fn name(&self) -> &str {
+            "Scripted"
+        }
+
+        fn trigger_type(&self) -> &str {
+            "scripted"
+        }
+
+        async fn start(&mut self) -> Result<(), TriggerError> {
+            Ok(())
+        }
+
+        async fn stop(&mut self) -> Result<(), TriggerError> {
+            Ok(())
+        }
+
+        async fn evaluate(&mut self) -> Result<TriggerState, TriggerError> {
+            let speed = self.speeds[self.index.min(self.speeds.len() - 1)];
+            self.index += 1;
+            let mut data = TriggerData::new();
+            data.insert("download_bps", TriggerValue::U64(speed));
+            data.insert("upload_bps", TriggerValue::U64(0));
+            Ok(TriggerState::Active(data))
+        }
+    }
+
+    fn engine(speeds: Vec<u64>, action: MockAction) -> MonitoringEngine {
+        MonitoringEngine::new(
+            Box::new(ScriptedTrigger { speeds, index: 0 }),
+            Box::new(ThresholdCondition::new(100, 0, MonitorMode::DownloadOnly)),
+            Box::new(action),
+            ActionScheduler::new(1, 2),
+        )
+    }
+
+    #[tokio::test]
+    async fn step_reports_threshold_zone() {
+        let mut engine = MonitoringEngine::new(
+            Box::new(ScriptedTrigger {
+                speeds: vec![150, 500, 50],
+                index: 0,
+            }),
+            Box::new(
+                ThresholdCondition::new(100, 60, MonitorMode::DownloadOnly)
+                    .with_warning_multiplier(2.0),
+            ),
+            Box::new(MockAction::new()),
+            ActionScheduler::new(1, 2),
+        );
+
+        let mut zones = Vec::new();
+        for _ in 0..3 {
+            let outcome = engine.step().await.unwrap();
+            zones.push(outcome.zone);
+        }
+        assert_eq!(
+            zones,
+            vec![Some(Zone::Warning), Some(Zone::Above), Some(Zone::Below)]
+        );
+    }
+
+    #[tokio::test]
+    async fn executes_action_after_countdown() {
+        let action = MockAction::new();
+        let mut engine = engine(vec![500, 10], action.clone());
+        engine.start().await.unwrap();
+
+        let first = engine.step().await.unwrap();
+        assert_eq!(first.condition, Some(ConditionResult::Waiting));
+
+        let second = engine.step().await.unwrap();
+        assert_eq!(second.scheduler, SchedulerState::Pending);
+
+        let mut executed = false;
+        for _ in 0..5 {
+            executed |= engine.step().await.unwrap().executed;
+        }
+        assert!(executed);
+        assert_eq!(action.executions(), 1);
+        assert_eq!(engine.scheduler().state(), SchedulerState::Executed);
+    }
+
+    #[tokio::test]
+    async fn action_failure_is_reported() {
+        let mut engine = engine(vec![10], MockAction::failing());
+        let mut result = Ok(());
+        for _ in 0..5 {
+            if let Err(e) = engine.step().await {
+                result = Err(e);
+                break;
+            }
+        }
+        assert!(matches!(result, Err(EngineError::Action(_))));
+    }
+
+    /// Hooks backed by fresh mock actions (start, stop, cancel).
+    fn hooks(fail_start: bool) -> (HookSet, [MockAction; 3]) {
+        let start = if fail_start {
+            MockAction::failing()
+        } else {
+            MockAction::new()
+        };
+        let mocks = [start, MockAction::new(), MockAction::new()];
+        let set = HookSet {
+            on_start: Some(Box::new(mocks[0].clone())),
+            on_stop: Some(Box::new(mocks[1].clone())),
+            on_cancel: Some(Box::new(mocks[2].clone())),
+        };
+        (set, mocks)
+    }
+
+    #[tokio::test]
+    async fn hooks_fire_once_at_each_transition() {
+        let (set, [start, stop, cancel]) = hooks(false);
+        let mut engine = engine(vec![10], MockAction::new()).with_hooks(set);
+
+        engine.start().await.unwrap();
+        assert_eq!(
+            (start.executions(), stop.executions(), cancel.executions()),
+            (1, 0, 0)
+        );
+
+        engine.step().await.unwrap();
+        assert_eq!(engine.scheduler().state(), SchedulerState::Pending);
+        engine.cancel().await.unwrap();
+        assert_eq!(
+            (start.executions(), stop.executions(), cancel.executions()),
+            (1, 0, 1)
+        );
+
+        engine.stop().await.unwrap();
+        assert_eq!(
+            (start.executions(), stop.executions(), cancel.executions()),
+            (1, 1, 1)
+        );
+
+        let log = engine.take_hook_log();
+        let reasons: Vec<_> = log.iter().map(|e| e.trigger_reason.as_str()).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "Lifecycle hook: on_start",
+                "Lifecycle hook: on_cancel",
+                "Lifecycle hook: on_stop",
+            ]
+        );
+        assert!(log.iter().all(|e| e.status == LogStatus::Info));
+        assert!(engine.take_hook_log().is_empty());
+    }
+
+    #[tokio::test]
+    async fn cancel_paths_record_their_reason() {
+        let mut ui = engine(vec![10], MockAction::new());
+        ui.step().await.unwrap();
+        ui.scheduler_mut().take_events();
+        ui.cancel().await.unwrap();
+        assert_eq!(
+            ui.scheduler_mut().take_events(),
+            vec![SchedulerEvent::Cancelled {
+                reason: CancelReason::UserUi
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn resumed_traffic_cancels_the_countdown() {
+        let (set, [_, _, cancel]) = hooks(false);
+        let action = MockAction::new();
+        let mut engine = engine(vec![10, 10, 500], action.clone()).with_hooks(set);
+
+        assert_eq!(
+            engine.step().await.unwrap().scheduler,
+            SchedulerState::Pending
+        );
+        assert_eq!(
+            engine.step().await.unwrap().scheduler,
+            SchedulerState::Countdown
+        );
+        let outcome = engine.step().await.unwrap();
+        assert_eq!(outcome.condition, Some(ConditionResult::Waiting));
+        assert_eq!(outcome.scheduler, SchedulerState::Cancelled);
+        assert_eq!(
+            outcome.events,
+            vec![SchedulerEvent::Cancelled {
+                reason: CancelReason::AutoTrafficResumed
+            }]
+        );
+        assert_eq!(cancel.executions(), 1);
+        assert_eq!(action.executions(), 0);
+    }
+
+    #[tokio::test]
+    async fn spikes_and_the_dead_band_do_not_cancel_the_countdown() {
+        let action = MockAction::new();
+        let mut engine = MonitoringEngine::new(
+            Box::new(ScriptedTrigger {
+                // Met, one spike within the budget, then the dead band.
+                speeds: vec![10, 10, 5000, 10, 150, 150],
+                index: 0,
+            }),
+            Box::new(
+                ThresholdCondition::new(100, 0, MonitorMode::DownloadOnly)
+                    .with_hysteresis(100)
+                    .with_max_spike_samples(1),
+            ),
+            Box::new(action.clone()),
+            ActionScheduler::new(1, 10),
+        );
+
+        for _ in 0..6 {
+            let outcome = engine.step().await.unwrap();
+            assert!(outcome
+                .events
+                .iter()
+                .all(|e| !matches!(e, SchedulerEvent::Cancelled { .. })));
+        }
+        assert_eq!(engine.scheduler().state(), SchedulerState::Countdown);
+        assert_eq!(action.executions(), 0);
+    }
+
+    #[tokio::test]
+    async fn suspend_gap_cancels_the_countdown() {
+        let clock = Arc::new(crate::VirtualClock::new());
+        let mut engine = engine(vec![10], MockAction::new()).with_clock(clock.clone());
+
+        engine.step().await.unwrap();
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(
+            engine.step().await.unwrap().scheduler,
+            SchedulerState::Countdown
+        );
+
+        clock.advance(DEFAULT_MAX_EVALUATION_GAP + Duration::from_secs(1));
+        let outcome = engine.step().await.unwrap();
+        assert_eq!(outcome.scheduler, SchedulerState::Cancelled);
+        assert_eq!(
+            engine.scheduler().cancel_reason(),
+            Some(&CancelReason::SystemResume)
+        );
+    }
+
+    #[test]
+    fn auto_cancel_reason_prefers_a_suspend_gap() {
+        let tick = Duration::from_secs(1);
+        let long = DEFAULT_MAX_EVALUATION_GAP + tick;
+        let waiting = ConditionResult::Waiting;
+        let in_progress = ConditionResult::in_progress(5, 60);
+        assert_eq!(auto_cancel_reason(tick, None), None);
+        assert_eq!(auto_cancel_reason(tick, Some(&in_progress)), None);
+        assert_eq!(
+            auto_cancel_reason(tick, Some(&waiting)),
+            Some(CancelReason::AutoTrafficResumed)
+        );
+        assert_eq!(
+            auto_cancel_reason(long, Some(&waiting)),
+            Some(CancelReason::SystemResume)
+        );
+        assert_eq!(
+            auto_cancel_reason(long, None),
+            Some(CancelReason::SystemResume)
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_cancel_does_not_fire_hook() {
+        let (set, [_, _, cancel]) = hooks(false);
+        let mut engine = engine(vec![500], MockAction::new()).with_hooks(set);
+
+        assert!(engine.cancel().await.is_err());
+        assert_eq!(cancel.executions(), 0);
+    }
+
+    #[tokio::test]
+    async fn failing_hook_does_not_abort_monitoring() {
+        let (set, [start, _, _]) = hooks(true);
+        let action = MockAction::new();
+        let mut engine = engine(vec![10], action.clone()).with_hooks(set);
+
+        engine.start().await.unwrap();
+        assert_eq!(start.executions(), 1);
+        for _ in 0..6 {
+            engine.step().await.unwrap();
+        }
+        assert_eq!(action.executions(), 1);
+        let log = engine.take_hook_log();
+        assert!(log[0].details.as_deref().unwrap().starts_with("failed:"));
+    }
+
+    #[tokio::test]
+    async fn stop_resets_scheduler() {
+        let mut engine = engine(vec![10], MockAction::new());
+        engine.step().await.unwrap();
+        assert_eq!(engine.scheduler().state(), SchedulerState::Pending);
+
+        engine.stop().await.unwrap();
+        assert_eq!(engine.scheduler().state(), SchedulerState::Idle);
+    }
+}
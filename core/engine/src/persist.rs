@@ -0,0 +1,276 @@
+//! Crash-safe persistence of small files (settings, the activity log).
+//!
+//! [`write_with_backup`] replaces a file atomically, so a power loss leaves
+//! either the old or the new version, never a truncated one, and keeps a
+//! copy of what it wrote as `<name>.bak`. [`load_with_backup`] falls back
+//! to that copy when the file itself cannot be read (e.g. it was edited by
+//! hand or damaged on disk) and reports the [`Recovery`] so the user can be
+//! told. Before a recovered file is rewritten, [`set_aside`] renames the
+//! unreadable copies to `<name>.corrupt`, so they can still be inspected
+//! or repaired by hand.
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// ---------------------------------------------------------------------------
+// Writing
+// ---------------------------------------------------------------------------
+
+/// The backup kept next to `path`, e.g. `settings.json.bak`.
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Write `contents` to a temporary file next to `path`, fsync it, and
+/// rename it over `path`, so readers see either the old or the new file.
+/// Parent directories are created.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp = path.with_file_name(temp_name);
+    let write = || -> std::io::Result<()> {
+        let mut file = std::fs::File::create(&temp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        std::fs::rename(&temp, path)
+    };
+    write().map_err(|e| {
+        let _ = std::fs::remove_file(&temp);
+        e.to_string()
+    })
+}
+
+/// Write `contents` to `path` atomically, then to its backup.
+///
+/// The backup is only written once `path` holds the new contents, so it
+/// always holds a complete version that was written successfully. Failing
+/// to update the backup is not an error; it keeps the previous version.
+pub fn write_with_backup(path: &Path, contents: &[u8]) -> Result<(), String> {
+    write_atomically(path, contents)?;
+    if let Err(e) = write_atomically(&backup_path(path), contents) {
+        tracing::warn!("Failed to update the backup of {}: {e}", path.display());
+    }
+    Ok(())
+}
+
+/// Delete `path` and its backup. Files that do not exist are ignored.
+pub fn remove_with_backup(path: &Path) -> Result<(), String> {
+    for file in [path.to_path_buf(), backup_path(path)] {
+        match std::fs::remove_file(&file) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.to_string()),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Loading
+// ---------------------------------------------------------------------------
+
+/// How a file that could not be read was recovered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Recovery {
+    /// The file was unreadable; its backup was loaded instead.
+    Backup,
+    /// The file and its backup were unreadable; nothing was loaded and the
+    /// caller falls back to defaults.
+    Defaults,
+}
+
+/// Result of [`load_with_backup`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loaded<T> {
+    /// The loaded value; `None` if the file does not exist or neither it
+    /// nor its backup could be read.
+    pub value: Option<T>,
+    /// Set when the file itself could not be read.
+    pub recovery: Option<Recovery>,
+}
+
+/// Where [`set_aside`] keeps an unreadable `path`, e.g.
+/// `settings.json.corrupt`.
+pub fn corrupt_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".corrupt");
+    path.with_file_name(name)
+}
+
+/// Rename the copies of `path` that `recovery` found unreadable to
+/// `<name>.corrupt`: the file itself, and with [`Recovery::Defaults`] its
+/// backup too. Call it before rewriting a recovered file. An earlier
+/// `.corrupt` copy is replaced.
+///
+/// Returns the paths the files were moved to.
+pub fn set_aside(path: &Path, recovery: Recovery) -> Result<Vec<PathBuf>, String> {
+    let mut files = vec![path.to_path_buf()];
+    if recovery == Recovery::Defaults {
+        files.push(backup_path(path));
+    }
+    let mut moved = Vec::new();
+    for file in files.into_iter().filter(|f| f.exists()) {
+        let target = corrupt_path(&file);
+        std::fs::rename(&file, &target)
+            .map_err(|e| format!("cannot move {} aside: {e}", file.display()))?;
+        moved.push(target);
+    }
+    Ok(moved)
+}
+
+/// Load `path` with `load`, falling back to its backup if that fails.
+///
+/// A missing file is not an error: it is how a first run (or a reset)
+/// looks, so the backup is not consulted and nothing is recovered.
+pub fn load_with_backup<T>(path: &Path, load: impl Fn(&Path) -> Result<T, String>) -> Loaded<T> {
+    if !path.exists() {
+        return Loaded {
+            value: None,
+            recovery: None,
+        };
+    }
+    let error = match load(path) {
+        Ok(value) => {
+            return Loaded {
+                value: Some(value),
+                recovery: None,
+            }
+        }
+        Err(e) => e,
+    };
+    let backup = backup_path(path);
+    let from_backup = if backup.exists() {
+        load(&backup)
+    } else {
+        Err("no backup".to_string())
+    };
+    match from_backup {
+        Ok(value) => {
+            tracing::warn!(
+                "{} is unreadable ({error}); loaded its backup",
+                path.display()
+            );
+            Loaded {
+                value: Some(value),
+                recovery: Some(Recovery::Backup),
+            }
+        }
+        Err(backup_error) => {
+            tracing::warn!(
+                "{} is unreadable ({error}) and so is its backup ({backup_error}); using defaults",
+                path.display()
+            );
+            Loaded {
+                value: None,
+                recovery: Some(Recovery::Defaults),
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_testing::ScratchDir;
+
+    fn load_number(path: &Path) -> Result<u32, String> {
+        let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        data.trim().parse().map_err(|e| format!("{e}"))
+    }
+
+    #[test]
+    fn writes_keep_a_complete_backup() {
+        let dir = ScratchDir::new("persist-write");
+        let path = dir.join("settings.json");
+        write_with_backup(&path, b"1").unwrap();
+        write_with_backup(&path, b"2").unwrap();
+        let main = std::fs::read_to_string(&path).unwrap();
+        let backup = std::fs::read_to_string(backup_path(&path)).unwrap();
+        let temp_left = dir.join("settings.json.tmp").exists();
+        assert_eq!(main, "2");
+        assert_eq!(backup, "2");
+        assert!(!temp_left);
+        assert_eq!(backup_path(&path), dir.join("settings.json.bak"));
+    }
+
+    #[test]
+    fn a_truncated_file_falls_back_to_its_backup_then_to_defaults() {
+        let dir = ScratchDir::new("persist-load");
+        let path = dir.join("settings.json");
+
+        let loaded = load_with_backup(&path, load_number);
+        assert_eq!(
+            loaded,
+            Loaded {
+                value: None,
+                recovery: None
+            }
+        );
+
+        write_with_backup(&path, b"42").unwrap();
+        assert_eq!(load_with_backup(&path, load_number).value, Some(42));
+
+        // A write torn by a power loss on a filesystem without atomic renames.
+        std::fs::write(&path, b"4x").unwrap();
+        let recovered = load_with_backup(&path, load_number);
+
+        std::fs::write(backup_path(&path), b"").unwrap();
+        let defaults = load_with_backup(&path, load_number);
+
+        remove_with_backup(&path).unwrap();
+        let removed = !path.exists() && !backup_path(&path).exists();
+
+        assert_eq!(
+            recovered,
+            Loaded {
+                value: Some(42),
+                recovery: Some(Recovery::Backup)
+            }
+        );
+        assert_eq!(
+            defaults,
+            Loaded {
+                value: None,
+                recovery: Some(Recovery::Defaults)
+            }
+        );
+        assert!(removed);
+    }
+
+    #[test]
+    fn unreadable_files_are_set_aside_before_a_rewrite() {
+        let dir = ScratchDir::new("persist-set-aside");
+        let path = dir.join("settings.json");
+        write_with_backup(&path, b"42").unwrap();
+        std::fs::write(&path, b"4x").unwrap();
+
+        let recovery = load_with_backup(&path, load_number).recovery.unwrap();
+        let moved = set_aside(&path, recovery).unwrap();
+        assert_eq!(moved, vec![dir.join("settings.json.corrupt")]);
+        assert_eq!(std::fs::read_to_string(&moved[0]).unwrap(), "4x");
+        assert!(!path.exists());
+        assert!(backup_path(&path).exists());
+
+        // Neither copy is readable: both are kept.
+        write_with_backup(&path, b"").unwrap();
+        let recovery = load_with_backup(&path, load_number).recovery.unwrap();
+        assert_eq!(recovery, Recovery::Defaults);
+        let moved = set_aside(&path, recovery).unwrap();
+        assert_eq!(
+            moved,
+            vec![corrupt_path(&path), corrupt_path(&backup_path(&path))]
+        );
+        assert_eq!(std::fs::read_to_string(&moved[0]).unwrap(), "");
+        assert!(!path.exists() && !backup_path(&path).exists());
+    }
+}
@@ -0,0 +1,166 @@
+//! Power guard — a last check on the power source before the action runs.
+//!
+//! Some actions are risky on a draining battery (a hibernate image write
+//! can fail part-way). [`PowerRules`] describe when running is allowed,
+//! and a [`PowerGuard`] evaluates them against a [`PowerStateProvider`]
+//! right before [`ActionScheduler::run_pending_action`] executes the
+//! action. A blocked run is skipped with a reason instead of failing.
+//!
+//! Unknown values never block: a desktop without a battery passes the
+//! battery rule, and if the power state cannot be read at all the guard
+//! logs a warning and lets the action run.
+//!
+//! [`ActionScheduler::run_pending_action`]: crate::ActionScheduler::run_pending_action
+
+use flowwatcher_platform::power::{PowerStateProvider, PowerStatus};
+use serde::{Deserialize, Serialize};
+
+// ---------------------------------------------------------------------------
+// Rules
+// ---------------------------------------------------------------------------
+
+/// When the action may run, judged by the power source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PowerRules {
+    /// Only run while on AC power.
+    pub require_ac_power: bool,
+    /// While on battery, only run with at least this much charge (0–100).
+    pub min_battery_percent: Option<u8>,
+}
+
+impl PowerRules {
+    /// Whether any rule is set.
+    pub fn is_active(&self) -> bool {
+        self.require_ac_power || self.min_battery_percent.is_some()
+    }
+
+    /// Why `status` blocks the action, or `None` if it may run.
+    pub fn blocking_reason(&self, status: &PowerStatus) -> Option<String> {
+        if self.require_ac_power && status.on_ac_power == Some(false) {
+            return Some("running on battery power, but AC power is required".to_string());
+        }
+        let min = self.min_battery_percent?;
+        match (status.on_ac_power, status.battery_percent) {
+            (Some(true), _) => None,
+            (_, Some(percent)) if percent < min => {
+                Some(format!("battery at {percent}%, below the required {min}%"))
+            }
+            _ => None,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PowerGuard
+// ---------------------------------------------------------------------------
+
+/// [`PowerRules`] plus the provider they are checked against.
+pub struct PowerGuard {
+    /// The rules to enforce.
+    rules: PowerRules,
+    /// Source of the current power state.
+    provider: Box<dyn PowerStateProvider>,
+}
+
+impl PowerGuard {
+    /// Check `rules` against `provider`.
+    pub fn new(rules: PowerRules, provider: Box<dyn PowerStateProvider>) -> Self {
+        Self { rules, provider }
+    }
+
+    /// The rules being enforced.
+    pub fn rules(&self) -> &PowerRules {
+        &self.rules
+    }
+
+    /// Read the power state now. Returns why the action must not run, or
+    /// `None` if it may (including when the state cannot be read).
+    pub fn check(&self) -> Option<String> {
+        if !self.rules.is_active() {
+            return None;
+        }
+        match self.provider.power_status() {
+            Ok(status) => self.rules.blocking_reason(&status),
+            Err(e) => {
+                tracing::warn!("Power guard could not read the power state, allowing: {e}");
+                None
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for PowerGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PowerGuard")
+            .field("rules", &self.rules)
+            .finish_non_exhaustive()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_platform::power::PowerError;
+
+    fn status(on_ac_power: Option<bool>, battery_percent: Option<u8>) -> PowerStatus {
+        PowerStatus {
+            on_ac_power,
+            battery_percent,
+        }
+    }
+
+    struct Unreadable;
+
+    impl PowerStateProvider for Unreadable {
+        fn power_status(&self) -> Result<PowerStatus, PowerError> {
+            Err(PowerError::NotSupported("test".into()))
+        }
+    }
+
+    #[test]
+    fn battery_rule_only_applies_on_battery() {
+        let rules = PowerRules {
+            min_battery_percent: Some(20),
+            ..PowerRules::default()
+        };
+        let reason = rules.blocking_reason(&status(Some(false), Some(15)));
+        assert_eq!(
+            reason.as_deref(),
+            Some("battery at 15%, below the required 20%")
+        );
+        assert_eq!(rules.blocking_reason(&status(Some(false), Some(20))), None);
+        assert_eq!(rules.blocking_reason(&status(Some(true), Some(5))), None);
+        // No battery (desktop).
+        assert_eq!(rules.blocking_reason(&status(Some(true), None)), None);
+    }
+
+    #[test]
+    fn ac_rule_blocks_on_battery_only() {
+        let rules = PowerRules {
+            require_ac_power: true,
+            ..PowerRules::default()
+        };
+        assert!(rules
+            .blocking_reason(&status(Some(false), Some(90)))
+            .is_some());
+        assert_eq!(rules.blocking_reason(&status(Some(true), Some(90))), None);
+        assert_eq!(rules.blocking_reason(&status(None, None)), None);
+    }
+
+    #[test]
+    fn unreadable_state_allows_the_action() {
+        let guard = PowerGuard::new(
+            PowerRules {
+                require_ac_power: true,
+                ..PowerRules::default()
+            },
+            Box::new(Unreadable),
+        );
+        assert_eq!(guard.check(), None);
+    }
+}
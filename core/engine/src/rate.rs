@@ -0,0 +1,295 @@
+//! Byte rates with human-readable parsing and formatting.
+//!
+//! Thresholds are stored in bytes per second, but people think in
+//! "200 KB/s" or "8 Mbps". [`ByteRate`] parses such strings and formats
+//! rates the way the dashboard and tray show them.
+//!
+//! Byte units are binary, as Windows shows them: `KB` and `KiB` are both
+//! 1024 bytes. Bit units (a lowercase `b`, `bit`, or a `bps` suffix) follow
+//! the network convention: `kbps` is 1000 bits, `Kibps` 1024 bits.
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+// ---------------------------------------------------------------------------
+// Errors
+// ---------------------------------------------------------------------------
+
+/// A byte rate string that could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ByteRateError {
+    /// The string is empty.
+    #[error("empty byte rate")]
+    Empty,
+
+    /// The numeric part is missing or not a non-negative number.
+    #[error("invalid number in byte rate \"{0}\"")]
+    InvalidNumber(String),
+
+    /// The unit is not recognised.
+    #[error("unknown unit \"{0}\" (expected e.g. B/s, KB/s, MiB/s or kbps)")]
+    UnknownUnit(String),
+
+    /// The rate does not fit in 64 bits.
+    #[error("byte rate \"{0}\" is too large")]
+    TooLarge(String),
+}
+
+// ---------------------------------------------------------------------------
+// ByteRate
+// ---------------------------------------------------------------------------
+
+/// A rate in bytes per second.
+///
+/// Serializes as a plain number; deserializes from a number or from a
+/// string such as `"200KB/s"`, `"1.5 MiB/s"` or `"500 kbps"`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteRate(u64);
+
+impl ByteRate {
+    /// A rate of `bytes_per_sec`.
+    pub const fn new(bytes_per_sec: u64) -> Self {
+        Self(bytes_per_sec)
+    }
+
+    /// The rate in bytes per second.
+    pub const fn bytes_per_sec(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for ByteRate {
+    fn from(bytes_per_sec: u64) -> Self {
+        Self(bytes_per_sec)
+    }
+}
+
+impl From<ByteRate> for u64 {
+    fn from(rate: ByteRate) -> Self {
+        rate.0
+    }
+}
+
+impl FromStr for ByteRate {
+    type Err = ByteRateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ByteRateError::Empty);
+        }
+        let split = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let value: f64 = number
+            .parse()
+            .map_err(|_| ByteRateError::InvalidNumber(s.to_string()))?;
+        let multiplier = unit_multiplier(unit.trim())
+            .ok_or_else(|| ByteRateError::UnknownUnit(unit.trim().into()))?;
+        let bytes = (value * multiplier).round();
+        if bytes >= u64::MAX as f64 {
+            return Err(ByteRateError::TooLarge(s.to_string()));
+        }
+        Ok(Self(bytes as u64))
+    }
+}
+
+/// Bytes per second in one `unit`, e.g. 1024 for `KB/s`, 125 for `kbps`.
+/// An empty unit means bytes per second.
+fn unit_multiplier(unit: &str) -> Option<f64> {
+    let unit = unit
+        .strip_suffix("/s")
+        .or_else(|| unit.strip_suffix("/sec"))
+        .unwrap_or(unit);
+    let (unit, per_sec_bits) = match unit.strip_suffix("ps") {
+        // "bps", "kbps", "Mbps": bits per second, unless spelled "Bps".
+        Some(prefix) if prefix.ends_with('B') => (prefix, false),
+        Some(prefix) => (prefix.strip_suffix('b').unwrap_or(prefix), true),
+        None => (unit, false),
+    };
+
+    let (prefix, bits) = if per_sec_bits {
+        (unit, true)
+    } else if let Some(prefix) = ["bytes", "byte", "B"]
+        .iter()
+        .find_map(|u| unit.strip_suffix(u))
+    {
+        (prefix, false)
+    } else if let Some(prefix) = ["bits", "bit", "b"]
+        .iter()
+        .find_map(|u| unit.strip_suffix(u))
+    {
+        (prefix, true)
+    } else if unit.is_empty() {
+        ("", false)
+    } else {
+        return None;
+    };
+
+    let (prefix, binary) = match prefix.strip_suffix('i') {
+        Some(prefix) if !prefix.is_empty() => (prefix, true),
+        _ => (prefix, false),
+    };
+    let power = match prefix.to_ascii_lowercase().as_str() {
+        "" => 0,
+        "k" => 1,
+        "m" => 2,
+        "g" => 3,
+        "t" => 4,
+        _ => return None,
+    };
+    // Byte prefixes are binary either way; bit prefixes only with `i`.
+    let base: f64 = if binary || !bits { 1024.0 } else { 1000.0 };
+    let scale = base.powi(power);
+    Some(if bits { scale / 8.0 } else { scale })
+}
+
+impl fmt::Display for ByteRate {
+    /// Format with the largest fitting unit, matching the tray and the
+    /// frontend's `formatSpeed`: `512 B/s`, `1.5 KB/s`, `1.20 MB/s`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const KB: f64 = 1024.0;
+        let b = self.0 as f64;
+        if b < KB {
+            write!(f, "{} B/s", self.0)
+        } else if b < KB * KB {
+            write!(f, "{:.1} KB/s", b / KB)
+        } else if b < KB * KB * KB {
+            write!(f, "{:.2} MB/s", b / (KB * KB))
+        } else {
+            write!(f, "{:.2} GB/s", b / (KB * KB * KB))
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Serde
+// ---------------------------------------------------------------------------
+
+impl Serialize for ByteRate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteRate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ByteRateVisitor)
+    }
+}
+
+/// Accepts a non-negative integer or a byte rate string.
+struct ByteRateVisitor;
+
+impl Visitor<'_> for ByteRateVisitor {
+    type Value = ByteRate;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("bytes per second, or a rate such as \"200 KB/s\"")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<ByteRate, E> {
+        Ok(ByteRate(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<ByteRate, E> {
+        u64::try_from(v)
+            .map(ByteRate)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<ByteRate, E> {
+        if v.is_finite() && v >= 0.0 && v < u64::MAX as f64 {
+            Ok(ByteRate(v.round() as u64))
+        } else {
+            Err(E::invalid_value(de::Unexpected::Float(v), &self))
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<ByteRate, E> {
+        v.parse().map_err(E::custom)
+    }
+}
+
+/// Deserialize a `u64` bytes-per-second field from a number or a byte
+/// rate string, for use with `#[serde(deserialize_with = "...")]`.
+pub fn deserialize_bytes_per_sec<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<u64, D::Error> {
+    ByteRate::deserialize(deserializer).map(u64::from)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> u64 {
+        s.parse::<ByteRate>().unwrap().bytes_per_sec()
+    }
+
+    #[test]
+    fn parses_byte_and_bit_units() {
+        assert_eq!(parse("204800"), 204_800);
+        assert_eq!(parse("200 B/s"), 200);
+        assert_eq!(parse("200KB/s"), 204_800);
+        assert_eq!(parse("200 kB/s"), 204_800);
+        assert_eq!(parse("200 KiB/s"), 204_800);
+        assert_eq!(parse("1.5 MiB/s"), 1_572_864);
+        assert_eq!(parse("2 MBps"), 2_097_152);
+        assert_eq!(parse("500 kbps"), 62_500);
+        assert_eq!(parse("200 Kb/s"), 25_000);
+        assert_eq!(parse("8 Mbit/s"), 1_000_000);
+        assert_eq!(parse("8 Mibps"), 1_048_576);
+        assert_eq!(parse(" 1 GB "), 1_073_741_824);
+    }
+
+    #[test]
+    fn rejects_malformed_rates() {
+        assert_eq!("".parse::<ByteRate>(), Err(ByteRateError::Empty));
+        assert!(matches!(
+            "fast".parse::<ByteRate>(),
+            Err(ByteRateError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            "-5 KB/s".parse::<ByteRate>(),
+            Err(ByteRateError::InvalidNumber(_))
+        ));
+        assert_eq!(
+            "5 furlongs".parse::<ByteRate>(),
+            Err(ByteRateError::UnknownUnit("furlongs".into()))
+        );
+        assert!(matches!(
+            "99999999 TB/s".parse::<ByteRate>(),
+            Err(ByteRateError::TooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn displays_with_sensible_units() {
+        assert_eq!(ByteRate::new(512).to_string(), "512 B/s");
+        assert_eq!(ByteRate::new(1536).to_string(), "1.5 KB/s");
+        assert_eq!(ByteRate::new(1_258_291).to_string(), "1.20 MB/s");
+        assert_eq!(ByteRate::new(3 << 30).to_string(), "3.00 GB/s");
+        // Formatting and parsing agree.
+        assert_eq!(parse(&ByteRate::new(204_800).to_string()), 204_800);
+    }
+
+    #[test]
+    fn serde_accepts_numbers_and_strings() {
+        let rate: ByteRate = serde_json::from_str("1024").unwrap();
+        assert_eq!(rate, ByteRate::new(1024));
+        let rate: ByteRate = serde_json::from_str("\"200 KB/s\"").unwrap();
+        assert_eq!(rate, ByteRate::new(204_800));
+        assert!(serde_json::from_str::<ByteRate>("-1").is_err());
+        assert!(serde_json::from_str::<ByteRate>("\"soon\"").is_err());
+        assert_eq!(serde_json::to_string(&rate).unwrap(), "204800");
+    }
+}
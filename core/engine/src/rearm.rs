@@ -0,0 +1,209 @@
+//! Re-arming monitoring after the action runs or is cancelled.
+//!
+//! By default a session ends once its action has executed
+//! ([`RepeatMode::OneShot`]). With [`RepeatMode::Repeat`] the host resets
+//! the scheduler and the condition and keeps monitoring, so e.g. the
+//! screen locks again the next time the network goes idle. A
+//! [`RearmGate`] holds condition evaluation back during the cooldown and,
+//! after a user cancel, until traffic has picked up once.
+
+use flowwatcher_conditions::ConditionResult;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// ---------------------------------------------------------------------------
+// Repeat mode
+// ---------------------------------------------------------------------------
+
+/// What happens after the action has executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum RepeatMode {
+    /// The session ends.
+    #[default]
+    OneShot,
+    /// Monitoring resumes after `cooldown_secs`.
+    Repeat {
+        /// Seconds after the action before the condition is evaluated
+        /// again.
+        #[serde(default)]
+        cooldown_secs: u64,
+    },
+}
+
+impl RepeatMode {
+    /// The cooldown before monitoring resumes, or `None` if the session
+    /// ends after the action.
+    pub fn cooldown(&self) -> Option<Duration> {
+        match self {
+            Self::OneShot => None,
+            Self::Repeat { cooldown_secs } => Some(Duration::from_secs(*cooldown_secs)),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Rearm gate
+// ---------------------------------------------------------------------------
+
+/// Why evaluation resumed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rearm {
+    /// The cooldown after an executed action ended.
+    CooldownEnded,
+    /// Traffic went above the threshold after a cancel.
+    ActivityResumed,
+}
+
+impl fmt::Display for Rearm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CooldownEnded => write!(f, "Re-armed after the cooldown"),
+            Self::ActivityResumed => write!(f, "Re-armed after traffic resumed"),
+        }
+    }
+}
+
+/// Holds condition evaluation back after the action ran or was cancelled.
+///
+/// Call [`poll`](Self::poll) every iteration with the loop's clock; skip
+/// evaluation while [`is_cooling_down`](Self::is_cooling_down), and pass
+/// each result through [`filter`](Self::filter) before acting on it.
+#[derive(Debug, Default)]
+pub struct RearmGate {
+    /// A cooldown not yet anchored to the loop's clock.
+    cooldown: Option<Duration>,
+    /// When the running cooldown ends.
+    cooling_until: Option<Instant>,
+    /// Whether `Met` is held back until the condition reports `Waiting`.
+    awaiting_activity: bool,
+}
+
+impl RearmGate {
+    /// A gate that holds nothing back.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold evaluation back for `cooldown`, counted from the next
+    /// [`poll`](Self::poll).
+    pub fn start_cooldown(&mut self, cooldown: Duration) {
+        self.cooldown = Some(cooldown);
+        self.cooling_until = None;
+    }
+
+    /// Hold `Met` back until the monitored speed has gone above the
+    /// threshold once, so a cancelled action does not come straight back.
+    pub fn require_activity(&mut self) {
+        self.awaiting_activity = true;
+    }
+
+    /// Whether a cooldown is running.
+    pub fn is_cooling_down(&self) -> bool {
+        self.cooldown.is_some() || self.cooling_until.is_some()
+    }
+
+    /// Whether `Met` is held back until traffic picks up.
+    pub fn is_awaiting_activity(&self) -> bool {
+        self.awaiting_activity
+    }
+
+    /// Advance to `now`. Returns [`Rearm::CooldownEnded`] once, when the
+    /// cooldown is over; the caller resets the condition then.
+    pub fn poll(&mut self, now: Instant) -> Option<Rearm> {
+        if let Some(cooldown) = self.cooldown.take() {
+            self.cooling_until = Some(now + cooldown);
+        }
+        match self.cooling_until {
+            Some(until) if now >= until => {
+                self.cooling_until = None;
+                Some(Rearm::CooldownEnded)
+            }
+            _ => None,
+        }
+    }
+
+    /// Pass a condition result through the gate. While awaiting activity
+    /// every result reads as `Waiting`; the first real `Waiting` opens the
+    /// gate and returns [`Rearm::ActivityResumed`].
+    pub fn filter(&mut self, result: ConditionResult) -> (ConditionResult, Option<Rearm>) {
+        if !self.awaiting_activity {
+            return (result, None);
+        }
+        if result == ConditionResult::Waiting {
+            self.awaiting_activity = false;
+            return (result, Some(Rearm::ActivityResumed));
+        }
+        (ConditionResult::Waiting, None)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeat_mode_serializes_with_a_mode_tag() {
+        let repeat: RepeatMode =
+            serde_json::from_str(r#"{"mode":"repeat","cooldown_secs":60}"#).unwrap();
+        assert_eq!(repeat, RepeatMode::Repeat { cooldown_secs: 60 });
+        assert_eq!(repeat.cooldown(), Some(Duration::from_secs(60)));
+        let repeat: RepeatMode = serde_json::from_str(r#"{"mode":"repeat"}"#).unwrap();
+        assert_eq!(repeat.cooldown(), Some(Duration::ZERO));
+        assert_eq!(
+            serde_json::to_string(&RepeatMode::OneShot).unwrap(),
+            r#"{"mode":"one_shot"}"#
+        );
+        assert_eq!(RepeatMode::default().cooldown(), None);
+    }
+
+    #[test]
+    fn cooldown_counts_from_the_next_poll() {
+        let mut gate = RearmGate::new();
+        let t0 = Instant::now();
+        assert_eq!(gate.poll(t0), None);
+        assert!(!gate.is_cooling_down());
+
+        gate.start_cooldown(Duration::from_secs(30));
+        assert!(gate.is_cooling_down());
+        assert_eq!(gate.poll(t0 + Duration::from_secs(100)), None);
+        assert_eq!(gate.poll(t0 + Duration::from_secs(129)), None);
+        assert!(gate.is_cooling_down());
+        assert_eq!(
+            gate.poll(t0 + Duration::from_secs(130)),
+            Some(Rearm::CooldownEnded)
+        );
+        assert!(!gate.is_cooling_down());
+        assert_eq!(gate.poll(t0 + Duration::from_secs(131)), None);
+
+        // Without a cooldown the next poll re-arms straight away.
+        gate.start_cooldown(Duration::ZERO);
+        assert_eq!(gate.poll(t0), Some(Rearm::CooldownEnded));
+    }
+
+    #[test]
+    fn activity_gate_opens_on_the_first_waiting() {
+        let mut gate = RearmGate::new();
+        let met = ConditionResult::Met;
+        assert_eq!(gate.filter(met.clone()), (met.clone(), None));
+
+        gate.require_activity();
+        assert_eq!(gate.filter(met.clone()), (ConditionResult::Waiting, None));
+        assert_eq!(
+            gate.filter(ConditionResult::in_progress(5, 60)),
+            (ConditionResult::Waiting, None)
+        );
+        assert!(gate.is_awaiting_activity());
+        assert_eq!(
+            gate.filter(ConditionResult::Waiting),
+            (ConditionResult::Waiting, Some(Rearm::ActivityResumed))
+        );
+        assert!(!gate.is_awaiting_activity());
+        assert_eq!(gate.filter(met.clone()), (met, None));
+    }
+}
@@ -0,0 +1,645 @@
+//! Session recording and replay.
+//!
+//! A [`SessionRecorder`] captures every step the monitoring loop sees
+//! (trigger data, condition result, scheduler state and events) into a
+//! compact JSONL file: one header line, then one line per sample. A
+//! [`SessionReplayer`] feeds a capture back through a [`MonitoringEngine`]
+//! driven by a [`VirtualClock`], reproducing the original decision
+//! sequence offline. The real action is never executed during replay.
+
+use crate::clock::VirtualClock;
+use crate::monitor::{EngineError, MonitoringEngine, StepOutcome};
+use crate::scheduler::{ActionScheduler, SchedulerEvent, SchedulerState};
+use async_trait::async_trait;
+use flowwatcher_actions::{Action, ActionError, ActionInfo};
+use flowwatcher_conditions::{ConditionResult, MonitorMode, ThresholdCondition};
+use flowwatcher_triggers::{Trigger, TriggerError, TriggerState};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Version of the capture format written by this build.
+pub const FORMAT_VERSION: u32 = 1;
+
+// ---------------------------------------------------------------------------
+// Error types
+// ---------------------------------------------------------------------------
+
+/// Errors from reading a session capture.
+#[derive(Debug, Error)]
+pub enum RecordingError {
+    /// The capture could not be read.
+    #[error("failed to read capture: {0}")]
+    Io(#[from] io::Error),
+
+    /// A line is not a valid record.
+    #[error("invalid record on line {line}: {source}")]
+    Parse {
+        /// 1-based line number.
+        line: usize,
+        /// The underlying JSON error.
+        source: serde_json::Error,
+    },
+
+    /// The first record is not a header.
+    #[error("capture does not start with a session header")]
+    MissingHeader,
+
+    /// The capture was written by an incompatible version.
+    #[error("unsupported capture format version: {0}")]
+    UnsupportedVersion(u32),
+}
+
+// ---------------------------------------------------------------------------
+// Capture format
+// ---------------------------------------------------------------------------
+
+/// Configuration needed to rebuild the pipeline for replay.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionHeader {
+    /// Capture format version (see [`FORMAT_VERSION`]).
+    pub format_version: u32,
+    /// Type id of the recorded trigger (informational).
+    pub trigger_type: String,
+    /// Threshold condition: speed threshold in bytes per second.
+    pub threshold_bytes_per_sec: u64,
+    /// Threshold condition: required duration in seconds.
+    pub required_duration_secs: u64,
+    /// Threshold condition: traffic direction(s).
+    pub monitor_mode: MonitorMode,
+    /// Threshold condition: hysteresis dead band in bytes per second.
+    #[serde(default)]
+    pub hysteresis_bytes_per_sec: u64,
+    /// Threshold condition: tolerated consecutive spikes.
+    #[serde(default)]
+    pub max_spike_samples: u32,
+    /// Threshold condition: longest tolerated spike in seconds.
+    #[serde(default)]
+    pub max_spike_duration_secs: Option<u64>,
+    /// Scheduler pre-warning duration in seconds.
+    pub pre_warning_secs: u64,
+    /// Scheduler countdown duration in seconds.
+    pub countdown_secs: u64,
+}
+
+impl SessionHeader {
+    /// Describe a threshold-condition pipeline.
+    pub fn new(
+        trigger_type: impl Into<String>,
+        condition: &ThresholdCondition,
+        pre_warning_secs: u64,
+        countdown_secs: u64,
+    ) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            trigger_type: trigger_type.into(),
+            threshold_bytes_per_sec: condition.threshold_bytes_per_sec,
+            required_duration_secs: condition.required_duration_secs,
+            monitor_mode: condition.monitor_mode,
+            hysteresis_bytes_per_sec: condition.hysteresis_bytes_per_sec,
+            max_spike_samples: condition.max_spike_samples,
+            max_spike_duration_secs: condition.max_spike_duration_secs,
+            pre_warning_secs,
+            countdown_secs,
+        }
+    }
+}
+
+/// One recorded step of the monitoring loop.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedSample {
+    /// Milliseconds since the first sample.
+    pub t_ms: u64,
+    /// Trigger output, if the trigger was evaluated this step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<TriggerState>,
+    /// Condition result, if the condition was evaluated this step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<ConditionResult>,
+    /// Scheduler state after the step.
+    pub scheduler: SchedulerState,
+    /// Scheduler events emitted during the step.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<SchedulerEvent>,
+    /// Whether the action ran during the step.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub executed: bool,
+}
+
+/// A single JSONL line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum SessionRecord {
+    Header(SessionHeader),
+    Sample(RecordedSample),
+}
+
+// ---------------------------------------------------------------------------
+// SessionRecorder
+// ---------------------------------------------------------------------------
+
+/// Writes a session capture, one JSON object per line.
+pub struct SessionRecorder {
+    writer: Box<dyn Write + Send>,
+    started: Option<Instant>,
+    samples: usize,
+}
+
+impl SessionRecorder {
+    /// Start a capture on `writer`, writing the header immediately.
+    pub fn new(writer: impl Write + Send + 'static, header: &SessionHeader) -> io::Result<Self> {
+        let mut recorder = Self {
+            writer: Box::new(writer),
+            started: None,
+            samples: 0,
+        };
+        recorder.write_record(&SessionRecord::Header(header.clone()))?;
+        Ok(recorder)
+    }
+
+    /// Start a capture in a new file (parent directories are created).
+    pub fn create(path: &Path, header: &SessionHeader) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = std::fs::File::create(path)?;
+        Self::new(BufWriter::new(file), header)
+    }
+
+    /// Record one step observed at `now`.
+    pub fn record(
+        &mut self,
+        now: Instant,
+        trigger: Option<&TriggerState>,
+        outcome: &StepOutcome,
+    ) -> io::Result<()> {
+        let started = *self.started.get_or_insert(now);
+        let sample = RecordedSample {
+            t_ms: now.saturating_duration_since(started).as_millis() as u64,
+            trigger: trigger.cloned(),
+            condition: outcome.condition.clone(),
+            scheduler: outcome.scheduler,
+            events: outcome.events.clone(),
+            executed: outcome.executed,
+        };
+        self.write_record(&SessionRecord::Sample(sample))?;
+        self.samples += 1;
+        Ok(())
+    }
+
+    /// Number of samples recorded so far.
+    pub fn sample_count(&self) -> usize {
+        self.samples
+    }
+
+    /// Flush buffered output. Call before dropping the recorder.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn write_record(&mut self, record: &SessionRecord) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SessionReplayer
+// ---------------------------------------------------------------------------
+
+/// The outcome of replaying a capture.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayReport {
+    /// Samples produced by the replay, in order.
+    pub replayed: Vec<RecordedSample>,
+    /// Index of the first sample that differs from the capture, if any.
+    pub divergence: Option<usize>,
+}
+
+impl ReplayReport {
+    /// Whether the replay reproduced the capture exactly.
+    pub fn is_identical(&self) -> bool {
+        self.divergence.is_none()
+    }
+}
+
+/// Loads a capture and re-runs it through a fresh engine.
+#[derive(Debug, Clone)]
+pub struct SessionReplayer {
+    header: SessionHeader,
+    samples: Vec<RecordedSample>,
+}
+
+impl SessionReplayer {
+    /// Parse a capture from any buffered reader. Blank lines are ignored.
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, RecordingError> {
+        let mut header = None;
+        let mut samples = Vec::new();
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: SessionRecord =
+                serde_json::from_str(&line).map_err(|source| RecordingError::Parse {
+                    line: index + 1,
+                    source,
+                })?;
+            match (record, &header) {
+                (SessionRecord::Header(h), None) => {
+                    if h.format_version != FORMAT_VERSION {
+                        return Err(RecordingError::UnsupportedVersion(h.format_version));
+                    }
+                    header = Some(h);
+                }
+                (SessionRecord::Sample(s), Some(_)) => samples.push(s),
+                _ => return Err(RecordingError::MissingHeader),
+            }
+        }
+
+        Ok(Self {
+            header: header.ok_or(RecordingError::MissingHeader)?,
+            samples,
+        })
+    }
+
+    /// Load a capture file.
+    pub fn open(path: &Path) -> Result<Self, RecordingError> {
+        let file = std::fs::File::open(path)?;
+        Self::from_reader(BufReader::new(file))
+    }
+
+    /// The capture's header.
+    pub fn header(&self) -> &SessionHeader {
+        &self.header
+    }
+
+    /// The recorded samples.
+    pub fn samples(&self) -> &[RecordedSample] {
+        &self.samples
+    }
+
+    /// Replay the capture and compare the resulting decisions.
+    ///
+    /// Recorded trigger outputs are fed back in order while a virtual clock
+    /// is set to each sample's timestamp, so time-based conditions see the
+    /// same elapsed durations as during the original session.
+    pub async fn replay(&self) -> Result<ReplayReport, EngineError> {
+        let clock = VirtualClock::new();
+        let base = crate::clock::Clock::now(&clock);
+
+        let trigger = ReplayTrigger {
+            trigger_type: self.header.trigger_type.clone(),
+            states: self
+                .samples
+                .iter()
+                .filter_map(|s| s.trigger.clone())
+                .collect(),
+        };
+        let condition = ThresholdCondition::new(
+            self.header.threshold_bytes_per_sec,
+            self.header.required_duration_secs,
+            self.header.monitor_mode,
+        )
+        .with_hysteresis(self.header.hysteresis_bytes_per_sec)
+        .with_max_spike_samples(self.header.max_spike_samples)
+        .with_max_spike_duration_secs(self.header.max_spike_duration_secs);
+        let scheduler =
+            ActionScheduler::new(self.header.pre_warning_secs, self.header.countdown_secs);
+
+        let mut engine = MonitoringEngine::new(
+            Box::new(trigger),
+            Box::new(condition),
+            Box::new(ReplayAction),
+            scheduler,
+        )
+        .with_clock(Arc::new(clock.clone()));
+
+        let buffer = SharedBuffer::default();
+        let recorder =
+            SessionRecorder::new(buffer.clone(), &self.header).map_err(RecordingError::from)?;
+        engine.start_recording(recorder);
+        engine.start().await?;
+        for sample in &self.samples {
+            clock.set(base + Duration::from_millis(sample.t_ms));
+            engine.step().await?;
+        }
+        engine.stop().await?;
+        if let Some(recorder) = engine.stop_recording() {
+            recorder.finish().map_err(RecordingError::from)?;
+        }
+
+        let bytes = buffer.contents();
+        let mut replayed = SessionReplayer::from_reader(bytes.as_slice())?.samples;
+        adopt_deadlines(&self.samples, &mut replayed);
+        let divergence = first_divergence(&self.samples, &replayed);
+        Ok(ReplayReport {
+            replayed,
+            divergence,
+        })
+    }
+}
+
+/// Give replayed countdowns the recorded wall-clock deadlines, which a
+/// replay cannot reproduce and which do not affect any decision.
+fn adopt_deadlines(expected: &[RecordedSample], replayed: &mut [RecordedSample]) {
+    let expected = expected.iter().flat_map(|s| &s.events);
+    let replayed = replayed.iter_mut().flat_map(|s| &mut s.events);
+    for (e, r) in expected.zip(replayed) {
+        if let (
+            SchedulerEvent::CountdownStarted {
+                deadline_epoch_ms: recorded,
+                ..
+            },
+            SchedulerEvent::CountdownStarted {
+                deadline_epoch_ms, ..
+            },
+        ) = (e, r)
+        {
+            *deadline_epoch_ms = *recorded;
+        }
+    }
+}
+
+/// Index of the first differing sample (or the shorter length).
+fn first_divergence(expected: &[RecordedSample], actual: &[RecordedSample]) -> Option<usize> {
+    expected
+        .iter()
+        .zip(actual)
+        .position(|(e, a)| e != a)
+        .or_else(|| (expected.len() != actual.len()).then(|| expected.len().min(actual.len())))
+}
+
+// ---------------------------------------------------------------------------
+// Replay stand-ins
+// ---------------------------------------------------------------------------
+
+/// Yields recorded trigger outputs in order, then stays idle.
+struct ReplayTrigger {
+    trigger_type: String,
+    states: VecDeque<TriggerState>,
+}
+
+#[async_trait]
+impl Trigger for ReplayTrigger {
+    fn name(&self) -> &str {
+        "Replay"
+    }
+
+    fn trigger_type(&self) -> &str {
+        &self.trigger_type
+    }
+
+    async fn start(&mut self) -> Result<(), TriggerError> {
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<(), TriggerError> {
+        Ok(())
+    }
+
+    async fn evaluate(&mut self) -> Result<TriggerState, TriggerError> {
+        Ok(self.states.pop_front().unwrap_or(TriggerState::Idle))
+    }
+}
+
+/// Stands in for the recorded action so replay never touches the system.
+struct ReplayAction;
+
+#[async_trait]
+impl Action for ReplayAction {
+    fn name(&self) -> &str {
+        "Replay"
+    }
+
+    fn action_type(&self) -> &str {
+        "replay"
+    }
+
+    fn info(&self) -> ActionInfo {
+        ActionInfo {
+            id: "replay".to_string(),
+            name: "Replay".to_string(),
+            description: "No-op stand-in used during session replay".to_string(),
+            available: true,
+            requires_elevation: false,
+            params_schema: None,
+        }
+    }
+
+    async fn validate(&self) -> Result<(), ActionError> {
+        Ok(())
+    }
+
+    async fn execute(&self) -> Result<(), ActionError> {
+        Ok(())
+    }
+}
+
+/// An in-memory writer whose contents outlive the recorder.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn contents(&self) -> Vec<u8> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::Clock;
+    use flowwatcher_testing::MockAction;
+    use flowwatcher_triggers::{TriggerData, TriggerValue};
+
+    /// Replays scripted download speeds (the "live" trigger of a session).
+    struct ScriptedTrigger {
+        speeds: Vec<u64>,
+        index: usize,
+    }
+
+    #[async_trait]
+    impl Trigger for ScriptedTrigger {
+        fn name(&self) -> &str {
+            "Scripted"
+        }
+
+        fn trigger_type(&self) -> &str {
+            "network_idle"
+        }
+
+        async fn start(&mut self) -> Result<(), TriggerError> {
+            Ok(())
+        }
+
+        async fn stop(&mut self) -> Result<(), TriggerError> {
+            Ok(())
+        }
+
+        async fn evaluate(&mut self) -> Result<TriggerState, TriggerError> {
+            let speed = self.speeds[self.index.min(self.speeds.len() - 1)];
+            self.index += 1;
+            let mut data = TriggerData::new();
+            data.insert("download_bps", TriggerValue::U64(speed));
+            data.insert("upload_bps", TriggerValue::U64(0));
+            Ok(TriggerState::Active(data))
+        }
+    }
+
+    /// Record a mock session: traffic drops, stays idle for 3s, action runs.
+    async fn record_session() -> (Vec<u8>, Vec<StepOutcome>) {
+        let clock = VirtualClock::new();
+        let condition = ThresholdCondition::new(100, 3, MonitorMode::DownloadOnly);
+        let header = SessionHeader::new("network_idle", &condition, 1, 2);
+
+        let mut engine = MonitoringEngine::new(
+            Box::new(ScriptedTrigger {
+                speeds: vec![5_000, 4_000, 50, 20, 10, 0],
+                index: 0,
+            }),
+            Box::new(condition),
+            Box::new(MockAction::new()),
+            ActionScheduler::new(1, 2),
+        )
+        .with_clock(Arc::new(clock.clone()));
+
+        let buffer = SharedBuffer::default();
+        engine.start_recording(SessionRecorder::new(buffer.clone(), &header).unwrap());
+
+        let mut outcomes = Vec::new();
+        for _ in 0..12 {
+            let outcome = engine.step().await.unwrap();
+            let done = outcome.executed;
+            outcomes.push(outcome);
+            if done {
+                break;
+            }
+            clock.advance(Duration::from_secs(1));
+        }
+        engine.stop_recording().unwrap().finish().unwrap();
+        (buffer.contents(), outcomes)
+    }
+
+    #[tokio::test]
+    async fn capture_contains_header_and_every_step() {
+        let (bytes, outcomes) = record_session().await;
+        let replayer = SessionReplayer::from_reader(bytes.as_slice()).unwrap();
+
+        assert_eq!(replayer.header().threshold_bytes_per_sec, 100);
+        assert_eq!(replayer.samples().len(), outcomes.len());
+        assert_eq!(replayer.samples()[0].t_ms, 0);
+        assert_eq!(replayer.samples()[1].t_ms, 1_000);
+        assert!(replayer.samples().last().unwrap().executed);
+    }
+
+    #[tokio::test]
+    async fn replay_reproduces_identical_event_sequence() {
+        let (bytes, outcomes) = record_session().await;
+        let replayer = SessionReplayer::from_reader(bytes.as_slice()).unwrap();
+
+        let report = replayer.replay().await.unwrap();
+        assert!(report.is_identical(), "diverged at {:?}", report.divergence);
+        assert_eq!(report.replayed, replayer.samples());
+
+        let recorded_events: Vec<_> = outcomes.into_iter().flat_map(|o| o.events).collect();
+        let replayed_events: Vec<_> = report.replayed.into_iter().flat_map(|s| s.events).collect();
+        assert!(replayed_events.contains(&SchedulerEvent::Executed));
+        assert_eq!(replayed_events, recorded_events);
+    }
+
+    #[tokio::test]
+    async fn tampered_capture_diverges() {
+        let (bytes, _) = record_session().await;
+        let mut replayer = SessionReplayer::from_reader(bytes.as_slice()).unwrap();
+        // Pretend the idle period was much shorter than recorded.
+        for sample in &mut replayer.samples {
+            sample.t_ms /= 10;
+        }
+
+        let report = replayer.replay().await.unwrap();
+        assert!(!report.is_identical());
+    }
+
+    #[test]
+    fn rejects_capture_without_header() {
+        let line = r#"{"kind":"sample","t_ms":0,"scheduler":"Idle"}"#;
+        let result = SessionReplayer::from_reader(line.as_bytes());
+        assert!(matches!(result, Err(RecordingError::MissingHeader)));
+    }
+
+    #[test]
+    fn rejects_unknown_format_version() {
+        let condition = ThresholdCondition::new(1, 1, MonitorMode::Both);
+        let mut header = SessionHeader::new("network_idle", &condition, 0, 0);
+        header.format_version = 99;
+        let line = serde_json::to_string(&SessionRecord::Header(header)).unwrap();
+
+        let result = SessionReplayer::from_reader(line.as_bytes());
+        assert!(matches!(
+            result,
+            Err(RecordingError::UnsupportedVersion(99))
+        ));
+    }
+
+    #[test]
+    fn reports_malformed_line_number() {
+        let condition = ThresholdCondition::new(1, 1, MonitorMode::Both);
+        let header = SessionHeader::new("network_idle", &condition, 0, 0);
+        let capture = format!(
+            "{}\nnot json\n",
+            serde_json::to_string(&SessionRecord::Header(header)).unwrap()
+        );
+
+        let result = SessionReplayer::from_reader(capture.as_bytes());
+        assert!(matches!(result, Err(RecordingError::Parse { line: 2, .. })));
+    }
+
+    #[test]
+    fn recorder_timestamps_are_relative_to_first_sample() {
+        let clock = VirtualClock::new();
+        let condition = ThresholdCondition::new(1, 1, MonitorMode::Both);
+        let header = SessionHeader::new("network_idle", &condition, 0, 0);
+        let buffer = SharedBuffer::default();
+        let mut recorder = SessionRecorder::new(buffer.clone(), &header).unwrap();
+        let outcome = StepOutcome {
+            condition: None,
+            scheduler: SchedulerState::Idle,
+            events: vec![],
+            executed: false,
+            zone: None,
+        };
+
+        recorder.record(clock.now(), None, &outcome).unwrap();
+        clock.advance(Duration::from_millis(250));
+        recorder.record(clock.now(), None, &outcome).unwrap();
+        assert_eq!(recorder.sample_count(), 2);
+
+        let replayer = SessionReplayer::from_reader(buffer.contents().as_slice()).unwrap();
+        let times: Vec<_> = replayer.samples().iter().map(|s| s.t_ms).collect();
+        assert_eq!(times, vec![0, 250]);
+    }
+}
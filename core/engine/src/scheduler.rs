@@ -1,10 +1,33 @@
 //! Action scheduler with countdown, pre-warning, and cancellation.
 //!
 //! Implements a state machine: `Idle → Pending → Countdown → Executed | Cancelled`
-//! with event emission at each transition.
+//! with event emission at each transition. An [`AckPolicy`] can require the
+//! pre-warning to be acknowledged before the countdown starts. The
+//! pre-warning and countdown can be paused and resumed. With an
+//! injected [`Action`], the scheduler runs it itself and ends in `Failed`
+//! if it errors.
 
-use serde::{Deserialize, Serialize};
+use crate::power::PowerGuard;
+use crate::user_idle::UserIdleGuard;
+use flowwatcher_actions::{Action, ActionError};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
+use tokio::sync::broadcast;
+
+/// Maximum number of undrained events kept by [`ActionScheduler`]. Older
+/// events are dropped (and counted) if `take_events` is never called.
+/// Also the capacity of each subscription channel.
+pub const MAX_BUFFERED_EVENTS: usize = 256;
+
+/// Default cap on the total seconds one scheduled action can be snoozed.
+pub const DEFAULT_MAX_SNOOZE_SECS: u64 = 60 * 60;
+
+/// Maximum number of state transitions kept by [`ActionScheduler`]; the
+/// oldest is dropped first.
+pub const MAX_TRANSITION_HISTORY: usize = 100;
 
 // ---------------------------------------------------------------------------
 // Error types
@@ -22,9 +45,22 @@ pub enum SchedulerError {
         state: String,
     },
 
+    /// A snooze would exceed the configured total.
+    #[error("cannot snooze {requested}s: only {available}s of snooze left")]
+    SnoozeLimit {
+        /// Seconds asked for.
+        requested: u64,
+        /// Seconds still available under the cap.
+        available: u64,
+    },
+
     /// The underlying action failed.
     #[error("action error: {0}")]
     ActionError(String),
+
+    /// The power guard blocked the action; it did not run.
+    #[error("action skipped: {0}")]
+    ExecutionSkipped(String),
 }
 
 // ---------------------------------------------------------------------------
@@ -40,10 +76,16 @@ pub enum SchedulerState {
     Pending,
     /// Countdown is active — the action will execute when it reaches zero.
     Countdown,
+    /// The pre-warning is paused; ticks are ignored until resumed.
+    PausedPending,
+    /// The countdown is paused; ticks are ignored until resumed.
+    PausedCountdown,
     /// The action was successfully executed.
     Executed,
     /// The action was cancelled before execution.
     Cancelled,
+    /// The injected action failed to validate or execute.
+    Failed,
 }
 
 impl std::fmt::Display for SchedulerState {
@@ -52,38 +94,358 @@ impl std::fmt::Display for SchedulerState {
             Self::Idle => write!(f, "Idle"),
             Self::Pending => write!(f, "Pending"),
             Self::Countdown => write!(f, "Countdown"),
+            Self::PausedPending => write!(f, "PausedPending"),
+            Self::PausedCountdown => write!(f, "PausedCountdown"),
             Self::Executed => write!(f, "Executed"),
             Self::Cancelled => write!(f, "Cancelled"),
+            Self::Failed => write!(f, "Failed"),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Cancel reasons
+// ---------------------------------------------------------------------------
+
+/// Why a scheduled action was cancelled.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelReason {
+    /// The user clicked Cancel (or pressed Esc) in the app window.
+    #[default]
+    #[serde(alias = "user_dialog")]
+    UserUi,
+    /// The user cancelled from the tray menu.
+    #[serde(alias = "tray")]
+    UserTray,
+    /// The user pressed the global cancel hotkey.
+    UserHotkey,
+    /// A client of the local HTTP API cancelled.
+    #[serde(alias = "api")]
+    RemoteApi,
+    /// Traffic picked up again before the action ran.
+    AutoTrafficResumed,
+    /// The system resumed from sleep and the schedule was reset.
+    SystemResume,
+    /// The interface watchdog found the monitored interface inactive, or
+    /// switched monitoring to another interface.
+    InterfaceFailover,
+    /// The application is shutting down.
+    Shutdown,
+    /// Monitoring was restarted with a new configuration.
+    Restarted,
+    /// Nobody acknowledged a pre-warning that required it.
+    NotAcknowledged,
+    /// The action stayed pending past the pending timeout.
+    PendingTimeout,
+    /// The power guard blocked the action when it was due; holds the
+    /// guard's reason. Logged as a skip (see [`crate::LogEntry::skipped`]).
+    GuardFailed(String),
+    /// The condition was re-armed (e.g. with a new calibrated threshold)
+    /// while an action it had scheduled was still pending.
+    ConditionReArmed,
+    /// Anything else, described in free text.
+    Other(String),
+}
+
+impl CancelReason {
+    /// Whether a person cancelled, from the app, tray, hotkey, or API.
+    pub fn is_user(&self) -> bool {
+        matches!(
+            self,
+            Self::UserUi | Self::UserTray | Self::UserHotkey | Self::RemoteApi
+        )
+    }
+}
+
+impl std::fmt::Display for CancelReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UserUi => write!(f, "cancelled in the app window"),
+            Self::UserTray => write!(f, "cancelled from the tray menu"),
+            Self::UserHotkey => write!(f, "cancelled with the global hotkey"),
+            Self::RemoteApi => write!(f, "cancelled through the HTTP API"),
+            Self::AutoTrafficResumed => write!(f, "traffic resumed"),
+            Self::SystemResume => write!(f, "system resumed from sleep"),
+            Self::InterfaceFailover => write!(f, "monitored interface went inactive"),
+            Self::Shutdown => write!(f, "application shutting down"),
+            Self::Restarted => write!(f, "monitoring restarted"),
+            Self::NotAcknowledged => write!(f, "pre-warning was not acknowledged"),
+            Self::PendingTimeout => write!(f, "pending timeout reached"),
+            Self::GuardFailed(reason) => write!(f, "guard failed: {reason}"),
+            Self::ConditionReArmed => write!(f, "condition re-armed"),
+            Self::Other(reason) => write!(f, "{reason}"),
         }
     }
 }
 
+// ---------------------------------------------------------------------------
+// Acknowledgment policy
+// ---------------------------------------------------------------------------
+
+/// Whether the pre-warning must be acknowledged before the countdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum AckPolicy {
+    /// The countdown starts when the pre-warning ends.
+    #[default]
+    None,
+    /// Without an acknowledgment, the pre-warning is extended once by
+    /// `extra_secs`; the countdown then starts either way.
+    ExtendOnce {
+        /// Seconds added to the pre-warning.
+        extra_secs: u64,
+    },
+    /// The countdown only starts once the pre-warning is acknowledged.
+    RequireAck {
+        /// What happens when the pre-warning ends unacknowledged.
+        timeout_behavior: AckTimeout,
+    },
+}
+
+/// What [`AckPolicy::RequireAck`] does when the pre-warning times out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AckTimeout {
+    /// Cancel with [`CancelReason::NotAcknowledged`].
+    #[default]
+    Cancel,
+    /// Extend the pre-warning once by `extra_secs`, then cancel if it is
+    /// still unacknowledged.
+    ExtendThenCancel {
+        /// Seconds added to the pre-warning.
+        extra_secs: u64,
+    },
+}
+
+/// Acknowledgment progress while the scheduler is `Pending`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AckState {
+    /// The policy does not ask for an acknowledgment.
+    NotRequired,
+    /// Waiting for an acknowledgment during the original pre-warning.
+    Awaiting,
+    /// Waiting for an acknowledgment during the one-time extension.
+    Extended,
+    /// The pre-warning was acknowledged.
+    Acknowledged,
+}
+
+// ---------------------------------------------------------------------------
+// Transition history
+// ---------------------------------------------------------------------------
+
+/// The scheduler call that caused a state transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransitionTrigger {
+    /// [`ActionScheduler::schedule`].
+    Schedule,
+    /// [`ActionScheduler::tick`], including a pre-warning that ended, timed
+    /// out, or went unacknowledged.
+    Tick,
+    /// [`ActionScheduler::pause`].
+    Pause,
+    /// [`ActionScheduler::resume`].
+    Resume,
+    /// [`ActionScheduler::cancel`] or [`ActionScheduler::cancel_with_reason`].
+    Cancel,
+    /// [`ActionScheduler::execute_now`] or
+    /// [`ActionScheduler::execute_now_with_force`].
+    ExecuteNow,
+    /// [`ActionScheduler::run_pending_action`].
+    RunAction,
+    /// [`ActionScheduler::reset`].
+    Reset,
+}
+
+/// One recorded state transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transition {
+    /// State before the call.
+    pub from: SchedulerState,
+    /// State after the call.
+    pub to: SchedulerState,
+    /// Number of `tick()` calls since the scheduler was created.
+    pub tick: u64,
+    /// The call that made the transition.
+    pub trigger: TransitionTrigger,
+}
+
 // ---------------------------------------------------------------------------
 // Scheduler events
 // ---------------------------------------------------------------------------
 
 /// Events emitted by the scheduler at state transitions.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum SchedulerEvent {
     /// A pre-warning before the countdown starts.
     PreWarning {
         /// Seconds until countdown begins.
         seconds_until_countdown: u64,
     },
+    /// The pre-warning needs an acknowledgment.
+    AckRequested {
+        /// Seconds left to acknowledge before the policy applies.
+        timeout_secs: u64,
+    },
+    /// The pre-warning was acknowledged.
+    Acknowledged,
     /// The countdown has started.
     CountdownStarted {
         /// Total countdown duration in seconds.
         total_seconds: u64,
+        /// When the countdown ends, in milliseconds since the Unix epoch
+        /// (0 in recordings made before deadlines were reported).
+        deadline_epoch_ms: u64,
     },
     /// A tick during the countdown.
     CountdownTick {
         /// Seconds remaining.
         remaining_seconds: u64,
     },
+    /// The pre-warning or countdown was extended by a snooze.
+    Snoozed {
+        /// Seconds this snooze added (0 in recordings made before it was
+        /// reported).
+        extra_secs: u64,
+        /// Seconds left in the current phase after the snooze.
+        new_remaining_secs: u64,
+        /// When the current phase now ends, in milliseconds since the Unix
+        /// epoch (0 in recordings made before it was reported).
+        until_epoch_ms: u64,
+    },
+    /// The pre-warning or countdown was paused.
+    Paused,
+    /// The pre-warning or countdown was resumed.
+    Resumed,
     /// The scheduled action was cancelled.
-    Cancelled,
+    Cancelled {
+        /// Who or what cancelled it.
+        reason: CancelReason,
+    },
     /// The action was executed.
     Executed,
+    /// The injected action failed to validate or execute.
+    ExecutionFailed {
+        /// The action's error message.
+        error: String,
+    },
+    /// The power guard blocked the injected action, so it did not run.
+    ExecutionSkipped {
+        /// Why the guard blocked it.
+        reason: String,
+    },
+    /// The countdown was postponed because the user is active.
+    Postponed {
+        /// Seconds since the last keyboard or mouse input.
+        seconds_since_input: u64,
+    },
+    /// The action stayed `Pending` past the pending timeout without the
+    /// countdown starting, and was cancelled.
+    TimedOut {
+        /// Seconds the action was pending.
+        pending_secs: u64,
+    },
+}
+
+/// Derived deserializer for [`SchedulerEvent`]'s current wire format.
+#[derive(Deserialize)]
+#[serde(remote = "SchedulerEvent")]
+enum SchedulerEventRepr {
+    PreWarning {
+        seconds_until_countdown: u64,
+    },
+    AckRequested {
+        timeout_secs: u64,
+    },
+    Acknowledged,
+    CountdownStarted {
+        total_seconds: u64,
+        #[serde(default)]
+        deadline_epoch_ms: u64,
+    },
+    CountdownTick {
+        remaining_seconds: u64,
+    },
+    Snoozed {
+        #[serde(default)]
+        extra_secs: u64,
+        new_remaining_secs: u64,
+        #[serde(default)]
+        until_epoch_ms: u64,
+    },
+    Paused,
+    Resumed,
+    Cancelled {
+        reason: CancelReason,
+    },
+    Executed,
+    ExecutionFailed {
+        error: String,
+    },
+    ExecutionSkipped {
+        reason: String,
+    },
+    Postponed {
+        seconds_since_input: u64,
+    },
+    TimedOut {
+        pending_secs: u64,
+    },
+}
+
+impl<'de> Deserialize<'de> for SchedulerEvent {
+    /// Also accepts the bare `"Cancelled"` written before cancel reasons
+    /// existed (e.g. in older session recordings), read as
+    /// [`CancelReason::UserUi`] — the only way to cancel back then.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        enum Legacy {
+            Cancelled,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Wire {
+            Legacy(Legacy),
+            Current(#[serde(with = "SchedulerEventRepr")] SchedulerEvent),
+        }
+
+        Ok(match Wire::deserialize(deserializer)? {
+            Wire::Legacy(Legacy::Cancelled) => SchedulerEvent::Cancelled {
+                reason: CancelReason::default(),
+            },
+            Wire::Current(event) => event,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PendingRun
+// ---------------------------------------------------------------------------
+
+/// The injected action, handed out by
+/// [`ActionScheduler::begin_pending_action`] so it can run without a
+/// borrow of the scheduler — a host that shares the scheduler behind a
+/// lock need not hold it while a retrying action backs off.
+pub struct PendingRun {
+    /// The action to run.
+    action: Arc<dyn Action>,
+    /// The run's result and duration, once [`Self::run`] has finished.
+    outcome: Option<(Result<(), ActionError>, Duration)>,
+}
+
+impl PendingRun {
+    /// Validate and execute the action.
+    pub async fn run(&mut self) {
+        let started = Instant::now();
+        let result = match self.action.validate().await {
+            Ok(()) => self.action.execute().await,
+            Err(e) => Err(e),
+        };
+        self.outcome = Some((result, started.elapsed()));
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -96,14 +458,60 @@ pub enum SchedulerEvent {
 ///
 /// ```text
 /// Idle → Pending → Countdown → Executed
-///           ↓          ↓
+///           ↓   ↕      ↓   ↕       ↓
+///           ↓ PausedPending/      Failed
+///           ↓ PausedCountdown
 ///       Cancelled  Cancelled
 /// ```
 ///
 /// - Pre-warning event is emitted during `Pending` state.
+/// - With an [`AckPolicy`], `Pending` also tracks an [`AckState`]; an
+///   unacknowledged pre-warning is extended or cancelled per the policy.
 /// - Countdown ticks are emitted during `Countdown` state.
-/// - `cancel()` can be called in `Pending` or `Countdown` states.
-/// - `execute_now()` can be called during `Countdown` to skip remaining time.
+/// - `snooze()` adds time to `Pending` or `Countdown`, up to a total of
+///   [`DEFAULT_MAX_SNOOZE_SECS`] (see [`Self::with_max_snooze_secs`]).
+/// - `pause()` / `resume()` suspend `Pending` or `Countdown`; `tick()` does
+///   nothing while paused and resuming keeps the remaining seconds.
+/// - The countdown's wall-clock deadline (see [`Self::deadline_epoch_ms`])
+///   is fixed when it starts and moves only on a snooze or a resume.
+/// - `cancel()` / `cancel_with_reason()` can be called in `Pending` or
+///   `Countdown` states, paused or not.
+/// - With a [`UserIdleGuard`] (see [`Self::with_user_idle_guard`]), a
+///   pre-warning that ends while the user is active stays in `Pending`
+///   (emitting `Postponed` once) until the user has been idle long enough.
+/// - `execute_now()` can be called during `Countdown` to skip remaining time
+///   (while paused only via `execute_now_with_force(true)`).
+/// - With a pending timeout (see [`Self::with_pending_timeout_secs`]), an
+///   action that has not reached `Countdown` after that many ticks in
+///   `Pending` — e.g. postponed by user activity or extended for an
+///   acknowledgment — is cancelled with a `TimedOut` event.
+///
+/// # Events
+///
+/// Every event is both buffered for [`Self::take_events`] and sent to all
+/// receivers from [`Self::subscribe`] at the moment of the transition, so
+/// both see the same events. Subscribers are independent of each other
+/// and of the buffer; a receiver that falls more than
+/// [`MAX_BUFFERED_EVENTS`] behind gets `RecvError::Lagged`.
+///
+/// Without an injected action, `tick()` and `execute_now()` return `true`
+/// and the caller runs the action. With one (see [`Self::with_action`] and
+/// [`Self::schedule_with_action`]), they still return `true`, but the
+/// `Executed` event is held back until [`Self::run_pending_action`] has run
+/// the action; if it fails, the state becomes `Failed` and an
+/// `ExecutionFailed` event is emitted instead. A [`PowerGuard`] (see
+/// [`Self::with_power_guard`]) is checked just before that run; if it
+/// blocks, the action does not run, an `ExecutionSkipped` event is emitted,
+/// and the scheduler ends `Cancelled` with [`CancelReason::GuardFailed`].
+/// A host that shares the scheduler can split that run into
+/// [`Self::begin_pending_action`], [`PendingRun::run`] and
+/// [`Self::finish_pending_action`].
+///
+/// # History
+///
+/// The last [`MAX_TRANSITION_HISTORY`] state changes are kept with the
+/// call that caused them; see [`Self::transition_history`]. `reset()`
+/// clears the history and records only its own transition.
 pub struct ActionScheduler {
     /// Current state of the scheduler.
     state: SchedulerState,
@@ -111,10 +519,64 @@ pub struct ActionScheduler {
     pre_warning_secs: u64,
     /// Countdown duration in seconds.
     countdown_secs: u64,
-    /// Accumulated events (consumed by the caller).
-    events: Vec<SchedulerEvent>,
+    /// Accumulated events (consumed by the caller), capped at
+    /// [`MAX_BUFFERED_EVENTS`].
+    events: VecDeque<SchedulerEvent>,
+    /// Events dropped from a full buffer since creation.
+    dropped_events: u64,
+    /// Broadcast channel for subscribers, created on first `subscribe()`.
+    subscribers: Option<broadcast::Sender<SchedulerEvent>>,
     /// Seconds elapsed in the current phase (pending or countdown).
     elapsed_secs: u64,
+    /// Why the last scheduled action was cancelled, while `Cancelled`.
+    cancel_reason: Option<CancelReason>,
+    /// Whether the pre-warning must be acknowledged.
+    ack_policy: AckPolicy,
+    /// Acknowledgment progress during `Pending`.
+    ack_state: AckState,
+    /// Length of the current pre-warning, including any extension.
+    pending_secs: u64,
+    /// Length of the current countdown, including any snooze.
+    countdown_total: u64,
+    /// Cap on the seconds snoozed per scheduled action.
+    max_snooze_secs: u64,
+    /// Seconds snoozed since the action was scheduled.
+    snoozed_secs: u64,
+    /// Action run by [`Self::run_pending_action`], if injected.
+    action: Option<Arc<dyn Action>>,
+    /// Whether the countdown finished and the injected action has not run.
+    action_due: bool,
+    /// Error from the injected action, while `Failed`.
+    last_error: Option<String>,
+    /// How long the injected action took on its last run.
+    last_run_duration: Option<Duration>,
+    /// Checked right before the injected action runs.
+    power_guard: Option<PowerGuard>,
+    /// Checked before the countdown starts.
+    user_idle_guard: Option<UserIdleGuard>,
+    /// Whether the countdown is being held back by user activity.
+    postponed: bool,
+    /// Ticks in `Pending` after which the action is cancelled (None = no
+    /// limit).
+    pending_timeout_secs: Option<u64>,
+    /// Ticks spent in `Pending` since the action was scheduled.
+    pending_ticks: u64,
+    /// `tick()` calls since creation.
+    ticks: u64,
+    /// Recent state transitions, capped at [`MAX_TRANSITION_HISTORY`].
+    history: Vec<Transition>,
+    /// Milliseconds since the Unix epoch, for countdown deadlines.
+    wall_clock: fn() -> u64,
+    /// When the running countdown ends, in milliseconds since the Unix
+    /// epoch.
+    deadline_epoch_ms: u64,
+}
+
+/// The system clock in milliseconds since the Unix epoch.
+fn system_epoch_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as u64)
 }
 
 impl ActionScheduler {
@@ -128,68 +590,339 @@ impl ActionScheduler {
             state: SchedulerState::Idle,
             pre_warning_secs,
             countdown_secs,
-            events: Vec::new(),
+            events: VecDeque::new(),
+            dropped_events: 0,
+            subscribers: None,
             elapsed_secs: 0,
+            cancel_reason: None,
+            ack_policy: AckPolicy::None,
+            ack_state: AckState::NotRequired,
+            pending_secs: pre_warning_secs,
+            countdown_total: countdown_secs,
+            max_snooze_secs: DEFAULT_MAX_SNOOZE_SECS,
+            snoozed_secs: 0,
+            action: None,
+            action_due: false,
+            last_error: None,
+            last_run_duration: None,
+            power_guard: None,
+            user_idle_guard: None,
+            postponed: false,
+            pending_timeout_secs: None,
+            pending_ticks: 0,
+            ticks: 0,
+            history: Vec::new(),
+            wall_clock: system_epoch_ms,
+            deadline_epoch_ms: 0,
+        }
+    }
+
+    /// Read the wall clock for countdown deadlines from `clock`
+    /// (milliseconds since the Unix epoch) instead of the system clock.
+    pub fn with_wall_clock(mut self, clock: fn() -> u64) -> Self {
+        self.wall_clock = clock;
+        self
+    }
+
+    /// Cap the total snooze per scheduled action at `secs` (0 disables
+    /// snoozing).
+    pub fn with_max_snooze_secs(mut self, secs: u64) -> Self {
+        self.max_snooze_secs = secs;
+        self
+    }
+
+    /// Cancel an action that is still `Pending` after `secs` ticks, with a
+    /// [`SchedulerEvent::TimedOut`] (None, the default, waits forever).
+    pub fn with_pending_timeout_secs(mut self, secs: Option<u64>) -> Self {
+        self.pending_timeout_secs = secs;
+        self
+    }
+
+    /// Seconds snoozed since the action was scheduled.
+    pub fn snoozed_secs(&self) -> u64 {
+        self.snoozed_secs
+    }
+
+    /// Run `action` when the countdown finishes.
+    pub fn with_action(mut self, action: Box<dyn Action>) -> Self {
+        self.action = Some(action.into());
+        self
+    }
+
+    /// Check `guard` right before the injected action runs.
+    pub fn with_power_guard(mut self, guard: PowerGuard) -> Self {
+        self.power_guard = Some(guard);
+        self
+    }
+
+    /// Postpone the countdown while `guard` reports recent user input.
+    pub fn with_user_idle_guard(mut self, guard: UserIdleGuard) -> Self {
+        self.user_idle_guard = Some(guard);
+        self
+    }
+
+    /// Whether the end of the pre-warning is being held back by user
+    /// activity.
+    pub fn is_postponed(&self) -> bool {
+        self.postponed
+    }
+
+    /// Run `action` instead of the injected one, in any state. Returns the
+    /// action it replaced.
+    pub fn replace_action(&mut self, action: Box<dyn Action>) -> Option<Arc<dyn Action>> {
+        self.action.replace(action.into())
+    }
+
+    /// Display name of the injected action, if any.
+    pub fn action_name(&self) -> Option<String> {
+        self.action.as_ref().map(|action| action.info().name)
+    }
+
+    /// How long the injected action's last run took, validation included.
+    /// `None` until it has run (a power guard skip is not a run) and after
+    /// `reset()`.
+    pub fn last_run_duration(&self) -> Option<Duration> {
+        self.last_run_duration
+    }
+
+    /// Whether an action has been injected.
+    pub fn has_action(&self) -> bool {
+        self.action.is_some()
+    }
+
+    /// Seconds left in the current pre-warning or countdown, paused or not.
+    pub fn remaining_secs(&self) -> Option<u64> {
+        match self.state {
+            SchedulerState::Pending | SchedulerState::PausedPending => {
+                Some(self.pending_secs.saturating_sub(self.elapsed_secs))
+            }
+            SchedulerState::Countdown | SchedulerState::PausedCountdown => {
+                Some(self.countdown_total.saturating_sub(self.elapsed_secs))
+            }
+            _ => None,
         }
     }
 
+    /// When the countdown ends, in milliseconds since the Unix epoch, while
+    /// it runs. It is fixed when the countdown starts and moves only on a
+    /// snooze or a resume; a paused countdown has no deadline.
+    pub fn deadline_epoch_ms(&self) -> Option<u64> {
+        (self.state == SchedulerState::Countdown).then_some(self.deadline_epoch_ms)
+    }
+
+    /// Fix the deadline at the remaining countdown from now.
+    fn update_deadline(&mut self) {
+        let remaining_ms = self.remaining_secs().unwrap_or(0).saturating_mul(1000);
+        self.deadline_epoch_ms = (self.wall_clock)().saturating_add(remaining_ms);
+    }
+
+    /// The injected action's error, if the state is `Failed`.
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// Require the pre-warning to be acknowledged according to `policy`.
+    pub fn with_ack_policy(mut self, policy: AckPolicy) -> Self {
+        self.ack_policy = policy;
+        self
+    }
+
+    /// The acknowledgment policy.
+    pub fn ack_policy(&self) -> AckPolicy {
+        self.ack_policy
+    }
+
+    /// Acknowledgment progress of the current pre-warning.
+    pub fn ack_state(&self) -> AckState {
+        self.ack_state
+    }
+
     /// Get the current state.
     pub fn state(&self) -> SchedulerState {
         self.state
     }
 
+    /// Why the scheduled action was cancelled, if the state is `Cancelled`.
+    pub fn cancel_reason(&self) -> Option<&CancelReason> {
+        self.cancel_reason.as_ref()
+    }
+
     /// Drain all pending events.
     pub fn take_events(&mut self) -> Vec<SchedulerEvent> {
-        std::mem::take(&mut self.events)
+        self.events.drain(..).collect()
+    }
+
+    /// Receive every event from now on, independently of `take_events`
+    /// and of other subscribers. Dropping the receiver is always safe.
+    pub fn subscribe(&mut self) -> broadcast::Receiver<SchedulerEvent> {
+        self.subscribers
+            .get_or_insert_with(|| broadcast::channel(MAX_BUFFERED_EVENTS).0)
+            .subscribe()
+    }
+
+    /// Number of events waiting to be taken.
+    pub fn buffered_events(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Events dropped because the buffer was full.
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped_events
+    }
+
+    /// Recent state transitions, oldest first.
+    pub fn transition_history(&self) -> &[Transition] {
+        &self.history
+    }
+
+    /// Move to `to`, recording the transition if the state changes.
+    fn set_state(&mut self, to: SchedulerState, trigger: TransitionTrigger) {
+        if to == self.state {
+            return;
+        }
+        if self.history.len() >= MAX_TRANSITION_HISTORY {
+            // A `Vec` rather than a ring buffer so the history can be
+            // borrowed as one slice; shifting 100 small entries is cheap.
+            self.history.remove(0);
+        }
+        self.history.push(Transition {
+            from: self.state,
+            to,
+            tick: self.ticks,
+            trigger,
+        });
+        self.state = to;
+    }
+
+    /// Send an event to subscribers and buffer it, dropping the oldest
+    /// buffered event if the buffer is full.
+    fn push_event(&mut self, event: SchedulerEvent) {
+        if let Some(subscribers) = &self.subscribers {
+            // Fails only when nobody is subscribed, which is fine.
+            let _ = subscribers.send(event.clone());
+        }
+        if self.events.len() >= MAX_BUFFERED_EVENTS {
+            self.events.pop_front();
+            self.dropped_events += 1;
+        }
+        self.events.push_back(event);
     }
 
     /// Schedule an action. Transitions from `Idle` → `Pending`.
+    ///
+    /// Also allowed after `Cancelled` or `Failed`.
     pub fn schedule(&mut self) -> Result<(), SchedulerError> {
-        if self.state != SchedulerState::Idle && self.state != SchedulerState::Cancelled {
+        if !matches!(
+            self.state,
+            SchedulerState::Idle | SchedulerState::Cancelled | SchedulerState::Failed
+        ) {
             return Err(SchedulerError::InvalidState {
                 action: "schedule".to_string(),
                 state: self.state.to_string(),
             });
         }
 
-        self.state = SchedulerState::Pending;
+        self.set_state(SchedulerState::Pending, TransitionTrigger::Schedule);
         self.elapsed_secs = 0;
-        self.events.push(SchedulerEvent::PreWarning {
+        self.pending_secs = self.pre_warning_secs;
+        self.countdown_total = self.countdown_secs;
+        self.snoozed_secs = 0;
+        self.cancel_reason = None;
+        self.last_error = None;
+        self.postponed = false;
+        self.pending_ticks = 0;
+        self.push_event(SchedulerEvent::PreWarning {
             seconds_until_countdown: self.pre_warning_secs,
         });
+        self.ack_state = if self.ack_policy == AckPolicy::None {
+            AckState::NotRequired
+        } else {
+            self.push_event(SchedulerEvent::AckRequested {
+                timeout_secs: self.pre_warning_secs,
+            });
+            AckState::Awaiting
+        };
+
+        Ok(())
+    }
 
+    /// Schedule `action`, replacing any injected before.
+    ///
+    /// The state is checked first, so a rejected call keeps the old action.
+    pub fn schedule_with_action(&mut self, action: Box<dyn Action>) -> Result<(), SchedulerError> {
+        self.schedule()?;
+        self.action = Some(action.into());
         Ok(())
     }
 
+    /// Acknowledge the pre-warning.
+    ///
+    /// Returns `true` if this satisfied a pending acknowledgment, `false`
+    /// if none was needed or it was already given.
+    pub fn acknowledge(&mut self) -> Result<bool, SchedulerError> {
+        if self.state != SchedulerState::Pending {
+            return Err(SchedulerError::InvalidState {
+                action: "acknowledge".to_string(),
+                state: self.state.to_string(),
+            });
+        }
+        match self.ack_state {
+            AckState::Awaiting | AckState::Extended => {
+                self.ack_state = AckState::Acknowledged;
+                self.push_event(SchedulerEvent::Acknowledged);
+                Ok(true)
+            }
+            AckState::NotRequired | AckState::Acknowledged => Ok(false),
+        }
+    }
+
     /// Advance the scheduler by one tick (typically 1 second).
     ///
     /// Returns `true` if the action should now be executed.
     pub fn tick(&mut self) -> Result<bool, SchedulerError> {
+        self.ticks += 1;
         match self.state {
             SchedulerState::Pending => {
                 self.elapsed_secs += 1;
-                if self.elapsed_secs >= self.pre_warning_secs {
-                    // Transition to Countdown.
-                    self.state = SchedulerState::Countdown;
-                    self.elapsed_secs = 0;
-                    self.events.push(SchedulerEvent::CountdownStarted {
-                        total_seconds: self.countdown_secs,
-                    });
+                self.pending_ticks += 1;
+                if self
+                    .pending_timeout_secs
+                    .is_some_and(|limit| self.pending_ticks >= limit)
+                {
+                    self.time_out();
+                    return Ok(false);
+                }
+                if self.elapsed_secs >= self.pending_secs {
+                    if let Some(secs) = self
+                        .user_idle_guard
+                        .as_ref()
+                        .and_then(UserIdleGuard::recent_input_secs)
+                    {
+                        self.elapsed_secs = self.pending_secs;
+                        if !self.postponed {
+                            self.postponed = true;
+                            self.push_event(SchedulerEvent::Postponed {
+                                seconds_since_input: secs,
+                            });
+                        }
+                        return Ok(false);
+                    }
+                    self.postponed = false;
+                    self.end_pre_warning()?;
                 }
                 Ok(false)
             }
             SchedulerState::Countdown => {
                 self.elapsed_secs += 1;
-                let remaining = self.countdown_secs.saturating_sub(self.elapsed_secs);
+                let remaining = self.countdown_total.saturating_sub(self.elapsed_secs);
 
-                self.events.push(SchedulerEvent::CountdownTick {
+                self.push_event(SchedulerEvent::CountdownTick {
                     remaining_seconds: remaining,
                 });
 
                 if remaining == 0 {
-                    self.state = SchedulerState::Executed;
-                    self.events.push(SchedulerEvent::Executed);
-                    Ok(true) // Caller should execute the action now.
+                    self.finish(TransitionTrigger::Tick);
+                    Ok(true) // The action should execute now.
                 } else {
                     Ok(false)
                 }
@@ -198,13 +931,165 @@ impl ActionScheduler {
         }
     }
 
-    /// Cancel the scheduled action.
+    /// Cancel an action that stayed pending too long.
+    fn time_out(&mut self) {
+        self.set_state(SchedulerState::Cancelled, TransitionTrigger::Tick);
+        self.elapsed_secs = 0;
+        self.postponed = false;
+        self.cancel_reason = Some(CancelReason::PendingTimeout);
+        self.push_event(SchedulerEvent::TimedOut {
+            pending_secs: self.pending_ticks,
+        });
+    }
+
+    /// Apply the acknowledgment policy at the end of the pre-warning.
+    fn end_pre_warning(&mut self) -> Result<(), SchedulerError> {
+        let extension = match (self.ack_policy, self.ack_state) {
+            (_, AckState::NotRequired | AckState::Acknowledged) => None,
+            (AckPolicy::ExtendOnce { extra_secs }, AckState::Awaiting) => Some(extra_secs),
+            (
+                AckPolicy::RequireAck {
+                    timeout_behavior: AckTimeout::ExtendThenCancel { extra_secs },
+                },
+                AckState::Awaiting,
+            ) => Some(extra_secs),
+            (AckPolicy::RequireAck { .. }, _) => {
+                return self.cancel_by(CancelReason::NotAcknowledged, TransitionTrigger::Tick);
+            }
+            // An extended pre-warning (or no policy at all) proceeds.
+            _ => None,
+        };
+
+        match extension {
+            Some(extra_secs) => {
+                self.ack_state = AckState::Extended;
+                self.pending_secs += extra_secs;
+                self.push_event(SchedulerEvent::PreWarning {
+                    seconds_until_countdown: extra_secs,
+                });
+                self.push_event(SchedulerEvent::AckRequested {
+                    timeout_secs: extra_secs,
+                });
+            }
+            None => {
+                self.set_state(SchedulerState::Countdown, TransitionTrigger::Tick);
+                self.elapsed_secs = 0;
+                self.update_deadline();
+                self.push_event(SchedulerEvent::CountdownStarted {
+                    total_seconds: self.countdown_total,
+                    deadline_epoch_ms: self.deadline_epoch_ms,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Add `extra_secs` to the pre-warning or countdown.
+    ///
+    /// Snoozes accumulate against the cap; one that would exceed it fails
+    /// with [`SchedulerError::SnoozeLimit`] and changes nothing.
+    pub fn snooze(&mut self, extra_secs: u64) -> Result<(), SchedulerError> {
+        if !matches!(
+            self.state,
+            SchedulerState::Pending | SchedulerState::Countdown
+        ) {
+            return Err(SchedulerError::InvalidState {
+                action: "snooze".to_string(),
+                state: self.state.to_string(),
+            });
+        }
+        let available = self.max_snooze_secs.saturating_sub(self.snoozed_secs);
+        if extra_secs > available {
+            return Err(SchedulerError::SnoozeLimit {
+                requested: extra_secs,
+                available,
+            });
+        }
+
+        self.snoozed_secs += extra_secs;
+        if self.state == SchedulerState::Pending {
+            self.pending_secs += extra_secs;
+        } else {
+            self.countdown_total += extra_secs;
+            self.update_deadline();
+        }
+        let remaining = self.remaining_secs().unwrap_or(0);
+        self.push_event(SchedulerEvent::Snoozed {
+            extra_secs,
+            new_remaining_secs: remaining,
+            until_epoch_ms: (self.wall_clock)().saturating_add(remaining.saturating_mul(1000)),
+        });
+        Ok(())
+    }
+
+    /// Pause the pre-warning or countdown. Transitions `Pending` →
+    /// `PausedPending` and `Countdown` → `PausedCountdown`.
+    pub fn pause(&mut self) -> Result<(), SchedulerError> {
+        let paused = match self.state {
+            SchedulerState::Pending => SchedulerState::PausedPending,
+            SchedulerState::Countdown => SchedulerState::PausedCountdown,
+            _ => {
+                return Err(SchedulerError::InvalidState {
+                    action: "pause".to_string(),
+                    state: self.state.to_string(),
+                })
+            }
+        };
+        self.set_state(paused, TransitionTrigger::Pause);
+        self.push_event(SchedulerEvent::Paused);
+        Ok(())
+    }
+
+    /// Resume a paused pre-warning or countdown with the same remaining
+    /// seconds.
+    pub fn resume(&mut self) -> Result<(), SchedulerError> {
+        let resumed = match self.state {
+            SchedulerState::PausedPending => SchedulerState::Pending,
+            SchedulerState::PausedCountdown => SchedulerState::Countdown,
+            _ => {
+                return Err(SchedulerError::InvalidState {
+                    action: "resume".to_string(),
+                    state: self.state.to_string(),
+                })
+            }
+        };
+        self.set_state(resumed, TransitionTrigger::Resume);
+        if resumed == SchedulerState::Countdown {
+            // The paused time pushes the end back.
+            self.update_deadline();
+        }
+        self.push_event(SchedulerEvent::Resumed);
+        Ok(())
+    }
+
+    /// Cancel the scheduled action from the app window
+    /// ([`CancelReason::UserUi`]).
     pub fn cancel(&mut self) -> Result<(), SchedulerError> {
+        self.cancel_with_reason(CancelReason::UserUi)
+    }
+
+    /// Cancel the scheduled action, recording why.
+    pub fn cancel_with_reason(&mut self, reason: CancelReason) -> Result<(), SchedulerError> {
+        self.cancel_by(reason, TransitionTrigger::Cancel)
+    }
+
+    /// Cancel, recording `trigger` as the call that did it.
+    fn cancel_by(
+        &mut self,
+        reason: CancelReason,
+        trigger: TransitionTrigger,
+    ) -> Result<(), SchedulerError> {
         match self.state {
-            SchedulerState::Pending | SchedulerState::Countdown => {
-                self.state = SchedulerState::Cancelled;
+            SchedulerState::Pending
+            | SchedulerState::Countdown
+            | SchedulerState::PausedPending
+            | SchedulerState::PausedCountdown => {
+                self.set_state(SchedulerState::Cancelled, trigger);
                 self.elapsed_secs = 0;
-                self.events.push(SchedulerEvent::Cancelled);
+                self.push_event(SchedulerEvent::Cancelled {
+                    reason: reason.clone(),
+                });
+                self.cancel_reason = Some(reason);
                 Ok(())
             }
             _ => Err(SchedulerError::InvalidState {
@@ -216,13 +1101,28 @@ impl ActionScheduler {
 
     /// Skip the countdown and mark as ready to execute immediately.
     ///
-    /// Returns `true` to indicate the caller should execute the action now.
+    /// Returns `true` to indicate the action should execute now. Refused
+    /// while paused; see [`Self::execute_now_with_force`].
     pub fn execute_now(&mut self) -> Result<bool, SchedulerError> {
+        self.execute_now_with_force(false)
+    }
+
+    /// Like [`Self::execute_now`], but with `force` also skips a paused
+    /// pre-warning or countdown.
+    pub fn execute_now_with_force(&mut self, force: bool) -> Result<bool, SchedulerError> {
+        let paused = matches!(
+            self.state,
+            SchedulerState::PausedPending | SchedulerState::PausedCountdown
+        );
         match self.state {
             SchedulerState::Pending | SchedulerState::Countdown => {
-                self.state = SchedulerState::Executed;
                 self.elapsed_secs = 0;
-                self.events.push(SchedulerEvent::Executed);
+                self.finish(TransitionTrigger::ExecuteNow);
+                Ok(true)
+            }
+            _ if paused && force => {
+                self.elapsed_secs = 0;
+                self.finish(TransitionTrigger::ExecuteNow);
                 Ok(true)
             }
             _ => Err(SchedulerError::InvalidState {
@@ -232,10 +1132,117 @@ impl ActionScheduler {
         }
     }
 
+    /// Enter `Executed`. Without an injected action the `Executed` event
+    /// is emitted now; otherwise the action becomes due.
+    fn finish(&mut self, trigger: TransitionTrigger) {
+        self.set_state(SchedulerState::Executed, trigger);
+        if self.action.is_some() {
+            self.action_due = true;
+        } else {
+            self.push_event(SchedulerEvent::Executed);
+        }
+    }
+
+    /// Validate and execute the injected action once the countdown has
+    /// finished (i.e. after `tick()` or `execute_now()` returned `true`).
+    ///
+    /// On success the `Executed` event is emitted. On failure the state
+    /// becomes `Failed`, an `ExecutionFailed` event is emitted, and the
+    /// error is returned as [`SchedulerError::ActionError`]. If the power
+    /// guard blocks the run, the state becomes `Cancelled` with
+    /// [`CancelReason::GuardFailed`], an `ExecutionSkipped` event is
+    /// emitted, and the reason is returned as
+    /// [`SchedulerError::ExecutionSkipped`].
+    pub async fn run_pending_action(&mut self) -> Result<(), SchedulerError> {
+        let mut run = self.begin_pending_action()?;
+        run.run().await;
+        self.finish_pending_action(run)
+    }
+
+    /// First half of [`Self::run_pending_action`]: check the power guard
+    /// and hand out the due action to be run with [`PendingRun::run`].
+    ///
+    /// A power guard skip is handled here and returned as
+    /// [`SchedulerError::ExecutionSkipped`].
+    pub fn begin_pending_action(&mut self) -> Result<PendingRun, SchedulerError> {
+        let Some(action) = self.action.clone().filter(|_| self.action_due) else {
+            return Err(SchedulerError::InvalidState {
+                action: "run_pending_action".to_string(),
+                state: self.state.to_string(),
+            });
+        };
+
+        if let Some(reason) = self.power_guard.as_ref().and_then(PowerGuard::check) {
+            self.action_due = false;
+            self.set_state(SchedulerState::Cancelled, TransitionTrigger::RunAction);
+            self.cancel_reason = Some(CancelReason::GuardFailed(reason.clone()));
+            self.push_event(SchedulerEvent::ExecutionSkipped {
+                reason: reason.clone(),
+            });
+            return Err(SchedulerError::ExecutionSkipped(reason));
+        }
+        self.action_due = false;
+        Ok(PendingRun {
+            action,
+            outcome: None,
+        })
+    }
+
+    /// Second half of [`Self::run_pending_action`]: record the outcome of
+    /// `run`.
+    ///
+    /// A run that never ran leaves the action due again. If the scheduler
+    /// left `Executed` while the action ran (e.g. it was reset), the
+    /// result is returned but not recorded.
+    pub fn finish_pending_action(&mut self, run: PendingRun) -> Result<(), SchedulerError> {
+        let Some((result, duration)) = run.outcome else {
+            self.action_due = self.state == SchedulerState::Executed;
+            return Err(SchedulerError::InvalidState {
+                action: "finish_pending_action".to_string(),
+                state: self.state.to_string(),
+            });
+        };
+        if self.state != SchedulerState::Executed {
+            return result.map_err(|e| SchedulerError::ActionError(e.to_string()));
+        }
+        self.last_run_duration = Some(duration);
+
+        match result {
+            Ok(()) => {
+                self.push_event(SchedulerEvent::Executed);
+                Ok(())
+            }
+            Err(e) => {
+                let error = e.to_string();
+                self.set_state(SchedulerState::Failed, TransitionTrigger::RunAction);
+                self.last_error = Some(error.clone());
+                self.push_event(SchedulerEvent::ExecutionFailed {
+                    error: error.clone(),
+                });
+                Err(SchedulerError::ActionError(error))
+            }
+        }
+    }
+
     /// Reset the scheduler back to `Idle`. Can be called from any state.
+    ///
+    /// Clears buffered events and the transition history, which then holds
+    /// only this reset; subscribers keep what they already received and
+    /// stay subscribed. An injected action is kept for the next schedule.
     pub fn reset(&mut self) {
-        self.state = SchedulerState::Idle;
+        self.history.clear();
+        self.set_state(SchedulerState::Idle, TransitionTrigger::Reset);
         self.elapsed_secs = 0;
+        self.pending_secs = self.pre_warning_secs;
+        self.countdown_total = self.countdown_secs;
+        self.snoozed_secs = 0;
+        self.ack_state = AckState::NotRequired;
+        self.cancel_reason = None;
+        self.action_due = false;
+        self.last_error = None;
+        self.last_run_duration = None;
+        self.postponed = false;
+        self.pending_ticks = 0;
         self.events.clear();
     }
 }
@@ -312,17 +1319,414 @@ mod tests {
         assert_eq!(scheduler.state(), SchedulerState::Cancelled);
 
         let events = scheduler.take_events();
-        assert!(events
-            .iter()
-            .any(|e| matches!(e, SchedulerEvent::Cancelled)));
+        assert!(events.contains(&SchedulerEvent::Cancelled {
+            reason: CancelReason::UserUi
+        }));
+        assert_eq!(scheduler.cancel_reason(), Some(&CancelReason::UserUi));
     }
 
     #[test]
-    fn cancel_during_countdown() {
-        let mut scheduler = ActionScheduler::new(0, 30);
+    fn cancel_with_reason_records_each_reason() {
+        let reasons = [
+            CancelReason::UserUi,
+            CancelReason::UserTray,
+            CancelReason::UserHotkey,
+            CancelReason::RemoteApi,
+            CancelReason::AutoTrafficResumed,
+            CancelReason::SystemResume,
+            CancelReason::Shutdown,
+            CancelReason::Restarted,
+            CancelReason::NotAcknowledged,
+            CancelReason::PendingTimeout,
+            CancelReason::GuardFailed("on battery".to_string()),
+            CancelReason::ConditionReArmed,
+            CancelReason::InterfaceFailover,
+            CancelReason::Other("policy".to_string()),
+        ];
+        let mut scheduler = ActionScheduler::new(60, 30);
+        for reason in reasons {
+            scheduler.schedule().unwrap();
+            scheduler.take_events();
+            scheduler.cancel_with_reason(reason.clone()).unwrap();
+            assert_eq!(scheduler.cancel_reason(), Some(&reason));
+            assert_eq!(
+                scheduler.take_events(),
+                vec![SchedulerEvent::Cancelled { reason }]
+            );
+        }
+
         scheduler.schedule().unwrap();
-        scheduler.tick().unwrap(); // → Countdown
-        scheduler.cancel().expect("should cancel");
+        assert_eq!(scheduler.cancel_reason(), None);
+    }
+
+    #[test]
+    fn only_people_count_as_user_cancels() {
+        assert!(CancelReason::UserTray.is_user());
+        assert!(CancelReason::RemoteApi.is_user());
+        assert!(!CancelReason::AutoTrafficResumed.is_user());
+        assert!(!CancelReason::PendingTimeout.is_user());
+    }
+
+    #[test]
+    fn cancelled_event_serde() {
+        let event = SchedulerEvent::Cancelled {
+            reason: CancelReason::AutoTrafficResumed,
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(json, r#"{"Cancelled":{"reason":"auto_traffic_resumed"}}"#);
+        assert_eq!(
+            serde_json::from_str::<SchedulerEvent>(&json).unwrap(),
+            event
+        );
+
+        let other = SchedulerEvent::Cancelled {
+            reason: CancelReason::Other("low battery".to_string()),
+        };
+        let json = serde_json::to_string(&other).unwrap();
+        assert_eq!(json, r#"{"Cancelled":{"reason":{"other":"low battery"}}}"#);
+        assert_eq!(
+            serde_json::from_str::<SchedulerEvent>(&json).unwrap(),
+            other
+        );
+    }
+
+    #[test]
+    fn transition_history_records_the_calls() {
+        use TransitionTrigger as T;
+        let mut scheduler = ActionScheduler::new(1, 2);
+        scheduler.schedule().unwrap();
+        scheduler.tick().unwrap(); // Pending → Countdown
+        scheduler.pause().unwrap();
+        scheduler.tick().unwrap(); // ignored while paused
+        scheduler.resume().unwrap();
+        scheduler.tick().unwrap();
+        scheduler.execute_now().unwrap();
+
+        let steps: Vec<_> = scheduler
+            .transition_history()
+            .iter()
+            .map(|t| (t.from, t.to, t.tick, t.trigger))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                (
+                    SchedulerState::Idle,
+                    SchedulerState::Pending,
+                    0,
+                    T::Schedule
+                ),
+                (
+                    SchedulerState::Pending,
+                    SchedulerState::Countdown,
+                    1,
+                    T::Tick
+                ),
+                (
+                    SchedulerState::Countdown,
+                    SchedulerState::PausedCountdown,
+                    1,
+                    T::Pause
+                ),
+                (
+                    SchedulerState::PausedCountdown,
+                    SchedulerState::Countdown,
+                    2,
+                    T::Resume
+                ),
+                (
+                    SchedulerState::Countdown,
+                    SchedulerState::Executed,
+                    3,
+                    T::ExecuteNow
+                ),
+            ]
+        );
+
+        scheduler.reset();
+        assert_eq!(
+            scheduler.transition_history(),
+            &[Transition {
+                from: SchedulerState::Executed,
+                to: SchedulerState::Idle,
+                tick: 3,
+                trigger: T::Reset,
+            }]
+        );
+    }
+
+    #[test]
+    fn transition_history_is_capped() {
+        let mut scheduler = ActionScheduler::new(60, 30);
+        for _ in 0..MAX_TRANSITION_HISTORY {
+            scheduler.schedule().unwrap();
+            scheduler.cancel().unwrap();
+        }
+        let history = scheduler.transition_history();
+        assert_eq!(history.len(), MAX_TRANSITION_HISTORY);
+        // The oldest entries are gone; the last one is the final cancel.
+        assert_eq!(history[0].from, SchedulerState::Cancelled);
+        assert_eq!(history.last().unwrap().trigger, TransitionTrigger::Cancel);
+    }
+
+    #[test]
+    fn cancel_reason_accepts_short_names() {
+        for (name, reason) in [
+            ("user_dialog", CancelReason::UserUi),
+            ("tray", CancelReason::UserTray),
+            ("api", CancelReason::RemoteApi),
+            ("condition_re_armed", CancelReason::ConditionReArmed),
+        ] {
+            let parsed: CancelReason = serde_json::from_str(&format!("\"{name}\"")).unwrap();
+            assert_eq!(parsed, reason);
+        }
+        let guard: CancelReason = serde_json::from_str(r#"{"guard_failed":"on battery"}"#).unwrap();
+        assert_eq!(guard.to_string(), "guard failed: on battery");
+    }
+
+    #[test]
+    fn legacy_cancelled_event_deserializes() {
+        let event: SchedulerEvent = serde_json::from_str(r#""Cancelled""#).unwrap();
+        assert_eq!(
+            event,
+            SchedulerEvent::Cancelled {
+                reason: CancelReason::UserUi
+            }
+        );
+        let tick: SchedulerEvent =
+            serde_json::from_str(r#"{"CountdownTick":{"remaining_seconds":3}}"#).unwrap();
+        assert_eq!(
+            tick,
+            SchedulerEvent::CountdownTick {
+                remaining_seconds: 3
+            }
+        );
+        let started: SchedulerEvent =
+            serde_json::from_str(r#"{"CountdownStarted":{"total_seconds":5}}"#).unwrap();
+        assert_eq!(
+            started,
+            SchedulerEvent::CountdownStarted {
+                total_seconds: 5,
+                deadline_epoch_ms: 0
+            }
+        );
+        let snoozed: SchedulerEvent =
+            serde_json::from_str(r#"{"Snoozed":{"new_remaining_secs":90}}"#).unwrap();
+        assert_eq!(
+            snoozed,
+            SchedulerEvent::Snoozed {
+                extra_secs: 0,
+                new_remaining_secs: 90,
+                until_epoch_ms: 0
+            }
+        );
+        let executed: SchedulerEvent = serde_json::from_str(r#""Executed""#).unwrap();
+        assert_eq!(executed, SchedulerEvent::Executed);
+        assert!(serde_json::from_str::<SchedulerEvent>(r#""Exploded""#).is_err());
+    }
+
+    /// Schedule with `policy` (3s pre-warning, 2s countdown) and tick
+    /// `ticks` times, returning the events after scheduling.
+    fn run_ack(
+        policy: AckPolicy,
+        ticks: u64,
+        ack_at: Option<u64>,
+    ) -> (ActionScheduler, Vec<SchedulerEvent>) {
+        let mut scheduler = ActionScheduler::new(3, 2)
+            .with_ack_policy(policy)
+            .with_wall_clock(|| 0);
+        scheduler.schedule().unwrap();
+        scheduler.take_events();
+        for tick in 0..ticks {
+            if ack_at == Some(tick) {
+                scheduler.acknowledge().unwrap();
+            }
+            scheduler.tick().unwrap();
+        }
+        let events = scheduler.take_events();
+        (scheduler, events)
+    }
+
+    const EXTEND_ONCE: AckPolicy = AckPolicy::ExtendOnce { extra_secs: 5 };
+    const REQUIRE_ACK: AckPolicy = AckPolicy::RequireAck {
+        timeout_behavior: AckTimeout::Cancel,
+    };
+    const REQUIRE_ACK_EXTEND: AckPolicy = AckPolicy::RequireAck {
+        timeout_behavior: AckTimeout::ExtendThenCancel { extra_secs: 5 },
+    };
+
+    #[test]
+    fn no_ack_policy_requests_nothing() {
+        let mut scheduler = ActionScheduler::new(3, 2);
+        scheduler.schedule().unwrap();
+        assert_eq!(scheduler.ack_state(), AckState::NotRequired);
+        assert_eq!(scheduler.take_events().len(), 1);
+        assert!(!scheduler.acknowledge().unwrap());
+        assert!(scheduler.take_events().is_empty());
+    }
+
+    #[test]
+    fn ack_policy_requests_acknowledgment_on_schedule() {
+        let mut scheduler = ActionScheduler::new(3, 2).with_ack_policy(REQUIRE_ACK);
+        scheduler.schedule().unwrap();
+        assert_eq!(scheduler.ack_state(), AckState::Awaiting);
+        assert_eq!(
+            scheduler.take_events(),
+            vec![
+                SchedulerEvent::PreWarning {
+                    seconds_until_countdown: 3
+                },
+                SchedulerEvent::AckRequested { timeout_secs: 3 },
+            ]
+        );
+    }
+
+    #[test]
+    fn acknowledged_pre_warning_proceeds_on_time() {
+        for policy in [EXTEND_ONCE, REQUIRE_ACK, REQUIRE_ACK_EXTEND] {
+            let (scheduler, events) = run_ack(policy, 3, Some(1));
+            assert_eq!(scheduler.state(), SchedulerState::Countdown);
+            assert_eq!(scheduler.ack_state(), AckState::Acknowledged);
+            assert_eq!(
+                events,
+                vec![
+                    SchedulerEvent::Acknowledged,
+                    SchedulerEvent::CountdownStarted {
+                        total_seconds: 2,
+                        deadline_epoch_ms: 2000
+                    },
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn extend_once_extends_then_proceeds_without_ack() {
+        let (scheduler, events) = run_ack(EXTEND_ONCE, 3, None);
+        assert_eq!(scheduler.state(), SchedulerState::Pending);
+        assert_eq!(scheduler.ack_state(), AckState::Extended);
+        assert_eq!(
+            events,
+            vec![
+                SchedulerEvent::PreWarning {
+                    seconds_until_countdown: 5
+                },
+                SchedulerEvent::AckRequested { timeout_secs: 5 },
+            ]
+        );
+
+        let (scheduler, events) = run_ack(EXTEND_ONCE, 8, None);
+        assert_eq!(scheduler.state(), SchedulerState::Countdown);
+        assert!(events.contains(&SchedulerEvent::CountdownStarted {
+            total_seconds: 2,
+            deadline_epoch_ms: 2000
+        }));
+    }
+
+    #[test]
+    fn require_ack_cancels_without_ack() {
+        let (scheduler, events) = run_ack(REQUIRE_ACK, 3, None);
+        assert_eq!(scheduler.state(), SchedulerState::Cancelled);
+        assert_eq!(
+            scheduler.cancel_reason(),
+            Some(&CancelReason::NotAcknowledged)
+        );
+        assert_eq!(
+            events,
+            vec![SchedulerEvent::Cancelled {
+                reason: CancelReason::NotAcknowledged
+            }]
+        );
+    }
+
+    #[test]
+    fn require_ack_extends_once_then_cancels() {
+        let (scheduler, _) = run_ack(REQUIRE_ACK_EXTEND, 3, None);
+        assert_eq!(scheduler.state(), SchedulerState::Pending);
+        assert_eq!(scheduler.ack_state(), AckState::Extended);
+
+        let (scheduler, events) = run_ack(REQUIRE_ACK_EXTEND, 8, None);
+        assert_eq!(scheduler.state(), SchedulerState::Cancelled);
+        assert_eq!(
+            events.last(),
+            Some(&SchedulerEvent::Cancelled {
+                reason: CancelReason::NotAcknowledged
+            })
+        );
+    }
+
+    #[test]
+    fn ack_during_extension_proceeds_at_its_end() {
+        for policy in [EXTEND_ONCE, REQUIRE_ACK_EXTEND] {
+            // Acknowledged on tick 4, inside the 5s extension.
+            let (scheduler, events) = run_ack(policy, 7, Some(4));
+            assert_eq!(scheduler.state(), SchedulerState::Pending);
+            assert_eq!(scheduler.ack_state(), AckState::Acknowledged);
+            assert!(events.contains(&SchedulerEvent::Acknowledged));
+
+            let (scheduler, events) = run_ack(policy, 8, Some(4));
+            assert_eq!(scheduler.state(), SchedulerState::Countdown);
+            assert_eq!(
+                events.last(),
+                Some(&SchedulerEvent::CountdownStarted {
+                    total_seconds: 2,
+                    deadline_epoch_ms: 2000
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn acknowledge_is_idempotent_and_pending_only() {
+        let mut scheduler = ActionScheduler::new(3, 2).with_ack_policy(REQUIRE_ACK);
+        assert!(scheduler.acknowledge().is_err());
+        scheduler.schedule().unwrap();
+        assert!(scheduler.acknowledge().unwrap());
+        assert!(!scheduler.acknowledge().unwrap());
+
+        scheduler.reset();
+        assert_eq!(scheduler.ack_state(), AckState::NotRequired);
+        scheduler.schedule().unwrap();
+        assert_eq!(scheduler.ack_state(), AckState::Awaiting);
+    }
+
+    #[test]
+    fn ack_policy_and_events_serde() {
+        let cases = [
+            (AckPolicy::None, r#"{"policy":"none"}"#),
+            (EXTEND_ONCE, r#"{"policy":"extend_once","extra_secs":5}"#),
+            (
+                REQUIRE_ACK,
+                r#"{"policy":"require_ack","timeout_behavior":"cancel"}"#,
+            ),
+            (
+                REQUIRE_ACK_EXTEND,
+                r#"{"policy":"require_ack","timeout_behavior":{"extend_then_cancel":{"extra_secs":5}}}"#,
+            ),
+        ];
+        for (policy, json) in cases {
+            assert_eq!(serde_json::to_string(&policy).unwrap(), json);
+            assert_eq!(serde_json::from_str::<AckPolicy>(json).unwrap(), policy);
+        }
+
+        for event in [
+            SchedulerEvent::AckRequested { timeout_secs: 60 },
+            SchedulerEvent::Acknowledged,
+        ] {
+            let json = serde_json::to_string(&event).unwrap();
+            assert_eq!(
+                serde_json::from_str::<SchedulerEvent>(&json).unwrap(),
+                event
+            );
+        }
+    }
+
+    #[test]
+    fn cancel_during_countdown() {
+        let mut scheduler = ActionScheduler::new(0, 30);
+        scheduler.schedule().unwrap();
+        scheduler.tick().unwrap(); // → Countdown
+        scheduler.cancel().expect("should cancel");
         assert_eq!(scheduler.state(), SchedulerState::Cancelled);
     }
 
@@ -337,6 +1741,23 @@ mod tests {
         assert_eq!(scheduler.state(), SchedulerState::Executed);
     }
 
+    #[test]
+    fn undrained_events_are_capped() {
+        let mut scheduler = ActionScheduler::new(1, 1);
+        for _ in 0..MAX_BUFFERED_EVENTS {
+            scheduler.schedule().unwrap();
+            scheduler.cancel().unwrap();
+        }
+        // Two events per cycle: half of them were dropped.
+        assert_eq!(scheduler.buffered_events(), MAX_BUFFERED_EVENTS);
+        assert_eq!(scheduler.dropped_events(), MAX_BUFFERED_EVENTS as u64);
+
+        let events = scheduler.take_events();
+        assert_eq!(events.len(), MAX_BUFFERED_EVENTS);
+        assert!(matches!(events[0], SchedulerEvent::PreWarning { .. }));
+        assert_eq!(scheduler.buffered_events(), 0);
+    }
+
     #[test]
     fn cannot_schedule_while_pending() {
         let mut scheduler = ActionScheduler::new(60, 30);
@@ -368,4 +1789,548 @@ mod tests {
             .expect("should reschedule after cancel");
         assert_eq!(scheduler.state(), SchedulerState::Pending);
     }
+
+    #[tokio::test]
+    async fn injected_action_runs_before_executed_event() {
+        let action = flowwatcher_testing::MockAction::new();
+        let mut scheduler = ActionScheduler::new(0, 1).with_action(Box::new(action.clone()));
+        scheduler.schedule().unwrap();
+        scheduler.tick().unwrap();
+        assert!(scheduler.tick().unwrap());
+        assert_eq!(scheduler.state(), SchedulerState::Executed);
+        assert!(!scheduler.take_events().contains(&SchedulerEvent::Executed));
+        assert_eq!(scheduler.action_name().as_deref(), Some("Mock Action"));
+        assert_eq!(scheduler.last_run_duration(), None);
+
+        scheduler.run_pending_action().await.unwrap();
+        assert_eq!(action.executions(), 1);
+        assert!(scheduler.last_run_duration().is_some());
+        assert_eq!(scheduler.take_events(), vec![SchedulerEvent::Executed]);
+
+        // The action only runs once per countdown.
+        assert!(scheduler.run_pending_action().await.is_err());
+        assert_eq!(action.executions(), 1);
+    }
+
+    #[tokio::test]
+    async fn a_split_run_does_not_borrow_the_scheduler() {
+        let action = flowwatcher_testing::MockAction::new();
+        let mut scheduler = ActionScheduler::new(0, 0).with_action(Box::new(action.clone()));
+        scheduler.schedule().unwrap();
+        assert!(scheduler.execute_now().unwrap());
+        scheduler.take_events();
+
+        let mut run = scheduler.begin_pending_action().unwrap();
+        assert!(scheduler.begin_pending_action().is_err());
+        // The scheduler can be read while the action runs.
+        assert_eq!(scheduler.action_name().as_deref(), Some("Mock Action"));
+        run.run().await;
+        scheduler.finish_pending_action(run).unwrap();
+        assert_eq!(action.executions(), 1);
+        assert!(scheduler.last_run_duration().is_some());
+        assert_eq!(scheduler.take_events(), vec![SchedulerEvent::Executed]);
+
+        // A reset during the run keeps the result out of the new session.
+        scheduler.reset();
+        scheduler.schedule().unwrap();
+        assert!(scheduler.execute_now().unwrap());
+        let mut run = scheduler.begin_pending_action().unwrap();
+        scheduler.reset();
+        run.run().await;
+        scheduler.finish_pending_action(run).unwrap();
+        assert_eq!(scheduler.state(), SchedulerState::Idle);
+        assert!(scheduler.take_events().is_empty());
+
+        // A run handed back unrun leaves the action due.
+        scheduler.schedule().unwrap();
+        assert!(scheduler.execute_now().unwrap());
+        let run = scheduler.begin_pending_action().unwrap();
+        assert!(scheduler.finish_pending_action(run).is_err());
+        scheduler.run_pending_action().await.unwrap();
+        assert_eq!(action.executions(), 3);
+    }
+
+    #[tokio::test]
+    async fn replaced_action_runs_in_place_of_the_original() {
+        let original = flowwatcher_testing::MockAction::new();
+        let replacement = flowwatcher_testing::MockAction::new();
+        let mut scheduler = ActionScheduler::new(0, 0).with_action(Box::new(original.clone()));
+        scheduler.schedule().unwrap();
+        assert!(scheduler
+            .replace_action(Box::new(replacement.clone()))
+            .is_some());
+
+        assert!(scheduler.execute_now().unwrap());
+        scheduler.run_pending_action().await.unwrap();
+        assert_eq!(original.executions(), 0);
+        assert_eq!(replacement.executions(), 1);
+    }
+
+    #[tokio::test]
+    async fn power_guard_skips_the_action_on_low_battery() {
+        use crate::power::PowerRules;
+        use flowwatcher_platform::power::{PowerError, PowerStateProvider, PowerStatus};
+
+        struct OnBattery(u8);
+
+        impl PowerStateProvider for OnBattery {
+            fn power_status(&self) -> Result<PowerStatus, PowerError> {
+                Ok(PowerStatus {
+                    on_ac_power: Some(false),
+                    battery_percent: Some(self.0),
+                })
+            }
+        }
+
+        let action = flowwatcher_testing::MockAction::new();
+        let guard = PowerGuard::new(
+            PowerRules {
+                min_battery_percent: Some(20),
+                ..PowerRules::default()
+            },
+            Box::new(OnBattery(12)),
+        );
+        let mut scheduler = ActionScheduler::new(0, 0)
+            .with_action(Box::new(action.clone()))
+            .with_power_guard(guard);
+        scheduler.schedule().unwrap();
+        assert!(scheduler.execute_now().unwrap());
+        scheduler.take_events();
+
+        let err = scheduler.run_pending_action().await.unwrap_err();
+        assert!(matches!(err, SchedulerError::ExecutionSkipped(ref r) if r.contains("12%")));
+        assert_eq!(action.executions(), 0);
+        assert_eq!(scheduler.last_run_duration(), None);
+        assert_eq!(scheduler.state(), SchedulerState::Cancelled);
+        assert!(matches!(
+            scheduler.cancel_reason(),
+            Some(CancelReason::GuardFailed(r)) if r.contains("12%")
+        ));
+        assert!(matches!(
+            scheduler.take_events().as_slice(),
+            [SchedulerEvent::ExecutionSkipped { .. }]
+        ));
+        // Monitoring can schedule it again.
+        scheduler.schedule().unwrap();
+    }
+
+    #[test]
+    fn user_activity_postpones_the_countdown_until_idle() {
+        let input = flowwatcher_testing::MockUserIdleProvider::new(5);
+        let mut scheduler = ActionScheduler::new(2, 10)
+            .with_user_idle_guard(UserIdleGuard::new(60, Box::new(input.clone())))
+            .with_wall_clock(|| 0);
+        scheduler.schedule().unwrap();
+        scheduler.take_events();
+
+        scheduler.tick().unwrap();
+        scheduler.tick().unwrap();
+        assert_eq!(scheduler.state(), SchedulerState::Pending);
+        assert!(scheduler.is_postponed());
+        assert_eq!(
+            scheduler.take_events(),
+            vec![SchedulerEvent::Postponed {
+                seconds_since_input: 5
+            }]
+        );
+
+        // Still active: no repeated event, still no countdown.
+        input.input();
+        scheduler.tick().unwrap();
+        assert_eq!(scheduler.state(), SchedulerState::Pending);
+        assert!(scheduler.take_events().is_empty());
+
+        // Idle long enough: the countdown starts on the next tick.
+        input.set_idle_secs(60);
+        scheduler.tick().unwrap();
+        assert_eq!(scheduler.state(), SchedulerState::Countdown);
+        assert!(!scheduler.is_postponed());
+        assert_eq!(
+            scheduler.take_events(),
+            vec![SchedulerEvent::CountdownStarted {
+                total_seconds: 10,
+                deadline_epoch_ms: 10_000
+            }]
+        );
+    }
+
+    #[test]
+    fn stuck_pending_times_out() {
+        let input = flowwatcher_testing::MockUserIdleProvider::new(5);
+        let mut scheduler = ActionScheduler::new(2, 10)
+            .with_user_idle_guard(UserIdleGuard::new(60, Box::new(input.clone())))
+            .with_pending_timeout_secs(Some(5));
+        scheduler.schedule().unwrap();
+        scheduler.take_events();
+
+        // Postponed by user activity past the end of the pre-warning...
+        for _ in 0..4 {
+            scheduler.tick().unwrap();
+            input.input();
+        }
+        assert_eq!(scheduler.state(), SchedulerState::Pending);
+        scheduler.take_events();
+
+        // ...until the timeout cancels it.
+        assert!(!scheduler.tick().unwrap());
+        assert_eq!(scheduler.state(), SchedulerState::Cancelled);
+        assert_eq!(
+            scheduler.cancel_reason(),
+            Some(&CancelReason::PendingTimeout)
+        );
+        assert_eq!(
+            scheduler.take_events(),
+            vec![SchedulerEvent::TimedOut { pending_secs: 5 }]
+        );
+
+        // A new schedule gets the full timeout again.
+        scheduler.schedule().unwrap();
+        for _ in 0..4 {
+            scheduler.tick().unwrap();
+        }
+        assert_eq!(scheduler.state(), SchedulerState::Pending);
+    }
+
+    #[test]
+    fn pending_waits_forever_without_a_timeout() {
+        let input = flowwatcher_testing::MockUserIdleProvider::new(5);
+        let mut scheduler = ActionScheduler::new(2, 10)
+            .with_user_idle_guard(UserIdleGuard::new(60, Box::new(input.clone())));
+        scheduler.schedule().unwrap();
+        for _ in 0..1000 {
+            scheduler.tick().unwrap();
+            input.input();
+        }
+        assert_eq!(scheduler.state(), SchedulerState::Pending);
+    }
+
+    #[test]
+    fn timed_out_event_round_trips() {
+        let event = SchedulerEvent::TimedOut { pending_secs: 300 };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SchedulerEvent>(&json).unwrap(),
+            event
+        );
+    }
+
+    #[tokio::test]
+    async fn failing_action_moves_to_failed() {
+        let mut scheduler = ActionScheduler::new(60, 30);
+        scheduler
+            .schedule_with_action(Box::new(flowwatcher_testing::MockAction::failing()))
+            .unwrap();
+        assert!(scheduler.execute_now().unwrap());
+
+        let err = scheduler.run_pending_action().await.unwrap_err();
+        assert!(matches!(err, SchedulerError::ActionError(_)));
+        assert_eq!(scheduler.state(), SchedulerState::Failed);
+        assert!(scheduler.last_error().unwrap().contains("mock failure"));
+        let events = scheduler.take_events();
+        assert!(matches!(
+            events.last(),
+            Some(SchedulerEvent::ExecutionFailed { error }) if error.contains("mock failure")
+        ));
+
+        // A failed run can be scheduled again.
+        scheduler.schedule().unwrap();
+        assert_eq!(scheduler.state(), SchedulerState::Pending);
+        assert_eq!(scheduler.last_error(), None);
+    }
+
+    #[tokio::test]
+    async fn run_pending_action_needs_an_injected_action() {
+        let mut scheduler = ActionScheduler::new(0, 30);
+        scheduler.schedule().unwrap();
+        assert!(scheduler.execute_now().unwrap());
+        assert!(scheduler.take_events().contains(&SchedulerEvent::Executed));
+        assert!(matches!(
+            scheduler.run_pending_action().await,
+            Err(SchedulerError::InvalidState { .. })
+        ));
+    }
+
+    #[test]
+    fn execution_failed_event_round_trips() {
+        let event = SchedulerEvent::ExecutionFailed {
+            error: "boom".to_string(),
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SchedulerEvent>(&json).unwrap(),
+            event
+        );
+    }
+
+    #[test]
+    fn pause_freezes_countdown_and_resume_continues() {
+        let mut scheduler = ActionScheduler::new(0, 5);
+        scheduler.schedule().unwrap();
+        scheduler.tick().unwrap(); // → Countdown
+        scheduler.tick().unwrap();
+        scheduler.tick().unwrap();
+        assert_eq!(scheduler.remaining_secs(), Some(3));
+        scheduler.take_events();
+
+        scheduler.pause().unwrap();
+        assert_eq!(scheduler.state(), SchedulerState::PausedCountdown);
+        for _ in 0..10 {
+            assert!(!scheduler.tick().unwrap());
+        }
+        assert_eq!(scheduler.remaining_secs(), Some(3));
+        assert_eq!(scheduler.take_events(), vec![SchedulerEvent::Paused]);
+
+        scheduler.resume().unwrap();
+        assert_eq!(scheduler.state(), SchedulerState::Countdown);
+        assert!(!scheduler.tick().unwrap());
+        assert!(!scheduler.tick().unwrap());
+        assert!(scheduler.tick().unwrap());
+        assert_eq!(
+            scheduler.take_events()[..2],
+            [
+                SchedulerEvent::Resumed,
+                SchedulerEvent::CountdownTick {
+                    remaining_seconds: 2
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn countdown_deadline_is_stable_until_snoozed_or_resumed() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        static NOW_MS: AtomicU64 = AtomicU64::new(1_000_000);
+        let mut scheduler =
+            ActionScheduler::new(0, 30).with_wall_clock(|| NOW_MS.load(Ordering::SeqCst));
+        scheduler.schedule().unwrap();
+        assert_eq!(scheduler.deadline_epoch_ms(), None);
+        scheduler.tick().unwrap(); // → Countdown
+        assert!(scheduler
+            .take_events()
+            .contains(&SchedulerEvent::CountdownStarted {
+                total_seconds: 30,
+                deadline_epoch_ms: 1_030_000
+            }));
+
+        // Ticks do not move it, even if they drift from the wall clock.
+        NOW_MS.store(1_002_500, Ordering::SeqCst);
+        scheduler.tick().unwrap();
+        scheduler.tick().unwrap();
+        assert_eq!(scheduler.deadline_epoch_ms(), Some(1_030_000));
+
+        // 28 s left, snoozed by 60 at 1_002_500.
+        scheduler.take_events();
+        scheduler.snooze(60).unwrap();
+        assert_eq!(scheduler.deadline_epoch_ms(), Some(1_090_500));
+        assert_eq!(
+            scheduler.take_events(),
+            vec![SchedulerEvent::Snoozed {
+                extra_secs: 60,
+                new_remaining_secs: 88,
+                until_epoch_ms: 1_090_500
+            }]
+        );
+
+        // Paused for 100 s: no deadline, then 88 s from the resume.
+        scheduler.pause().unwrap();
+        assert_eq!(scheduler.deadline_epoch_ms(), None);
+        NOW_MS.store(1_102_500, Ordering::SeqCst);
+        scheduler.resume().unwrap();
+        assert_eq!(scheduler.deadline_epoch_ms(), Some(1_190_500));
+
+        scheduler.cancel().unwrap();
+        assert_eq!(scheduler.deadline_epoch_ms(), None);
+    }
+
+    #[test]
+    fn pause_during_pre_warning() {
+        let mut scheduler = ActionScheduler::new(3, 5);
+        scheduler.schedule().unwrap();
+        scheduler.tick().unwrap();
+        scheduler.pause().unwrap();
+        assert_eq!(scheduler.state(), SchedulerState::PausedPending);
+        scheduler.tick().unwrap();
+        scheduler.tick().unwrap();
+        assert_eq!(scheduler.state(), SchedulerState::PausedPending);
+        assert!(scheduler.pause().is_err(), "already paused");
+
+        scheduler.resume().unwrap();
+        assert_eq!(scheduler.remaining_secs(), Some(2));
+        scheduler.tick().unwrap();
+        scheduler.tick().unwrap();
+        assert_eq!(scheduler.state(), SchedulerState::Countdown);
+    }
+
+    #[test]
+    fn execute_now_while_paused_needs_force() {
+        let mut scheduler = ActionScheduler::new(60, 30);
+        scheduler.schedule().unwrap();
+        scheduler.pause().unwrap();
+        assert!(scheduler.execute_now().is_err());
+        assert_eq!(scheduler.state(), SchedulerState::PausedPending);
+        assert!(scheduler.execute_now_with_force(true).unwrap());
+        assert_eq!(scheduler.state(), SchedulerState::Executed);
+    }
+
+    #[test]
+    fn paused_states_can_be_cancelled_and_reset() {
+        let mut scheduler = ActionScheduler::new(60, 30);
+        scheduler.schedule().unwrap();
+        scheduler.pause().unwrap();
+        scheduler.cancel().unwrap();
+        assert_eq!(scheduler.state(), SchedulerState::Cancelled);
+
+        scheduler.schedule().unwrap();
+        scheduler.pause().unwrap();
+        scheduler.reset();
+        assert_eq!(scheduler.state(), SchedulerState::Idle);
+        assert!(scheduler.resume().is_err());
+        assert!(scheduler.pause().is_err());
+    }
+
+    #[test]
+    fn snooze_extends_countdown() {
+        let mut scheduler = ActionScheduler::new(0, 5).with_wall_clock(|| 1_000_000);
+        scheduler.schedule().unwrap();
+        scheduler.tick().unwrap(); // → Countdown
+        scheduler.tick().unwrap();
+        scheduler.take_events();
+
+        scheduler.snooze(600).unwrap();
+        assert_eq!(scheduler.state(), SchedulerState::Countdown);
+        assert_eq!(
+            scheduler.take_events(),
+            vec![SchedulerEvent::Snoozed {
+                extra_secs: 600,
+                new_remaining_secs: 604,
+                until_epoch_ms: 1_604_000
+            }]
+        );
+        scheduler.tick().unwrap();
+        assert_eq!(
+            scheduler.take_events(),
+            vec![SchedulerEvent::CountdownTick {
+                remaining_seconds: 603
+            }]
+        );
+    }
+
+    #[test]
+    fn snooze_extends_pre_warning() {
+        let mut scheduler = ActionScheduler::new(2, 5).with_wall_clock(|| 1_000_000);
+        scheduler.schedule().unwrap();
+        scheduler.tick().unwrap();
+        scheduler.take_events();
+        scheduler.snooze(3).unwrap();
+        assert_eq!(scheduler.remaining_secs(), Some(4));
+        assert_eq!(
+            scheduler.take_events(),
+            vec![SchedulerEvent::Snoozed {
+                extra_secs: 3,
+                new_remaining_secs: 4,
+                until_epoch_ms: 1_004_000
+            }]
+        );
+        for _ in 0..3 {
+            scheduler.tick().unwrap();
+        }
+        assert_eq!(scheduler.state(), SchedulerState::Pending);
+        scheduler.tick().unwrap();
+        assert_eq!(scheduler.state(), SchedulerState::Countdown);
+        assert_eq!(scheduler.remaining_secs(), Some(5));
+    }
+
+    #[test]
+    fn snoozes_accumulate_against_the_cap() {
+        let mut scheduler = ActionScheduler::new(60, 30).with_max_snooze_secs(900);
+        scheduler.schedule().unwrap();
+        scheduler.snooze(600).unwrap();
+        scheduler.snooze(300).unwrap();
+        assert_eq!(scheduler.snoozed_secs(), 900);
+        assert!(matches!(
+            scheduler.snooze(1),
+            Err(SchedulerError::SnoozeLimit {
+                requested: 1,
+                available: 0
+            })
+        ));
+        assert_eq!(scheduler.remaining_secs(), Some(960));
+
+        // A new schedule starts with the full allowance and durations.
+        scheduler.cancel().unwrap();
+        scheduler.schedule().unwrap();
+        assert_eq!(scheduler.snoozed_secs(), 0);
+        assert_eq!(scheduler.remaining_secs(), Some(60));
+    }
+
+    #[test]
+    fn snooze_outside_pending_or_countdown_is_rejected() {
+        let mut scheduler = ActionScheduler::new(60, 30);
+        assert!(matches!(
+            scheduler.snooze(10),
+            Err(SchedulerError::InvalidState { .. })
+        ));
+        scheduler.schedule().unwrap();
+        scheduler.pause().unwrap();
+        assert!(scheduler.snooze(10).is_err());
+    }
+
+    #[test]
+    fn subscribers_each_receive_every_event() {
+        let mut scheduler = ActionScheduler::new(0, 1).with_wall_clock(|| 0);
+        let mut first = scheduler.subscribe();
+        let mut second = scheduler.subscribe();
+
+        scheduler.schedule().unwrap();
+        scheduler.tick().unwrap(); // → Countdown
+        assert!(scheduler.tick().unwrap());
+
+        let drain = |rx: &mut broadcast::Receiver<SchedulerEvent>| {
+            std::iter::from_fn(|| rx.try_recv().ok()).collect::<Vec<_>>()
+        };
+        let buffered = scheduler.take_events();
+        for received in [drain(&mut first), drain(&mut second)] {
+            assert_eq!(received, buffered);
+            assert!(received.contains(&SchedulerEvent::CountdownStarted {
+                total_seconds: 1,
+                deadline_epoch_ms: 1000
+            }));
+            assert_eq!(received.last(), Some(&SchedulerEvent::Executed));
+        }
+    }
+
+    #[test]
+    fn events_arrive_at_the_transition() {
+        let mut scheduler = ActionScheduler::new(60, 30);
+        let mut rx = scheduler.subscribe();
+        scheduler.schedule().unwrap();
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(SchedulerEvent::PreWarning { .. })
+        ));
+        scheduler.cancel().unwrap();
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(SchedulerEvent::Cancelled { .. })
+        ));
+    }
+
+    #[test]
+    fn dropped_receiver_does_not_disturb_the_scheduler() {
+        let mut scheduler = ActionScheduler::new(0, 2);
+        drop(scheduler.subscribe());
+        let mut kept = scheduler.subscribe();
+        drop(scheduler.subscribe());
+
+        scheduler.schedule().unwrap();
+        scheduler.tick().unwrap();
+        scheduler.tick().unwrap();
+        assert!(scheduler.tick().unwrap());
+        scheduler.reset();
+        assert_eq!(scheduler.buffered_events(), 0);
+        assert_eq!(
+            std::iter::from_fn(|| kept.try_recv().ok()).last(),
+            Some(SchedulerEvent::Executed)
+        );
+    }
 }
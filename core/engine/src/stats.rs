@@ -0,0 +1,264 @@
+//! Activity statistics — counts and timings aggregated from the activity
+//! log.
+//!
+//! [`ActivityLogger::stats`] counts entries per [`LogStatus`], per action,
+//! per trigger and per local day within a [`StatsRange`], and summarises
+//! the monitoring sessions that started in it: how they ended and how long
+//! they ran on average. Logs without sessions simply report no session
+//! figures.
+//!
+//! [`ActivityLogger::stats`]: crate::ActivityLogger::stats
+
+use crate::logger::{within_dates, LogEntry, LogStatus, SessionOutcome, SessionRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// ---------------------------------------------------------------------------
+// Range
+// ---------------------------------------------------------------------------
+
+/// Which part of the log to aggregate. The default covers all of it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatsRange {
+    /// Earliest timestamp, as an ISO date or date-time prefix in local
+    /// time (e.g. `2025-01-01`), inclusive.
+    pub from: Option<String>,
+    /// Latest timestamp, as an ISO date or date-time prefix, inclusive.
+    pub to: Option<String>,
+}
+
+impl StatsRange {
+    /// Whether `timestamp` lies within the range.
+    fn contains(&self, timestamp: &str) -> bool {
+        within_dates(timestamp, self.from.as_deref(), self.to.as_deref())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Statistics
+// ---------------------------------------------------------------------------
+
+/// Entry counts by status.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusCounts {
+    /// Actions that ran.
+    pub executed: usize,
+    /// Actions that were cancelled.
+    pub cancelled: usize,
+    /// Errors.
+    pub error: usize,
+    /// Informational events.
+    pub info: usize,
+    /// All of the above.
+    pub total: usize,
+}
+
+impl StatusCounts {
+    /// Count one entry with `status`.
+    fn add(&mut self, status: &LogStatus) {
+        match status {
+            LogStatus::Executed => self.executed += 1,
+            LogStatus::Cancelled => self.cancelled += 1,
+            LogStatus::Error => self.error += 1,
+            LogStatus::Info => self.info += 1,
+        }
+        self.total += 1;
+    }
+}
+
+/// How the monitoring sessions in a range ended and how long they ran.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionStats {
+    /// Sessions started in the range.
+    pub count: usize,
+    /// Sessions that ended with the action running.
+    pub executed: usize,
+    /// Sessions stopped before the action ran.
+    pub stopped: usize,
+    /// Sessions cut short by the app exiting.
+    pub interrupted: usize,
+    /// Sessions still running.
+    pub running: usize,
+    /// Average time from starting to monitor to the action running.
+    pub average_secs_to_execute: Option<u64>,
+    /// Average time from starting to monitor to stopping.
+    pub average_secs_to_stop: Option<u64>,
+}
+
+/// Aggregated activity over a [`StatsRange`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivityStats {
+    /// Counts over every entry in the range.
+    pub totals: StatusCounts,
+    /// Counts per action name.
+    pub by_action: BTreeMap<String, StatusCounts>,
+    /// Counts per trigger reason.
+    pub by_trigger: BTreeMap<String, StatusCounts>,
+    /// Counts per local day (`YYYY-MM-DD`).
+    pub by_day: BTreeMap<String, StatusCounts>,
+    /// Sessions started in the range.
+    pub sessions: SessionStats,
+}
+
+impl ActivityStats {
+    /// Aggregate `entries` and `sessions` within `range`.
+    pub(crate) fn collect(
+        entries: &[LogEntry],
+        sessions: &[SessionRecord],
+        range: &StatsRange,
+    ) -> Self {
+        let mut stats = Self::default();
+        for entry in entries.iter().filter(|e| range.contains(&e.timestamp)) {
+            stats.totals.add(&entry.status);
+            stats
+                .by_action
+                .entry(entry.action_name.clone())
+                .or_default()
+                .add(&entry.status);
+            stats
+                .by_trigger
+                .entry(entry.trigger_reason.clone())
+                .or_default()
+                .add(&entry.status);
+            let day = entry.timestamp.get(..10).unwrap_or(&entry.timestamp);
+            stats
+                .by_day
+                .entry(day.to_string())
+                .or_default()
+                .add(&entry.status);
+        }
+
+        let in_range: Vec<_> = sessions
+            .iter()
+            .filter(|s| range.contains(&s.started_at))
+            .collect();
+        let durations = |outcome: SessionOutcome| -> Vec<u64> {
+            in_range
+                .iter()
+                .filter(|s| s.outcome == Some(outcome))
+                .filter_map(|s| s.duration_secs)
+                .collect()
+        };
+        let count = |outcome: Option<SessionOutcome>| {
+            in_range.iter().filter(|s| s.outcome == outcome).count()
+        };
+        stats.sessions = SessionStats {
+            count: in_range.len(),
+            executed: count(Some(SessionOutcome::Executed)),
+            stopped: count(Some(SessionOutcome::Stopped)),
+            interrupted: count(Some(SessionOutcome::Interrupted)),
+            running: count(None),
+            average_secs_to_execute: average(&durations(SessionOutcome::Executed)),
+            average_secs_to_stop: average(&durations(SessionOutcome::Stopped)),
+        };
+        stats
+    }
+}
+
+/// Rounded-down mean, or `None` for no values.
+fn average(values: &[u64]) -> Option<u64> {
+    let count = u64::try_from(values.len()).ok().filter(|&n| n > 0)?;
+    Some(values.iter().sum::<u64>() / count)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: &str, action: &str, status: LogStatus) -> LogEntry {
+        LogEntry {
+            timestamp: timestamp.into(),
+            trigger_reason: "Network idle".into(),
+            action_name: action.into(),
+            status,
+            details: None,
+            cancel_reason: None,
+            session_id: None,
+            duration_ms: None,
+            metadata: None,
+        }
+    }
+
+    fn session(
+        started_at: &str,
+        outcome: Option<SessionOutcome>,
+        duration_secs: u64,
+    ) -> SessionRecord {
+        SessionRecord {
+            id: 1,
+            started_at: started_at.into(),
+            started_unix_secs: 0,
+            ended_at: None,
+            duration_secs: outcome.map(|_| duration_secs),
+            trigger_reason: "Network idle".into(),
+            action_name: "Shutdown".into(),
+            outcome,
+            details: None,
+        }
+    }
+
+    #[test]
+    fn entries_are_counted_per_status_action_and_day() {
+        let entries = [
+            entry("2025-01-31T23:00:00+01:00", "Shutdown", LogStatus::Executed),
+            entry("2025-02-01T08:00:00+01:00", "Shutdown", LogStatus::Executed),
+            entry(
+                "2025-02-01T09:00:00+01:00",
+                "Shutdown",
+                LogStatus::Cancelled,
+            ),
+            entry("2025-02-02T10:00:00+01:00", "Sleep", LogStatus::Error),
+        ];
+        let range = StatsRange {
+            from: Some("2025-02".into()),
+            to: None,
+        };
+        let stats = ActivityStats::collect(&entries, &[], &range);
+
+        assert_eq!(stats.totals.total, 3);
+        assert_eq!(stats.by_action["Shutdown"].executed, 1);
+        assert_eq!(stats.by_action["Shutdown"].cancelled, 1);
+        assert_eq!(stats.by_action["Sleep"].error, 1);
+        assert_eq!(stats.by_trigger["Network idle"].total, 3);
+        assert_eq!(
+            stats.by_day.keys().collect::<Vec<_>>(),
+            ["2025-02-01", "2025-02-02"]
+        );
+        assert_eq!(stats.sessions, SessionStats::default());
+    }
+
+    #[test]
+    fn sessions_report_outcomes_and_average_durations() {
+        let sessions = [
+            session(
+                "2025-02-01T08:00:00+01:00",
+                Some(SessionOutcome::Executed),
+                600,
+            ),
+            session(
+                "2025-02-01T12:00:00+01:00",
+                Some(SessionOutcome::Executed),
+                301,
+            ),
+            session(
+                "2025-02-02T08:00:00+01:00",
+                Some(SessionOutcome::Stopped),
+                60,
+            ),
+            session("2025-02-03T08:00:00+01:00", None, 0),
+        ];
+        let stats = ActivityStats::collect(&[], &sessions, &StatsRange::default());
+
+        assert_eq!(stats.sessions.count, 4);
+        assert_eq!(stats.sessions.executed, 2);
+        assert_eq!(stats.sessions.running, 1);
+        assert_eq!(stats.sessions.average_secs_to_execute, Some(450));
+        assert_eq!(stats.sessions.average_secs_to_stop, Some(60));
+        assert_eq!(stats.sessions.interrupted, 0);
+    }
+}
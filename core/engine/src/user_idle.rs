@@ -0,0 +1,79 @@
+//! User-idle guard — holds the countdown back while someone is at the
+//! keyboard.
+//!
+//! A quiet network does not mean nobody is using the machine. When the
+//! pre-warning ends, [`ActionScheduler::tick`] asks the [`UserIdleGuard`]
+//! whether there was keyboard or mouse input within the configured window;
+//! if so the countdown is postponed (the scheduler stays in `Pending` and
+//! emits `Postponed` once) and starts on the first tick after the user has
+//! been idle long enough.
+//!
+//! If input activity cannot be read, the guard logs a warning and lets the
+//! countdown start.
+//!
+//! [`ActionScheduler::tick`]: crate::ActionScheduler::tick
+
+use flowwatcher_platform::user_idle::UserIdleProvider;
+
+/// Required input-free time plus the provider it is checked against.
+pub struct UserIdleGuard {
+    /// Input within this many seconds postpones the countdown.
+    required_idle_secs: u64,
+    /// Source of the time since the last input.
+    provider: Box<dyn UserIdleProvider>,
+}
+
+impl UserIdleGuard {
+    /// Require `required_idle_secs` without input before a countdown.
+    pub fn new(required_idle_secs: u64, provider: Box<dyn UserIdleProvider>) -> Self {
+        Self {
+            required_idle_secs,
+            provider,
+        }
+    }
+
+    /// Input-free seconds required before a countdown.
+    pub fn required_idle_secs(&self) -> u64 {
+        self.required_idle_secs
+    }
+
+    /// Seconds since the last input if that is within the window (the user
+    /// is active), or `None` if the countdown may start.
+    pub fn recent_input_secs(&self) -> Option<u64> {
+        match self.provider.seconds_since_last_input() {
+            Ok(secs) => (secs < self.required_idle_secs).then_some(secs),
+            Err(e) => {
+                tracing::warn!("User-idle guard could not read input activity, allowing: {e}");
+                None
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for UserIdleGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UserIdleGuard")
+            .field("required_idle_secs", &self.required_idle_secs)
+            .finish_non_exhaustive()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_testing::MockUserIdleProvider;
+
+    #[test]
+    fn input_within_the_window_counts_as_active() {
+        let input = MockUserIdleProvider::new(10);
+        let guard = UserIdleGuard::new(60, Box::new(input.clone()));
+        assert_eq!(guard.recent_input_secs(), Some(10));
+
+        input.set_idle_secs(60);
+        assert_eq!(guard.recent_input_secs(), None);
+    }
+}
@@ -0,0 +1,134 @@
+//! Long-run memory test: 100k simulated iterations (roughly a day of
+//! one-second ticks) must leave every in-memory buffer at its cap.
+
+use async_trait::async_trait;
+use flowwatcher_conditions::{MonitorMode, ThresholdCondition};
+use flowwatcher_engine::hooks::HookSet;
+use flowwatcher_engine::logger::MAX_ENTRIES;
+use flowwatcher_engine::scheduler::MAX_BUFFERED_EVENTS;
+use flowwatcher_engine::{
+    AckPolicy, ActionScheduler, ActivityLogger, IdleEstimator, LogEntry, LogStatus,
+    MonitoringEngine, MAX_HOOK_LOG,
+};
+use flowwatcher_testing::{process, MockAction};
+use flowwatcher_triggers::history::DEFAULT_MAX_SERIES;
+use flowwatcher_triggers::{
+    ProcessActivityHistory, Trigger, TriggerData, TriggerError, TriggerState, TriggerValue,
+};
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u64 = 100_000;
+
+/// A trigger that alternates between busy and idle traffic forever.
+struct SawtoothTrigger {
+    tick: u64,
+}
+
+#[async_trait]
+impl Trigger for SawtoothTrigger {
+    fn name(&self) -> &str {
+        "Sawtooth"
+    }
+
+    fn trigger_type(&self) -> &str {
+        "sawtooth"
+    }
+
+    async fn start(&mut self) -> Result<(), TriggerError> {
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<(), TriggerError> {
+        Ok(())
+    }
+
+    async fn evaluate(&mut self) -> Result<TriggerState, TriggerError> {
+        self.tick += 1;
+        let speed = if self.tick % 10 < 5 { 10_000 } else { 0 };
+        let mut data = TriggerData::new();
+        data.insert("download_bps", TriggerValue::U64(speed));
+        data.insert("upload_bps", TriggerValue::U64(0));
+        Ok(TriggerState::Active(data))
+    }
+}
+
+#[test]
+fn undrained_scheduler_events_stay_capped() {
+    let mut scheduler =
+        ActionScheduler::new(1, 1).with_ack_policy(AckPolicy::ExtendOnce { extra_secs: 1 });
+    for _ in 0..ITERATIONS {
+        scheduler.schedule().unwrap();
+        scheduler.tick().unwrap();
+        scheduler.cancel().unwrap();
+    }
+    assert_eq!(scheduler.buffered_events(), MAX_BUFFERED_EVENTS);
+    assert!(scheduler.dropped_events() > 0);
+}
+
+#[test]
+fn activity_log_stays_capped() {
+    let mut logger = ActivityLogger::new();
+    for i in 0..ITERATIONS {
+        logger.add_entry(LogEntry::now(
+            "Network idle",
+            "Shutdown",
+            LogStatus::Info,
+            Some(format!("entry {i}")),
+        ));
+    }
+    assert_eq!(logger.len(), MAX_ENTRIES);
+    assert_eq!(logger.snapshot().len(), MAX_ENTRIES);
+}
+
+#[test]
+fn history_buffers_stay_capped() {
+    let start = Instant::now();
+    let mut estimator = IdleEstimator::default();
+    let mut history = ProcessActivityHistory::default();
+    for i in 0..ITERATIONS {
+        estimator.push(start + Duration::from_secs(i), i);
+        // A glob-like watch list that keeps matching new process names.
+        let snapshot = [
+            process(1, "steam.exe", i),
+            process(2, &format!("worker-{}.exe", i % 1_000), 1),
+        ];
+        history.record(i * 1_000, &snapshot);
+    }
+
+    assert_eq!(estimator.len(), estimator.window());
+    assert_eq!(history.len(), DEFAULT_MAX_SERIES);
+    assert!(history.cap_reached());
+    assert!(history.sample_count() <= history.capacity() * DEFAULT_MAX_SERIES);
+    assert_eq!(history.get("steam.exe").unwrap().len(), history.capacity());
+}
+
+#[tokio::test]
+async fn engine_buffers_stay_capped() {
+    let hooks = HookSet {
+        on_start: Some(Box::new(MockAction::new())),
+        on_stop: Some(Box::new(MockAction::new())),
+        on_cancel: None,
+    };
+    let action = MockAction::new();
+    let mut engine = MonitoringEngine::new(
+        Box::new(SawtoothTrigger { tick: 0 }),
+        Box::new(ThresholdCondition::new(100, 0, MonitorMode::DownloadOnly)),
+        Box::new(action.clone()),
+        ActionScheduler::new(1, 1),
+    )
+    .with_hooks(hooks);
+
+    for i in 0..ITERATIONS {
+        // Restart every 50 steps so hooks keep firing and the scheduler
+        // leaves `Executed`; the hook log is never taken.
+        if i % 50 == 0 {
+            engine.stop().await.unwrap();
+            engine.start().await.unwrap();
+        }
+        engine.step().await.unwrap();
+    }
+
+    assert_eq!(engine.hook_log_len(), MAX_HOOK_LOG);
+    assert!(engine.scheduler().buffered_events() <= MAX_BUFFERED_EVENTS);
+    assert!(action.executions() > 0);
+}
@@ -0,0 +1,148 @@
+//! End-to-end pipeline test: scripted traffic → condition → countdown →
+//! action → activity log.
+
+use flowwatcher_actions::Action;
+use flowwatcher_conditions::{Condition, ConditionResult, MonitorMode, ThresholdCondition};
+use flowwatcher_engine::scheduler::SchedulerState;
+use flowwatcher_engine::{
+    ActionScheduler, ActivityLogger, LogEntry, LogStatus, MonitoringEngine, SpeedMonitor,
+    VirtualClock,
+};
+use flowwatcher_testing::{
+    process, MockAction, ScriptedNetworkProvider, ScriptedProcessProvider, MOCK_INTERFACE_ID,
+};
+use flowwatcher_triggers::{ProcessTrigger, TriggerData, TriggerValue};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+const THRESHOLD_BPS: u64 = 1_000;
+
+#[tokio::test]
+async fn traffic_drop_executes_action_and_logs_it() {
+    // Busy for three seconds, then the download finishes.
+    let mut provider = ScriptedNetworkProvider::from_speeds(&[
+        (50_000, 1_000),
+        (50_000, 1_000),
+        (50_000, 1_000),
+        (0, 0),
+        (0, 0),
+        (0, 0),
+        (0, 0),
+    ]);
+    let mut monitor = SpeedMonitor::new(MOCK_INTERFACE_ID, 3);
+    let mut condition = ThresholdCondition::new(THRESHOLD_BPS, 0, MonitorMode::Both);
+    let mut scheduler = ActionScheduler::new(2, 3);
+    let mut logger = ActivityLogger::new();
+    let action = MockAction::new();
+    let boxed: Box<dyn Action> = Box::new(action.clone());
+
+    let mut condition_met_at = None;
+    for second in 0..20 {
+        monitor.poll(&mut provider).expect("poll should succeed");
+        if monitor.latest_reading().is_none() {
+            continue; // Baseline sample — no speed yet.
+        }
+
+        if scheduler.state() == SchedulerState::Idle {
+            let mut data = TriggerData::new();
+            data.insert(
+                "download_bps",
+                TriggerValue::U64(monitor.current_download_speed()),
+            );
+            data.insert(
+                "upload_bps",
+                TriggerValue::U64(monitor.current_upload_speed()),
+            );
+            if condition.evaluate(&data).unwrap() == ConditionResult::Met {
+                condition_met_at = Some(second);
+                scheduler.schedule().unwrap();
+            }
+            continue;
+        }
+
+        if scheduler.tick().unwrap() {
+            boxed.execute().await.expect("mock action should succeed");
+            logger.add_entry(LogEntry::now(
+                "Network idle",
+                boxed.name(),
+                LogStatus::Executed,
+                None,
+            ));
+            break;
+        }
+    }
+
+    let met_at = condition_met_at.expect("condition should have been met");
+    assert!(met_at >= 4, "busy samples must keep the condition waiting");
+    assert_eq!(scheduler.state(), SchedulerState::Executed);
+    assert_eq!(action.executions(), 1);
+
+    let entries = logger.get_all();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].status, LogStatus::Executed);
+    assert_eq!(entries[0].action_name, "Mock Action");
+}
+
+#[tokio::test]
+async fn sustained_traffic_never_schedules() {
+    let mut provider = ScriptedNetworkProvider::from_speeds(&[(50_000, 0); 10]);
+    let mut monitor = SpeedMonitor::new(MOCK_INTERFACE_ID, 3);
+    let mut condition = ThresholdCondition::new(THRESHOLD_BPS, 0, MonitorMode::DownloadOnly);
+
+    for _ in 0..10 {
+        monitor.poll(&mut provider).unwrap();
+        let mut data = TriggerData::new();
+        data.insert(
+            "download_bps",
+            TriggerValue::U64(monitor.current_download_speed()),
+        );
+        data.insert("upload_bps", TriggerValue::U64(0));
+        if monitor.latest_reading().is_some() {
+            assert_eq!(condition.evaluate(&data).unwrap(), ConditionResult::Waiting);
+        }
+    }
+}
+
+#[tokio::test]
+async fn quiet_watched_process_schedules_through_the_condition() {
+    // steam.exe is downloading, then goes quiet; chrome.exe is not watched.
+    let provider = ScriptedProcessProvider::new(vec![
+        vec![
+            process(1, "steam.exe", 50_000),
+            process(2, "chrome.exe", 90_000),
+        ],
+        vec![
+            process(1, "steam.exe", 20),
+            process(2, "chrome.exe", 90_000),
+        ],
+    ]);
+    let trigger = ProcessTrigger::new(vec!["steam.exe".to_string()], vec![], THRESHOLD_BPS)
+        .with_provider(Arc::new(Mutex::new(Box::new(provider))));
+    let clock = VirtualClock::new();
+    let mut engine = MonitoringEngine::new(
+        Box::new(trigger),
+        Box::new(ThresholdCondition::new(
+            THRESHOLD_BPS,
+            2,
+            MonitorMode::DownloadOnly,
+        )),
+        Box::new(MockAction::new()),
+        ActionScheduler::new(2, 3),
+    )
+    .with_clock(Arc::new(clock.clone()));
+    engine.start().await.unwrap();
+
+    // Busy: the trigger is idle and the condition is not consulted.
+    let outcome = engine.step().await.unwrap();
+    assert_eq!(outcome.condition, None);
+
+    // Quiet: the trigger is active and the condition starts timing.
+    let outcome = engine.step().await.unwrap();
+    assert_eq!(outcome.condition.and_then(|c| c.elapsed_secs()), Some(0));
+
+    clock.advance(Duration::from_secs(2));
+    let outcome = engine.step().await.unwrap();
+    assert_eq!(outcome.condition, Some(ConditionResult::Met));
+    assert_eq!(outcome.scheduler, SchedulerState::Pending);
+}
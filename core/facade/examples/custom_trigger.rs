@@ -0,0 +1,149 @@
+//! A third-party trigger and condition running through the engine.
+//!
+//! `PresenceTrigger` simulates an MQTT-style presence sensor that reports
+//! how many people are home. `NobodyHome` is met once that count drops to
+//! zero. Both are registered and driven by a [`MonitoringEngine`] with a
+//! no-op action — no `flowwatcher-*` crate other than `flowwatcher-core`
+//! is needed.
+//!
+//! Run with: `cargo run -p flowwatcher-core --example custom_trigger`
+
+use flowwatcher_core::prelude::*;
+
+// ---------------------------------------------------------------------------
+// Custom trigger
+// ---------------------------------------------------------------------------
+
+/// Reports the number of people present from a scripted sensor feed.
+struct PresenceTrigger {
+    readings: Vec<u64>,
+    index: usize,
+}
+
+#[async_trait]
+impl Trigger for PresenceTrigger {
+    fn name(&self) -> &str {
+        "Presence"
+    }
+
+    fn trigger_type(&self) -> &str {
+        "presence"
+    }
+
+    async fn start(&mut self) -> Result<(), TriggerError> {
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<(), TriggerError> {
+        Ok(())
+    }
+
+    async fn evaluate(&mut self) -> Result<TriggerState, TriggerError> {
+        let people = self.readings[self.index.min(self.readings.len() - 1)];
+        self.index += 1;
+
+        let mut data = TriggerData::new();
+        data.insert("people_present", TriggerValue::U64(people));
+        Ok(TriggerState::Active(data))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Custom condition
+// ---------------------------------------------------------------------------
+
+/// Met when nobody is present.
+struct NobodyHome;
+
+impl Condition for NobodyHome {
+    fn evaluate(&mut self, data: &TriggerData) -> Result<ConditionResult, ConditionError> {
+        match data.get_u64("people_present")? {
+            0 => Ok(ConditionResult::Met),
+            _ => Ok(ConditionResult::Waiting),
+        }
+    }
+
+    fn reset(&mut self) {}
+}
+
+// ---------------------------------------------------------------------------
+// No-op action
+// ---------------------------------------------------------------------------
+
+/// Prints instead of touching the system.
+struct NoopAction;
+
+#[async_trait]
+impl Action for NoopAction {
+    fn name(&self) -> &str {
+        "No-op"
+    }
+
+    fn action_type(&self) -> &str {
+        "noop"
+    }
+
+    fn info(&self) -> ActionInfo {
+        ActionInfo {
+            id: "noop".to_string(),
+            name: "No-op".to_string(),
+            description: "Does nothing".to_string(),
+            available: true,
+            requires_elevation: false,
+            params_schema: None,
+        }
+    }
+
+    async fn validate(&self) -> Result<(), ActionError> {
+        Ok(())
+    }
+
+    async fn execute(&self) -> Result<(), ActionError> {
+        println!("noop action executed");
+        Ok(())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Wiring
+// ---------------------------------------------------------------------------
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), EngineError> {
+    let mut triggers = TriggerRegistry::new();
+    triggers.register(
+        TriggerDescriptor::new("presence", "Presence", "Fires when nobody is home"),
+        |config| {
+            let readings = config
+                .get("readings")
+                .and_then(|v| v.as_array())
+                .map(|a| a.iter().filter_map(|v| v.as_u64()).collect::<Vec<_>>())
+                .filter(|r| !r.is_empty())
+                .ok_or_else(|| TriggerError::StartFailed("missing readings".to_string()))?;
+            Ok(Box::new(PresenceTrigger { readings, index: 0 }))
+        },
+    );
+
+    let mut actions = ActionRegistry::new();
+    actions.register("noop", |_| Ok(Box::new(NoopAction)));
+
+    let config = serde_json::json!({ "readings": [2, 1, 0] });
+    let mut engine = MonitoringEngine::new(
+        triggers.create("presence", &config)?,
+        Box::new(NobodyHome),
+        actions.create("noop", &serde_json::Value::Null)?,
+        ActionScheduler::new(1, 2),
+    );
+
+    engine.start().await?;
+    for second in 0..30 {
+        let outcome = engine.step().await?;
+        println!("t={second}s scheduler={}", outcome.scheduler);
+        if outcome.executed {
+            break;
+        }
+    }
+    engine.stop().await?;
+
+    Ok(())
+}
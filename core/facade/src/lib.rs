@@ -0,0 +1,62 @@
+//! FlowWatcher core — a single dependency for building plugins.
+//!
+//! Re-exports the public surface of the `flowwatcher-*` crates so third
+//! parties can implement their own triggers, conditions, and actions
+//! against one version without hunting through individual crates.
+//!
+//! Most plugins only need the [`prelude`]:
+//!
+//! ```
+//! use flowwatcher_core::prelude::*;
+//!
+//! struct AlwaysIdle;
+//!
+//! #[async_trait]
+//! impl Trigger for AlwaysIdle {
+//!     fn name(&self) -> &str { "Always Idle" }
+//!     fn trigger_type(&self) -> &str { "always_idle" }
+//!     async fn start(&mut self) -> Result<(), TriggerError> { Ok(()) }
+//!     async fn stop(&mut self) -> Result<(), TriggerError> { Ok(()) }
+//!     async fn evaluate(&mut self) -> Result<TriggerState, TriggerError> {
+//!         Ok(TriggerState::Idle)
+//!     }
+//! }
+//!
+//! let mut registry = TriggerRegistry::new();
+//! registry.register(
+//!     TriggerDescriptor::new("always_idle", "Always Idle", "Never fires"),
+//!     |_| Ok(Box::new(AlwaysIdle)),
+//! );
+//! assert!(registry.contains("always_idle"));
+//! ```
+//!
+//! See `examples/custom_trigger.rs` for a complete trigger running
+//! through a [`MonitoringEngine`](prelude::MonitoringEngine).
+
+pub use flowwatcher_actions as actions;
+pub use flowwatcher_conditions as conditions;
+pub use flowwatcher_engine as engine;
+pub use flowwatcher_platform as platform;
+pub use flowwatcher_triggers as triggers;
+
+/// The types needed to write and run a plugin.
+pub mod prelude {
+    pub use async_trait::async_trait;
+
+    pub use flowwatcher_triggers::{
+        Trigger, TriggerData, TriggerDataError, TriggerDescriptor, TriggerError, TriggerRegistry,
+        TriggerState, TriggerValue,
+    };
+
+    pub use flowwatcher_conditions::{
+        Condition, ConditionError, ConditionResult, MonitorMode, ThresholdCondition,
+    };
+
+    pub use flowwatcher_actions::{Action, ActionError, ActionInfo, ActionRegistry};
+
+    pub use flowwatcher_engine::scheduler::{SchedulerEvent, SchedulerState};
+    pub use flowwatcher_engine::{
+        ActionScheduler, ActivityLogger, EngineError, LogEntry, LogStatus, MonitoringEngine,
+        StepOutcome,
+    };
+}
@@ -1,8 +1,19 @@
-//! Windows system action implementations.
+//! System action implementations.
+//!
+//! Each action implements the [`Action`] trait from `flowwatcher-actions`
+//! and maps to an OS command:
+//!
+//! - Windows: `ExitWindowsEx`, `SetSuspendState` and `LockWorkStation`
+//!   (with the `windows` feature), or `shutdown.exe` and `rundll32` with
+//!   [`ExecutionMode::Command`].
+//! - Linux: `systemctl` (poweroff, reboot, suspend, hibernate) and
+//!   `loginctl` (lock-session, terminate-session).
+//! - macOS: `osascript` (System Events) and `pmset`.
 //!
-//! Each action implements the [`Action`] trait from `flowwatcher-actions`.
 //! Actions validate OS capability before executing (e.g., checking if
-//! hibernation is enabled).
+//! hibernation is enabled), and `info().available` reports the same
+//! result. On other platforms every power and session action reports
+//! itself unavailable.
 //!
 //! # Safety
 //!
@@ -11,15 +22,432 @@
 //! All actions are guarded behind `validate()` checks.
 
 use async_trait::async_trait;
-use flowwatcher_actions::{Action, ActionError, ActionInfo};
+use flowwatcher_actions::{Action, ActionError, ActionInfo, ActionRegistry};
 use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// ---------------------------------------------------------------------------
+// Platform commands
+// ---------------------------------------------------------------------------
+
+/// The power and session operations behind the built-in actions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SystemOp {
+    Shutdown,
+    Restart,
+    Sleep,
+    Hibernate,
+    SignOut,
+    LockScreen,
+}
+
+/// How a power or session action is carried out on Windows.
+///
+/// Other platforms always run their command-line tools.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExecutionMode {
+    /// Call the Win32 API directly (`ExitWindowsEx`, `SetSuspendState`,
+    /// `LockWorkStation`), so failures are reported with the OS error.
+    #[default]
+    Native,
+    /// Spawn `shutdown.exe` / `rundll32.exe`. Works without the `windows`
+    /// feature, but only a failure to start the tool is reported.
+    Command,
+}
+
+/// A program and its arguments that perform one [`SystemOp`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OsCommand {
+    /// Program to run (looked up on `PATH`).
+    program: &'static str,
+    /// Arguments passed to the program.
+    args: Vec<String>,
+}
+
+impl OsCommand {
+    fn new(program: &'static str, args: &[&str]) -> Self {
+        Self {
+            program,
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    /// Start the command without waiting for it. `what` completes the
+    /// error message ("Failed to {what}").
+    fn spawn(&self, what: &str) -> Result<(), ActionError> {
+        Command::new(self.program)
+            .args(&self.args)
+            .spawn()
+            .map_err(|e| ActionError::OsError(format!("Failed to {what}: {e}")))?;
+        Ok(())
+    }
+}
+
+/// Resolve how to perform `op` here, or why it cannot be done.
+fn os_command(op: SystemOp) -> Result<OsCommand, String> {
+    platform_commands::command(op)
+}
+
+/// `info().available` for an action backed by `op`.
+fn is_available(op: SystemOp) -> bool {
+    os_command(op).is_ok()
+}
+
+/// `info().requires_elevation` for shutdown and restart: the native call
+/// enables `SeShutdownPrivilege`, which accounts that do not hold it (e.g.
+/// standard users on Windows Server) only get when elevated.
+fn needs_shutdown_privilege(mode: ExecutionMode) -> bool {
+    cfg!(windows) && mode == ExecutionMode::Native
+}
+
+/// `validate()` for an action backed by `op`.
+fn validate_op(op: SystemOp) -> Result<(), ActionError> {
+    os_command(op)
+        .map(|_| ())
+        .map_err(ActionError::NotSupported)
+}
+
+/// `execute()` for an action backed by `op`.
+fn execute_op(op: SystemOp, what: &str, mode: ExecutionMode) -> Result<(), ActionError> {
+    let command = os_command(op).map_err(ActionError::NotSupported)?;
+    #[cfg(all(windows, feature = "windows"))]
+    if mode == ExecutionMode::Native {
+        return native::execute(op, what);
+    }
+    #[cfg(not(all(windows, feature = "windows")))]
+    let _ = mode;
+    command.spawn(what)
+}
+
+/// Direct Win32 calls for [`ExecutionMode::Native`].
+#[cfg(all(windows, feature = "windows"))]
+mod native {
+    use super::SystemOp;
+    use flowwatcher_actions::ActionError;
+
+    pub(super) fn execute(op: SystemOp, what: &str) -> Result<(), ActionError> {
+        use windows_sys::Win32::System::Power::SetSuspendState;
+        use windows_sys::Win32::System::Shutdown::{
+            ExitWindowsEx, LockWorkStation, EWX_LOGOFF, EWX_POWEROFF, EWX_REBOOT, EWX_SHUTDOWN,
+            SHTDN_REASON_FLAG_PLANNED, SHTDN_REASON_MAJOR_OTHER,
+        };
+
+        let reason = SHTDN_REASON_MAJOR_OTHER | SHTDN_REASON_FLAG_PLANNED;
+        // SAFETY: none of these calls take pointers; each return value is
+        // checked below.
+        let ok = unsafe {
+            match op {
+                SystemOp::Shutdown => {
+                    enable_shutdown_privilege()?;
+                    ExitWindowsEx(EWX_SHUTDOWN | EWX_POWEROFF, reason) != 0
+                }
+                SystemOp::Restart => {
+                    enable_shutdown_privilege()?;
+                    ExitWindowsEx(EWX_REBOOT, reason) != 0
+                }
+                // Args: Hibernate, ForceCritical, DisableWakeEvent.
+                SystemOp::Sleep => {
+                    enable_shutdown_privilege()?;
+                    SetSuspendState(0, 1, 0) != 0
+                }
+                SystemOp::Hibernate => {
+                    enable_shutdown_privilege()?;
+                    SetSuspendState(1, 0, 0) != 0
+                }
+                SystemOp::SignOut => ExitWindowsEx(EWX_LOGOFF, reason) != 0,
+                SystemOp::LockScreen => LockWorkStation() != 0,
+            }
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(ActionError::OsError(format!(
+                "Failed to {what}: {}",
+                std::io::Error::last_os_error()
+            )))
+        }
+    }
+
+    /// Enable `SeShutdownPrivilege` on the process token, which shutting
+    /// down, restarting and suspending require.
+    fn enable_shutdown_privilege() -> Result<(), ActionError> {
+        use windows_sys::Win32::Foundation::{
+            CloseHandle, GetLastError, ERROR_NOT_ALL_ASSIGNED, HANDLE, LUID,
+        };
+        use windows_sys::Win32::Security::{
+            AdjustTokenPrivileges, LookupPrivilegeValueW, LUID_AND_ATTRIBUTES,
+            SE_PRIVILEGE_ENABLED, SE_SHUTDOWN_NAME, TOKEN_ADJUST_PRIVILEGES, TOKEN_PRIVILEGES,
+            TOKEN_QUERY,
+        };
+        use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+        let denied = |step: &str| {
+            ActionError::InsufficientPrivileges(format!(
+                "{step} failed for SeShutdownPrivilege: {}",
+                std::io::Error::last_os_error()
+            ))
+        };
+
+        // SAFETY: the token handle is checked before use and always closed;
+        // `luid` and `privileges` are plain structs passed by pointer.
+        unsafe {
+            let mut token: HANDLE = std::ptr::null_mut();
+            if OpenProcessToken(
+                GetCurrentProcess(),
+                TOKEN_ADJUST_PRIVILEGES | TOKEN_QUERY,
+                &mut token,
+            ) == 0
+            {
+                return Err(denied("OpenProcessToken"));
+            }
+
+            let mut luid = LUID {
+                LowPart: 0,
+                HighPart: 0,
+            };
+            let result =
+                if LookupPrivilegeValueW(std::ptr::null(), SE_SHUTDOWN_NAME, &mut luid) == 0 {
+                    Err(denied("LookupPrivilegeValueW"))
+                } else {
+                    let privileges = TOKEN_PRIVILEGES {
+                        PrivilegeCount: 1,
+                        Privileges: [LUID_AND_ATTRIBUTES {
+                            Luid: luid,
+                            Attributes: SE_PRIVILEGE_ENABLED,
+                        }],
+                    };
+                    let ok = AdjustTokenPrivileges(
+                        token,
+                        0,
+                        &privileges,
+                        0,
+                        std::ptr::null_mut(),
+                        std::ptr::null_mut(),
+                    );
+                    // AdjustTokenPrivileges also succeeds when the account does
+                    // not hold the privilege; only the last error tells.
+                    if ok == 0 {
+                        Err(denied("AdjustTokenPrivileges"))
+                    } else if GetLastError() == ERROR_NOT_ALL_ASSIGNED {
+                        Err(ActionError::InsufficientPrivileges(
+                            "this account does not hold SeShutdownPrivilege".to_string(),
+                        ))
+                    } else {
+                        Ok(())
+                    }
+                };
+            CloseHandle(token);
+            result
+        }
+    }
+}
+
+#[cfg(windows)]
+mod platform_commands {
+    use super::{OsCommand, SystemOp};
+    use std::process::Command;
+
+    pub(super) fn command(op: SystemOp) -> Result<OsCommand, String> {
+        Ok(match op {
+            SystemOp::Shutdown => OsCommand::new("shutdown", &["/s", "/t", "0"]),
+            SystemOp::Restart => OsCommand::new("shutdown", &["/r", "/t", "0"]),
+            // `rundll32 powrprof.dll,SetSuspendState 0,1,0` puts machine to sleep.
+            // Args: Hibernate=false, ForceCritical=true, DisableWakeEvent=false
+            SystemOp::Sleep => {
+                OsCommand::new("rundll32.exe", &["powrprof.dll,SetSuspendState", "0,1,0"])
+            }
+            // `shutdown /h` initiates hibernate.
+            SystemOp::Hibernate => OsCommand::new("shutdown", &["/h"]),
+            SystemOp::SignOut => OsCommand::new("shutdown", &["/l"]),
+            SystemOp::LockScreen => OsCommand::new("rundll32.exe", &["user32.dll,LockWorkStation"]),
+        })
+    }
+
+    /// Whether hibernation is enabled, asking the power manager directly
+    /// and falling back to `powercfg` if that fails.
+    pub(super) fn hibernate_available() -> bool {
+        native_hibernate_available().unwrap_or_else(powercfg_lists_hibernate)
+    }
+
+    /// `SystemS4` plus a hibernation file, from `CallNtPowerInformation`.
+    #[cfg(feature = "windows")]
+    fn native_hibernate_available() -> Option<bool> {
+        use windows_sys::Win32::System::Power::{
+            CallNtPowerInformation, SystemPowerCapabilities, SYSTEM_POWER_CAPABILITIES,
+        };
+
+        // SAFETY: SYSTEM_POWER_CAPABILITIES is plain data; the output buffer
+        // is passed with its exact size and the status is checked below.
+        let mut caps: SYSTEM_POWER_CAPABILITIES = unsafe { std::mem::zeroed() };
+        let status = unsafe {
+            CallNtPowerInformation(
+                SystemPowerCapabilities,
+                std::ptr::null(),
+                0,
+                &mut caps as *mut SYSTEM_POWER_CAPABILITIES as *mut core::ffi::c_void,
+                std::mem::size_of::<SYSTEM_POWER_CAPABILITIES>() as u32,
+            )
+        };
+        (status == 0).then(|| caps.SystemS4 != 0 && caps.HiberFilePresent != 0)
+    }
+
+    #[cfg(not(feature = "windows"))]
+    fn native_hibernate_available() -> Option<bool> {
+        None
+    }
+
+    /// Check if hibernation is enabled by parsing `powercfg` output. Only
+    /// reliable on English Windows.
+    fn powercfg_lists_hibernate() -> bool {
+        Command::new("powercfg")
+            .args(["/availablesleepstates"])
+            .output()
+            .map(|output| {
+                let stdout = String::from_utf8_lossy(&output.stdout).to_lowercase();
+                stdout.contains("hibernate")
+            })
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform_commands {
+    use super::{OsCommand, SystemOp};
+    use std::path::Path;
+
+    /// Kernel sleep states, e.g. `"freeze mem disk"`.
+    const POWER_STATE_PATH: &str = "/sys/power/state";
+
+    pub(super) fn command(op: SystemOp) -> Result<OsCommand, String> {
+        let command = match op {
+            SystemOp::Shutdown => OsCommand::new("systemctl", &["poweroff"]),
+            SystemOp::Restart => OsCommand::new("systemctl", &["reboot"]),
+            SystemOp::Sleep => {
+                let states = std::fs::read_to_string(POWER_STATE_PATH).unwrap_or_default();
+                if !supports_suspend(&states) {
+                    return Err("This system does not support suspend to RAM".to_string());
+                }
+                OsCommand::new("systemctl", &["suspend"])
+            }
+            SystemOp::Hibernate => OsCommand::new("systemctl", &["hibernate"]),
+            SystemOp::SignOut => {
+                let session = std::env::var("XDG_SESSION_ID")
+                    .ok()
+                    .filter(|id| !id.is_empty())
+                    .ok_or_else(|| {
+                        "No login session to sign out of (XDG_SESSION_ID is not set)".to_string()
+                    })?;
+                OsCommand::new("loginctl", &["terminate-session", &session])
+            }
+            SystemOp::LockScreen => OsCommand::new("loginctl", &["lock-session"]),
+        };
+        if !on_path(command.program) {
+            return Err(format!(
+                "`{}` was not found; systemd is required",
+                command.program
+            ));
+        }
+        Ok(command)
+    }
+
+    /// Whether the kernel offers suspend to disk.
+    pub(super) fn hibernate_available() -> bool {
+        let states = std::fs::read_to_string(POWER_STATE_PATH).unwrap_or_default();
+        supports_hibernate(&states)
+    }
+
+    /// Whether `/sys/power/state` offers suspend to RAM or to idle.
+    pub(super) fn supports_suspend(states: &str) -> bool {
+        states
+            .split_whitespace()
+            .any(|state| state == "mem" || state == "freeze")
+    }
+
+    /// Whether `/sys/power/state` offers suspend to disk.
+    pub(super) fn supports_hibernate(states: &str) -> bool {
+        states.split_whitespace().any(|state| state == "disk")
+    }
+
+    /// Whether `program` is an executable file on `PATH`.
+    fn on_path(program: &str) -> bool {
+        std::env::var_os("PATH").is_some_and(|paths| {
+            std::env::split_paths(&paths).any(|dir| Path::new(&dir).join(program).is_file())
+        })
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform_commands {
+    use super::{OsCommand, SystemOp};
+
+    /// Ask System Events to do something, e.g. `"shut down"`.
+    fn system_events(verb: &str) -> OsCommand {
+        let script = format!("tell application \"System Events\" to {verb}");
+        OsCommand::new("osascript", &["-e", &script])
+    }
+
+    pub(super) fn command(op: SystemOp) -> Result<OsCommand, String> {
+        Ok(match op {
+            SystemOp::Shutdown => system_events("shut down"),
+            SystemOp::Restart => system_events("restart"),
+            SystemOp::Sleep => OsCommand::new("pmset", &["sleepnow"]),
+            SystemOp::Hibernate => {
+                return Err(
+                    "Hibernate is not available on macOS; use Sleep (pmset hibernatemode decides whether memory is saved to disk)"
+                        .to_string(),
+                )
+            }
+            SystemOp::SignOut => system_events("log out"),
+            // Control-Command-Q, the system "Lock Screen" shortcut.
+            SystemOp::LockScreen => {
+                system_events("keystroke \"q\" using {control down, command down}")
+            }
+        })
+    }
+
+    /// Hibernate has no command here; see [`command`].
+    pub(super) fn hibernate_available() -> bool {
+        false
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+mod platform_commands {
+    use super::{OsCommand, SystemOp};
+
+    pub(super) fn command(_op: SystemOp) -> Result<OsCommand, String> {
+        Err("System actions are not supported on this platform".to_string())
+    }
+
+    pub(super) fn hibernate_available() -> bool {
+        false
+    }
+}
 
 // ---------------------------------------------------------------------------
 // Shutdown Action
 // ---------------------------------------------------------------------------
 
 /// Shuts down the computer gracefully.
-pub struct ShutdownAction;
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShutdownAction {
+    /// Native Win32 call or the command-line tool.
+    mode: ExecutionMode,
+}
+
+impl ShutdownAction {
+    /// Create the action using the native Win32 API where available.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Choose how the action is carried out on Windows.
+    pub fn with_execution_mode(mut self, mode: ExecutionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
 
 #[async_trait]
 impl Action for ShutdownAction {
@@ -36,21 +464,18 @@ impl Action for ShutdownAction {
             id: "shutdown".to_string(),
             name: "Shut Down".to_string(),
             description: "Shut down the computer".to_string(),
-            available: true,
+            available: is_available(SystemOp::Shutdown),
+            requires_elevation: needs_shutdown_privilege(self.mode),
+            params_schema: None,
         }
     }
 
     async fn validate(&self) -> Result<(), ActionError> {
-        // Shutdown is always available on Windows.
-        Ok(())
+        validate_op(SystemOp::Shutdown)
     }
 
     async fn execute(&self) -> Result<(), ActionError> {
-        Command::new("shutdown")
-            .args(["/s", "/t", "0"])
-            .spawn()
-            .map_err(|e| ActionError::OsError(format!("Failed to initiate shutdown: {e}")))?;
-        Ok(())
+        execute_op(SystemOp::Shutdown, "initiate shutdown", self.mode)
     }
 }
 
@@ -59,7 +484,24 @@ impl Action for ShutdownAction {
 // ---------------------------------------------------------------------------
 
 /// Restarts the computer.
-pub struct RestartAction;
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RestartAction {
+    /// Native Win32 call or the command-line tool.
+    mode: ExecutionMode,
+}
+
+impl RestartAction {
+    /// Create the action using the native Win32 API where available.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Choose how the action is carried out on Windows.
+    pub fn with_execution_mode(mut self, mode: ExecutionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
 
 #[async_trait]
 impl Action for RestartAction {
@@ -76,20 +518,18 @@ impl Action for RestartAction {
             id: "restart".to_string(),
             name: "Restart".to_string(),
             description: "Restart the computer".to_string(),
-            available: true,
+            available: is_available(SystemOp::Restart),
+            requires_elevation: needs_shutdown_privilege(self.mode),
+            params_schema: None,
         }
     }
 
     async fn validate(&self) -> Result<(), ActionError> {
-        Ok(())
+        validate_op(SystemOp::Restart)
     }
 
     async fn execute(&self) -> Result<(), ActionError> {
-        Command::new("shutdown")
-            .args(["/r", "/t", "0"])
-            .spawn()
-            .map_err(|e| ActionError::OsError(format!("Failed to initiate restart: {e}")))?;
-        Ok(())
+        execute_op(SystemOp::Restart, "initiate restart", self.mode)
     }
 }
 
@@ -98,7 +538,24 @@ impl Action for RestartAction {
 // ---------------------------------------------------------------------------
 
 /// Puts the computer to sleep (S3 suspend).
-pub struct SleepAction;
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SleepAction {
+    /// Native Win32 call or the command-line tool.
+    mode: ExecutionMode,
+}
+
+impl SleepAction {
+    /// Create the action using the native Win32 API where available.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Choose how the action is carried out on Windows.
+    pub fn with_execution_mode(mut self, mode: ExecutionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
 
 #[async_trait]
 impl Action for SleepAction {
@@ -115,22 +572,18 @@ impl Action for SleepAction {
             id: "sleep".to_string(),
             name: "Sleep".to_string(),
             description: "Put the computer to sleep".to_string(),
-            available: true,
+            available: is_available(SystemOp::Sleep),
+            requires_elevation: false,
+            params_schema: None,
         }
     }
 
     async fn validate(&self) -> Result<(), ActionError> {
-        Ok(())
+        validate_op(SystemOp::Sleep)
     }
 
     async fn execute(&self) -> Result<(), ActionError> {
-        // `rundll32 powrprof.dll,SetSuspendState 0,1,0` puts machine to sleep.
-        // Args: Hibernate=false, ForceCritical=true, DisableWakeEvent=false
-        Command::new("rundll32.exe")
-            .args(["powrprof.dll,SetSuspendState", "0,1,0"])
-            .spawn()
-            .map_err(|e| ActionError::OsError(format!("Failed to initiate sleep: {e}")))?;
-        Ok(())
+        execute_op(SystemOp::Sleep, "initiate sleep", self.mode)
     }
 }
 
@@ -138,20 +591,105 @@ impl Action for SleepAction {
 // Hibernate Action
 // ---------------------------------------------------------------------------
 
+/// How long a [`HibernateQuery`] answer is reused.
+pub const HIBERNATE_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Why [`HibernateAction`] is unavailable when the query says no.
+const HIBERNATE_DISABLED: &str = if cfg!(windows) {
+    "Hibernation is not enabled on this system. Enable with: powercfg /hibernate on"
+} else {
+    "Hibernation is not supported on this system (no \"disk\" in /sys/power/state)"
+};
+
+/// Answers whether the machine can hibernate.
+pub trait HibernateQuery: Send + Sync {
+    /// Whether hibernation is currently enabled.
+    fn hibernate_available(&self) -> bool;
+}
+
+/// [`HibernateQuery`] against the running OS: `CallNtPowerInformation`
+/// on Windows (falling back to parsing `powercfg`), `/sys/power/state`
+/// on Linux.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemHibernateQuery;
+
+impl HibernateQuery for SystemHibernateQuery {
+    fn hibernate_available(&self) -> bool {
+        platform_commands::hibernate_available()
+    }
+}
+
 /// Hibernates the computer (S4 state, saves to disk).
-pub struct HibernateAction;
+///
+/// Availability is cached for [`HIBERNATE_CACHE_TTL`], so listing actions
+/// repeatedly does not query the OS each time.
+pub struct HibernateAction {
+    /// Native Win32 call or the command-line tool.
+    mode: ExecutionMode,
+    /// Source of the availability answer.
+    query: Box<dyn HibernateQuery>,
+    /// Last answer and when it was obtained.
+    cached: Mutex<Option<(Instant, bool)>>,
+}
 
 impl HibernateAction {
-    /// Check if hibernation is enabled by querying `powercfg`.
-    fn is_hibernate_available() -> bool {
-        Command::new("powercfg")
-            .args(["/availablesleepstates"])
-            .output()
-            .map(|output| {
-                let stdout = String::from_utf8_lossy(&output.stdout).to_lowercase();
-                stdout.contains("hibernate")
-            })
-            .unwrap_or(false)
+    /// Create the action using the native Win32 API where available.
+    pub fn new() -> Self {
+        Self {
+            mode: ExecutionMode::default(),
+            query: Box::new(SystemHibernateQuery),
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// Choose how the action is carried out on Windows.
+    pub fn with_execution_mode(mut self, mode: ExecutionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Ask `query` whether hibernation is available.
+    pub fn with_query(mut self, query: Box<dyn HibernateQuery>) -> Self {
+        self.query = query;
+        self.cached = Mutex::new(None);
+        self
+    }
+
+    /// The cached availability, refreshed once it is older than the TTL.
+    fn hibernate_available(&self) -> bool {
+        let mut cached = self.cached.lock().unwrap_or_else(|e| e.into_inner());
+        match *cached {
+            Some((at, available)) if at.elapsed() < HIBERNATE_CACHE_TTL => available,
+            _ => {
+                let available = self.query.hibernate_available();
+                *cached = Some((Instant::now(), available));
+                available
+            }
+        }
+    }
+
+    /// Why hibernating is impossible right now, if it is.
+    fn check(&self) -> Result<(), String> {
+        os_command(SystemOp::Hibernate)?;
+        if self.hibernate_available() {
+            Ok(())
+        } else {
+            Err(HIBERNATE_DISABLED.to_string())
+        }
+    }
+}
+
+impl Default for HibernateAction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for HibernateAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HibernateAction")
+            .field("mode", &self.mode)
+            .finish_non_exhaustive()
     }
 }
 
@@ -170,29 +708,19 @@ impl Action for HibernateAction {
             id: "hibernate".to_string(),
             name: "Hibernate".to_string(),
             description: "Hibernate the computer (save state to disk)".to_string(),
-            available: Self::is_hibernate_available(),
+            available: self.check().is_ok(),
+            requires_elevation: false,
+            params_schema: None,
         }
     }
 
     async fn validate(&self) -> Result<(), ActionError> {
-        if Self::is_hibernate_available() {
-            Ok(())
-        } else {
-            Err(ActionError::NotSupported(
-                "Hibernation is not enabled on this system. Enable with: powercfg /hibernate on"
-                    .to_string(),
-            ))
-        }
+        self.check().map_err(ActionError::NotSupported)
     }
 
     async fn execute(&self) -> Result<(), ActionError> {
         self.validate().await?;
-        // `shutdown /h` initiates hibernate.
-        Command::new("shutdown")
-            .args(["/h"])
-            .spawn()
-            .map_err(|e| ActionError::OsError(format!("Failed to initiate hibernate: {e}")))?;
-        Ok(())
+        execute_op(SystemOp::Hibernate, "initiate hibernate", self.mode)
     }
 }
 
@@ -201,7 +729,24 @@ impl Action for HibernateAction {
 // ---------------------------------------------------------------------------
 
 /// Signs out the current user.
-pub struct SignOutAction;
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SignOutAction {
+    /// Native Win32 call or the command-line tool.
+    mode: ExecutionMode,
+}
+
+impl SignOutAction {
+    /// Create the action using the native Win32 API where available.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Choose how the action is carried out on Windows.
+    pub fn with_execution_mode(mut self, mode: ExecutionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
 
 #[async_trait]
 impl Action for SignOutAction {
@@ -218,20 +763,18 @@ impl Action for SignOutAction {
             id: "sign_out".to_string(),
             name: "Sign Out".to_string(),
             description: "Sign out the current user".to_string(),
-            available: true,
+            available: is_available(SystemOp::SignOut),
+            requires_elevation: false,
+            params_schema: None,
         }
     }
 
     async fn validate(&self) -> Result<(), ActionError> {
-        Ok(())
+        validate_op(SystemOp::SignOut)
     }
 
     async fn execute(&self) -> Result<(), ActionError> {
-        Command::new("shutdown")
-            .args(["/l"])
-            .spawn()
-            .map_err(|e| ActionError::OsError(format!("Failed to sign out: {e}")))?;
-        Ok(())
+        execute_op(SystemOp::SignOut, "sign out", self.mode)
     }
 }
 
@@ -240,7 +783,24 @@ impl Action for SignOutAction {
 // ---------------------------------------------------------------------------
 
 /// Locks the workstation screen.
-pub struct LockScreenAction;
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LockScreenAction {
+    /// Native Win32 call or the command-line tool.
+    mode: ExecutionMode,
+}
+
+impl LockScreenAction {
+    /// Create the action using the native Win32 API where available.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Choose how the action is carried out on Windows.
+    pub fn with_execution_mode(mut self, mode: ExecutionMode) -> Self {
+        self.mode = mode;
+        self
+    }
+}
 
 #[async_trait]
 impl Action for LockScreenAction {
@@ -257,21 +817,102 @@ impl Action for LockScreenAction {
             id: "lock_screen".to_string(),
             name: "Lock Screen".to_string(),
             description: "Lock the workstation".to_string(),
-            available: true,
+            available: is_available(SystemOp::LockScreen),
+            requires_elevation: false,
+            params_schema: None,
         }
     }
 
     async fn validate(&self) -> Result<(), ActionError> {
-        Ok(())
+        validate_op(SystemOp::LockScreen)
     }
 
     async fn execute(&self) -> Result<(), ActionError> {
-        Command::new("rundll32.exe")
-            .args(["user32.dll,LockWorkStation"])
-            .spawn()
-            .map_err(|e| ActionError::OsError(format!("Failed to lock screen: {e}")))?;
-        Ok(())
+        execute_op(SystemOp::LockScreen, "lock screen", self.mode)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Turn Off Display Action
+// ---------------------------------------------------------------------------
+
+/// Powers down the display without sleeping the computer.
+///
+/// Broadcasts `WM_SYSCOMMAND` / `SC_MONITORPOWER` so downloads and other
+/// background work keep running while the monitor is off.
+pub struct TurnOffDisplayAction;
+
+/// `SC_MONITORPOWER` argument that powers the display off.
+#[cfg(all(windows, feature = "windows"))]
+const MONITOR_POWER_OFF: isize = 2;
+
+#[async_trait]
+impl Action for TurnOffDisplayAction {
+    fn name(&self) -> &str {
+        "Turn Off Display"
+    }
+
+    fn action_type(&self) -> &str {
+        "turn_off_display"
     }
+
+    fn info(&self) -> ActionInfo {
+        ActionInfo {
+            id: "turn_off_display".to_string(),
+            name: "Turn Off Display".to_string(),
+            description: "Turn off the monitor without sleeping the computer".to_string(),
+            available: cfg!(all(windows, feature = "windows")),
+            requires_elevation: false,
+            params_schema: None,
+        }
+    }
+
+    async fn validate(&self) -> Result<(), ActionError> {
+        if cfg!(all(windows, feature = "windows")) {
+            Ok(())
+        } else {
+            Err(ActionError::NotSupported(
+                "Turning off the display is only supported on Windows".to_string(),
+            ))
+        }
+    }
+
+    async fn execute(&self) -> Result<(), ActionError> {
+        self.validate().await?;
+        power_off_display()
+    }
+}
+
+#[cfg(all(windows, feature = "windows"))]
+fn power_off_display() -> Result<(), ActionError> {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        PostMessageW, HWND_BROADCAST, SC_MONITORPOWER, WM_SYSCOMMAND,
+    };
+
+    // SAFETY: posting a system command to HWND_BROADCAST passes no pointers;
+    // the return value is checked below.
+    let ok = unsafe {
+        PostMessageW(
+            HWND_BROADCAST,
+            WM_SYSCOMMAND,
+            SC_MONITORPOWER as usize,
+            MONITOR_POWER_OFF,
+        )
+    };
+    if ok == 0 {
+        return Err(ActionError::OsError(format!(
+            "Failed to turn off display: {}",
+            std::io::Error::last_os_error()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(not(all(windows, feature = "windows")))]
+fn power_off_display() -> Result<(), ActionError> {
+    Err(ActionError::NotSupported(
+        "Turning off the display is only supported on Windows".to_string(),
+    ))
 }
 
 // ---------------------------------------------------------------------------
@@ -279,17 +920,70 @@ impl Action for LockScreenAction {
 // ---------------------------------------------------------------------------
 
 /// Returns a list of all available system actions for the current platform.
+///
+/// Actions the platform has no implementation for are left out (hibernate
+/// on macOS, turning off the display outside Windows); actions that exist
+/// but are unusable right now are listed with `available: false`.
 pub fn all_system_actions() -> Vec<Box<dyn Action>> {
     vec![
-        Box::new(ShutdownAction),
-        Box::new(RestartAction),
-        Box::new(SleepAction),
-        Box::new(HibernateAction),
-        Box::new(SignOutAction),
-        Box::new(LockScreenAction),
+        Box::new(ShutdownAction::new()),
+        Box::new(RestartAction::new()),
+        Box::new(SleepAction::new()),
+        #[cfg(not(target_os = "macos"))]
+        Box::new(HibernateAction::new()),
+        Box::new(SignOutAction::new()),
+        Box::new(LockScreenAction::new()),
+        #[cfg(windows)]
+        Box::new(TurnOffDisplayAction),
+        #[cfg(feature = "sysinfo")]
+        Box::new(crate::kill::KillProcessAction::new(
+            crate::kill::KillProcessConfig::default(),
+            crate::kill::sysinfo_process_factory(),
+        )),
+        Box::new(flowwatcher_actions::RunScriptAction::default()),
+        #[cfg(feature = "webhook")]
+        Box::new(flowwatcher_actions::WebhookAction::default()),
     ]
 }
 
+/// An action registry pre-populated with the system actions.
+///
+/// External plugins are registered on top of this at startup.
+pub fn system_action_registry() -> ActionRegistry {
+    let mut registry = ActionRegistry::new();
+    registry.register("shutdown", |_| Ok(Box::new(ShutdownAction::new())));
+    registry.register("restart", |_| Ok(Box::new(RestartAction::new())));
+    registry.register("sleep", |_| Ok(Box::new(SleepAction::new())));
+    #[cfg(not(target_os = "macos"))]
+    registry.register("hibernate", |_| Ok(Box::new(HibernateAction::new())));
+    registry.register("sign_out", |_| Ok(Box::new(SignOutAction::new())));
+    registry.register("lock_screen", |_| Ok(Box::new(LockScreenAction::new())));
+    #[cfg(windows)]
+    registry.register("turn_off_display", |_| Ok(Box::new(TurnOffDisplayAction)));
+    #[cfg(feature = "sysinfo")]
+    registry.register(crate::kill::KILL_PROCESS_ACTION_ID, |params| {
+        Ok(Box::new(crate::kill::KillProcessAction::from_config(
+            params,
+            crate::kill::sysinfo_process_factory(),
+        )?))
+    });
+    registry.register(
+        flowwatcher_actions::script::RUN_SCRIPT_ACTION_ID,
+        |params| {
+            Ok(Box::new(flowwatcher_actions::RunScriptAction::from_config(
+                params,
+            )?))
+        },
+    );
+    #[cfg(feature = "webhook")]
+    registry.register(flowwatcher_actions::webhook::WEBHOOK_ACTION_ID, |params| {
+        Ok(Box::new(flowwatcher_actions::WebhookAction::from_config(
+            params,
+        )?))
+    });
+    registry
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -297,6 +991,8 @@ pub fn all_system_actions() -> Vec<Box<dyn Action>> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
 
     #[test]
     fn all_actions_have_unique_ids() {
@@ -308,6 +1004,18 @@ mod tests {
         assert_eq!(ids.len(), original_len, "some actions have duplicate IDs");
     }
 
+    #[test]
+    fn registry_covers_all_system_actions() {
+        let registry = system_action_registry();
+        for action in all_system_actions() {
+            let created = registry
+                .create(action.action_type(), &serde_json::Value::Null)
+                .expect("system action should be registered");
+            assert_eq!(created.action_type(), action.action_type());
+        }
+        assert_eq!(registry.ids().len(), all_system_actions().len());
+    }
+
     #[test]
     fn all_actions_have_names_and_descriptions() {
         for action in all_system_actions() {
@@ -321,18 +1029,123 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn validate_agrees_with_availability() {
+        // Only queries capability; nothing is executed. Configurable
+        // actions validate their params instead.
+        for action in all_system_actions() {
+            let info = action.info();
+            if info.params_schema.is_some() {
+                continue;
+            }
+            match action.validate().await {
+                Ok(()) => assert!(info.available, "{} validated but is unavailable", info.id),
+                Err(ActionError::NotSupported(reason)) => {
+                    assert!(!info.available, "{} is available but: {reason}", info.id)
+                }
+                Err(e) => panic!("{}: unexpected validation error {e}", info.id),
+            }
+        }
+    }
+
+    #[cfg(any(windows, target_os = "macos"))]
     #[tokio::test]
     async fn shutdown_validates_successfully() {
-        let action = ShutdownAction;
+        let action = ShutdownAction::new();
         action.validate().await.expect("shutdown should validate");
     }
 
+    #[cfg(any(windows, target_os = "macos"))]
     #[tokio::test]
     async fn lock_screen_validates_successfully() {
-        let action = LockScreenAction;
+        let action = LockScreenAction::new();
         action
             .validate()
             .await
             .expect("lock screen should validate");
     }
+
+    #[test]
+    fn actions_default_to_native_execution() {
+        assert_eq!(ShutdownAction::new().mode, ExecutionMode::Native);
+        let action = LockScreenAction::new().with_execution_mode(ExecutionMode::Command);
+        assert_eq!(action.mode, ExecutionMode::Command);
+    }
+
+    /// Counts queries instead of touching the OS.
+    struct CountingQuery {
+        available: bool,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl HibernateQuery for CountingQuery {
+        fn hibernate_available(&self) -> bool {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.available
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[tokio::test]
+    async fn hibernate_info_uses_the_cached_query() {
+        let has_command = os_command(SystemOp::Hibernate).is_ok();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let action = HibernateAction::new().with_query(Box::new(CountingQuery {
+            available: false,
+            calls: calls.clone(),
+        }));
+
+        // No process is spawned: availability comes from the query alone,
+        // asked at most once within the TTL.
+        assert!(!action.info().available);
+        assert!(!action.info().available);
+        assert!(matches!(
+            action.validate().await,
+            Err(ActionError::NotSupported(_))
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), usize::from(has_command));
+
+        let action = HibernateAction::new().with_query(Box::new(CountingQuery {
+            available: true,
+            calls: Arc::new(AtomicUsize::new(0)),
+        }));
+        assert_eq!(action.info().available, has_command);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn linux_sleep_states_are_parsed() {
+        use platform_commands::{supports_hibernate, supports_suspend};
+        assert!(supports_suspend("freeze mem disk\n"));
+        assert!(supports_hibernate("freeze mem disk\n"));
+        assert!(supports_suspend("freeze"));
+        assert!(!supports_hibernate("freeze mem"));
+        assert!(!supports_suspend(""));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn linux_commands_use_systemd() {
+        match os_command(SystemOp::Shutdown) {
+            Ok(command) => {
+                assert_eq!(command, OsCommand::new("systemctl", &["poweroff"]));
+            }
+            Err(reason) => assert!(reason.contains("systemctl"), "{reason}"),
+        }
+    }
+
+    #[cfg(not(all(windows, feature = "windows")))]
+    #[tokio::test]
+    async fn turn_off_display_is_unsupported_off_windows() {
+        let action = TurnOffDisplayAction;
+        assert!(!action.info().available);
+        assert!(matches!(
+            action.validate().await,
+            Err(ActionError::NotSupported(_))
+        ));
+        assert!(matches!(
+            action.execute().await,
+            Err(ActionError::NotSupported(_))
+        ));
+    }
 }
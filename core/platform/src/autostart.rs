@@ -0,0 +1,538 @@
+//! Launching the app at login.
+//!
+//! Provides the [`AutostartProvider`] trait for the OS login-item store and
+//! [`LaunchAtLogin`], which registers the current executable with it. On
+//! Windows (with the `windows` feature) the command lives in the
+//! `HKCU\Software\Microsoft\Windows\CurrentVersion\Run` key, on macOS in a
+//! launch agent plist, and on Linux in an XDG autostart desktop file.
+//!
+//! Installing a new version can move the executable, so
+//! [`LaunchAtLogin::set_enabled`] rewrites a registration that points
+//! somewhere else.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+// ---------------------------------------------------------------------------
+// Error types
+// ---------------------------------------------------------------------------
+
+/// Errors that can occur while reading or changing the login item.
+#[derive(Debug, Clone, PartialEq, Eq, Error, Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum AutostartError {
+    /// The registry key or autostart file may not be changed.
+    #[error("access denied: {0}")]
+    AccessDenied(String),
+
+    /// Login items are not supported on this platform.
+    #[error("not supported: {0}")]
+    NotSupported(String),
+
+    /// Any other failure.
+    #[error("platform error: {0}")]
+    PlatformError(String),
+}
+
+impl From<std::io::Error> for AutostartError {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::PermissionDenied => Self::AccessDenied(e.to_string()),
+            _ => Self::PlatformError(e.to_string()),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Provider trait
+// ---------------------------------------------------------------------------
+
+/// Platform-agnostic trait for the OS store of commands run at login.
+pub trait AutostartProvider: Send + Sync {
+    /// The registered command, or `None` if there is none.
+    fn registered_command(&self) -> Result<Option<String>, AutostartError>;
+
+    /// Register `command` to run at login, replacing any earlier one.
+    fn register(&self, command: &str) -> Result<(), AutostartError>;
+
+    /// Remove the registration. Succeeds if there is none.
+    fn unregister(&self) -> Result<(), AutostartError>;
+
+    /// The command that launches `exe`, as [`registered_command`] reports
+    /// it. Defaults to the quoted path.
+    ///
+    /// [`registered_command`]: AutostartProvider::registered_command
+    fn command_for(&self, exe: &Path) -> String {
+        format!("\"{}\"", exe.display())
+    }
+}
+
+/// The login-item store of this system.
+///
+/// `name` is the display name (e.g. "FlowWatcher"), `id` the reverse-DNS
+/// app identifier used for file names (e.g. "com.flowwatcher.app").
+pub fn system_autostart_provider(
+    name: &str,
+    id: &str,
+) -> Result<Box<dyn AutostartProvider>, AutostartError> {
+    #[cfg(all(windows, feature = "windows"))]
+    {
+        let _ = id;
+        Ok(Box::new(RegistryRunProvider::new(name)))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let _ = name;
+        let home = std::env::var_os("HOME")
+            .ok_or_else(|| AutostartError::PlatformError("HOME is not set".to_string()))?;
+        let path = PathBuf::from(home)
+            .join("Library/LaunchAgents")
+            .join(format!("{id}.plist"));
+        Ok(Box::new(LaunchAgentProvider::new(path, id)))
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let config = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .ok_or_else(|| AutostartError::PlatformError("HOME is not set".to_string()))?;
+        let path = config.join("autostart").join(format!("{id}.desktop"));
+        Ok(Box::new(XdgAutostartProvider::new(path, name)))
+    }
+    #[cfg(not(any(unix, all(windows, feature = "windows"))))]
+    {
+        let _ = (name, id);
+        Err(AutostartError::NotSupported(
+            "Launching at login is not supported on this platform".to_string(),
+        ))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// LaunchAtLogin
+// ---------------------------------------------------------------------------
+
+/// Registers an executable with an [`AutostartProvider`].
+pub struct LaunchAtLogin {
+    /// Where the registration is stored.
+    provider: Box<dyn AutostartProvider>,
+    /// The executable to launch.
+    exe: PathBuf,
+}
+
+impl LaunchAtLogin {
+    /// Launch `exe` at login via `provider`.
+    pub fn new(provider: Box<dyn AutostartProvider>, exe: impl Into<PathBuf>) -> Self {
+        Self {
+            provider,
+            exe: exe.into(),
+        }
+    }
+
+    /// Whether anything is registered, even for an older executable path.
+    pub fn is_enabled(&self) -> Result<bool, AutostartError> {
+        Ok(self.provider.registered_command()?.is_some())
+    }
+
+    /// Register or remove the executable. A registration for a different
+    /// command (e.g. a previous install location) is rewritten. Returns
+    /// whether anything changed.
+    pub fn set_enabled(&self, enabled: bool) -> Result<bool, AutostartError> {
+        let registered = self.provider.registered_command()?;
+        if enabled {
+            let command = self.provider.command_for(&self.exe);
+            if registered.as_deref() == Some(command.as_str()) {
+                return Ok(false);
+            }
+            self.provider.register(&command)?;
+        } else {
+            if registered.is_none() {
+                return Ok(false);
+            }
+            self.provider.unregister()?;
+        }
+        Ok(true)
+    }
+}
+
+impl std::fmt::Debug for LaunchAtLogin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LaunchAtLogin")
+            .field("exe", &self.exe)
+            .finish_non_exhaustive()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// File-based providers
+// ---------------------------------------------------------------------------
+
+/// Remove `path`, treating a missing file as success.
+fn remove_if_present(path: &Path) -> Result<(), AutostartError> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Read `path`, or `None` if it does not exist.
+fn read_if_present(path: &Path) -> Result<Option<String>, AutostartError> {
+    match std::fs::read_to_string(path) {
+        Ok(data) => Ok(Some(data)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Write `data` to `path`, creating parent directories.
+fn write_creating_dirs(path: &Path, data: &str) -> Result<(), AutostartError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    Ok(std::fs::write(path, data)?)
+}
+
+/// An XDG autostart desktop entry (`~/.config/autostart/<id>.desktop`),
+/// used by Linux desktops.
+#[derive(Debug, Clone)]
+pub struct XdgAutostartProvider {
+    /// The desktop file.
+    path: PathBuf,
+    /// The entry's display name.
+    name: String,
+}
+
+impl XdgAutostartProvider {
+    /// Store the entry called `name` in `path`.
+    pub fn new(path: impl Into<PathBuf>, name: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl AutostartProvider for XdgAutostartProvider {
+    fn registered_command(&self) -> Result<Option<String>, AutostartError> {
+        Ok(read_if_present(&self.path)?.and_then(|data| {
+            data.lines()
+                .find_map(|line| line.strip_prefix("Exec="))
+                .map(str::to_string)
+        }))
+    }
+
+    fn register(&self, command: &str) -> Result<(), AutostartError> {
+        write_creating_dirs(
+            &self.path,
+            &format!(
+                "[Desktop Entry]\nType=Application\nName={}\nExec={command}\nX-GNOME-Autostart-enabled=true\n",
+                self.name
+            ),
+        )
+    }
+
+    fn unregister(&self) -> Result<(), AutostartError> {
+        remove_if_present(&self.path)
+    }
+}
+
+/// A launch agent plist (`~/Library/LaunchAgents/<id>.plist`), used by
+/// macOS.
+#[derive(Debug, Clone)]
+pub struct LaunchAgentProvider {
+    /// The plist file.
+    path: PathBuf,
+    /// The agent's label.
+    label: String,
+}
+
+impl LaunchAgentProvider {
+    /// Store the agent labelled `label` in `path`.
+    pub fn new(path: impl Into<PathBuf>, label: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            label: label.into(),
+        }
+    }
+}
+
+impl AutostartProvider for LaunchAgentProvider {
+    fn registered_command(&self) -> Result<Option<String>, AutostartError> {
+        Ok(read_if_present(&self.path)?.and_then(|data| {
+            let args = &data[data.find("<array>")?..];
+            let start = args.find("<string>")? + "<string>".len();
+            let end = start + args[start..].find("</string>")?;
+            Some(xml_unescape(&args[start..end]))
+        }))
+    }
+
+    fn register(&self, command: &str) -> Result<(), AutostartError> {
+        write_creating_dirs(
+            &self.path,
+            &format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+                xml_escape(&self.label),
+                xml_escape(command)
+            ),
+        )
+    }
+
+    fn unregister(&self) -> Result<(), AutostartError> {
+        remove_if_present(&self.path)
+    }
+
+    /// The plain path: launchd takes arguments unquoted.
+    fn command_for(&self, exe: &Path) -> String {
+        exe.display().to_string()
+    }
+}
+
+/// Escape text for an XML element.
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Undo [`xml_escape`].
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+// ---------------------------------------------------------------------------
+// Windows: Run registry key
+// ---------------------------------------------------------------------------
+
+/// A value in `HKCU\Software\Microsoft\Windows\CurrentVersion\Run`.
+#[cfg(all(windows, feature = "windows"))]
+#[derive(Debug, Clone)]
+pub struct RegistryRunProvider {
+    /// Name of the registry value.
+    value_name: String,
+}
+
+#[cfg(all(windows, feature = "windows"))]
+impl RegistryRunProvider {
+    /// Store the command in the value called `value_name`.
+    pub fn new(value_name: impl Into<String>) -> Self {
+        Self {
+            value_name: value_name.into(),
+        }
+    }
+}
+
+#[cfg(all(windows, feature = "windows"))]
+impl AutostartProvider for RegistryRunProvider {
+    fn registered_command(&self) -> Result<Option<String>, AutostartError> {
+        registry::read(&self.value_name)
+    }
+
+    fn register(&self, command: &str) -> Result<(), AutostartError> {
+        registry::write(&self.value_name, command)
+    }
+
+    fn unregister(&self) -> Result<(), AutostartError> {
+        registry::delete(&self.value_name)
+    }
+}
+
+#[cfg(all(windows, feature = "windows"))]
+mod registry {
+    use super::AutostartError;
+    use windows_sys::Win32::Foundation::{
+        ERROR_ACCESS_DENIED, ERROR_FILE_NOT_FOUND, ERROR_SUCCESS, WIN32_ERROR,
+    };
+    use windows_sys::Win32::System::Registry::{
+        RegCloseKey, RegDeleteValueW, RegOpenKeyExW, RegQueryValueExW, RegSetValueExW, HKEY,
+        HKEY_CURRENT_USER, KEY_QUERY_VALUE, KEY_SET_VALUE, REG_SZ,
+    };
+
+    const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    fn error(call: &str, code: WIN32_ERROR) -> AutostartError {
+        let message = format!(
+            "{call} failed: {}",
+            std::io::Error::from_raw_os_error(code as i32)
+        );
+        if code == ERROR_ACCESS_DENIED {
+            AutostartError::AccessDenied(message)
+        } else {
+            AutostartError::PlatformError(message)
+        }
+    }
+
+    /// The open Run key, closed on drop.
+    struct RunKey(HKEY);
+
+    impl RunKey {
+        fn open(access: u32) -> Result<Self, AutostartError> {
+            let path = wide(RUN_KEY);
+            let mut key: HKEY = std::ptr::null_mut();
+            // SAFETY: `path` is NUL-terminated and `key` receives the handle.
+            let code =
+                unsafe { RegOpenKeyExW(HKEY_CURRENT_USER, path.as_ptr(), 0, access, &mut key) };
+            if code != ERROR_SUCCESS {
+                return Err(error("RegOpenKeyExW", code));
+            }
+            Ok(Self(key))
+        }
+    }
+
+    impl Drop for RunKey {
+        fn drop(&mut self) {
+            // SAFETY: the handle was opened by RegOpenKeyExW.
+            unsafe { RegCloseKey(self.0) };
+        }
+    }
+
+    pub(super) fn read(name: &str) -> Result<Option<String>, AutostartError> {
+        let key = RunKey::open(KEY_QUERY_VALUE)?;
+        let name = wide(name);
+        let mut size: u32 = 0;
+        // SAFETY: a null data pointer asks only for the size.
+        let code = unsafe {
+            RegQueryValueExW(
+                key.0,
+                name.as_ptr(),
+                std::ptr::null(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                &mut size,
+            )
+        };
+        match code {
+            ERROR_SUCCESS => {}
+            ERROR_FILE_NOT_FOUND => return Ok(None),
+            code => return Err(error("RegQueryValueExW", code)),
+        }
+        let mut data = vec![0u16; (size as usize).div_ceil(2)];
+        // SAFETY: `data` holds `size` bytes.
+        let code = unsafe {
+            RegQueryValueExW(
+                key.0,
+                name.as_ptr(),
+                std::ptr::null(),
+                std::ptr::null_mut(),
+                data.as_mut_ptr().cast(),
+                &mut size,
+            )
+        };
+        if code != ERROR_SUCCESS {
+            return Err(error("RegQueryValueExW", code));
+        }
+        let text = String::from_utf16_lossy(&data);
+        Ok(Some(text.trim_end_matches('\0').to_string()))
+    }
+
+    pub(super) fn write(name: &str, command: &str) -> Result<(), AutostartError> {
+        let key = RunKey::open(KEY_SET_VALUE)?;
+        let name = wide(name);
+        let data = wide(command);
+        // SAFETY: `data` is a NUL-terminated UTF-16 string of the given
+        // byte length.
+        let code = unsafe {
+            RegSetValueExW(
+                key.0,
+                name.as_ptr(),
+                0,
+                REG_SZ,
+                data.as_ptr().cast(),
+                (data.len() * 2) as u32,
+            )
+        };
+        if code != ERROR_SUCCESS {
+            return Err(error("RegSetValueExW", code));
+        }
+        Ok(())
+    }
+
+    pub(super) fn delete(name: &str) -> Result<(), AutostartError> {
+        let key = RunKey::open(KEY_SET_VALUE)?;
+        let name = wide(name);
+        // SAFETY: `name` is NUL-terminated.
+        match unsafe { RegDeleteValueW(key.0, name.as_ptr()) } {
+            ERROR_SUCCESS | ERROR_FILE_NOT_FOUND => Ok(()),
+            code => Err(error("RegDeleteValueW", code)),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_testing::ScratchDir;
+
+    #[test]
+    fn stale_registrations_are_rewritten() {
+        let dir = ScratchDir::new("autostart-xdg");
+        let path = dir.join("flowwatcher.desktop");
+        let old = LaunchAtLogin::new(
+            Box::new(XdgAutostartProvider::new(&path, "FlowWatcher")),
+            "/opt/FlowWatcher 0.1/flowwatcher",
+        );
+        assert!(!old.is_enabled().unwrap());
+        assert!(old.set_enabled(true).unwrap());
+        assert!(!old.set_enabled(true).unwrap());
+
+        let new = LaunchAtLogin::new(
+            Box::new(XdgAutostartProvider::new(&path, "FlowWatcher")),
+            "/opt/FlowWatcher 0.2/flowwatcher",
+        );
+        assert!(new.is_enabled().unwrap());
+        assert!(new.set_enabled(true).unwrap());
+        let data = std::fs::read_to_string(&path).unwrap();
+        assert!(data.contains("Exec=\"/opt/FlowWatcher 0.2/flowwatcher\"\n"));
+
+        assert!(new.set_enabled(false).unwrap());
+        assert!(!new.is_enabled().unwrap());
+        assert!(!new.set_enabled(false).unwrap());
+    }
+
+    #[test]
+    fn launch_agent_round_trips_escaped_paths() {
+        let dir = ScratchDir::new("autostart-launch-agent");
+        let path = dir.join("com.flowwatcher.app.plist");
+        let provider = LaunchAgentProvider::new(&path, "com.flowwatcher.app");
+        let exe = Path::new("/Applications/Flow & Watcher.app/Contents/MacOS/FlowWatcher");
+        let command = provider.command_for(exe);
+        provider.register(&command).unwrap();
+        assert_eq!(provider.registered_command().unwrap(), Some(command));
+        provider.unregister().unwrap();
+        provider.unregister().unwrap();
+        assert_eq!(provider.registered_command().unwrap(), None);
+    }
+
+    #[test]
+    fn errors_serialize_with_their_kind() {
+        let json = serde_json::to_value(AutostartError::AccessDenied("no".into())).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "kind": "access_denied", "message": "no" })
+        );
+    }
+}
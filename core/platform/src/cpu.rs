@@ -0,0 +1,149 @@
+//! CPU usage sampling.
+//!
+//! Provides the [`CpuProvider`] trait used by the CPU idle trigger and a
+//! `sysinfo`-backed implementation.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+#[cfg(feature = "sysinfo")]
+use sysinfo::{CpuRefreshKind, RefreshKind, System};
+use thiserror::Error;
+
+// ---------------------------------------------------------------------------
+// Error types
+// ---------------------------------------------------------------------------
+
+/// Errors that can occur while sampling CPU usage.
+#[derive(Debug, Error)]
+pub enum CpuError {
+    /// A platform-specific error occurred.
+    #[error("platform error: {0}")]
+    PlatformError(String),
+}
+
+// ---------------------------------------------------------------------------
+// Data types
+// ---------------------------------------------------------------------------
+
+/// CPU utilization measured since the previous refresh.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CpuUsage {
+    /// Utilization across all cores, 0–100.
+    pub total_percent: f64,
+    /// Utilization of each logical core, 0–100.
+    pub per_core_percent: Vec<f64>,
+}
+
+impl CpuUsage {
+    /// Utilization of the busiest core (the total if no cores are listed).
+    pub fn max_core_percent(&self) -> f64 {
+        self.per_core_percent
+            .iter()
+            .copied()
+            .reduce(f64::max)
+            .unwrap_or(self.total_percent)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// CPU provider trait
+// ---------------------------------------------------------------------------
+
+/// Platform-agnostic trait for CPU usage sampling.
+///
+/// Usage is measured between consecutive refreshes, so the first refresh
+/// after creation only establishes a baseline.
+pub trait CpuProvider: Send + Sync {
+    /// Refresh and return usage since the previous refresh.
+    fn refresh(&mut self) -> Result<CpuUsage, CpuError>;
+
+    /// Shortest gap between refreshes that yields a meaningful reading.
+    fn min_refresh_interval(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+// ---------------------------------------------------------------------------
+// sysinfo-based implementation
+// ---------------------------------------------------------------------------
+
+/// CPU provider backed by the `sysinfo` crate.
+///
+/// Requires the `sysinfo` feature (on by default).
+#[cfg(feature = "sysinfo")]
+pub struct SysinfoCpuProvider {
+    system: System,
+}
+
+#[cfg(feature = "sysinfo")]
+impl SysinfoCpuProvider {
+    /// Create a new provider that only tracks CPU usage.
+    pub fn new() -> Self {
+        let system = System::new_with_specifics(
+            RefreshKind::nothing().with_cpu(CpuRefreshKind::nothing().with_cpu_usage()),
+        );
+        Self { system }
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Default for SysinfoCpuProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl CpuProvider for SysinfoCpuProvider {
+    fn refresh(&mut self) -> Result<CpuUsage, CpuError> {
+        self.system.refresh_cpu_usage();
+        Ok(CpuUsage {
+            total_percent: f64::from(self.system.global_cpu_usage()),
+            per_core_percent: self
+                .system
+                .cpus()
+                .iter()
+                .map(|cpu| f64::from(cpu.cpu_usage()))
+                .collect(),
+        })
+    }
+
+    fn min_refresh_interval(&self) -> Duration {
+        sysinfo::MINIMUM_CPU_UPDATE_INTERVAL
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_core_picks_busiest_core() {
+        let usage = CpuUsage {
+            total_percent: 30.0,
+            per_core_percent: vec![5.0, 95.0, 10.0],
+        };
+        assert_eq!(usage.max_core_percent(), 95.0);
+
+        let no_cores = CpuUsage {
+            total_percent: 12.5,
+            per_core_percent: vec![],
+        };
+        assert_eq!(no_cores.max_core_percent(), 12.5);
+    }
+
+    #[cfg(feature = "sysinfo")]
+    #[test]
+    fn sysinfo_provider_reports_percentages() {
+        let mut provider = SysinfoCpuProvider::new();
+        provider.refresh().expect("baseline refresh");
+        std::thread::sleep(provider.min_refresh_interval());
+        let usage = provider.refresh().expect("should sample cpu usage");
+        assert!((0.0..=100.0).contains(&usage.total_percent));
+        assert!(!usage.per_core_percent.is_empty());
+    }
+}
@@ -0,0 +1,572 @@
+//! Privilege elevation: detection, pre-flight checks, and the elevated helper.
+//!
+//! Some actions only work for administrators. Rather than failing at
+//! execution time, callers check [`current_elevation`] against each
+//! action's [`ActionInfo::requires_elevation`] hint up front with
+//! [`check_elevation`].
+//!
+//! Privileged operations can be delegated to a small helper executable
+//! (`flowwatcher-elevated`) launched through UAC with [`run_elevated`].
+//! The helper speaks a narrow JSON protocol: one [`HelperRequest`] in,
+//! one [`HelperResponse`] out. Windows cannot redirect the standard
+//! streams of a process started with the `runas` verb, so the launcher
+//! exchanges the same JSON through two files in a private directory
+//! instead (see [`write_exchange_file`]).
+
+use crate::actions::all_system_actions;
+use flowwatcher_actions::ActionInfo;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Version of the helper protocol spoken by this build.
+pub const HELPER_PROTOCOL_VERSION: u32 = 1;
+
+/// File name of the elevated helper executable.
+pub const HELPER_EXE_NAME: &str = if cfg!(windows) {
+    "flowwatcher-elevated.exe"
+} else {
+    "flowwatcher-elevated"
+};
+
+// ---------------------------------------------------------------------------
+// Error types
+// ---------------------------------------------------------------------------
+
+/// Errors from the elevated helper pathway.
+#[derive(Debug, Error)]
+pub enum ElevationError {
+    /// A protocol message could not be parsed.
+    #[error("invalid helper message: {0}")]
+    Protocol(String),
+
+    /// The peer speaks a different protocol version.
+    #[error("helper protocol version mismatch: expected {expected}, found {found}")]
+    VersionMismatch {
+        /// Version this build speaks.
+        expected: u32,
+        /// Version found in the message.
+        found: u32,
+    },
+
+    /// The user declined the UAC prompt.
+    #[error("elevation was cancelled by the user")]
+    Cancelled,
+
+    /// The helper could not be started or did not answer.
+    #[error("failed to run elevated helper: {0}")]
+    Launch(String),
+
+    /// Elevation is not available on this platform.
+    #[error("elevation is not supported on this platform")]
+    NotSupported,
+}
+
+// ---------------------------------------------------------------------------
+// Detection
+// ---------------------------------------------------------------------------
+
+/// Whether the current process runs with administrator rights.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ElevationStatus {
+    /// The process token is elevated.
+    Elevated,
+    /// The process runs as a standard (filtered) user.
+    NotElevated,
+    /// Elevation could not be determined.
+    Unknown,
+}
+
+impl ElevationStatus {
+    /// Interpret the `TokenIsElevated` field of a `TOKEN_ELEVATION` structure.
+    pub fn from_token_is_elevated(value: u32) -> Self {
+        if value != 0 {
+            Self::Elevated
+        } else {
+            Self::NotElevated
+        }
+    }
+}
+
+/// Query the elevation of the current process.
+///
+/// On Windows this reads `TokenElevation` from the process token. Other
+/// platforms report [`ElevationStatus::Unknown`].
+#[cfg(all(windows, feature = "windows"))]
+pub fn current_elevation() -> ElevationStatus {
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::Security::{
+        GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY,
+    };
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    // SAFETY: the token handle is checked before use and always closed;
+    // `elevation` is a plain struct sized exactly as passed.
+    unsafe {
+        let mut token: HANDLE = std::ptr::null_mut();
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+            return ElevationStatus::Unknown;
+        }
+
+        let mut elevation = TOKEN_ELEVATION { TokenIsElevated: 0 };
+        let mut returned = 0u32;
+        let ok = GetTokenInformation(
+            token,
+            TokenElevation,
+            &mut elevation as *mut TOKEN_ELEVATION as *mut core::ffi::c_void,
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut returned,
+        );
+        CloseHandle(token);
+
+        if ok == 0 {
+            ElevationStatus::Unknown
+        } else {
+            ElevationStatus::from_token_is_elevated(elevation.TokenIsElevated)
+        }
+    }
+}
+
+/// Query the elevation of the current process.
+///
+/// On Windows this reads `TokenElevation` from the process token. Other
+/// platforms report [`ElevationStatus::Unknown`].
+#[cfg(not(all(windows, feature = "windows")))]
+pub fn current_elevation() -> ElevationStatus {
+    ElevationStatus::Unknown
+}
+
+// ---------------------------------------------------------------------------
+// Pre-flight
+// ---------------------------------------------------------------------------
+
+/// Warning that an action will probably fail without elevation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ElevationWarning {
+    /// The affected action id.
+    pub action_id: String,
+    /// Human-readable explanation.
+    pub message: String,
+}
+
+/// Check whether `action` likely needs rights the process lacks.
+///
+/// Returns `None` when the action needs no elevation or the process is
+/// already elevated. An unknown status is treated as not elevated.
+pub fn check_elevation(action: &ActionInfo, status: ElevationStatus) -> Option<ElevationWarning> {
+    if !action.requires_elevation || status == ElevationStatus::Elevated {
+        return None;
+    }
+    Some(ElevationWarning {
+        action_id: action.id.clone(),
+        message: format!(
+            "\"{}\" requires administrator rights but FlowWatcher is not running \
+             elevated; it will fail unless approved through a UAC prompt",
+            action.name
+        ),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Helper protocol
+// ---------------------------------------------------------------------------
+
+/// The operation requested from the helper.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum HelperCommand {
+    /// Liveness check; performs nothing.
+    Ping,
+    /// Validate and execute a built-in system action.
+    Execute {
+        /// Action type id (e.g. "shutdown").
+        action_id: String,
+    },
+}
+
+/// A request sent to the elevated helper.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HelperRequest {
+    /// Protocol version of the sender.
+    pub version: u32,
+    /// The requested operation.
+    #[serde(flatten)]
+    pub command: HelperCommand,
+}
+
+impl HelperRequest {
+    /// Wrap a command with the current protocol version.
+    pub fn new(command: HelperCommand) -> Self {
+        Self {
+            version: HELPER_PROTOCOL_VERSION,
+            command,
+        }
+    }
+
+    /// Encode as a single JSON line.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("helper request is always serializable")
+    }
+
+    /// Decode and check the protocol version.
+    pub fn from_json(json: &str) -> Result<Self, ElevationError> {
+        let request: Self = serde_json::from_str(json.trim())
+            .map_err(|e| ElevationError::Protocol(e.to_string()))?;
+        if request.version != HELPER_PROTOCOL_VERSION {
+            return Err(ElevationError::VersionMismatch {
+                expected: HELPER_PROTOCOL_VERSION,
+                found: request.version,
+            });
+        }
+        Ok(request)
+    }
+}
+
+/// The helper's answer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum HelperResponse {
+    /// The operation succeeded.
+    Ok,
+    /// The operation failed.
+    Error {
+        /// Why it failed.
+        message: String,
+    },
+}
+
+impl HelperResponse {
+    /// Encode as a single JSON line.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("helper response is always serializable")
+    }
+
+    /// Decode a response.
+    pub fn from_json(json: &str) -> Result<Self, ElevationError> {
+        serde_json::from_str(json.trim()).map_err(|e| ElevationError::Protocol(e.to_string()))
+    }
+}
+
+/// Carry out a request inside the helper process.
+pub async fn handle_request(request: &HelperRequest) -> HelperResponse {
+    match &request.command {
+        HelperCommand::Ping => HelperResponse::Ok,
+        HelperCommand::Execute { action_id } => {
+            let Some(action) = all_system_actions()
+                .into_iter()
+                .find(|a| a.action_type() == action_id)
+            else {
+                return HelperResponse::Error {
+                    message: format!("unknown action: {action_id}"),
+                };
+            };
+            let result = match action.validate().await {
+                Ok(()) => action.execute().await,
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(()) => HelperResponse::Ok,
+                Err(e) => HelperResponse::Error {
+                    message: e.to_string(),
+                },
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Launcher
+// ---------------------------------------------------------------------------
+
+/// Run `request` in the helper at `helper`, elevated through UAC.
+///
+/// Blocks until the helper exits. Shows a UAC prompt unless the current
+/// process is already elevated.
+#[cfg(all(windows, feature = "windows"))]
+pub fn run_elevated(
+    helper: &Path,
+    request: &HelperRequest,
+) -> Result<HelperResponse, ElevationError> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Foundation::{CloseHandle, GetLastError, ERROR_CANCELLED};
+    use windows_sys::Win32::System::Threading::{WaitForSingleObject, INFINITE};
+    use windows_sys::Win32::UI::Shell::{
+        ShellExecuteExW, SEE_MASK_NOASYNC, SEE_MASK_NOCLOSEPROCESS, SHELLEXECUTEINFOW,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::SW_HIDE;
+
+    fn wide(s: &std::ffi::OsStr) -> Vec<u16> {
+        s.encode_wide().chain(std::iter::once(0)).collect()
+    }
+
+    let exchange = Exchange::create().map_err(|e| ElevationError::Launch(e.to_string()))?;
+    let request_path = exchange.request_path();
+    let response_path = exchange.response_path();
+    write_exchange_file(&request_path, &request.to_json())
+        .map_err(|e| ElevationError::Launch(e.to_string()))?;
+
+    let params = format!(
+        "--request \"{}\" --response \"{}\"",
+        request_path.display(),
+        response_path.display()
+    );
+    let verb = wide("runas".as_ref());
+    let file = wide(helper.as_os_str());
+    let params = wide(params.as_ref());
+
+    // SAFETY: all string pointers outlive the call; the process handle
+    // returned via SEE_MASK_NOCLOSEPROCESS is waited on and closed.
+    let launched = unsafe {
+        let mut info: SHELLEXECUTEINFOW = std::mem::zeroed();
+        info.cbSize = std::mem::size_of::<SHELLEXECUTEINFOW>() as u32;
+        info.fMask = SEE_MASK_NOCLOSEPROCESS | SEE_MASK_NOASYNC;
+        info.lpVerb = verb.as_ptr();
+        info.lpFile = file.as_ptr();
+        info.lpParameters = params.as_ptr();
+        info.nShow = SW_HIDE;
+
+        if ShellExecuteExW(&mut info) == 0 {
+            if GetLastError() == ERROR_CANCELLED {
+                Err(ElevationError::Cancelled)
+            } else {
+                Err(ElevationError::Launch(
+                    std::io::Error::last_os_error().to_string(),
+                ))
+            }
+        } else {
+            if !info.hProcess.is_null() {
+                WaitForSingleObject(info.hProcess, INFINITE);
+                CloseHandle(info.hProcess);
+            }
+            Ok(())
+        }
+    };
+
+    launched?;
+    let response = std::fs::read_to_string(&response_path)
+        .map_err(|e| ElevationError::Launch(format!("helper did not respond: {e}")))?;
+    HelperResponse::from_json(&response)
+}
+
+/// Run `request` in the helper at `helper`, elevated through UAC.
+///
+/// Blocks until the helper exits. Shows a UAC prompt unless the current
+/// process is already elevated.
+#[cfg(not(all(windows, feature = "windows")))]
+pub fn run_elevated(
+    _helper: &Path,
+    _request: &HelperRequest,
+) -> Result<HelperResponse, ElevationError> {
+    Err(ElevationError::NotSupported)
+}
+
+/// Write one exchange file, failing if `path` already exists, so a file
+/// planted at a predicted path is never read as the request or response.
+pub fn write_exchange_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?
+        .write_all(contents.as_bytes())
+}
+
+/// A private directory holding one request/response exchange.
+///
+/// Its name is unpredictable and it is created fresh, failing if the name
+/// is taken. It lives in the per-user temp directory, which on Windows
+/// only its owner and administrators can read; on Unix it is also made
+/// owner-only. The directory and both files are removed on drop.
+#[cfg_attr(not(all(windows, feature = "windows")), allow(dead_code))]
+struct Exchange {
+    /// The directory.
+    dir: PathBuf,
+}
+
+#[cfg_attr(not(all(windows, feature = "windows")), allow(dead_code))]
+impl Exchange {
+    /// Create a new exchange directory.
+    fn create() -> std::io::Result<Self> {
+        use std::hash::{BuildHasher, Hasher};
+
+        // `RandomState` is seeded from the OS, so the name cannot be
+        // guessed ahead of time.
+        let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+        hasher.write_u128(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos(),
+        );
+        let dir = std::env::temp_dir().join(format!(
+            "flowwatcher-elevated-{}-{:016x}",
+            std::process::id(),
+            hasher.finish()
+        ));
+
+        let mut builder = std::fs::DirBuilder::new();
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        builder.create(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Where the launcher writes the request.
+    fn request_path(&self) -> PathBuf {
+        self.dir.join("request.json")
+    }
+
+    /// Where the helper writes the response.
+    fn response_path(&self) -> PathBuf {
+        self.dir.join("response.json")
+    }
+}
+
+impl Drop for Exchange {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(requires_elevation: bool) -> ActionInfo {
+        ActionInfo {
+            id: "stop_service".to_string(),
+            name: "Stop Service".to_string(),
+            description: String::new(),
+            available: true,
+            requires_elevation,
+            params_schema: None,
+        }
+    }
+
+    #[test]
+    fn token_elevation_flag_is_interpreted() {
+        assert_eq!(
+            ElevationStatus::from_token_is_elevated(1),
+            ElevationStatus::Elevated
+        );
+        assert_eq!(
+            ElevationStatus::from_token_is_elevated(0),
+            ElevationStatus::NotElevated
+        );
+    }
+
+    #[test]
+    fn preflight_warns_only_when_elevation_is_missing() {
+        assert!(check_elevation(&action(false), ElevationStatus::NotElevated).is_none());
+        assert!(check_elevation(&action(true), ElevationStatus::Elevated).is_none());
+
+        let warning = check_elevation(&action(true), ElevationStatus::NotElevated).unwrap();
+        assert_eq!(warning.action_id, "stop_service");
+        assert!(warning.message.contains("Stop Service"));
+
+        assert!(check_elevation(&action(true), ElevationStatus::Unknown).is_some());
+    }
+
+    #[test]
+    fn only_native_shutdown_and_restart_need_elevation() {
+        let status = ElevationStatus::NotElevated;
+        for a in all_system_actions() {
+            let privileged = cfg!(windows) && matches!(a.action_type(), "shutdown" | "restart");
+            assert_eq!(
+                check_elevation(&a.info(), status).is_some(),
+                privileged,
+                "{}",
+                a.action_type()
+            );
+        }
+    }
+
+    #[test]
+    fn exchanges_are_private_and_never_reuse_files() {
+        let exchange = Exchange::create().unwrap();
+        let other = Exchange::create().unwrap();
+        assert_ne!(exchange.dir, other.dir);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&exchange.dir)
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+
+        let request = exchange.request_path();
+        write_exchange_file(&request, "{}").unwrap();
+        assert!(write_exchange_file(&request, "planted").is_err());
+        assert_eq!(std::fs::read_to_string(&request).unwrap(), "{}");
+
+        let dir = exchange.dir.clone();
+        drop(exchange);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn request_wire_format() {
+        let request = HelperRequest::new(HelperCommand::Execute {
+            action_id: "shutdown".to_string(),
+        });
+        let json = request.to_json();
+        assert_eq!(
+            json,
+            r#"{"version":1,"op":"execute","action_id":"shutdown"}"#
+        );
+        assert_eq!(HelperRequest::from_json(&json).unwrap(), request);
+
+        let ping = HelperRequest::from_json("{\"version\":1,\"op\":\"ping\"}\n").unwrap();
+        assert_eq!(ping.command, HelperCommand::Ping);
+    }
+
+    #[test]
+    fn response_wire_format() {
+        assert_eq!(HelperResponse::Ok.to_json(), r#"{"status":"ok"}"#);
+        let error = HelperResponse::Error {
+            message: "denied".to_string(),
+        };
+        assert_eq!(error.to_json(), r#"{"status":"error","message":"denied"}"#);
+        assert_eq!(HelperResponse::from_json(&error.to_json()).unwrap(), error);
+    }
+
+    #[test]
+    fn rejects_malformed_and_mismatched_requests() {
+        assert!(matches!(
+            HelperRequest::from_json("not json"),
+            Err(ElevationError::Protocol(_))
+        ));
+        assert!(matches!(
+            HelperRequest::from_json(r#"{"version":1,"op":"format_disk"}"#),
+            Err(ElevationError::Protocol(_))
+        ));
+        assert!(matches!(
+            HelperRequest::from_json(r#"{"version":2,"op":"ping"}"#),
+            Err(ElevationError::VersionMismatch {
+                expected: 1,
+                found: 2
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn helper_answers_ping_and_rejects_unknown_actions() {
+        let ping = HelperRequest::new(HelperCommand::Ping);
+        assert_eq!(handle_request(&ping).await, HelperResponse::Ok);
+
+        let unknown = HelperRequest::new(HelperCommand::Execute {
+            action_id: "format_disk".to_string(),
+        });
+        assert!(matches!(
+            handle_request(&unknown).await,
+            HelperResponse::Error { message } if message.contains("format_disk")
+        ));
+    }
+}
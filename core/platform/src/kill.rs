@@ -0,0 +1,566 @@
+//! Kill-process action — closes selected processes when triggered.
+//!
+//! Targets are resolved against a fresh [`ProcessProvider`] at execution
+//! time. Each matching process is first asked to close; any still running
+//! after the grace period is force-killed. The resulting [`KillReport`]
+//! lists what was closed, killed, missed, or could not be stopped, so the
+//! activity log shows exactly what happened.
+
+use crate::process::{ProcessError, ProcessInfo, ProcessProvider};
+use async_trait::async_trait;
+use flowwatcher_actions::{Action, ActionError, ActionInfo};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Action type id.
+pub const KILL_PROCESS_ACTION_ID: &str = "kill_process";
+
+/// Default seconds to wait for a process to close before force-killing it.
+pub const DEFAULT_GRACE_PERIOD_SECS: u64 = 5;
+
+/// How often a closing process is checked during the grace period.
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Builds the process provider used to resolve targets at execution time.
+pub type ProcessProviderFactory = Arc<dyn Fn() -> Box<dyn ProcessProvider> + Send + Sync>;
+
+/// A factory for the `sysinfo`-backed process provider.
+#[cfg(feature = "sysinfo")]
+pub fn sysinfo_process_factory() -> ProcessProviderFactory {
+    Arc::new(|| Box::new(crate::process::SysinfoProcessProvider::new()))
+}
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+/// A process to terminate, by PID or by executable name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ProcessTarget {
+    /// A specific process ID.
+    Pid(u32),
+    /// An executable name (e.g., "qbittorrent.exe"), matched case-insensitively.
+    Name(String),
+}
+
+impl fmt::Display for ProcessTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pid(pid) => write!(f, "pid {pid}"),
+            Self::Name(name) => f.write_str(name),
+        }
+    }
+}
+
+/// Which instances of a name target are terminated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceMatch {
+    /// Every running instance.
+    #[default]
+    All,
+    /// Only the instance with the lowest PID.
+    First,
+}
+
+/// Kill-process parameters, as passed to the action registry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KillProcessConfig {
+    /// Processes to terminate.
+    pub targets: Vec<ProcessTarget>,
+    /// Which instances of a name target are terminated.
+    #[serde(default)]
+    pub instances: InstanceMatch,
+    /// Seconds to wait after the close request before force-killing.
+    #[serde(default = "default_grace_period_secs")]
+    pub grace_period_secs: u64,
+}
+
+fn default_grace_period_secs() -> u64 {
+    DEFAULT_GRACE_PERIOD_SECS
+}
+
+impl KillProcessConfig {
+    /// JSON Schema of the kill-process params, for [`ActionInfo::params_schema`].
+    pub fn schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "targets": {
+                    "type": "array",
+                    "minItems": 1,
+                    "items": {
+                        "oneOf": [
+                            { "type": "integer", "minimum": 1, "description": "Process ID" },
+                            { "type": "string", "minLength": 1, "description": "Executable name" }
+                        ]
+                    }
+                },
+                "instances": { "enum": ["all", "first"], "default": "all" },
+                "grace_period_secs": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "default": DEFAULT_GRACE_PERIOD_SECS
+                }
+            },
+            "required": ["targets"]
+        })
+    }
+}
+
+impl Default for KillProcessConfig {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            instances: InstanceMatch::default(),
+            grace_period_secs: DEFAULT_GRACE_PERIOD_SECS,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Report
+// ---------------------------------------------------------------------------
+
+/// What happened to each target of a kill-process run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KillReport {
+    /// Processes that exited after the close request.
+    pub closed: Vec<String>,
+    /// Processes that had to be force-killed.
+    pub killed: Vec<String>,
+    /// Targets with no running process.
+    pub missed: Vec<String>,
+    /// Processes that could not be stopped, with the reason.
+    pub failed: Vec<String>,
+}
+
+impl KillReport {
+    /// One-line summary for an activity log entry.
+    pub fn summary(&self) -> String {
+        let parts: Vec<String> = [
+            ("closed", &self.closed),
+            ("force-killed", &self.killed),
+            ("not found", &self.missed),
+            ("failed", &self.failed),
+        ]
+        .into_iter()
+        .filter(|(_, entries)| !entries.is_empty())
+        .map(|(label, entries)| format!("{label}: {}", entries.join(", ")))
+        .collect();
+
+        if parts.is_empty() {
+            "no processes configured".to_string()
+        } else {
+            parts.join("; ")
+        }
+    }
+}
+
+/// Log label for a process, e.g. `qbittorrent.exe (pid 1234)`.
+fn label(process: &ProcessInfo) -> String {
+    format!("{} (pid {})", process.name, process.pid)
+}
+
+// ---------------------------------------------------------------------------
+// KillProcessAction
+// ---------------------------------------------------------------------------
+
+/// An action that terminates the configured processes.
+#[derive(Clone)]
+pub struct KillProcessAction {
+    /// Kill parameters.
+    config: KillProcessConfig,
+    /// Builds the provider used at execution time.
+    processes: ProcessProviderFactory,
+}
+
+impl KillProcessAction {
+    /// Create a kill-process action from its parameters.
+    pub fn new(config: KillProcessConfig, processes: ProcessProviderFactory) -> Self {
+        Self { config, processes }
+    }
+
+    /// Build from registry params. `null` yields an unconfigured action
+    /// whose `validate()` fails, so the type can still be listed.
+    pub fn from_config(
+        params: &serde_json::Value,
+        processes: ProcessProviderFactory,
+    ) -> Result<Self, ActionError> {
+        if params.is_null() {
+            return Ok(Self::new(KillProcessConfig::default(), processes));
+        }
+        serde_json::from_value(params.clone())
+            .map(|config| Self::new(config, processes))
+            .map_err(|e| ActionError::InvalidConfig(format!("{KILL_PROCESS_ACTION_ID}: {e}")))
+    }
+
+    /// The kill parameters.
+    pub fn config(&self) -> &KillProcessConfig {
+        &self.config
+    }
+
+    /// Match the targets against the running processes.
+    ///
+    /// Returns the processes to terminate (deduplicated by PID) and the
+    /// targets that matched nothing.
+    fn resolve(&self, running: &[ProcessInfo]) -> (Vec<ProcessInfo>, Vec<String>) {
+        let mut matched: Vec<ProcessInfo> = Vec::new();
+        let mut missed = Vec::new();
+
+        for target in &self.config.targets {
+            let mut found: Vec<&ProcessInfo> = match target {
+                ProcessTarget::Pid(pid) => running.iter().filter(|p| p.pid == *pid).collect(),
+                ProcessTarget::Name(name) => running
+                    .iter()
+                    .filter(|p| p.name.eq_ignore_ascii_case(name.trim()))
+                    .collect(),
+            };
+            found.sort_by_key(|p| p.pid);
+            if self.config.instances == InstanceMatch::First {
+                found.truncate(1);
+            }
+
+            if found.is_empty() {
+                missed.push(target.to_string());
+            }
+            for process in found {
+                if !matched.iter().any(|m| m.pid == process.pid) {
+                    matched.push(process.clone());
+                }
+            }
+        }
+        (matched, missed)
+    }
+
+    /// Terminate the matching processes and report what happened.
+    ///
+    /// Fails with [`ActionError::ExecutionFailed`] (carrying the report
+    /// summary) if any matched process could not be stopped.
+    pub async fn execute_with_report(&self) -> Result<KillReport, ActionError> {
+        self.validate().await?;
+
+        let mut provider = (self.processes)();
+        let running = provider
+            .list_processes()
+            .map_err(|e| ActionError::OsError(format!("failed to list processes: {e}")))?;
+        let (targets, missed) = self.resolve(&running);
+
+        let mut report = KillReport {
+            missed,
+            ..KillReport::default()
+        };
+
+        // Ask everything to close first so the grace periods overlap.
+        let mut closing = Vec::new();
+        for process in targets {
+            match provider.terminate_process(process.pid, false) {
+                Ok(()) => closing.push(process),
+                Err(ProcessError::ProcessNotFound(_)) => report.closed.push(label(&process)),
+                // No graceful option: go straight to the force kill.
+                Err(_) => closing.push(process),
+            }
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(self.config.grace_period_secs);
+        loop {
+            closing.retain(|process| {
+                let running = provider.get_process(process.pid).is_ok();
+                if !running {
+                    report.closed.push(label(process));
+                }
+                running
+            });
+            if closing.is_empty() || Instant::now() >= deadline {
+                break;
+            }
+            tokio::time::sleep(EXIT_POLL_INTERVAL).await;
+        }
+
+        for process in closing {
+            match provider.terminate_process(process.pid, true) {
+                Ok(()) => report.killed.push(label(&process)),
+                // Exited between the last check and the kill.
+                Err(ProcessError::ProcessNotFound(_)) => report.closed.push(label(&process)),
+                Err(e) => report.failed.push(format!("{} ({e})", label(&process))),
+            }
+        }
+
+        if report.failed.is_empty() {
+            Ok(report)
+        } else {
+            Err(ActionError::ExecutionFailed(report.summary()))
+        }
+    }
+}
+
+#[async_trait]
+impl Action for KillProcessAction {
+    fn name(&self) -> &str {
+        "Kill Process"
+    }
+
+    fn action_type(&self) -> &str {
+        KILL_PROCESS_ACTION_ID
+    }
+
+    fn info(&self) -> ActionInfo {
+        ActionInfo {
+            id: KILL_PROCESS_ACTION_ID.to_string(),
+            name: self.name().to_string(),
+            description: "Closes the selected processes, force-killing any that do not exit"
+                .to_string(),
+            available: true,
+            requires_elevation: false,
+            params_schema: Some(KillProcessConfig::schema()),
+        }
+    }
+
+    /// Check that at least one target is configured and none is blank.
+    async fn validate(&self) -> Result<(), ActionError> {
+        if self.config.targets.is_empty() {
+            return Err(ActionError::InvalidConfig("no processes configured".into()));
+        }
+        for target in &self.config.targets {
+            match target {
+                ProcessTarget::Pid(0) => {
+                    return Err(ActionError::InvalidConfig("pid 0 is not a process".into()));
+                }
+                ProcessTarget::Name(name) if name.trim().is_empty() => {
+                    return Err(ActionError::InvalidConfig("empty process name".into()));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    async fn execute(&self) -> Result<(), ActionError> {
+        self.execute_with_report().await.map(|_| ())
+    }
+
+    async fn execute_with_details(&self) -> Result<Option<String>, ActionError> {
+        self.execute_with_report()
+            .await
+            .map(|report| Some(report.summary()))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Shared state of a [`FakeProcesses`] provider, inspected by tests.
+    #[derive(Default)]
+    struct FakeState {
+        /// Running processes.
+        running: Vec<ProcessInfo>,
+        /// PIDs that ignore close requests.
+        stubborn: Vec<u32>,
+        /// PIDs that cannot be killed at all.
+        unkillable: Vec<u32>,
+        /// Every termination request, as `(pid, force)`.
+        requests: Vec<(u32, bool)>,
+    }
+
+    /// A process provider over shared, mutable state.
+    struct FakeProcesses(Arc<Mutex<FakeState>>);
+
+    impl ProcessProvider for FakeProcesses {
+        fn list_processes(&mut self) -> Result<Vec<ProcessInfo>, ProcessError> {
+            Ok(self.0.lock().unwrap().running.clone())
+        }
+
+        fn get_process(&mut self, pid: u32) -> Result<ProcessInfo, ProcessError> {
+            let state = self.0.lock().unwrap();
+            state
+                .running
+                .iter()
+                .find(|p| p.pid == pid)
+                .cloned()
+                .ok_or(ProcessError::ProcessNotFound(pid))
+        }
+
+        fn get_suggestions(&mut self, _top_n: usize) -> Result<Vec<ProcessInfo>, ProcessError> {
+            self.list_processes()
+        }
+
+        fn terminate_process(&mut self, pid: u32, force: bool) -> Result<(), ProcessError> {
+            let mut state = self.0.lock().unwrap();
+            state.requests.push((pid, force));
+            if !state.running.iter().any(|p| p.pid == pid) {
+                return Err(ProcessError::ProcessNotFound(pid));
+            }
+            if state.unkillable.contains(&pid) {
+                return Err(ProcessError::PlatformError("access denied".into()));
+            }
+            if force || !state.stubborn.contains(&pid) {
+                state.running.retain(|p| p.pid != pid);
+            }
+            Ok(())
+        }
+    }
+
+    fn process(pid: u32, name: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            path: None,
+            activity_bps: 0,
+            network_bytes_per_sec: None,
+            disk_bytes_per_sec: 0,
+            is_suggested: false,
+            instance_count: 1,
+        }
+    }
+
+    /// Shared state seeded with two qBittorrent instances and a browser.
+    fn fake_state() -> Arc<Mutex<FakeState>> {
+        Arc::new(Mutex::new(FakeState {
+            running: vec![
+                process(30, "qbittorrent.exe"),
+                process(10, "qBittorrent.exe"),
+                process(20, "firefox.exe"),
+            ],
+            ..FakeState::default()
+        }))
+    }
+
+    fn action(state: &Arc<Mutex<FakeState>>, config: KillProcessConfig) -> KillProcessAction {
+        let state = Arc::clone(state);
+        KillProcessAction::new(
+            config,
+            Arc::new(move || Box::new(FakeProcesses(Arc::clone(&state)))),
+        )
+    }
+
+    fn targets(targets: Vec<ProcessTarget>) -> KillProcessConfig {
+        KillProcessConfig {
+            targets,
+            grace_period_secs: 0,
+            ..KillProcessConfig::default()
+        }
+    }
+
+    fn name(name: &str) -> ProcessTarget {
+        ProcessTarget::Name(name.to_string())
+    }
+
+    #[test]
+    fn config_parses_mixed_targets() {
+        let state = fake_state();
+        let parsed = KillProcessAction::from_config(
+            &serde_json::json!({ "targets": ["qbittorrent.exe", 42], "instances": "first" }),
+            Arc::new(move || Box::new(FakeProcesses(Arc::clone(&state)))),
+        )
+        .unwrap();
+        assert_eq!(
+            parsed.config().targets,
+            vec![name("qbittorrent.exe"), ProcessTarget::Pid(42)]
+        );
+        assert_eq!(parsed.config().instances, InstanceMatch::First);
+        assert_eq!(parsed.config().grace_period_secs, DEFAULT_GRACE_PERIOD_SECS);
+    }
+
+    #[tokio::test]
+    async fn validate_rejects_missing_or_blank_targets() {
+        let state = fake_state();
+        for config in [
+            targets(vec![]),
+            targets(vec![name("  ")]),
+            targets(vec![ProcessTarget::Pid(0)]),
+        ] {
+            assert!(matches!(
+                action(&state, config).validate().await,
+                Err(ActionError::InvalidConfig(_))
+            ));
+        }
+        action(&state, targets(vec![name("qbittorrent.exe")]))
+            .validate()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn closes_every_instance_by_default() {
+        let state = fake_state();
+        let report = action(&state, targets(vec![name("QBITTORRENT.EXE")]))
+            .execute_with_report()
+            .await
+            .unwrap();
+        assert_eq!(
+            report.closed,
+            vec!["qBittorrent.exe (pid 10)", "qbittorrent.exe (pid 30)"]
+        );
+        assert!(report.killed.is_empty());
+        assert_eq!(state.lock().unwrap().running.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn first_instance_only_takes_lowest_pid() {
+        let state = fake_state();
+        let mut config = targets(vec![name("qbittorrent.exe")]);
+        config.instances = InstanceMatch::First;
+        let report = action(&state, config).execute_with_report().await.unwrap();
+        assert_eq!(report.closed, vec!["qBittorrent.exe (pid 10)"]);
+        assert!(state.lock().unwrap().running.iter().any(|p| p.pid == 30));
+    }
+
+    #[tokio::test]
+    async fn force_kills_after_grace_period() {
+        let state = fake_state();
+        state.lock().unwrap().stubborn.push(20);
+        let report = action(&state, targets(vec![ProcessTarget::Pid(20)]))
+            .execute_with_report()
+            .await
+            .unwrap();
+        assert_eq!(report.killed, vec!["firefox.exe (pid 20)"]);
+        assert_eq!(
+            state.lock().unwrap().requests,
+            vec![(20, false), (20, true)]
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_missed_targets_without_failing() {
+        let state = fake_state();
+        let details = action(
+            &state,
+            targets(vec![name("steam.exe"), ProcessTarget::Pid(20)]),
+        )
+        .execute_with_details()
+        .await
+        .unwrap();
+        assert_eq!(
+            details.as_deref(),
+            Some("closed: firefox.exe (pid 20); not found: steam.exe")
+        );
+    }
+
+    #[tokio::test]
+    async fn unkillable_process_fails_with_summary() {
+        let state = fake_state();
+        state.lock().unwrap().unkillable.push(20);
+        let err = action(&state, targets(vec![name("firefox.exe")]))
+            .execute()
+            .await
+            .unwrap_err();
+        match err {
+            ActionError::ExecutionFailed(summary) => {
+                assert!(
+                    summary.contains("failed: firefox.exe (pid 20)"),
+                    "{summary}"
+                );
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+}
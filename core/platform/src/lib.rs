@@ -1,17 +1,67 @@
 //! Platform abstraction layer for FlowWatcher.
 //!
 //! Provides traits and implementations for OS-level operations like
-//! network interface querying, stats collection, system actions, and
-//! process enumeration. Currently supports Windows; macOS/Linux can
-//! be added by implementing the same traits.
+//! network interface querying, stats collection, system actions, process
+//! enumeration, and launching at login. System actions and login items are
+//! implemented for Windows, Linux, and macOS; the native power, input, and
+//! elevation queries are Windows-only so far.
+//!
+//! # Features
+//!
+//! - `sysinfo` (default): the `sysinfo`-backed network, process, and CPU
+//!   providers.
+//! - `webhook` (default): lists the webhook action alongside the system
+//!   actions.
+//! - `windows` (default): native elevation checks, UAC launching, the power
+//!   state, input activity, and the Run registry key via `windows-sys`.
+//!   Without it, Windows builds fall back to the portable behaviour used on
+//!   other platforms.
+//!
+//! With `default-features = false` only the traits, data types, and
+//! portable helpers remain, which is all the engine needs.
 
 pub mod actions;
+pub mod autostart;
+pub mod cpu;
+pub mod elevation;
+pub mod kill;
 pub mod network;
+pub mod power;
 pub mod process;
+pub mod process_network;
+pub mod user_idle;
 
 pub use actions::{
-    all_system_actions, HibernateAction, LockScreenAction, RestartAction, ShutdownAction,
-    SignOutAction, SleepAction,
+    all_system_actions, system_action_registry, ExecutionMode, HibernateAction, HibernateQuery,
+    LockScreenAction, RestartAction, ShutdownAction, SignOutAction, SleepAction,
+    SystemHibernateQuery, TurnOffDisplayAction,
+};
+pub use autostart::{
+    system_autostart_provider, AutostartError, AutostartProvider, LaunchAgentProvider,
+    LaunchAtLogin, XdgAutostartProvider,
 };
-pub use network::{InterfaceInfo, NetworkProvider, NetworkStats, SysinfoNetworkProvider};
-pub use process::{ProcessInfo, ProcessProvider, SysinfoProcessProvider};
+pub use cpu::{CpuError, CpuProvider, CpuUsage};
+pub use elevation::{check_elevation, current_elevation, ElevationStatus, ElevationWarning};
+pub use kill::{
+    InstanceMatch, KillProcessAction, KillProcessConfig, KillReport, ProcessProviderFactory,
+    ProcessTarget,
+};
+pub use network::{
+    pick_default_interface, resolve_default_interface, summarize_traffic, InterfaceInfo,
+    InterfaceTraffic, NetworkProvider, NetworkStats, NetworkStatsSnapshot, TrafficSnapshot,
+};
+pub use power::{PowerError, PowerStateProvider, PowerStatus, SystemPowerProvider};
+pub use process::{
+    ProcessInfo, ProcessPage, ProcessProvider, ProcessQuery, ProcessRefresher, ProcessSample,
+};
+pub use process_network::{system_process_network_provider, ProcessNetworkProvider};
+pub use user_idle::{SystemUserIdleProvider, UserIdleError, UserIdleProvider};
+
+#[cfg(all(windows, feature = "windows"))]
+pub use autostart::RegistryRunProvider;
+#[cfg(feature = "sysinfo")]
+pub use cpu::SysinfoCpuProvider;
+#[cfg(feature = "sysinfo")]
+pub use network::SysinfoNetworkProvider;
+#[cfg(feature = "sysinfo")]
+pub use process::{SysinfoProcessProvider, SysinfoProcessRefresher};
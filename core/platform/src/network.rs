@@ -1,7 +1,10 @@
 //! Network interface abstraction and platform implementations.
 
 use serde::{Deserialize, Serialize};
-use std::time::Instant;
+#[cfg(feature = "sysinfo")]
+use std::collections::HashMap;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "sysinfo")]
 use sysinfo::Networks;
 use thiserror::Error;
 
@@ -25,6 +28,110 @@ pub enum NetworkError {
 // Data types
 // ---------------------------------------------------------------------------
 
+/// Broad category of a network interface.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterfaceKind {
+    /// Wired Ethernet.
+    Ethernet,
+    /// Wireless LAN.
+    Wifi,
+    /// The loopback interface.
+    Loopback,
+    /// A software adapter: VPN, tunnel, bridge, hypervisor switch, etc.
+    Virtual,
+    /// Anything not recognised.
+    #[default]
+    Other,
+}
+
+impl InterfaceKind {
+    /// Whether this is a physical network adapter.
+    pub fn is_physical(self) -> bool {
+        matches!(self, Self::Ethernet | Self::Wifi)
+    }
+
+    /// Guess the kind from an adapter name or description.
+    pub fn from_label(label: &str) -> Self {
+        const VIRTUAL_MARKERS: &[&str] = &[
+            "virtual",
+            "vethernet",
+            "hyper-v",
+            "vpn",
+            "tap-windows",
+            "wireguard",
+            "tailscale",
+            "zerotier",
+            "vmware",
+            "virtualbox",
+            "docker",
+            "wi-fi direct",
+            "local area connection*",
+            "isatap",
+            "teredo",
+        ];
+        const VIRTUAL_PREFIXES: &[&str] = &[
+            "veth", "br-", "virbr", "vmnet", "vboxnet", "tun", "tap", "wg", "utun", "zt", "bridge",
+            "awdl", "llw",
+        ];
+
+        let label = label.trim().to_lowercase();
+        if label == "lo" || label.starts_with("lo0") || label.contains("loopback") {
+            Self::Loopback
+        } else if VIRTUAL_MARKERS.iter().any(|m| label.contains(m))
+            || VIRTUAL_PREFIXES.iter().any(|p| label.starts_with(p))
+        {
+            Self::Virtual
+        } else if ["wi-fi", "wifi", "wireless", "wlan", "802.11"]
+            .iter()
+            .any(|m| label.contains(m))
+            || label.starts_with("wl")
+        {
+            Self::Wifi
+        } else if label.contains("ethernet")
+            || ["eth", "en", "em"].iter().any(|p| label.starts_with(p))
+        {
+            Self::Ethernet
+        } else {
+            Self::Other
+        }
+    }
+
+    /// Map an IANA `ifType` (as reported by Windows' `IfType`).
+    pub fn from_if_type(if_type: u32) -> Self {
+        match if_type {
+            6 => Self::Ethernet,
+            71 => Self::Wifi,
+            24 => Self::Loopback,
+            53 | 131 => Self::Virtual,
+            _ => Self::Other,
+        }
+    }
+
+    /// Combine name and description heuristics with the kind the OS
+    /// reports, if any.
+    ///
+    /// The OS wins, except that a "physical" adapter whose name or
+    /// description marks it as virtual (a Hyper-V switch reports as
+    /// Ethernet, a Wi-Fi Direct adapter as Wi-Fi) counts as virtual.
+    pub fn classify(name: &str, description: Option<&str>, reported: Option<Self>) -> Self {
+        let by_name = Self::from_label(name);
+        let by_description = description.map_or(Self::Other, Self::from_label);
+        let hinted = if by_name == Self::Virtual || by_description == Self::Virtual {
+            Self::Virtual
+        } else if by_name != Self::Other {
+            by_name
+        } else {
+            by_description
+        };
+        match reported {
+            Some(kind) if kind.is_physical() && hinted == Self::Virtual => Self::Virtual,
+            Some(Self::Other) | None => hinted,
+            Some(kind) => kind,
+        }
+    }
+}
+
 /// Information about a single network interface.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InterfaceInfo {
@@ -36,6 +143,29 @@ pub struct InterfaceInfo {
     pub mac: String,
     /// Whether the interface is currently up and connected.
     pub is_up: bool,
+    /// Ethernet, Wi-Fi, loopback, virtual, or other.
+    #[serde(default)]
+    pub kind: InterfaceKind,
+    /// Adapter description from the OS (e.g. the driver's product name).
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Negotiated link speed in megabits per second, if the OS reports
+    /// one. Virtual adapters report a nominal figure, not a real limit.
+    #[serde(default)]
+    pub link_speed_mbps: Option<u64>,
+}
+
+/// Sort interfaces for display: physical adapters that are up first, then
+/// other connected ones, then disconnected ones, with loopback last. Ties
+/// keep alphabetical order.
+pub fn order_interfaces(interfaces: &mut [InterfaceInfo]) {
+    let rank = |info: &InterfaceInfo| match (info.kind, info.is_up) {
+        (InterfaceKind::Loopback, _) => 3,
+        (kind, true) if kind.is_physical() => 0,
+        (_, true) => 1,
+        (_, false) => 2,
+    };
+    interfaces.sort_by(|a, b| rank(a).cmp(&rank(b)).then_with(|| a.name.cmp(&b.name)));
 }
 
 /// A snapshot of network traffic counters for one interface.
@@ -49,6 +179,169 @@ pub struct NetworkStats {
     pub timestamp: Instant,
 }
 
+impl NetworkStats {
+    /// The serializable form for `interface_id`, with the monotonic
+    /// timestamp mapped onto the wall clock.
+    pub fn to_snapshot(&self, interface_id: impl Into<String>) -> NetworkStatsSnapshot {
+        let (now, wall) = (Instant::now(), SystemTime::now());
+        let taken_at = match now.checked_duration_since(self.timestamp) {
+            Some(age) => wall.checked_sub(age),
+            None => wall.checked_add(self.timestamp - now),
+        }
+        .unwrap_or(wall);
+        NetworkStatsSnapshot {
+            interface_id: interface_id.into(),
+            bytes_sent: self.bytes_sent,
+            bytes_received: self.bytes_received,
+            timestamp_epoch_ms: taken_at
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis() as u64),
+        }
+    }
+}
+
+/// Serializable counters of one interface, for the frontend and persisted
+/// history. Deltas are still calculated from [`NetworkStats`], whose
+/// monotonic timestamp is immune to clock changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkStatsSnapshot {
+    /// Interface identifier.
+    pub interface_id: String,
+    /// Total bytes sent since boot.
+    pub bytes_sent: u64,
+    /// Total bytes received since boot.
+    pub bytes_received: u64,
+    /// When the counters were read, in milliseconds since the Unix epoch.
+    pub timestamp_epoch_ms: u64,
+}
+
+/// Cumulative counters for every interface at one instant.
+#[derive(Debug, Clone)]
+pub struct TrafficSnapshot {
+    /// When the counters were read.
+    pub taken_at: Instant,
+    /// `(interface id, bytes received, bytes sent)` per interface.
+    pub counters: Vec<(String, u64, u64)>,
+}
+
+impl TrafficSnapshot {
+    /// The counters of one interface, if it was present.
+    pub fn stats(&self, interface_id: &str) -> Option<NetworkStats> {
+        self.counters
+            .iter()
+            .find(|(id, _, _)| id == interface_id)
+            .map(|(_, received, sent)| NetworkStats {
+                bytes_sent: *sent,
+                bytes_received: *received,
+                timestamp: self.taken_at,
+            })
+    }
+
+    /// The serializable counters of every interface, in snapshot order.
+    pub fn to_stats_snapshots(&self) -> Vec<NetworkStatsSnapshot> {
+        self.interface_ids()
+            .filter_map(|id| Some(self.stats(id)?.to_snapshot(id)))
+            .collect()
+    }
+
+    /// Ids of the interfaces present, in snapshot order.
+    pub fn interface_ids(&self) -> impl Iterator<Item = &str> {
+        self.counters.iter().map(|(id, _, _)| id.as_str())
+    }
+}
+
+/// Per-interface traffic: totals since boot plus the most recent window.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterfaceTraffic {
+    /// Interface identifier.
+    pub id: String,
+    /// Bytes received since boot.
+    pub total_received: u64,
+    /// Bytes sent since boot.
+    pub total_sent: u64,
+    /// Bytes received during the last sampling window, if known.
+    pub recent_received: Option<u64>,
+    /// Bytes sent during the last sampling window, if known.
+    pub recent_sent: Option<u64>,
+    /// Length of the last sampling window in seconds, if known.
+    pub window_secs: Option<f64>,
+}
+
+impl InterfaceTraffic {
+    /// Bytes in both directions since boot.
+    pub fn total_bytes(&self) -> u64 {
+        self.total_received.saturating_add(self.total_sent)
+    }
+
+    /// Bytes in both directions during the last window, if known.
+    pub fn recent_bytes(&self) -> Option<u64> {
+        Some(self.recent_received?.saturating_add(self.recent_sent?))
+    }
+}
+
+/// Diff two snapshots into a per-interface traffic summary.
+///
+/// Recent figures are `None` without a previous snapshot, for interfaces
+/// that appeared since, and when a counter went backwards (e.g. the
+/// adapter was reset).
+pub fn summarize_traffic(
+    previous: Option<&TrafficSnapshot>,
+    current: &TrafficSnapshot,
+) -> Vec<InterfaceTraffic> {
+    let window_secs = previous.map(|p| {
+        current
+            .taken_at
+            .saturating_duration_since(p.taken_at)
+            .as_secs_f64()
+    });
+
+    current
+        .counters
+        .iter()
+        .map(|(id, received, sent)| {
+            let before = previous
+                .and_then(|p| p.counters.iter().find(|(prev_id, _, _)| prev_id == id))
+                .filter(|(_, prev_rx, prev_tx)| received >= prev_rx && sent >= prev_tx);
+            InterfaceTraffic {
+                id: id.clone(),
+                total_received: *received,
+                total_sent: *sent,
+                recent_received: before.map(|(_, prev_rx, _)| received - prev_rx),
+                recent_sent: before.map(|(_, _, prev_tx)| sent - prev_tx),
+                window_secs: before.and(window_secs),
+            }
+        })
+        .collect()
+}
+
+/// Pick the interface "auto" should resolve to.
+///
+/// Prefers the most recent activity when any interface has some; ties go
+/// to the larger historical total, then to the lower id. Without recent
+/// activity, falls back to the largest historical total. This keeps an
+/// idle adapter with huge past traffic (e.g. a management VLAN) from
+/// winning over the one currently in use.
+pub fn pick_default_interface(summary: &[InterfaceTraffic]) -> Option<&InterfaceTraffic> {
+    let by_id = |a: &&InterfaceTraffic, b: &&InterfaceTraffic| b.id.cmp(&a.id);
+
+    let recent = summary
+        .iter()
+        .filter(|t| t.recent_bytes().is_some_and(|b| b > 0))
+        .max_by(|a, b| {
+            (a.recent_bytes(), a.total_bytes())
+                .cmp(&(b.recent_bytes(), b.total_bytes()))
+                .then_with(|| by_id(a, b))
+        });
+
+    recent.or_else(|| {
+        summary.iter().max_by(|a, b| {
+            a.total_bytes()
+                .cmp(&b.total_bytes())
+                .then_with(|| by_id(a, b))
+        })
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Trait
 // ---------------------------------------------------------------------------
@@ -67,6 +360,58 @@ pub trait NetworkProvider: Send + Sync {
 
     /// Get cumulative byte counters for a specific interface.
     fn get_stats(&mut self, interface_id: &str) -> Result<NetworkStats, NetworkError>;
+
+    /// Copy the counters of every interface, by value.
+    ///
+    /// Providers whose refresh blocks should read all interfaces in one
+    /// refresh here, so callers can run it on a blocking thread and work
+    /// with the result without holding on to the provider. The default
+    /// implementation reads each listed interface with
+    /// [`get_stats`](Self::get_stats).
+    fn snapshot_all(&mut self) -> Result<TrafficSnapshot, NetworkError> {
+        let mut counters = Vec::new();
+        for info in self.list_interfaces()? {
+            let stats = self.get_stats(&info.id)?;
+            counters.push((info.id, stats.bytes_received, stats.bytes_sent));
+        }
+        Ok(TrafficSnapshot {
+            taken_at: Instant::now(),
+            counters,
+        })
+    }
+
+    /// Traffic per interface: totals since boot and, where the provider
+    /// keeps a previous snapshot, bytes since the last call.
+    ///
+    /// The default implementation reports totals only.
+    fn get_interface_traffic_summary(&mut self) -> Result<Vec<InterfaceTraffic>, NetworkError> {
+        Ok(summarize_traffic(None, &self.snapshot_all()?))
+    }
+
+    /// Look up one interface by id.
+    fn find_interface(&self, interface_id: &str) -> Result<Option<InterfaceInfo>, NetworkError> {
+        Ok(self
+            .list_interfaces()?
+            .into_iter()
+            .find(|info| info.id == interface_id))
+    }
+
+    /// Whether an interface with this id is visible to the OS.
+    fn interface_exists(&self, interface_id: &str) -> Result<bool, NetworkError> {
+        Ok(self.find_interface(interface_id)?.is_some())
+    }
+}
+
+/// Resolve the "auto" interface from the traffic summary, falling back to
+/// the provider's own default when the summary is empty.
+pub fn resolve_default_interface(
+    provider: &mut dyn NetworkProvider,
+) -> Result<Option<String>, NetworkError> {
+    let summary = provider.get_interface_traffic_summary()?;
+    match pick_default_interface(&summary) {
+        Some(traffic) => Ok(Some(traffic.id.clone())),
+        None => Ok(provider.get_default_interface()?.map(|i| i.id)),
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -76,56 +421,64 @@ pub trait NetworkProvider: Send + Sync {
 /// Network provider backed by the `sysinfo` crate.
 ///
 /// Works on Windows, macOS, and Linux without any platform-specific code.
+/// Requires the `sysinfo` feature (on by default).
+#[cfg(feature = "sysinfo")]
 pub struct SysinfoNetworkProvider {
     networks: Networks,
+    /// Snapshot from the previous traffic summary call.
+    last_snapshot: Option<TrafficSnapshot>,
 }
 
+#[cfg(feature = "sysinfo")]
 impl SysinfoNetworkProvider {
     /// Create a new provider. Performs an initial refresh so the first
     /// `get_stats` call returns meaningful deltas.
     pub fn new() -> Self {
         let networks = Networks::new_with_refreshed_list();
-        Self { networks }
+        Self {
+            networks,
+            last_snapshot: None,
+        }
     }
 }
 
+#[cfg(feature = "sysinfo")]
 impl Default for SysinfoNetworkProvider {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[cfg(feature = "sysinfo")]
 impl NetworkProvider for SysinfoNetworkProvider {
     fn list_interfaces(&self) -> Result<Vec<InterfaceInfo>, NetworkError> {
-        let interfaces = self
+        let details = adapter_details();
+        let mut interfaces: Vec<InterfaceInfo> = self
             .networks
             .iter()
-            .map(|(name, data)| InterfaceInfo {
-                id: name.clone(),
-                name: name.clone(),
-                mac: data.mac_address().to_string(),
-                is_up: data.total_received() > 0 || data.total_transmitted() > 0,
-            })
+            .map(|(name, data)| interface_info(name, data, &details))
             .collect();
+        order_interfaces(&mut interfaces);
         Ok(interfaces)
     }
 
     fn get_default_interface(&self) -> Result<Option<InterfaceInfo>, NetworkError> {
-        // Heuristic: pick the interface with the most total traffic.
+        // Heuristic: pick the interface with the most total traffic,
+        // preferring real adapters over virtual and loopback ones.
+        let details = adapter_details();
         let best = self
             .networks
             .iter()
-            .max_by_key(|(_, data)| data.total_received() + data.total_transmitted());
-
-        match best {
-            Some((name, data)) => Ok(Some(InterfaceInfo {
-                id: name.clone(),
-                name: name.clone(),
-                mac: data.mac_address().to_string(),
-                is_up: true,
-            })),
-            None => Ok(None),
-        }
+            .map(|(name, data)| {
+                let traffic = data.total_received() + data.total_transmitted();
+                (interface_info(name, data, &details), traffic)
+            })
+            .max_by_key(|(info, traffic)| {
+                let preferred =
+                    !matches!(info.kind, InterfaceKind::Virtual | InterfaceKind::Loopback);
+                (preferred, *traffic)
+            });
+        Ok(best.map(|(info, _)| info))
     }
 
     fn get_stats(&mut self, interface_id: &str) -> Result<NetworkStats, NetworkError> {
@@ -145,6 +498,257 @@ impl NetworkProvider for SysinfoNetworkProvider {
             timestamp: Instant::now(),
         })
     }
+
+    fn snapshot_all(&mut self) -> Result<TrafficSnapshot, NetworkError> {
+        // One refresh for every interface.
+        self.networks.refresh(true);
+        Ok(TrafficSnapshot {
+            taken_at: Instant::now(),
+            counters: self
+                .networks
+                .iter()
+                .map(|(name, data)| {
+                    (
+                        name.clone(),
+                        data.total_received(),
+                        data.total_transmitted(),
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    fn get_interface_traffic_summary(&mut self) -> Result<Vec<InterfaceTraffic>, NetworkError> {
+        let current = self.snapshot_all()?;
+        let summary = summarize_traffic(self.last_snapshot.as_ref(), &current);
+        self.last_snapshot = Some(current);
+        Ok(summary)
+    }
+}
+
+/// What the OS reports about one adapter.
+#[cfg(feature = "sysinfo")]
+#[derive(Debug, Clone, Default)]
+struct AdapterDetail {
+    /// Kind reported by the OS.
+    kind: InterfaceKind,
+    /// Adapter description, if any.
+    description: Option<String>,
+    /// Link speed in megabits per second, if known.
+    link_speed_mbps: Option<u64>,
+}
+
+/// Adapter details keyed by interface name.
+#[cfg(feature = "sysinfo")]
+type AdapterDetails = HashMap<String, AdapterDetail>;
+
+/// Build the [`InterfaceInfo`] for one sysinfo interface.
+#[cfg(feature = "sysinfo")]
+fn interface_info(
+    name: &str,
+    data: &sysinfo::NetworkData,
+    details: &AdapterDetails,
+) -> InterfaceInfo {
+    let detail = details.get(name).cloned();
+    let reported = detail.as_ref().map(|d| d.kind);
+    let detail = detail.unwrap_or_default();
+    InterfaceInfo {
+        id: name.to_string(),
+        name: name.to_string(),
+        mac: data.mac_address().to_string(),
+        is_up: link_is_up(name, data),
+        kind: InterfaceKind::classify(name, detail.description.as_deref(), reported),
+        description: detail.description,
+        link_speed_mbps: detail.link_speed_mbps,
+    }
+}
+
+/// Adapter types, descriptions and link speeds from
+/// `GetAdaptersAddresses`, keyed by friendly name (which is what sysinfo
+/// uses as the interface name). Returns an empty map if the call fails.
+#[cfg(all(feature = "sysinfo", windows, feature = "windows"))]
+fn adapter_details() -> AdapterDetails {
+    use windows_sys::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, NO_ERROR};
+    use windows_sys::Win32::NetworkManagement::IpHelper::{
+        GetAdaptersAddresses, GAA_FLAG_INCLUDE_ALL_INTERFACES, GAA_FLAG_SKIP_ANYCAST,
+        GAA_FLAG_SKIP_DNS_SERVER, GAA_FLAG_SKIP_MULTICAST, GAA_FLAG_SKIP_UNICAST,
+        IP_ADAPTER_ADDRESSES_LH,
+    };
+    use windows_sys::Win32::Networking::WinSock::AF_UNSPEC;
+
+    /// # Safety
+    /// `ptr` must be null or point to a NUL-terminated UTF-16 string.
+    unsafe fn wide_to_string(ptr: *const u16) -> Option<String> {
+        if ptr.is_null() {
+            return None;
+        }
+        let len = (0..).take_while(|&i| *ptr.add(i) != 0).count();
+        Some(String::from_utf16_lossy(std::slice::from_raw_parts(
+            ptr, len,
+        )))
+    }
+
+    let flags = GAA_FLAG_SKIP_UNICAST
+        | GAA_FLAG_SKIP_ANYCAST
+        | GAA_FLAG_SKIP_MULTICAST
+        | GAA_FLAG_SKIP_DNS_SERVER
+        | GAA_FLAG_INCLUDE_ALL_INTERFACES;
+    let mut details = AdapterDetails::new();
+    // Start at 16 KiB and retry with the size Windows asks for; `u64`
+    // elements keep the buffer aligned for the adapter structs.
+    let mut size: u32 = 16 * 1024;
+    let mut buffer: Vec<u64> = Vec::new();
+    for _ in 0..3 {
+        buffer.resize((size as usize).div_ceil(8), 0);
+        let first = buffer.as_mut_ptr().cast::<IP_ADAPTER_ADDRESSES_LH>();
+        // SAFETY: `buffer` holds at least `size` bytes, suitably aligned.
+        let result = unsafe {
+            GetAdaptersAddresses(
+                u32::from(AF_UNSPEC),
+                flags,
+                std::ptr::null(),
+                first,
+                &mut size,
+            )
+        };
+        if result == ERROR_BUFFER_OVERFLOW {
+            continue;
+        }
+        if result != NO_ERROR {
+            break;
+        }
+        let mut current: *const IP_ADAPTER_ADDRESSES_LH = first;
+        while !current.is_null() {
+            // SAFETY: a linked list inside `buffer`, filled in by the call
+            // above; the strings are NUL-terminated.
+            let adapter = unsafe { &*current };
+            let name = unsafe { wide_to_string(adapter.FriendlyName) };
+            let description =
+                unsafe { wide_to_string(adapter.Description) }.filter(|d| !d.is_empty());
+            if let Some(name) = name {
+                let link_speed_mbps = link_speed_mbps_from_bps(adapter.ReceiveLinkSpeed)
+                    .max(link_speed_mbps_from_bps(adapter.TransmitLinkSpeed));
+                details.insert(
+                    name,
+                    AdapterDetail {
+                        kind: InterfaceKind::from_if_type(adapter.IfType),
+                        description,
+                        link_speed_mbps,
+                    },
+                );
+            }
+            current = adapter.Next;
+        }
+        break;
+    }
+    details
+}
+
+/// Adapter types from sysfs: loopback by device type, Wi-Fi by its
+/// `wireless` directory, and virtual when no hardware device backs it.
+/// Link speeds come from `speed`.
+#[cfg(all(feature = "sysinfo", target_os = "linux"))]
+fn adapter_details() -> AdapterDetails {
+    const ARPHRD_ETHER: &str = "1";
+    const ARPHRD_LOOPBACK: &str = "772";
+
+    let Ok(entries) = std::fs::read_dir("/sys/class/net") else {
+        return AdapterDetails::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            let if_type = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+            let kind = if if_type.trim() == ARPHRD_LOOPBACK {
+                InterfaceKind::Loopback
+            } else if path.join("wireless").exists() || path.join("phy80211").exists() {
+                InterfaceKind::Wifi
+            } else if !path.join("device").exists() {
+                InterfaceKind::Virtual
+            } else if if_type.trim() == ARPHRD_ETHER {
+                InterfaceKind::Ethernet
+            } else {
+                InterfaceKind::Other
+            };
+            let link_speed_mbps = std::fs::read_to_string(path.join("speed"))
+                .ok()
+                .and_then(|speed| link_speed_from_sysfs(&speed));
+            (
+                entry.file_name().to_string_lossy().into_owned(),
+                AdapterDetail {
+                    kind,
+                    description: None,
+                    link_speed_mbps,
+                },
+            )
+        })
+        .collect()
+}
+
+/// No platform query: names alone decide the kind.
+#[cfg(all(
+    feature = "sysinfo",
+    not(target_os = "linux"),
+    not(all(windows, feature = "windows"))
+))]
+fn adapter_details() -> AdapterDetails {
+    AdapterDetails::new()
+}
+
+/// Interpret a Linux `operstate` value: `Some(true)` for a connected
+/// link, `Some(false)` for one that is down, `None` when the kernel does
+/// not know (common for virtual and loopback devices).
+pub fn link_state_from_operstate(operstate: &str) -> Option<bool> {
+    match operstate.trim() {
+        "up" => Some(true),
+        "down" | "dormant" | "lowerlayerdown" | "notpresent" => Some(false),
+        _ => None,
+    }
+}
+
+/// Interpret a Linux sysfs `speed` value (megabits per second). The
+/// kernel reports `-1` (or refuses the read) when the speed is unknown,
+/// e.g. for a disconnected or virtual device.
+pub fn link_speed_from_sysfs(speed: &str) -> Option<u64> {
+    speed
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .and_then(|mbps| u64::try_from(mbps).ok())
+        .filter(|&mbps| mbps > 0)
+}
+
+/// Convert a link speed in bits per second, as Windows reports it, to
+/// megabits per second. Zero and `u64::MAX` mean unknown.
+pub fn link_speed_mbps_from_bps(bps: u64) -> Option<u64> {
+    match bps {
+        0 | u64::MAX => None,
+        bps => Some(bps.div_ceil(1_000_000)),
+    }
+}
+
+/// Current link state of an interface.
+///
+/// sysinfo has no link-state API. On Linux the kernel's `operstate` is
+/// read; elsewhere (and when it is unknown) an interface counts as up if
+/// it has an IP address assigned. On Windows, sysinfo already drops
+/// disconnected adapters from the list on refresh.
+#[cfg(feature = "sysinfo")]
+fn link_is_up(name: &str, data: &sysinfo::NetworkData) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        let path = format!("/sys/class/net/{name}/operstate");
+        if let Some(up) = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|state| link_state_from_operstate(&state))
+        {
+            return up;
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = name;
+    !data.ip_networks().is_empty()
 }
 
 // ---------------------------------------------------------------------------
@@ -156,6 +760,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[cfg(feature = "sysinfo")]
     fn sysinfo_provider_lists_interfaces() {
         let provider = SysinfoNetworkProvider::new();
         let interfaces = provider.list_interfaces().expect("should list interfaces");
@@ -167,6 +772,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "sysinfo")]
     fn sysinfo_provider_gets_default_interface() {
         let provider = SysinfoNetworkProvider::new();
         let default = provider
@@ -177,6 +783,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "sysinfo")]
     fn sysinfo_provider_gets_stats_for_known_interface() {
         let mut provider = SysinfoNetworkProvider::new();
         let interfaces = provider.list_interfaces().expect("should list interfaces");
@@ -190,7 +797,263 @@ mod tests {
         }
     }
 
+    fn info(name: &str, kind: InterfaceKind, is_up: bool) -> InterfaceInfo {
+        InterfaceInfo {
+            id: name.to_string(),
+            name: name.to_string(),
+            mac: String::new(),
+            is_up,
+            kind,
+            description: None,
+            link_speed_mbps: None,
+        }
+    }
+
+    #[test]
+    fn labels_map_to_kinds() {
+        assert_eq!(
+            InterfaceKind::from_label("Ethernet 3"),
+            InterfaceKind::Ethernet
+        );
+        assert_eq!(InterfaceKind::from_label("enp3s0"), InterfaceKind::Ethernet);
+        assert_eq!(InterfaceKind::from_label("Wi-Fi"), InterfaceKind::Wifi);
+        assert_eq!(InterfaceKind::from_label("wlp2s0"), InterfaceKind::Wifi);
+        assert_eq!(InterfaceKind::from_label("lo"), InterfaceKind::Loopback);
+        assert_eq!(
+            InterfaceKind::from_label("Loopback Pseudo-Interface 1"),
+            InterfaceKind::Loopback
+        );
+        assert_eq!(
+            InterfaceKind::from_label("Local Area Connection* 12"),
+            InterfaceKind::Virtual
+        );
+        assert_eq!(InterfaceKind::from_label("docker0"), InterfaceKind::Virtual);
+        assert_eq!(InterfaceKind::from_label("xyz"), InterfaceKind::Other);
+    }
+
+    #[test]
+    fn classify_prefers_the_os_unless_marked_virtual() {
+        // Hyper-V switch reports as Ethernet.
+        assert_eq!(
+            InterfaceKind::classify(
+                "vEthernet (Default Switch)",
+                Some("Hyper-V Virtual Ethernet Adapter"),
+                Some(InterfaceKind::Ethernet)
+            ),
+            InterfaceKind::Virtual
+        );
+        // A renamed adapter: the OS knows better than the name.
+        assert_eq!(
+            InterfaceKind::classify("Office", None, Some(InterfaceKind::Wifi)),
+            InterfaceKind::Wifi
+        );
+        // No OS answer: the description decides.
+        assert_eq!(
+            InterfaceKind::classify("Office", Some("Intel(R) Wi-Fi 6 AX201"), None),
+            InterfaceKind::Wifi
+        );
+        assert_eq!(InterfaceKind::from_if_type(71), InterfaceKind::Wifi);
+    }
+
+    #[test]
+    fn physical_up_interfaces_are_listed_first() {
+        let mut list = vec![
+            info("lo", InterfaceKind::Loopback, true),
+            info("eth1", InterfaceKind::Ethernet, false),
+            info("docker0", InterfaceKind::Virtual, true),
+            info("wlan0", InterfaceKind::Wifi, true),
+            info("eth0", InterfaceKind::Ethernet, true),
+        ];
+        order_interfaces(&mut list);
+        let names: Vec<&str> = list.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(names, ["eth0", "wlan0", "docker0", "eth1", "lo"]);
+    }
+
+    #[test]
+    fn operstate_maps_to_link_state() {
+        assert_eq!(link_state_from_operstate("up\n"), Some(true));
+        assert_eq!(link_state_from_operstate("down\n"), Some(false));
+        assert_eq!(link_state_from_operstate("lowerlayerdown"), Some(false));
+        assert_eq!(link_state_from_operstate("unknown"), None);
+    }
+
+    #[test]
+    fn reported_link_speeds_are_parsed() {
+        assert_eq!(link_speed_from_sysfs("1000\n"), Some(1000));
+        assert_eq!(link_speed_from_sysfs("-1\n"), None);
+        assert_eq!(link_speed_from_sysfs("0"), None);
+        assert_eq!(link_speed_from_sysfs(""), None);
+        assert_eq!(link_speed_mbps_from_bps(1_000_000_000), Some(1000));
+        assert_eq!(link_speed_mbps_from_bps(866_700_000), Some(867));
+        assert_eq!(link_speed_mbps_from_bps(0), None);
+        assert_eq!(link_speed_mbps_from_bps(u64::MAX), None);
+    }
+
+    #[test]
+    #[cfg(feature = "sysinfo")]
+    fn sysinfo_provider_finds_listed_interfaces() {
+        let provider = SysinfoNetworkProvider::new();
+        let first = provider.list_interfaces().unwrap().remove(0);
+        let found = provider.find_interface(&first.id).unwrap().unwrap();
+        assert_eq!(found.id, first.id);
+        assert!(!provider
+            .interface_exists("nonexistent_interface_xyz_999")
+            .unwrap());
+    }
+
+    fn snapshot(at: Instant, counters: &[(&str, u64, u64)]) -> TrafficSnapshot {
+        TrafficSnapshot {
+            taken_at: at,
+            counters: counters
+                .iter()
+                .map(|(id, rx, tx)| (id.to_string(), *rx, *tx))
+                .collect(),
+        }
+    }
+
+    fn traffic(id: &str, total: u64, recent: Option<u64>) -> InterfaceTraffic {
+        InterfaceTraffic {
+            id: id.to_string(),
+            total_received: total,
+            total_sent: 0,
+            recent_received: recent,
+            recent_sent: recent.map(|_| 0),
+            window_secs: recent.map(|_| 1.0),
+        }
+    }
+
+    #[test]
+    fn summary_diffs_snapshots() {
+        let t0 = Instant::now();
+        let before = snapshot(t0, &[("eth0", 1_000, 500), ("vlan", 9_000_000, 0)]);
+        let after = snapshot(
+            t0 + std::time::Duration::from_secs(2),
+            &[
+                ("eth0", 1_600, 700),
+                ("vlan", 9_000_000, 0),
+                ("wifi", 10, 10),
+            ],
+        );
+
+        let summary = summarize_traffic(Some(&before), &after);
+        assert_eq!(summary[0].recent_received, Some(600));
+        assert_eq!(summary[0].recent_sent, Some(200));
+        assert_eq!(summary[0].recent_bytes(), Some(800));
+        assert_eq!(summary[0].window_secs, Some(2.0));
+        assert_eq!(summary[1].recent_bytes(), Some(0));
+        // New interface: no baseline yet.
+        assert_eq!(summary[2].recent_bytes(), None);
+        assert_eq!(summary[2].total_bytes(), 20);
+    }
+
+    #[test]
+    fn snapshot_stats_are_stamped_with_the_snapshot_time() {
+        let t0 = Instant::now();
+        let snap = snapshot(t0, &[("eth0", 1_000, 500), ("wifi", 10, 20)]);
+        let stats = snap.stats("wifi").unwrap();
+        assert_eq!((stats.bytes_received, stats.bytes_sent), (10, 20));
+        assert_eq!(stats.timestamp, t0);
+        assert!(snap.stats("eth9").is_none());
+        assert_eq!(snap.interface_ids().collect::<Vec<_>>(), ["eth0", "wifi"]);
+    }
+
+    #[test]
+    fn stats_snapshots_carry_wall_clock_time_and_round_trip() {
+        let wall_ms = |t: SystemTime| t.duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        let before = wall_ms(SystemTime::now());
+        let stats = NetworkStats {
+            bytes_sent: 20,
+            bytes_received: 10,
+            timestamp: Instant::now() - std::time::Duration::from_secs(5),
+        };
+        let snap = stats.to_snapshot("eth0");
+        let after = wall_ms(SystemTime::now());
+        assert_eq!((snap.bytes_received, snap.bytes_sent), (10, 20));
+        assert!(snap.timestamp_epoch_ms + 5_000 >= before);
+        assert!(snap.timestamp_epoch_ms + 5_000 <= after);
+
+        let json = serde_json::to_value(&snap).unwrap();
+        assert_eq!(json["interface_id"], "eth0");
+        let back: NetworkStatsSnapshot = serde_json::from_value(json).unwrap();
+        assert_eq!(back, snap);
+
+        let traffic = snapshot(Instant::now(), &[("eth0", 1, 2), ("wifi", 3, 4)]);
+        let all = traffic.to_stats_snapshots();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[1].interface_id, "wifi");
+        assert_eq!(all[1].bytes_sent, 4);
+    }
+
+    #[test]
+    fn summary_without_previous_has_totals_only() {
+        let summary = summarize_traffic(None, &snapshot(Instant::now(), &[("eth0", 5, 7)]));
+        assert_eq!(summary[0].total_bytes(), 12);
+        assert_eq!(summary[0].recent_bytes(), None);
+        assert_eq!(summary[0].window_secs, None);
+    }
+
+    #[test]
+    fn counter_reset_drops_recent_figure() {
+        let t0 = Instant::now();
+        let before = snapshot(t0, &[("eth0", 1_000, 1_000)]);
+        let after = snapshot(t0, &[("eth0", 10, 2_000)]);
+        let summary = summarize_traffic(Some(&before), &after);
+        assert_eq!(summary[0].recent_received, None);
+        assert_eq!(summary[0].recent_bytes(), None);
+    }
+
+    #[test]
+    fn recent_activity_beats_historical_totals() {
+        let summary = [
+            traffic("vlan", 9_000_000_000, Some(0)),
+            traffic("eth0", 1_000, Some(50_000)),
+        ];
+        assert_eq!(pick_default_interface(&summary).unwrap().id, "eth0");
+    }
+
+    #[test]
+    fn recent_tie_goes_to_larger_total_then_lower_id() {
+        let summary = [
+            traffic("b", 100, Some(500)),
+            traffic("a", 900, Some(500)),
+            traffic("c", 5_000, Some(10)),
+        ];
+        assert_eq!(pick_default_interface(&summary).unwrap().id, "a");
+
+        let summary = [traffic("b", 100, Some(500)), traffic("a", 100, Some(500))];
+        assert_eq!(pick_default_interface(&summary).unwrap().id, "a");
+    }
+
+    #[test]
+    fn no_recent_data_falls_back_to_totals() {
+        let summary = [traffic("eth0", 1_000, None), traffic("vlan", 9_000, None)];
+        assert_eq!(pick_default_interface(&summary).unwrap().id, "vlan");
+
+        // All-quiet windows count as "no recent data" too.
+        let summary = [
+            traffic("eth0", 1_000, Some(0)),
+            traffic("vlan", 9_000, Some(0)),
+        ];
+        assert_eq!(pick_default_interface(&summary).unwrap().id, "vlan");
+
+        let summary = [traffic("b", 9_000, None), traffic("a", 9_000, None)];
+        assert_eq!(pick_default_interface(&summary).unwrap().id, "a");
+
+        assert!(pick_default_interface(&[]).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "sysinfo")]
+    fn sysinfo_provider_summarizes_traffic() {
+        let mut provider = SysinfoNetworkProvider::new();
+        let first = provider.get_interface_traffic_summary().unwrap();
+        assert!(first.iter().all(|t| t.recent_bytes().is_none()));
+        let second = provider.get_interface_traffic_summary().unwrap();
+        assert!(second.iter().all(|t| t.window_secs.is_some()));
+    }
+
     #[test]
+    #[cfg(feature = "sysinfo")]
     fn sysinfo_provider_returns_error_for_unknown_interface() {
         let mut provider = SysinfoNetworkProvider::new();
         let result = provider.get_stats("nonexistent_interface_xyz");
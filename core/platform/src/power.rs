@@ -0,0 +1,174 @@
+//! Power source and battery state.
+//!
+//! Provides the [`PowerStateProvider`] trait consulted by the engine's
+//! power guard before an action runs, and a [`SystemPowerProvider`] backed
+//! by `GetSystemPowerStatus` on Windows (with the `windows` feature). On
+//! other platforms the system provider reports [`PowerError::NotSupported`].
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+// ---------------------------------------------------------------------------
+// Error types
+// ---------------------------------------------------------------------------
+
+/// Errors that can occur while reading the power state.
+#[derive(Debug, Error)]
+pub enum PowerError {
+    /// The power state cannot be read on this platform.
+    #[error("not supported: {0}")]
+    NotSupported(String),
+
+    /// A platform-specific error occurred.
+    #[error("platform error: {0}")]
+    PlatformError(String),
+}
+
+// ---------------------------------------------------------------------------
+// Data types
+// ---------------------------------------------------------------------------
+
+/// The machine's power source and battery charge.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowerStatus {
+    /// Whether the machine runs on AC power (`None` if unknown).
+    pub on_ac_power: Option<bool>,
+    /// Remaining battery charge, 0–100 (`None` without a battery or if
+    /// unknown).
+    pub battery_percent: Option<u8>,
+}
+
+/// `SYSTEM_POWER_STATUS.BatteryFlag` bit for "no system battery".
+const NO_SYSTEM_BATTERY: u8 = 128;
+/// `SYSTEM_POWER_STATUS` value for an unknown status.
+const UNKNOWN_STATUS: u8 = 255;
+
+impl PowerStatus {
+    /// Interpret the raw `SYSTEM_POWER_STATUS` fields.
+    pub fn from_system_power_status(ac_line_status: u8, battery_flag: u8, percent: u8) -> Self {
+        let on_ac_power = match ac_line_status {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        };
+        let battery_percent = (battery_flag != UNKNOWN_STATUS
+            && battery_flag & NO_SYSTEM_BATTERY == 0
+            && percent != UNKNOWN_STATUS)
+            .then(|| percent.min(100));
+        Self {
+            on_ac_power,
+            battery_percent,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Power state provider trait
+// ---------------------------------------------------------------------------
+
+/// Platform-agnostic trait for reading the power state.
+pub trait PowerStateProvider: Send {
+    /// Read the current power source and battery charge.
+    fn power_status(&self) -> Result<PowerStatus, PowerError>;
+
+    /// Whether the machine runs on AC power (`None` if unknown).
+    fn on_ac_power(&self) -> Result<Option<bool>, PowerError> {
+        self.power_status().map(|s| s.on_ac_power)
+    }
+
+    /// Remaining battery charge, 0–100 (`None` without a battery).
+    fn battery_percent(&self) -> Result<Option<u8>, PowerError> {
+        self.power_status().map(|s| s.battery_percent)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// System implementation
+// ---------------------------------------------------------------------------
+
+/// Power state of the running machine.
+///
+/// Supported on Windows with the `windows` feature (on by default).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemPowerProvider;
+
+impl SystemPowerProvider {
+    /// Create a new provider.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl PowerStateProvider for SystemPowerProvider {
+    fn power_status(&self) -> Result<PowerStatus, PowerError> {
+        read_power_status()
+    }
+}
+
+#[cfg(all(windows, feature = "windows"))]
+fn read_power_status() -> Result<PowerStatus, PowerError> {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    // SAFETY: SYSTEM_POWER_STATUS is plain data; the call fills it in and
+    // its return value is checked below.
+    let mut raw: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut raw) } == 0 {
+        return Err(PowerError::PlatformError(format!(
+            "GetSystemPowerStatus failed: {}",
+            std::io::Error::last_os_error()
+        )));
+    }
+    Ok(PowerStatus::from_system_power_status(
+        raw.ACLineStatus,
+        raw.BatteryFlag,
+        raw.BatteryLifePercent,
+    ))
+}
+
+#[cfg(not(all(windows, feature = "windows")))]
+fn read_power_status() -> Result<PowerStatus, PowerError> {
+    Err(PowerError::NotSupported(
+        "Reading the power state is only supported on Windows".to_string(),
+    ))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_status_is_interpreted() {
+        assert_eq!(
+            PowerStatus::from_system_power_status(0, 2, 15),
+            PowerStatus {
+                on_ac_power: Some(false),
+                battery_percent: Some(15),
+            }
+        );
+        // Desktop without a battery.
+        assert_eq!(
+            PowerStatus::from_system_power_status(1, NO_SYSTEM_BATTERY, UNKNOWN_STATUS),
+            PowerStatus {
+                on_ac_power: Some(true),
+                battery_percent: None,
+            }
+        );
+        assert_eq!(
+            PowerStatus::from_system_power_status(UNKNOWN_STATUS, UNKNOWN_STATUS, 40),
+            PowerStatus::default()
+        );
+    }
+
+    #[cfg(not(all(windows, feature = "windows")))]
+    #[test]
+    fn system_provider_is_unsupported_off_windows() {
+        assert!(matches!(
+            SystemPowerProvider::new().on_ac_power(),
+            Err(PowerError::NotSupported(_))
+        ));
+    }
+}
@@ -2,9 +2,22 @@
 //!
 //! Provides process listing, per-process network usage estimation,
 //! and smart suggestion logic for identifying high-traffic processes.
+//!
+//! Real per-process network rates come from a
+//! [`ProcessNetworkProvider`] where one is available; otherwise disk I/O
+//! stands in as a proxy.
 
+#[cfg(feature = "sysinfo")]
+use crate::process_network::{system_process_network_provider, ProcessNetworkProvider};
 use serde::{Deserialize, Serialize};
-use sysinfo::{Process, System};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::hash::Hash;
+#[cfg(feature = "sysinfo")]
+use std::time::Duration;
+use std::time::Instant;
+#[cfg(feature = "sysinfo")]
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, Signal, System, UpdateKind};
 use thiserror::Error;
 
 // ---------------------------------------------------------------------------
@@ -36,12 +49,126 @@ pub struct ProcessInfo {
     pub name: String,
     /// Full path to the executable (if available).
     pub path: Option<String>,
-    /// Estimated network usage in bytes (combined read+write disk I/O as proxy).
-    /// Note: True per-process network usage requires ETW on Windows, which is
-    /// complex. We use disk I/O as an initial proxy and can upgrade later.
-    pub estimated_network_bytes: u64,
+    /// Activity used by triggers and suggestions, in bytes per second:
+    /// `network_bytes_per_sec` when known, otherwise `disk_bytes_per_sec`
+    /// as a proxy.
+    pub activity_bps: u64,
+    /// Measured network throughput in bytes per second (both directions),
+    /// or `None` when no per-process network source is available.
+    #[serde(default)]
+    pub network_bytes_per_sec: Option<u64>,
+    /// Disk reads plus writes in bytes per second.
+    #[serde(default)]
+    pub disk_bytes_per_sec: u64,
     /// Whether this process is suggested as a high-traffic candidate.
     pub is_suggested: bool,
+    /// Number of running instances this entry stands for (more than 1 only
+    /// when grouped by name; rates are then summed over all instances).
+    #[serde(default = "one_instance")]
+    pub instance_count: u32,
+}
+
+fn one_instance() -> u32 {
+    1
+}
+
+/// How [`ProcessProvider::get_filtered`] narrows and pages the process
+/// list. The default returns every process, ungrouped.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessQuery {
+    /// Case-insensitive substring the process name must contain.
+    pub filter: Option<String>,
+    /// Merge instances of the same executable into one entry.
+    pub group_by_name: bool,
+    /// Drop system processes (see [`is_system_process`]) with less activity
+    /// than this, in bytes per second.
+    pub system_activity_floor: u64,
+    /// Mark this many of the busiest entries as suggested. The name filter
+    /// does not change which entries are suggested.
+    pub suggest_top: usize,
+    /// Entries to skip.
+    pub offset: usize,
+    /// Maximum number of entries to return (`None` for all).
+    pub limit: Option<usize>,
+}
+
+/// One page of processes from [`ProcessProvider::get_filtered`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProcessPage {
+    /// Entries on this page, busiest first.
+    pub processes: Vec<ProcessInfo>,
+    /// Number of matching entries across all pages.
+    pub total: usize,
+}
+
+// ---------------------------------------------------------------------------
+// Counter rates
+// ---------------------------------------------------------------------------
+
+/// Turns cumulative byte counters into per-process rates.
+///
+/// Counters are identified by `K` (a PID, or a connection's address/port
+/// tuple when several counters belong to one process). A counter seen for
+/// the first time contributes nothing until the next sample; counters that
+/// disappear are forgotten.
+#[derive(Debug)]
+pub struct CounterRates<K> {
+    /// Cumulative bytes per counter at the previous sample.
+    previous: HashMap<K, u64>,
+    /// When the previous sample was taken.
+    taken_at: Option<Instant>,
+}
+
+impl<K: Hash + Eq> CounterRates<K> {
+    /// Start without any previous sample.
+    pub fn new() -> Self {
+        Self {
+            previous: HashMap::new(),
+            taken_at: None,
+        }
+    }
+
+    /// Record `(counter, pid, cumulative bytes)` samples taken at `at`
+    /// and return bytes per second per PID since the previous sample.
+    pub fn update(
+        &mut self,
+        at: Instant,
+        samples: impl IntoIterator<Item = (K, u32, u64)>,
+    ) -> HashMap<u32, u64> {
+        let elapsed = self
+            .taken_at
+            .map(|previous| at.saturating_duration_since(previous).as_secs_f64());
+        let mut deltas: HashMap<u32, u64> = HashMap::new();
+        let mut current = HashMap::new();
+        for (key, pid, bytes) in samples {
+            let delta = self
+                .previous
+                .get(&key)
+                .map_or(0, |&before| bytes.saturating_sub(before));
+            *deltas.entry(pid).or_default() += delta;
+            current.insert(key, bytes);
+        }
+        self.previous = current;
+        self.taken_at = Some(at);
+
+        deltas
+            .into_iter()
+            .map(|(pid, delta)| {
+                let rate = match elapsed {
+                    Some(secs) if secs > 0.0 => (delta as f64 / secs).round() as u64,
+                    _ => 0,
+                };
+                (pid, rate)
+            })
+            .collect()
+    }
+}
+
+impl<K: Hash + Eq> Default for CounterRates<K> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -59,75 +186,373 @@ pub trait ProcessProvider: Send + Sync {
     /// Get smart suggestions — processes sorted by network usage descending,
     /// with the top N marked as suggested.
     fn get_suggestions(&mut self, top_n: usize) -> Result<Vec<ProcessInfo>, ProcessError>;
+
+    /// Get the processes matching `query`, busiest first, one page at a
+    /// time.
+    fn get_filtered(&mut self, query: &ProcessQuery) -> Result<ProcessPage, ProcessError> {
+        Ok(query_processes(self.list_processes()?, query))
+    }
+
+    /// Ask a process to exit.
+    ///
+    /// Without `force` the process is asked to close (SIGTERM, or a close
+    /// request on Windows) and may take a moment to exit; with `force` it
+    /// is killed outright.
+    fn terminate_process(&mut self, pid: u32, force: bool) -> Result<(), ProcessError>;
+}
+
+// ---------------------------------------------------------------------------
+// Filtering and paging
+// ---------------------------------------------------------------------------
+
+/// Kernel pseudo-processes and OS services that are rarely what the user
+/// wants to watch (compared case-insensitively).
+const SYSTEM_PROCESS_NAMES: &[&str] = &[
+    "system",
+    "system idle process",
+    "idle",
+    "registry",
+    "memory compression",
+    "secure system",
+    "smss.exe",
+    "csrss.exe",
+    "wininit.exe",
+    "winlogon.exe",
+    "services.exe",
+    "lsass.exe",
+    "svchost.exe",
+    "fontdrvhost.exe",
+    "dwm.exe",
+    "conhost.exe",
+    "kernel_task",
+    "launchd",
+    "kthreadd",
+    "systemd",
+];
+
+/// Whether `process` is a kernel pseudo-process or OS service: a known
+/// system name, or no executable path (kernel threads, and processes
+/// whose executable cannot be read).
+pub fn is_system_process(process: &ProcessInfo) -> bool {
+    process.path.is_none()
+        || SYSTEM_PROCESS_NAMES
+            .iter()
+            .any(|name| process.name.eq_ignore_ascii_case(name))
+}
+
+/// Merge processes with the same name (case-insensitively). Each group
+/// keeps the PID and path of its busiest instance and sums the rates.
+pub fn group_by_name(processes: Vec<ProcessInfo>) -> Vec<ProcessInfo> {
+    let mut groups: HashMap<String, ProcessInfo> = HashMap::new();
+    for process in processes {
+        match groups.entry(process.name.to_lowercase()) {
+            Entry::Vacant(entry) => {
+                entry.insert(process);
+            }
+            Entry::Occupied(mut entry) => {
+                let group = entry.get_mut();
+                if process.activity_bps > group.activity_bps {
+                    group.pid = process.pid;
+                    group.path = process.path.clone();
+                }
+                group.activity_bps += process.activity_bps;
+                group.network_bytes_per_sec =
+                    match (group.network_bytes_per_sec, process.network_bytes_per_sec) {
+                        (None, None) => None,
+                        (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+                    };
+                group.disk_bytes_per_sec += process.disk_bytes_per_sec;
+                group.instance_count += process.instance_count;
+                group.is_suggested |= process.is_suggested;
+            }
+        }
+    }
+    groups.into_values().collect()
+}
+
+/// Apply `query` to `processes`: group, drop quiet system processes, sort
+/// busiest first (ties by name, then PID), mark suggestions, filter by
+/// name, then cut out the requested page.
+pub fn query_processes(processes: Vec<ProcessInfo>, query: &ProcessQuery) -> ProcessPage {
+    let mut processes = if query.group_by_name {
+        group_by_name(processes)
+    } else {
+        processes
+    };
+    processes.retain(|p| !is_system_process(p) || p.activity_bps >= query.system_activity_floor);
+    processes.sort_by(|a, b| {
+        b.activity_bps
+            .cmp(&a.activity_bps)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+            .then_with(|| a.pid.cmp(&b.pid))
+    });
+    for (i, process) in processes.iter_mut().enumerate() {
+        process.is_suggested = i < query.suggest_top;
+    }
+
+    if let Some(filter) = query
+        .filter
+        .as_deref()
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+    {
+        let filter = filter.to_lowercase();
+        processes.retain(|p| p.name.to_lowercase().contains(&filter));
+    }
+    let total = processes.len();
+    let processes = processes
+        .into_iter()
+        .skip(query.offset)
+        .take(query.limit.unwrap_or(usize::MAX))
+        .collect();
+    ProcessPage { processes, total }
 }
 
 // ---------------------------------------------------------------------------
 // sysinfo-based implementation
 // ---------------------------------------------------------------------------
 
+/// One process as read by a [`ProcessRefresher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessSample {
+    /// Process ID.
+    pub pid: u32,
+    /// Process name (e.g. "chrome.exe").
+    pub name: String,
+    /// Full executable path, if available.
+    pub path: Option<String>,
+    /// Bytes read plus written since the process started.
+    pub disk_bytes: u64,
+}
+
+/// Reads the process table for [`SysinfoProcessProvider`].
+///
+/// Every call is one full, potentially slow, read; the provider decides
+/// when a read is due. Tests inject their own to count reads.
+pub trait ProcessRefresher: Send + Sync {
+    /// Read every running process.
+    fn refresh(&mut self) -> Vec<ProcessSample>;
+}
+
+/// [`ProcessRefresher`] backed by `sysinfo`, reading only the process list
+/// and disk usage (no CPU, memory or environment).
+#[cfg(feature = "sysinfo")]
+pub struct SysinfoProcessRefresher {
+    system: System,
+}
+
+#[cfg(feature = "sysinfo")]
+impl SysinfoProcessRefresher {
+    /// Create a refresher; nothing is read until the first refresh.
+    pub fn new() -> Self {
+        Self {
+            system: System::new(),
+        }
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl Default for SysinfoProcessRefresher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "sysinfo")]
+impl ProcessRefresher for SysinfoProcessRefresher {
+    fn refresh(&mut self) -> Vec<ProcessSample> {
+        self.system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::nothing()
+                .with_disk_usage()
+                .with_exe(UpdateKind::OnlyIfNotSet),
+        );
+        self.system
+            .processes()
+            .iter()
+            .map(|(pid, process)| {
+                let usage = process.disk_usage();
+                ProcessSample {
+                    pid: pid.as_u32(),
+                    name: process.name().to_string_lossy().to_string(),
+                    path: process.exe().map(|p| p.to_string_lossy().to_string()),
+                    disk_bytes: usage.total_read_bytes + usage.total_written_bytes,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Shortest time between two process refreshes. Calls within it (e.g.
+/// several commands in the same second) reuse the previous snapshot.
+#[cfg(feature = "sysinfo")]
+pub const MIN_PROCESS_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Process provider backed by the `sysinfo` crate.
+///
+/// Keeps the previous snapshot so activity is reported as bytes per
+/// second over the time actually elapsed between refreshes. Listing,
+/// lookups and suggestions share one snapshot, read again only once it is
+/// older than the refresh interval.
+///
+/// Requires the `sysinfo` feature (on by default).
+#[cfg(feature = "sysinfo")]
 pub struct SysinfoProcessProvider {
-    system: System,
+    /// Reads the process table.
+    refresher: Box<dyn ProcessRefresher>,
+    /// Source of real per-process network rates, if any.
+    network: Option<Box<dyn ProcessNetworkProvider>>,
+    /// Cumulative disk reads plus writes per PID at the previous refresh.
+    disk: CounterRates<u32>,
+    /// Processes as of the last refresh.
+    snapshot: Vec<ProcessInfo>,
+    /// When the snapshot was taken (`None` forces the next refresh).
+    refreshed_at: Option<Instant>,
+    /// Minimum time between refreshes.
+    min_refresh_interval: Duration,
 }
 
+#[cfg(feature = "sysinfo")]
 impl SysinfoProcessProvider {
-    /// Create a new provider with an initial process list refresh.
+    /// Create a new provider with an initial process list refresh, using
+    /// the system's per-process network source when one can be set up.
+    ///
+    /// The initial refresh is the baseline, so rates read within the first
+    /// refresh interval are 0.
     pub fn new() -> Self {
-        let mut system = System::new_all();
-        system.refresh_all();
-        Self { system }
+        let mut provider = Self {
+            refresher: Box::new(SysinfoProcessRefresher::new()),
+            network: system_process_network_provider(),
+            disk: CounterRates::new(),
+            snapshot: Vec::new(),
+            refreshed_at: None,
+            min_refresh_interval: MIN_PROCESS_REFRESH_INTERVAL,
+        };
+        provider.refresh();
+        provider
     }
 
-    /// Convert a sysinfo Process to our ProcessInfo.
-    fn to_process_info(pid: u32, process: &Process) -> ProcessInfo {
-        let disk_usage = process.disk_usage();
-        let estimated_bytes = disk_usage.read_bytes + disk_usage.written_bytes;
+    /// Use `network` for per-process network rates (`None` for the disk
+    /// I/O proxy only). The next read refreshes.
+    pub fn with_network_provider(
+        mut self,
+        network: Option<Box<dyn ProcessNetworkProvider>>,
+    ) -> Self {
+        self.network = network;
+        self.refreshed_at = None;
+        self
+    }
+
+    /// Read the process table through `refresher`. The next read
+    /// refreshes.
+    pub fn with_refresher(mut self, refresher: Box<dyn ProcessRefresher>) -> Self {
+        self.refresher = refresher;
+        self.refreshed_at = None;
+        self
+    }
 
+    /// Reuse the snapshot for reads within `interval` of the last refresh
+    /// (default [`MIN_PROCESS_REFRESH_INTERVAL`]).
+    pub fn with_min_refresh_interval(mut self, interval: Duration) -> Self {
+        self.min_refresh_interval = interval;
+        self
+    }
+
+    /// Whether real per-process network rates are being measured.
+    pub fn has_network_rates(&self) -> bool {
+        self.network.is_some()
+    }
+
+    /// Refresh processes and rates unless the snapshot is younger than the
+    /// minimum interval. A network source that fails is dropped, falling
+    /// back to the disk proxy.
+    fn refresh(&mut self) {
+        let now = Instant::now();
+        if self
+            .refreshed_at
+            .is_some_and(|at| now.saturating_duration_since(at) < self.min_refresh_interval)
+        {
+            return;
+        }
+        let samples = self.refresher.refresh();
+        self.refreshed_at = Some(now);
+
+        let network = match self.network.as_mut().map(|n| n.bytes_per_sec()) {
+            Some(Ok(rates)) => Some(rates),
+            Some(Err(_)) => {
+                self.network = None;
+                None
+            }
+            None => None,
+        };
+        let disk = self.disk.update(
+            now,
+            samples
+                .iter()
+                .map(|sample| (sample.pid, sample.pid, sample.disk_bytes)),
+        );
+
+        self.snapshot = samples
+            .into_iter()
+            .map(|sample| {
+                let network_bytes_per_sec = network
+                    .as_ref()
+                    .map(|rates| rates.get(&sample.pid).copied().unwrap_or(0));
+                let disk_bytes_per_sec = disk.get(&sample.pid).copied().unwrap_or(0);
+                Self::to_process_info(sample, network_bytes_per_sec, disk_bytes_per_sec)
+            })
+            .collect();
+    }
+
+    /// Convert a process sample to our ProcessInfo.
+    fn to_process_info(
+        sample: ProcessSample,
+        network_bytes_per_sec: Option<u64>,
+        disk_bytes_per_sec: u64,
+    ) -> ProcessInfo {
         ProcessInfo {
-            pid,
-            name: process.name().to_string_lossy().to_string(),
-            path: process.exe().map(|p| p.to_string_lossy().to_string()),
-            estimated_network_bytes: estimated_bytes,
+            pid: sample.pid,
+            name: sample.name,
+            path: sample.path,
+            activity_bps: network_bytes_per_sec.unwrap_or(disk_bytes_per_sec),
+            network_bytes_per_sec,
+            disk_bytes_per_sec,
             is_suggested: false,
+            instance_count: 1,
         }
     }
 }
 
+#[cfg(feature = "sysinfo")]
 impl Default for SysinfoProcessProvider {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[cfg(feature = "sysinfo")]
 impl ProcessProvider for SysinfoProcessProvider {
     fn list_processes(&mut self) -> Result<Vec<ProcessInfo>, ProcessError> {
-        self.system.refresh_all();
-
-        let processes = self
-            .system
-            .processes()
-            .iter()
-            .map(|(pid, process)| Self::to_process_info(pid.as_u32(), process))
-            .collect();
-
-        Ok(processes)
+        self.refresh();
+        Ok(self.snapshot.clone())
     }
 
     fn get_process(&mut self, pid: u32) -> Result<ProcessInfo, ProcessError> {
-        self.system.refresh_all();
-
-        let sysinfo_pid = sysinfo::Pid::from_u32(pid);
-        self.system
-            .process(sysinfo_pid)
-            .map(|p| Self::to_process_info(pid, p))
+        self.refresh();
+        self.snapshot
+            .iter()
+            .find(|p| p.pid == pid)
+            .cloned()
             .ok_or(ProcessError::ProcessNotFound(pid))
     }
 
     fn get_suggestions(&mut self, top_n: usize) -> Result<Vec<ProcessInfo>, ProcessError> {
         let mut processes = self.list_processes()?;
 
-        // Sort by estimated network bytes descending.
-        processes.sort_by(|a, b| b.estimated_network_bytes.cmp(&a.estimated_network_bytes));
+        // Sort by measured network rate when available, else by the disk
+        // proxy (both are in `activity_bps`).
+        processes.sort_by_key(|p| std::cmp::Reverse(p.activity_bps));
 
         // Mark top N as suggested.
         for (i, process) in processes.iter_mut().enumerate() {
@@ -136,15 +561,66 @@ impl ProcessProvider for SysinfoProcessProvider {
 
         Ok(processes)
     }
+
+    fn terminate_process(&mut self, pid: u32, force: bool) -> Result<(), ProcessError> {
+        // Look the process up on its own rather than through the snapshot,
+        // which may be stale.
+        let sysinfo_pid = sysinfo::Pid::from_u32(pid);
+        let mut system = System::new();
+        system.refresh_processes(ProcessesToUpdate::Some(&[sysinfo_pid]), true);
+        let process = system
+            .process(sysinfo_pid)
+            .ok_or(ProcessError::ProcessNotFound(pid))?;
+
+        let sent = if force {
+            Some(process.kill())
+        } else {
+            process.kill_with(Signal::Term)
+        };
+        match sent {
+            Some(true) => Ok(()),
+            Some(false) => Err(ProcessError::PlatformError(format!(
+                "failed to signal pid {pid}"
+            ))),
+            // SIGTERM has no equivalent on this platform.
+            None => request_close(pid),
+        }
+    }
+}
+
+/// Ask a process to close via `taskkill` without `/F`, which posts a
+/// close message to its windows.
+#[cfg(all(feature = "sysinfo", windows))]
+fn request_close(pid: u32) -> Result<(), ProcessError> {
+    let status = std::process::Command::new("taskkill")
+        .args(["/PID", &pid.to_string()])
+        .status()
+        .map_err(|e| ProcessError::PlatformError(format!("failed to run taskkill: {e}")))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(ProcessError::PlatformError(format!(
+            "taskkill could not close pid {pid} ({status})"
+        )))
+    }
+}
+
+#[cfg(all(feature = "sysinfo", not(windows)))]
+fn request_close(pid: u32) -> Result<(), ProcessError> {
+    Err(ProcessError::PlatformError(format!(
+        "graceful termination of pid {pid} is not supported on this platform"
+    )))
 }
 
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
 
-#[cfg(test)]
+#[cfg(all(test, feature = "sysinfo"))]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
 
     #[test]
     fn sysinfo_provider_lists_processes() {
@@ -171,6 +647,215 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn terminating_nonexistent_pid_reports_not_found() {
+        let mut provider = SysinfoProcessProvider::new();
+        assert!(matches!(
+            provider.terminate_process(u32::MAX, true),
+            Err(ProcessError::ProcessNotFound(_))
+        ));
+    }
+
+    /// Reports a fixed rate for the current process.
+    struct FixedRate(u64);
+
+    impl ProcessNetworkProvider for FixedRate {
+        fn bytes_per_sec(&mut self) -> Result<HashMap<u32, u64>, ProcessError> {
+            Ok(HashMap::from([(std::process::id(), self.0)]))
+        }
+    }
+
+    /// Fails like a network source that lost its rights.
+    struct Broken;
+
+    impl ProcessNetworkProvider for Broken {
+        fn bytes_per_sec(&mut self) -> Result<HashMap<u32, u64>, ProcessError> {
+            Err(ProcessError::PlatformError("access denied".into()))
+        }
+    }
+
+    #[test]
+    fn rates_come_from_counter_deltas() {
+        let start = Instant::now();
+        let mut rates = CounterRates::new();
+
+        // First sample: nothing to diff against.
+        let first = rates.update(start, [("a", 1, 1_000), ("b", 1, 500), ("c", 2, 0)]);
+        assert_eq!(first.get(&1), Some(&0));
+
+        // Two seconds later: pid 1 moved 4000 bytes over two connections,
+        // "d" is new and does not count yet.
+        let second = rates.update(
+            start + Duration::from_secs(2),
+            [("a", 1, 4_000), ("b", 1, 1_500), ("d", 2, 9_999)],
+        );
+        assert_eq!(second.get(&1), Some(&2_000));
+        assert_eq!(second.get(&2), Some(&0));
+    }
+
+    #[test]
+    fn counter_reset_does_not_underflow() {
+        let start = Instant::now();
+        let mut rates = CounterRates::new();
+        rates.update(start, [("a", 1, 1_000)]);
+        let after = rates.update(start + Duration::from_secs(1), [("a", 1, 10)]);
+        assert_eq!(after.get(&1), Some(&0));
+    }
+
+    fn info(pid: u32, name: &str, system: bool, activity_bps: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            path: (!system).then(|| format!("/opt/{name}")),
+            activity_bps,
+            network_bytes_per_sec: None,
+            disk_bytes_per_sec: activity_bps,
+            is_suggested: false,
+            instance_count: 1,
+        }
+    }
+
+    fn sample() -> Vec<ProcessInfo> {
+        vec![
+            info(10, "svchost.exe", false, 200),
+            info(11, "svchost.exe", false, 5_000),
+            info(12, "svchost.exe", false, 0),
+            info(20, "Chrome.exe", false, 3_000),
+            info(21, "chrome.exe", false, 1_000),
+            info(30, "kworker/0:1", true, 0),
+            info(40, "steam.exe", false, 50_000),
+            info(50, "notepad.exe", false, 0),
+        ]
+    }
+
+    #[test]
+    fn grouping_sums_rates_and_keeps_the_busiest_pid() {
+        let page = query_processes(
+            sample(),
+            &ProcessQuery {
+                group_by_name: true,
+                ..ProcessQuery::default()
+            },
+        );
+        assert_eq!(page.total, 5);
+        let svchost = &page.processes[1];
+        assert_eq!(svchost.name, "svchost.exe");
+        assert_eq!(
+            (svchost.pid, svchost.instance_count, svchost.activity_bps),
+            (11, 3, 5_200)
+        );
+        assert_eq!(svchost.disk_bytes_per_sec, 5_200);
+        let chrome = &page.processes[2];
+        assert_eq!((chrome.pid, chrome.instance_count), (20, 2));
+    }
+
+    #[test]
+    fn quiet_system_processes_are_dropped() {
+        let page = query_processes(
+            sample(),
+            &ProcessQuery {
+                system_activity_floor: 1_000,
+                ..ProcessQuery::default()
+            },
+        );
+        let pids: Vec<u32> = page.processes.iter().map(|p| p.pid).collect();
+        // Busy svchost (11) stays; the others and the kernel thread go.
+        assert_eq!(pids, [40, 11, 20, 21, 50]);
+    }
+
+    #[test]
+    fn filter_and_paging_keep_suggestions_and_total() {
+        let query = ProcessQuery {
+            filter: Some(" SVC ".to_string()),
+            suggest_top: 2,
+            offset: 1,
+            limit: Some(1),
+            ..ProcessQuery::default()
+        };
+        let page = query_processes(sample(), &query);
+        assert_eq!(page.total, 3);
+        assert_eq!(page.processes.len(), 1);
+        assert_eq!(page.processes[0].pid, 10);
+        assert!(!page.processes[0].is_suggested);
+
+        let busiest = query_processes(sample(), &ProcessQuery { offset: 0, ..query });
+        // pid 11 is second overall, so it is suggested despite the filter.
+        assert_eq!(busiest.processes[0].pid, 11);
+        assert!(busiest.processes[0].is_suggested);
+    }
+
+    #[test]
+    fn reads_within_the_interval_reuse_the_snapshot() {
+        let mut provider =
+            SysinfoProcessProvider::new().with_network_provider(Some(Box::new(FixedRate(4096))));
+        assert_eq!(
+            provider
+                .get_process(std::process::id())
+                .unwrap()
+                .activity_bps,
+            4096
+        );
+
+        // Switching the source forces a refresh, but the one after that is
+        // held back by the interval.
+        provider = provider.with_network_provider(None);
+        let first = provider.list_processes().unwrap();
+        let again = provider.list_processes().unwrap();
+        assert_eq!(first.len(), again.len());
+        assert!(again.iter().all(|p| p.network_bytes_per_sec.is_none()));
+    }
+
+    /// Reports one process and counts how often it is asked.
+    struct CountingRefresher(Arc<AtomicUsize>);
+
+    impl ProcessRefresher for CountingRefresher {
+        fn refresh(&mut self) -> Vec<ProcessSample> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            vec![ProcessSample {
+                pid: 42,
+                name: "steam".into(),
+                path: None,
+                disk_bytes: 0,
+            }]
+        }
+    }
+
+    #[test]
+    fn rapid_reads_share_one_refresh() {
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let mut provider = SysinfoProcessProvider::new()
+            .with_network_provider(None)
+            .with_refresher(Box::new(CountingRefresher(refreshes.clone())))
+            .with_min_refresh_interval(Duration::from_secs(60));
+
+        let first = provider.get_suggestions(5).unwrap();
+        let second = provider.get_suggestions(5).unwrap();
+        assert_eq!(first[0].pid, second[0].pid);
+        assert_eq!(provider.get_process(42).unwrap().name, "steam");
+        assert_eq!(provider.list_processes().unwrap().len(), 1);
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+
+        let mut provider = provider.with_min_refresh_interval(Duration::ZERO);
+        provider.get_suggestions(5).unwrap();
+        assert_eq!(refreshes.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn network_rates_replace_the_disk_proxy() {
+        let mut provider =
+            SysinfoProcessProvider::new().with_network_provider(Some(Box::new(FixedRate(4096))));
+        let info = provider.get_process(std::process::id()).unwrap();
+        assert_eq!(info.network_bytes_per_sec, Some(4096));
+        assert_eq!(info.activity_bps, 4096);
+
+        let mut provider =
+            SysinfoProcessProvider::new().with_network_provider(Some(Box::new(Broken)));
+        let info = provider.get_process(std::process::id()).unwrap();
+        assert_eq!(info.network_bytes_per_sec, None);
+        assert_eq!(info.activity_bps, info.disk_bytes_per_sec);
+        assert!(!provider.has_network_rates());
+    }
+
     #[test]
     fn suggestions_sorted_by_usage_descending() {
         let mut provider = SysinfoProcessProvider::new();
@@ -179,7 +864,7 @@ mod tests {
         // Verify sorted descending.
         for window in suggestions.windows(2) {
             assert!(
-                window[0].estimated_network_bytes >= window[1].estimated_network_bytes,
+                window[0].activity_bps >= window[1].activity_bps,
                 "suggestions should be sorted descending"
             );
         }
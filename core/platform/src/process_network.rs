@@ -0,0 +1,315 @@
+//! Per-process network throughput.
+//!
+//! Provides the [`ProcessNetworkProvider`] trait the process provider
+//! consults for real per-process network rates, and a
+//! [`TcpEStatsProvider`] on Windows (with the `windows` feature) that sums
+//! the extended TCP statistics (`GetPerTcpConnectionEStats`) of each
+//! process's connections. Collecting those statistics needs administrator
+//! rights; without them [`system_process_network_provider`] returns `None`
+//! and callers fall back to the disk I/O proxy.
+//!
+//! Only TCP is counted, so UDP-heavy traffic (QUIC, games, DNS) is missed.
+
+#[cfg(all(windows, feature = "windows"))]
+use crate::process::CounterRates;
+use crate::process::ProcessError;
+use std::collections::HashMap;
+#[cfg(all(windows, feature = "windows"))]
+use std::time::Instant;
+
+// ---------------------------------------------------------------------------
+// Provider trait
+// ---------------------------------------------------------------------------
+
+/// Platform-agnostic trait for per-process network throughput.
+pub trait ProcessNetworkProvider: Send + Sync {
+    /// Bytes per second in both directions per PID since the previous
+    /// call. Processes without traffic may be missing or report 0; the
+    /// first call reports 0 for everything it sees.
+    fn bytes_per_sec(&mut self) -> Result<HashMap<u32, u64>, ProcessError>;
+}
+
+/// The best per-process network provider for this system, or `None` if
+/// none can be initialised (unsupported platform, or missing rights).
+pub fn system_process_network_provider() -> Option<Box<dyn ProcessNetworkProvider>> {
+    #[cfg(all(windows, feature = "windows"))]
+    if let Ok(provider) = TcpEStatsProvider::new() {
+        return Some(Box::new(provider));
+    }
+    None
+}
+
+// ---------------------------------------------------------------------------
+// Windows: extended TCP statistics
+// ---------------------------------------------------------------------------
+
+/// Identifies one TCP connection: address family plus local and remote
+/// address and port.
+#[cfg(all(windows, feature = "windows"))]
+type ConnectionKey = (u8, [u8; 16], u32, [u8; 16], u32);
+
+/// Per-process TCP throughput from `GetPerTcpConnectionEStats`.
+///
+/// Statistics collection is switched on for each connection the first
+/// time it is seen, so a connection's traffic is counted from then on.
+#[cfg(all(windows, feature = "windows"))]
+pub struct TcpEStatsProvider {
+    rates: CounterRates<ConnectionKey>,
+}
+
+#[cfg(all(windows, feature = "windows"))]
+impl TcpEStatsProvider {
+    /// Create the provider. Fails if statistics collection cannot be
+    /// enabled, which usually means the process is not elevated.
+    pub fn new() -> Result<Self, ProcessError> {
+        let mut provider = Self {
+            rates: CounterRates::new(),
+        };
+        provider.bytes_per_sec()?;
+        Ok(provider)
+    }
+}
+
+#[cfg(all(windows, feature = "windows"))]
+impl ProcessNetworkProvider for TcpEStatsProvider {
+    fn bytes_per_sec(&mut self) -> Result<HashMap<u32, u64>, ProcessError> {
+        let mut samples = estats::tcp4_samples()?;
+        samples.extend(estats::tcp6_samples()?);
+        Ok(self.rates.update(Instant::now(), samples))
+    }
+}
+
+#[cfg(all(windows, feature = "windows"))]
+mod estats {
+    use super::ConnectionKey;
+    use crate::process::ProcessError;
+    use windows_sys::Win32::Foundation::{
+        ERROR_ACCESS_DENIED, ERROR_INSUFFICIENT_BUFFER, NO_ERROR,
+    };
+    use windows_sys::Win32::NetworkManagement::IpHelper::{
+        GetExtendedTcpTable, GetPerTcp6ConnectionEStats, GetPerTcpConnectionEStats,
+        SetPerTcp6ConnectionEStats, SetPerTcpConnectionEStats, TCP_ESTATS_DATA_ROD_v0,
+        TCP_ESTATS_DATA_RW_v0, TcpConnectionEstatsData, MIB_TCP6ROW, MIB_TCP6ROW_OWNER_PID,
+        MIB_TCP6TABLE_OWNER_PID, MIB_TCPROW_LH, MIB_TCPROW_LH_0, MIB_TCPROW_OWNER_PID,
+        MIB_TCPTABLE_OWNER_PID, MIB_TCP_STATE_ESTAB, TCP_TABLE_OWNER_PID_CONNECTIONS,
+    };
+    use windows_sys::Win32::Networking::WinSock::{AF_INET, AF_INET6, IN6_ADDR, IN6_ADDR_0};
+
+    /// One sample per connection: key, owning PID, cumulative bytes.
+    type Sample = (ConnectionKey, u32, u64);
+
+    /// Read the owner-PID TCP table for `family` into an aligned buffer.
+    fn tcp_table(family: u16) -> Result<Vec<u64>, ProcessError> {
+        let mut size: u32 = 0;
+        let mut buffer: Vec<u64> = Vec::new();
+        loop {
+            // SAFETY: `buffer` holds at least `size` bytes, aligned for the
+            // table structs; the call only writes within `size`.
+            let result = unsafe {
+                GetExtendedTcpTable(
+                    buffer.as_mut_ptr().cast(),
+                    &mut size,
+                    0,
+                    u32::from(family),
+                    TCP_TABLE_OWNER_PID_CONNECTIONS,
+                    0,
+                )
+            };
+            match result {
+                NO_ERROR => return Ok(buffer),
+                ERROR_INSUFFICIENT_BUFFER => buffer.resize((size as usize).div_ceil(8), 0),
+                code => {
+                    return Err(ProcessError::PlatformError(format!(
+                        "GetExtendedTcpTable failed: {}",
+                        std::io::Error::from_raw_os_error(code as i32)
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Turn a `SetPerTcp*ConnectionEStats` result into an error if
+    /// collection could not be enabled. Connections that closed in the
+    /// meantime are ignored.
+    fn check_enable(result: u32) -> Result<bool, ProcessError> {
+        match result {
+            NO_ERROR => Ok(true),
+            ERROR_ACCESS_DENIED => Err(ProcessError::PlatformError(
+                "per-connection TCP statistics need administrator rights".to_string(),
+            )),
+            _ => Ok(false),
+        }
+    }
+
+    pub(super) fn tcp4_samples() -> Result<Vec<Sample>, ProcessError> {
+        let buffer = tcp_table(AF_INET)?;
+        if buffer.is_empty() {
+            return Ok(Vec::new());
+        }
+        // SAFETY: the buffer holds a MIB_TCPTABLE_OWNER_PID with
+        // `dwNumEntries` rows, as filled in by GetExtendedTcpTable.
+        let rows: &[MIB_TCPROW_OWNER_PID] = unsafe {
+            let table = &*buffer.as_ptr().cast::<MIB_TCPTABLE_OWNER_PID>();
+            std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize)
+        };
+
+        let mut samples = Vec::new();
+        for row in rows
+            .iter()
+            .filter(|r| r.dwState == MIB_TCP_STATE_ESTAB as u32)
+        {
+            let tcp_row = MIB_TCPROW_LH {
+                Anonymous: MIB_TCPROW_LH_0 {
+                    dwState: row.dwState,
+                },
+                dwLocalAddr: row.dwLocalAddr,
+                dwLocalPort: row.dwLocalPort,
+                dwRemoteAddr: row.dwRemoteAddr,
+                dwRemotePort: row.dwRemotePort,
+            };
+            let enable = TCP_ESTATS_DATA_RW_v0 {
+                EnableCollection: 1,
+            };
+            let mut data: TCP_ESTATS_DATA_ROD_v0 = unsafe { std::mem::zeroed() };
+            // SAFETY: `tcp_row`, `enable` and `data` are plain structs passed
+            // with their exact sizes; results are checked.
+            unsafe {
+                let enabled = SetPerTcpConnectionEStats(
+                    &tcp_row,
+                    TcpConnectionEstatsData,
+                    (&enable as *const TCP_ESTATS_DATA_RW_v0).cast(),
+                    0,
+                    std::mem::size_of::<TCP_ESTATS_DATA_RW_v0>() as u32,
+                    0,
+                );
+                if !check_enable(enabled)? {
+                    continue;
+                }
+                let read = GetPerTcpConnectionEStats(
+                    &tcp_row,
+                    TcpConnectionEstatsData,
+                    std::ptr::null_mut(),
+                    0,
+                    0,
+                    std::ptr::null_mut(),
+                    0,
+                    0,
+                    (&mut data as *mut TCP_ESTATS_DATA_ROD_v0).cast(),
+                    0,
+                    std::mem::size_of::<TCP_ESTATS_DATA_ROD_v0>() as u32,
+                );
+                if read != NO_ERROR {
+                    continue;
+                }
+            }
+            let mut local = [0u8; 16];
+            local[..4].copy_from_slice(&row.dwLocalAddr.to_ne_bytes());
+            let mut remote = [0u8; 16];
+            remote[..4].copy_from_slice(&row.dwRemoteAddr.to_ne_bytes());
+            samples.push((
+                (4, local, row.dwLocalPort, remote, row.dwRemotePort),
+                row.dwOwningPid,
+                data.DataBytesIn.saturating_add(data.DataBytesOut),
+            ));
+        }
+        Ok(samples)
+    }
+
+    pub(super) fn tcp6_samples() -> Result<Vec<Sample>, ProcessError> {
+        let buffer = tcp_table(AF_INET6)?;
+        if buffer.is_empty() {
+            return Ok(Vec::new());
+        }
+        // SAFETY: the buffer holds a MIB_TCP6TABLE_OWNER_PID with
+        // `dwNumEntries` rows, as filled in by GetExtendedTcpTable.
+        let rows: &[MIB_TCP6ROW_OWNER_PID] = unsafe {
+            let table = &*buffer.as_ptr().cast::<MIB_TCP6TABLE_OWNER_PID>();
+            std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize)
+        };
+
+        let mut samples = Vec::new();
+        for row in rows
+            .iter()
+            .filter(|r| r.dwState == MIB_TCP_STATE_ESTAB as u32)
+        {
+            let tcp_row = MIB_TCP6ROW {
+                State: row.dwState as i32,
+                LocalAddr: IN6_ADDR {
+                    u: IN6_ADDR_0 {
+                        Byte: row.ucLocalAddr,
+                    },
+                },
+                dwLocalScopeId: row.dwLocalScopeId,
+                dwLocalPort: row.dwLocalPort,
+                RemoteAddr: IN6_ADDR {
+                    u: IN6_ADDR_0 {
+                        Byte: row.ucRemoteAddr,
+                    },
+                },
+                dwRemoteScopeId: row.dwRemoteScopeId,
+                dwRemotePort: row.dwRemotePort,
+            };
+            let enable = TCP_ESTATS_DATA_RW_v0 {
+                EnableCollection: 1,
+            };
+            let mut data: TCP_ESTATS_DATA_ROD_v0 = unsafe { std::mem::zeroed() };
+            // SAFETY: as for IPv4 above.
+            unsafe {
+                let enabled = SetPerTcp6ConnectionEStats(
+                    &tcp_row,
+                    TcpConnectionEstatsData,
+                    (&enable as *const TCP_ESTATS_DATA_RW_v0).cast(),
+                    0,
+                    std::mem::size_of::<TCP_ESTATS_DATA_RW_v0>() as u32,
+                    0,
+                );
+                if !check_enable(enabled)? {
+                    continue;
+                }
+                let read = GetPerTcp6ConnectionEStats(
+                    &tcp_row,
+                    TcpConnectionEstatsData,
+                    std::ptr::null_mut(),
+                    0,
+                    0,
+                    std::ptr::null_mut(),
+                    0,
+                    0,
+                    (&mut data as *mut TCP_ESTATS_DATA_ROD_v0).cast(),
+                    0,
+                    std::mem::size_of::<TCP_ESTATS_DATA_ROD_v0>() as u32,
+                );
+                if read != NO_ERROR {
+                    continue;
+                }
+            }
+            samples.push((
+                (
+                    6,
+                    row.ucLocalAddr,
+                    row.dwLocalPort,
+                    row.ucRemoteAddr,
+                    row.dwRemotePort,
+                ),
+                row.dwOwningPid,
+                data.DataBytesIn.saturating_add(data.DataBytesOut),
+            ));
+        }
+        Ok(samples)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(all(windows, feature = "windows")))]
+    #[test]
+    fn no_system_provider_off_windows() {
+        assert!(system_process_network_provider().is_none());
+    }
+}
@@ -0,0 +1,104 @@
+//! Keyboard and mouse inactivity.
+//!
+//! Provides the [`UserIdleProvider`] trait the scheduler consults before
+//! starting a countdown, and a [`SystemUserIdleProvider`] backed by
+//! `GetLastInputInfo` on Windows (with the `windows` feature). On other
+//! platforms the system provider reports [`UserIdleError::NotSupported`].
+
+use thiserror::Error;
+
+// ---------------------------------------------------------------------------
+// Error types
+// ---------------------------------------------------------------------------
+
+/// Errors that can occur while reading the time since the last input.
+#[derive(Debug, Error)]
+pub enum UserIdleError {
+    /// Input activity cannot be read on this platform.
+    #[error("not supported: {0}")]
+    NotSupported(String),
+
+    /// A platform-specific error occurred.
+    #[error("platform error: {0}")]
+    PlatformError(String),
+}
+
+// ---------------------------------------------------------------------------
+// User idle provider trait
+// ---------------------------------------------------------------------------
+
+/// Platform-agnostic trait for reading user input activity.
+pub trait UserIdleProvider: Send {
+    /// Seconds since the last keyboard or mouse input.
+    fn seconds_since_last_input(&self) -> Result<u64, UserIdleError>;
+}
+
+// ---------------------------------------------------------------------------
+// System implementation
+// ---------------------------------------------------------------------------
+
+/// Input activity of the interactive session.
+///
+/// Supported on Windows with the `windows` feature (on by default).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemUserIdleProvider;
+
+impl SystemUserIdleProvider {
+    /// Create a new provider.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl UserIdleProvider for SystemUserIdleProvider {
+    fn seconds_since_last_input(&self) -> Result<u64, UserIdleError> {
+        read_seconds_since_last_input()
+    }
+}
+
+#[cfg(all(windows, feature = "windows"))]
+fn read_seconds_since_last_input() -> Result<u64, UserIdleError> {
+    use windows_sys::Win32::System::SystemInformation::GetTickCount;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    // SAFETY: `info` is a valid LASTINPUTINFO with `cbSize` set; the return
+    // value is checked below.
+    if unsafe { GetLastInputInfo(&mut info) } == 0 {
+        return Err(UserIdleError::PlatformError(format!(
+            "GetLastInputInfo failed: {}",
+            std::io::Error::last_os_error()
+        )));
+    }
+    // SAFETY: no arguments.
+    let now = unsafe { GetTickCount() };
+    // Both are 32-bit tick counts, which wrap after ~49.7 days.
+    Ok(u64::from(now.wrapping_sub(info.dwTime)) / 1000)
+}
+
+#[cfg(not(all(windows, feature = "windows")))]
+fn read_seconds_since_last_input() -> Result<u64, UserIdleError> {
+    Err(UserIdleError::NotSupported(
+        "Reading input activity is only supported on Windows".to_string(),
+    ))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(all(test, not(all(windows, feature = "windows"))))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system_provider_is_unsupported_off_windows() {
+        assert!(matches!(
+            SystemUserIdleProvider::new().seconds_since_last_input(),
+            Err(UserIdleError::NotSupported(_))
+        ));
+    }
+}
@@ -0,0 +1,76 @@
+//! Recording action double.
+
+use async_trait::async_trait;
+use flowwatcher_actions::{Action, ActionError, ActionInfo};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// An action that counts executions instead of touching the OS.
+///
+/// Clones share the same counter, so a test can keep a handle while the
+/// engine owns the boxed action.
+#[derive(Debug, Clone, Default)]
+pub struct MockAction {
+    executions: Arc<AtomicUsize>,
+    failures: usize,
+}
+
+impl MockAction {
+    /// Create an action that always succeeds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an action whose `execute()` always fails.
+    pub fn failing() -> Self {
+        Self::failing_first(usize::MAX)
+    }
+
+    /// Create an action whose first `failures` executions fail.
+    pub fn failing_first(failures: usize) -> Self {
+        Self {
+            failures,
+            ..Self::default()
+        }
+    }
+
+    /// How many times `execute()` has been called.
+    pub fn executions(&self) -> usize {
+        self.executions.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl Action for MockAction {
+    fn name(&self) -> &str {
+        "Mock Action"
+    }
+
+    fn action_type(&self) -> &str {
+        "mock"
+    }
+
+    fn info(&self) -> ActionInfo {
+        ActionInfo {
+            id: "mock".to_string(),
+            name: "Mock Action".to_string(),
+            description: "Records executions for tests".to_string(),
+            available: true,
+            requires_elevation: false,
+            params_schema: None,
+        }
+    }
+
+    async fn validate(&self) -> Result<(), ActionError> {
+        Ok(())
+    }
+
+    async fn execute(&self) -> Result<(), ActionError> {
+        let execution = self.executions.fetch_add(1, Ordering::SeqCst) + 1;
+        if execution <= self.failures {
+            Err(ActionError::ExecutionFailed("mock failure".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Scripted CPU provider and usage fixtures.
+
+use flowwatcher_platform::cpu::{CpuError, CpuProvider, CpuUsage};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Build a `CpuUsage` whose total is the mean of `per_core`.
+pub fn cpu_usage(per_core: &[f64]) -> CpuUsage {
+    let total = if per_core.is_empty() {
+        0.0
+    } else {
+        per_core.iter().sum::<f64>() / per_core.len() as f64
+    };
+    CpuUsage {
+        total_percent: total,
+        per_core_percent: per_core.to_vec(),
+    }
+}
+
+/// A CPU provider that replays a fixed sequence of readings.
+///
+/// Each `refresh` call returns the next reading; once the script is
+/// exhausted the last reading is repeated. Clones share the refresh
+/// counter, so a test can keep one to check how often it was sampled.
+#[derive(Clone)]
+pub struct ScriptedCpuProvider {
+    /// Sequence of readings.
+    readings: Arc<Vec<CpuUsage>>,
+    /// Number of `refresh` calls served so far.
+    refreshes: Arc<AtomicUsize>,
+    /// Reported minimum refresh interval.
+    min_interval: Duration,
+}
+
+impl ScriptedCpuProvider {
+    /// Create a provider from a sequence of readings.
+    ///
+    /// # Panics
+    /// Panics if `readings` is empty.
+    pub fn new(readings: Vec<CpuUsage>) -> Self {
+        assert!(!readings.is_empty(), "script needs at least one reading");
+        Self {
+            readings: Arc::new(readings),
+            refreshes: Arc::new(AtomicUsize::new(0)),
+            min_interval: Duration::ZERO,
+        }
+    }
+
+    /// Report `interval` as the minimum refresh interval.
+    pub fn with_min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// Number of `refresh` calls served so far.
+    pub fn refreshes(&self) -> usize {
+        self.refreshes.load(Ordering::SeqCst)
+    }
+}
+
+impl CpuProvider for ScriptedCpuProvider {
+    fn refresh(&mut self) -> Result<CpuUsage, CpuError> {
+        let idx = self.refreshes.fetch_add(1, Ordering::SeqCst);
+        Ok(self.readings[idx.min(self.readings.len() - 1)].clone())
+    }
+
+    fn min_refresh_interval(&self) -> Duration {
+        self.min_interval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replays_readings_in_order() {
+        let mut provider =
+            ScriptedCpuProvider::new(vec![cpu_usage(&[80.0, 40.0]), cpu_usage(&[2.0, 4.0])]);
+        let observer = provider.clone();
+        assert_eq!(provider.refresh().unwrap().total_percent, 60.0);
+        assert_eq!(provider.refresh().unwrap().total_percent, 3.0);
+        assert_eq!(provider.refresh().unwrap().total_percent, 3.0);
+        assert_eq!(observer.refreshes(), 3);
+    }
+}
@@ -0,0 +1,31 @@
+//! Shared test doubles for FlowWatcher crates.
+//!
+//! Provides scripted implementations of the platform traits so engine,
+//! trigger, and desktop tests can drive the full pipeline without a real
+//! machine's behavior leaking in:
+//!
+//! - [`MockClock`] — a controllable monotonic clock.
+//! - [`ScriptedNetworkProvider`] — returns a fixed sequence of byte counters.
+//! - [`ScriptedProcessProvider`] — returns a fixed sequence of process snapshots.
+//! - [`ScriptedCpuProvider`] — returns a fixed sequence of CPU readings.
+//! - [`MockAction`] — records executions instead of touching the OS.
+//! - [`MockUserIdleProvider`] — reports a settable time since the last input.
+//! - [`ScratchDir`] — a temporary directory removed when the test ends.
+//!
+//! This crate is a dev-dependency only and is never shipped.
+
+pub mod action;
+pub mod clock;
+pub mod cpu;
+pub mod network;
+pub mod process;
+pub mod scratch;
+pub mod user_idle;
+
+pub use action::MockAction;
+pub use clock::MockClock;
+pub use cpu::{cpu_usage, ScriptedCpuProvider};
+pub use network::{MultiInterfaceProvider, ScriptedNetworkProvider, MOCK_INTERFACE_ID};
+pub use process::{process, sample_processes, ScriptedProcessProvider};
+pub use scratch::ScratchDir;
+pub use user_idle::MockUserIdleProvider;
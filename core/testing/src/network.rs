@@ -0,0 +1,312 @@
+//! Scripted network provider.
+
+use crate::MockClock;
+use flowwatcher_platform::network::{
+    InterfaceInfo, InterfaceKind, NetworkError, NetworkProvider, NetworkStats, TrafficSnapshot,
+};
+use std::time::Duration;
+
+/// Identifier of the single interface exposed by [`ScriptedNetworkProvider`].
+pub const MOCK_INTERFACE_ID: &str = "mock0";
+
+/// A network provider that replays a fixed sequence of byte counters.
+///
+/// Each `get_stats` call returns the next `(bytes_received, bytes_sent)`
+/// pair and advances the clock by `step` (1 second by default). Once the
+/// script is exhausted the last pair is repeated, which models a link
+/// that has gone completely idle.
+pub struct ScriptedNetworkProvider {
+    /// Sequence of `(bytes_received, bytes_sent)` counters.
+    snapshots: Vec<(u64, u64)>,
+    /// Number of `get_stats` calls served so far.
+    call_count: usize,
+    /// Clock used to timestamp snapshots.
+    clock: MockClock,
+    /// How far the clock advances per `get_stats` call.
+    step: Duration,
+    /// Link state reported for the mock interface.
+    is_up: bool,
+}
+
+impl ScriptedNetworkProvider {
+    /// Create a provider with its own clock and a 1-second step.
+    ///
+    /// # Panics
+    /// Panics if `snapshots` is empty.
+    pub fn new(snapshots: Vec<(u64, u64)>) -> Self {
+        Self::with_clock(snapshots, MockClock::new())
+    }
+
+    /// Create a provider that timestamps snapshots from a shared clock.
+    pub fn with_clock(snapshots: Vec<(u64, u64)>, clock: MockClock) -> Self {
+        assert!(!snapshots.is_empty(), "script needs at least one snapshot");
+        Self {
+            snapshots,
+            call_count: 0,
+            clock,
+            step: Duration::from_secs(1),
+            is_up: true,
+        }
+    }
+
+    /// Build a script from per-second speeds instead of cumulative counters.
+    ///
+    /// The first snapshot is the `(0, 0)` baseline.
+    pub fn from_speeds(speeds: &[(u64, u64)]) -> Self {
+        let mut received = 0;
+        let mut sent = 0;
+        let mut snapshots = vec![(0, 0)];
+        for &(down, up) in speeds {
+            received += down;
+            sent += up;
+            snapshots.push((received, sent));
+        }
+        Self::new(snapshots)
+    }
+
+    /// Change how far the clock advances per call.
+    pub fn with_step(mut self, step: Duration) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// Report the mock interface as disconnected.
+    pub fn with_link_down(mut self) -> Self {
+        self.is_up = false;
+        self
+    }
+
+    /// Number of `get_stats` calls served so far.
+    pub fn call_count(&self) -> usize {
+        self.call_count
+    }
+
+    fn interface(&self) -> InterfaceInfo {
+        InterfaceInfo {
+            id: MOCK_INTERFACE_ID.to_string(),
+            name: "Mock Interface".to_string(),
+            mac: "00:00:00:00:00:00".to_string(),
+            is_up: self.is_up,
+            kind: InterfaceKind::Ethernet,
+            description: None,
+            link_speed_mbps: None,
+        }
+    }
+}
+
+impl NetworkProvider for ScriptedNetworkProvider {
+    fn list_interfaces(&self) -> Result<Vec<InterfaceInfo>, NetworkError> {
+        Ok(vec![self.interface()])
+    }
+
+    fn get_default_interface(&self) -> Result<Option<InterfaceInfo>, NetworkError> {
+        Ok(Some(self.interface()))
+    }
+
+    fn get_stats(&mut self, interface_id: &str) -> Result<NetworkStats, NetworkError> {
+        if interface_id != MOCK_INTERFACE_ID {
+            return Err(NetworkError::InterfaceNotFound(interface_id.to_string()));
+        }
+        let idx = self.call_count.min(self.snapshots.len() - 1);
+        let (received, sent) = self.snapshots[idx];
+        self.call_count += 1;
+        self.clock.advance(self.step);
+
+        Ok(NetworkStats {
+            bytes_received: received,
+            bytes_sent: sent,
+            timestamp: self.clock.now(),
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Several interfaces
+// ---------------------------------------------------------------------------
+
+/// `(download, upload)` bytes per second per step; `None` = not present.
+type SpeedScript = Vec<Option<(u64, u64)>>;
+
+/// Several interfaces replaying per-second speeds in lockstep.
+///
+/// Unlike [`ScriptedNetworkProvider`], reading counters does not move
+/// time: every `get_stats` call returns the counters of the current step,
+/// and [`advance`](Self::advance) moves all interfaces (and the clock, by
+/// one second) to the next one. A `None` speed means the interface is not
+/// present at that step, so a script can make an adapter vanish or appear
+/// mid-run. Past the end of its script an interface keeps its last
+/// speed. The default interface is the first one present.
+pub struct MultiInterfaceProvider {
+    /// Interface id and `(download, upload)` speed per step.
+    interfaces: Vec<(String, SpeedScript)>,
+    /// Index of the current step.
+    step: usize,
+    /// Clock used to timestamp snapshots.
+    clock: MockClock,
+}
+
+impl MultiInterfaceProvider {
+    /// A provider without interfaces, at step 0.
+    pub fn new() -> Self {
+        Self {
+            interfaces: Vec::new(),
+            step: 0,
+            clock: MockClock::new(),
+        }
+    }
+
+    /// Add an interface with `(download, upload)` bytes per second for
+    /// each step after the first (step 0 is the baseline).
+    pub fn with_interface(mut self, id: &str, speeds: &[Option<(u64, u64)>]) -> Self {
+        self.interfaces.push((id.to_string(), speeds.to_vec()));
+        self
+    }
+
+    /// Move every interface to the next step, one second later.
+    pub fn advance(&mut self) {
+        self.step += 1;
+        self.clock.advance(Duration::from_secs(1));
+    }
+
+    /// Counters of `id` at the current step, if it is present.
+    fn counters(&self, id: &str) -> Option<(u64, u64)> {
+        let (_, speeds) = self.interfaces.iter().find(|(iface, _)| iface == id)?;
+        let speed = |step: usize| *speeds.get(step.min(speeds.len().checked_sub(1)?))?;
+        speed(self.step.saturating_sub(1))?;
+        Some(
+            (0..self.step)
+                .filter_map(speed)
+                .fold((0, 0), |(down, up), (d, u)| (down + d, up + u)),
+        )
+    }
+
+    fn info(id: &str) -> InterfaceInfo {
+        InterfaceInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            mac: "00:00:00:00:00:00".to_string(),
+            is_up: true,
+            kind: InterfaceKind::Other,
+            description: None,
+            link_speed_mbps: None,
+        }
+    }
+}
+
+impl Default for MultiInterfaceProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NetworkProvider for MultiInterfaceProvider {
+    fn list_interfaces(&self) -> Result<Vec<InterfaceInfo>, NetworkError> {
+        Ok(self
+            .interfaces
+            .iter()
+            .filter(|(id, _)| self.counters(id).is_some())
+            .map(|(id, _)| Self::info(id))
+            .collect())
+    }
+
+    fn get_default_interface(&self) -> Result<Option<InterfaceInfo>, NetworkError> {
+        Ok(self.list_interfaces()?.into_iter().next())
+    }
+
+    fn get_stats(&mut self, interface_id: &str) -> Result<NetworkStats, NetworkError> {
+        let (bytes_received, bytes_sent) = self
+            .counters(interface_id)
+            .ok_or_else(|| NetworkError::InterfaceNotFound(interface_id.to_string()))?;
+        Ok(NetworkStats {
+            bytes_sent,
+            bytes_received,
+            timestamp: self.clock.now(),
+        })
+    }
+
+    fn snapshot_all(&mut self) -> Result<TrafficSnapshot, NetworkError> {
+        Ok(TrafficSnapshot {
+            taken_at: self.clock.now(),
+            counters: self
+                .interfaces
+                .iter()
+                .filter_map(|(id, _)| {
+                    let (received, sent) = self.counters(id)?;
+                    Some((id.clone(), received, sent))
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replays_and_repeats_last_snapshot() {
+        let mut provider = ScriptedNetworkProvider::new(vec![(0, 0), (10, 5)]);
+        let a = provider.get_stats(MOCK_INTERFACE_ID).unwrap();
+        let b = provider.get_stats(MOCK_INTERFACE_ID).unwrap();
+        let c = provider.get_stats(MOCK_INTERFACE_ID).unwrap();
+
+        assert_eq!((a.bytes_received, a.bytes_sent), (0, 0));
+        assert_eq!((b.bytes_received, b.bytes_sent), (10, 5));
+        assert_eq!((c.bytes_received, c.bytes_sent), (10, 5));
+        assert_eq!(
+            b.timestamp.duration_since(a.timestamp),
+            Duration::from_secs(1)
+        );
+    }
+
+    #[test]
+    fn from_speeds_accumulates_counters() {
+        let mut provider = ScriptedNetworkProvider::from_speeds(&[(100, 10), (50, 0)]);
+        provider.get_stats(MOCK_INTERFACE_ID).unwrap();
+        provider.get_stats(MOCK_INTERFACE_ID).unwrap();
+        let last = provider.get_stats(MOCK_INTERFACE_ID).unwrap();
+        assert_eq!((last.bytes_received, last.bytes_sent), (150, 10));
+    }
+
+    #[test]
+    fn unknown_interface_is_an_error() {
+        let mut provider = ScriptedNetworkProvider::new(vec![(0, 0)]);
+        assert!(provider.get_stats("eth9").is_err());
+    }
+
+    #[test]
+    fn interfaces_advance_in_lockstep_and_can_vanish() {
+        let mut provider = MultiInterfaceProvider::new()
+            .with_interface("wlan0", &[Some((100, 10)), None])
+            .with_interface("eth0", &[None, Some((500, 0))]);
+        let ids = |p: &MultiInterfaceProvider| -> Vec<String> {
+            p.list_interfaces()
+                .unwrap()
+                .into_iter()
+                .map(|i| i.id)
+                .collect()
+        };
+        assert_eq!(ids(&provider), ["wlan0"]);
+
+        provider.advance();
+        let stats = provider.get_stats("wlan0").unwrap();
+        assert_eq!((stats.bytes_received, stats.bytes_sent), (100, 10));
+        assert_eq!(
+            provider.get_stats("wlan0").unwrap().timestamp,
+            stats.timestamp
+        );
+        assert!(provider.get_stats("eth0").is_err());
+
+        provider.advance();
+        assert_eq!(ids(&provider), ["eth0"]);
+        assert_eq!(
+            provider.get_default_interface().unwrap().unwrap().id,
+            "eth0"
+        );
+        assert_eq!(provider.get_stats("eth0").unwrap().bytes_received, 500);
+        assert!(matches!(
+            provider.get_stats("wlan0"),
+            Err(NetworkError::InterfaceNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+//! Scripted process provider and process fixtures.
+
+use flowwatcher_platform::process::{ProcessError, ProcessInfo, ProcessProvider};
+
+/// Build a `ProcessInfo` with a synthetic path.
+pub fn process(pid: u32, name: &str, activity_bps: u64) -> ProcessInfo {
+    ProcessInfo {
+        pid,
+        name: name.to_string(),
+        path: Some(format!("C:\\Programs\\{name}")),
+        activity_bps,
+        network_bytes_per_sec: None,
+        disk_bytes_per_sec: activity_bps,
+        is_suggested: false,
+        instance_count: 1,
+    }
+}
+
+/// A representative process list: two busy and two quiet processes.
+///
+/// | pid | name           | bytes/s |
+/// |-----|----------------|---------|
+/// | 1   | `steam.exe`    | 50 000  |
+/// | 2   | `chrome.exe`   | 100     |
+/// | 3   | `explorer.exe` | 0       |
+/// | 4   | `svchost.exe`  | 30 000  |
+pub fn sample_processes() -> Vec<ProcessInfo> {
+    vec![
+        process(1, "steam.exe", 50_000),
+        process(2, "chrome.exe", 100),
+        process(3, "explorer.exe", 0),
+        process(4, "svchost.exe", 30_000),
+    ]
+}
+
+/// A process provider that replays a fixed sequence of snapshots.
+///
+/// Each `list_processes` call returns the next snapshot; once the script
+/// is exhausted the last snapshot is repeated. Terminated processes drop
+/// out of every later result.
+pub struct ScriptedProcessProvider {
+    /// Sequence of process lists.
+    snapshots: Vec<Vec<ProcessInfo>>,
+    /// Number of `list_processes` calls served so far.
+    call_count: usize,
+    /// Every `terminate_process` call, as `(pid, force)`.
+    terminations: Vec<(u32, bool)>,
+    /// Whether close requests (non-forced terminations) are ignored.
+    ignore_close_requests: bool,
+}
+
+impl ScriptedProcessProvider {
+    /// Create a provider from a sequence of snapshots.
+    ///
+    /// # Panics
+    /// Panics if `snapshots` is empty.
+    pub fn new(snapshots: Vec<Vec<ProcessInfo>>) -> Self {
+        assert!(!snapshots.is_empty(), "script needs at least one snapshot");
+        Self {
+            snapshots,
+            call_count: 0,
+            terminations: Vec::new(),
+            ignore_close_requests: false,
+        }
+    }
+
+    /// Keep processes running after a close request, so only a forced
+    /// termination removes them.
+    pub fn with_ignored_close_requests(mut self) -> Self {
+        self.ignore_close_requests = true;
+        self
+    }
+
+    /// Every `terminate_process` call so far, as `(pid, force)`.
+    pub fn terminations(&self) -> &[(u32, bool)] {
+        &self.terminations
+    }
+
+    /// Whether a termination of `pid` has taken effect.
+    fn is_terminated(&self, pid: u32) -> bool {
+        self.terminations
+            .iter()
+            .any(|&(p, force)| p == pid && (force || !self.ignore_close_requests))
+    }
+
+    /// Create a provider that always returns the same process list.
+    pub fn fixed(processes: Vec<ProcessInfo>) -> Self {
+        Self::new(vec![processes])
+    }
+
+    /// The snapshot most recently returned (or the first, before any call).
+    fn current(&self) -> &[ProcessInfo] {
+        let idx = self
+            .call_count
+            .saturating_sub(1)
+            .min(self.snapshots.len() - 1);
+        &self.snapshots[idx]
+    }
+}
+
+impl ProcessProvider for ScriptedProcessProvider {
+    fn list_processes(&mut self) -> Result<Vec<ProcessInfo>, ProcessError> {
+        let idx = self.call_count.min(self.snapshots.len() - 1);
+        self.call_count += 1;
+        Ok(self.snapshots[idx]
+            .iter()
+            .filter(|p| !self.is_terminated(p.pid))
+            .cloned()
+            .collect())
+    }
+
+    fn get_process(&mut self, pid: u32) -> Result<ProcessInfo, ProcessError> {
+        self.current()
+            .iter()
+            .find(|p| p.pid == pid && !self.is_terminated(pid))
+            .cloned()
+            .ok_or(ProcessError::ProcessNotFound(pid))
+    }
+
+    fn get_suggestions(&mut self, top_n: usize) -> Result<Vec<ProcessInfo>, ProcessError> {
+        let mut processes = self.list_processes()?;
+        processes.sort_by_key(|p| std::cmp::Reverse(p.activity_bps));
+        for (i, process) in processes.iter_mut().enumerate() {
+            process.is_suggested = i < top_n;
+        }
+        Ok(processes)
+    }
+
+    fn terminate_process(&mut self, pid: u32, force: bool) -> Result<(), ProcessError> {
+        self.get_process(pid)?;
+        self.terminations.push((pid, force));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replays_snapshots_in_order() {
+        let mut provider = ScriptedProcessProvider::new(vec![
+            vec![process(1, "a.exe", 10)],
+            vec![process(1, "a.exe", 0)],
+        ]);
+        assert_eq!(provider.list_processes().unwrap()[0].activity_bps, 10);
+        assert_eq!(provider.list_processes().unwrap()[0].activity_bps, 0);
+        assert_eq!(provider.list_processes().unwrap()[0].activity_bps, 0);
+    }
+
+    #[test]
+    fn suggestions_mark_top_n() {
+        let mut provider = ScriptedProcessProvider::fixed(sample_processes());
+        let suggestions = provider.get_suggestions(2).unwrap();
+        assert_eq!(suggestions[0].name, "steam.exe");
+        assert!(suggestions[1].is_suggested);
+        assert!(!suggestions[2].is_suggested);
+    }
+
+    #[test]
+    fn get_process_by_pid() {
+        let mut provider = ScriptedProcessProvider::fixed(sample_processes());
+        assert_eq!(provider.get_process(2).unwrap().name, "chrome.exe");
+        assert!(provider.get_process(99).is_err());
+    }
+
+    #[test]
+    fn terminated_processes_disappear() {
+        let mut provider = ScriptedProcessProvider::fixed(sample_processes());
+        provider.terminate_process(1, false).unwrap();
+        assert!(provider.get_process(1).is_err());
+        assert_eq!(provider.list_processes().unwrap().len(), 3);
+        assert!(provider.terminate_process(1, true).is_err());
+        assert_eq!(provider.terminations(), &[(1, false)]);
+    }
+
+    #[test]
+    fn ignored_close_requests_need_force() {
+        let mut provider =
+            ScriptedProcessProvider::fixed(sample_processes()).with_ignored_close_requests();
+        provider.terminate_process(2, false).unwrap();
+        assert!(provider.get_process(2).is_ok());
+        provider.terminate_process(2, true).unwrap();
+        assert!(provider.get_process(2).is_err());
+    }
+}
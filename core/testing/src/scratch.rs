@@ -0,0 +1,75 @@
+//! Temporary directories for tests that touch the filesystem.
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Distinguishes directories created with the same name in one process.
+static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+
+/// A fresh, empty directory under the system temp dir, removed with
+/// everything in it when dropped.
+///
+/// Each directory is unique to the process and the call, so tests that
+/// run in parallel (or reuse a name) never share files, and a test that
+/// panics still cleans up while unwinding.
+#[derive(Debug)]
+pub struct ScratchDir {
+    path: PathBuf,
+}
+
+impl ScratchDir {
+    /// Create a directory whose name starts with `flowwatcher-{name}`.
+    pub fn new(name: &str) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let path =
+            std::env::temp_dir().join(format!("flowwatcher-{name}-{}-{id}", std::process::id()));
+        // Left over from an earlier process with the same id.
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    /// The directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Deref for ScratchDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for ScratchDir {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directories_are_fresh_and_removed_on_drop() {
+        let first = ScratchDir::new("scratch");
+        let second = ScratchDir::new("scratch");
+        assert_ne!(first.path(), second.path());
+        assert!(first.is_dir());
+
+        std::fs::write(first.join("file.txt"), "data").unwrap();
+        let path = first.path().to_path_buf();
+        drop(first);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,53 @@
+//! Controllable user input activity.
+
+use flowwatcher_platform::user_idle::{UserIdleError, UserIdleProvider};
+use std::sync::{Arc, Mutex};
+
+/// A [`UserIdleProvider`] whose idle time only changes when told to.
+///
+/// Clones share the same value, so a test can hand one clone to the
+/// scheduler and keep another to simulate typing or walking away.
+#[derive(Debug, Clone, Default)]
+pub struct MockUserIdleProvider {
+    idle_secs: Arc<Mutex<u64>>,
+}
+
+impl MockUserIdleProvider {
+    /// Create a provider reporting `idle_secs` since the last input.
+    pub fn new(idle_secs: u64) -> Self {
+        Self {
+            idle_secs: Arc::new(Mutex::new(idle_secs)),
+        }
+    }
+
+    /// Simulate input right now.
+    pub fn input(&self) {
+        self.set_idle_secs(0);
+    }
+
+    /// Report `secs` since the last input.
+    pub fn set_idle_secs(&self, secs: u64) {
+        *self.idle_secs.lock().unwrap() = secs;
+    }
+}
+
+impl UserIdleProvider for MockUserIdleProvider {
+    fn seconds_since_last_input(&self) -> Result<u64, UserIdleError> {
+        Ok(*self.idle_secs.lock().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_idle_time() {
+        let provider = MockUserIdleProvider::new(120);
+        let handle = provider.clone();
+        assert_eq!(provider.seconds_since_last_input().unwrap(), 120);
+
+        handle.input();
+        assert_eq!(provider.seconds_since_last_input().unwrap(), 0);
+    }
+}
@@ -0,0 +1,351 @@
+//! CPU idle trigger.
+//!
+//! `CpuIdleTrigger` samples CPU utilization through a [`CpuProvider`] and
+//! reports it as trigger data, so a render box or build machine can be
+//! shut down once it stops working rather than once the network goes
+//! quiet.
+
+use crate::{Trigger, TriggerData, TriggerDescriptor, TriggerError, TriggerState, TriggerValue};
+use async_trait::async_trait;
+use flowwatcher_platform::cpu::{CpuProvider, CpuUsage};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+/// Default time between CPU samples.
+pub const DEFAULT_CPU_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+// ---------------------------------------------------------------------------
+// CPU mode
+// ---------------------------------------------------------------------------
+
+/// Which CPU figure is compared against the threshold.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CpuMode {
+    /// Average utilization across all cores.
+    #[default]
+    Aggregate,
+    /// Utilization of the busiest core, so a single-threaded job pinning
+    /// one core keeps the machine awake.
+    PerCore,
+}
+
+/// JSON config of a [`CpuIdleTrigger`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuIdleConfig {
+    /// Which figure is reported as `cpu_percent`.
+    #[serde(default)]
+    pub mode: CpuMode,
+    /// Milliseconds between CPU samples.
+    #[serde(default = "default_sample_interval_ms")]
+    pub sample_interval_ms: u64,
+}
+
+fn default_sample_interval_ms() -> u64 {
+    DEFAULT_CPU_SAMPLE_INTERVAL.as_millis() as u64
+}
+
+// ---------------------------------------------------------------------------
+// CpuIdleTrigger
+// ---------------------------------------------------------------------------
+
+/// A trigger that reports CPU utilization for threshold evaluation.
+///
+/// Once started, evaluation returns [`TriggerState::Active`] with:
+///
+/// - `cpu_percent` — the figure selected by [`CpuMode`], as `F64`.
+/// - `max_core_percent` — the busiest core, as `F64`.
+/// - `core_count` — number of logical cores.
+/// - `download_bps` / `upload_bps` — `cpu_percent` rounded up to a whole
+///   percent, and 0, so `ThresholdCondition` works unchanged with its
+///   threshold read as a percentage.
+///
+/// The provider is refreshed at most once per sample interval; evaluations
+/// in between reuse the last reading, so `evaluate()` never waits on the
+/// platform's minimum refresh delay. Until the first full interval after
+/// `start()` has passed there is no reading yet and the trigger is
+/// [`TriggerState::Idle`].
+pub struct CpuIdleTrigger {
+    /// Source of CPU readings.
+    provider: Box<dyn CpuProvider>,
+    /// Which figure is reported as `cpu_percent`.
+    mode: CpuMode,
+    /// Requested time between provider refreshes.
+    sample_interval: Duration,
+    /// When the provider was last refreshed.
+    last_refresh: Option<Instant>,
+    /// Most recent reading (None until the first interval has passed).
+    last_usage: Option<CpuUsage>,
+    /// Whether the trigger has been started.
+    started: bool,
+}
+
+impl CpuIdleTrigger {
+    /// Create a new CPU trigger sampling every
+    /// [`DEFAULT_CPU_SAMPLE_INTERVAL`].
+    pub fn new(provider: Box<dyn CpuProvider>, mode: CpuMode) -> Self {
+        Self {
+            provider,
+            mode,
+            sample_interval: DEFAULT_CPU_SAMPLE_INTERVAL,
+            last_refresh: None,
+            last_usage: None,
+            started: false,
+        }
+    }
+
+    /// Registry metadata, including the config schema.
+    pub fn descriptor() -> TriggerDescriptor {
+        TriggerDescriptor::new(
+            "cpu_idle",
+            "CPU Idle",
+            "Triggers when CPU usage falls below a percentage",
+        )
+        .with_config_schema(json!({
+            "type": "object",
+            "properties": {
+                "mode": { "enum": ["aggregate", "per_core"], "default": "aggregate" },
+                "sample_interval_ms": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "default": default_sample_interval_ms()
+                }
+            }
+        }))
+    }
+
+    /// Build from a JSON [`CpuIdleConfig`] (for the trigger registry).
+    pub fn from_config(
+        config: &serde_json::Value,
+        provider: Box<dyn CpuProvider>,
+    ) -> Result<Self, TriggerError> {
+        let config: CpuIdleConfig = serde_json::from_value(config.clone())
+            .map_err(|e| TriggerError::InvalidConfig(format!("cpu_idle: {e}")))?;
+        Ok(Self::new(provider, config.mode)
+            .with_sample_interval(Duration::from_millis(config.sample_interval_ms)))
+    }
+
+    /// Sample every `interval` instead of the default.
+    pub fn with_sample_interval(mut self, interval: Duration) -> Self {
+        self.sample_interval = interval;
+        self
+    }
+
+    /// Which figure is reported as `cpu_percent`.
+    pub fn mode(&self) -> CpuMode {
+        self.mode
+    }
+
+    /// Effective time between samples: the requested interval, raised to
+    /// the provider's minimum if that is longer.
+    pub fn sample_interval(&self) -> Duration {
+        self.sample_interval
+            .max(self.provider.min_refresh_interval())
+    }
+
+    /// Evaluate as if the current time were `now` (for testability).
+    pub fn evaluate_at(&mut self, now: Instant) -> Result<TriggerState, TriggerError> {
+        if !self.started {
+            return Ok(TriggerState::Idle);
+        }
+
+        let due = self
+            .last_refresh
+            .is_none_or(|at| now.saturating_duration_since(at) >= self.sample_interval());
+        if due {
+            let usage = self
+                .provider
+                .refresh()
+                .map_err(|e| TriggerError::EvaluationError(e.to_string()))?;
+            self.last_refresh = Some(now);
+            self.last_usage = Some(usage);
+        }
+
+        Ok(match &self.last_usage {
+            Some(usage) => TriggerState::Active(self.trigger_data(usage)),
+            None => TriggerState::Idle,
+        })
+    }
+
+    /// Trigger data for one reading.
+    fn trigger_data(&self, usage: &CpuUsage) -> TriggerData {
+        let max_core = usage.max_core_percent();
+        let cpu_percent = match self.mode {
+            CpuMode::Aggregate => usage.total_percent,
+            CpuMode::PerCore => max_core,
+        };
+
+        let mut data = TriggerData::new();
+        data.insert("cpu_percent", TriggerValue::F64(cpu_percent));
+        data.insert("max_core_percent", TriggerValue::F64(max_core));
+        data.insert(
+            "core_count",
+            TriggerValue::U64(usage.per_core_percent.len() as u64),
+        );
+        data.insert(
+            "download_bps",
+            TriggerValue::U64(cpu_percent.max(0.0).ceil() as u64),
+        );
+        data.insert("upload_bps", TriggerValue::U64(0));
+        data
+    }
+}
+
+#[async_trait]
+impl Trigger for CpuIdleTrigger {
+    fn name(&self) -> &str {
+        "CPU Idle"
+    }
+
+    fn trigger_type(&self) -> &str {
+        "cpu_idle"
+    }
+
+    /// Start sampling. Takes the baseline reading the provider needs;
+    /// the first usable reading follows one sample interval later.
+    async fn start(&mut self) -> Result<(), TriggerError> {
+        self.provider
+            .refresh()
+            .map_err(|e| TriggerError::StartFailed(e.to_string()))?;
+        self.last_refresh = Some(Instant::now());
+        self.last_usage = None;
+        self.started = true;
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<(), TriggerError> {
+        self.started = false;
+        self.last_refresh = None;
+        self.last_usage = None;
+        Ok(())
+    }
+
+    async fn evaluate(&mut self) -> Result<TriggerState, TriggerError> {
+        self.evaluate_at(Instant::now())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_testing::{cpu_usage, ScriptedCpuProvider};
+
+    async fn started(provider: &ScriptedCpuProvider, mode: CpuMode) -> CpuIdleTrigger {
+        let mut trigger = CpuIdleTrigger::new(Box::new(provider.clone()), mode);
+        trigger.start().await.unwrap();
+        trigger
+    }
+
+    fn active(state: TriggerState) -> TriggerData {
+        match state {
+            TriggerState::Active(data) => data,
+            other => panic!("expected Active, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn aggregate_mode_reports_average() {
+        let provider = ScriptedCpuProvider::new(vec![cpu_usage(&[2.0, 90.0, 4.0, 4.0])]);
+        let mut trigger = started(&provider, CpuMode::Aggregate).await;
+
+        let data = active(
+            trigger
+                .evaluate_at(Instant::now() + Duration::from_secs(1))
+                .unwrap(),
+        );
+        assert_eq!(data.get("cpu_percent"), Some(&TriggerValue::F64(25.0)));
+        assert_eq!(data.get("max_core_percent"), Some(&TriggerValue::F64(90.0)));
+        assert_eq!(data.get("core_count"), Some(&TriggerValue::U64(4)));
+        assert_eq!(data.get("download_bps"), Some(&TriggerValue::U64(25)));
+        assert_eq!(data.get("upload_bps"), Some(&TriggerValue::U64(0)));
+    }
+
+    #[tokio::test]
+    async fn per_core_mode_reports_busiest_core() {
+        let provider = ScriptedCpuProvider::new(vec![cpu_usage(&[2.0, 90.5, 4.0, 4.0])]);
+        let mut trigger = started(&provider, CpuMode::PerCore).await;
+
+        let data = active(
+            trigger
+                .evaluate_at(Instant::now() + Duration::from_secs(1))
+                .unwrap(),
+        );
+        assert_eq!(data.get("cpu_percent"), Some(&TriggerValue::F64(90.5)));
+        // Rounded up so a fractional reading never looks idler than it is.
+        assert_eq!(data.get("download_bps"), Some(&TriggerValue::U64(91)));
+    }
+
+    #[tokio::test]
+    async fn readings_are_reused_within_the_interval() {
+        let provider = ScriptedCpuProvider::new(vec![
+            cpu_usage(&[0.0]),
+            cpu_usage(&[80.0]),
+            cpu_usage(&[5.0]),
+        ]);
+        let mut trigger = started(&provider, CpuMode::Aggregate)
+            .await
+            .with_sample_interval(Duration::from_secs(2));
+        let t0 = Instant::now();
+        assert_eq!(provider.refreshes(), 1, "start takes the baseline");
+
+        // Before the first interval there is no reading yet.
+        assert_eq!(
+            trigger
+                .evaluate_at(t0 + Duration::from_millis(500))
+                .unwrap(),
+            TriggerState::Idle
+        );
+        assert_eq!(provider.refreshes(), 1);
+
+        let first = active(trigger.evaluate_at(t0 + Duration::from_secs(2)).unwrap());
+        assert_eq!(first.get("cpu_percent"), Some(&TriggerValue::F64(80.0)));
+        let cached = active(trigger.evaluate_at(t0 + Duration::from_secs(3)).unwrap());
+        assert_eq!(cached, first);
+        assert_eq!(provider.refreshes(), 2);
+
+        let next = active(trigger.evaluate_at(t0 + Duration::from_secs(4)).unwrap());
+        assert_eq!(next.get("cpu_percent"), Some(&TriggerValue::F64(5.0)));
+        assert_eq!(provider.refreshes(), 3);
+    }
+
+    #[tokio::test]
+    async fn interval_respects_provider_minimum() {
+        let provider = ScriptedCpuProvider::new(vec![cpu_usage(&[10.0])])
+            .with_min_interval(Duration::from_millis(200));
+        let trigger = CpuIdleTrigger::new(Box::new(provider), CpuMode::Aggregate)
+            .with_sample_interval(Duration::from_millis(50));
+        assert_eq!(trigger.sample_interval(), Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn stopped_trigger_is_idle_and_restarts_cleanly() {
+        let provider = ScriptedCpuProvider::new(vec![cpu_usage(&[10.0])]);
+        let mut trigger = CpuIdleTrigger::new(Box::new(provider.clone()), CpuMode::Aggregate);
+        assert_eq!(
+            trigger.evaluate_at(Instant::now()).unwrap(),
+            TriggerState::Idle
+        );
+        assert_eq!(provider.refreshes(), 0);
+
+        trigger.start().await.unwrap();
+        let later = Instant::now() + Duration::from_secs(1);
+        assert!(matches!(
+            trigger.evaluate_at(later).unwrap(),
+            TriggerState::Active(_)
+        ));
+
+        trigger.stop().await.unwrap();
+        assert_eq!(trigger.evaluate_at(later).unwrap(), TriggerState::Idle);
+        trigger.start().await.unwrap();
+        assert_eq!(
+            trigger.evaluate_at(Instant::now()).unwrap(),
+            TriggerState::Idle,
+            "a restart waits for a fresh reading"
+        );
+    }
+}
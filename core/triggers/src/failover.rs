@@ -0,0 +1,422 @@
+//! Interface failover for the speed monitor.
+//!
+//! When Wi-Fi drops and traffic moves to Ethernet, a [`SpeedMonitor`]
+//! pinned to the Wi-Fi adapter reads 0 B/s (or fails with
+//! `InterfaceNotFound`) and the idle condition would happily be met. An
+//! [`InterfaceWatchdog`] checks each poll against the other interfaces:
+//! once the monitored one has been silent or missing for a number of
+//! polls while another carries real traffic, it either switches the
+//! monitor over or reports the monitored interface as inactive so the
+//! host can hold condition evaluation back.
+
+use crate::speed::{InterfaceSelection, SpeedError, SpeedMonitor, SpeedReading};
+use flowwatcher_platform::network::{summarize_traffic, NetworkError, TrafficSnapshot};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Default consecutive suspicious polls before failing over.
+pub const DEFAULT_FAILOVER_POLLS: u32 = 5;
+
+/// Default traffic another interface must carry, in bytes per second.
+pub const DEFAULT_FAILOVER_MIN_BPS: u64 = 10 * 1024;
+
+// ---------------------------------------------------------------------------
+// Policy
+// ---------------------------------------------------------------------------
+
+/// What happens when the monitored interface looks dead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailoverMode {
+    /// Keep monitoring the configured interface.
+    #[default]
+    Off,
+    /// Switch the monitor to the interface carrying the traffic.
+    Switch,
+    /// Keep the interface but hold condition evaluation back until it
+    /// carries traffic again.
+    Suspend,
+}
+
+/// When and how to fail over from a dead interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailoverPolicy {
+    /// What to do.
+    #[serde(default)]
+    pub mode: FailoverMode,
+    /// Consecutive polls the interface must look dead.
+    #[serde(default = "default_failover_polls")]
+    pub after_polls: u32,
+    /// Bytes per second another interface must carry for the monitored
+    /// one to count as dead rather than idle.
+    #[serde(default = "default_failover_min_bps")]
+    pub min_active_bps: u64,
+}
+
+fn default_failover_polls() -> u32 {
+    DEFAULT_FAILOVER_POLLS
+}
+
+fn default_failover_min_bps() -> u64 {
+    DEFAULT_FAILOVER_MIN_BPS
+}
+
+impl Default for FailoverPolicy {
+    fn default() -> Self {
+        Self {
+            mode: FailoverMode::Off,
+            after_polls: DEFAULT_FAILOVER_POLLS,
+            min_active_bps: DEFAULT_FAILOVER_MIN_BPS,
+        }
+    }
+}
+
+impl FailoverPolicy {
+    /// A policy with `mode` and the default thresholds.
+    pub fn new(mode: FailoverMode) -> Self {
+        Self {
+            mode,
+            ..Self::default()
+        }
+    }
+
+    /// Fail over after `polls` consecutive suspicious polls.
+    pub fn with_after_polls(mut self, polls: u32) -> Self {
+        self.after_polls = polls;
+        self
+    }
+
+    /// Require `bps` on another interface.
+    pub fn with_min_active_bps(mut self, bps: u64) -> Self {
+        self.min_active_bps = bps;
+        self
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Events
+// ---------------------------------------------------------------------------
+
+/// A failover decision, for the host to log and surface.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FailoverEvent {
+    /// The monitor now watches `to` instead of `from`.
+    Switched {
+        /// The interface that went dead.
+        from: String,
+        /// The interface carrying the traffic.
+        to: String,
+    },
+    /// The monitored interface is inactive while `active` carries
+    /// traffic; condition evaluation should be held back.
+    Inactive {
+        /// The monitored interface.
+        interface: String,
+        /// The interface carrying the traffic.
+        active: String,
+    },
+    /// The monitored interface is usable again.
+    Recovered {
+        /// The monitored interface.
+        interface: String,
+    },
+}
+
+impl fmt::Display for FailoverEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Switched { from, to } => {
+                write!(
+                    f,
+                    "Interface {from} went inactive; switched monitoring to {to}"
+                )
+            }
+            Self::Inactive { interface, active } => write!(
+                f,
+                "Monitored interface {interface} inactive while traffic flows on {active}; \
+                 idle detection suspended"
+            ),
+            Self::Recovered { interface } => {
+                write!(f, "Monitored interface {interface} active again")
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// InterfaceWatchdog
+// ---------------------------------------------------------------------------
+
+/// Watches the monitored interface for going dead while another one
+/// carries the traffic.
+///
+/// Call [`check`](Self::check) after every
+/// [`SpeedMonitor::poll_snapshot`] with the snapshot and its result. A poll is suspicious when the monitored interface is missing
+/// or read exactly 0 B/s both ways while another interface moved at
+/// least [`FailoverPolicy::min_active_bps`]. After
+/// [`FailoverPolicy::after_polls`] suspicious polls in a row the policy's
+/// mode applies. Monitors summing every interface are never failed over.
+#[derive(Debug, Clone)]
+pub struct InterfaceWatchdog {
+    /// When and how to fail over.
+    policy: FailoverPolicy,
+    /// Counters of every interface at the previous check.
+    previous: Option<TrafficSnapshot>,
+    /// Consecutive suspicious polls so far.
+    suspicious_polls: u32,
+    /// Whether evaluation is being held back ([`FailoverMode::Suspend`]).
+    suspended: bool,
+}
+
+impl InterfaceWatchdog {
+    /// A watchdog applying `policy`.
+    pub fn new(policy: FailoverPolicy) -> Self {
+        Self {
+            policy,
+            previous: None,
+            suspicious_polls: 0,
+            suspended: false,
+        }
+    }
+
+    /// The policy applied.
+    pub fn policy(&self) -> FailoverPolicy {
+        self.policy
+    }
+
+    /// Whether condition evaluation should be held back because the
+    /// monitored interface is inactive.
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    /// Forget counters and suspicion, e.g. when monitoring restarts.
+    pub fn reset(&mut self) {
+        self.previous = None;
+        self.suspicious_polls = 0;
+        self.suspended = false;
+    }
+
+    /// Check the result of the poll `monitor` just made of `snapshot`,
+    /// switching `monitor` over if the policy says so.
+    pub fn check(
+        &mut self,
+        monitor: &mut SpeedMonitor,
+        snapshot: &TrafficSnapshot,
+        polled: &Result<Option<SpeedReading>, SpeedError>,
+    ) -> Option<FailoverEvent> {
+        if self.policy.mode == FailoverMode::Off {
+            return None;
+        }
+        let InterfaceSelection::Ids(monitored) = monitor.interfaces().clone() else {
+            return None;
+        };
+        let dead = match polled {
+            Ok(Some(reading)) => reading.download_bps == 0 && reading.upload_bps == 0,
+            Err(SpeedError::NetworkError(NetworkError::InterfaceNotFound(_))) => true,
+            // A new baseline or a transient error says nothing either way.
+            Ok(None) | Err(_) => false,
+        };
+        let busiest = self.busiest_other(snapshot, &monitored);
+
+        let Some(active) = busiest.filter(|_| dead) else {
+            self.suspicious_polls = 0;
+            if !std::mem::take(&mut self.suspended) {
+                return None;
+            }
+            return Some(FailoverEvent::Recovered {
+                interface: monitored.join(", "),
+            });
+        };
+        self.suspicious_polls = self.suspicious_polls.saturating_add(1);
+        if self.suspicious_polls < self.policy.after_polls.max(1) {
+            return None;
+        }
+
+        match self.policy.mode {
+            FailoverMode::Off => None,
+            FailoverMode::Switch => {
+                self.suspicious_polls = 0;
+                monitor.switch_interfaces(InterfaceSelection::Ids(vec![active.clone()]));
+                Some(FailoverEvent::Switched {
+                    from: monitored.join(", "),
+                    to: active,
+                })
+            }
+            FailoverMode::Suspend if self.suspended => None,
+            FailoverMode::Suspend => {
+                self.suspended = true;
+                Some(FailoverEvent::Inactive {
+                    interface: monitored.join(", "),
+                    active,
+                })
+            }
+        }
+    }
+
+    /// The unmonitored interface that moved the most bytes per second
+    /// since the previous check, if it reached the policy's minimum.
+    fn busiest_other(
+        &mut self,
+        snapshot: &TrafficSnapshot,
+        monitored: &[String],
+    ) -> Option<String> {
+        let summary = summarize_traffic(self.previous.as_ref(), snapshot);
+        self.previous = Some(snapshot.clone());
+        summary
+            .into_iter()
+            .filter(|traffic| !monitored.contains(&traffic.id))
+            .filter_map(|traffic| {
+                let secs = traffic.window_secs.filter(|secs| *secs > 0.0)?;
+                let bps = (traffic.recent_bytes()? as f64 / secs) as u64;
+                Some((bps, traffic.id))
+            })
+            .filter(|(bps, _)| *bps >= self.policy.min_active_bps)
+            .max_by_key(|(bps, _)| *bps)
+            .map(|(_, id)| id)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_platform::network::NetworkProvider;
+    use flowwatcher_testing::MultiInterfaceProvider;
+
+    const BUSY: Option<(u64, u64)> = Some((50_000, 1_000));
+    const QUIET: Option<(u64, u64)> = Some((0, 0));
+
+    /// Wi-Fi carries traffic for two seconds, then goes silent (or
+    /// vanishes) while Ethernet takes over.
+    fn flipping(wifi_after: Option<(u64, u64)>) -> MultiInterfaceProvider {
+        MultiInterfaceProvider::new()
+            .with_interface("wlan0", &[BUSY, BUSY, wifi_after])
+            .with_interface("eth0", &[QUIET, QUIET, BUSY])
+    }
+
+    /// Advance `provider`, poll `monitor` and check it, `steps` times,
+    /// returning every event.
+    fn run(
+        provider: &mut MultiInterfaceProvider,
+        monitor: &mut SpeedMonitor,
+        watchdog: &mut InterfaceWatchdog,
+        steps: usize,
+    ) -> Vec<FailoverEvent> {
+        let mut events = Vec::new();
+        for _ in 0..steps {
+            provider.advance();
+            let snapshot = provider.snapshot_all().unwrap();
+            let polled = monitor.poll_snapshot(&snapshot);
+            events.extend(watchdog.check(monitor, &snapshot, &polled));
+        }
+        events
+    }
+
+    fn setup(policy: FailoverPolicy) -> (SpeedMonitor, InterfaceWatchdog) {
+        let monitor = SpeedMonitor::new("wlan0", 1);
+        (monitor, InterfaceWatchdog::new(policy.with_after_polls(3)))
+    }
+
+    #[test]
+    fn switches_to_the_busy_interface_after_n_silent_polls() {
+        let mut provider = flipping(QUIET);
+        let (mut monitor, mut watchdog) = setup(FailoverPolicy::new(FailoverMode::Switch));
+        let snapshot = provider.snapshot_all().unwrap();
+        monitor.poll_snapshot(&snapshot).unwrap();
+        watchdog.check(&mut monitor, &snapshot, &Ok(None));
+
+        // Steps 1-2 busy, 3-4 silent: not yet.
+        assert!(run(&mut provider, &mut monitor, &mut watchdog, 4).is_empty());
+        assert_eq!(
+            run(&mut provider, &mut monitor, &mut watchdog, 1),
+            vec![FailoverEvent::Switched {
+                from: "wlan0".into(),
+                to: "eth0".into()
+            }]
+        );
+        assert_eq!(
+            monitor.interfaces(),
+            &InterfaceSelection::Ids(vec!["eth0".to_string()])
+        );
+
+        // The new interface reports its traffic after a fresh baseline.
+        assert!(run(&mut provider, &mut monitor, &mut watchdog, 2).is_empty());
+        assert_eq!(monitor.latest_reading().unwrap().download_bps, 50_000);
+    }
+
+    #[test]
+    fn a_vanished_interface_counts_as_dead() {
+        let mut provider = flipping(None);
+        let (mut monitor, mut watchdog) = setup(FailoverPolicy::new(FailoverMode::Switch));
+        monitor.poll(&mut provider).unwrap();
+
+        let events = run(&mut provider, &mut monitor, &mut watchdog, 6);
+        assert_eq!(
+            events,
+            vec![FailoverEvent::Switched {
+                from: "wlan0".into(),
+                to: "eth0".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn suspend_mode_reports_inactive_once_then_recovers() {
+        let mut provider = MultiInterfaceProvider::new()
+            .with_interface("wlan0", &[QUIET, QUIET, QUIET, QUIET, QUIET, BUSY])
+            .with_interface("eth0", &[BUSY]);
+        let (mut monitor, mut watchdog) = setup(FailoverPolicy::new(FailoverMode::Suspend));
+        monitor.poll(&mut provider).unwrap();
+
+        let events = run(&mut provider, &mut monitor, &mut watchdog, 5);
+        assert_eq!(
+            events,
+            vec![FailoverEvent::Inactive {
+                interface: "wlan0".into(),
+                active: "eth0".into()
+            }]
+        );
+        assert!(watchdog.is_suspended());
+        assert_eq!(
+            monitor.interfaces(),
+            &InterfaceSelection::Ids(vec!["wlan0".to_string()])
+        );
+
+        assert_eq!(
+            run(&mut provider, &mut monitor, &mut watchdog, 1),
+            vec![FailoverEvent::Recovered {
+                interface: "wlan0".into()
+            }]
+        );
+        assert!(!watchdog.is_suspended());
+    }
+
+    #[test]
+    fn quiet_everywhere_is_just_idle() {
+        let mut provider = MultiInterfaceProvider::new()
+            .with_interface("wlan0", &[QUIET])
+            .with_interface("eth0", &[Some((100, 0))]);
+        for mode in [
+            FailoverMode::Off,
+            FailoverMode::Switch,
+            FailoverMode::Suspend,
+        ] {
+            let (mut monitor, mut watchdog) = setup(FailoverPolicy::new(mode));
+            assert!(run(&mut provider, &mut monitor, &mut watchdog, 10).is_empty());
+            assert!(!watchdog.is_suspended());
+        }
+    }
+
+    #[test]
+    fn policy_deserializes_with_defaults() {
+        let policy: FailoverPolicy = serde_json::from_str(r#"{"mode":"suspend"}"#).unwrap();
+        assert_eq!(policy, FailoverPolicy::new(FailoverMode::Suspend));
+        assert_eq!(policy.after_polls, DEFAULT_FAILOVER_POLLS);
+        assert_eq!(FailoverPolicy::default().mode, FailoverMode::Off);
+    }
+}
@@ -0,0 +1,278 @@
+//! Per-process activity history for sparklines.
+//!
+//! [`ProcessActivityHistory`] keeps a fixed-size ring buffer of
+//! `(unix_ms, bytes)` samples for each watched process name. Both the
+//! number of samples per series and the number of series are capped, so
+//! memory stays bounded even when a watch list matches many processes.
+
+use flowwatcher_platform::process::ProcessInfo;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// Default number of samples kept per process.
+pub const DEFAULT_HISTORY_CAPACITY: usize = 300;
+
+/// Default maximum number of tracked process series.
+pub const DEFAULT_MAX_SERIES: usize = 64;
+
+/// One `(unix timestamp in ms, bytes)` sample.
+pub type ActivitySample = (u64, u64);
+
+// ---------------------------------------------------------------------------
+// ProcessActivityHistory
+// ---------------------------------------------------------------------------
+
+/// Bounded per-process history of activity samples.
+#[derive(Debug, Clone)]
+pub struct ProcessActivityHistory {
+    /// Samples kept per series before the oldest is dropped.
+    capacity: usize,
+    /// Maximum number of series tracked at once.
+    max_series: usize,
+    /// Samples per lowercased process name, oldest first.
+    series: HashMap<String, VecDeque<ActivitySample>>,
+    /// Whether a process has been turned away because of `max_series`.
+    cap_reached: bool,
+}
+
+impl Default for ProcessActivityHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_CAPACITY, DEFAULT_MAX_SERIES)
+    }
+}
+
+impl ProcessActivityHistory {
+    /// Create a history keeping `capacity` samples for up to `max_series`
+    /// processes. Both limits are at least 1.
+    pub fn new(capacity: usize, max_series: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            max_series: max_series.max(1),
+            series: HashMap::new(),
+            cap_reached: false,
+        }
+    }
+
+    /// Record one snapshot taken at `at_ms`.
+    ///
+    /// Processes sharing a name are summed into one sample. Returns the
+    /// number of names that were not recorded because the series cap was
+    /// already full.
+    pub fn record(&mut self, at_ms: u64, processes: &[ProcessInfo]) -> usize {
+        let mut totals: BTreeMap<String, u64> = BTreeMap::new();
+        for process in processes {
+            let total = totals.entry(process.name.to_lowercase()).or_default();
+            *total = total.saturating_add(process.activity_bps);
+        }
+
+        let mut skipped = 0;
+        for (name, bytes) in totals {
+            if !self.series.contains_key(&name) && self.series.len() >= self.max_series {
+                skipped += 1;
+                continue;
+            }
+            let samples = self
+                .series
+                .entry(name)
+                .or_insert_with(|| VecDeque::with_capacity(self.capacity));
+            if samples.len() >= self.capacity {
+                samples.pop_front();
+            }
+            samples.push_back((at_ms, bytes));
+        }
+
+        if skipped > 0 {
+            self.cap_reached = true;
+        }
+        skipped
+    }
+
+    /// Whether any process has been skipped because of the series cap
+    /// since the last reset.
+    pub fn cap_reached(&self) -> bool {
+        self.cap_reached
+    }
+
+    /// Samples per series.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Maximum number of tracked series.
+    pub fn max_series(&self) -> usize {
+        self.max_series
+    }
+
+    /// Number of tracked series.
+    pub fn len(&self) -> usize {
+        self.series.len()
+    }
+
+    /// Total number of samples across all series.
+    pub fn sample_count(&self) -> usize {
+        self.series.values().map(VecDeque::len).sum()
+    }
+
+    /// Whether no series are tracked.
+    pub fn is_empty(&self) -> bool {
+        self.series.is_empty()
+    }
+
+    /// Samples for one process name (case-insensitive), oldest first.
+    pub fn get(&self, name: &str) -> Option<Vec<ActivitySample>> {
+        self.series
+            .get(&name.to_lowercase())
+            .map(|samples| samples.iter().copied().collect())
+    }
+
+    /// All series keyed by lowercased process name.
+    pub fn snapshot(&self) -> BTreeMap<String, Vec<ActivitySample>> {
+        self.series
+            .iter()
+            .map(|(name, samples)| (name.clone(), samples.iter().copied().collect()))
+            .collect()
+    }
+
+    /// All series reduced to at most `max_points` samples each.
+    ///
+    /// Samples are grouped into consecutive buckets; each bucket keeps the
+    /// timestamp of its last sample and its peak byte count so short bursts
+    /// stay visible.
+    pub fn downsampled(&self, max_points: usize) -> BTreeMap<String, Vec<ActivitySample>> {
+        let max_points = max_points.max(1);
+        self.series
+            .iter()
+            .map(|(name, samples)| {
+                let samples: Vec<ActivitySample> = samples.iter().copied().collect();
+                let bucket = samples.len().div_ceil(max_points).max(1);
+                let reduced = samples
+                    .chunks(bucket)
+                    .map(|chunk| {
+                        let at = chunk[chunk.len() - 1].0;
+                        let peak = chunk.iter().map(|s| s.1).max().unwrap_or(0);
+                        (at, peak)
+                    })
+                    .collect();
+                (name.clone(), reduced)
+            })
+            .collect()
+    }
+
+    /// Change the per-series capacity, trimming existing series.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        for samples in self.series.values_mut() {
+            while samples.len() > self.capacity {
+                samples.pop_front();
+            }
+        }
+    }
+
+    /// Drop all series and clear the cap flag.
+    pub fn reset(&mut self) {
+        self.series.clear();
+        self.cap_reached = false;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_testing::{process, sample_processes};
+
+    #[test]
+    fn ring_buffer_drops_oldest_samples() {
+        let mut history = ProcessActivityHistory::new(3, 8);
+        for i in 0..5u64 {
+            history.record(i * 1_000, &[process(1, "Steam.exe", i * 10)]);
+        }
+
+        assert_eq!(history.len(), 1);
+        assert_eq!(
+            history.get("steam.exe").unwrap(),
+            vec![(2_000, 20), (3_000, 30), (4_000, 40)]
+        );
+    }
+
+    #[test]
+    fn same_name_processes_are_summed() {
+        let mut history = ProcessActivityHistory::default();
+        history.record(
+            0,
+            &[process(1, "chrome.exe", 100), process(2, "Chrome.exe", 50)],
+        );
+        assert_eq!(history.get("CHROME.EXE").unwrap(), vec![(0, 150)]);
+    }
+
+    #[test]
+    fn series_cap_skips_new_names() {
+        let mut history = ProcessActivityHistory::new(10, 2);
+        let skipped = history.record(0, &sample_processes());
+
+        assert_eq!(history.len(), 2);
+        assert_eq!(skipped, 2);
+        assert!(history.cap_reached());
+
+        // Tracked names keep recording; untracked ones stay out.
+        let tracked: Vec<String> = history.snapshot().into_keys().collect();
+        assert_eq!(history.record(1_000, &sample_processes()), 2);
+        for name in &tracked {
+            assert_eq!(history.get(name).unwrap().len(), 2);
+        }
+        assert_eq!(history.len(), 2);
+
+        history.reset();
+        assert!(history.is_empty());
+        assert!(!history.cap_reached());
+    }
+
+    #[test]
+    fn many_glob_matches_stay_bounded() {
+        let mut history = ProcessActivityHistory::new(5, DEFAULT_MAX_SERIES);
+        for tick in 0..20u64 {
+            let snapshot: Vec<ProcessInfo> = (0..500u32)
+                .map(|i| process(i, &format!("worker-{tick}-{i}.exe"), 1))
+                .collect();
+            history.record(tick, &snapshot);
+        }
+        assert_eq!(history.len(), DEFAULT_MAX_SERIES);
+        assert!(history
+            .snapshot()
+            .values()
+            .all(|samples| samples.len() <= 5));
+    }
+
+    #[test]
+    fn downsampling_keeps_peaks() {
+        let mut history = ProcessActivityHistory::new(300, 4);
+        for i in 0..10u64 {
+            let bytes = if i == 3 { 9_000 } else { i };
+            history.record(i, &[process(1, "steam.exe", bytes)]);
+        }
+
+        let reduced = history.downsampled(5);
+        assert_eq!(
+            reduced["steam.exe"],
+            vec![(1, 1), (3, 9_000), (5, 5), (7, 7), (9, 9)]
+        );
+        // Short series are returned unchanged.
+        assert_eq!(history.downsampled(50)["steam.exe"].len(), 10);
+    }
+
+    #[test]
+    fn shrinking_capacity_trims_series() {
+        let mut history = ProcessActivityHistory::new(10, 4);
+        for i in 0..10u64 {
+            history.record(i, &[process(1, "steam.exe", i)]);
+        }
+        history.set_capacity(4);
+        assert_eq!(history.capacity(), 4);
+        assert_eq!(
+            history.get("steam.exe").unwrap(),
+            vec![(6, 6), (7, 7), (8, 8), (9, 9)]
+        );
+    }
+}
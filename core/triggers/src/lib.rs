@@ -12,12 +12,29 @@
 //! modifying existing engine code.
 
 use async_trait::async_trait;
+use flowwatcher_platform::process::ProcessInfo;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use thiserror::Error;
 
+pub mod cpu;
+pub mod failover;
+pub mod history;
+pub mod network;
 pub mod process;
-pub use process::ProcessTrigger;
+pub mod registry;
+pub mod speed;
+pub mod timer;
+pub use cpu::{CpuIdleTrigger, CpuMode};
+pub use failover::{FailoverEvent, FailoverMode, FailoverPolicy, InterfaceWatchdog};
+pub use history::{ActivitySample, ProcessActivityHistory};
+pub use network::{InterfaceChoice, NetworkIdleTrigger};
+pub use process::{ProcessMatch, ProcessTrigger, SharedProcessProvider};
+pub use registry::{builtin_registry, TriggerDescriptor, TriggerFactory, TriggerRegistry};
+pub use speed::{
+    ByteDelta, InterfaceSelection, SmoothingStrategy, SpeedMonitor, TimestampedReading,
+};
+pub use timer::{TimerTarget, TimerTrigger};
 
 // ---------------------------------------------------------------------------
 // Error types
@@ -37,6 +54,42 @@ pub enum TriggerError {
     /// An error occurred during evaluation.
     #[error("trigger evaluation error: {0}")]
     EvaluationError(String),
+
+    /// No trigger is registered under the requested type id.
+    #[error("unknown trigger type: {0}")]
+    UnknownType(String),
+
+    /// The trigger configuration could not be parsed.
+    #[error("invalid trigger config: {0}")]
+    InvalidConfig(String),
+
+    /// The configured network interface does not exist.
+    #[error("unknown network interface `{id}`; available: {available}")]
+    InterfaceNotFound {
+        /// The requested interface id.
+        id: String,
+        /// Ids of the interfaces that do exist, comma-separated, or "none".
+        available: String,
+    },
+}
+
+/// Why a typed [`TriggerData`] accessor found no usable value.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TriggerDataError {
+    /// The key is not present.
+    #[error("missing trigger data key: {0}")]
+    Missing(String),
+
+    /// The key holds a value of another type.
+    #[error("trigger data key {key} holds {found}, expected {expected}")]
+    WrongType {
+        /// The key looked up.
+        key: String,
+        /// The type the caller asked for.
+        expected: &'static str,
+        /// The type found (see [`TriggerValue::type_name`]).
+        found: &'static str,
+    },
 }
 
 // ---------------------------------------------------------------------------
@@ -86,6 +139,92 @@ impl TriggerData {
     pub fn get(&self, key: &str) -> Option<&TriggerValue> {
         self.values.get(key)
     }
+
+    /// Get a whole-number value: `U64`, `Bytes` or `DurationSecs`.
+    pub fn get_u64(&self, key: &str) -> Result<u64, TriggerDataError> {
+        match self.value(key)? {
+            TriggerValue::U64(v) | TriggerValue::Bytes(v) | TriggerValue::DurationSecs(v) => Ok(*v),
+            other => Err(wrong_type(key, "u64", other)),
+        }
+    }
+
+    /// Get any numeric value as a `u64`, rounding finite non-negative
+    /// floats. See [`TriggerValue::as_u64`].
+    pub fn get_as_u64(&self, key: &str) -> Result<u64, TriggerDataError> {
+        let value = self.value(key)?;
+        value.as_u64().ok_or_else(|| wrong_type(key, "u64", value))
+    }
+
+    /// Get any numeric value as a finite `f64`. See [`TriggerValue::as_f64`].
+    pub fn get_as_f64(&self, key: &str) -> Result<f64, TriggerDataError> {
+        let value = self.value(key)?;
+        value.as_f64().ok_or_else(|| wrong_type(key, "f64", value))
+    }
+
+    /// Get any numeric value as a `u64` like [`get_as_u64`](Self::get_as_u64),
+    /// or `default` when the key is missing. `Bytes` and `DurationSecs`
+    /// convert as is and an `F64` is rounded; a non-numeric value is still
+    /// an error.
+    pub fn get_u64_or(&self, key: &str, default: u64) -> Result<u64, TriggerDataError> {
+        match self.get_as_u64(key) {
+            Err(TriggerDataError::Missing(_)) => Ok(default),
+            result => result,
+        }
+    }
+
+    /// Get a finite `F64` value.
+    pub fn get_f64(&self, key: &str) -> Result<f64, TriggerDataError> {
+        match self.value(key)? {
+            TriggerValue::F64(v) if v.is_finite() => Ok(*v),
+            other => Err(wrong_type(key, "f64", other)),
+        }
+    }
+
+    /// Get a `Bool` value.
+    pub fn get_bool(&self, key: &str) -> Result<bool, TriggerDataError> {
+        match self.value(key)? {
+            TriggerValue::Bool(v) => Ok(*v),
+            other => Err(wrong_type(key, "bool", other)),
+        }
+    }
+
+    /// Get a `String` value.
+    pub fn get_str(&self, key: &str) -> Result<&str, TriggerDataError> {
+        match self.value(key)? {
+            TriggerValue::String(v) => Ok(v),
+            other => Err(wrong_type(key, "string", other)),
+        }
+    }
+
+    /// The value under `key`, or a [`TriggerDataError::Missing`] error.
+    fn value(&self, key: &str) -> Result<&TriggerValue, TriggerDataError> {
+        self.get(key)
+            .ok_or_else(|| TriggerDataError::Missing(key.to_string()))
+    }
+
+    /// The values as a plain JSON object with sorted keys, e.g.
+    /// `{"download_bps": 120, "upload_bps": 40}`, for logs and exports.
+    /// Invalid measurements become `null`.
+    pub fn to_json(&self) -> serde_json::Value {
+        let sorted: std::collections::BTreeMap<_, _> = self
+            .values
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    TriggerValue::U64(v)
+                    | TriggerValue::DurationSecs(v)
+                    | TriggerValue::Bytes(v) => (*v).into(),
+                    TriggerValue::F64(v) => serde_json::Number::from_f64(*v)
+                        .map_or(serde_json::Value::Null, serde_json::Value::Number),
+                    TriggerValue::String(v) => v.clone().into(),
+                    TriggerValue::Bool(v) => (*v).into(),
+                    TriggerValue::Invalid => serde_json::Value::Null,
+                };
+                (key.clone(), value)
+            })
+            .collect();
+        serde_json::Value::Object(sorted.into_iter().collect())
+    }
 }
 
 impl Default for TriggerData {
@@ -94,17 +233,149 @@ impl Default for TriggerData {
     }
 }
 
+/// The error for `key` holding `found` where `expected` was asked for.
+fn wrong_type(key: &str, expected: &'static str, found: &TriggerValue) -> TriggerDataError {
+    TriggerDataError::WrongType {
+        key: key.to_string(),
+        expected,
+        found: found.type_name(),
+    }
+}
+
 /// A typed value in trigger data.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+///
+/// # Non-finite floats
+///
+/// `NaN` and `±∞` cannot be represented in JSON and make equality
+/// surprising, so producers should build floats with [`TriggerValue::f64`],
+/// which maps non-finite input to [`TriggerValue::Invalid`]. A raw
+/// `F64(NaN)` built directly is still handled safely: it serializes as
+/// `Invalid` and compares equal to `Invalid` (see the `PartialEq` impl).
+///
+/// # Serialization
+///
+/// Values are externally tagged, e.g. `{"U64":5}` or `{"Bytes":1024}`.
+/// New variants are only ever appended, so the tags and variant indexes of
+/// older payloads (such as a persisted `TriggerState`) keep deserializing.
+#[derive(Debug, Clone, Deserialize)]
 pub enum TriggerValue {
     /// Unsigned 64-bit integer (e.g., bytes per second).
     U64(u64),
-    /// Floating point (e.g., percentage).
+    /// Floating point (e.g., percentage). Always finite when built via [`TriggerValue::f64`].
     F64(f64),
     /// String value.
     String(String),
     /// Boolean flag.
     Bool(bool),
+    /// A measurement that could not be computed (e.g., a `0/0` percentage).
+    Invalid,
+    /// A duration in whole seconds (e.g., time spent idle).
+    DurationSecs(u64),
+    /// A byte count (e.g., bytes transferred since the last sample).
+    Bytes(u64),
+}
+
+impl TriggerValue {
+    /// Build a float value, mapping `NaN` and `±∞` to [`TriggerValue::Invalid`].
+    pub fn f64(value: f64) -> Self {
+        if value.is_finite() {
+            Self::F64(value)
+        } else {
+            Self::Invalid
+        }
+    }
+
+    /// The value's type for error messages: `"u64"`, `"f64"`, `"string"`,
+    /// `"bool"`, `"duration_secs"`, `"bytes"` or `"invalid"` (which
+    /// includes non-finite floats).
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            _ if self.is_invalid() => "invalid",
+            Self::U64(_) => "u64",
+            Self::F64(_) => "f64",
+            Self::String(_) => "string",
+            Self::Bool(_) => "bool",
+            Self::Invalid => "invalid",
+            Self::DurationSecs(_) => "duration_secs",
+            Self::Bytes(_) => "bytes",
+        }
+    }
+
+    /// The value as a `u64` if it is numeric. Whole-number variants convert
+    /// as is; a finite, non-negative `F64` within range is rounded to the
+    /// nearest integer. Anything else is `None`.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::U64(v) | Self::DurationSecs(v) | Self::Bytes(v) => Some(*v),
+            Self::F64(v) if v.is_finite() && *v >= 0.0 && *v < u64::MAX as f64 => {
+                Some(v.round() as u64)
+            }
+            _ => None,
+        }
+    }
+
+    /// The value as a finite `f64` if it is numeric. Large integers lose
+    /// precision above 2^53.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::U64(v) | Self::DurationSecs(v) | Self::Bytes(v) => Some(*v as f64),
+            Self::F64(v) if v.is_finite() => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Whether this value is [`TriggerValue::Invalid`] or a non-finite `F64`.
+    pub fn is_invalid(&self) -> bool {
+        match self {
+            Self::Invalid => true,
+            Self::F64(v) => !v.is_finite(),
+            _ => false,
+        }
+    }
+}
+
+/// Equality with defined semantics for non-finite floats.
+///
+/// Every non-finite `F64` is treated as [`TriggerValue::Invalid`], so
+/// `F64(NaN) == F64(NaN)`, `F64(NaN) == Invalid`, and `F64(∞) == Invalid`.
+/// This keeps equality reflexive and consistent with serialization.
+/// Finite floats compare with ordinary `f64` equality.
+impl PartialEq for TriggerValue {
+    fn eq(&self, other: &Self) -> bool {
+        if self.is_invalid() || other.is_invalid() {
+            return self.is_invalid() && other.is_invalid();
+        }
+        match (self, other) {
+            (Self::U64(a), Self::U64(b)) => a == b,
+            (Self::F64(a), Self::F64(b)) => a == b,
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::DurationSecs(a), Self::DurationSecs(b)) => a == b,
+            (Self::Bytes(a), Self::Bytes(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Serializes like the derived impl, except non-finite `F64` values are
+/// emitted as `Invalid` so the output is always valid JSON.
+impl Serialize for TriggerValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        const NAME: &str = "TriggerValue";
+        match self {
+            Self::U64(v) => serializer.serialize_newtype_variant(NAME, 0, "U64", v),
+            Self::F64(v) if v.is_finite() => {
+                serializer.serialize_newtype_variant(NAME, 1, "F64", v)
+            }
+            Self::String(v) => serializer.serialize_newtype_variant(NAME, 2, "String", v),
+            Self::Bool(v) => serializer.serialize_newtype_variant(NAME, 3, "Bool", v),
+            Self::F64(_) | Self::Invalid => serializer.serialize_unit_variant(NAME, 4, "Invalid"),
+            Self::DurationSecs(v) => {
+                serializer.serialize_newtype_variant(NAME, 5, "DurationSecs", v)
+            }
+            Self::Bytes(v) => serializer.serialize_newtype_variant(NAME, 6, "Bytes", v),
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -139,6 +410,16 @@ pub trait Trigger: Send + Sync {
     /// [`TriggerState::Active`] with data if the condition is currently
     /// true, or [`TriggerState::Triggered`] if it has been sustained.
     async fn evaluate(&mut self) -> Result<TriggerState, TriggerError>;
+
+    /// Take the watched processes listed by the last
+    /// [`evaluate`](Trigger::evaluate), if the trigger reads a process list
+    /// and they have not been taken yet.
+    ///
+    /// Lets callers record process activity without listing processes
+    /// again. The default returns `None`.
+    fn take_watched_processes(&mut self) -> Option<Vec<ProcessInfo>> {
+        None
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -220,4 +501,186 @@ mod tests {
         assert_eq!(data.get("upload_bps"), Some(&TriggerValue::U64(512)));
         assert_eq!(data.get("nonexistent"), None);
     }
+
+    #[test]
+    fn typed_accessors_distinguish_missing_from_wrong_type() {
+        let mut data = TriggerData::new();
+        data.insert("download_bps", TriggerValue::U64(1024));
+        data.insert("cpu_percent", TriggerValue::f64(12.5));
+        data.insert("busy", TriggerValue::Bool(true));
+        data.insert("target_time", TriggerValue::String("22:00".into()));
+        data.insert("ratio", TriggerValue::Invalid);
+
+        assert_eq!(data.get_u64("download_bps"), Ok(1024));
+        assert_eq!(data.get_f64("cpu_percent"), Ok(12.5));
+        assert_eq!(data.get_bool("busy"), Ok(true));
+        assert_eq!(data.get_str("target_time"), Ok("22:00"));
+        assert_eq!(data.get_u64_or("upload_bps", 7), Ok(7));
+        assert_eq!(data.get_u64_or("download_bps", 7), Ok(1024));
+
+        assert_eq!(
+            data.get_u64("upload_bps"),
+            Err(TriggerDataError::Missing("upload_bps".into()))
+        );
+        let err = data.get_u64("cpu_percent").unwrap_err();
+        assert_eq!(
+            err,
+            TriggerDataError::WrongType {
+                key: "cpu_percent".into(),
+                expected: "u64",
+                found: "f64",
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "trigger data key cpu_percent holds f64, expected u64"
+        );
+        assert!(matches!(
+            data.get_f64("ratio"),
+            Err(TriggerDataError::WrongType {
+                found: "invalid",
+                ..
+            })
+        ));
+        assert!(data.get_u64_or("busy", 0).is_err());
+    }
+
+    #[test]
+    fn get_u64_or_coerces_numeric_values() {
+        let mut data = TriggerData::new();
+        data.insert("cpu_percent", TriggerValue::f64(12.6));
+        data.insert("idle_secs", TriggerValue::DurationSecs(90));
+        data.insert("total", TriggerValue::Bytes(4096));
+
+        assert_eq!(data.get_u64_or("cpu_percent", 7), Ok(13));
+        assert_eq!(data.get_u64_or("idle_secs", 7), Ok(90));
+        assert_eq!(data.get_u64_or("total", 7), Ok(4096));
+        assert_eq!(data.get_u64_or("missing", 7), Ok(7));
+    }
+
+    #[test]
+    fn f64_constructor_sanitizes_non_finite() {
+        assert_eq!(TriggerValue::f64(42.5), TriggerValue::F64(42.5));
+        assert!(matches!(TriggerValue::f64(f64::NAN), TriggerValue::Invalid));
+        assert!(matches!(
+            TriggerValue::f64(f64::INFINITY),
+            TriggerValue::Invalid
+        ));
+        assert!(matches!(
+            TriggerValue::f64(f64::NEG_INFINITY),
+            TriggerValue::Invalid
+        ));
+    }
+
+    #[test]
+    fn nan_equality_is_reflexive() {
+        let nan = TriggerValue::F64(f64::NAN);
+        assert_eq!(nan, nan.clone());
+        assert_eq!(nan, TriggerValue::Invalid);
+        assert_eq!(TriggerValue::F64(f64::INFINITY), TriggerValue::Invalid);
+        assert_ne!(nan, TriggerValue::F64(0.0));
+        assert_ne!(TriggerValue::Invalid, TriggerValue::U64(0));
+    }
+
+    #[test]
+    fn finite_values_round_trip() {
+        for value in [
+            TriggerValue::U64(7),
+            TriggerValue::f64(12.25),
+            TriggerValue::String("eth0".into()),
+            TriggerValue::Bool(true),
+            TriggerValue::Invalid,
+        ] {
+            let json = serde_json::to_string(&value).unwrap();
+            let back: TriggerValue = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, value, "round trip of {json}");
+        }
+    }
+
+    #[test]
+    fn non_finite_values_serialize_as_invalid() {
+        for raw in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let json = serde_json::to_string(&TriggerValue::F64(raw)).unwrap();
+            assert_eq!(json, "\"Invalid\"");
+            let back: TriggerValue = serde_json::from_str(&json).unwrap();
+            assert!(matches!(back, TriggerValue::Invalid));
+        }
+    }
+
+    #[test]
+    fn numeric_values_coerce() {
+        assert_eq!(TriggerValue::U64(7).as_u64(), Some(7));
+        assert_eq!(TriggerValue::Bytes(1024).as_u64(), Some(1024));
+        assert_eq!(TriggerValue::DurationSecs(30).as_f64(), Some(30.0));
+        assert_eq!(TriggerValue::f64(2.6).as_u64(), Some(3));
+        assert_eq!(TriggerValue::f64(2.5).as_f64(), Some(2.5));
+        assert_eq!(TriggerValue::f64(-1.0).as_u64(), None);
+        assert_eq!(TriggerValue::f64(1e30).as_u64(), None);
+        assert_eq!(TriggerValue::F64(f64::NAN).as_f64(), None);
+        assert_eq!(TriggerValue::Bool(true).as_u64(), None);
+        assert_eq!(TriggerValue::String("5".into()).as_f64(), None);
+
+        let mut data = TriggerData::new();
+        data.insert("speed", TriggerValue::f64(99.6));
+        data.insert("sent", TriggerValue::Bytes(512));
+        data.insert("label", TriggerValue::String("eth0".into()));
+        assert_eq!(data.get_as_u64("speed"), Ok(100));
+        assert_eq!(data.get_u64("sent"), Ok(512));
+        assert_eq!(data.get_as_f64("sent"), Ok(512.0));
+        assert_eq!(
+            data.get_as_u64("label"),
+            Err(TriggerDataError::WrongType {
+                key: "label".into(),
+                expected: "u64",
+                found: "string",
+            })
+        );
+    }
+
+    #[test]
+    fn new_variants_serialize_with_explicit_tags() {
+        for (value, json) in [
+            (TriggerValue::DurationSecs(90), r#"{"DurationSecs":90}"#),
+            (TriggerValue::Bytes(4096), r#"{"Bytes":4096}"#),
+        ] {
+            assert_eq!(serde_json::to_string(&value).unwrap(), json);
+            let back: TriggerValue = serde_json::from_str(json).unwrap();
+            assert_eq!(back, value);
+        }
+        assert_ne!(TriggerValue::Bytes(1), TriggerValue::U64(1));
+    }
+
+    #[test]
+    fn previously_serialized_values_still_deserialize() {
+        let json = r#"{"values":{"a":{"U64":5},"b":{"F64":1.5},"c":{"String":"x"},"d":{"Bool":true},"e":"Invalid"}}"#;
+        let data: TriggerData = serde_json::from_str(json).unwrap();
+        assert_eq!(data.get("a"), Some(&TriggerValue::U64(5)));
+        assert_eq!(data.get("b"), Some(&TriggerValue::F64(1.5)));
+        assert_eq!(data.get("c"), Some(&TriggerValue::String("x".into())));
+        assert_eq!(data.get("d"), Some(&TriggerValue::Bool(true)));
+        assert_eq!(data.get("e"), Some(&TriggerValue::Invalid));
+    }
+
+    #[test]
+    fn trigger_data_flattens_to_plain_json() {
+        let mut data = TriggerData::new();
+        data.insert("download_bps", TriggerValue::U64(120));
+        data.insert("cpu_percent", TriggerValue::f64(12.5));
+        data.insert("busy", TriggerValue::Bool(false));
+        data.insert("ratio", TriggerValue::Invalid);
+        data.insert("sent", TriggerValue::Bytes(2048));
+        assert_eq!(
+            data.to_json().to_string(),
+            r#"{"busy":false,"cpu_percent":12.5,"download_bps":120,"ratio":null,"sent":2048}"#
+        );
+    }
+
+    #[test]
+    fn trigger_data_with_nan_serializes_to_valid_json() {
+        let mut data = TriggerData::new();
+        data.insert("cpu_percent", TriggerValue::F64(f64::NAN));
+        let json = serde_json::to_string(&data).unwrap();
+        let back: TriggerData = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, data);
+    }
 }
@@ -0,0 +1,377 @@
+//! Network idle trigger.
+//!
+//! `NetworkIdleTrigger` polls interface counters through a
+//! [`NetworkProvider`] with a [`SpeedMonitor`] and reports the speed as
+//! trigger data for `ThresholdCondition`.
+
+use crate::speed::{InterfaceSelection, SmoothingStrategy, SpeedMonitor};
+use crate::{Trigger, TriggerData, TriggerDescriptor, TriggerError, TriggerState, TriggerValue};
+use async_trait::async_trait;
+use flowwatcher_platform::network::{resolve_default_interface, InterfaceInfo, NetworkProvider};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+/// Which interface(s) a network trigger monitors: one id (including the
+/// special values "auto" and "all") or a list of ids.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum InterfaceChoice {
+    /// An interface id, "auto" or "all".
+    One(String),
+    /// Several interfaces whose speeds are summed.
+    Many(Vec<String>),
+}
+
+impl Default for InterfaceChoice {
+    fn default() -> Self {
+        Self::One("auto".to_string())
+    }
+}
+
+impl InterfaceChoice {
+    /// Whether the default interface should be picked automatically.
+    pub fn is_auto(&self) -> bool {
+        matches!(self, Self::One(id) if id == "auto")
+    }
+
+    /// Check explicitly chosen interfaces against what the OS reports.
+    ///
+    /// Unknown ids are rejected with a message listing the available ones.
+    /// Returns the chosen interfaces whose link is down, which can still
+    /// be monitored but will read as idle. "auto" and "all" pass as-is.
+    pub fn check(
+        &self,
+        provider: &dyn NetworkProvider,
+    ) -> Result<Vec<InterfaceInfo>, TriggerError> {
+        let ids: &[String] = match self {
+            Self::One(id) if id == "auto" || id == "all" => return Ok(Vec::new()),
+            Self::One(id) => std::slice::from_ref(id),
+            Self::Many(ids) => ids,
+        };
+        let available = provider
+            .list_interfaces()
+            .map_err(|e| TriggerError::StartFailed(e.to_string()))?;
+
+        let mut down = Vec::new();
+        for id in ids {
+            match available.iter().find(|info| &info.id == id) {
+                Some(info) if !info.is_up => down.push(info.clone()),
+                Some(_) => {}
+                None => {
+                    let names: Vec<&str> = available.iter().map(|i| i.id.as_str()).collect();
+                    return Err(TriggerError::InterfaceNotFound {
+                        id: id.clone(),
+                        available: if names.is_empty() {
+                            "none".to_string()
+                        } else {
+                            names.join(", ")
+                        },
+                    });
+                }
+            }
+        }
+        Ok(down)
+    }
+
+    /// Build a speed monitor for this choice.
+    ///
+    /// "auto" resolves to the interface active right now through
+    /// `provider` and is re-resolved if it disappears; an explicitly
+    /// chosen interface is not. "all" and lists sum their interfaces.
+    pub fn speed_monitor(
+        &self,
+        provider: &mut dyn NetworkProvider,
+        window_size: usize,
+    ) -> Result<SpeedMonitor, TriggerError> {
+        let monitor = match self {
+            Self::One(id) if id == "auto" => {
+                let interface_id = resolve_default_interface(provider)
+                    .map_err(|e| TriggerError::StartFailed(e.to_string()))?
+                    .ok_or_else(|| {
+                        TriggerError::StartFailed("no network interface found".to_string())
+                    })?;
+                SpeedMonitor::new(interface_id, window_size).with_interface_fallback(true)
+            }
+            Self::One(id) if id == "all" => {
+                SpeedMonitor::for_interfaces(InterfaceSelection::All, window_size)
+            }
+            Self::One(id) => SpeedMonitor::new(id.clone(), window_size),
+            Self::Many(ids) if ids.is_empty() => {
+                return Err(TriggerError::InvalidConfig(
+                    "no network interface selected".to_string(),
+                ));
+            }
+            Self::Many(ids) => {
+                SpeedMonitor::for_interfaces(InterfaceSelection::Ids(ids.clone()), window_size)
+            }
+        };
+        Ok(monitor)
+    }
+}
+
+/// JSON config of a [`NetworkIdleTrigger`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkIdleConfig {
+    /// Interface(s) to monitor (default "auto").
+    #[serde(default)]
+    pub interface_id: InterfaceChoice,
+    /// How speed readings are smoothed.
+    #[serde(default)]
+    pub smoothing: SmoothingStrategy,
+}
+
+// ---------------------------------------------------------------------------
+// NetworkIdleTrigger
+// ---------------------------------------------------------------------------
+
+/// A trigger that reports network speed for threshold evaluation.
+///
+/// Every evaluation polls the provider once. Once started and past the
+/// baseline, evaluation returns [`TriggerState::Active`] with:
+///
+/// - `download_bps` / `upload_bps` — the monitor's current speed, smoothed
+///   per its [`SmoothingStrategy`](crate::speed::SmoothingStrategy) (use
+///   `None` for raw readings).
+/// - `download_bytes_delta` / `upload_bytes_delta` — bytes transferred
+///   since the previous evaluation, unsmoothed.
+///
+/// Until a baseline exists — after `start()`, a counter reset, or a switch
+/// to a fallback interface — the trigger is [`TriggerState::Idle`].
+pub struct NetworkIdleTrigger {
+    /// Source of interface counters.
+    provider: Box<dyn NetworkProvider>,
+    /// Turns counters into (smoothed) speeds.
+    monitor: SpeedMonitor,
+    /// Whether the trigger has been started.
+    started: bool,
+}
+
+impl NetworkIdleTrigger {
+    /// Create a network trigger that polls `provider` through `monitor`.
+    pub fn new(provider: Box<dyn NetworkProvider>, monitor: SpeedMonitor) -> Self {
+        Self {
+            provider,
+            monitor,
+            started: false,
+        }
+    }
+
+    /// Registry metadata, including the config schema.
+    pub fn descriptor() -> TriggerDescriptor {
+        TriggerDescriptor::new(
+            "network_idle",
+            "Network Idle",
+            "Triggers when network speed falls below threshold",
+        )
+        .with_config_schema(json!({
+            "type": "object",
+            "properties": {
+                "interface_id": {
+                    "description": "An interface id, \"auto\", \"all\", or a list of ids to sum",
+                    "oneOf": [
+                        { "type": "string" },
+                        { "type": "array", "items": { "type": "string" }, "minItems": 1 }
+                    ],
+                    "default": "auto"
+                },
+                "smoothing": {
+                    "type": "object",
+                    "properties": {
+                        "type": {
+                            "enum": ["simple_moving_average", "exponential_moving_average", "none"]
+                        },
+                        "window": { "type": "integer", "minimum": 1 },
+                        "alpha": { "type": "number", "minimum": 0, "maximum": 1 }
+                    }
+                }
+            }
+        }))
+    }
+
+    /// Build from a JSON [`NetworkIdleConfig`] (for the trigger registry),
+    /// resolving "auto" through `provider`.
+    pub fn from_config(
+        config: &serde_json::Value,
+        mut provider: Box<dyn NetworkProvider>,
+    ) -> Result<Self, TriggerError> {
+        let config: NetworkIdleConfig = serde_json::from_value(config.clone())
+            .map_err(|e| TriggerError::InvalidConfig(format!("network_idle: {e}")))?;
+        let monitor = config
+            .interface_id
+            .speed_monitor(provider.as_mut(), 3)?
+            .with_smoothing(config.smoothing);
+        Ok(Self::new(provider, monitor))
+    }
+
+    /// The speed monitor, e.g. for its history or per-interface breakdown.
+    pub fn monitor(&self) -> &SpeedMonitor {
+        &self.monitor
+    }
+
+    /// Trigger data for the monitor's current speed.
+    fn trigger_data(&self) -> TriggerData {
+        let mut data = TriggerData::new();
+        data.insert(
+            "download_bps",
+            TriggerValue::U64(self.monitor.current_download_speed()),
+        );
+        data.insert(
+            "upload_bps",
+            TriggerValue::U64(self.monitor.current_upload_speed()),
+        );
+        let delta = self.monitor.latest_delta().unwrap_or_default();
+        data.insert(
+            "download_bytes_delta",
+            TriggerValue::U64(delta.download_bytes),
+        );
+        data.insert("upload_bytes_delta", TriggerValue::U64(delta.upload_bytes));
+        data
+    }
+}
+
+#[async_trait]
+impl Trigger for NetworkIdleTrigger {
+    fn name(&self) -> &str {
+        "Network Idle"
+    }
+
+    fn trigger_type(&self) -> &str {
+        "network_idle"
+    }
+
+    /// Start monitoring. Takes the baseline snapshot; speed is available
+    /// from the next evaluation.
+    async fn start(&mut self) -> Result<(), TriggerError> {
+        self.monitor.reset();
+        self.monitor
+            .poll(self.provider.as_mut())
+            .map_err(|e| TriggerError::StartFailed(e.to_string()))?;
+        self.started = true;
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<(), TriggerError> {
+        self.started = false;
+        self.monitor.reset();
+        Ok(())
+    }
+
+    async fn evaluate(&mut self) -> Result<TriggerState, TriggerError> {
+        if !self.started {
+            return Ok(TriggerState::Idle);
+        }
+        let reading = self
+            .monitor
+            .poll(self.provider.as_mut())
+            .map_err(|e| TriggerError::EvaluationError(e.to_string()))?;
+        Ok(match reading {
+            Some(_) => TriggerState::Active(self.trigger_data()),
+            None => TriggerState::Idle,
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::speed::SmoothingStrategy;
+    use flowwatcher_testing::{ScriptedNetworkProvider, MOCK_INTERFACE_ID};
+
+    fn trigger(speeds: &[(u64, u64)]) -> NetworkIdleTrigger {
+        NetworkIdleTrigger::new(
+            Box::new(ScriptedNetworkProvider::from_speeds(speeds)),
+            SpeedMonitor::new(MOCK_INTERFACE_ID, 3).with_smoothing(SmoothingStrategy::None),
+        )
+    }
+
+    fn active(state: TriggerState) -> TriggerData {
+        match state {
+            TriggerState::Active(data) => data,
+            other => panic!("expected Active, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_speed_after_baseline() {
+        let mut trigger = trigger(&[(2048, 512), (1024, 0)]);
+        assert_eq!(trigger.trigger_type(), "network_idle");
+        trigger.start().await.unwrap();
+
+        let data = active(trigger.evaluate().await.unwrap());
+        assert_eq!(data.get("download_bps"), Some(&TriggerValue::U64(2048)));
+        assert_eq!(data.get("upload_bps"), Some(&TriggerValue::U64(512)));
+        assert_eq!(
+            data.get("download_bytes_delta"),
+            Some(&TriggerValue::U64(2048))
+        );
+        assert_eq!(
+            data.get("upload_bytes_delta"),
+            Some(&TriggerValue::U64(512))
+        );
+
+        let data = active(trigger.evaluate().await.unwrap());
+        assert_eq!(data.get("download_bps"), Some(&TriggerValue::U64(1024)));
+        assert!(trigger.monitor().latest_reading().is_some());
+    }
+
+    #[tokio::test]
+    async fn idle_until_started_and_after_stop() {
+        let mut trigger = trigger(&[(100, 0), (100, 0)]);
+        assert_eq!(trigger.evaluate().await.unwrap(), TriggerState::Idle);
+
+        trigger.start().await.unwrap();
+        assert!(matches!(
+            trigger.evaluate().await.unwrap(),
+            TriggerState::Active(_)
+        ));
+
+        trigger.stop().await.unwrap();
+        assert_eq!(trigger.evaluate().await.unwrap(), TriggerState::Idle);
+    }
+
+    #[test]
+    fn check_rejects_unknown_and_reports_down_interfaces() {
+        let provider = ScriptedNetworkProvider::new(vec![(0, 0)]);
+        let unknown = InterfaceChoice::Many(vec![MOCK_INTERFACE_ID.into(), "eth9".into()]);
+        match unknown.check(&provider) {
+            Err(e @ TriggerError::InterfaceNotFound { .. }) => {
+                assert_eq!(
+                    e.to_string(),
+                    "unknown network interface `eth9`; available: mock0"
+                )
+            }
+            other => panic!("expected InterfaceNotFound, got {other:?}"),
+        }
+
+        let chosen = InterfaceChoice::One(MOCK_INTERFACE_ID.into());
+        assert!(chosen.check(&provider).unwrap().is_empty());
+        let down = chosen
+            .check(&ScriptedNetworkProvider::new(vec![(0, 0)]).with_link_down())
+            .unwrap();
+        assert_eq!(down.len(), 1);
+        assert!(InterfaceChoice::default()
+            .check(&provider)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn missing_interface_fails_to_start() {
+        let mut trigger = NetworkIdleTrigger::new(
+            Box::new(ScriptedNetworkProvider::new(vec![(0, 0)])),
+            SpeedMonitor::new("eth9", 3),
+        );
+        assert!(matches!(
+            trigger.start().await,
+            Err(TriggerError::StartFailed(_))
+        ));
+    }
+}
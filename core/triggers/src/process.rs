@@ -5,12 +5,88 @@
 //! were needed to add this trigger type.
 //!
 //! It monitors a set of user-selected processes and evaluates whether
-//! ALL of them have low network/disk activity.
+//! ALL of them have low network/disk activity. Processes are selected by
+//! [`ProcessMatch`] specs — name, path substring, or PID — so one of
+//! several same-named processes can be watched or excluded.
+//!
+//! Activity is a rate: each process's [`ProcessInfo::activity_bps`] is
+//! bytes per second since the provider's previous refresh, so the
+//! threshold is compared against current throughput rather than a total
+//! that grows for as long as the process runs.
+//!
+//! [`Trigger::evaluate`] lists processes through a [`SharedProcessProvider`]
+//! — shared so the process picker and the trigger read one provider,
+//! whose minimum refresh interval then coalesces their refreshes. Without
+//! a provider the trigger stays `Idle`. The watched processes of each
+//! listing can be taken afterwards with
+//! [`Trigger::take_watched_processes`], so recording their activity does
+//! not list processes a second time.
 
-use crate::{Trigger, TriggerData, TriggerError, TriggerState, TriggerValue};
+use crate::{Trigger, TriggerData, TriggerDescriptor, TriggerError, TriggerState, TriggerValue};
 use async_trait::async_trait;
-use flowwatcher_platform::process::ProcessInfo;
-use std::collections::HashSet;
+use flowwatcher_platform::process::{ProcessInfo, ProcessProvider};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// A process provider shared between the trigger and other readers.
+pub type SharedProcessProvider = Arc<Mutex<Box<dyn ProcessProvider>>>;
+
+// ---------------------------------------------------------------------------
+// Process matching
+// ---------------------------------------------------------------------------
+
+/// Selects processes for the watch or exclusion list.
+///
+/// Serialized as `{ "kind": "name" | "path_contains" | "pid", "value": … }`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+pub enum ProcessMatch {
+    /// Executable name, case-insensitive (e.g. "node.exe").
+    Name(String),
+    /// Case-insensitive substring of the executable's full path (e.g.
+    /// `\tools\node`); processes without a known path never match.
+    PathContains(String),
+    /// One specific process instance.
+    Pid(u32),
+}
+
+impl ProcessMatch {
+    /// Whether `process` is selected by this spec.
+    pub fn matches(&self, process: &ProcessInfo) -> bool {
+        match self {
+            Self::Name(name) => process.name.eq_ignore_ascii_case(name),
+            Self::PathContains(part) => process
+                .path
+                .as_ref()
+                .is_some_and(|path| path.to_lowercase().contains(&part.to_lowercase())),
+            Self::Pid(pid) => process.pid == *pid,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Config
+// ---------------------------------------------------------------------------
+
+/// JSON config of a [`ProcessTrigger`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessIdleConfig {
+    /// Process names to monitor.
+    pub watched_processes: Vec<String>,
+    /// Process names to always ignore.
+    #[serde(default)]
+    pub excluded_processes: Vec<String>,
+    /// Further processes to monitor, by name, path, or PID.
+    #[serde(default)]
+    pub watched_matches: Vec<ProcessMatch>,
+    /// Further processes to ignore, by name, path, or PID.
+    #[serde(default)]
+    pub excluded_matches: Vec<ProcessMatch>,
+    /// Activity threshold in bytes per second.
+    pub threshold_bytes: u64,
+}
 
 // ---------------------------------------------------------------------------
 // ProcessTrigger
@@ -24,49 +100,114 @@ use std::collections::HashSet;
 /// without modifying the engine, the `Trigger` trait, or any existing
 /// trigger code — exactly as the Strategic Shift mandated.
 pub struct ProcessTrigger {
-    /// Processes to monitor (by name, case-insensitive).
-    watched_names: HashSet<String>,
-    /// Processes to always ignore (by name, case-insensitive).
-    excluded_names: HashSet<String>,
-    /// Activity threshold in bytes — processes below this are "idle".
+    /// Processes to monitor.
+    watched: Vec<ProcessMatch>,
+    /// Processes to always ignore, even if watched.
+    excluded: Vec<ProcessMatch>,
+    /// Activity threshold in bytes per second — processes below this are
+    /// "idle".
     threshold_bytes: u64,
     /// Whether the trigger has been started.
     started: bool,
+    /// Source of the process list for [`Trigger::evaluate`].
+    provider: Option<SharedProcessProvider>,
+    /// Watched processes of the last listing, until taken.
+    last_watched: Option<Vec<ProcessInfo>>,
 }
 
 impl ProcessTrigger {
-    /// Create a new process trigger.
+    /// Create a new process trigger that matches processes by name.
     ///
     /// # Arguments
     /// * `watched_names` — Process names to monitor (e.g., "steam.exe").
     /// * `excluded_names` — Process names to always ignore.
-    /// * `threshold_bytes` — Activity below this is considered "idle".
+    /// * `threshold_bytes` — Bytes per second below which a process is
+    ///   considered "idle".
     pub fn new(
         watched_names: Vec<String>,
         excluded_names: Vec<String>,
         threshold_bytes: u64,
+    ) -> Self {
+        Self::with_matches(
+            watched_names.into_iter().map(ProcessMatch::Name).collect(),
+            excluded_names.into_iter().map(ProcessMatch::Name).collect(),
+            threshold_bytes,
+        )
+    }
+
+    /// Create a new process trigger from match specs. A process is watched
+    /// if any `watched` spec matches it and no `excluded` spec does.
+    pub fn with_matches(
+        watched: Vec<ProcessMatch>,
+        excluded: Vec<ProcessMatch>,
+        threshold_bytes: u64,
     ) -> Self {
         Self {
-            watched_names: watched_names
-                .into_iter()
-                .map(|n| n.to_lowercase())
-                .collect(),
-            excluded_names: excluded_names
-                .into_iter()
-                .map(|n| n.to_lowercase())
-                .collect(),
+            watched,
+            excluded,
             threshold_bytes,
             started: false,
+            provider: None,
+            last_watched: None,
         }
     }
 
+    /// Read processes from `provider` when evaluated.
+    pub fn with_provider(mut self, provider: SharedProcessProvider) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Registry metadata, including the config schema.
+    pub fn descriptor() -> TriggerDescriptor {
+        TriggerDescriptor::new(
+            "process_idle",
+            "Process Monitor",
+            "Triggers when selected processes have low network activity",
+        )
+        .with_config_schema(json!({
+            "type": "object",
+            "properties": {
+                "watched_processes": { "type": "array", "items": { "type": "string" } },
+                "excluded_processes": { "type": "array", "items": { "type": "string" } },
+                "watched_matches": { "type": "array", "items": match_schema() },
+                "excluded_matches": { "type": "array", "items": match_schema() },
+                "threshold_bytes": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["watched_processes", "threshold_bytes"]
+        }))
+    }
+
+    /// Build from a JSON [`ProcessIdleConfig`] (for the trigger registry),
+    /// reading processes from `provider`.
+    pub fn from_config(
+        config: &serde_json::Value,
+        provider: SharedProcessProvider,
+    ) -> Result<Self, TriggerError> {
+        let config: ProcessIdleConfig = serde_json::from_value(config.clone())
+            .map_err(|e| TriggerError::InvalidConfig(format!("process_idle: {e}")))?;
+        let watched = config
+            .watched_processes
+            .into_iter()
+            .map(ProcessMatch::Name)
+            .chain(config.watched_matches)
+            .collect();
+        let excluded = config
+            .excluded_processes
+            .into_iter()
+            .map(ProcessMatch::Name)
+            .chain(config.excluded_matches)
+            .collect();
+        Ok(Self::with_matches(watched, excluded, config.threshold_bytes).with_provider(provider))
+    }
+
     /// Filter processes to only those being watched, excluding ignored ones.
-    fn filter_processes(&self, processes: &[ProcessInfo]) -> Vec<ProcessInfo> {
+    pub fn filter_processes(&self, processes: &[ProcessInfo]) -> Vec<ProcessInfo> {
         processes
             .iter()
             .filter(|p| {
-                let name = p.name.to_lowercase();
-                self.watched_names.contains(&name) && !self.excluded_names.contains(&name)
+                self.watched.iter().any(|m| m.matches(p))
+                    && !self.excluded.iter().any(|m| m.matches(p))
             })
             .cloned()
             .collect()
@@ -80,7 +221,7 @@ impl ProcessTrigger {
         }
         filtered
             .iter()
-            .all(|p| p.estimated_network_bytes < self.threshold_bytes)
+            .all(|p| p.activity_bps < self.threshold_bytes)
     }
 
     /// Evaluate the trigger using a provided process list (for testability).
@@ -89,16 +230,20 @@ impl ProcessTrigger {
         processes: &[ProcessInfo],
     ) -> Result<TriggerState, TriggerError> {
         let filtered = self.filter_processes(processes);
-        let total_activity: u64 = filtered.iter().map(|p| p.estimated_network_bytes).sum();
+        let total_activity: u64 = filtered.iter().map(|p| p.activity_bps).sum();
         let active_count = filtered
             .iter()
-            .filter(|p| p.estimated_network_bytes >= self.threshold_bytes)
+            .filter(|p| p.activity_bps >= self.threshold_bytes)
             .count();
 
         let mut data = TriggerData::new();
         data.insert("watched_count", TriggerValue::U64(filtered.len() as u64));
         data.insert("active_count", TriggerValue::U64(active_count as u64));
         data.insert("total_activity_bytes", TriggerValue::U64(total_activity));
+        let mut pids: Vec<u32> = filtered.iter().map(|p| p.pid).collect();
+        pids.sort_unstable();
+        let pids: Vec<String> = pids.iter().map(u32::to_string).collect();
+        data.insert("matched_pids", TriggerValue::String(pids.join(",")));
 
         if self.all_below_threshold(&filtered) {
             // All processes are idle — trigger is active (ready for condition evaluation).
@@ -111,6 +256,18 @@ impl ProcessTrigger {
     }
 }
 
+/// JSON Schema of one [`ProcessMatch`].
+fn match_schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "kind": { "enum": ["name", "path_contains", "pid"] },
+            "value": { "type": ["string", "integer"] }
+        },
+        "required": ["kind", "value"]
+    })
+}
+
 #[async_trait]
 impl Trigger for ProcessTrigger {
     fn name(&self) -> &str {
@@ -128,18 +285,25 @@ impl Trigger for ProcessTrigger {
 
     async fn stop(&mut self) -> Result<(), TriggerError> {
         self.started = false;
+        self.last_watched = None;
         Ok(())
     }
 
     async fn evaluate(&mut self) -> Result<TriggerState, TriggerError> {
-        if !self.started {
+        let Some(provider) = self.provider.as_ref().filter(|_| self.started) else {
             return Ok(TriggerState::Idle);
-        }
+        };
+        let processes = provider
+            .lock()
+            .await
+            .list_processes()
+            .map_err(|e| TriggerError::EvaluationError(e.to_string()))?;
+        self.last_watched = Some(self.filter_processes(&processes));
+        self.evaluate_with_processes(&processes)
+    }
 
-        // In real usage, this would use a ProcessProvider. For now,
-        // the Tauri integration layer (Phase 4) will inject the provider.
-        // The evaluate_with_processes() method is the testable core.
-        Ok(TriggerState::Idle)
+    fn take_watched_processes(&mut self) -> Option<Vec<ProcessInfo>> {
+        self.last_watched.take()
     }
 }
 
@@ -150,38 +314,10 @@ impl Trigger for ProcessTrigger {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use flowwatcher_testing::{process, sample_processes, ScriptedProcessProvider};
 
-    fn mock_processes() -> Vec<ProcessInfo> {
-        vec![
-            ProcessInfo {
-                pid: 1,
-                name: "steam.exe".to_string(),
-                path: Some("C:\\Steam\\steam.exe".to_string()),
-                estimated_network_bytes: 50_000, // high activity
-                is_suggested: true,
-            },
-            ProcessInfo {
-                pid: 2,
-                name: "chrome.exe".to_string(),
-                path: Some("C:\\Chrome\\chrome.exe".to_string()),
-                estimated_network_bytes: 100, // low
-                is_suggested: false,
-            },
-            ProcessInfo {
-                pid: 3,
-                name: "explorer.exe".to_string(),
-                path: Some("C:\\Windows\\explorer.exe".to_string()),
-                estimated_network_bytes: 0,
-                is_suggested: false,
-            },
-            ProcessInfo {
-                pid: 4,
-                name: "svchost.exe".to_string(),
-                path: Some("C:\\Windows\\svchost.exe".to_string()),
-                estimated_network_bytes: 30_000,
-                is_suggested: false,
-            },
-        ]
+    fn shared(provider: ScriptedProcessProvider) -> SharedProcessProvider {
+        Arc::new(Mutex::new(Box::new(provider)))
     }
 
     #[test]
@@ -191,7 +327,9 @@ mod tests {
             vec![],
             1000, // threshold
         );
-        let result = trigger.evaluate_with_processes(&mock_processes()).unwrap();
+        let result = trigger
+            .evaluate_with_processes(&sample_processes())
+            .unwrap();
         assert!(
             matches!(result, TriggerState::Active(_)),
             "should fire when all watched processes are below threshold"
@@ -205,7 +343,9 @@ mod tests {
             vec![],
             1000, // steam.exe at 50000 > 1000
         );
-        let result = trigger.evaluate_with_processes(&mock_processes()).unwrap();
+        let result = trigger
+            .evaluate_with_processes(&sample_processes())
+            .unwrap();
         assert_eq!(
             result,
             TriggerState::Idle,
@@ -226,7 +366,9 @@ mod tests {
         );
         // With steam.exe excluded, only chrome (100) and svchost (30000) are watched.
         // svchost is above threshold → Idle.
-        let result = trigger.evaluate_with_processes(&mock_processes()).unwrap();
+        let result = trigger
+            .evaluate_with_processes(&sample_processes())
+            .unwrap();
         assert_eq!(result, TriggerState::Idle);
     }
 
@@ -238,7 +380,9 @@ mod tests {
             1000,
         );
         // Only chrome (100 < 1000) remains → Active.
-        let result = trigger.evaluate_with_processes(&mock_processes()).unwrap();
+        let result = trigger
+            .evaluate_with_processes(&sample_processes())
+            .unwrap();
         assert!(matches!(result, TriggerState::Active(_)));
     }
 
@@ -246,14 +390,18 @@ mod tests {
     fn empty_watched_list_is_idle() {
         let trigger = ProcessTrigger::new(vec![], vec![], 1000);
         // No processes watched → considered idle → Active.
-        let result = trigger.evaluate_with_processes(&mock_processes()).unwrap();
+        let result = trigger
+            .evaluate_with_processes(&sample_processes())
+            .unwrap();
         assert!(matches!(result, TriggerState::Active(_)));
     }
 
     #[test]
     fn trigger_data_contains_metrics() {
         let trigger = ProcessTrigger::new(vec!["chrome.exe".to_string()], vec![], 1000);
-        let result = trigger.evaluate_with_processes(&mock_processes()).unwrap();
+        let result = trigger
+            .evaluate_with_processes(&sample_processes())
+            .unwrap();
         if let TriggerState::Active(data) = result {
             assert_eq!(data.get("watched_count"), Some(&TriggerValue::U64(1)));
             assert_eq!(data.get("active_count"), Some(&TriggerValue::U64(0)));
@@ -262,6 +410,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn threshold_is_a_rate() {
+        let trigger = ProcessTrigger::new(vec!["steam.exe".to_string()], vec![], 1000);
+        let mut steam = sample_processes()
+            .into_iter()
+            .find(|p| p.name == "steam.exe")
+            .unwrap();
+
+        // 999 B/s is below a 1000 B/s threshold however long it lasts.
+        steam.activity_bps = 999;
+        let result = trigger.evaluate_with_processes(&[steam.clone()]).unwrap();
+        assert!(matches!(result, TriggerState::Active(_)));
+
+        // Reaching the threshold rate counts as active.
+        steam.activity_bps = 1000;
+        let result = trigger.evaluate_with_processes(&[steam]).unwrap();
+        assert_eq!(result, TriggerState::Idle);
+    }
+
     #[test]
     fn case_insensitive_matching() {
         let trigger = ProcessTrigger::new(
@@ -269,10 +436,125 @@ mod tests {
             vec![],
             1000,
         );
-        let result = trigger.evaluate_with_processes(&mock_processes()).unwrap();
+        let result = trigger
+            .evaluate_with_processes(&sample_processes())
+            .unwrap();
         assert!(
             matches!(result, TriggerState::Active(_)),
             "should match case-insensitively"
         );
     }
+
+    /// Two node.exe instances from different installs.
+    fn node_processes() -> Vec<ProcessInfo> {
+        let mut tools = process(7, "node.exe", 80_000);
+        tools.path = Some("C:\\Tools\\node.exe".to_string());
+        let mut app = process(8, "node.exe", 10);
+        app.path = Some("C:\\App\\node.exe".to_string());
+        vec![tools, app, process(9, "steam.exe", 0)]
+    }
+
+    #[test]
+    fn matches_by_path_and_pid() {
+        let processes = node_processes();
+        let by_path = ProcessTrigger::with_matches(
+            vec![ProcessMatch::PathContains("\\app\\".to_string())],
+            vec![],
+            1000,
+        );
+        let pids: Vec<u32> = by_path
+            .filter_processes(&processes)
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        assert_eq!(pids, [8]);
+        assert!(matches!(
+            by_path.evaluate_with_processes(&processes).unwrap(),
+            TriggerState::Active(_)
+        ));
+
+        let by_pid = ProcessTrigger::with_matches(vec![ProcessMatch::Pid(7)], vec![], 1000);
+        assert_eq!(
+            by_pid.evaluate_with_processes(&processes).unwrap(),
+            TriggerState::Idle
+        );
+    }
+
+    #[test]
+    fn exclusions_use_the_same_specs() {
+        let trigger = ProcessTrigger::with_matches(
+            vec![ProcessMatch::Name("NODE.EXE".to_string())],
+            vec![ProcessMatch::Pid(7)],
+            1000,
+        );
+        match trigger.evaluate_with_processes(&node_processes()).unwrap() {
+            TriggerState::Active(data) => assert_eq!(
+                data.get("matched_pids"),
+                Some(&TriggerValue::String("8".to_string()))
+            ),
+            other => panic!("expected Active, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn config_combines_names_and_specs() {
+        let provider = shared(ScriptedProcessProvider::fixed(node_processes()));
+        let config = json!({
+            "watched_processes": ["steam.exe"],
+            "watched_matches": [{ "kind": "path_contains", "value": "tools" }],
+            "excluded_matches": [{ "kind": "pid", "value": 9 }],
+            "threshold_bytes": 1000
+        });
+        let trigger = ProcessTrigger::from_config(&config, provider).unwrap();
+        let pids: Vec<u32> = trigger
+            .filter_processes(&node_processes())
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        assert_eq!(pids, [7]);
+    }
+
+    #[tokio::test]
+    async fn evaluate_reads_the_provider_once_started() {
+        let provider = shared(ScriptedProcessProvider::new(vec![
+            vec![process(1, "steam.exe", 50_000)],
+            vec![process(1, "steam.exe", 10)],
+        ]));
+        let mut trigger = ProcessTrigger::new(vec!["steam.exe".to_string()], vec![], 1000)
+            .with_provider(provider);
+
+        assert_eq!(trigger.evaluate().await.unwrap(), TriggerState::Idle);
+        trigger.start().await.unwrap();
+        assert_eq!(trigger.evaluate().await.unwrap(), TriggerState::Idle);
+        assert!(matches!(
+            trigger.evaluate().await.unwrap(),
+            TriggerState::Active(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn each_listing_can_be_taken_once() {
+        let provider = shared(ScriptedProcessProvider::new(vec![sample_processes()]));
+        let mut trigger = ProcessTrigger::new(vec!["steam.exe".to_string()], vec![], 1000)
+            .with_provider(provider);
+        trigger.start().await.unwrap();
+        assert!(trigger.take_watched_processes().is_none());
+
+        trigger.evaluate().await.unwrap();
+        let watched = trigger.take_watched_processes().unwrap();
+        let names: Vec<_> = watched.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["steam.exe"]);
+        assert!(trigger.take_watched_processes().is_none());
+
+        trigger.evaluate().await.unwrap();
+        trigger.stop().await.unwrap();
+        assert!(trigger.take_watched_processes().is_none());
+    }
+
+    #[tokio::test]
+    async fn without_a_provider_the_trigger_stays_idle() {
+        let mut trigger = ProcessTrigger::new(vec![], vec![], 1000);
+        trigger.start().await.unwrap();
+        assert_eq!(trigger.evaluate().await.unwrap(), TriggerState::Idle);
+    }
 }
@@ -0,0 +1,271 @@
+//! Trigger registry — construct triggers by type id.
+//!
+//! Factories are registered under a `trigger_type` string and build a
+//! boxed [`Trigger`] from a JSON config blob, so new trigger types can be
+//! plugged in without the engine knowing their concrete types.
+//! [`builtin_registry`] registers the triggers shipped with FlowWatcher.
+
+use crate::process::SharedProcessProvider;
+use crate::{
+    CpuIdleTrigger, NetworkIdleTrigger, ProcessTrigger, TimerTrigger, Trigger, TriggerError,
+};
+use flowwatcher_platform::cpu::CpuProvider;
+use flowwatcher_platform::network::NetworkProvider;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Factory closure that builds a trigger from its JSON config.
+pub type TriggerFactory =
+    Box<dyn Fn(&serde_json::Value) -> Result<Box<dyn Trigger>, TriggerError> + Send + Sync>;
+
+/// Metadata describing a registered trigger type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TriggerDescriptor {
+    /// Machine-readable type id (e.g., "network_idle").
+    pub id: String,
+    /// Human-readable display name.
+    pub name: String,
+    /// Description of what the trigger detects.
+    pub description: String,
+    /// JSON Schema of the config accepted by the factory (`null` if
+    /// undocumented).
+    #[serde(default)]
+    pub config_schema: serde_json::Value,
+}
+
+impl TriggerDescriptor {
+    /// Describe a trigger type without a config schema.
+    pub fn new(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            description: description.into(),
+            config_schema: serde_json::Value::Null,
+        }
+    }
+
+    /// Attach the JSON Schema of the trigger's config.
+    pub fn with_config_schema(mut self, schema: serde_json::Value) -> Self {
+        self.config_schema = schema;
+        self
+    }
+}
+
+/// A registered trigger type.
+struct Entry {
+    descriptor: TriggerDescriptor,
+    factory: TriggerFactory,
+}
+
+/// Maps trigger type ids to factories.
+#[derive(Default)]
+pub struct TriggerRegistry {
+    entries: BTreeMap<String, Entry>,
+}
+
+impl TriggerRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a trigger type. Replaces any existing factory with the same id.
+    pub fn register<F>(&mut self, descriptor: TriggerDescriptor, factory: F)
+    where
+        F: Fn(&serde_json::Value) -> Result<Box<dyn Trigger>, TriggerError> + Send + Sync + 'static,
+    {
+        self.entries.insert(
+            descriptor.id.clone(),
+            Entry {
+                descriptor,
+                factory: Box::new(factory),
+            },
+        );
+    }
+
+    /// Construct a trigger of the given type from its config.
+    pub fn create(
+        &self,
+        trigger_type: &str,
+        config: &serde_json::Value,
+    ) -> Result<Box<dyn Trigger>, TriggerError> {
+        let entry = self
+            .entries
+            .get(trigger_type)
+            .ok_or_else(|| TriggerError::UnknownType(trigger_type.to_string()))?;
+        (entry.factory)(config)
+    }
+
+    /// Whether a trigger type is registered.
+    pub fn contains(&self, trigger_type: &str) -> bool {
+        self.entries.contains_key(trigger_type)
+    }
+
+    /// Descriptors of all registered trigger types, sorted by id.
+    pub fn descriptors(&self) -> Vec<TriggerDescriptor> {
+        self.entries
+            .values()
+            .map(|e| e.descriptor.clone())
+            .collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Built-in triggers
+// ---------------------------------------------------------------------------
+
+/// A registry with the built-in `network_idle`, `process_idle`, `cpu_idle`
+/// and `timer` triggers.
+///
+/// Each network or CPU trigger gets its own provider from `network` or
+/// `cpu`, since providers keep per-consumer snapshots. Process triggers
+/// all read the shared `process` provider.
+///
+/// The triggers report different data, so not every condition can follow
+/// every trigger: `network_idle` feeds `download_bps`/`upload_bps` (and
+/// byte deltas) to threshold and volume conditions, while `timer` carries
+/// no traffic data and schedules the action itself when it fires (see
+/// [`TimerTrigger`]).
+pub fn builtin_registry<N, C>(network: N, cpu: C, process: SharedProcessProvider) -> TriggerRegistry
+where
+    N: Fn() -> Box<dyn NetworkProvider> + Send + Sync + 'static,
+    C: Fn() -> Box<dyn CpuProvider> + Send + Sync + 'static,
+{
+    let mut registry = TriggerRegistry::new();
+    registry.register(NetworkIdleTrigger::descriptor(), move |config| {
+        Ok(Box::new(NetworkIdleTrigger::from_config(
+            config,
+            network(),
+        )?))
+    });
+    registry.register(ProcessTrigger::descriptor(), move |config| {
+        Ok(Box::new(ProcessTrigger::from_config(
+            config,
+            process.clone(),
+        )?))
+    });
+    registry.register(CpuIdleTrigger::descriptor(), move |config| {
+        Ok(Box::new(CpuIdleTrigger::from_config(config, cpu())?))
+    });
+    registry.register(TimerTrigger::descriptor(), |config| {
+        Ok(Box::new(TimerTrigger::from_config(config)?))
+    });
+    registry
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_testing::{
+        cpu_usage, sample_processes, ScriptedCpuProvider, ScriptedNetworkProvider,
+        ScriptedProcessProvider,
+    };
+    use serde_json::json;
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
+
+    fn descriptor(id: &str) -> TriggerDescriptor {
+        TriggerDescriptor::new(id, id, "")
+    }
+
+    fn builtin() -> TriggerRegistry {
+        builtin_registry(
+            || Box::new(ScriptedNetworkProvider::new(vec![(0, 0)])),
+            || Box::new(ScriptedCpuProvider::new(vec![cpu_usage(&[0.0])])),
+            Arc::new(Mutex::new(Box::new(ScriptedProcessProvider::new(vec![
+                sample_processes(),
+            ])))),
+        )
+    }
+
+    fn registry() -> TriggerRegistry {
+        let mut registry = TriggerRegistry::new();
+        registry.register(descriptor("process_idle"), |_| {
+            Ok(Box::new(ProcessTrigger::new(vec![], vec![], 1000)))
+        });
+        registry
+    }
+
+    #[test]
+    fn creates_registered_trigger() {
+        let trigger = registry()
+            .create("process_idle", &serde_json::Value::Null)
+            .expect("should create");
+        assert_eq!(trigger.trigger_type(), "process_idle");
+    }
+
+    #[test]
+    fn unknown_type_is_an_error() {
+        let result = registry().create("nope", &serde_json::Value::Null);
+        assert!(matches!(result, Err(TriggerError::UnknownType(t)) if t == "nope"));
+    }
+
+    #[test]
+    fn descriptors_list_registered_types() {
+        let registry = registry();
+        assert!(registry.contains("process_idle"));
+        assert_eq!(registry.descriptors(), vec![descriptor("process_idle")]);
+    }
+
+    #[test]
+    fn builtin_registry_creates_every_builtin_type() {
+        let registry = builtin();
+        let configs = [
+            ("network_idle", json!({ "interface_id": "auto" })),
+            (
+                "process_idle",
+                json!({ "watched_processes": ["steam.exe"], "threshold_bytes": 1000 }),
+            ),
+            ("cpu_idle", json!({ "mode": "per_core" })),
+            ("timer", json!({ "in_secs": 60 })),
+        ];
+        for (trigger_type, config) in configs {
+            let trigger = registry.create(trigger_type, &config).expect(trigger_type);
+            assert_eq!(trigger.trigger_type(), trigger_type);
+        }
+    }
+
+    #[test]
+    fn builtin_descriptors_carry_config_schemas() {
+        let descriptors = builtin().descriptors();
+        let ids: Vec<_> = descriptors.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec!["cpu_idle", "network_idle", "process_idle", "timer"]
+        );
+        for descriptor in &descriptors {
+            assert_eq!(
+                descriptor.config_schema["type"], "object",
+                "{}",
+                descriptor.id
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_builtin_config_is_an_error() {
+        let registry = builtin();
+        for (trigger_type, config) in [
+            ("process_idle", json!({ "watched_processes": "steam.exe" })),
+            ("cpu_idle", json!({ "mode": "turbo" })),
+            ("network_idle", json!({ "interface_id": [] })),
+            ("timer", json!({ "in_minutes": 5 })),
+        ] {
+            assert!(
+                matches!(
+                    registry.create(trigger_type, &config),
+                    Err(TriggerError::InvalidConfig(_))
+                ),
+                "{trigger_type}"
+            );
+        }
+    }
+}
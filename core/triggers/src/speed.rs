@@ -0,0 +1,1037 @@
+//! Speed monitoring with delta calculation and rolling average smoothing.
+//!
+//! A counter that goes backwards (an adapter reconnecting, e.g. a VPN)
+//! starts a new baseline instead of producing a bogus 0 B/s reading
+//! followed by a spike.
+
+use flowwatcher_platform::network::{
+    pick_default_interface, resolve_default_interface, summarize_traffic, NetworkError,
+    NetworkProvider, NetworkStats, NetworkStatsSnapshot, TrafficSnapshot,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Longest speed history a [`SpeedMonitor`] keeps for charting.
+pub const MAX_HISTORY_WINDOW: Duration = Duration::from_secs(30 * 60);
+
+// ---------------------------------------------------------------------------
+// Error types
+// ---------------------------------------------------------------------------
+
+/// Errors from the speed monitor.
+#[derive(Debug, Error)]
+pub enum SpeedError {
+    /// No previous snapshot exists to calculate a delta.
+    #[error("no previous snapshot — need at least two polls to calculate speed")]
+    NoPreviousSnapshot,
+
+    /// The underlying network provider returned an error.
+    #[error("network provider error: {0}")]
+    NetworkError(#[from] flowwatcher_platform::network::NetworkError),
+}
+
+// ---------------------------------------------------------------------------
+// Speed snapshot
+// ---------------------------------------------------------------------------
+
+/// A calculated speed reading for one poll interval.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeedReading {
+    /// Download speed in bytes per second.
+    pub download_bps: u64,
+    /// Upload speed in bytes per second.
+    pub upload_bps: u64,
+}
+
+/// Bytes transferred during one poll interval, summed over interfaces.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ByteDelta {
+    /// Bytes received.
+    pub download_bytes: u64,
+    /// Bytes sent.
+    pub upload_bytes: u64,
+}
+
+/// A raw reading with the time its counters were read, for charting.
+#[derive(Debug, Clone)]
+pub struct TimestampedReading {
+    /// When the reading was taken.
+    pub at: Instant,
+    /// The (summed) speed reading.
+    pub reading: SpeedReading,
+}
+
+// ---------------------------------------------------------------------------
+// Smoothing
+// ---------------------------------------------------------------------------
+
+/// How [`SpeedMonitor`] smooths raw readings into the current speed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SmoothingStrategy {
+    /// Plain average of the last `window` readings.
+    SimpleMovingAverage {
+        /// Number of readings averaged.
+        window: usize,
+    },
+    /// Each reading moves the average by `alpha` of the difference
+    /// (clamped to 0–1; higher reacts faster).
+    ExponentialMovingAverage {
+        /// Weight of the newest reading.
+        alpha: f64,
+    },
+    /// The latest raw reading.
+    None,
+}
+
+impl Default for SmoothingStrategy {
+    fn default() -> Self {
+        Self::SimpleMovingAverage { window: 3 }
+    }
+}
+
+impl SmoothingStrategy {
+    /// Readings to keep in the history for this strategy.
+    fn history_len(&self) -> usize {
+        match self {
+            Self::SimpleMovingAverage { window } => (*window).max(1),
+            Self::ExponentialMovingAverage { .. } | Self::None => 1,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Interface selection
+// ---------------------------------------------------------------------------
+
+/// Which interfaces a [`SpeedMonitor`] sums.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterfaceSelection {
+    /// These interfaces (usually just one).
+    Ids(Vec<String>),
+    /// Every interface the provider lists, re-listed on each poll.
+    All,
+}
+
+impl fmt::Display for InterfaceSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ids(ids) => f.write_str(&ids.join(", ")),
+            Self::All => f.write_str("all"),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SpeedMonitor
+// ---------------------------------------------------------------------------
+
+/// Monitors network speed by polling stats and calculating deltas.
+///
+/// Uses a rolling average (configurable window size) to smooth out
+/// momentary spikes and prevent false triggers; see [`SmoothingStrategy`]
+/// for the alternatives.
+///
+/// With several interfaces (e.g. bonded Ethernet and Wi-Fi) the reading is
+/// the sum over every interface that has a baseline; the per-interface
+/// figures are kept in [`latest_breakdown`](Self::latest_breakdown). An
+/// interface that appears only gets its baseline on that poll, and one
+/// that disappears is dropped. Only when all of them are missing does the
+/// poll fail (or fall back, see
+/// [`with_interface_fallback`](Self::with_interface_fallback)).
+pub struct SpeedMonitor {
+    /// The network interfaces to monitor.
+    interfaces: InterfaceSelection,
+    /// Previous stats snapshot per interface for delta calculation.
+    last_stats: BTreeMap<String, NetworkStats>,
+    /// Rolling window of recent (summed) speed readings.
+    history: VecDeque<SpeedReading>,
+    /// How readings are smoothed.
+    smoothing: SmoothingStrategy,
+    /// Exponential moving average as `(download, upload)`, if one is kept.
+    ema: Option<(f64, f64)>,
+    /// How far back the charting history reaches (None = not kept).
+    history_window: Option<Duration>,
+    /// Timestamped readings within `history_window`, oldest first.
+    timeline: VecDeque<TimestampedReading>,
+    /// Per-interface readings from the latest poll that produced one.
+    breakdown: BTreeMap<String, SpeedReading>,
+    /// Bytes transferred over the interval of the latest reading.
+    last_delta: Option<ByteDelta>,
+    /// Whether to switch to the default interface when ours disappears.
+    interface_fallback: bool,
+    /// Number of counter regressions seen so far.
+    counter_resets: u64,
+}
+
+impl SpeedMonitor {
+    /// Create a new speed monitor for a specific interface.
+    ///
+    /// # Arguments
+    /// * `interface_id` — The network interface to monitor.
+    /// * `window_size` — Number of samples for rolling average smoothing (default: 3).
+    pub fn new(interface_id: impl Into<String>, window_size: usize) -> Self {
+        Self::for_interfaces(
+            InterfaceSelection::Ids(vec![interface_id.into()]),
+            window_size,
+        )
+    }
+
+    /// Create a speed monitor that sums several interfaces.
+    pub fn for_interfaces(interfaces: InterfaceSelection, window_size: usize) -> Self {
+        Self {
+            interfaces,
+            last_stats: BTreeMap::new(),
+            history: VecDeque::with_capacity(window_size),
+            smoothing: SmoothingStrategy::SimpleMovingAverage {
+                window: window_size,
+            },
+            ema: None,
+            history_window: None,
+            timeline: VecDeque::new(),
+            breakdown: BTreeMap::new(),
+            last_delta: None,
+            interface_fallback: false,
+            counter_resets: 0,
+        }
+    }
+
+    /// Smooth readings with `smoothing` instead of the rolling average
+    /// given to the constructor.
+    pub fn with_smoothing(mut self, smoothing: SmoothingStrategy) -> Self {
+        self.smoothing = smoothing;
+        self.history = VecDeque::with_capacity(smoothing.history_len());
+        self.ema = None;
+        self
+    }
+
+    /// Keep timestamped readings from the last `window` (capped at
+    /// [`MAX_HISTORY_WINDOW`]) for [`get_history`](Self::get_history).
+    ///
+    /// At most one reading per second of the window is kept, so polling
+    /// faster than once a second shortens the covered time instead of
+    /// growing memory.
+    pub fn with_history_window(mut self, window: Duration) -> Self {
+        self.history_window = Some(window.min(MAX_HISTORY_WINDOW));
+        self.timeline = VecDeque::new();
+        self
+    }
+
+    /// Timestamped readings newer than `since` (all with `None`), oldest
+    /// first. Empty unless a history window was configured.
+    pub fn get_history(&self, since: Option<Instant>) -> Vec<TimestampedReading> {
+        self.timeline
+            .iter()
+            .filter(|entry| since.is_none_or(|since| entry.at > since))
+            .cloned()
+            .collect()
+    }
+
+    /// Mean of the charting history over the `window` up to the newest
+    /// reading, or `None` without history.
+    pub fn average_over(&self, window: Duration) -> Option<SpeedReading> {
+        let newest = self.timeline.back()?.at;
+        let recent: Vec<&SpeedReading> = self
+            .timeline
+            .iter()
+            .filter(|entry| newest.saturating_duration_since(entry.at) < window)
+            .map(|entry| &entry.reading)
+            .collect();
+        let count = recent.len() as u64;
+        Some(SpeedReading {
+            download_bps: recent.iter().map(|r| r.download_bps).sum::<u64>() / count,
+            upload_bps: recent.iter().map(|r| r.upload_bps).sum::<u64>() / count,
+        })
+    }
+
+    /// Number of readings in the charting history.
+    pub fn history_len(&self) -> usize {
+        self.timeline.len()
+    }
+
+    /// Maximum number of readings the charting history holds.
+    pub fn history_capacity(&self) -> usize {
+        self.history_window
+            .map_or(0, |window| (window.as_secs() as usize).max(1))
+    }
+
+    /// The smoothing strategy in use.
+    pub fn smoothing(&self) -> SmoothingStrategy {
+        self.smoothing
+    }
+
+    /// When every monitored interface is no longer found, re-resolve the
+    /// default interface through the provider and continue with it.
+    pub fn with_interface_fallback(mut self, enabled: bool) -> Self {
+        self.interface_fallback = enabled;
+        self
+    }
+
+    /// Monitor `interfaces` from now on. Speed history is cleared and the
+    /// next poll establishes a new baseline, as on the first call.
+    pub fn switch_interfaces(&mut self, interfaces: InterfaceSelection) {
+        self.interfaces = interfaces;
+        self.last_stats.clear();
+        self.history.clear();
+        self.ema = None;
+        self.breakdown.clear();
+        self.last_delta = None;
+    }
+
+    /// The interfaces currently being monitored.
+    pub fn interfaces(&self) -> &InterfaceSelection {
+        &self.interfaces
+    }
+
+    /// The counters of the latest poll, which the next one is measured
+    /// against, in serializable form.
+    pub fn last_counters(&self) -> Vec<NetworkStatsSnapshot> {
+        self.last_stats
+            .iter()
+            .map(|(id, stats)| stats.to_snapshot(id.as_str()))
+            .collect()
+    }
+
+    /// How many times a counter went backwards and the baseline was
+    /// re-established.
+    pub fn counter_resets(&self) -> u64 {
+        self.counter_resets
+    }
+
+    /// Poll the network provider and calculate current speed.
+    ///
+    /// Must be called repeatedly at a fixed interval (e.g., every 1 second).
+    /// The first call establishes a baseline; speed is available from the
+    /// second call onward. After a counter reset or a switch to the
+    /// fallback interface the call returns `None` and history is cleared,
+    /// as on the first call.
+    pub fn poll(
+        &mut self,
+        provider: &mut dyn NetworkProvider,
+    ) -> Result<Option<SpeedReading>, SpeedError> {
+        let ids = match &self.interfaces {
+            InterfaceSelection::Ids(ids) => ids.clone(),
+            InterfaceSelection::All => provider
+                .list_interfaces()?
+                .into_iter()
+                .map(|info| info.id)
+                .collect(),
+        };
+
+        let mut snapshots = BTreeMap::new();
+        let mut missing = None;
+        for id in ids {
+            match provider.get_stats(&id) {
+                Ok(stats) => {
+                    snapshots.insert(id, stats);
+                }
+                Err(NetworkError::InterfaceNotFound(_)) => missing = Some(id),
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        if snapshots.is_empty() {
+            let id = missing.unwrap_or_else(|| self.interfaces.to_string());
+            if self.interface_fallback {
+                let fallback = resolve_default_interface(provider)?.filter(|f| *f != id);
+                if let Some(fallback) = fallback {
+                    let current = provider.get_stats(&fallback)?;
+                    self.fall_back_to(fallback, current);
+                    return Ok(None);
+                }
+            }
+            return Err(NetworkError::InterfaceNotFound(id).into());
+        }
+        Ok(self.update(snapshots))
+    }
+
+    /// Calculate the current speed from counters already read, e.g. by
+    /// [`NetworkProvider::snapshot_all`] on a blocking thread.
+    ///
+    /// Behaves like [`poll`](Self::poll), except that the fallback
+    /// interface is the one with the most traffic in `snapshot`.
+    pub fn poll_snapshot(
+        &mut self,
+        snapshot: &TrafficSnapshot,
+    ) -> Result<Option<SpeedReading>, SpeedError> {
+        let ids: Vec<String> = match &self.interfaces {
+            InterfaceSelection::Ids(ids) => ids.clone(),
+            InterfaceSelection::All => snapshot.interface_ids().map(str::to_string).collect(),
+        };
+        let mut snapshots = BTreeMap::new();
+        let mut missing = None;
+        for id in ids {
+            match snapshot.stats(&id) {
+                Some(stats) => {
+                    snapshots.insert(id, stats);
+                }
+                None => missing = Some(id),
+            }
+        }
+
+        if snapshots.is_empty() {
+            let id = missing.unwrap_or_else(|| self.interfaces.to_string());
+            if self.interface_fallback {
+                let summary = summarize_traffic(None, snapshot);
+                let fallback = pick_default_interface(&summary)
+                    .filter(|traffic| traffic.id != id)
+                    .and_then(|traffic| Some((traffic.id.clone(), snapshot.stats(&traffic.id)?)));
+                if let Some((fallback, current)) = fallback {
+                    self.fall_back_to(fallback, current);
+                    return Ok(None);
+                }
+            }
+            return Err(NetworkError::InterfaceNotFound(id).into());
+        }
+        Ok(self.update(snapshots))
+    }
+
+    /// Continue with `fallback`, whose counters are `current`, as the only
+    /// monitored interface.
+    fn fall_back_to(&mut self, fallback: String, current: NetworkStats) {
+        self.interfaces = InterfaceSelection::Ids(vec![fallback.clone()]);
+        self.history.clear();
+        self.ema = None;
+        self.breakdown.clear();
+        self.last_delta = None;
+        self.last_stats = BTreeMap::from([(fallback, current)]);
+    }
+
+    /// Advance to the counters of the monitored interfaces that were
+    /// found, returning the new reading if there is one.
+    fn update(&mut self, snapshots: BTreeMap<String, NetworkStats>) -> Option<SpeedReading> {
+        let regressed = snapshots.iter().any(|(id, current)| {
+            self.last_stats.get(id).is_some_and(|prev| {
+                current.bytes_received < prev.bytes_received || current.bytes_sent < prev.bytes_sent
+            })
+        });
+        if regressed {
+            self.counter_resets += 1;
+            self.history.clear();
+            self.ema = None;
+            self.breakdown.clear();
+            self.last_delta = None;
+            self.last_stats = snapshots;
+            return None;
+        }
+
+        let taken_at = snapshots.values().map(|stats| stats.timestamp).max();
+        let mut breakdown = BTreeMap::new();
+        let mut delta = ByteDelta::default();
+        for (id, current) in &snapshots {
+            let Some(prev) = self.last_stats.get(id) else {
+                // New interface (or first poll) — baseline only.
+                continue;
+            };
+            let elapsed_secs = current
+                .timestamp
+                .duration_since(prev.timestamp)
+                .as_secs_f64();
+            if elapsed_secs <= 0.0 {
+                // Avoid division by zero if called too fast.
+                continue;
+            }
+            let download_delta = current.bytes_received - prev.bytes_received;
+            let upload_delta = current.bytes_sent - prev.bytes_sent;
+            delta.download_bytes += download_delta;
+            delta.upload_bytes += upload_delta;
+            breakdown.insert(
+                id.clone(),
+                SpeedReading {
+                    download_bps: (download_delta as f64 / elapsed_secs) as u64,
+                    upload_bps: (upload_delta as f64 / elapsed_secs) as u64,
+                },
+            );
+        }
+        self.last_stats = snapshots;
+        if breakdown.is_empty() {
+            return None;
+        }
+        self.last_delta = Some(delta);
+
+        let reading = SpeedReading {
+            download_bps: breakdown.values().map(|r| r.download_bps).sum(),
+            upload_bps: breakdown.values().map(|r| r.upload_bps).sum(),
+        };
+
+        // Add to rolling window.
+        if self.history.len() >= self.smoothing.history_len() {
+            self.history.pop_front();
+        }
+        self.history.push_back(reading.clone());
+        if let SmoothingStrategy::ExponentialMovingAverage { alpha } = self.smoothing {
+            let alpha = alpha.clamp(0.0, 1.0);
+            let (down, up) = (reading.download_bps as f64, reading.upload_bps as f64);
+            self.ema = Some(match self.ema {
+                Some((d, u)) => (d + alpha * (down - d), u + alpha * (up - u)),
+                // The first reading seeds the average.
+                None => (down, up),
+            });
+        }
+        self.breakdown = breakdown;
+        if let Some(at) = taken_at {
+            self.record_history(at, &reading);
+        }
+
+        Some(reading)
+    }
+
+    /// Get the current download speed (smoothed), in bytes/second.
+    pub fn current_download_speed(&self) -> u64 {
+        self.smoothed(|r| r.download_bps, |(down, _)| down)
+    }
+
+    /// Get the current upload speed (smoothed), in bytes/second.
+    pub fn current_upload_speed(&self) -> u64 {
+        self.smoothed(|r| r.upload_bps, |(_, up)| up)
+    }
+
+    /// One direction of the current speed per the smoothing strategy.
+    fn smoothed(&self, raw: fn(&SpeedReading) -> u64, ema: fn((f64, f64)) -> f64) -> u64 {
+        if self.history.is_empty() {
+            return 0;
+        }
+        match self.smoothing {
+            SmoothingStrategy::SimpleMovingAverage { .. } => {
+                let sum: u64 = self.history.iter().map(raw).sum();
+                sum / self.history.len() as u64
+            }
+            SmoothingStrategy::ExponentialMovingAverage { .. } => {
+                self.ema.map_or(0, |avg| ema(avg).round() as u64)
+            }
+            SmoothingStrategy::None => self.history.back().map_or(0, raw),
+        }
+    }
+
+    /// Get the latest raw (non-averaged) speed reading.
+    pub fn latest_reading(&self) -> Option<&SpeedReading> {
+        self.history.back()
+    }
+
+    /// Bytes transferred over the interval of
+    /// [`latest_reading`](Self::latest_reading), summed over interfaces.
+    pub fn latest_delta(&self) -> Option<ByteDelta> {
+        self.last_delta
+    }
+
+    /// Get the latest raw reading of each interface that contributed to
+    /// [`latest_reading`](Self::latest_reading).
+    pub fn latest_breakdown(&self) -> &BTreeMap<String, SpeedReading> {
+        &self.breakdown
+    }
+
+    /// Append to the charting history, dropping readings that are too old
+    /// or over capacity.
+    fn record_history(&mut self, at: Instant, reading: &SpeedReading) {
+        let Some(window) = self.history_window else {
+            return;
+        };
+        while self.timeline.len() >= self.history_capacity() {
+            self.timeline.pop_front();
+        }
+        while self
+            .timeline
+            .front()
+            .is_some_and(|oldest| at.saturating_duration_since(oldest.at) > window)
+        {
+            self.timeline.pop_front();
+        }
+        self.timeline.push_back(TimestampedReading {
+            at,
+            reading: reading.clone(),
+        });
+    }
+
+    /// Reset the monitor state (clears history and previous snapshot).
+    pub fn reset(&mut self) {
+        self.timeline.clear();
+        self.last_stats.clear();
+        self.history.clear();
+        self.breakdown.clear();
+        self.last_delta = None;
+        self.ema = None;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flowwatcher_platform::network::{InterfaceInfo, InterfaceKind};
+    use flowwatcher_testing::{MockClock, MultiInterfaceProvider, ScriptedNetworkProvider};
+
+    #[test]
+    fn first_poll_returns_none() {
+        let mut provider = ScriptedNetworkProvider::new(vec![(0, 0)]);
+        let mut monitor = SpeedMonitor::new("mock0", 3);
+
+        let result = monitor.poll(&mut provider).unwrap();
+        assert!(
+            result.is_none(),
+            "first poll should return None (no baseline)"
+        );
+    }
+
+    #[test]
+    fn speed_calculation_from_delta() {
+        // Simulate: 0 bytes → 1024 bytes received in 1 second = 1024 B/s
+        let mut provider = ScriptedNetworkProvider::new(vec![
+            (0, 0),      // first poll (baseline)
+            (1024, 512), // second poll
+        ]);
+        let mut monitor = SpeedMonitor::new("mock0", 3);
+
+        monitor.poll(&mut provider).unwrap(); // baseline
+        let reading = monitor
+            .poll(&mut provider)
+            .unwrap()
+            .expect("should have reading");
+
+        assert_eq!(reading.download_bps, 1024);
+        assert_eq!(reading.upload_bps, 512);
+    }
+
+    #[test]
+    fn rolling_average_smooths_spikes() {
+        // 3 readings: 1000, 100, 1000 → average = 700
+        let mut provider = ScriptedNetworkProvider::new(vec![
+            (0, 0),
+            (1000, 0), // +1000 in 1s = 1000 bps
+            (1100, 0), // +100 in 1s  = 100 bps
+            (2100, 0), // +1000 in 1s = 1000 bps
+        ]);
+        let mut monitor = SpeedMonitor::new("mock0", 3);
+
+        monitor.poll(&mut provider).unwrap(); // baseline
+        monitor.poll(&mut provider).unwrap(); // 1000
+        monitor.poll(&mut provider).unwrap(); // 100
+        monitor.poll(&mut provider).unwrap(); // 1000
+
+        let avg = monitor.current_download_speed();
+        assert_eq!(
+            avg, 700,
+            "rolling average of [1000, 100, 1000] should be 700"
+        );
+    }
+
+    #[test]
+    fn rolling_window_evicts_old_samples() {
+        // Window size = 2, push 3 values → oldest is dropped
+        let mut provider = ScriptedNetworkProvider::new(vec![
+            (0, 0),
+            (500, 0),  // +500
+            (1000, 0), // +500
+            (3000, 0), // +2000
+        ]);
+        let mut monitor = SpeedMonitor::new("mock0", 2); // window=2
+
+        monitor.poll(&mut provider).unwrap();
+        monitor.poll(&mut provider).unwrap(); // 500
+        monitor.poll(&mut provider).unwrap(); // 500
+        monitor.poll(&mut provider).unwrap(); // 2000
+
+        // Window should contain [500, 2000], average = 1250
+        let avg = monitor.current_download_speed();
+        assert_eq!(avg, 1250);
+    }
+
+    #[test]
+    fn reset_clears_state() {
+        let mut provider = ScriptedNetworkProvider::new(vec![(0, 0), (1000, 0)]);
+        let mut monitor = SpeedMonitor::new("mock0", 3);
+
+        monitor.poll(&mut provider).unwrap();
+        monitor.poll(&mut provider).unwrap();
+        assert!(monitor.current_download_speed() > 0);
+
+        monitor.reset();
+        assert_eq!(monitor.current_download_speed(), 0);
+        assert!(monitor.latest_reading().is_none());
+        assert!(monitor.latest_delta().is_none());
+    }
+
+    #[test]
+    fn latest_delta_counts_bytes_since_the_previous_poll() {
+        let mut provider =
+            ScriptedNetworkProvider::new(vec![(0, 0), (5000, 100), (12_000, 300), (300, 10)]);
+        let mut monitor = SpeedMonitor::new("mock0", 3);
+
+        monitor.poll(&mut provider).unwrap();
+        assert!(monitor.latest_delta().is_none());
+        monitor.poll(&mut provider).unwrap();
+        let delta = ByteDelta {
+            download_bytes: 5000,
+            upload_bytes: 100,
+        };
+        assert_eq!(monitor.latest_delta(), Some(delta));
+        monitor.poll(&mut provider).unwrap();
+        let delta = ByteDelta {
+            download_bytes: 7000,
+            upload_bytes: 200,
+        };
+        assert_eq!(monitor.latest_delta(), Some(delta));
+
+        // A counter reset starts over without a delta.
+        monitor.poll(&mut provider).unwrap();
+        assert!(monitor.latest_delta().is_none());
+    }
+
+    #[test]
+    fn counter_regression_reestablishes_baseline() {
+        let mut provider = ScriptedNetworkProvider::new(vec![
+            (0, 0),
+            (5000, 100),
+            (10_000, 200),
+            (300, 10), // adapter reconnected, counters restarted
+            (1300, 20),
+        ]);
+        let mut monitor = SpeedMonitor::new("mock0", 3);
+
+        monitor.poll(&mut provider).unwrap();
+        monitor.poll(&mut provider).unwrap();
+        monitor.poll(&mut provider).unwrap();
+        assert!(monitor.poll(&mut provider).unwrap().is_none());
+        assert_eq!(monitor.counter_resets(), 1);
+        assert!(monitor.latest_reading().is_none());
+        // The new baseline is the restarted counters.
+        let baseline = monitor.last_counters();
+        assert_eq!(baseline.len(), 1);
+        assert_eq!(baseline[0].interface_id, "mock0");
+        assert_eq!(
+            (baseline[0].bytes_received, baseline[0].bytes_sent),
+            (300, 10)
+        );
+
+        let reading = monitor.poll(&mut provider).unwrap().unwrap();
+        assert_eq!((reading.download_bps, reading.upload_bps), (1000, 10));
+        assert_eq!(monitor.current_download_speed(), 1000);
+    }
+
+    #[test]
+    fn missing_interface_errors_without_fallback() {
+        let mut provider = ScriptedNetworkProvider::new(vec![(0, 0)]);
+        let mut monitor = SpeedMonitor::new("vpn0", 3);
+
+        assert!(matches!(
+            monitor.poll(&mut provider),
+            Err(SpeedError::NetworkError(NetworkError::InterfaceNotFound(_)))
+        ));
+    }
+
+    #[test]
+    fn missing_interface_falls_back_to_default() {
+        let mut provider = ScriptedNetworkProvider::new(vec![(0, 0), (0, 0), (2048, 0)]);
+        let mut monitor = SpeedMonitor::new("vpn0", 3).with_interface_fallback(true);
+
+        assert!(monitor.poll(&mut provider).unwrap().is_none());
+        assert_eq!(
+            monitor.interfaces(),
+            &InterfaceSelection::Ids(vec!["mock0".to_string()])
+        );
+
+        let reading = monitor.poll(&mut provider).unwrap().unwrap();
+        assert_eq!(reading.download_bps, 2048);
+    }
+
+    #[test]
+    fn prefetched_snapshots_are_polled_without_the_provider() {
+        let mut provider = MultiInterfaceProvider::new()
+            .with_interface("eth0", &[Some((1_000, 100))])
+            .with_interface("wlan0", &[Some((4_000, 0))]);
+        let mut all = SpeedMonitor::for_interfaces(InterfaceSelection::All, 1);
+        let mut gone = SpeedMonitor::new("vpn0", 1).with_interface_fallback(true);
+        assert!(all
+            .poll_snapshot(&provider.snapshot_all().unwrap())
+            .unwrap()
+            .is_none());
+
+        provider.advance();
+        let snapshot = provider.snapshot_all().unwrap();
+        let reading = all.poll_snapshot(&snapshot).unwrap().unwrap();
+        assert_eq!((reading.download_bps, reading.upload_bps), (5_000, 100));
+        // The fallback is the interface with the most traffic.
+        assert!(gone.poll_snapshot(&snapshot).unwrap().is_none());
+        assert_eq!(
+            gone.interfaces(),
+            &InterfaceSelection::Ids(vec!["wlan0".to_string()])
+        );
+
+        provider.advance();
+        let reading = gone
+            .poll_snapshot(&provider.snapshot_all().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(reading.download_bps, 4_000);
+
+        let mut strict = SpeedMonitor::new("vpn0", 1);
+        assert!(matches!(
+            strict.poll_snapshot(&provider.snapshot_all().unwrap()),
+            Err(SpeedError::NetworkError(NetworkError::InterfaceNotFound(_)))
+        ));
+    }
+
+    /// Two interfaces replaying their own counters from a shared clock.
+    struct TwoInterfaces {
+        clock: MockClock,
+        scripts: BTreeMap<&'static str, Vec<(u64, u64)>>,
+        calls: BTreeMap<String, usize>,
+    }
+
+    impl TwoInterfaces {
+        fn new(eth: Vec<(u64, u64)>, wifi: Vec<(u64, u64)>) -> Self {
+            Self {
+                clock: MockClock::new(),
+                scripts: BTreeMap::from([("eth0", eth), ("wlan0", wifi)]),
+                calls: BTreeMap::new(),
+            }
+        }
+
+        /// Poll after advancing the clock by a second.
+        fn poll(&mut self, monitor: &mut SpeedMonitor) -> Option<SpeedReading> {
+            self.clock.advance_secs(1);
+            monitor.poll(self).unwrap()
+        }
+    }
+
+    impl NetworkProvider for TwoInterfaces {
+        fn list_interfaces(&self) -> Result<Vec<InterfaceInfo>, NetworkError> {
+            Ok(self
+                .scripts
+                .keys()
+                .map(|id| InterfaceInfo {
+                    id: id.to_string(),
+                    name: id.to_string(),
+                    mac: "00:00:00:00:00:00".to_string(),
+                    is_up: true,
+                    kind: InterfaceKind::Other,
+                    description: None,
+                    link_speed_mbps: None,
+                })
+                .collect())
+        }
+
+        fn get_default_interface(&self) -> Result<Option<InterfaceInfo>, NetworkError> {
+            Ok(self.list_interfaces()?.into_iter().next())
+        }
+
+        fn get_stats(&mut self, interface_id: &str) -> Result<NetworkStats, NetworkError> {
+            let script = self
+                .scripts
+                .get(interface_id)
+                .ok_or_else(|| NetworkError::InterfaceNotFound(interface_id.to_string()))?;
+            let call = self.calls.entry(interface_id.to_string()).or_default();
+            let (bytes_received, bytes_sent) = script[(*call).min(script.len() - 1)];
+            *call += 1;
+            Ok(NetworkStats {
+                bytes_sent,
+                bytes_received,
+                timestamp: self.clock.now(),
+            })
+        }
+    }
+
+    #[test]
+    fn all_interfaces_are_summed_with_breakdown() {
+        let mut provider = TwoInterfaces::new(
+            vec![(0, 0), (1000, 100), (3000, 200)],
+            vec![(0, 0), (500, 0), (1000, 50)],
+        );
+        let mut monitor = SpeedMonitor::for_interfaces(InterfaceSelection::All, 3);
+
+        assert!(provider.poll(&mut monitor).is_none());
+        let reading = provider.poll(&mut monitor).unwrap();
+        assert_eq!((reading.download_bps, reading.upload_bps), (1500, 100));
+
+        let reading = provider.poll(&mut monitor).unwrap();
+        assert_eq!((reading.download_bps, reading.upload_bps), (2500, 150));
+        let breakdown = monitor.latest_breakdown();
+        assert_eq!(breakdown["eth0"].download_bps, 2000);
+        assert_eq!(breakdown["wlan0"].download_bps, 500);
+        assert_eq!(breakdown["wlan0"].upload_bps, 50);
+        assert_eq!(monitor.current_download_speed(), 2000);
+    }
+
+    #[test]
+    fn listed_interfaces_only_sum_the_selection() {
+        let mut provider = TwoInterfaces::new(vec![(0, 0), (1000, 0)], vec![(0, 0), (500, 0)]);
+        let mut monitor = SpeedMonitor::for_interfaces(
+            InterfaceSelection::Ids(vec!["wlan0".to_string(), "gone0".to_string()]),
+            3,
+        );
+
+        provider.poll(&mut monitor);
+        let reading = provider.poll(&mut monitor).unwrap();
+        assert_eq!(reading.download_bps, 500);
+        assert_eq!(monitor.latest_breakdown().len(), 1);
+    }
+
+    #[test]
+    fn selection_displays_for_logs() {
+        assert_eq!(InterfaceSelection::All.to_string(), "all");
+        let ids = InterfaceSelection::Ids(vec!["eth0".to_string(), "wlan0".to_string()]);
+        assert_eq!(ids.to_string(), "eth0, wlan0");
+    }
+
+    /// Feed `before` B/s for a while, then `after` B/s, and return the
+    /// smoothed download speed after each step reading.
+    fn step_response(smoothing: SmoothingStrategy, steps: usize) -> Vec<u64> {
+        let mut speeds = vec![(100, 0); 5];
+        speeds.extend(vec![(1100, 0); steps]);
+        let mut provider = ScriptedNetworkProvider::from_speeds(&speeds);
+        let mut monitor = SpeedMonitor::new("mock0", 3).with_smoothing(smoothing);
+        for _ in 0..=5 {
+            monitor.poll(&mut provider).unwrap();
+        }
+        (0..steps)
+            .map(|_| {
+                monitor.poll(&mut provider).unwrap();
+                monitor.current_download_speed()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn default_smoothing_is_three_sample_average() {
+        let monitor = SpeedMonitor::new("mock0", 3);
+        assert_eq!(monitor.smoothing(), SmoothingStrategy::default());
+    }
+
+    #[test]
+    fn sma_reaches_a_step_after_its_window() {
+        let response = step_response(SmoothingStrategy::SimpleMovingAverage { window: 3 }, 4);
+        assert_eq!(response, vec![433, 766, 1100, 1100]);
+    }
+
+    #[test]
+    fn ema_approaches_a_step_gradually() {
+        let response = step_response(
+            SmoothingStrategy::ExponentialMovingAverage { alpha: 0.5 },
+            4,
+        );
+        assert_eq!(response, vec![600, 850, 975, 1038]);
+
+        // A higher alpha reacts faster than the 3-sample SMA at first.
+        let fast = step_response(
+            SmoothingStrategy::ExponentialMovingAverage { alpha: 0.8 },
+            1,
+        );
+        let sma = step_response(SmoothingStrategy::default(), 1);
+        assert!(fast[0] > sma[0]);
+    }
+
+    #[test]
+    fn no_smoothing_follows_raw_readings() {
+        let response = step_response(SmoothingStrategy::None, 2);
+        assert_eq!(response, vec![1100, 1100]);
+    }
+
+    #[test]
+    fn smoothing_strategy_serde_is_tagged() {
+        let json = r#"{"type":"exponential_moving_average","alpha":0.3}"#;
+        let strategy: SmoothingStrategy = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            strategy,
+            SmoothingStrategy::ExponentialMovingAverage { alpha: 0.3 }
+        );
+        assert_eq!(
+            serde_json::to_string(&SmoothingStrategy::None).unwrap(),
+            r#"{"type":"none"}"#
+        );
+    }
+
+    #[test]
+    fn history_is_off_by_default() {
+        let mut provider = ScriptedNetworkProvider::from_speeds(&[(100, 0), (100, 0)]);
+        let mut monitor = SpeedMonitor::new("mock0", 3);
+        for _ in 0..3 {
+            monitor.poll(&mut provider).unwrap();
+        }
+        assert!(monitor.get_history(None).is_empty());
+        assert_eq!(monitor.history_capacity(), 0);
+    }
+
+    #[test]
+    fn history_keeps_timestamped_readings_since() {
+        let clock = MockClock::new();
+        let mut provider = ScriptedNetworkProvider::with_clock(
+            vec![(0, 0), (100, 10), (300, 20), (600, 30)],
+            clock.clone(),
+        );
+        let mut monitor =
+            SpeedMonitor::new("mock0", 3).with_history_window(Duration::from_secs(60));
+        for _ in 0..4 {
+            monitor.poll(&mut provider).unwrap();
+        }
+
+        let history = monitor.get_history(None);
+        let speeds: Vec<u64> = history.iter().map(|e| e.reading.download_bps).collect();
+        assert_eq!(speeds, vec![100, 200, 300]);
+        assert_eq!(history[2].at, clock.now());
+
+        let recent = monitor.get_history(Some(history[0].at));
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].reading.download_bps, 200);
+    }
+
+    #[test]
+    fn history_is_bounded_by_window_and_capacity() {
+        let speeds = vec![(10, 0); 20];
+        let mut provider =
+            ScriptedNetworkProvider::from_speeds(&speeds).with_step(Duration::from_secs(2));
+        let mut monitor =
+            SpeedMonitor::new("mock0", 3).with_history_window(Duration::from_secs(10));
+        for _ in 0..=20 {
+            monitor.poll(&mut provider).unwrap();
+        }
+        // Readings 2 s apart: only those within the last 10 s remain.
+        assert_eq!(monitor.history_len(), 6);
+
+        let mut provider =
+            ScriptedNetworkProvider::from_speeds(&speeds).with_step(Duration::from_millis(100));
+        let mut monitor =
+            SpeedMonitor::new("mock0", 3).with_history_window(Duration::from_secs(10));
+        for _ in 0..=20 {
+            monitor.poll(&mut provider).unwrap();
+        }
+        // Fast polling is capped at one reading per second of window.
+        assert_eq!(monitor.history_len(), 10);
+
+        monitor.reset();
+        assert!(monitor.get_history(None).is_empty());
+    }
+
+    #[test]
+    fn averages_cover_the_window_before_the_newest_reading() {
+        let speeds: Vec<(u64, u64)> = (1..=6).map(|i| (i * 100, i * 10)).collect();
+        let mut provider = ScriptedNetworkProvider::from_speeds(&speeds);
+        let mut monitor =
+            SpeedMonitor::new("mock0", 3).with_history_window(Duration::from_secs(60));
+        assert!(monitor.average_over(Duration::from_secs(10)).is_none());
+        for _ in 0..6 {
+            monitor.poll(&mut provider).unwrap();
+        }
+
+        // After the baseline, readings one second apart: 100 to 500.
+        let last_two = monitor.average_over(Duration::from_secs(2)).unwrap();
+        assert_eq!((last_two.download_bps, last_two.upload_bps), (450, 45));
+        let all = monitor.average_over(Duration::from_secs(60)).unwrap();
+        assert_eq!((all.download_bps, all.upload_bps), (300, 30));
+    }
+
+    #[test]
+    fn history_window_is_capped() {
+        let monitor =
+            SpeedMonitor::new("mock0", 3).with_history_window(Duration::from_secs(86_400));
+        assert_eq!(
+            monitor.history_capacity(),
+            MAX_HISTORY_WINDOW.as_secs() as usize
+        );
+    }
+}
@@ -0,0 +1,349 @@
+//! Wall-clock timer trigger.
+//!
+//! `TimerTrigger` fires once at a fixed point in time, independent of any
+//! traffic. The target is stored as an absolute [`SystemTime`], so local
+//! daylight-saving changes cannot move it, and once fired the trigger
+//! stays quiet even if the system clock is set back past the target.
+
+use crate::registry::TriggerDescriptor;
+use crate::{Trigger, TriggerData, TriggerError, TriggerState, TriggerValue};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// ---------------------------------------------------------------------------
+// Target
+// ---------------------------------------------------------------------------
+
+/// When a [`TimerTrigger`] should fire, as given in its JSON config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerTarget {
+    /// At an absolute time, in seconds since the Unix epoch (UTC).
+    AtUnixSecs(u64),
+    /// This many seconds after the trigger is created.
+    InSecs(u64),
+}
+
+impl TimerTarget {
+    /// Resolve to an absolute time, taking `now` as the creation time.
+    pub fn resolve(self, now: SystemTime) -> SystemTime {
+        match self {
+            Self::AtUnixSecs(secs) => UNIX_EPOCH + Duration::from_secs(secs),
+            Self::InSecs(secs) => now + Duration::from_secs(secs),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// TimerTrigger
+// ---------------------------------------------------------------------------
+
+/// Fires once when the wall clock reaches a target time.
+///
+/// While waiting, evaluation returns [`TriggerState::Active`] with:
+///
+/// - `target_time` — the target as an ISO-8601 UTC string.
+/// - `target_unix_secs` — the target in seconds since the Unix epoch.
+/// - `seconds_remaining` — whole seconds left, rounded up.
+///
+/// At or after the target it returns [`TriggerState::Triggered`] exactly
+/// once; later evaluations return [`TriggerState::Idle`]. A target in the
+/// past fires on the first evaluation.
+///
+/// `Triggered` schedules the action whatever the condition, so the timer
+/// needs no condition of its own. Its waiting data carries no traffic
+/// fields (no `download_bps`, `upload_bps` or byte deltas), so it can only
+/// be paired with a condition that does not read them: `ThresholdCondition`
+/// and `VolumeCondition` fail with a missing-data error on every tick.
+pub struct TimerTrigger {
+    /// When to fire.
+    target: SystemTime,
+    /// Whether the trigger is running.
+    started: bool,
+    /// Whether `Triggered` has already been reported.
+    fired: bool,
+}
+
+impl TimerTrigger {
+    /// Fire at `target`.
+    pub fn at(target: SystemTime) -> Self {
+        Self {
+            target,
+            started: false,
+            fired: false,
+        }
+    }
+
+    /// Fire `delay` from now.
+    pub fn after(delay: Duration) -> Self {
+        Self::at(SystemTime::now() + delay)
+    }
+
+    /// Registry metadata, including the JSON Schema of [`TimerTarget`].
+    pub fn descriptor() -> TriggerDescriptor {
+        TriggerDescriptor::new(
+            "timer",
+            "Timer",
+            "Triggers once at a fixed time, regardless of activity",
+        )
+        .with_config_schema(json!({
+            "type": "object",
+            "oneOf": [
+                {
+                    "properties": {
+                        "at_unix_secs": { "type": "integer", "minimum": 0 }
+                    },
+                    "required": ["at_unix_secs"]
+                },
+                {
+                    "properties": {
+                        "in_secs": { "type": "integer", "minimum": 0 }
+                    },
+                    "required": ["in_secs"]
+                }
+            ]
+        }))
+    }
+
+    /// Build from a JSON [`TimerTarget`] (for the trigger registry).
+    pub fn from_config(config: &serde_json::Value) -> Result<Self, TriggerError> {
+        let target: TimerTarget = serde_json::from_value(config.clone())
+            .map_err(|e| TriggerError::InvalidConfig(format!("timer: {e}")))?;
+        Ok(Self::at(target.resolve(SystemTime::now())))
+    }
+
+    /// The target time.
+    pub fn target(&self) -> SystemTime {
+        self.target
+    }
+
+    /// Whether the trigger has already fired.
+    pub fn has_fired(&self) -> bool {
+        self.fired
+    }
+
+    /// Evaluate as if the wall clock read `now` (for testability).
+    pub fn evaluate_at(&mut self, now: SystemTime) -> TriggerState {
+        if !self.started || self.fired {
+            return TriggerState::Idle;
+        }
+
+        let remaining = match self.target.duration_since(now) {
+            Ok(remaining) if !remaining.is_zero() => remaining,
+            _ => {
+                self.fired = true;
+                return TriggerState::Triggered;
+            }
+        };
+
+        let mut data = TriggerData::new();
+        data.insert("target_time", TriggerValue::String(format_utc(self.target)));
+        data.insert(
+            "target_unix_secs",
+            TriggerValue::U64(unix_secs(self.target)),
+        );
+        data.insert(
+            "seconds_remaining",
+            TriggerValue::U64(remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0)),
+        );
+        TriggerState::Active(data)
+    }
+}
+
+#[async_trait]
+impl Trigger for TimerTrigger {
+    fn name(&self) -> &str {
+        "Timer"
+    }
+
+    fn trigger_type(&self) -> &str {
+        "timer"
+    }
+
+    /// Start (or resume) waiting. The target time is kept across restarts.
+    async fn start(&mut self) -> Result<(), TriggerError> {
+        self.started = true;
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<(), TriggerError> {
+        self.started = false;
+        Ok(())
+    }
+
+    async fn evaluate(&mut self) -> Result<TriggerState, TriggerError> {
+        Ok(self.evaluate_at(SystemTime::now()))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Time formatting
+// ---------------------------------------------------------------------------
+
+/// Seconds since the Unix epoch (0 for earlier times).
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Format as `YYYY-MM-DDTHH:MM:SSZ`.
+fn format_utc(time: SystemTime) -> String {
+    let secs = unix_secs(time);
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let rem = secs % 86_400;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60
+    )
+}
+
+/// Convert days since 1970-01-01 to a proleptic Gregorian date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2026-03-29 00:30:00 UTC, half an hour before the EU switches to
+    /// summer time.
+    const BASE: u64 = 1_774_744_200;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    async fn started(target: SystemTime) -> TimerTrigger {
+        let mut trigger = TimerTrigger::at(target);
+        trigger.start().await.unwrap();
+        trigger
+    }
+
+    #[tokio::test]
+    async fn waits_then_fires_once() {
+        let mut trigger = started(at(BASE + 90)).await;
+
+        let TriggerState::Active(data) = trigger.evaluate_at(at(BASE)) else {
+            panic!("should be waiting");
+        };
+        assert_eq!(data.get("seconds_remaining"), Some(&TriggerValue::U64(90)));
+        assert_eq!(
+            data.get("target_time"),
+            Some(&TriggerValue::String("2026-03-29T00:31:30Z".into()))
+        );
+        assert_eq!(
+            data.get("target_unix_secs"),
+            Some(&TriggerValue::U64(BASE + 90))
+        );
+
+        assert_eq!(trigger.evaluate_at(at(BASE + 90)), TriggerState::Triggered);
+        assert!(trigger.has_fired());
+        assert_eq!(trigger.evaluate_at(at(BASE + 91)), TriggerState::Idle);
+    }
+
+    #[tokio::test]
+    async fn remaining_seconds_round_up() {
+        let mut trigger = started(at(BASE + 10)).await;
+        let now = at(BASE) + Duration::from_millis(500);
+        let TriggerState::Active(data) = trigger.evaluate_at(now) else {
+            panic!("should be waiting");
+        };
+        assert_eq!(data.get("seconds_remaining"), Some(&TriggerValue::U64(10)));
+    }
+
+    #[tokio::test]
+    async fn past_target_fires_immediately() {
+        let mut trigger = started(at(BASE - 3_600)).await;
+        assert_eq!(trigger.evaluate_at(at(BASE)), TriggerState::Triggered);
+    }
+
+    #[tokio::test]
+    async fn target_survives_stop_and_start() {
+        let mut trigger = started(at(BASE + 60)).await;
+        assert!(matches!(
+            trigger.evaluate_at(at(BASE)),
+            TriggerState::Active(_)
+        ));
+
+        trigger.stop().await.unwrap();
+        assert_eq!(trigger.evaluate_at(at(BASE + 30)), TriggerState::Idle);
+        trigger.start().await.unwrap();
+
+        assert_eq!(trigger.target(), at(BASE + 60));
+        assert!(matches!(
+            trigger.evaluate_at(at(BASE + 30)),
+            TriggerState::Active(_)
+        ));
+        assert_eq!(trigger.evaluate_at(at(BASE + 60)), TriggerState::Triggered);
+
+        // A restart after firing does not re-arm it.
+        trigger.stop().await.unwrap();
+        trigger.start().await.unwrap();
+        assert_eq!(trigger.evaluate_at(at(BASE + 61)), TriggerState::Idle);
+    }
+
+    #[tokio::test]
+    async fn clock_set_back_does_not_fire_twice() {
+        // Across a DST change the UTC target does not move; if the clock is
+        // set back an hour after firing, the trigger stays quiet.
+        let mut trigger = started(at(BASE + 3_600)).await;
+        assert_eq!(
+            trigger.evaluate_at(at(BASE + 3_600)),
+            TriggerState::Triggered
+        );
+        assert_eq!(trigger.evaluate_at(at(BASE)), TriggerState::Idle);
+        assert_eq!(trigger.evaluate_at(at(BASE + 3_600)), TriggerState::Idle);
+    }
+
+    #[tokio::test]
+    async fn unstarted_trigger_is_idle() {
+        let mut trigger = TimerTrigger::at(at(BASE));
+        assert_eq!(trigger.evaluate_at(at(BASE + 1)), TriggerState::Idle);
+        assert!(!trigger.has_fired());
+    }
+
+    #[test]
+    fn config_accepts_absolute_and_relative_targets() {
+        let now = at(BASE);
+        let absolute: TimerTarget =
+            serde_json::from_value(serde_json::json!({ "at_unix_secs": BASE + 5 })).unwrap();
+        assert_eq!(absolute.resolve(now), at(BASE + 5));
+
+        let relative: TimerTarget =
+            serde_json::from_value(serde_json::json!({ "in_secs": 7_200 })).unwrap();
+        assert_eq!(relative.resolve(now), at(BASE + 7_200));
+
+        let trigger = TimerTrigger::from_config(&serde_json::json!({ "in_secs": 60 })).unwrap();
+        assert!(trigger.target() > SystemTime::now());
+        assert!(matches!(
+            TimerTrigger::from_config(&serde_json::json!({ "tomorrow": true })),
+            Err(TriggerError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn formats_utc_dates() {
+        assert_eq!(format_utc(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc(at(951_782_400)), "2000-02-29T00:00:00Z");
+        assert_eq!(format_utc(at(BASE)), "2026-03-29T00:30:00Z");
+    }
+}